@@ -0,0 +1,30 @@
+//! Talking to Mojang's authentication and profile services.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::utils::prelude::*;
+
+pub mod session;
+pub mod skins;
+
+/// Logins wait on these requests, so they can't be allowed to hang.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// The client every request to Mojang goes through, so connections are reused between logins.
+///
+/// Building it fails if the TLS backend can't be set up, in which case it's tried again next time.
+pub fn http_client() -> Result<&'static reqwest::Client> {
+    if let Some(client) = HTTP_CLIENT.get() {
+        return Ok(client);
+    }
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("ferrumc/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| Error::Generic(format!("Failed to build the HTTP client: {}", e)))?;
+    // Another login may have built one in the meantime, in which case this one is dropped
+    Ok(HTTP_CLIENT.get_or_init(|| client))
+}
@@ -0,0 +1,62 @@
+use reqwest::StatusCode;
+
+use crate::auth::http_client;
+use crate::utils::components::game_profile::GameProfile;
+use crate::utils::prelude::*;
+
+const HAS_JOINED_URL: &str = "https://sessionserver.mojang.com/session/minecraft/hasJoined";
+
+/// Asks Mojang's session servers whether `username` really joined this server, which they only
+/// did if they own the account.
+///
+/// Returns the player's profile if they did, and `None` if they didn't.
+pub async fn has_joined(username: &str, server_hash: &str) -> Result<Option<GameProfile>> {
+    let response = http_client()?
+        .get(HAS_JOINED_URL)
+        .query(&[("username", username), ("serverId", server_hash)])
+        .send()
+        .await
+        .map_err(|e| Error::Generic(format!("Failed to reach the session servers: {}", e)))?;
+
+    // Players that didn't join get an empty response
+    if response.status() == StatusCode::NO_CONTENT {
+        return Ok(None);
+    }
+
+    let profile = response
+        .error_for_status()
+        .map_err(|e| Error::Generic(format!("Session servers returned an error: {}", e)))?
+        .json::<GameProfile>()
+        .await
+        .map_err(|e| Error::Generic(format!("Invalid profile from the session servers: {}", e)))?;
+
+    Ok(Some(profile))
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_has_joined_response() {
+        let json = r#"{
+            "id": "069a79f444e94726a5befca90e38aaf5",
+            "name": "Notch",
+            "properties": [
+                {"name": "textures", "value": "e30=", "signature": "c2ln"}
+            ]
+        }"#;
+
+        let profile: GameProfile = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            profile.id,
+            Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap()
+        );
+        assert_eq!(profile.name, "Notch");
+        assert_eq!(profile.properties.len(), 1);
+        assert_eq!(profile.properties[0].name, "textures");
+        assert_eq!(profile.properties[0].signature.as_deref(), Some("c2ln"));
+    }
+}
@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use moka::future::Cache;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::auth::http_client;
+use crate::utils::components::game_profile::{GameProfile, ProfileProperty};
+use crate::utils::prelude::*;
+
+const PROFILE_LOOKUP_URL: &str = "https://api.mojang.com/users/profiles/minecraft";
+const PROFILE_URL: &str = "https://sessionserver.mojang.com/session/minecraft/profile";
+/// Mojang rate limits profile lookups, so skins are kept for a while once they've been looked up.
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+const CACHE_CAPACITY: u64 = 1024;
+
+/// The answer to a name lookup.
+#[derive(Deserialize)]
+struct ProfileLookup {
+    id: Uuid,
+}
+
+/// The skins and capes of the accounts players in offline mode are named after, so they don't all
+/// show up as Steve and Alex.
+pub struct SkinCache {
+    textures: Cache<String, Vec<ProfileProperty>>,
+}
+
+impl Default for SkinCache {
+    fn default() -> Self {
+        Self::new(CACHE_CAPACITY, CACHE_TTL)
+    }
+}
+
+impl SkinCache {
+    pub fn new(capacity: u64, time_to_live: Duration) -> Self {
+        Self {
+            textures: Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(time_to_live)
+                .build(),
+        }
+    }
+
+    /// The `textures` property of the account with this name, looked up from Mojang the first
+    /// time it's asked for. Empty if there's no such account, or if Mojang couldn't be reached,
+    /// in which case it's looked up again next time.
+    pub async fn textures(&self, name: &str) -> Vec<ProfileProperty> {
+        if !is_valid_name(name) {
+            return Vec::new();
+        }
+        let key = name.to_lowercase();
+        if let Some(textures) = self.textures.get(&key).await {
+            return textures;
+        }
+        match fetch_textures(name).await {
+            Ok(textures) => {
+                self.textures.insert(key, textures.clone()).await;
+                textures
+            }
+            Err(e) => {
+                debug!("Couldn't look up the skin of {}: {}", name, e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Only names a Mojang account could have are worth looking up.
+fn is_valid_name(name: &str) -> bool {
+    (1..=16).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Asks Mojang for the signed `textures` property of the account with this name.
+pub async fn fetch_textures(name: &str) -> Result<Vec<ProfileProperty>> {
+    let response = http_client()?
+        .get(format!("{}/{}", PROFILE_LOOKUP_URL, name))
+        .send()
+        .await
+        .map_err(|e| Error::Generic(format!("Failed to reach the profile API: {}", e)))?;
+    // Names nobody owns get an empty response or a 404, depending on the day
+    if matches!(
+        response.status(),
+        StatusCode::NO_CONTENT | StatusCode::NOT_FOUND
+    ) {
+        return Ok(Vec::new());
+    }
+    let lookup = response
+        .error_for_status()
+        .map_err(|e| Error::Generic(format!("Profile API returned an error: {}", e)))?
+        .json::<ProfileLookup>()
+        .await
+        .map_err(|e| Error::Generic(format!("Invalid answer from the profile API: {}", e)))?;
+
+    let profile = http_client()?
+        .get(format!("{}/{}", PROFILE_URL, lookup.id.simple()))
+        .query(&[("unsigned", "false")])
+        .send()
+        .await
+        .map_err(|e| Error::Generic(format!("Failed to reach the session servers: {}", e)))?
+        .error_for_status()
+        .map_err(|e| Error::Generic(format!("Session servers returned an error: {}", e)))?
+        .json::<GameProfile>()
+        .await
+        .map_err(|e| Error::Generic(format!("Invalid profile from the session servers: {}", e)))?;
+
+    Ok(textures_of(profile))
+}
+
+fn textures_of(profile: GameProfile) -> Vec<ProfileProperty> {
+    profile
+        .properties
+        .into_iter()
+        .filter(|property| property.name == "textures")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_account_names_are_looked_up() {
+        assert!(is_valid_name("Notch"));
+        assert!(is_valid_name("jeb_"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("seventeen_chars__"));
+        assert!(!is_valid_name("../../admin"));
+    }
+
+    #[test]
+    fn test_parse_profile_textures() {
+        let lookup: ProfileLookup =
+            serde_json::from_str(r#"{"id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch"}"#)
+                .unwrap();
+        assert_eq!(
+            lookup.id,
+            Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap()
+        );
+
+        let profile: GameProfile = serde_json::from_str(
+            r#"{
+                "id": "069a79f444e94726a5befca90e38aaf5",
+                "name": "Notch",
+                "properties": [
+                    {"name": "textures", "value": "e30=", "signature": "c2ln"},
+                    {"name": "something_else", "value": "e30="}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let textures = textures_of(profile);
+        assert_eq!(textures.len(), 1);
+        assert_eq!(textures[0].signature.as_deref(), Some("c2ln"));
+    }
+}
@@ -0,0 +1,20 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ferrumc::world::block_registry::BlockStateRegistry;
+
+/// Startup cost of the block registry, parsed from the bundled JSON versus read from the cache.
+fn bench_block_registry(c: &mut Criterion) {
+    let cache = BlockStateRegistry::bundled().to_cache_bytes().unwrap();
+
+    let mut group = c.benchmark_group("block registry");
+    group.sample_size(10);
+    group.bench_function("from json", |b| {
+        b.iter(|| black_box(BlockStateRegistry::bundled()))
+    });
+    group.bench_function("from cache", |b| {
+        b.iter(|| black_box(BlockStateRegistry::from_cache_bytes(black_box(&cache)).unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_block_registry);
+criterion_main!(benches);
@@ -0,0 +1,920 @@
+//! The commands every server has.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::commands::{
+    ArgumentParser, CommandContext, CommandExecutor, CommandNode, CommandSender, NodeKind,
+    StringKind,
+};
+use crate::net::disconnect;
+use crate::state::ServerState;
+use crate::utils::bans::{BanReason, IpBan, IpRange, PlayerBan};
+use crate::utils::components::entity_info::EntityKind;
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::player::Player;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::encoding::position::Position;
+use crate::utils::permissions::{OpEntry, MAX_PERMISSION_LEVEL};
+use crate::utils::prelude::*;
+use crate::utils::text::TextComponent;
+use crate::utils::whitelist::WhitelistEntry;
+use crate::world::dimension::Dimension;
+use crate::world::time::TICKS_PER_DAY;
+use crate::world::weather::WeatherKind;
+
+pub fn register_default_commands(state: &ServerState) {
+    state.register_command(
+        CommandNode::literal("help")
+            .description("Lists the commands you can run")
+            .executes(help),
+    );
+    state.register_command(
+        CommandNode::literal("list")
+            .description("Lists the players that are online")
+            .executes(list),
+    );
+    state.register_command(
+        CommandNode::literal("stop")
+            .description("Saves the world, kicks everyone and stops the server")
+            .requires(4)
+            .executes(stop),
+    );
+    state.register_command(
+        CommandNode::literal("save-all")
+            .description("Saves the world and every player's data right away")
+            .requires(4)
+            .executes(save_all),
+    );
+    state.register_command(
+        CommandNode::literal("timings")
+            .description("Shows how long ticks are taking")
+            .operator_only()
+            .executes(timings),
+    );
+    state.register_command(
+        CommandNode::literal("kick")
+            .description("Disconnects a player")
+            .requires(3)
+            .then(
+                CommandNode::argument("player", ArgumentParser::String(StringKind::Word))
+                    .executes(kick_player)
+                    .then(
+                        CommandNode::argument("reason", ArgumentParser::String(StringKind::Greedy))
+                            .executes(kick_player),
+                    ),
+            ),
+    );
+    state.register_command(
+        CommandNode::literal("dimension")
+            .description("Moves you to another dimension")
+            .operator_only()
+            .then(
+                CommandNode::argument("dimension", ArgumentParser::String(StringKind::Word))
+                    .executes(change_dimension),
+            ),
+    );
+    state.register_command(
+        CommandNode::literal("worldborder")
+            .description("Shows or changes the world border")
+            .operator_only()
+            .then(CommandNode::literal("get").executes(border_get))
+            .then(
+                CommandNode::literal("set").then(
+                    CommandNode::argument(
+                        "diameter",
+                        ArgumentParser::Double {
+                            min: Some(1.0),
+                            max: Some(59_999_968.0),
+                        },
+                    )
+                    .executes(border_set)
+                    .then(
+                        CommandNode::argument(
+                            "seconds",
+                            ArgumentParser::Integer {
+                                min: Some(0),
+                                max: None,
+                            },
+                        )
+                        .executes(border_set),
+                    ),
+                ),
+            )
+            .then(
+                CommandNode::literal("center").then(
+                    CommandNode::argument(
+                        "x",
+                        ArgumentParser::Double {
+                            min: None,
+                            max: None,
+                        },
+                    )
+                    .then(
+                        CommandNode::argument(
+                            "z",
+                            ArgumentParser::Double {
+                                min: None,
+                                max: None,
+                            },
+                        )
+                        .executes(border_center),
+                    ),
+                ),
+            ),
+    );
+
+    state.register_command(
+        CommandNode::literal("time")
+            .description("Shows or changes the time of day")
+            .operator_only()
+            .then(
+                CommandNode::literal("set")
+                    .then(
+                        CommandNode::argument(
+                            "time",
+                            ArgumentParser::Integer {
+                                min: Some(0),
+                                max: None,
+                            },
+                        )
+                        .executes(time_set),
+                    )
+                    // The same times of day vanilla has names for
+                    .then(CommandNode::literal("day").executes(|ctx| Box::pin(set_time(ctx, 1000))))
+                    .then(
+                        CommandNode::literal("noon").executes(|ctx| Box::pin(set_time(ctx, 6000))),
+                    )
+                    .then(
+                        CommandNode::literal("night")
+                            .executes(|ctx| Box::pin(set_time(ctx, 13000))),
+                    )
+                    .then(
+                        CommandNode::literal("midnight")
+                            .executes(|ctx| Box::pin(set_time(ctx, 18000))),
+                    ),
+            )
+            .then(
+                CommandNode::literal("add").then(
+                    CommandNode::argument(
+                        "time",
+                        ArgumentParser::Integer {
+                            min: Some(0),
+                            max: None,
+                        },
+                    )
+                    .executes(time_add),
+                ),
+            )
+            .then(CommandNode::literal("query").executes(time_query)),
+    );
+    state.register_command(
+        CommandNode::literal("weather")
+            .description("Changes the weather, optionally for a number of ticks")
+            .operator_only()
+            .then(weather_node("clear", |ctx| {
+                Box::pin(set_weather(ctx, WeatherKind::Clear))
+            }))
+            .then(weather_node("rain", |ctx| {
+                Box::pin(set_weather(ctx, WeatherKind::Rain))
+            }))
+            .then(weather_node("thunder", |ctx| {
+                Box::pin(set_weather(ctx, WeatherKind::Thunder))
+            })),
+    );
+    state.register_command(
+        CommandNode::literal("summon")
+            .description("Spawns an entity where you're standing")
+            .operator_only()
+            .then(
+                CommandNode::argument("entity", ArgumentParser::String(StringKind::Word))
+                    .executes(summon),
+            ),
+    );
+    state.register_command(
+        CommandNode::literal("gamemode")
+            .description("Changes your game mode, or another player's")
+            .operator_only()
+            .then(game_mode_node(GameMode::Survival, |ctx| {
+                Box::pin(set_game_mode(ctx, GameMode::Survival))
+            }))
+            .then(game_mode_node(GameMode::Creative, |ctx| {
+                Box::pin(set_game_mode(ctx, GameMode::Creative))
+            }))
+            .then(game_mode_node(GameMode::Adventure, |ctx| {
+                Box::pin(set_game_mode(ctx, GameMode::Adventure))
+            }))
+            .then(game_mode_node(GameMode::Spectator, |ctx| {
+                Box::pin(set_game_mode(ctx, GameMode::Spectator))
+            })),
+    );
+    state.register_command(
+        CommandNode::literal("whitelist")
+            .description("Manages the players that may join while the whitelist is on")
+            .requires(3)
+            .then(
+                CommandNode::literal("add").then(
+                    CommandNode::argument("player", ArgumentParser::String(StringKind::Word))
+                        .executes(whitelist_add),
+                ),
+            )
+            .then(
+                CommandNode::literal("remove").then(
+                    CommandNode::argument("player", ArgumentParser::String(StringKind::Word))
+                        .executes(whitelist_remove),
+                ),
+            )
+            .then(CommandNode::literal("list").executes(whitelist_list))
+            .then(CommandNode::literal("reload").executes(whitelist_reload)),
+    );
+    state.register_command(
+        CommandNode::literal("ban")
+            .description("Bans a player from the server")
+            .requires(3)
+            .then(
+                CommandNode::argument("player", ArgumentParser::String(StringKind::Word))
+                    .executes(ban_player)
+                    .then(
+                        CommandNode::argument("reason", ArgumentParser::String(StringKind::Greedy))
+                            .executes(ban_player),
+                    ),
+            ),
+    );
+    state.register_command(
+        CommandNode::literal("pardon")
+            .description("Lifts a player's ban")
+            .requires(3)
+            .then(
+                CommandNode::argument("player", ArgumentParser::String(StringKind::Word))
+                    .executes(pardon_player),
+            ),
+    );
+    state.register_command(
+        CommandNode::literal("ban-ip")
+            .description("Bans an address, or the address of an online player")
+            .requires(3)
+            .then(
+                CommandNode::argument("target", ArgumentParser::String(StringKind::Word))
+                    .executes(ban_ip)
+                    .then(
+                        CommandNode::argument("reason", ArgumentParser::String(StringKind::Greedy))
+                            .executes(ban_ip),
+                    ),
+            ),
+    );
+    state.register_command(
+        CommandNode::literal("pardon-ip")
+            .description("Lifts the ban on an address")
+            .requires(3)
+            .then(
+                CommandNode::argument("target", ArgumentParser::String(StringKind::Word))
+                    .executes(pardon_ip),
+            ),
+    );
+    state.register_command(
+        CommandNode::literal("op")
+            .description("Makes a player an operator")
+            .requires(3)
+            .then(
+                CommandNode::argument("player", ArgumentParser::String(StringKind::Word))
+                    .executes(op_player),
+            ),
+    );
+    state.register_command(
+        CommandNode::literal("deop")
+            .description("Takes away a player's operator status")
+            .requires(3)
+            .then(
+                CommandNode::argument("player", ArgumentParser::String(StringKind::Word))
+                    .executes(deop_player),
+            ),
+    );
+}
+
+/// `/weather <name> [duration]`, which does the same thing either way.
+fn weather_node(name: &str, executor: CommandExecutor) -> CommandNode {
+    CommandNode::literal(name).executes(executor).then(
+        CommandNode::argument(
+            "duration",
+            ArgumentParser::Integer {
+                min: Some(1),
+                max: None,
+            },
+        )
+        .executes(executor),
+    )
+}
+
+/// `/gamemode <mode> [player]`, for yourself or someone else.
+fn game_mode_node(game_mode: GameMode, executor: CommandExecutor) -> CommandNode {
+    CommandNode::literal(game_mode.name())
+        .executes(executor)
+        .then(
+            CommandNode::argument("player", ArgumentParser::String(StringKind::Word))
+                .executes(executor),
+        )
+}
+
+fn help(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let permission_level = ctx.sender.permission_level(&ctx.state).await;
+        let mut lines = ctx
+            .state
+            .commands
+            .read()
+            .unwrap()
+            .commands(permission_level)
+            .filter_map(|command| match (&command.kind, &command.description) {
+                (NodeKind::Literal(name), Some(description)) => {
+                    Some(format!("/{} - {}", name, description))
+                }
+                (NodeKind::Literal(name), None) => Some(format!("/{}", name)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+
+        let mut message = TextComponent::text("Commands:").color("gold");
+        for line in lines {
+            message = message.append(TextComponent::text(format!("\n{}", line)).color("white"));
+        }
+        ctx.reply(&message).await
+    })
+}
+
+fn list(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let mut names = Vec::new();
+        let query = ctx.state.world.query::<&Player>();
+        for (_, player) in query.iter().await {
+            names.push(player.username.clone());
+        }
+        names.sort_by_key(|name| name.to_lowercase());
+
+        let message = format!(
+            "There are {} of a max of {} players online: {}",
+            names.len(),
+            ctx.state.config.max_players,
+            names.join(", ")
+        );
+        ctx.reply(&TextComponent::text(message)).await
+    })
+}
+
+fn stop(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        ctx.reply(&TextComponent::text("Stopping the server"))
+            .await?;
+        ctx.state.request_shutdown();
+        Ok(())
+    })
+}
+
+fn save_all(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        ctx.reply(&TextComponent::text("Saving the world..."))
+            .await?;
+        let chunks = ctx.state.save_all().await?;
+        ctx.reply(&TextComponent::text(format!(
+            "Saved the world ({} changed chunks)",
+            chunks
+        )))
+        .await
+    })
+}
+
+fn timings(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let message = {
+            let timings = ctx.state.tick_timings.lock().unwrap();
+            let mut message = TextComponent::text(format!(
+                "{:.1} TPS, {:.2}ms per tick",
+                timings.tps(),
+                timings.tick.as_secs_f64() * 1000.0
+            ))
+            .color("gold");
+            for (name, average) in &timings.systems {
+                message = message.append(
+                    TextComponent::text(format!(
+                        "\n{}: {:.2}ms",
+                        name,
+                        average.as_secs_f64() * 1000.0
+                    ))
+                    .color("white"),
+                );
+            }
+            message
+        };
+        ctx.reply(&message).await
+    })
+}
+
+fn kick_player(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let name = ctx.args.string("player").unwrap_or_default();
+        let reason = ctx.args.string("reason").unwrap_or("Kicked by an operator");
+
+        let Some((id, _, username)) = find_online_player(&ctx, name).await else {
+            let message = TextComponent::text(format!("No player named {} is online", name));
+            return ctx.reply(&message.color("red")).await;
+        };
+        disconnect(id, &TextComponent::text(reason), ctx.state.clone()).await?;
+        ctx.reply(&TextComponent::text(format!(
+            "Kicked {}: {}",
+            username, reason
+        )))
+        .await
+    })
+}
+
+fn change_dimension(
+    ctx: CommandContext,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let CommandSender::Player(conn_id) = ctx.sender else {
+            let message = TextComponent::text("Only players can change dimension");
+            return ctx.reply(&message.color("red")).await;
+        };
+        let name = ctx.args.string("dimension").unwrap_or_default();
+        let Some(dimension) = Dimension::lookup(name) else {
+            let message = TextComponent::text(format!("Unknown dimension: {}", name));
+            return ctx.reply(&message.color("red")).await;
+        };
+        if ctx.state.dimension_of(conn_id).await == dimension {
+            let message = TextComponent::text(format!("You're already in {}", dimension.name()));
+            return ctx.reply(&message.color("red")).await;
+        }
+
+        // Same coordinates, moved up or down into the other dimension if they're outside of it
+        let mut position = ctx
+            .state
+            .world
+            .get_component::<Position>(conn_id)
+            .await?
+            .clone();
+        position.y = (position.y as i32).clamp(dimension.min_y(), dimension.max_y()) as i16;
+
+        ctx.state
+            .change_dimension(conn_id, dimension, position)
+            .await?;
+        ctx.reply(&TextComponent::text(format!(
+            "Moved to {}",
+            dimension.name()
+        )))
+        .await
+    })
+}
+
+fn summon(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let CommandSender::Player(conn_id) = ctx.sender else {
+            let message = TextComponent::text("Only players can summon entities");
+            return ctx.reply(&message.color("red")).await;
+        };
+        let name = ctx.args.string("entity").unwrap_or_default();
+        let Some(kind) = EntityKind::summonable(name) else {
+            let message = TextComponent::text(format!("Can't summon {}", name));
+            return ctx.reply(&message.color("red")).await;
+        };
+
+        let position = ctx
+            .state
+            .world
+            .get_component::<Position>(conn_id)
+            .await?
+            .clone();
+        let rotation = ctx
+            .state
+            .world
+            .get_component::<Rotation>(conn_id)
+            .await?
+            .clone();
+        let dimension = ctx.state.dimension_of(conn_id).await;
+        ctx.state
+            .spawn_entity(kind, dimension, position, rotation)
+            .await;
+        ctx.reply(&TextComponent::text(format!("Summoned a new {}", name)))
+            .await
+    })
+}
+
+/// Looks up an online player by name, ignoring case. Returns their entity ID, UUID and the name
+/// as they spell it.
+async fn find_online_player(ctx: &CommandContext, name: &str) -> Option<(usize, Uuid, String)> {
+    let query = ctx.state.world.query::<&Player>();
+    for (id, player) in query.iter().await {
+        if player.username.eq_ignore_ascii_case(name) {
+            return Some((
+                id,
+                Uuid::from_u128(player.get_uuid()),
+                player.username.clone(),
+            ));
+        }
+    }
+    None
+}
+
+/// The UUID and name to list a player under in the whitelist, bans or ops, along with their
+/// entity ID if they're online.
+///
+/// Players that aren't online are listed by name with a nil UUID. Their offline mode UUID depends
+/// on how their name is capitalised, so it can't be worked out from what was typed.
+async fn resolve_player(ctx: &CommandContext, name: &str) -> (Option<usize>, Uuid, String) {
+    match find_online_player(ctx, name).await {
+        Some((id, uuid, username)) => (Some(id), uuid, username),
+        None => (None, Uuid::nil(), name.to_string()),
+    }
+}
+
+/// The dimension a command acts on: the one the player running it is in, or the overworld.
+async fn sender_dimension(ctx: &CommandContext) -> Dimension {
+    match ctx.sender {
+        CommandSender::Player(conn_id) => ctx.state.dimension_of(conn_id).await,
+        CommandSender::Console | CommandSender::Rcon(_) => Dimension::Overworld,
+    }
+}
+
+fn border_get(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let border = ctx
+            .state
+            .world_border
+            .settings(sender_dimension(&ctx).await);
+        ctx.reply(&TextComponent::text(format!(
+            "The world border is {:.1} blocks wide, centered on {:.1}, {:.1}",
+            border.diameter, border.center_x, border.center_z
+        )))
+        .await
+    })
+}
+
+fn border_set(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let diameter = ctx.args.double("diameter").unwrap_or_default();
+        let seconds = ctx.args.integer("seconds").unwrap_or_default();
+        let dimension = sender_dimension(&ctx).await;
+        ctx.state
+            .set_border_diameter(
+                dimension,
+                diameter,
+                Duration::from_secs(seconds.max(0) as u64),
+            )
+            .await?;
+
+        let message = if seconds > 0 {
+            format!(
+                "Resizing the world border to {:.1} blocks over {} seconds",
+                diameter, seconds
+            )
+        } else {
+            format!("Set the world border to {:.1} blocks wide", diameter)
+        };
+        ctx.reply(&TextComponent::text(message)).await
+    })
+}
+
+fn border_center(
+    ctx: CommandContext,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let x = ctx.args.double("x").unwrap_or_default();
+        let z = ctx.args.double("z").unwrap_or_default();
+        let dimension = sender_dimension(&ctx).await;
+        ctx.state.set_border_center(dimension, x, z).await?;
+        ctx.reply(&TextComponent::text(format!(
+            "Set the center of the world border to {:.1}, {:.1}",
+            x, z
+        )))
+        .await
+    })
+}
+
+fn time_set(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let time = ctx.args.integer("time").unwrap_or_default() as i64;
+        set_time(ctx, time).await
+    })
+}
+
+fn time_add(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let time = ctx.args.integer("time").unwrap_or_default() as i64;
+        let time = ctx.state.world_time.time_of_day() + time;
+        set_time(ctx, time).await
+    })
+}
+
+async fn set_time(ctx: CommandContext, time: i64) -> Result<()> {
+    ctx.state.set_time_of_day(time).await?;
+    ctx.reply(&TextComponent::text(format!("Set the time to {}", time)))
+        .await
+}
+
+async fn set_weather(ctx: CommandContext, kind: WeatherKind) -> Result<()> {
+    let dimension = sender_dimension(&ctx).await;
+    if !dimension.has_weather() {
+        return ctx
+            .reply(
+                &TextComponent::text(format!("There's no weather in {}", dimension.name()))
+                    .color("red"),
+            )
+            .await;
+    }
+
+    let duration = ctx.args.integer("duration");
+    ctx.state.weather.set(dimension, kind, duration);
+    let name = match kind {
+        WeatherKind::Clear => "clear",
+        WeatherKind::Rain => "rain",
+        WeatherKind::Thunder => "rain & thunder",
+    };
+    ctx.reply(&TextComponent::text(format!("Set the weather to {}", name)))
+        .await
+}
+
+async fn set_game_mode(ctx: CommandContext, game_mode: GameMode) -> Result<()> {
+    let target = match ctx.args.string("player") {
+        Some(name) => match find_online_player(&ctx, name).await {
+            Some((id, _, name)) => (id, Some(name)),
+            None => {
+                let message = TextComponent::text(format!("No player named {} is online", name));
+                return ctx.reply(&message.color("red")).await;
+            }
+        },
+        None => match ctx.sender {
+            CommandSender::Player(conn_id) => (conn_id, None),
+            _ => {
+                let message = TextComponent::text("Say whose game mode to change");
+                return ctx.reply(&message.color("red")).await;
+            }
+        },
+    };
+
+    let (id, name) = target;
+    ctx.state.set_game_mode(id, game_mode).await?;
+    let message = match name {
+        Some(name) => format!("Set {}'s game mode to {}", name, game_mode.name()),
+        None => format!("Set your game mode to {}", game_mode.name()),
+    };
+    ctx.reply(&TextComponent::text(message)).await
+}
+
+fn time_query(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let time = &ctx.state.world_time;
+        ctx.reply(&TextComponent::text(format!(
+            "The time is {} on day {}",
+            time.time_of_day().rem_euclid(TICKS_PER_DAY),
+            time.day()
+        )))
+        .await
+    })
+}
+
+fn whitelist_add(
+    ctx: CommandContext,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let name = ctx.args.string("player").unwrap_or_default();
+        let (_, uuid, name) = resolve_player(&ctx, name).await;
+        let entry = WhitelistEntry {
+            uuid,
+            name: name.clone(),
+        };
+        if !ctx.state.whitelist.add(entry)? {
+            let message = TextComponent::text(format!("{} is already whitelisted", name));
+            return ctx.reply(&message.color("red")).await;
+        }
+        ctx.reply(&TextComponent::text(format!(
+            "Added {} to the whitelist",
+            name
+        )))
+        .await
+    })
+}
+
+fn whitelist_remove(
+    ctx: CommandContext,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let name = ctx.args.string("player").unwrap_or_default();
+        let Some(removed) = ctx.state.whitelist.remove(name)? else {
+            let message = TextComponent::text(format!("{} is not whitelisted", name));
+            return ctx.reply(&message.color("red")).await;
+        };
+        ctx.reply(&TextComponent::text(format!(
+            "Removed {} from the whitelist",
+            removed.name
+        )))
+        .await
+    })
+}
+
+fn whitelist_list(
+    ctx: CommandContext,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let mut names = ctx
+            .state
+            .whitelist
+            .entries()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect::<Vec<_>>();
+        names.sort_by_key(|name| name.to_lowercase());
+
+        let message = match names.len() {
+            0 => "There are no whitelisted players".to_string(),
+            count => format!(
+                "There are {} whitelisted players: {}",
+                count,
+                names.join(", ")
+            ),
+        };
+        ctx.reply(&TextComponent::text(message)).await
+    })
+}
+
+fn whitelist_reload(
+    ctx: CommandContext,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let count = ctx.state.whitelist.reload()?;
+        ctx.reply(&TextComponent::text(format!(
+            "Reloaded the whitelist, {} players are on it",
+            count
+        )))
+        .await
+    })
+}
+
+fn ban_player(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let name = ctx.args.string("player").unwrap_or_default();
+        let reason = ctx
+            .args
+            .string("reason")
+            .unwrap_or("Banned by an operator.");
+        let (online, uuid, name) = resolve_player(&ctx, name).await;
+        ctx.state.bans.ban_player(PlayerBan {
+            uuid,
+            name: name.clone(),
+            reason: reason.to_string(),
+            expires: None,
+        })?;
+        if let Some(id) = online {
+            let message = BanReason {
+                reason: reason.to_string(),
+                expires: None,
+            }
+            .message();
+            disconnect(id, &TextComponent::text(message), ctx.state.clone()).await?;
+        }
+        ctx.reply(&TextComponent::text(format!("Banned {}: {}", name, reason)))
+            .await
+    })
+}
+
+fn pardon_player(
+    ctx: CommandContext,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let name = ctx.args.string("player").unwrap_or_default();
+        let Some(pardoned) = ctx.state.bans.pardon_player(name)? else {
+            let message = TextComponent::text(format!("{} is not banned", name));
+            return ctx.reply(&message.color("red")).await;
+        };
+        ctx.reply(&TextComponent::text(format!("Unbanned {}", pardoned.name)))
+            .await
+    })
+}
+
+/// The address of an online player's connection.
+async fn player_address(ctx: &CommandContext, id: usize) -> Option<std::net::IpAddr> {
+    let conn = ctx.state.connections.get_connection(id).ok()?;
+    let conn = conn.read().await;
+    conn.remote_ip().await
+}
+
+fn ban_ip(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let target = ctx.args.string("target").unwrap_or_default();
+        let reason = ctx
+            .args
+            .string("reason")
+            .unwrap_or("Banned by an operator.");
+        // Either an address or range, or the name of a player whose address gets banned
+        let ip = match target.parse::<IpRange>() {
+            Ok(ip) => ip,
+            Err(_) => {
+                let address = match find_online_player(&ctx, target).await {
+                    Some((id, _, _)) => player_address(&ctx, id).await,
+                    None => None,
+                };
+                let Some(address) = address else {
+                    let message = TextComponent::text(format!(
+                        "{} is not a valid address or online player",
+                        target
+                    ));
+                    return ctx.reply(&message.color("red")).await;
+                };
+                address.to_string().parse::<IpRange>()?
+            }
+        };
+
+        ctx.state.bans.ban_ip(IpBan {
+            ip,
+            reason: reason.to_string(),
+            expires: None,
+        })?;
+
+        let mut banned = Vec::new();
+        let query = ctx.state.world.query::<&Player>();
+        for (id, _) in query.iter().await {
+            if player_address(&ctx, id)
+                .await
+                .is_some_and(|address| ip.contains(address))
+            {
+                banned.push(id);
+            }
+        }
+        let message = BanReason {
+            reason: reason.to_string(),
+            expires: None,
+        }
+        .message();
+        for id in &banned {
+            disconnect(*id, &TextComponent::text(&message), ctx.state.clone()).await?;
+        }
+
+        ctx.reply(&TextComponent::text(format!(
+            "Banned {} ({} players disconnected): {}",
+            ip,
+            banned.len(),
+            reason
+        )))
+        .await
+    })
+}
+
+fn pardon_ip(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let target = ctx.args.string("target").unwrap_or_default();
+        let Ok(ip) = target.parse::<IpRange>() else {
+            let message = TextComponent::text(format!("{} is not a valid address", target));
+            return ctx.reply(&message.color("red")).await;
+        };
+        if !ctx.state.bans.pardon_ip(ip)? {
+            let message = TextComponent::text(format!("{} is not banned", ip));
+            return ctx.reply(&message.color("red")).await;
+        }
+        ctx.reply(&TextComponent::text(format!("Unbanned {}", ip)))
+            .await
+    })
+}
+
+fn op_player(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let name = ctx.args.string("player").unwrap_or_default();
+        let (online, uuid, name) = resolve_player(&ctx, name).await;
+        let entry = OpEntry {
+            uuid,
+            name: name.clone(),
+            level: MAX_PERMISSION_LEVEL,
+            bypasses_player_limit: false,
+        };
+        if !ctx.state.operators.op(entry)? {
+            let message = TextComponent::text(format!("{} is already an operator", name));
+            return ctx.reply(&message.color("red")).await;
+        }
+        if let Some(id) = online {
+            ctx.state.resend_commands(id).await?;
+        }
+        ctx.reply(&TextComponent::text(format!(
+            "Made {} a server operator",
+            name
+        )))
+        .await
+    })
+}
+
+fn deop_player(ctx: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+    Box::pin(async move {
+        let name = ctx.args.string("player").unwrap_or_default();
+        let Some(removed) = ctx.state.operators.deop(name)? else {
+            let message = TextComponent::text(format!("{} is not an operator", name));
+            return ctx.reply(&message.color("red")).await;
+        };
+        if let Some((id, _, _)) = find_online_player(&ctx, &removed.name).await {
+            ctx.state.resend_commands(id).await?;
+        }
+        ctx.reply(&TextComponent::text(format!(
+            "Made {} no longer a server operator",
+            removed.name
+        )))
+        .await
+    })
+}
@@ -0,0 +1,578 @@
+//! Commands players can run from chat, like `/help`.
+//!
+//! Commands are a tree of [CommandNode]s, the same shape as vanilla's brigadier trees. The tree is
+//! sent to clients when they join, so they can tab-complete and highlight commands as they're
+//! typed, and each command a player runs is routed through it to the node's executor. Commands
+//! can also be run from the server console, see [crate::net::systems::console].
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use hashbrown::HashMap;
+use tracing::{debug, info, warn};
+
+use crate::net::packets::outgoing::commands::Commands;
+use crate::net::packets::outgoing::system_chat::SystemChatMessage;
+use crate::net::packets::ConnectionId;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::permissions::{permission_level, MAX_PERMISSION_LEVEL, OPERATOR_LEVEL};
+use crate::utils::prelude::*;
+use crate::utils::text::TextComponent;
+
+pub mod builtin;
+
+/// Runs a command. Gets everything it needs from the context, including the parsed arguments.
+pub type CommandExecutor =
+    fn(CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Root,
+    /// A word that has to be typed exactly, like the `help` in `/help`.
+    Literal(String),
+    /// A value the player fills in, parsed by `parser`.
+    Argument {
+        name: String,
+        parser: ArgumentParser,
+    },
+}
+
+/// How an argument is parsed, mirroring brigadier's argument types.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentParser {
+    Bool,
+    Integer { min: Option<i32>, max: Option<i32> },
+    Double { min: Option<f64>, max: Option<f64> },
+    String(StringKind),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringKind {
+    /// A single word.
+    Word,
+    /// A single word, or a phrase in double quotes.
+    Quotable,
+    /// Everything up to the end of the command.
+    Greedy,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentValue {
+    Bool(bool),
+    Integer(i32),
+    Double(f64),
+    String(String),
+}
+
+/// A node in the command tree, along with the nodes that can follow it.
+///
+/// Commands are built from the top down:
+/// ```ignore
+/// CommandNode::literal("tp")
+///     .description("Teleports you to a player")
+///     .then(CommandNode::argument("player", ArgumentParser::String(StringKind::Word)).executes(tp))
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandNode {
+    pub kind: NodeKind,
+    pub children: Vec<CommandNode>,
+    /// Runs the command when the input ends at this node.
+    pub executor: Option<CommandExecutor>,
+    /// Shown by `/help`, for top level commands.
+    pub description: Option<String>,
+    /// The permission level needed to see and run it, for top level commands.
+    pub permission_level: u8,
+}
+
+impl CommandNode {
+    fn new(kind: NodeKind) -> Self {
+        Self {
+            kind,
+            children: Vec::new(),
+            executor: None,
+            description: None,
+            permission_level: 0,
+        }
+    }
+
+    pub fn root() -> Self {
+        Self::new(NodeKind::Root)
+    }
+
+    pub fn literal(name: impl Into<String>) -> Self {
+        Self::new(NodeKind::Literal(name.into()))
+    }
+
+    pub fn argument(name: impl Into<String>, parser: ArgumentParser) -> Self {
+        Self::new(NodeKind::Argument {
+            name: name.into(),
+            parser,
+        })
+    }
+
+    /// Adds a node that can follow this one.
+    pub fn then(mut self, child: CommandNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Makes the command runnable when the input ends at this node.
+    pub fn executes(mut self, executor: CommandExecutor) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn operator_only(self) -> Self {
+        self.requires(OPERATOR_LEVEL)
+    }
+
+    /// Only senders with at least this permission level can see and run it.
+    pub fn requires(mut self, permission_level: u8) -> Self {
+        self.permission_level = permission_level;
+        self
+    }
+
+    /// The name of a literal or argument node. The root has none.
+    pub fn name(&self) -> Option<&str> {
+        match &self.kind {
+            NodeKind::Root => None,
+            NodeKind::Literal(name) | NodeKind::Argument { name, .. } => Some(name),
+        }
+    }
+
+    /// Consumes this node's part of the input, returning the rest of it.
+    fn consume<'a>(&self, input: &'a str, args: &mut CommandArguments) -> Option<&'a str> {
+        match &self.kind {
+            NodeKind::Root => Some(input),
+            NodeKind::Literal(literal) => {
+                let (word, rest) = split_word(input);
+                (word == literal.as_str()).then_some(rest)
+            }
+            NodeKind::Argument { name, parser } => {
+                let (value, rest) = parser.parse(input)?;
+                args.0.insert(name.clone(), value);
+                Some(rest)
+            }
+        }
+    }
+
+    /// Walks the input down the tree, backtracking when a branch doesn't pan out, and returns
+    /// the executor of the node it ends at.
+    fn route(&self, input: &str, args: &mut CommandArguments) -> Option<CommandExecutor> {
+        let rest = self.consume(input, args)?;
+        if rest.is_empty() {
+            return self.executor;
+        }
+        route_children(self.children.iter(), rest.strip_prefix(' ')?, args)
+    }
+}
+
+/// Tries each of the nodes on the input in turn, until one of them leads to an executor.
+fn route_children<'a>(
+    children: impl Iterator<Item = &'a CommandNode>,
+    input: &str,
+    args: &mut CommandArguments,
+) -> Option<CommandExecutor> {
+    // Literals take priority over arguments, like in brigadier
+    let (literals, arguments): (Vec<_>, Vec<_>) =
+        children.partition(|child| matches!(child.kind, NodeKind::Literal(_)));
+    literals.into_iter().chain(arguments).find_map(|child| {
+        let mut child_args = args.clone();
+        let executor = child.route(input, &mut child_args)?;
+        *args = child_args;
+        Some(executor)
+    })
+}
+
+fn split_word(input: &str) -> (&str, &str) {
+    input.split_at(input.find(' ').unwrap_or(input.len()))
+}
+
+impl ArgumentParser {
+    /// Parses the argument off the front of the input, returning it with the rest of the input.
+    pub fn parse<'a>(&self, input: &'a str) -> Option<(ArgumentValue, &'a str)> {
+        if let ArgumentParser::String(StringKind::Greedy) = self {
+            return (!input.is_empty()).then(|| (ArgumentValue::String(input.to_string()), ""));
+        }
+        if let (ArgumentParser::String(StringKind::Quotable), Some(quoted)) =
+            (self, input.strip_prefix('"'))
+        {
+            let end = quoted.find('"')?;
+            let rest = &quoted[end + 1..];
+            if !rest.is_empty() && !rest.starts_with(' ') {
+                return None;
+            }
+            return Some((ArgumentValue::String(quoted[..end].to_string()), rest));
+        }
+
+        let (word, rest) = split_word(input);
+        if word.is_empty() {
+            return None;
+        }
+        let value = match self {
+            ArgumentParser::Bool => ArgumentValue::Bool(word.parse().ok()?),
+            ArgumentParser::Integer { min, max } => {
+                let value: i32 = word.parse().ok()?;
+                if min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max) {
+                    return None;
+                }
+                ArgumentValue::Integer(value)
+            }
+            ArgumentParser::Double { min, max } => {
+                let value: f64 = word.parse().ok()?;
+                if !value.is_finite()
+                    || min.is_some_and(|min| value < min)
+                    || max.is_some_and(|max| value > max)
+                {
+                    return None;
+                }
+                ArgumentValue::Double(value)
+            }
+            ArgumentParser::String(_) => ArgumentValue::String(word.to_string()),
+        };
+        Some((value, rest))
+    }
+}
+
+/// The arguments a command was run with, by name.
+#[derive(Debug, Clone, Default)]
+pub struct CommandArguments(HashMap<String, ArgumentValue>);
+
+impl CommandArguments {
+    pub fn get(&self, name: &str) -> Option<&ArgumentValue> {
+        self.0.get(name)
+    }
+
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            ArgumentValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn integer(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            ArgumentValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn double(&self, name: &str) -> Option<f64> {
+        match self.get(name)? {
+            ArgumentValue::Double(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn string(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            ArgumentValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Who ran a command.
+#[derive(Debug, Clone)]
+pub enum CommandSender {
+    /// A player, by their connection.
+    Player(ConnectionId),
+    /// Someone typing into the terminal the server runs in.
+    Console,
+    /// An admin tool connected over RCON, which gets the replies sent back once the command is done.
+    Rcon(CommandOutput),
+}
+
+/// Collects the replies to a command, for senders that can't be sent them straight away.
+#[derive(Debug, Clone, Default)]
+pub struct CommandOutput(Arc<Mutex<Vec<String>>>);
+
+impl CommandOutput {
+    pub fn push(&self, line: String) {
+        self.0.lock().unwrap().push(line);
+    }
+
+    /// Everything replied so far, one reply per line.
+    pub fn take(&self) -> String {
+        std::mem::take(&mut *self.0.lock().unwrap()).join("\n")
+    }
+}
+
+impl CommandSender {
+    /// The console can run everything, players what their permission level allows.
+    pub async fn permission_level(&self, state: &GlobalState) -> u8 {
+        match self {
+            CommandSender::Player(conn_id) => permission_level(state, *conn_id).await,
+            CommandSender::Console | CommandSender::Rcon(_) => MAX_PERMISSION_LEVEL,
+        }
+    }
+
+    pub async fn is_operator(&self, state: &GlobalState) -> bool {
+        self.permission_level(state).await >= OPERATOR_LEVEL
+    }
+}
+
+/// Everything a command gets to work with when it's run.
+pub struct CommandContext {
+    pub state: GlobalState,
+    pub sender: CommandSender,
+    pub args: CommandArguments,
+}
+
+impl CommandContext {
+    /// Sends a message back to whoever ran the command. The console gets it logged.
+    pub async fn reply(&self, message: &TextComponent) -> Result<()> {
+        match &self.sender {
+            CommandSender::Player(conn_id) => {
+                let conn = self.state.connections.get_connection(*conn_id)?;
+                let conn = conn.read().await;
+                conn.send_packet(SystemChatMessage::new(message)).await
+            }
+            CommandSender::Console => {
+                info!("{}", message.plain_text());
+                Ok(())
+            }
+            CommandSender::Rcon(output) => {
+                output.push(message.plain_text());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The root of the command tree, which every command is registered under.
+#[derive(Debug, Clone)]
+pub struct CommandDispatcher {
+    root: CommandNode,
+}
+
+impl Default for CommandDispatcher {
+    fn default() -> Self {
+        Self {
+            root: CommandNode::root(),
+        }
+    }
+}
+
+impl CommandDispatcher {
+    /// Adds a top level command, replacing any command registered with the same name.
+    pub fn register(&mut self, command: CommandNode) {
+        self.root
+            .children
+            .retain(|existing| existing.name() != command.name());
+        self.root.children.push(command);
+    }
+
+    pub fn root(&self) -> &CommandNode {
+        &self.root
+    }
+
+    /// The top level commands someone can see and run.
+    pub fn commands(&self, permission_level: u8) -> impl Iterator<Item = &CommandNode> {
+        self.root
+            .children
+            .iter()
+            .filter(move |command| command.permission_level <= permission_level)
+    }
+
+    /// The tree sent to a player, without the commands they aren't allowed to run.
+    pub fn tree_for(&self, permission_level: u8) -> CommandNode {
+        let mut root = CommandNode::root();
+        root.children = self.commands(permission_level).cloned().collect();
+        root
+    }
+
+    /// Finds the executor for a command, without the leading slash, along with its arguments.
+    pub fn dispatch(
+        &self,
+        input: &str,
+        permission_level: u8,
+    ) -> Option<(CommandExecutor, CommandArguments)> {
+        let mut args = CommandArguments::default();
+        let executor =
+            route_children(self.commands(permission_level), input.trim_end(), &mut args)?;
+        Some((executor, args))
+    }
+}
+
+impl ServerState {
+    /// Adds a top level command. Players that join afterwards get it sent in their command tree.
+    pub fn register_command(&self, command: CommandNode) {
+        self.commands.write().unwrap().register(command);
+    }
+
+    /// Sends a player their command tree again, after their permission level changed.
+    pub async fn resend_commands(self: &GlobalState, conn_id: usize) -> Result<()> {
+        let level = permission_level(self, conn_id).await;
+        let commands = Commands::new(&self.commands.read().unwrap().tree_for(level));
+        let conn = self.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(commands).await
+    }
+
+    /// Runs a command, without the leading slash, telling the sender if it doesn't exist.
+    pub async fn execute_command(
+        self: &GlobalState,
+        sender: CommandSender,
+        input: &str,
+    ) -> Result<()> {
+        debug!("{:?} ran command /{}", sender, input);
+        let permission_level = sender.permission_level(self).await;
+        // Not held across the await, so commands can look at the tree themselves
+        let dispatched = self
+            .commands
+            .read()
+            .unwrap()
+            .dispatch(input, permission_level);
+
+        let Some((executor, args)) = dispatched else {
+            let context = CommandContext {
+                state: self.clone(),
+                sender,
+                args: CommandArguments::default(),
+            };
+            let message =
+                TextComponent::text("Unknown or incomplete command, see /help").color("red");
+            return context.reply(&message).await;
+        };
+
+        let context = CommandContext {
+            state: self.clone(),
+            sender,
+            args,
+        };
+        if let Err(e) = executor(context).await {
+            warn!("Command /{} failed: {}", input, e);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noop(_: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn other(_: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn dispatcher() -> CommandDispatcher {
+        let mut dispatcher = CommandDispatcher::default();
+        dispatcher.register(
+            CommandNode::literal("give")
+                .then(CommandNode::literal("all").executes(other))
+                .then(
+                    CommandNode::argument("player", ArgumentParser::String(StringKind::Word)).then(
+                        CommandNode::argument(
+                            "count",
+                            ArgumentParser::Integer {
+                                min: Some(1),
+                                max: Some(64),
+                            },
+                        )
+                        .executes(noop),
+                    ),
+                ),
+        );
+        dispatcher.register(
+            CommandNode::literal("say").operator_only().then(
+                CommandNode::argument("message", ArgumentParser::String(StringKind::Greedy))
+                    .executes(noop),
+            ),
+        );
+        dispatcher
+    }
+
+    #[test]
+    fn test_dispatch_parses_arguments() {
+        let dispatcher = dispatcher();
+
+        let (executor, args) = dispatcher.dispatch("give Steve 32", 0).unwrap();
+        assert_eq!(executor as usize, noop as CommandExecutor as usize);
+        assert_eq!(args.string("player"), Some("Steve"));
+        assert_eq!(args.integer("count"), Some(32));
+
+        let (_, args) = dispatcher.dispatch("say hello there  world", 4).unwrap();
+        assert_eq!(args.string("message"), Some("hello there  world"));
+    }
+
+    #[test]
+    fn test_dispatch_prefers_literals_and_backtracks() {
+        let dispatcher = dispatcher();
+
+        let (executor, args) = dispatcher.dispatch("give all", 0).unwrap();
+        assert_eq!(executor as usize, other as CommandExecutor as usize);
+        assert_eq!(args.string("player"), None);
+
+        // The "all" literal can't be followed by a count, so it's read as a player's name instead
+        let (executor, args) = dispatcher.dispatch("give all 5", 0).unwrap();
+        assert_eq!(executor as usize, noop as CommandExecutor as usize);
+        assert_eq!(args.string("player"), Some("all"));
+    }
+
+    #[test]
+    fn test_dispatch_rejects_bad_input() {
+        let dispatcher = dispatcher();
+        assert!(dispatcher.dispatch("", 0).is_none());
+        assert!(dispatcher.dispatch("give", 0).is_none());
+        assert!(dispatcher.dispatch("give Steve 65", 0).is_none());
+        assert!(dispatcher.dispatch("give Steve lots", 0).is_none());
+        assert!(dispatcher.dispatch("gives Steve 1", 0).is_none());
+        assert!(dispatcher.dispatch("say", 4).is_none());
+    }
+
+    #[test]
+    fn test_operator_only_commands() {
+        let dispatcher = dispatcher();
+        assert!(dispatcher.dispatch("say hi", 0).is_none());
+        assert!(dispatcher.dispatch("say hi", 4).is_some());
+
+        let names = |permission_level| {
+            dispatcher
+                .tree_for(permission_level)
+                .children
+                .iter()
+                .map(|command| command.name().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(0), vec!["give"]);
+        assert_eq!(names(4), vec!["give", "say"]);
+    }
+
+    #[test]
+    fn test_permission_levels() {
+        let mut dispatcher = dispatcher();
+        dispatcher.register(CommandNode::literal("stop").requires(4).executes(noop));
+
+        assert!(dispatcher.dispatch("say hi", 1).is_none());
+        assert!(dispatcher.dispatch("say hi", 2).is_some());
+        assert!(dispatcher.dispatch("stop", 3).is_none());
+        assert!(dispatcher.dispatch("stop", 4).is_some());
+        assert_eq!(dispatcher.tree_for(2).children.len(), 2);
+        assert_eq!(dispatcher.tree_for(4).children.len(), 3);
+    }
+
+    #[test]
+    fn test_quoted_strings() {
+        let parser = ArgumentParser::String(StringKind::Quotable);
+        assert_eq!(
+            parser.parse("\"two words\" rest"),
+            Some((ArgumentValue::String("two words".to_string()), " rest"))
+        );
+        assert_eq!(
+            parser.parse("word rest"),
+            Some((ArgumentValue::String("word".to_string()), " rest"))
+        );
+        assert_eq!(parser.parse("\"unterminated"), None);
+    }
+}
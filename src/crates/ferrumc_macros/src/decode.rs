@@ -1,8 +1,105 @@
 use quote::quote;
-use syn::{DeriveInput, parse_macro_input};
+use syn::{parse_macro_input, DeriveInput, Field};
 
 use proc_macro::TokenStream;
 
+/// How a field is read, picked with the `#[decode(...)]` attribute.
+enum FieldKind {
+    /// Through the field type's own `NetDecode` implementation.
+    Plain,
+    /// A VarInt, converted into the field's integer type.
+    VarInt,
+    /// Raw bytes, either everything left in the packet or a VarInt length followed by that many.
+    RawBytes { prepend_length: bool },
+}
+
+fn parse_field_kind(field: &Field) -> FieldKind {
+    let mut kind = FieldKind::Plain;
+
+    for attr in &field.attrs {
+        if !attr.path().is_ident("decode") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("varint") {
+                kind = FieldKind::VarInt;
+            } else if meta.path.is_ident("raw_bytes") {
+                let mut prepend_length = false;
+                if meta.input.peek(syn::token::Paren) {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("prepend_length") {
+                            prepend_length = meta.value()?.parse::<syn::LitBool>()?.value;
+                        }
+                        Ok(())
+                    })?;
+                }
+                kind = FieldKind::RawBytes { prepend_length };
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    kind
+}
+
+fn generate_field_decode_statement(field: Field) -> proc_macro2::TokenStream {
+    let kind = parse_field_kind(&field);
+    // Get the identifier of the field
+    let ident = field.ident.unwrap();
+    let type_name = field.ty;
+
+    match kind {
+        FieldKind::Plain => quote! {
+            #ident: match <#type_name as NetDecode>::net_decode(bytes).await {
+                Ok(value) => Box::into_inner(value),
+                Err(e) => return Err(Error::Generic(format!("Failed to decode field {}: {}", stringify!(#ident), e)))
+            },
+        },
+        FieldKind::VarInt => quote! {
+            #ident: match <ferrumc_codec::network_types::varint::VarInt as NetDecode>::net_decode(bytes).await {
+                Ok(value) => value.get_val() as #type_name,
+                Err(e) => return Err(Error::Generic(format!("Failed to decode field {}: {}", stringify!(#ident), e)))
+            },
+        },
+        FieldKind::RawBytes {
+            prepend_length: false,
+        } => quote! {
+            #ident: {
+                let mut raw = Vec::new();
+                if let Err(e) = tokio::io::AsyncReadExt::read_to_end(bytes, &mut raw).await {
+                    return Err(Error::Generic(format!("Failed to decode field {}: {}", stringify!(#ident), e)));
+                }
+                raw
+            },
+        },
+        FieldKind::RawBytes {
+            prepend_length: true,
+        } => quote! {
+            #ident: {
+                let len = match <ferrumc_codec::network_types::varint::VarInt as NetDecode>::net_decode(bytes).await {
+                    Ok(value) => value.get_val(),
+                    Err(e) => return Err(Error::Generic(format!("Failed to decode field {}: {}", stringify!(#ident), e)))
+                };
+                if len < 0 {
+                    return Err(Error::Generic(format!("Negative length for field {}: {}", stringify!(#ident), len)));
+                }
+                // Read through `take` so a bogus length can't make us allocate it all up front
+                let mut raw = Vec::new();
+                let mut limited = tokio::io::AsyncReadExt::take(&mut *bytes, len as u64);
+                if let Err(e) = tokio::io::AsyncReadExt::read_to_end(&mut limited, &mut raw).await {
+                    return Err(Error::Generic(format!("Failed to decode field {}: {}", stringify!(#ident), e)));
+                }
+                if raw.len() != len as usize {
+                    return Err(Error::Generic(format!("Field {} is {} bytes short", stringify!(#ident), len as usize - raw.len())));
+                }
+                raw
+            },
+        },
+    }
+}
+
 pub fn derive(input: TokenStream) -> TokenStream {
     // Parse the input tokens into a syntax tree
     let input = parse_macro_input!(input as DeriveInput);
@@ -17,17 +114,8 @@ pub fn derive(input: TokenStream) -> TokenStream {
     }) = input.data
     {
         for field in fields.named {
-            // Get the identifier of the field
-            let ident = field.ident.unwrap();
             // Generate a statement to decode this field from the bytes
-            let type_name = field.ty;
-            let statement = quote! {
-                #ident: match <#type_name as NetDecode>::net_decode(bytes).await {
-                    Ok(value) => Box::into_inner(value),
-                    Err(e) => return Err(Error::Generic(format!("Failed to decode field {}: {}", stringify!(#ident), e)))
-                },
-            };
-            field_statements.push(statement);
+            field_statements.push(generate_field_decode_statement(field));
         }
     }
 
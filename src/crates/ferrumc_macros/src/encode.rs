@@ -131,6 +131,8 @@ fn generate_encode_impl(
                     if matches!(encode_option, ferrumc_codec::enc::EncodeOption::AlwaysOmitSize) {
                         #(#field_statements)*
 
+                        bytes_out.write_all(&bytes_.into_inner()).await?;
+
                         Ok(())
                     } else {
                         #(#field_statements)*
@@ -210,6 +212,8 @@ fn generate_modified_constructor(
 
     quote! {
         impl #impl_generics #name #ty_generics #where_clause {
+            // Takes one argument per field, however many fields the packet has
+            #[allow(clippy::too_many_arguments)]
             pub fn new_auto(#(#non_default_fields_params)*) -> Self {
                 Self {
                     #(#non_default_fields_names)*
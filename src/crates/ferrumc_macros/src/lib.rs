@@ -10,7 +10,7 @@ mod packet;
 mod utils;
 mod events;
 
-#[proc_macro_derive(NetDecode)]
+#[proc_macro_derive(NetDecode, attributes(decode))]
 pub fn decode_derive(input: TokenStream) -> TokenStream {
     decode::derive(input)
 }
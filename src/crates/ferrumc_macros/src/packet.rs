@@ -7,26 +7,45 @@ use syn::{parse_macro_input, LitInt, LitStr};
 
 use proc_macro::TokenStream;
 
+/// The connection states a packet can be registered for, as they're spelled in the attribute.
+const STATES: [&str; 4] = ["handshake", "status", "login", "play"];
+
 pub fn attribute(args: TokenStream, input: TokenStream) -> TokenStream {
-    // check if the packet attribute has the packet_id and state fields
-    // if not, compile_error
+    // The attribute itself doesn't generate anything, the registry is baked by
+    // bake_packet_registry. It's only checked here, so mistakes show up on the packet.
+    let mut packet_id = None;
+    let mut state = None;
+
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("packet_id") || meta.path.is_ident("id") {
+            let value = meta.value()?.parse::<LitInt>()?;
+            let id: u8 = value.base10_parse().map_err(|_| {
+                syn::Error::new(value.span(), "packet ids have to fit in a single byte")
+            })?;
+            packet_id = Some(id);
+        } else if meta.path.is_ident("state") {
+            let value = meta.value()?.parse::<LitStr>()?;
+            if !STATES.contains(&value.value().as_str()) {
+                return Err(syn::Error::new(
+                    value.span(),
+                    format!("state has to be one of {}", STATES.join(", ")),
+                ));
+            }
+            state = Some(value.value());
+        } else {
+            return Err(meta.error("expected `packet_id` (or `id`) and `state`"));
+        }
+        Ok(())
+    });
+    parse_macro_input!(args with parser);
 
-    if args.is_empty() {
+    if packet_id.is_none() || state.is_none() {
         return TokenStream::from(quote! {
             compile_error!("packet attribute must have the packet_id and state fields");
         });
     }
 
-    if !vec!["packet_id", "state"]
-        .iter()
-        .all(|x| args.to_string().contains(x))
-    {
-        return TokenStream::from(quote! {
-            compile_error!("packet attribute must have the packet_id and state fields");
-        });
-    }
-
-    TokenStream::from(input)
+    input
 }
 
 pub fn bake(input: TokenStream) -> TokenStream {
@@ -49,7 +68,8 @@ pub fn bake(input: TokenStream) -> TokenStream {
         });
     }
 
-    let mut match_arms = Vec::new();
+    // (packet id, state, path to the struct) for every packet found
+    let mut packets = Vec::new();
 
     let start = std::time::Instant::now();
 
@@ -86,7 +106,7 @@ pub fn bake(input: TokenStream) -> TokenStream {
                     };
 
                     match ident.to_string().as_str() {
-                        "packet_id" => {
+                        "packet_id" | "id" => {
                             let value = meta.value().expect("value failed");
                             let value = value.parse::<LitInt>().expect("parse failed");
                             let n: usize = value.base10_parse().expect("base10_parse failed");
@@ -133,23 +153,51 @@ pub fn bake(input: TokenStream) -> TokenStream {
 
             let struct_path = format!("{}::{}", path, struct_name);
 
-            let struct_path = syn::parse_str::<syn::Path>(&struct_path).expect("parse_str failed");
+            packets.push((packet_id, state, struct_path));
+        }
+    }
+
+    // The directory is read in whatever order the filesystem likes, sort so the output is stable
+    packets.sort();
 
-            match_arms.push(quote! {
+    // Two packets on the same id would leave one of them silently unreachable
+    for pair in packets.windows(2) {
+        let ((id, state, first), (next_id, next_state, second)) = (&pair[0], &pair[1]);
+        if id == next_id && state == next_state {
+            let message = format!(
+                "Packet 0x{:02X} in state {} is registered twice, by {} and {}",
+                id, state, first, second
+            );
+            return TokenStream::from(quote! {
+                compile_error!(#message);
+            });
+        }
+    }
+
+    let match_arms = packets
+        .iter()
+        .map(|(packet_id, state, struct_path)| {
+            let struct_path = syn::parse_str::<syn::Path>(struct_path).expect("parse_str failed");
+            quote! {
                 (#packet_id, #state) => {
                     let packet= #struct_path::net_decode(cursor).await?;
                     packet.handle(conn_id, state).await?;
                 },
-            });
+            }
+        })
+        .collect::<Vec<_>>();
 
-            /*match_arms.push(quote! {
+    let decode_arms = packets
+        .iter()
+        .map(|(packet_id, state, struct_path)| {
+            let struct_path = syn::parse_str::<syn::Path>(struct_path).expect("parse_str failed");
+            quote! {
                 (#packet_id, #state) => {
-                    let packet= #path::#struct_name::decode(cursor).await?;
-                    packet.handle(conn_owned).await?;
+                    #struct_path::net_decode(cursor).await?;
                 },
-            });*/
-        }
-    }
+            }
+        })
+        .collect::<Vec<_>>();
 
     let elapsed = start.elapsed();
     println!("[FERRUMC_MACROS] Found {} packets", match_arms.len());
@@ -169,6 +217,17 @@ pub fn bake(input: TokenStream) -> TokenStream {
 
             Ok(())
         }
+
+        /// Decodes a packet without handling it. Returns false if no packet is registered for the
+        /// id in that state.
+        pub async fn decode_packet(packet_id: u8, conn_state: &crate::net::State, cursor: &mut std::io::Cursor<Vec<u8>>) -> crate::utils::prelude::Result<bool> {
+            match (packet_id, conn_state.as_str()) {
+                #(#decode_arms)*
+                _ => return Ok(false),
+            }
+
+            Ok(true)
+        }
     };
 
     TokenStream::from(output)
@@ -0,0 +1,54 @@
+use std::io::Cursor;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ferrumc_codec::network_types::varint::{write_varint, VarInt};
+
+const VARINT_COUNT: i32 = 10_000;
+
+/// A buffer of 10k varints, spread over every encoded length.
+fn varint_buffer(runtime: &tokio::runtime::Runtime) -> Vec<u8> {
+    runtime.block_on(async {
+        let mut buffer = Vec::new();
+        for i in 0..VARINT_COUNT {
+            let value = i.wrapping_mul(214_013).wrapping_add(2_531_011);
+            write_varint(value, &mut buffer).await.unwrap();
+        }
+        buffer
+    })
+}
+
+fn bench_varint_decoding(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let buffer = varint_buffer(&runtime);
+
+    let mut group = c.benchmark_group("varint_decode_10k");
+
+    group.bench_function("sync_from_bytes", |b| {
+        b.iter(|| {
+            let mut offset = 0;
+            for _ in 0..VARINT_COUNT {
+                let (varint, read) = VarInt::from_bytes(&buffer[offset..]).unwrap();
+                offset += read;
+                black_box(varint);
+            }
+        })
+    });
+
+    group.bench_function("async_read", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let mut cursor = Cursor::new(buffer.as_slice());
+                for _ in 0..VARINT_COUNT {
+                    black_box(VarInt::read(&mut cursor).await.unwrap());
+                }
+            })
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_varint_decoding);
+criterion_main!(benches);
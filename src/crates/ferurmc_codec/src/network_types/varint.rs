@@ -75,6 +75,23 @@ impl VarInt {
         Err(CodecError::VarIntTooBig)
     }
 
+    /// Read a VarInt from a buffer that's already in memory, without going through an async reader.
+    ///
+    /// Returns the VarInt and the number of bytes it took up.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize)> {
+        let mut val = 0;
+        for i in 0..5 {
+            let Some(&byte) = bytes.get(i) else {
+                return Err(CodecError::Io(std::io::ErrorKind::UnexpectedEof.into()));
+            };
+            val |= (i32::from(byte) & 0b01111111) << (i * 7);
+            if byte & 0b10000000 == 0 {
+                return Ok((VarInt { val, len: i + 1 }, i + 1));
+            }
+        }
+        Err(CodecError::VarIntTooBig)
+    }
+
     // Write a VarInt to the given cursor.
     // Yoinked from valence: https://github.com/valence-rs/valence/blob/main/crates/valence_protocol/src/var_int.rs#L98
     pub async fn write<T>(&self, cursor: &mut T) -> Result<()>
@@ -218,6 +235,52 @@ mod tests {
         assert_eq!(result.unwrap(), VarInt::new(1));
     }
 
+    #[tokio::test]
+    async fn from_bytes_matches_async_read_at_boundaries() {
+        let boundaries = [
+            0,
+            1,
+            127,
+            128,
+            16383,
+            16384,
+            2097151,
+            2097152,
+            268435455,
+            268435456,
+            i32::MAX,
+            -1,
+            i32::MIN,
+        ];
+        for value in boundaries {
+            let mut bytes = Vec::new();
+            write_varint(value, &mut bytes).await.unwrap();
+            let len = bytes.len();
+            // Trailing data shouldn't be consumed
+            bytes.push(0xAB);
+
+            let (varint, consumed) = VarInt::from_bytes(&bytes).unwrap();
+            assert_eq!(varint.get_val(), value);
+            assert_eq!(consumed, len);
+            assert_eq!(varint.get_len(), len);
+
+            let read = VarInt::read(&mut Cursor::new(bytes)).await.unwrap();
+            assert_eq!(varint, read);
+        }
+    }
+
+    #[test]
+    fn from_bytes_too_big() {
+        let result = VarInt::from_bytes(&[0b10000000; 6]);
+        assert!(matches!(result, Err(CodecError::VarIntTooBig)));
+    }
+
+    #[test]
+    fn from_bytes_truncated_input() {
+        assert!(VarInt::from_bytes(&[]).is_err());
+        assert!(VarInt::from_bytes(&[0xff, 0xff]).is_err());
+    }
+
     #[tokio::test]
     async fn write_varint_negative_input() {
         let mut cursor = Cursor::new(Vec::new());
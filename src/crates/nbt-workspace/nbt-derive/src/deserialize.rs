@@ -73,7 +73,7 @@ pub(crate) fn nbt_deserialize_derive(input: TokenStream) -> TokenStream {
         quote! {
             impl ::nbt_lib::NBTDeserializeBytes for #struct_name {
                 fn read_from_bytes(cursor: &mut std::io::Cursor<Vec<u8>>) -> ::nbt_lib::NBTResult<Self> {
-                    let nbt = ::nbt_lib::read_tag(cursor)?;
+                    let (nbt, _) = ::nbt_lib::read_root_tag(cursor)?;
                     <Self as ::nbt_lib::NBTDeserialize>::read_from(nbt)
                 }
             }
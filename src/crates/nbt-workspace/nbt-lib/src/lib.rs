@@ -7,7 +7,7 @@ pub use nbt_derive::NBTDeserialize;
 #[cfg(feature = "derive")]
 pub use nbt_derive::NBTSerialize;
 pub use nbt_spec::deserializer::{NBTDeserialize, NBTDeserializeBytes};
-pub use nbt_spec::deserializer::nbt_tag_reader::{NBTTag, read_tag};
+pub use nbt_spec::deserializer::nbt_tag_reader::{NBTTag, read_root_tag, read_tag};
 pub use nbt_spec::serializer::NBTSerialize;
 
 pub mod error;
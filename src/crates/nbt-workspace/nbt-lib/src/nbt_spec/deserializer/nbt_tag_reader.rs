@@ -45,6 +45,26 @@ pub fn read_tag(cursor: &mut Cursor<Vec<u8>>) -> NBTResult<NBTTag> {
     }
 }
 
+/// Reads a single named root tag and stops right after it, without looking at anything that
+/// follows. Region files pad every chunk out to a whole sector, so there's usually junk after the
+/// root compound's END tag.
+///
+/// The root comes back wrapped in a compound under its name, the same shape [read_tag] gives, along
+/// with the number of bytes that were consumed.
+pub fn read_root_tag(cursor: &mut Cursor<Vec<u8>>) -> NBTResult<(NBTTag, usize)> {
+    let start = cursor.position();
+    let mut root = HashMap::new();
+
+    let tag_type = cursor.read_i8()? as u8;
+    if tag_type != 0 {
+        let name = cursor.read_nbt_string()?;
+        let tag = read_tag_based_on_type(cursor, tag_type)?;
+        root.insert(name, tag);
+    }
+
+    Ok((NBTTag::Compound(root), (cursor.position() - start) as usize))
+}
+
 #[inline]
 fn read_tag_based_on_type(cursor: &mut Cursor<Vec<u8>>, tag_type: u8) -> NBTResult<NBTTag> {
     match tag_type {
@@ -111,17 +111,15 @@ where
     T: NBTSerialize + NBTAnonymousType,
 {
     fn nbt_serialize<W: Write>(&self, writer: &mut W) -> NBTResult<()> {
+        // Arrays are just a length and the values, lists also say what type their elements are
         let tag_type = <T as NBTAnonymousType>::tag_type();
-        if tag_type == TAG_LIST {
+        if !matches!(tag_type, TAG_BYTE | TAG_INT | TAG_LONG) {
             writer.write_all(&tag_type.to_be_bytes())?;
         }
         writer.write_all(&(self.len() as i32).to_be_bytes())?;
         for v in self {
             v.nbt_serialize(writer)?;
         }
-        if self.len() == 0 {
-            TAG_END.nbt_serialize(writer)?;
-        }
         Ok(())
     }
 }
@@ -218,3 +216,55 @@ impl NBTSerialize for VarInt {
         i32::nbt_serialize(&self.get_val(), writer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{read_tag, NBTDeserialize, NBTTag};
+
+    use super::*;
+
+    fn list_payload<T: NBTSerialize>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.nbt_serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Wraps a list payload in a root compound under `name`, the way a derived struct writes a
+    /// field, and reads it back.
+    fn read_field(name: &str, payload: &[u8]) -> NBTTag {
+        let mut bytes = vec![TAG_COMPOUND, 0, 0, TAG_LIST];
+        name.nbt_serialize(&mut bytes).unwrap();
+        bytes.extend_from_slice(payload);
+        bytes.push(TAG_END);
+        let mut root = read_tag(&mut Cursor::new(bytes)).unwrap().get("").unwrap();
+        root.get(name).unwrap()
+    }
+
+    #[test]
+    fn test_list_writes_element_type() {
+        let payload = list_payload(&vec![1.5f64, -2.0]);
+        assert_eq!(payload[0], TAG_DOUBLE);
+        assert_eq!(&payload[1..5], &2i32.to_be_bytes());
+        assert_eq!(payload.len(), 5 + 2 * 8);
+
+        let pos = Vec::<f64>::read_from(read_field("Pos", &payload)).unwrap();
+        assert_eq!(pos, vec![1.5, -2.0]);
+    }
+
+    #[test]
+    fn test_empty_list() {
+        let payload = list_payload(&Vec::<String>::new());
+        assert_eq!(payload, [&[TAG_STRING][..], &0i32.to_be_bytes()].concat());
+
+        assert!(matches!(read_field("Names", &payload), NBTTag::List(list) if list.is_empty()));
+    }
+
+    #[test]
+    fn test_arrays_have_no_element_type() {
+        let payload = list_payload(&vec![7i64, 8]);
+        assert_eq!(&payload[..4], &2i32.to_be_bytes());
+        assert_eq!(payload.len(), 4 + 2 * 8);
+    }
+}
@@ -1,4 +1,5 @@
 use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use crate::events::creation::registry::{dispatch_event};
 use crate::state::GlobalState;
@@ -16,13 +17,42 @@ impl EventDispatcher {
     }
 }
 
+/// An event whose handlers can call off whatever caused it, e.g. keep a chat message from being sent.
+pub trait CancellableEvent {
+    fn cancelled(&self) -> &Cancelled;
+
+    fn cancel(&self) {
+        self.cancelled().0.store(true, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled().0.load(Ordering::Relaxed)
+    }
+}
+
+/// Whether a [CancellableEvent] was cancelled. Handlers only get the event behind an [Arc], so this
+/// can be set through a shared reference.
+#[derive(Debug, Default)]
+pub struct Cancelled(AtomicBool);
+
 pub trait EventDispatcherExt {
     #[allow(async_fn_in_trait)]
     async fn dispatch_event<T: 'static + Any + Send + Sync>(&self, event: T);
+
+    /// Dispatches the event, and returns whether whatever caused it should go ahead, i.e. no handler
+    /// cancelled it.
+    #[allow(async_fn_in_trait)]
+    async fn dispatch_cancellable_event<T: 'static + CancellableEvent + Any + Send + Sync>(&self, event: T) -> bool;
 }
 
 impl EventDispatcherExt for GlobalState {
     async fn dispatch_event<T: 'static + Any + Send + Sync>(&self, event: T) {
         self.event_dispatcher.dispatch_event(event, self.clone()).await;
     }
+
+    async fn dispatch_cancellable_event<T: 'static + CancellableEvent + Any + Send + Sync>(&self, event: T) -> bool {
+        let event = Arc::new(event);
+        dispatch_event::<T>(Arc::clone(&event), self.clone()).await;
+        !event.is_cancelled()
+    }
 }
\ No newline at end of file
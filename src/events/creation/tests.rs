@@ -28,6 +28,7 @@ impl EventHandler for Handler2 {
 }
 
 */
+use crate::events::creation::dispatcher::{CancellableEvent, Cancelled, EventDispatcherExt};
 use crate::events::creation::registry::dispatch_event;
 use ferrumc_macros::event_handler;
 use std::sync::Arc;
@@ -77,3 +78,35 @@ async fn test_if_this_even_compiles() -> anyhow::Result<()> {
 
 
 
+
+
+struct CancelTestEvent {
+    value: i32,
+    cancelled: Cancelled,
+}
+
+impl CancellableEvent for CancelTestEvent {
+    fn cancelled(&self) -> &Cancelled {
+        &self.cancelled
+    }
+}
+
+#[event_handler]
+async fn cancel_negative(event: Arc<CancelTestEvent>, _state: GlobalState) {
+    if event.value < 0 {
+        event.cancel();
+    }
+}
+
+#[tokio::test]
+async fn test_cancelled_events_dont_go_ahead() -> anyhow::Result<()> {
+    let state = create_state(TcpListener::bind("0.0.0.0:0").await?).await?;
+
+    let event = CancelTestEvent { value: 1, cancelled: Cancelled::default() };
+    assert!(state.dispatch_cancellable_event(event).await);
+
+    let event = CancelTestEvent { value: -1, cancelled: Cancelled::default() };
+    assert!(!state.dispatch_cancellable_event(event).await);
+
+    Ok(())
+}
@@ -1,2 +1,4 @@
 pub mod creation;
+pub mod network_events;
+pub mod player_events;
 pub mod world_events;
\ No newline at end of file
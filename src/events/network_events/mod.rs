@@ -0,0 +1,28 @@
+use crate::events::creation::dispatcher::{CancellableEvent, Cancelled};
+use crate::net::State;
+
+/// Dispatched for every packet received in the play state, before it's handled. Cancelling it
+/// drops the packet.
+pub struct PacketReceivedEvent {
+    pub conn_id: usize,
+    pub packet_id: u8,
+    pub state: State,
+    cancelled: Cancelled,
+}
+
+impl PacketReceivedEvent {
+    pub fn new(conn_id: usize, packet_id: u8, state: State) -> Self {
+        Self {
+            conn_id,
+            packet_id,
+            state,
+            cancelled: Cancelled::default(),
+        }
+    }
+}
+
+impl CancellableEvent for PacketReceivedEvent {
+    fn cancelled(&self) -> &Cancelled {
+        &self.cancelled
+    }
+}
@@ -0,0 +1,27 @@
+use crate::events::creation::dispatcher::{CancellableEvent, Cancelled};
+
+/// Dispatched when a player sends a chat message, before it's broadcast. Cancelling it keeps the
+/// message from being sent to anyone.
+pub struct ChatMessageEvent {
+    pub entity_id: usize,
+    pub username: String,
+    pub message: String,
+    cancelled: Cancelled,
+}
+
+impl ChatMessageEvent {
+    pub fn new(entity_id: usize, username: String, message: String) -> Self {
+        Self {
+            entity_id,
+            username,
+            message,
+            cancelled: Cancelled::default(),
+        }
+    }
+}
+
+impl CancellableEvent for ChatMessageEvent {
+    fn cancelled(&self) -> &Cancelled {
+        &self.cancelled
+    }
+}
@@ -1,12 +1,74 @@
+use crate::events::creation::dispatcher::{CancellableEvent, Cancelled};
 use crate::state::GlobalState;
 use crate::utils::components::player::{Player};
+use crate::utils::encoding::position::Position;
 use ferrumc_macros::{event_handler, Constructor};
 use std::sync::Arc;
 use tracing::{error, info};
 
 #[derive(Constructor)]
 pub struct PlayerJoinWorldEvent {
-    entity_id: usize,
+    pub entity_id: usize,
+}
+
+/// Dispatched when a player leaves, while their entity is still around.
+#[derive(Constructor)]
+pub struct PlayerLeaveWorldEvent {
+    pub entity_id: usize,
+    pub username: String,
+}
+
+/// Dispatched when a player breaks a block, before it's removed. Cancelling it puts the block back.
+pub struct BlockBreakEvent {
+    pub entity_id: usize,
+    pub position: Position,
+    /// The block state that's being broken.
+    pub block_state: i32,
+    cancelled: Cancelled,
+}
+
+impl BlockBreakEvent {
+    pub fn new(entity_id: usize, position: Position, block_state: i32) -> Self {
+        Self {
+            entity_id,
+            position,
+            block_state,
+            cancelled: Cancelled::default(),
+        }
+    }
+}
+
+impl CancellableEvent for BlockBreakEvent {
+    fn cancelled(&self) -> &Cancelled {
+        &self.cancelled
+    }
+}
+
+/// Dispatched when a player places a block, before it's put in the world. Cancelling it removes
+/// the block again.
+pub struct BlockPlaceEvent {
+    pub entity_id: usize,
+    pub position: Position,
+    /// The block state that's being placed.
+    pub block_state: i32,
+    cancelled: Cancelled,
+}
+
+impl BlockPlaceEvent {
+    pub fn new(entity_id: usize, position: Position, block_state: i32) -> Self {
+        Self {
+            entity_id,
+            position,
+            block_state,
+            cancelled: Cancelled::default(),
+        }
+    }
+}
+
+impl CancellableEvent for BlockPlaceEvent {
+    fn cancelled(&self) -> &Cancelled {
+        &self.cancelled
+    }
 }
 
 #[event_handler(priority = "slow")]
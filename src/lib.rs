@@ -8,12 +8,32 @@ use net::ConnectionList;
 use state::{GlobalState, ServerState};
 use tokio::net::TcpListener;
 use utils::prelude::*;
+use crate::commands::builtin::register_default_commands;
 use crate::events::creation::dispatcher::EventDispatcher;
+use crate::net::systems::game_loop::register_default_tick_systems;
+use crate::net::utils::plugin_channels::register_default_channels;
+use crate::world::autosave::Autosave;
+use crate::world::block_entities::BlockEntityStore;
+use crate::world::block_registry::block_registry;
+use crate::auth::skins::SkinCache;
+use crate::utils::bans::BanList;
+use crate::utils::config::get_global_config;
+use crate::utils::permissions::OpList;
+use crate::utils::whitelist::Whitelist;
+use crate::world::border::WorldBorder;
+use crate::world::chunk_cache::ChunkCache;
+use crate::world::generation::configured_generator;
+use crate::world::player_data::PlayerDataStore;
+use crate::world::spawn::WorldSpawn;
+use crate::world::time::WorldTime;
+use crate::world::weather::Weather;
 
 extern crate core;
 #[macro_use]
 extern crate macro_rules_attribute;
 
+pub mod auth;
+pub mod commands;
 pub mod ecs;
 pub mod net;
 pub mod setup;
@@ -27,7 +47,8 @@ pub mod world;
 pub mod events;
 
 pub async fn create_state(tcp_listener: TcpListener) -> Result<GlobalState> {
-    Ok(Arc::new(ServerState {
+    let state = Arc::new(ServerState {
+        config: get_global_config(),
         world: Arc::new(World::new()),
         connections: ConnectionList {
             connections: DashMap::new(),
@@ -36,5 +57,31 @@ pub async fn create_state(tcp_listener: TcpListener) -> Result<GlobalState> {
         database: database::start_database().await?,
         server_stream: tcp_listener,
         event_dispatcher: Arc::new(EventDispatcher::new()),
-    }))
+        world_time: WorldTime::default(),
+        world_spawn: WorldSpawn::default(),
+        world_border: WorldBorder::default(),
+        weather: Weather::default(),
+        block_entities: BlockEntityStore::default(),
+        block_registry: block_registry(),
+        whitelist: Whitelist::load(&get_global_config().whitelist)?,
+        bans: BanList::load(&get_global_config().bans)?,
+        operators: OpList::load(&get_global_config().ops_file)?,
+        player_data: PlayerDataStore::new(&get_global_config().player_data_dir),
+        skins: SkinCache::default(),
+        chunk_generator: configured_generator()?,
+        chunk_cache: ChunkCache::configured(),
+        autosave: Autosave::default(),
+        chunk_writes: Default::default(),
+        block_edits: Default::default(),
+        pending_block_changes: Default::default(),
+        tick_systems: Default::default(),
+        tick_timings: Default::default(),
+        commands: Default::default(),
+        plugin_channels: Default::default(),
+        shutdown: tokio::sync::watch::channel(false).0,
+    });
+    register_default_tick_systems(&state);
+    register_default_commands(&state);
+    register_default_channels(&state);
+    Ok(state)
 }
@@ -3,7 +3,6 @@ use std::process::exit;
 
 use ferrumc::{create_state, setup, utils, world};
 use tokio::net::TcpListener;
-use tokio::select;
 use tokio::task::JoinHandle;
 use tracing::{error, info, trace};
 
@@ -38,27 +37,15 @@ async fn entry() -> Result<()> {
 
     let server_handle = start_server().await?;
 
-    let need_to_kill = select! {
-        server_result = server_handle => {
-            match server_result.expect("join_error") {
-                Ok(_) => {
-                    info!("Server exited successfully!");
-                }
-                Err(e) => {
-                    error!("Server exited with an error");
-                    error!("{}", e);
-                }
-            }
-            false
-        },
-        _ = tokio::signal::ctrl_c() => {
-            info!("Received ctrl+c.. Shutting down..");
-            true
+    // Ctrl+C and SIGTERM are handled by the systems, so everything gets saved before this returns
+    match server_handle.await.expect("join_error") {
+        Ok(_) => {
+            info!("Server exited successfully!");
+        }
+        Err(e) => {
+            error!("Server exited with an error");
+            error!("{}", e);
         }
-    };
-  
-    if need_to_kill {
-        kill_all_systems().await?;
     }
 
     info!("Exiting server;");
@@ -1,6 +1,7 @@
 use std::cmp::PartialEq;
 use std::fmt::{Debug, Display};
 use std::io::{Cursor, Read, Write};
+use std::net::IpAddr;
 use std::sync::atomic::AtomicU32;
 use std::sync::{atomic, Arc};
 use std::time::Duration;
@@ -9,14 +10,32 @@ use dashmap::DashMap;
 use ferrumc_codec::enc::{EncodeOption, NetEncode};
 use ferrumc_codec::network_types::varint::VarInt;
 use flate2::read::ZlibDecoder;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::AsyncWriteExt;
 use tokio::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
-use tracing::{debug, error, trace};
+use tracing::{debug, error, trace, warn};
+use uuid::Uuid;
 
 use ferrumc_macros::Component;
 
+use crate::events::creation::dispatcher::EventDispatcherExt;
+use crate::events::network_events::PacketReceivedEvent;
+use crate::events::world_events::PlayerLeaveWorldEvent;
+use crate::net::packets::outgoing::login_disconnect::LoginDisconnect;
+use crate::net::packets::outgoing::play_disconnect::PlayDisconnect;
+use crate::net::packets::outgoing::player_info_remove::PlayerInfoRemove;
 use crate::net::packets::{handle_packet, ConnectionId};
+use crate::net::utils::bandwidth::BandwidthMeter;
+use crate::net::utils::broadcast::broadcast;
+use crate::net::utils::encryption::{PacketDecryptor, PendingLogin};
+use crate::net::utils::forwarding::{ForwardedPlayer, PendingForwarding};
+use crate::net::utils::frame_reader::FrameReader;
+use crate::net::utils::legacy_ping::LegacyPing;
+use crate::net::utils::outbound::{OutboundQueue, Priority};
+use crate::net::utils::packet_queue::PacketQueue;
 use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::constants::GAME_VERSION;
+use crate::utils::text::TextComponent;
 
 use super::utils::config::get_global_config;
 use super::utils::prelude::*;
@@ -108,9 +127,47 @@ pub struct Connection {
     pub drop: bool,
 }
 
+impl Connection {
+    /// Sets up a connection over a socket, and starts the task that writes out the packets sent
+    /// on it.
+    pub fn new(id: usize, socket: tokio::net::TcpStream, state: State) -> Self {
+        let (in_stream, out_stream) = socket.into_split();
+        let stream = NetStream {
+            in_stream: Mutex::new(in_stream),
+            out_stream: Arc::new(Mutex::new(out_stream)),
+            outbound: Arc::new(OutboundQueue::new()),
+        };
+        let metadata = ConnectionMetadata::default();
+
+        let outbound = stream.outbound.clone();
+        let out_stream = stream.out_stream.clone();
+        let bandwidth = metadata.bandwidth.clone();
+        tokio::spawn(async move {
+            outbound.write_until_closed(&out_stream, &bandwidth).await;
+        });
+
+        Self {
+            id,
+            stream,
+            player_uuid: None,
+            state,
+            metadata,
+            drop: false,
+        }
+    }
+}
+
 pub struct NetStream {
     pub in_stream: Mutex<tokio::net::tcp::OwnedReadHalf>,
-    pub out_stream: Mutex<tokio::net::tcp::OwnedWriteHalf>,
+    pub out_stream: Arc<Mutex<tokio::net::tcp::OwnedWriteHalf>>,
+    /// Frames waiting for their turn on `out_stream`.
+    pub outbound: Arc<OutboundQueue>,
+}
+
+impl Drop for NetStream {
+    fn drop(&mut self) {
+        self.outbound.close();
+    }
 }
 
 /// Metadata for a connection.
@@ -118,11 +175,21 @@ pub struct NetStream {
 /// - `protocol_version`: The protocol version of the connection.
 /// - `entity`: The entity ID of the player.
 /// - `compressed`: Whether the connection is compressed. Default is false, until the server sends a SetCompression packet.
+/// - `bandwidth`: How many bytes have been sent to and received from the client ([BandwidthMeter]).
+/// - `pending_login`: The login waiting on the client's encryption response ([PendingLogin]).
+/// - `pending_decryptor`: Decrypts incoming bytes once the connection's receiver picks it up.
+/// - `pending_forwarding`: The login waiting on Velocity's forwarding data ([PendingForwarding]).
+/// - `forwarded`: What a proxy in front of the server said about the player ([ForwardedPlayer]).
 #[derive(Debug, Default)]
 pub struct ConnectionMetadata {
     pub protocol_version: i32,
     pub entity: usize,
     pub compressed: bool, // Default false, until server sends SetCompression
+    pub bandwidth: Arc<BandwidthMeter>,
+    pub pending_login: Option<PendingLogin>,
+    pub pending_decryptor: Option<PacketDecryptor>,
+    pub pending_forwarding: Option<PendingForwarding>,
+    pub forwarded: Option<ForwardedPlayer>,
 }
 
 pub fn setup_tracer() {
@@ -137,21 +204,11 @@ pub fn setup_tracer() {
 pub async fn init_connection(socket: tokio::net::TcpStream, state: GlobalState) -> Result<()> {
     let entity_id = state.world.create_entity().await.build();
 
-    let (in_stream, out_stream) = socket.into_split();
-
-    let conn = Connection {
-        id: entity_id,
-        stream: NetStream {
-            in_stream: Mutex::new(in_stream),
-            out_stream: Mutex::new(out_stream),
-        },
-        player_uuid: None,
-        state: State::Handshake,
-        metadata: ConnectionMetadata::default(),
-        drop: false,
-    };
-
-    let conn = Arc::new(RwLock::new(conn));
+    let conn = Arc::new(RwLock::new(Connection::new(
+        entity_id,
+        socket,
+        State::Handshake,
+    )));
 
     state
         .world
@@ -185,7 +242,8 @@ pub async fn init_connection(socket: tokio::net::TcpStream, state: GlobalState)
             "Error occurred in {:?}: {:?}, dropping connection",
             entity_id, e
         );
-        drop_conn(entity_id, state).await?;
+        let reason = TextComponent::text(format!("Internal Exception: {}", e));
+        disconnect(entity_id, &reason, state).await?;
     }
 
     Ok(())
@@ -210,7 +268,13 @@ pub async fn manage_conn(conn: Arc<RwLock<Connection>>, state: GlobalState) -> R
         debug!("Starting receiver for the addr: {:?}", local_addr);
     }
 
-    let network_compression_threshold = get_global_config().network_compression_threshold;
+    // Holds on to partially received packets between reads.
+    let mut frames = FrameReader::new();
+
+    if answer_legacy_ping(&conn, &mut frames, &state).await? {
+        let conn_id = conn.read().await.id;
+        return drop_conn(conn_id, state).await;
+    }
 
     loop {
         // Get the length of the packet
@@ -218,8 +282,7 @@ pub async fn manage_conn(conn: Arc<RwLock<Connection>>, state: GlobalState) -> R
 
         trace!("Reading length buffer");
 
-        let (packet_length, buffer) =
-            get_packet_length_and_buffer(&conn_read, network_compression_threshold).await?;
+        let (packet_length, buffer) = get_packet_length_and_buffer(&conn_read, &mut frames).await?;
         let (conn_id, conn_state, is_compressed) = (
             conn_read.id,
             conn_read.state.clone(),
@@ -229,34 +292,40 @@ pub async fn manage_conn(conn: Arc<RwLock<Connection>>, state: GlobalState) -> R
 
         trace!("Packet Length: {}", packet_length.get_val());
 
-        let mut cursor = Cursor::new(buffer);
-
-        if is_compressed {
-            // If the packet is compressed, handle decompression
-            let data_length = VarInt::read(&mut cursor).await?.get_val() as usize;
+        let (packet_id, mut cursor) = decode_frame(buffer, is_compressed)?;
 
-            if data_length != 0 {
-                let mut z = ZlibDecoder::new(cursor);
-                let mut decompressed_data = Vec::new();
-                z.read_to_end(&mut decompressed_data)?;
+        // The handshake and login packets change how everything after them is read, so they're
+        // handled before the next frame is. Everything else is handled in the background.
+        if matches!(conn_state, State::Handshake | State::Login) {
+            handle_packet(packet_id, conn_id, &conn_state, &mut cursor, state.clone()).await?;
 
-                cursor = Cursor::new(decompressed_data); // Update cursor with decompressed data
-            } else {
-                trace!("Packet is not compressed (size below compression threshold)");
+            if let Some(decryptor) = conn.write().await.metadata.pending_decryptor.take() {
+                frames.enable_decryption(decryptor);
             }
+        } else {
+            let state_clone = state.clone();
+            tokio::spawn(async move {
+                let event = PacketReceivedEvent::new(conn_id, packet_id, conn_state.clone());
+                if !state_clone.dispatch_cancellable_event(event).await {
+                    trace!("Packet {:#04x} from {} was cancelled", packet_id, conn_id);
+                    return;
+                }
+                let handled =
+                    handle_packet(packet_id, conn_id, &conn_state, &mut cursor, state_clone.clone())
+                        .await;
+                if let Err(e) = handled {
+                    error!(
+                        "Error handling packet {:#04x} from {}: {:?}, dropping connection",
+                        packet_id, conn_id, e
+                    );
+                    let reason = TextComponent::text(format!("Internal Exception: {}", e));
+                    if let Err(e) = disconnect(conn_id, &reason, state_clone).await {
+                        debug!("Couldn't disconnect {}: {}", conn_id, e);
+                    }
+                }
+            });
         }
 
-        // Get the packet id
-        let packet_id = VarInt::read(&mut cursor).await?;
-        trace!("Packet ID: {}", packet_id);
-
-        let packet_id = packet_id.get_val() as u8;
-
-        let state_clone = state.clone();
-        tokio::spawn(async move {
-            handle_packet(packet_id, conn_id, &conn_state, &mut cursor, state_clone).await
-        });
-
         // Drop connection if flagged
         drop_conn_if_flagged(conn.clone(), state.clone()).await?;
 
@@ -272,41 +341,113 @@ pub async fn manage_conn(conn: Arc<RwLock<Connection>>, state: GlobalState) -> R
     #[allow(unreachable_code)]
     Ok(())
 }
+
+/// How long to wait for the rest of a legacy ping after its first bytes. Beta clients only ever
+/// send the one byte, newer ones send the rest straight after.
+const LEGACY_PING_WAIT: Duration = Duration::from_millis(100);
+
+/// Answers the pre-1.7 server list ping, if that's what the connection starts with instead of a
+/// handshake. Returns whether it did, in which case the connection is done.
+async fn answer_legacy_ping(
+    conn: &Arc<RwLock<Connection>>,
+    frames: &mut FrameReader,
+    state: &GlobalState,
+) -> Result<bool> {
+    let conn = conn.read().await;
+    {
+        let mut in_stream = conn.get_in_stream().await;
+        frames.fill(&mut *in_stream).await?;
+        while LegacyPing::is_partial(frames.buffered_bytes()) {
+            match tokio::time::timeout(LEGACY_PING_WAIT, frames.fill(&mut *in_stream)).await {
+                Ok(filled) => filled?,
+                Err(_) => break,
+            }
+        }
+    }
+    let Some(ping) = LegacyPing::detect(frames.buffered_bytes()) else {
+        return Ok(false);
+    };
+    debug!("Answering a legacy ping ({:?}) from {}", ping, conn.id);
+
+    let config = get_global_config();
+    let motd = config.motd.first().map_or("", String::as_str);
+    let online = state.world.query::<&Player>().iter().await.count();
+    let response = ping.response(GAME_VERSION, motd, online, config.max_players);
+    let mut out_stream = conn.get_out_stream().await;
+    out_stream.write_all(&response).await?;
+    out_stream.flush().await?;
+    Ok(true)
+}
+
 async fn get_packet_length_and_buffer(
     conn: &RwLockReadGuard<'_, Connection>,
-    network_compression_threshold: i32,
+    frames: &mut FrameReader,
 ) -> Result<(VarInt, Vec<u8>)> {
-    let compressed = conn.metadata.compressed;
+    let bandwidth = &conn.metadata.bandwidth;
     let mut conn = conn.get_in_stream().await;
 
-    // Read length of packet
-    let packet_length = VarInt::read(&mut *conn).await?;
+    // Only the frame reader touches the socket, and it keeps whatever it has read so far if this
+    // future gets dropped, so a cancelled read can't leave the stream halfway through a packet.
+    let (packet_length, buffer) = frames.read_frame(&mut *conn).await?;
 
-    // Read packet data into buffer
-    let mut buffer = vec![0u8; packet_length.get_val() as usize];
-    conn.read_exact(&mut buffer).await?;
+    bandwidth.record_received(packet_length.get_len() + buffer.len());
 
-    // Handle cases when compression is enabled
-    if compressed {
-        // Decompress the packet
-        let mut cursor = Cursor::new(&buffer);
-        let data_length = VarInt::read(&mut cursor).await?;
+    // If the connection is compressed, the buffer still starts with the data length.
+    // Decompression is left to the caller.
+    Ok((packet_length, buffer))
+}
+
+/// The largest uncompressed packet a compressed frame may claim to hold, same as vanilla.
+pub const MAX_DECOMPRESSED_LENGTH: i32 = 8388608;
+
+/// Turns a complete frame into its packet id and a cursor positioned at the start of the packet
+/// body, decompressing it first if needed.
+///
+/// This only ever works on the buffered frame and never awaits, so nothing here can be cancelled
+/// halfway or read past the end of the packet into the socket. The data length of a compressed
+/// frame is checked against what it actually inflates to, so a small frame can't be used to make
+/// the server inflate an arbitrary amount of data.
+pub fn decode_frame(buffer: Vec<u8>, is_compressed: bool) -> Result<(u8, Cursor<Vec<u8>>)> {
+    let mut cursor = Cursor::new(buffer);
+
+    if is_compressed {
+        // If the packet is compressed, handle decompression
+        let (data_length, read) = VarInt::from_bytes(cursor.get_ref())?;
+        cursor.set_position(read as u64);
+
+        if !(0..=MAX_DECOMPRESSED_LENGTH).contains(&data_length.get_val()) {
+            return Err(Error::Generic(format!(
+                "Invalid uncompressed packet length: {}",
+                data_length.get_val()
+            )));
+        }
 
-        // If the data length is greater than or equal to the threshold, the data is compressed
-        if data_length.get_val() >= network_compression_threshold {
-            // Compressed packet. Need to decompress it.
-            let compressed_data = &buffer[cursor.position() as usize..];
-            let mut z = ZlibDecoder::new(Cursor::new(compressed_data));
+        if data_length.get_val() != 0 {
+            // Reading one byte past the claimed length is enough to tell it was a lie
+            let mut z = ZlibDecoder::new(cursor).take(data_length.get_val() as u64 + 1);
             let mut decompressed_data = Vec::new();
             z.read_to_end(&mut decompressed_data)?;
+            if decompressed_data.len() != data_length.get_val() as usize {
+                return Err(Error::Generic(format!(
+                    "Packet inflated to {} bytes, but claimed to be {}",
+                    decompressed_data.len(),
+                    data_length.get_val()
+                )));
+            }
 
-            // return the decompressed data
-            return Ok((data_length, decompressed_data));
+            cursor = Cursor::new(decompressed_data); // Update cursor with decompressed data
+        } else {
+            trace!("Packet is not compressed (size below compression threshold)");
         }
     }
 
-    // Compression off or data length less than threshold. Return the buffer as is.
-    Ok((packet_length, buffer))
+    // Get the packet id
+    let position = cursor.position() as usize;
+    let (packet_id, read) = VarInt::from_bytes(&cursor.get_ref()[position..])?;
+    cursor.set_position((position + read) as u64);
+    trace!("Packet ID: {}", packet_id);
+
+    Ok((packet_id.get_val() as u8, cursor))
 }
 async fn drop_conn_if_flagged(conn: Arc<RwLock<Connection>>, state: GlobalState) -> Result<()> {
     let read = conn.read().await;
@@ -334,95 +475,206 @@ pub async fn drop_conn(connection_id: usize, state: GlobalState) -> Result<()> {
     {
         let read_lock = conn_arc.read().await;
         let entity_id = read_lock.id;
-        state.world.delete_entity(entity_id).await?;
+        let player = state
+            .world
+            .get_component::<Player>(entity_id)
+            .await
+            .map(|player| (Uuid::from_u128(player.get_uuid()), player.username.clone()))
+            .ok();
+        let uuid = player.as_ref().map(|(uuid, _)| *uuid);
+        if let Some((uuid, username)) = player {
+            state
+                .dispatch_event(PlayerLeaveWorldEvent::new(entity_id, username))
+                .await;
+            // Failing to save shouldn't stop the player from being removed
+            if let Err(e) = state.save_player_data(entity_id, uuid).await {
+                warn!("Failed to save player data for {}: {}", uuid, e);
+            }
+        }
+        state.despawn_entity(entity_id).await?;
+
+        // Take them off everyone else's tab list, they've already been removed from the
+        // connections so they won't get it themselves
+        if let Some(uuid) = uuid {
+            broadcast(&PlayerInfoRemove::new(&[uuid]), &state).await?;
+        }
     }
 
-    // drop the connection in the end, just in case it errors out
+    // drop the connection in the end, just in case it errors out. Whatever is still queued,
+    // like the reason they were disconnected, goes out first.
     let conn = conn_arc.read().await;
+    conn.stream.outbound.close();
+    if let Err(e) = conn.flush().await {
+        debug!("Couldn't write the last packets to {}: {}", conn.id, e);
+    }
     let mut conn = conn.get_out_stream().await;
     conn.shutdown().await?;
     Ok(())
 }
 
+/// Disconnects a player, showing them why if they're logging in or in game.
+///
+/// The connection is dropped even if the reason can't be sent, e.g. because the player already
+/// closed it.
+pub async fn disconnect(
+    connection_id: usize,
+    reason: &TextComponent,
+    state: GlobalState,
+) -> Result<()> {
+    debug!(
+        "Disconnecting connection {}: {}",
+        connection_id,
+        reason.plain_text()
+    );
+    let conn = state.connections.get_connection(connection_id)?;
+    if let Err(e) = conn.read().await.send_disconnect(reason).await {
+        debug!(
+            "Couldn't tell {} why they were disconnected: {}",
+            connection_id, e
+        );
+    }
+    drop_conn(connection_id, state).await
+}
+
+/// Encodes a packet into its full wire frame.
+///
+/// With a `compression_threshold` the compressed packet format is used, compressing the packet
+/// if it's at least that many bytes long. Without one, the plain format is used.
+pub async fn frame_packet(
+    packet: impl NetEncode,
+    compression_threshold: Option<i32>,
+) -> Result<Vec<u8>> {
+    let mut frame = Vec::new();
+
+    let Some(network_compression_threshold) = compression_threshold else {
+        trace!("Compression is disabled");
+        // Compression is disabled
+        // Send the packet with no compression format (Default EncodeOption)
+        packet
+            .net_encode(&mut frame, &EncodeOption::Default)
+            .await?;
+        return Ok(frame);
+    };
+
+    trace!("Compression is enabled");
+
+    // Get the packet without length information
+    let mut packet_data = Vec::new();
+    packet
+        .net_encode(&mut packet_data, &EncodeOption::AlwaysOmitSize)
+        .await?;
+
+    // Get the length of the data
+    let data_length = VarInt::from(packet_data.len() as i32);
+
+    if data_length.get_val() >= network_compression_threshold {
+        trace!("Compressing packet");
+        // Compress the packet
+        let mut compressed_data = Vec::new();
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(&mut compressed_data, flate2::Compression::default());
+        encoder.write_all(&packet_data)?;
+        encoder.finish()?;
+
+        // Compressed packet structure
+        let compressed_length = compressed_data.len();
+        let packet_length = VarInt::from((data_length.get_len() + compressed_length) as i32);
+
+        packet_length
+            .net_encode(&mut frame, &EncodeOption::AlwaysOmitSize)
+            .await?;
+
+        data_length
+            .net_encode(&mut frame, &EncodeOption::AlwaysOmitSize)
+            .await?;
+
+        frame.extend_from_slice(&compressed_data); // Raw compressed data
+    } else {
+        trace!("Data length is less than threshold");
+        // No compression applied, use a 0 length
+        let zero_length = VarInt::from(0); // Indicate no compression
+
+        let packet_length = VarInt::from((zero_length.get_len() + packet_data.len()) as i32);
+
+        packet_length
+            .net_encode(&mut frame, &EncodeOption::AlwaysOmitSize)
+            .await?;
+
+        zero_length
+            .net_encode(&mut frame, &EncodeOption::AlwaysOmitSize)
+            .await?;
+
+        frame.extend_from_slice(&packet_data);
+    }
+
+    Ok(frame)
+}
+
 impl Connection {
     pub async fn send_packet(&self, packet: impl NetEncode) -> Result<()> {
+        self.send_packet_with_priority(packet, Priority::Normal)
+            .await
+    }
+
+    /// Sends a packet ahead of anything else that's waiting to be written, like the rest of a
+    /// batch of chunks. For keep alives, pings and disconnects.
+    pub async fn send_urgent_packet(&self, packet: impl NetEncode) -> Result<()> {
+        self.send_packet_with_priority(packet, Priority::High).await
+    }
+
+    pub async fn send_packet_with_priority(
+        &self,
+        packet: impl NetEncode,
+        priority: Priority,
+    ) -> Result<()> {
         trace!("Sending packet");
-        let mut out_stream = self.get_out_stream().await;
 
-        // Is compression enabled?
-        let compressed = self.metadata.compressed;
-        if compressed {
-            trace!("Compression is enabled");
-
-            // Get the packet without length information
-            let mut packet_data = Vec::new();
-            packet
-                .net_encode(&mut packet_data, &EncodeOption::AlwaysOmitSize)
-                .await?;
-
-            // Get the length of the data
-            let data_length = VarInt::from(packet_data.len() as i32);
-
-            let network_compression_threshold = get_global_config().network_compression_threshold;
-            if data_length.get_val() >= network_compression_threshold {
-                trace!("Compressing packet");
-                // Compress the packet
-                let mut compressed_data = Vec::new();
-                let mut encoder = flate2::write::ZlibEncoder::new(
-                    &mut compressed_data,
-                    flate2::Compression::default(),
-                );
-                encoder.write_all(&packet_data)?;
-                encoder.finish()?;
-
-                // Compressed packet structure
-                let compressed_length = compressed_data.len();
-                let packet_length =
-                    VarInt::from((data_length.get_len() + compressed_length) as i32);
-
-                // Send the packet
-                packet_length
-                    .net_encode(&mut *out_stream, &EncodeOption::AlwaysOmitSize)
-                    .await?;
-
-                data_length
-                    .net_encode(&mut *out_stream, &EncodeOption::AlwaysOmitSize)
-                    .await?;
-
-                out_stream.write_all(&compressed_data).await?; // Sending raw compressed data
-            } else {
-                trace!("Data length is less than threshold");
-                // No compression applied, use a 0 length
-                let zero_length = VarInt::from(0); // Indicate no compression
-
-                let packet_length =
-                    VarInt::from((zero_length.get_len() + packet_data.len()) as i32);
-
-                // Send the packet length and uncompressed data
-                packet_length
-                    .net_encode(&mut *out_stream, &EncodeOption::AlwaysOmitSize)
-                    .await?;
-
-                zero_length
-                    .net_encode(&mut *out_stream, &EncodeOption::AlwaysOmitSize)
-                    .await?;
-
-                out_stream.write_all(&packet_data).await?;
-            }
-        } else {
-            trace!("Compression is disabled");
-            // Compression is disabled
-            // Send the packet with no compression format (Default EncodeOption)
-            packet
-                .net_encode(&mut *out_stream, &EncodeOption::Default)
-                .await?;
+        // Frame the whole packet first, so we know exactly how many bytes go over the wire.
+        let frame = frame_packet(packet, self.compression_threshold()).await?;
+        self.queue_frame(frame, priority)
+    }
+
+    /// Hands a frame to the connection's writing task, see [OutboundQueue].
+    fn queue_frame(&self, frame: Vec<u8>, priority: Priority) -> Result<()> {
+        if self.stream.outbound.is_closed() {
+            return Err(Error::TcpError(format!("connection {} is closed", self.id)));
         }
+        self.stream.outbound.push(frame, priority);
         Ok(())
     }
 
-    /// Just exists so it doesn't seem weird when sending a packet_queue, since multiple packetS are sent.
-    pub async fn send_packets(&self, packets: impl NetEncode) -> Result<()> {
-        self.send_packet(packets).await
+    /// Writes out everything that's queued right away, instead of leaving it to the writing
+    /// task.
+    pub async fn flush(&self) -> Result<()> {
+        let mut out_stream = self.get_out_stream().await;
+        let written = self.stream.outbound.write_to(&mut *out_stream).await?;
+        self.metadata.bandwidth.record_sent(written);
+
+        Ok(())
+    }
+
+    /// The threshold outgoing packets should be compressed at, or `None` if they shouldn't be.
+    ///
+    /// Compression only kicks in once Set Compression has been sent, and never applies to the
+    /// status exchange.
+    pub fn compression_threshold(&self) -> Option<i32> {
+        self.should_compress()
+            .then(|| get_global_config().network_compression_threshold)
+    }
+
+    /// Whether outgoing packets should use the compressed packet format.
+    pub fn should_compress(&self) -> bool {
+        self.metadata.compressed && self.state != State::Status
+    }
+
+    /// Sends all the packets in a [PacketQueue]. They're already framed, so they're written as is.
+    pub async fn send_packets(&self, packets: PacketQueue) -> Result<()> {
+        if packets.is_empty() {
+            return Ok(());
+        }
+
+        // The whole batch is one entry, so it still goes out in a single write
+        self.queue_frame(packets.into_bytes(), Priority::Normal)
     }
 
     pub async fn get_in_stream(&self) -> MutexGuard<'_, tokio::net::tcp::OwnedReadHalf> {
@@ -433,6 +685,29 @@ impl Connection {
         self.stream.out_stream.lock().await
     }
 
+    /// The player's address: the one a proxy forwarded, or else the one they connected from.
+    pub async fn remote_ip(&self) -> Option<IpAddr> {
+        if let Some(forwarded) = &self.metadata.forwarded {
+            return Some(forwarded.ip);
+        }
+        // The write half isn't locked while waiting on the next packet, unlike the read half
+        self.get_out_stream()
+            .await
+            .peer_addr()
+            .ok()
+            .map(|addr| addr.ip())
+    }
+
+    /// Sends the disconnect packet for the state the connection is in. Connections that are only
+    /// pinging the server don't get told anything.
+    pub async fn send_disconnect(&self, reason: &TextComponent) -> Result<()> {
+        match self.state {
+            State::Login => self.send_urgent_packet(LoginDisconnect::new(reason)).await,
+            State::Play => self.send_urgent_packet(PlayDisconnect::new(reason)).await,
+            _ => Ok(()),
+        }
+    }
+
     pub async fn drop_connection(&self, state: GlobalState) -> Result<()> {
         drop_conn(self.id, state).await
     }
@@ -0,0 +1,57 @@
+use tracing::debug;
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+
+/// Sent when the player opens or closes one of the recipe books, or toggles its "show craftable"
+/// filter.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x21, state = "play")]
+pub struct ChangeRecipeBookSettings {
+    pub book: RecipeBook,
+    pub book_open: bool,
+    pub filter_active: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipeBook {
+    Crafting,
+    Furnace,
+    BlastFurnace,
+    Smoker,
+}
+
+impl NetDecode for RecipeBook {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let book = VarInt::read(bytes).await?.get_val();
+        let book = match book {
+            0 => RecipeBook::Crafting,
+            1 => RecipeBook::Furnace,
+            2 => RecipeBook::BlastFurnace,
+            3 => RecipeBook::Smoker,
+            _ => return Err(Error::Generic(format!("Invalid recipe book: {}", book))),
+        };
+        Ok(Box::new(book))
+    }
+}
+
+impl IncomingPacket for ChangeRecipeBookSettings {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        _state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        // Recipe book settings aren't saved yet, the client remembers them for the session
+        debug!(
+            "Player {} set their {:?} recipe book to open: {}, filtering: {}",
+            conn_id, self.book, self.book_open, self.filter_active
+        );
+        Ok(())
+    }
+}
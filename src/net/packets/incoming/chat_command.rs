@@ -0,0 +1,27 @@
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::commands::CommandSender;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+
+/// A command typed into chat, without the leading slash. The argument signatures that follow the
+/// salt are ignored, since commands aren't signed.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x04, state = "play")]
+pub struct ChatCommand {
+    pub command: String,
+    pub timestamp: i64,
+    pub salt: i64,
+}
+
+impl IncomingPacket for ChatCommand {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        state
+            .execute_command(CommandSender::Player(conn_id), &self.command)
+            .await
+    }
+}
@@ -2,15 +2,20 @@ use tracing::debug;
 
 use ferrumc_macros::{packet, NetDecode};
 
+use crate::events::creation::dispatcher::EventDispatcherExt;
+use crate::events::player_events::ChatMessageEvent;
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::state::GlobalState;
 use crate::utils::components::player::Player;
 
+/// A chat message from a player. Messages are broadcast unsigned, so the signature and
+/// acknowledgements that follow the salt are ignored.
 #[derive(NetDecode)]
 #[packet(packet_id = 0x05, state = "play")]
 pub struct PacketChatMessage {
     pub message: String,
     pub timestamp: i64,
+    pub salt: i64,
 }
 
 impl IncomingPacket for PacketChatMessage {
@@ -22,9 +27,17 @@ impl IncomingPacket for PacketChatMessage {
         let my_id = conn_id;
 
         let my_player = state.world.get_component::<Player>(my_id).await?;
+        let (uuid, username) = (my_player.uuid, my_player.username.clone());
+        drop(my_player);
 
-        debug!("[{}]: {}", my_player.username, self.message);
+        let event = ChatMessageEvent::new(my_id, username.clone(), self.message.clone());
+        if !state.dispatch_cancellable_event(event).await {
+            debug!("Chat message from {} was cancelled", username);
+            return Ok(());
+        }
 
-        Ok(())
+        state
+            .broadcast_chat(uuid, &username, &self.message, self.timestamp, self.salt)
+            .await
     }
 }
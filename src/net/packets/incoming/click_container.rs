@@ -0,0 +1,201 @@
+use tracing::{debug, trace};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::ConnectionWrapper;
+use crate::state::GlobalState;
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::open_container::OpenContainer;
+use crate::utils::encoding::slot::Slot;
+
+/// Sent when the player clicks a slot in an open container.
+///
+/// The client predicts the outcome itself and sends along every slot it thinks changed.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x0B, state = "play")]
+pub struct ClickContainer {
+    pub window_id: u8,
+    /// The last state id the server sent for this container.
+    pub state_id: VarInt,
+    /// -999 for clicks outside the window.
+    pub slot: i16,
+    pub button: i8,
+    pub mode: VarInt,
+    pub changed_slots: Vec<ChangedSlot>,
+    pub carried_item: Slot,
+}
+
+#[derive(Debug)]
+pub struct ChangedSlot {
+    pub slot: i16,
+    pub item: Slot,
+}
+
+impl NetDecode for ChangedSlot {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let slot = *i16::net_decode(bytes).await?;
+        let item = *Slot::net_decode(bytes).await?;
+        Ok(Box::new(ChangedSlot { slot, item }))
+    }
+}
+
+impl IncomingPacket for ClickContainer {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("ClickContainer packet received: {:?}", self);
+
+        let component_storage = state.world.get_component_storage();
+        let creative = state
+            .world
+            .get_component::<GameMode>(conn_id)
+            .await
+            .is_ok_and(|game_mode| *game_mode == GameMode::Creative);
+        let resync = if self.window_id == 0 {
+            let mut inventory = component_storage.get_mut::<Inventory>(conn_id).await?;
+            match inventory.click(&self, creative) {
+                Ok(()) => return Ok(()),
+                Err(resync) => resync,
+            }
+        } else {
+            let Ok(mut container) = component_storage.get_mut::<OpenContainer>(conn_id).await
+            else {
+                trace!("Player {} clicked without a container open", conn_id);
+                return Ok(());
+            };
+            if container.window_id != self.window_id {
+                return Ok(());
+            }
+            let mut inventory = component_storage.get_mut::<Inventory>(conn_id).await?;
+            match container.click(&self, &mut inventory, creative) {
+                Ok(()) => return Ok(()),
+                Err(resync) => resync,
+            }
+        };
+
+        debug!("Player {} clicked on a stale window, resyncing", conn_id);
+        let conn = state
+            .world
+            .get_component::<ConnectionWrapper>(conn_id)
+            .await?;
+        let conn = conn.0.read().await;
+        conn.send_packet(resync).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::utils::components::inventory::HOTBAR_START;
+    use crate::utils::components::open_container::WindowType;
+    use crate::world::item_registry::item_registry;
+
+    #[tokio::test]
+    async fn test_stale_click_resyncs_container() {
+        // Window 1, state id 3, slot 0, left click, regular click, one changed slot (0, empty),
+        // nothing carried
+        let data = vec![
+            0x01, 0x03, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let click = ClickContainer::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(click.state_id.get_val(), 3);
+        assert_eq!(click.changed_slots.len(), 1);
+
+        let mut container = OpenContainer::new(1, WindowType::Generic9x3);
+        container.slots[0] = Slot::new(1, 64);
+        container.state_id = 5;
+        let mut inventory = Inventory::default();
+
+        let resync = container.click(&click, &mut inventory, false).unwrap_err();
+        assert_eq!(resync.state_id.get_val(), 6);
+        // The chest's slots and the player's main inventory and hotbar
+        assert_eq!(resync.slot_count.get_val(), 63);
+        // The stale click didn't touch anything
+        assert_eq!(resync.slots[0], Slot::new(1, 64));
+
+        // Once caught up, the same click goes through, taken as it is from a creative player
+        let data = vec![
+            0x01, 0x06, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let click = ClickContainer::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert!(container.click(&click, &mut inventory, true).is_ok());
+        assert!(container.slots[0].is_empty());
+        assert_eq!(container.state_id, 6);
+    }
+
+    #[tokio::test]
+    async fn test_stale_click_resyncs_inventory() {
+        // Window 0, state id 1, slot 36, left click, regular click, one changed slot (36,
+        // empty), carrying 64 of item 1
+        let data = vec![
+            0x00, 0x01, 0x00, 0x24, 0x00, 0x00, 0x01, 0x00, 0x24, 0x00, 0x01, 0x01, 0x40, 0x00,
+        ];
+        let click = ClickContainer::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+
+        let mut inventory = Inventory::default();
+        inventory.slots[36] = Slot::new(1, 64);
+
+        let resync = inventory.click(&click, false).unwrap_err();
+        assert_eq!(resync.window_id, 0);
+        assert_eq!(resync.state_id.get_val(), 1);
+        assert_eq!(resync.slots[36], Slot::new(1, 64));
+
+        assert!(inventory.click(&click, false).is_ok());
+        assert!(inventory.slots[36].is_empty());
+        assert_eq!(inventory.carried_item, Slot::new(1, 64));
+    }
+
+    #[test]
+    fn test_container_clicks_move_inventory_items() {
+        let click = |slot, mode, changed: Vec<(i16, Slot)>| ClickContainer {
+            window_id: 1,
+            state_id: VarInt::from(0),
+            slot,
+            button: 0,
+            mode: VarInt::from(mode),
+            changed_slots: changed
+                .into_iter()
+                .map(|(slot, item)| ChangedSlot { slot, item })
+                .collect(),
+            carried_item: Slot::empty(),
+        };
+        let mut container = OpenContainer::new(1, WindowType::Anvil);
+        let mut inventory = Inventory::default();
+        inventory.slots[HOTBAR_START] = Slot::new(1, 10);
+
+        // The hotbar starts at 30 in an anvil, and shift clicking it fills the first input
+        let shift_click = click(30, 1, vec![(0, Slot::new(1, 10)), (30, Slot::empty())]);
+        assert!(container.click(&shift_click, &mut inventory, false).is_ok());
+        assert_eq!(container.slots[0], Slot::new(1, 10));
+        assert!(inventory.slots[HOTBAR_START].is_empty());
+
+        // Items made up by the client don't go in
+        let made_up = click(1, 0, vec![(1, Slot::new(2, 64))]);
+        let resync = container
+            .click(&made_up, &mut inventory, false)
+            .unwrap_err();
+        assert!(container.slots[1].is_empty());
+        assert!(resync.slots[1].is_empty());
+        assert_eq!(resync.slots[0], Slot::new(1, 10));
+
+        // Closing the anvil gives the input back
+        assert!(container.close(&mut inventory, item_registry()));
+        assert_eq!(inventory.slots[HOTBAR_START], Slot::new(1, 10));
+    }
+}
@@ -0,0 +1,220 @@
+use tracing::debug;
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::respawn::Respawn;
+use crate::net::packets::outgoing::set_health::SetHealth;
+use crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::systems::chunk_sender::ChunkSender;
+use crate::state::GlobalState;
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::health::Health;
+use crate::utils::components::loaded_chunks::LoadedChunks;
+use crate::utils::components::precise_position::PrecisePosition;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::components::visible_entities::VisibleEntities;
+use crate::utils::constants::init;
+use crate::world::dimension::Dimension;
+
+/// The client status packet (client command on wiki.vg) is sent by the client when it's ready to
+/// respawn after dying, or when it opens the statistics menu.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x07, state = "play")]
+pub struct ClientStatus {
+    pub action: ClientStatusAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientStatusAction {
+    PerformRespawn,
+    RequestStats,
+}
+
+impl NetDecode for ClientStatusAction {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let action = VarInt::read(bytes).await?.get_val();
+        match action {
+            0 => Ok(Box::new(ClientStatusAction::PerformRespawn)),
+            1 => Ok(Box::new(ClientStatusAction::RequestStats)),
+            _ => Err(Error::Generic(format!(
+                "Invalid client status action: {}",
+                action
+            ))),
+        }
+    }
+}
+
+impl IncomingPacket for ClientStatus {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        debug!("ClientStatus packet received: {:?}", self.action);
+
+        match self.action {
+            ClientStatusAction::PerformRespawn => respawn(conn_id, state).await,
+            ClientStatusAction::RequestStats => {
+                // Statistics aren't tracked yet, so there's nothing to send back.
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Brings a dead player back to life. Returns false if they weren't dead to begin with.
+///
+/// The client throws away every chunk it had when it respawns, so they're all forgotten here too
+/// and get sent again.
+fn revive(health: &mut Health, loaded_chunks: &mut LoadedChunks) -> bool {
+    if !health.respawn() {
+        return false;
+    }
+    loaded_chunks.clear();
+    true
+}
+
+/// Moves a dead player back to the spawn point and resends the world around it.
+async fn respawn(conn_id: ConnectionId, state: GlobalState) -> crate::utils::prelude::Result<()> {
+    let set_health = {
+        let component_storage = state.world.get_component_storage();
+        let mut health = component_storage.get_mut::<Health>(conn_id).await?;
+        let mut loaded_chunks = component_storage
+            .get_mut_or_insert_with::<LoadedChunks>(conn_id, Default::default)
+            .await;
+        if !revive(&mut health, &mut loaded_chunks) {
+            debug!("Player {} asked to respawn without being dead", conn_id);
+            return Ok(());
+        }
+        SetHealth::new(&health)
+    };
+
+    let game_mode = state
+        .world
+        .get_component::<GameMode>(conn_id)
+        .await
+        .map(|game_mode| *game_mode)
+        .unwrap_or_default();
+    let position = state.world_spawn.position();
+    let rotation = Rotation::new(state.world_spawn.angle(), init::DEFAULT_SPAWN_PITCH);
+
+    let sync_position = SynchronizePlayerPosition::new(&position, &rotation);
+
+    // The client forgets every entity along with its chunks, so they're spawned for it again.
+    // Players always come back in the overworld, wherever they died.
+    state
+        .world
+        .get_component_storage()
+        .insert(conn_id, Dimension::Overworld)
+        .insert(conn_id, PrecisePosition::corner(&position))
+        .insert(conn_id, position)
+        .insert(conn_id, rotation)
+        .insert(conn_id, VisibleEntities::default());
+
+    {
+        let conn = state.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(Respawn::after_death(game_mode)).await?;
+        conn.send_packet(set_health).await?;
+        conn.send_packet(sync_position).await?;
+    }
+
+    ChunkSender::send_chunks_to_player(state.clone(), conn_id).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::Instant;
+
+    use tokio::net::TcpListener;
+
+    use crate::net::packets::incoming::client_info::ClientInfo;
+    use crate::tests::connections::add_play_connection;
+    use crate::utils::components::loaded_chunks::chunks_in_view;
+    use crate::utils::components::player::Player;
+    use crate::utils::encoding::position::Position;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_perform_respawn() {
+        let mut data = Cursor::new(vec![0x00]);
+        let packet = ClientStatus::net_decode(&mut data).await.unwrap();
+        assert_eq!(packet.action, ClientStatusAction::PerformRespawn);
+    }
+
+    #[test]
+    fn test_death_then_respawn_resends_chunks() {
+        let now = Instant::now();
+        let mut health = Health::default();
+        let mut loaded_chunks = LoadedChunks::default();
+        let sent = loaded_chunks.update_view(chunks_in_view(0, 0, 2), now);
+        loaded_chunks.mark_sent(&sent);
+
+        // Respawning while alive does nothing
+        assert!(!revive(&mut health, &mut loaded_chunks));
+        assert!(loaded_chunks.is_loaded(0, 0));
+
+        assert!(!health.set_health(4.0));
+        assert!(health.set_health(-3.0));
+        assert!(health.is_dead());
+        assert_eq!(health.health, 0.0);
+        // Dead players can't die again, or be healed
+        assert!(!health.set_health(0.0));
+        assert!(!health.set_health(20.0));
+        assert!(health.is_dead());
+
+        assert!(revive(&mut health, &mut loaded_chunks));
+        assert!(!health.is_dead());
+        assert_eq!(health.health, Health::MAX_HEALTH);
+
+        // Every chunk around spawn counts as new again, so they're all sent
+        let resent = loaded_chunks.update_view(chunks_in_view(0, 0, 2), now);
+        assert_eq!(resent.len(), 25);
+    }
+
+    #[tokio::test]
+    async fn test_respawn_at_world_spawn() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let (player, _client) = add_play_connection(&state).await;
+        let mut health = Health::default();
+        health.set_health(0.0);
+        state
+            .world
+            .get_component_storage()
+            .insert(player, Player::new(1, "Steve".to_string()))
+            // Keeps the chunks sent on respawn down to a few
+            .insert(
+                player,
+                ClientInfo {
+                    locale: "en_us".to_string(),
+                    view_distance: 1,
+                    chat_mode: 0,
+                    chat_colors: true,
+                    displayed_skin_parts: 0,
+                    main_hand: 1,
+                },
+            )
+            .insert(player, health)
+            .insert(player, Dimension::Nether);
+
+        let spawn = Position::new(100, 72, -40);
+        state.set_world_spawn(spawn.clone(), 90.0).await.unwrap();
+        respawn(player, state.clone()).await.unwrap();
+
+        let position = state.world.get_component::<Position>(player).await.unwrap();
+        assert_eq!(*position, spawn);
+        let rotation = state.world.get_component::<Rotation>(player).await.unwrap();
+        assert_eq!(rotation.yaw, 90.0);
+    }
+}
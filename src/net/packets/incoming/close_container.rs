@@ -0,0 +1,23 @@
+use tracing::trace;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+/// Sent when the player closes a container window.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x0C, state = "play")]
+pub struct CloseContainer {
+    pub window_id: u8,
+}
+
+impl IncomingPacket for CloseContainer {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("CloseContainer packet received: {:?}", self);
+
+        state.close_container(conn_id, self.window_id).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use tracing::{debug, warn};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::auth::session::has_joined;
+use crate::net::packets::incoming::login_start::LoginStart;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::encryption::{ciphers, minecraft_digest, server_keys};
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+/// The client's answer to [crate::net::packets::outgoing::encryption_request::EncryptionRequest].
+///
+/// Both fields are encrypted with the server's public key. Everything after this packet is
+/// encrypted with the shared secret, in both directions. Once the session servers confirm the
+/// player owns the account, the login carries on as usual with their verified profile.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x01, state = "login")]
+pub struct EncryptionResponse {
+    pub shared_secret: Vec<u8>,
+    pub verify_token: Vec<u8>,
+}
+
+impl IncomingPacket for EncryptionResponse {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        let conn = state.connections.get_connection(conn_id)?;
+        let Some(pending) = conn.write().await.metadata.pending_login.take() else {
+            return Err(Error::Generic(
+                "Got an encryption response without asking for one".to_string(),
+            ));
+        };
+
+        let keys = server_keys()?;
+        if keys.decrypt(&self.verify_token)? != pending.verify_token {
+            return Err(Error::Generic("Verify token doesn't match".to_string()));
+        }
+        let shared_secret = keys.decrypt(&self.shared_secret)?;
+        let (encryptor, decryptor) = ciphers(&shared_secret)?;
+        {
+            let mut conn = conn.write().await;
+            conn.stream.outbound.enable_encryption(encryptor);
+            conn.metadata.pending_decryptor = Some(decryptor);
+        }
+
+        let server_hash = minecraft_digest("", &shared_secret, keys.public_key_der());
+        let profile = match has_joined(&pending.username, &server_hash).await {
+            Ok(Some(profile)) => profile,
+            Ok(None) => {
+                debug!("{} failed to authenticate", pending.username);
+                return LoginStart::disconnect(&conn, "Failed to verify username!").await;
+            }
+            Err(e) => {
+                warn!("Couldn't authenticate {}: {}", pending.username, e);
+                return LoginStart::disconnect(
+                    &conn,
+                    "Authentication servers are down. Please try again later, sorry!",
+                )
+                .await;
+            }
+        };
+
+        let login = LoginStart {
+            username: profile.name.clone(),
+            uuid: profile.id.as_u128(),
+        };
+        login.join(conn_id, state, Some(profile)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_encryption_response() {
+        let data = vec![0x03, 0xAA, 0xBB, 0xCC, 0x02, 0x01, 0x02];
+
+        let packet = EncryptionResponse::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(packet.shared_secret, vec![0xAA, 0xBB, 0xCC]);
+        assert_eq!(packet.verify_token, vec![0x01, 0x02]);
+    }
+}
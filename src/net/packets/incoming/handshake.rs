@@ -1,22 +1,29 @@
-use ferrumc_codec::network_types::varint::VarInt;
+use tracing::debug;
 
 use ferrumc_macros::{packet, NetDecode};
 
+use crate::net::packets::incoming::login_start::LoginStart;
 use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::forwarding::{parse_legacy, LEGACY_FORWARDING_MISSING};
 use crate::net::State;
 use crate::state::GlobalState;
+use crate::utils::config::{get_global_config, ForwardingMode};
 use crate::utils::prelude::*;
 
 /// The first packet sent by the client to the server.
 ///
 /// This packet is used to negotiate the protocol version, server address, server port, and the next state.
+///
+/// Behind BungeeCord, the server address also carries the player's real address, UUID and skin.
 #[derive(NetDecode)]
 #[packet(packet_id = 0x00, state = "handshake")]
 pub struct Handshake {
-    pub protocol_version: VarInt,
+    #[decode(varint)]
+    pub protocol_version: i32,
     pub server_address: String,
     pub server_port: u16,
-    pub next_state: VarInt,
+    #[decode(varint)]
+    pub next_state: i32,
 }
 
 impl IncomingPacket for Handshake {
@@ -25,14 +32,26 @@ impl IncomingPacket for Handshake {
             return Err(Error::ConnectionNotFound(conn_id));
         };
 
-        let mut conn = conn.write().await;
+        {
+            let mut conn = conn.write().await;
 
-        conn.metadata.protocol_version = self.protocol_version.get_val();
-        conn.state = match self.next_state.get_val() {
-            1 => State::Status,
-            2 => State::Login,
-            s => return Err(Error::InvalidState(s)),
-        };
+            conn.metadata.protocol_version = self.protocol_version;
+            conn.state = match self.next_state {
+                1 => State::Status,
+                2 => State::Login,
+                s => return Err(Error::InvalidState(s)),
+            };
+        }
+
+        if self.next_state == 2 && get_global_config().forwarding.mode == ForwardingMode::Legacy {
+            match parse_legacy(&self.server_address) {
+                Ok(forwarded) => conn.write().await.metadata.forwarded = Some(forwarded),
+                Err(e) => {
+                    debug!("Rejected a login without BungeeCord forwarding: {}", e);
+                    return LoginStart::disconnect(&conn, LEGACY_FORWARDING_MISSING).await;
+                }
+            }
+        }
 
         Ok(())
     }
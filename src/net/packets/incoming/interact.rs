@@ -0,0 +1,236 @@
+use tracing::{debug, trace};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::incoming::use_item_on::Hand;
+use crate::net::packets::outgoing::set_passengers::SetPassengers;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast_to_observers;
+use crate::state::GlobalState;
+use crate::utils::components::entity_info::EntityInfo;
+use crate::utils::components::precise_position::PrecisePosition;
+use crate::utils::components::riding::Riding;
+use crate::utils::encoding::position::Position;
+use crate::world::reach::MAX_REACH_SQUARED;
+
+/// Sent when the player right or left clicks an entity.
+///
+/// Also known as Use Entity.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x10, state = "play")]
+pub struct Interact {
+    pub entity_id: VarInt,
+    pub action: InteractAction,
+    pub sneaking: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InteractAction {
+    /// A right click. Sent along with [InteractAction::InteractAt] for entities that care where
+    /// they were clicked, like armor stands.
+    Interact { hand: Hand },
+    /// A left click.
+    Attack,
+    /// A right click at a point on the entity, relative to its position.
+    InteractAt { target: (f32, f32, f32), hand: Hand },
+}
+
+impl NetDecode for InteractAction {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let action = VarInt::read(bytes).await?.get_val();
+        let action = match action {
+            0 => InteractAction::Interact {
+                hand: *Hand::net_decode(bytes).await?,
+            },
+            1 => InteractAction::Attack,
+            2 => InteractAction::InteractAt {
+                target: (
+                    *f32::net_decode(bytes).await?,
+                    *f32::net_decode(bytes).await?,
+                    *f32::net_decode(bytes).await?,
+                ),
+                hand: *Hand::net_decode(bytes).await?,
+            },
+            _ => {
+                return Err(Error::Generic(format!(
+                    "Invalid interact action: {}",
+                    action
+                )))
+            }
+        };
+        Ok(Box::new(action))
+    }
+}
+
+impl IncomingPacket for Interact {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("Interact packet received: {:?}", self);
+
+        // Only a plain right click with the main hand gets in a vehicle
+        let InteractAction::Interact {
+            hand: Hand::MainHand,
+        } = self.action
+        else {
+            return Ok(());
+        };
+        let vehicle_id = self.entity_id.get_val() as usize;
+        if !can_mount(&state, conn_id, vehicle_id).await {
+            return Ok(());
+        }
+
+        state
+            .world
+            .get_component_storage()
+            .insert(conn_id, Riding::new(vehicle_id));
+        debug!("Player {} got in vehicle {}", conn_id, vehicle_id);
+
+        let packet = SetPassengers::new(vehicle_id, &[conn_id]);
+        let conn = state.connections.get_connection(conn_id)?;
+        conn.read().await.send_packet(packet.clone()).await?;
+        broadcast_to_observers(&packet, &state, vehicle_id).await
+    }
+}
+
+/// Whether a player can get in a vehicle: it has to be something rideable within reach, and
+/// neither of them can be riding already.
+async fn can_mount(state: &GlobalState, player_id: usize, vehicle_id: usize) -> bool {
+    let rideable = state
+        .world
+        .get_component::<EntityInfo>(vehicle_id)
+        .await
+        .is_ok_and(|info| info.kind.is_rideable());
+    if !rideable || state.world.get_component::<Riding>(player_id).await.is_ok() {
+        return false;
+    }
+
+    let query = state.world.query::<&Riding>();
+    let taken = query
+        .iter()
+        .await
+        .any(|(_, riding)| riding.vehicle_id == vehicle_id);
+    if taken {
+        return false;
+    }
+
+    let (Some(player), Some(vehicle)) = (
+        position_of(state, player_id).await,
+        position_of(state, vehicle_id).await,
+    ) else {
+        return false;
+    };
+    let (dx, dy, dz) = (
+        player.x - vehicle.x,
+        player.y - vehicle.y,
+        player.z - vehicle.z,
+    );
+    dx * dx + dy * dy + dz * dz <= MAX_REACH_SQUARED
+}
+
+/// Where an entity is, in the middle of its block if only that is known.
+async fn position_of(state: &GlobalState, entity_id: usize) -> Option<PrecisePosition> {
+    if let Ok(precise) = state
+        .world
+        .get_component::<PrecisePosition>(entity_id)
+        .await
+    {
+        return Some(*precise);
+    }
+    let position = state
+        .world
+        .get_component::<Position>(entity_id)
+        .await
+        .ok()?;
+    Some(PrecisePosition::centered(&position))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::tests::connections::add_play_connection;
+    use crate::utils::components::entity_info::EntityKind;
+    use crate::utils::components::rotation::Rotation;
+    use crate::world::dimension::Dimension;
+
+    #[tokio::test]
+    async fn test_decode_interact_at() {
+        let mut data = vec![0x05, 0x02];
+        data.extend_from_slice(&0.5f32.to_be_bytes());
+        data.extend_from_slice(&1.0f32.to_be_bytes());
+        data.extend_from_slice(&(-0.25f32).to_be_bytes());
+        data.extend_from_slice(&[0x01, 0x01]);
+
+        let mut cursor = Cursor::new(data);
+        let packet = Interact::net_decode(&mut cursor).await.unwrap();
+        assert_eq!(packet.entity_id.get_val(), 5);
+        assert_eq!(
+            packet.action,
+            InteractAction::InteractAt {
+                target: (0.5, 1.0, -0.25),
+                hand: Hand::OffHand,
+            }
+        );
+        assert!(packet.sneaking);
+
+        let mut cursor = Cursor::new(vec![0x05, 0x01, 0x00]);
+        let packet = Interact::net_decode(&mut cursor).await.unwrap();
+        assert_eq!(packet.action, InteractAction::Attack);
+        assert!(!packet.sneaking);
+    }
+
+    #[tokio::test]
+    async fn test_right_clicking_a_boat_mounts_it() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let (player, _client) = add_play_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(player, PrecisePosition::new(0.5, 64.0, 0.5));
+        let boat = state
+            .spawn_entity(
+                EntityKind::Boat,
+                Dimension::Overworld,
+                Position::new(2, 64, 0),
+                Rotation::new(0.0, 0.0),
+            )
+            .await;
+        let far_boat = state
+            .spawn_entity(
+                EntityKind::Boat,
+                Dimension::Overworld,
+                Position::new(40, 64, 0),
+                Rotation::new(0.0, 0.0),
+            )
+            .await;
+
+        let interact = |entity_id: usize| Interact {
+            entity_id: VarInt::from(entity_id as i32),
+            action: InteractAction::Interact {
+                hand: Hand::MainHand,
+            },
+            sneaking: false,
+        };
+        interact(far_boat)
+            .handle(player, state.clone())
+            .await
+            .unwrap();
+        assert!(state.world.get_component::<Riding>(player).await.is_err());
+
+        interact(boat).handle(player, state.clone()).await.unwrap();
+        let riding = state.world.get_component::<Riding>(player).await.unwrap();
+        assert_eq!(riding.vehicle_id, boat);
+    }
+}
@@ -28,7 +28,12 @@ impl IncomingPacket for KeepAlivePacketIn {
 
         debug!("KeepAlive for player: {:?}", *keep_alive);
 
-        keep_alive.last_received = std::time::Instant::now();
+        if !keep_alive.answer(self.keep_alive_id, std::time::Instant::now()) {
+            debug!(
+                "Unexpected keep alive {} from {}",
+                self.keep_alive_id, player
+            );
+        }
 
         Ok(())
     }
@@ -0,0 +1,79 @@
+use tracing::debug;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::incoming::login_start::LoginStart;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::forwarding::{parse_modern, MODERN_FORWARDING_MISSING};
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+/// The client's answer to a
+/// [crate::net::packets::outgoing::login_query_request::LoginQueryRequest].
+///
+/// The only query the server sends is Velocity's forwarding query, so this carries on with the
+/// login as the player the proxy vouched for.
+#[derive(NetDecode)]
+#[packet(packet_id = 0x02, state = "login")]
+pub struct LoginQueryResponse {
+    #[decode(varint)]
+    pub message_id: i32,
+    /// False if the client doesn't know the channel, i.e. there's no proxy answering for it.
+    pub successful: bool,
+    #[decode(raw_bytes)]
+    pub data: Vec<u8>,
+}
+
+impl IncomingPacket for LoginQueryResponse {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        let conn = state.connections.get_connection(conn_id)?;
+        let pending = conn.write().await.metadata.pending_forwarding.take();
+        let Some(pending) = pending.filter(|pending| pending.message_id == self.message_id) else {
+            return Err(Error::Generic(format!(
+                "Got a login query response for message {} without asking for one",
+                self.message_id
+            )));
+        };
+
+        if !self.successful {
+            debug!("{} didn't connect through Velocity", pending.username);
+            return LoginStart::disconnect(&conn, MODERN_FORWARDING_MISSING).await;
+        }
+        let secret = get_global_config().forwarding.secret.as_bytes();
+        let forwarded = match parse_modern(&self.data, secret).await {
+            Ok(forwarded) => forwarded,
+            Err(e) => {
+                debug!("Rejected forwarding for {}: {}", pending.username, e);
+                return LoginStart::disconnect(&conn, "Unable to verify player details").await;
+            }
+        };
+
+        let profile = forwarded.profile(&pending.username);
+        conn.write().await.metadata.forwarded = Some(forwarded);
+        let login = LoginStart {
+            username: profile.name.clone(),
+            uuid: profile.id.as_u128(),
+        };
+        login.join(conn_id, state, Some(profile)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_login_query_response() {
+        let data = vec![0x07, 0x01, 0xAA, 0xBB];
+
+        let packet = LoginQueryResponse::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(packet.message_id, 7);
+        assert!(packet.successful);
+        assert_eq!(packet.data, vec![0xAA, 0xBB]);
+    }
+}
@@ -9,24 +9,50 @@ use uuid::Uuid;
 
 use crate::events::creation::dispatcher::EventDispatcherExt;
 use crate::events::world_events::PlayerJoinWorldEvent;
-use crate::net::packets::outgoing::default_spawn_position::DefaultSpawnPosition;
+use crate::net::packets::outgoing::commands::Commands;
+use crate::net::packets::outgoing::encryption_request::EncryptionRequest;
+use crate::net::packets::outgoing::feature_flags::FeatureFlags;
+use crate::net::packets::outgoing::initialize_world_border::InitializeWorldBorder;
 use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
-use crate::net::packets::outgoing::login_plugin_request::LoginPluginRequest;
+use crate::net::packets::outgoing::login_query_request::LoginQueryRequest;
 use crate::net::packets::outgoing::login_success::LoginSuccess;
+use crate::net::packets::outgoing::plugin_message::PluginMessage;
+use crate::net::packets::outgoing::resource_pack::ResourcePack;
+use crate::net::packets::outgoing::set_container_content::SetContainerContent;
+use crate::net::packets::outgoing::set_held_item::SetHeldItem;
 use crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition;
+use crate::net::packets::outgoing::update_recipes::UpdateRecipes;
+use crate::net::packets::outgoing::update_tags::UpdateTags;
 use crate::net::packets::{ConnectionId, IncomingPacket};
 use crate::net::systems::chunk_sender::ChunkSender;
+use crate::net::utils::encryption::{server_keys, PendingLogin};
+use crate::net::utils::forwarding::{
+    PendingForwarding, LEGACY_FORWARDING_MISSING, VELOCITY_CHANNEL, VELOCITY_FORWARDING_VERSION,
+};
 use crate::net::utils::packet_queue::PacketQueue;
 use crate::net::Connection;
 use crate::net::State::Play;
 use crate::state::GlobalState;
+use crate::utils::bans::unix_now;
+use crate::utils::components::entity_flags::EntityFlags;
+use crate::utils::components::entity_info::{EntityInfo, EntityKind};
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::game_profile::GameProfile;
+use crate::utils::components::health::Health;
+use crate::utils::components::inventory::Inventory;
 use crate::utils::components::keep_alive::KeepAlive;
 use crate::utils::components::player::Player;
+use crate::utils::components::plugin_channels::PluginChannels;
+use crate::utils::components::precise_position::PrecisePosition;
 use crate::utils::components::rotation::Rotation;
-use crate::utils::config::get_global_config;
+use crate::utils::components::visible_entities::VisibleEntities;
+use crate::utils::config::{get_global_config, ForwardingMode};
 use crate::utils::constants::init;
-use crate::utils::encoding::position::Position;
+use crate::utils::permissions::permission_level_of;
 use crate::utils::prelude::*;
+use crate::utils::text::TextComponent;
+use crate::world::dimension::Dimension;
+use crate::world::player_data::PlayerData;
 use ferrumc_macros::{packet, NetDecode};
 
 /// The login start packet is sent by the client to the server to start the login process.
@@ -34,10 +60,19 @@ use ferrumc_macros::{packet, NetDecode};
 /// Server responds with:
 /// [crate::net::packets::outgoing::set_compression::SetCompression],
 /// [crate::net::packets::outgoing::login_success::LoginSuccess],
-/// [crate::net::packets::outgoing::login_play::LoginPlay], and
+/// [crate::net::packets::outgoing::login_play::LoginPlay],
+/// [crate::net::packets::outgoing::feature_flags::FeatureFlags],
+/// [crate::net::packets::outgoing::update_recipes::UpdateRecipes],
+/// [crate::net::packets::outgoing::update_tags::UpdateTags], and
 /// [crate::net::packets::outgoing::default_spawn_position::DefaultSpawnPosition] packets in that order.
 /// No response is required from the client while these are being sent.
 ///
+/// In online mode the server first sends an
+/// [crate::net::packets::outgoing::encryption_request::EncryptionRequest], and the rest only
+/// happens once the client's
+/// [crate::net::packets::incoming::encryption_response::EncryptionResponse] checks out.
+/// Behind a proxy, the player's UUID, address and skin come from the proxy instead.
+///
 /// This is the final stage in the login process. The client is now in the play state.
 #[derive(NetDecode)]
 #[packet(packet_id = 0x00, state = "login")]
@@ -50,7 +85,7 @@ pub struct LoginStart {
 // The NBT encoded data for the dimension codec. Using flate_include cos the codec file is like 40kb
 #[cfg(not(test))]
 // flate!(pub static NBT_CODEC: [u8] from "./.etc/nbt_codec.nbt");
-const NBT_CODEC: &[u8] = include_bytes!("../../../../.etc/nbt_codec.nbt");
+const NBT_CODEC: &[u8] = crate::world::biome_registry::REGISTRY_CODEC;
 
 #[cfg(test)]
 const NBT_CODEC: &[u8] = &[0u8; 1];
@@ -59,9 +94,84 @@ impl IncomingPacket for LoginStart {
     async fn handle(mut self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
         self.username = self.username.trim().to_string();
 
+        // Behind a proxy, the proxy has already authenticated the player
+        match get_global_config().forwarding.mode {
+            ForwardingMode::None => {}
+            ForwardingMode::Legacy => {
+                let conn = state.connections.get_connection(conn_id)?;
+                let forwarded = conn.read().await.metadata.forwarded.clone();
+                let Some(forwarded) = forwarded else {
+                    return Self::disconnect(&conn, LEGACY_FORWARDING_MISSING).await;
+                };
+                let profile = forwarded.profile(&self.username);
+                self.uuid = profile.id.as_u128();
+                return self.join(conn_id, state, Some(profile)).await;
+            }
+            ForwardingMode::Modern => {
+                let conn = state.connections.get_connection(conn_id)?;
+                return Self::request_forwarding(&conn, self.username).await;
+            }
+        }
+
+        // In online mode the player has to prove they own the account first, the login carries
+        // on once they answer with an EncryptionResponse
+        if get_global_config().online_mode {
+            let conn = state.connections.get_connection(conn_id)?;
+            return Self::request_encryption(&conn, self.username).await;
+        }
+
+        // The UUID the client sent can be anything, so it's derived from the name like vanilla
+        // does, which keeps player data and permissions tied to the name across sessions
+        let mut profile =
+            GameProfile::offline(GameProfile::offline_uuid(&self.username), &self.username);
+        if get_global_config().offline_skins {
+            profile.properties = state.skins.textures(&self.username).await;
+        }
+        self.uuid = profile.id.as_u128();
+        self.join(conn_id, state, Some(profile)).await
+    }
+}
+
+impl LoginStart {
+    /// Finishes the login and sends the player into the world.
+    ///
+    /// `profile` is the profile the session servers verified or a proxy forwarded, or the offline
+    /// profile with the skin looked up for the player's name. Without one, the player gets a
+    /// profile without a skin.
+    pub async fn join(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+        profile: Option<GameProfile>,
+    ) -> Result<()> {
         let conn = state.connections.get_connection(conn_id)?;
         // let conn = conn.read().await;
 
+        let uuid = Uuid::from_u128(self.uuid);
+        let ip = conn.read().await.remote_ip().await;
+
+        if let Some(ban) = state.bans.find(uuid, &self.username, ip, unix_now()) {
+            debug!("{} is banned, disconnecting", self.username);
+            return Self::disconnect(&conn, ban.message()).await;
+        }
+
+        if !state.whitelist.is_allowed(uuid, &self.username) {
+            debug!("{} isn't whitelisted, disconnecting", self.username);
+            let message = &get_global_config().whitelist.kick_message;
+            return Self::disconnect(&conn, message.as_str()).await;
+        }
+
+        // Returning players pick up where they left off
+        let player_data = state.player_data.load_player(uuid).await;
+        let game_mode = player_data
+            .as_ref()
+            .and_then(PlayerData::game_mode)
+            .unwrap_or_default();
+        let dimension = player_data
+            .as_ref()
+            .map(PlayerData::dimension)
+            .unwrap_or_default();
+
         let mut packet_queue = PacketQueue::new();
 
         // Encryption logic here
@@ -70,29 +180,55 @@ impl IncomingPacket for LoginStart {
         self.send_set_compression(&mut packet_queue, conn.clone())
             .await?;
 
-        self.send_login_success(&mut packet_queue, &*conn.read().await)
+        // Offline players keep the name they logged in with and their offline UUID, so the client's
+        // own tab list entry and chat messages line up with what it was told here
+        let profile = profile
+            .unwrap_or_else(|| GameProfile::offline(Uuid::from_u128(self.uuid), &self.username));
+        self.send_login_success(&mut packet_queue, &profile, &*conn.read().await)
+            .await?;
+        self.send_login_play(&mut packet_queue, game_mode, dimension, &*conn.read().await)
+            .await?;
+        self.send_recipes_and_tags(&mut packet_queue, &*conn.read().await)
+            .await?;
+        self.send_commands(&state, &mut packet_queue, &*conn.read().await)
             .await?;
-        self.send_login_play(&mut packet_queue, &*conn.read().await)
+        self.send_spawn_position(&state, &mut packet_queue, &*conn.read().await)
             .await?;
-        self.send_spawn_position(&mut packet_queue, &*conn.read().await)
+        self.send_world_border(&state, dimension, &mut packet_queue, &*conn.read().await)
+            .await?;
+        self.send_time(&state, &mut packet_queue, &*conn.read().await)
+            .await?;
+        self.send_weather(&state, dimension, &mut packet_queue, &*conn.read().await)
             .await?;
 
         let data: i64 = random();
-        let mut keep_alive = KeepAlive::new(Instant::now(), Instant::now(), data);
+        let mut keep_alive = KeepAlive::new(Instant::now(), data);
         self.send_keep_alive(&mut packet_queue, &mut keep_alive, &*conn.read().await)
             .await?;
-        self.update_world_state(&*conn.read().await, keep_alive, state.clone())
-            .await?;
+        self.update_world_state(
+            &*conn.read().await,
+            keep_alive,
+            player_data.as_ref(),
+            profile,
+            state.clone(),
+        )
+        .await?;
 
         self.synchronize_player_position(state.clone(), &*conn.read().await, &mut packet_queue)
             .await?;
-
-        let packet = LoginPluginRequest::server_brand("🦀".repeat(100)).await;
-        // conn.send_packet(packet).await?;
-        packet_queue
-            .queue(packet, conn.read().await.metadata.compressed)
+        self.send_inventory(&state, &mut packet_queue, &*conn.read().await)
             .await?;
 
+        let compressed = conn.read().await.metadata.compressed;
+        let packet = PluginMessage::brand(&get_global_config().server_brand).await;
+        packet_queue.queue(packet, compressed).await?;
+        if let Some(packet) = state.channel_registration() {
+            packet_queue.queue(packet, compressed).await?;
+        }
+        if let Some(packet) = ResourcePack::from_config(&get_global_config().resource_pack) {
+            packet_queue.queue(packet, compressed).await?;
+        }
+
         let event = PlayerJoinWorldEvent::new(conn_id);
         state.dispatch_event(event).await;
 
@@ -107,16 +243,60 @@ impl IncomingPacket for LoginStart {
         // Drop connection to avoid deadlock with chunk sender since it also needs to write to the connection
         drop(conn);
 
+        state.add_to_tab_list(entity).await?;
+
         ChunkSender::send_chunks_to_player(state.clone(), entity).await?;
 
         Ok(())
     }
-}
 
-impl LoginStart {
+    /// Sends the client the server's public key, and remembers who's logging in until it answers.
+    async fn request_encryption(conn: &RwLock<Connection>, username: String) -> Result<()> {
+        let verify_token: [u8; 4] = random();
+        let request = EncryptionRequest::new(server_keys()?.public_key_der(), &verify_token);
+
+        let mut conn = conn.write().await;
+        conn.metadata.pending_login = Some(PendingLogin {
+            username,
+            verify_token,
+        });
+        conn.send_packet(request).await
+    }
+
+    /// Asks Velocity for the player's real UUID, address and skin. The login carries on once it
+    /// answers with a [crate::net::packets::incoming::login_query_response::LoginQueryResponse].
+    async fn request_forwarding(conn: &RwLock<Connection>, username: String) -> Result<()> {
+        let message_id: i32 = random();
+        let request = LoginQueryRequest::new(
+            message_id,
+            VELOCITY_CHANNEL,
+            vec![VELOCITY_FORWARDING_VERSION],
+        );
+
+        let mut conn = conn.write().await;
+        conn.metadata.pending_forwarding = Some(PendingForwarding {
+            message_id,
+            username,
+        });
+        conn.send_packet(request).await
+    }
+
+    /// Turns the player away before they join, showing them `reason`. The connection is dropped
+    /// once the packet being handled is done with.
+    pub async fn disconnect(
+        conn: &RwLock<Connection>,
+        reason: impl Into<TextComponent>,
+    ) -> Result<()> {
+        let mut conn = conn.write().await;
+        conn.send_disconnect(&reason.into()).await?;
+        conn.drop = true;
+        Ok(())
+    }
+
     async fn send_login_success(
         &self,
         packet_queue: &mut PacketQueue,
+        profile: &GameProfile,
         conn: &Connection,
     ) -> Result<()> {
         debug!("LoginStart packet received");
@@ -124,15 +304,7 @@ impl LoginStart {
         let uuid = Uuid::from_u128(self.uuid);
         debug!("UUID: {uuid}");
 
-        let namespace_uuid = Uuid::new_v5(&Uuid::NAMESPACE_URL, "OfflinePlayer".as_bytes());
-        let uuid = Uuid::new_v3(&namespace_uuid, self.username.as_bytes());
-
-        let response = LoginSuccess::new_auto(
-            uuid.as_bytes().into(),
-            "OfflinePlayer".to_string(),
-            VarInt::new(0),
-            vec![],
-        );
+        let response = LoginSuccess::from_profile(profile);
 
         packet_queue
             .queue(response, conn.metadata.compressed)
@@ -148,22 +320,27 @@ impl LoginStart {
     async fn send_login_play(
         &self,
         packet_queue: &mut PacketQueue,
+        game_mode: GameMode,
+        dimension: Dimension,
         conn: &Connection,
     ) -> Result<()> {
         let play_packet = crate::net::packets::outgoing::login_play::LoginPlay {
             packet_id: VarInt::from(0x28),
-            entity_id: 0,
+            entity_id: conn.id as i32,
             hardcore: false,
-            gamemode: 1,
+            gamemode: game_mode.id(),
             previous_gamemode: -1,
-            dimension_length: VarInt::new(1),
-            dimension_names: vec!["minecraft:overworld".to_string()],
+            dimension_length: VarInt::new(Dimension::ALL.len() as i32),
+            dimension_names: Dimension::ALL
+                .iter()
+                .map(|dimension| dimension.name().to_string())
+                .collect(),
             registry_codec: NBT_CODEC,
-            dimension_type: "minecraft:overworld".to_string(),
-            dimension_name: "minecraft:overworld".to_string(),
+            dimension_type: dimension.name().to_string(),
+            dimension_name: dimension.name().to_string(),
             seed_hash: 0,
-            max_players: VarInt::new(20),
-            view_distance: VarInt::new(10),
+            max_players: VarInt::new(get_global_config().max_players),
+            view_distance: VarInt::new(get_global_config().view_distance as i32),
             simulation_distance: VarInt::new(10),
             reduced_debug_info: false,
             enable_respawn_screen: true,
@@ -176,6 +353,9 @@ impl LoginStart {
         packet_queue
             .queue(play_packet, conn.metadata.compressed)
             .await?;
+        packet_queue
+            .queue(FeatureFlags::vanilla(), conn.metadata.compressed)
+            .await?;
         /*let mut cursor = std::io::Cursor::new(Vec::new());
         play_packet.net_encode(&mut cursor).await?;
         let play_packet = cursor.into_inner();
@@ -184,23 +364,97 @@ impl LoginStart {
         Ok(())
     }
 
+    async fn send_recipes_and_tags(
+        &self,
+        packet_queue: &mut PacketQueue,
+        conn: &Connection,
+    ) -> Result<()> {
+        packet_queue
+            .queue(UpdateRecipes::empty(), conn.metadata.compressed)
+            .await?;
+        packet_queue
+            .queue(UpdateTags::minimal(), conn.metadata.compressed)
+            .await?;
+        Ok(())
+    }
+
+    async fn send_commands(
+        &self,
+        state: &GlobalState,
+        packet_queue: &mut PacketQueue,
+        conn: &Connection,
+    ) -> Result<()> {
+        let level = permission_level_of(state, Uuid::from_u128(self.uuid), &self.username);
+        let tree = state.commands.read().unwrap().tree_for(level);
+        let commands = Commands::new(&tree);
+        packet_queue
+            .queue(commands, conn.metadata.compressed)
+            .await?;
+        Ok(())
+    }
+
     async fn send_spawn_position(
         &self,
+        state: &GlobalState,
         packet_queue: &mut PacketQueue,
         conn: &Connection,
     ) -> Result<()> {
-        let player_position = Position {
-            x: init::DEFAULT_SPAWN_X_POS,
-            y: init::DEFAULT_SPAWN_Y_POS,
-            z: init::DEFAULT_SPAWN_Z_POS,
-        };
-        let spawn_position = DefaultSpawnPosition::new_auto(player_position.clone(), 0.0);
+        let spawn_position = state.world_spawn.spawn_position_packet();
         packet_queue
             .queue(spawn_position, conn.metadata.compressed)
             .await?;
         Ok(())
     }
 
+    /// The border of the dimension the player is joining in.
+    async fn send_world_border(
+        &self,
+        state: &GlobalState,
+        dimension: Dimension,
+        packet_queue: &mut PacketQueue,
+        conn: &Connection,
+    ) -> Result<()> {
+        let border = state.world_border.settings(dimension);
+        packet_queue
+            .queue(
+                InitializeWorldBorder::new(&border),
+                conn.metadata.compressed,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Without it, the sky would be wrong until the next time sync.
+    async fn send_time(
+        &self,
+        state: &GlobalState,
+        packet_queue: &mut PacketQueue,
+        conn: &Connection,
+    ) -> Result<()> {
+        let do_daylight_cycle = get_global_config().gamerules.do_daylight_cycle;
+        packet_queue
+            .queue(
+                state.world_time.update_time_packet(do_daylight_cycle),
+                conn.metadata.compressed,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Players joining while it rains would otherwise see clear skies until it stops.
+    async fn send_weather(
+        &self,
+        state: &GlobalState,
+        dimension: Dimension,
+        packet_queue: &mut PacketQueue,
+        conn: &Connection,
+    ) -> Result<()> {
+        for packet in state.weather.state(dimension).join_packets() {
+            packet_queue.queue(packet, conn.metadata.compressed).await?;
+        }
+        Ok(())
+    }
+
     async fn send_keep_alive(
         &self,
         packet_queue: &mut PacketQueue,
@@ -219,27 +473,41 @@ impl LoginStart {
         &self,
         conn: &Connection,
         keep_alive: KeepAlive,
+        player_data: Option<&PlayerData>,
+        profile: GameProfile,
         state: GlobalState,
     ) -> Result<()> {
         let entity = conn.id;
 
         let component_storage = state.world.get_component_storage();
 
+        let position = player_data
+            .and_then(PlayerData::position)
+            .unwrap_or_else(|| PrecisePosition::corner(&state.world_spawn.position()));
+        let rotation = player_data
+            .and_then(PlayerData::rotation)
+            .unwrap_or_else(|| Rotation::new(state.world_spawn.angle(), init::DEFAULT_SPAWN_PITCH));
+        let game_mode = player_data
+            .and_then(PlayerData::game_mode)
+            .unwrap_or_default();
+        let dimension = player_data.map(PlayerData::dimension).unwrap_or_default();
+        let inventory = player_data.map(PlayerData::inventory).unwrap_or_default();
+
         component_storage
-            .insert(
-                entity,
-                Position::new(
-                    init::DEFAULT_SPAWN_X_POS,
-                    init::DEFAULT_SPAWN_Y_POS,
-                    init::DEFAULT_SPAWN_Z_POS,
-                ),
-            )
-            .insert(
-                entity,
-                Rotation::new(init::DEFAULT_SPAWN_YAW, init::DEFAULT_SPAWN_PITCH),
-            )
+            .insert(entity, position.block())
+            .insert(entity, position)
+            .insert(entity, rotation)
             .insert(entity, keep_alive)
-            .insert(entity, Player::new(self.uuid, self.username.clone()));
+            .insert(entity, EntityInfo::new(EntityKind::Player, self.uuid))
+            .insert(entity, EntityFlags::default())
+            .insert(entity, Health::default())
+            .insert(entity, inventory)
+            .insert(entity, VisibleEntities::default())
+            .insert(entity, PluginChannels::default())
+            .insert(entity, game_mode)
+            .insert(entity, dimension)
+            .insert(entity, Player::new(self.uuid, self.username.clone()))
+            .insert(entity, profile);
 
         Ok(())
     }
@@ -252,16 +520,39 @@ impl LoginStart {
         let entity = conn.id;
         let component_storage = state.world.get_component_storage();
 
-        let position = component_storage.get::<Position>(entity).await?;
+        let position = component_storage.get::<PrecisePosition>(entity).await?;
         let rotation = component_storage.get::<Rotation>(entity).await?;
 
-        let packet = SynchronizePlayerPosition::new(&position, &rotation);
+        let packet = SynchronizePlayerPosition::precise(&position, &rotation);
 
         packet_queue.queue(packet, conn.metadata.compressed).await?;
 
         Ok(())
     }
 
+    /// What the player was carrying when they left, and which hotbar slot they had selected.
+    async fn send_inventory(
+        &self,
+        state: &GlobalState,
+        packet_queue: &mut PacketQueue,
+        conn: &Connection,
+    ) -> Result<()> {
+        let inventory = state.world.get_component::<Inventory>(conn.id).await?;
+        packet_queue
+            .queue(
+                SetContainerContent::inventory(&inventory),
+                conn.metadata.compressed,
+            )
+            .await?;
+        packet_queue
+            .queue(
+                SetHeldItem::new(inventory.selected_slot),
+                conn.metadata.compressed,
+            )
+            .await?;
+        Ok(())
+    }
+
     async fn send_set_compression(
         &self,
         _packet_queue: &mut PacketQueue,
@@ -1,11 +1,37 @@
+pub mod change_recipe_book_settings;
+pub mod chat_command;
 pub mod chat_message;
+pub mod click_container;
 pub mod client_info;
+pub mod client_status;
+pub mod close_container;
+pub mod encryption_response;
 pub mod handshake;
+pub mod interact;
 pub mod keep_alive;
+pub mod login_query_response;
 pub mod login_start;
+pub mod move_vehicle;
+pub mod paddle_boat;
 pub mod ping;
+pub mod place_recipe;
 pub mod player_abilities;
+pub mod player_action;
+pub mod player_command;
+pub mod player_input;
+pub mod player_session;
+pub mod plugin_message;
+pub mod program_command_block;
+pub mod program_jigsaw_block;
+pub mod program_structure_block;
+pub mod rename_item;
+pub mod resource_pack_response;
+pub mod select_trade;
+pub mod set_creative_mode_slot;
+pub mod set_held_item;
 pub mod set_player_pos_and_rotate;
 pub mod set_player_position;
 pub mod set_player_rotation;
+pub mod set_seen_recipe;
 pub mod status;
+pub mod use_item_on;
@@ -0,0 +1,92 @@
+use tracing::trace;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::teleport_entity::TeleportEntity;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::systems::chunk_sender::ChunkSender;
+use crate::net::utils::broadcast::broadcast_except;
+use crate::state::GlobalState;
+use crate::utils::components::precise_position::PrecisePosition;
+use crate::utils::components::riding::Riding;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::prelude::*;
+
+/// Sent by the client instead of its own position while it's steering a vehicle.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x18, state = "play")]
+pub struct MoveVehicle {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl IncomingPacket for MoveVehicle {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("MoveVehicle packet received: {:?}", self);
+
+        let component_storage = state.world.get_component_storage();
+
+        let Ok(riding) = component_storage.get::<Riding>(conn_id).await else {
+            trace!("Player {} moved a vehicle without riding one", conn_id);
+            return Ok(());
+        };
+        let vehicle_id = riding.vehicle_id;
+        drop(riding);
+
+        let precise = PrecisePosition::new(self.x, self.y, self.z);
+        let position = precise.block();
+
+        // The passenger moves along with the vehicle
+        component_storage
+            .insert(vehicle_id, position.clone())
+            .insert(vehicle_id, Rotation::new(self.yaw, self.pitch))
+            .insert(conn_id, position.clone())
+            .insert(vehicle_id, precise)
+            .insert(conn_id, precise);
+
+        ChunkSender::send_chunks_to_player_if_needed(
+            state.clone(),
+            conn_id,
+            (position.x >> 4, position.z >> 4),
+        )
+        .await?;
+
+        let packet = TeleportEntity::new(
+            vehicle_id as i32,
+            (self.x, self.y, self.z),
+            self.yaw,
+            self.pitch,
+            false,
+        );
+        broadcast_except(&packet, &state, conn_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_move_vehicle() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&12.5f64.to_be_bytes());
+        data.extend_from_slice(&63.0f64.to_be_bytes());
+        data.extend_from_slice(&(-4.25f64).to_be_bytes());
+        data.extend_from_slice(&90.0f32.to_be_bytes());
+        data.extend_from_slice(&(-10.0f32).to_be_bytes());
+
+        let mut cursor = Cursor::new(data);
+        let packet = MoveVehicle::net_decode(&mut cursor).await.unwrap();
+        assert_eq!(packet.x, 12.5);
+        assert_eq!(packet.y, 63.0);
+        assert_eq!(packet.z, -4.25);
+        assert_eq!(packet.yaw, 90.0);
+        assert_eq!(packet.pitch, -10.0);
+        assert_eq!(cursor.position(), 32);
+    }
+}
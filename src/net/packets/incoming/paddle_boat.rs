@@ -0,0 +1,52 @@
+use tracing::trace;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::set_entity_metadata::{
+    MetadataEntry, MetadataValue, SetEntityMetadata,
+};
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast_to_observers;
+use crate::state::GlobalState;
+use crate::utils::components::riding::Riding;
+use crate::utils::prelude::*;
+
+/// Boat metadata index for whether the left paddle is turning.
+const LEFT_PADDLE_INDEX: u8 = 12;
+/// Boat metadata index for whether the right paddle is turning.
+const RIGHT_PADDLE_INDEX: u8 = 13;
+
+/// Sent while steering a boat, so other players can see its paddles move.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x19, state = "play")]
+pub struct PaddleBoat {
+    pub left_paddle_turning: bool,
+    pub right_paddle_turning: bool,
+}
+
+impl IncomingPacket for PaddleBoat {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("PaddleBoat packet received: {:?}", self);
+
+        let Ok(riding) = state.world.get_component::<Riding>(conn_id).await else {
+            return Ok(());
+        };
+        let vehicle_id = riding.vehicle_id;
+        drop(riding);
+
+        let packet = SetEntityMetadata::new(
+            vehicle_id as i32,
+            vec![
+                MetadataEntry::new(
+                    LEFT_PADDLE_INDEX,
+                    MetadataValue::Boolean(self.left_paddle_turning),
+                ),
+                MetadataEntry::new(
+                    RIGHT_PADDLE_INDEX,
+                    MetadataValue::Boolean(self.right_paddle_turning),
+                ),
+            ],
+        );
+        broadcast_to_observers(&packet, &state, vehicle_id).await
+    }
+}
@@ -42,6 +42,6 @@ impl IncomingPacket for Ping {
         .await
         .map_err(|e| e.into())*/
 
-        conn.send_packet(response).await
+        conn.send_urgent_packet(response).await
     }
 }
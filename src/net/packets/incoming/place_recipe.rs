@@ -0,0 +1,52 @@
+use tracing::debug;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+/// Sent when the player clicks a recipe in the recipe book, asking for its ingredients to be
+/// moved into the crafting grid.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x1B, state = "play")]
+pub struct PlaceRecipe {
+    pub window_id: i8,
+    /// The recipe's identifier, e.g. `minecraft:crafting_table`.
+    pub recipe: String,
+    /// Shift clicking fills the grid with as many sets of ingredients as possible.
+    pub make_all: bool,
+}
+
+impl IncomingPacket for PlaceRecipe {
+    async fn handle(self, conn_id: ConnectionId, _state: GlobalState) -> Result<()> {
+        // There's no crafting yet, so there's nothing to move the ingredients into
+        debug!(
+            "Player {} wants to place recipe {} in window {} (make all: {})",
+            conn_id, self.recipe, self.window_id, self.make_all
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_place_recipe() {
+        let recipe = b"minecraft:crafting_table";
+        let mut data = vec![0x01, recipe.len() as u8];
+        data.extend_from_slice(recipe);
+        data.push(0x01);
+
+        let mut cursor = Cursor::new(data);
+        let packet = PlaceRecipe::net_decode(&mut cursor).await.unwrap();
+        assert_eq!(packet.window_id, 1);
+        assert_eq!(packet.recipe, "minecraft:crafting_table");
+        assert!(packet.make_all);
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+    }
+}
@@ -0,0 +1,300 @@
+use std::time::Duration;
+
+use tracing::{debug, trace};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::events::creation::dispatcher::EventDispatcherExt;
+use crate::events::world_events::BlockBreakEvent;
+use crate::net::packets::outgoing::block_update::BlockUpdate;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::systems::game_loop::TICK_DURATION;
+use crate::state::GlobalState;
+use crate::utils::components::digging::Digging;
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::encoding::position::Position;
+use crate::world::block_changes::BlockChange;
+use crate::world::chunk_format::Palette;
+use crate::world::hardness::hardness;
+use crate::world::reach::in_reach;
+
+/// How far through digging a block players need to be for it to break. Like vanilla, this leaves
+/// some room for lag between the client and the server.
+pub const MIN_DIG_PROGRESS: f32 = 0.7;
+
+/// Tells the player's client what the block really is, after it changed the block itself in a
+/// way the server didn't go along with.
+pub async fn resend_block(
+    state: &GlobalState,
+    conn_id: ConnectionId,
+    block: &Position,
+    block_state: i32,
+) -> crate::utils::prelude::Result<()> {
+    let conn = state.connections.get_connection(conn_id)?;
+    let conn = conn.read().await;
+    conn.send_packet(BlockUpdate::new(block.clone(), block_state))
+        .await
+}
+
+/// Sent when the player starts, stops or finishes digging a block, as well as for dropping items
+/// and swapping them to the off hand.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x1D, state = "play")]
+pub struct PlayerAction {
+    pub status: PlayerActionStatus,
+    pub location: Position,
+    /// The face of the block that was hit.
+    pub face: i8,
+    /// Acknowledged once the action has been dealt with.
+    pub sequence: VarInt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerActionStatus {
+    StartedDigging,
+    CancelledDigging,
+    FinishedDigging,
+    DropItemStack,
+    DropItem,
+    /// Also finishes eating.
+    ShootArrow,
+    SwapItemInHand,
+}
+
+impl NetDecode for PlayerActionStatus {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let status = VarInt::read(bytes).await?.get_val();
+        match status {
+            0 => Ok(Box::new(PlayerActionStatus::StartedDigging)),
+            1 => Ok(Box::new(PlayerActionStatus::CancelledDigging)),
+            2 => Ok(Box::new(PlayerActionStatus::FinishedDigging)),
+            3 => Ok(Box::new(PlayerActionStatus::DropItemStack)),
+            4 => Ok(Box::new(PlayerActionStatus::DropItem)),
+            5 => Ok(Box::new(PlayerActionStatus::ShootArrow)),
+            6 => Ok(Box::new(PlayerActionStatus::SwapItemInHand)),
+            _ => Err(Error::Generic(format!(
+                "Invalid player action status: {}",
+                status
+            ))),
+        }
+    }
+}
+
+impl IncomingPacket for PlayerAction {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("PlayerAction packet received: {:?}", self);
+
+        match self.status {
+            PlayerActionStatus::StartedDigging => self.start_digging(conn_id, &state).await?,
+            PlayerActionStatus::CancelledDigging => {
+                // They might not have been digging anything that takes time
+                let _ = state
+                    .world
+                    .get_component_storage()
+                    .remove::<Digging>(conn_id);
+            }
+            PlayerActionStatus::FinishedDigging => self.finish_digging(conn_id, &state).await?,
+            _ => {
+                trace!("Player action {:?} isn't handled yet", self.status);
+                return Ok(());
+            }
+        }
+
+        // Acknowledged with the tick's block changes, see PendingBlockChanges
+        state
+            .pending_block_changes
+            .acknowledge(conn_id, self.sequence.get_val());
+        Ok(())
+    }
+}
+
+/// How long it takes to dig through a block by hand. Creative players break everything straight
+/// away, and blocks that can't be broken take forever.
+pub fn dig_time(block_state: i32, game_mode: GameMode) -> Duration {
+    if game_mode == GameMode::Creative {
+        return Duration::ZERO;
+    }
+    let Some(block) = Palette::from_block_id(block_state) else {
+        return Duration::ZERO;
+    };
+    match hardness(&block) {
+        Some(hardness) => TICK_DURATION * hardness.ticks(),
+        None => Duration::MAX,
+    }
+}
+
+impl PlayerAction {
+    async fn start_digging(
+        &self,
+        conn_id: ConnectionId,
+        state: &GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        let game_mode = *state.world.get_component::<GameMode>(conn_id).await?;
+        let dimension = state.dimension_of(conn_id).await;
+        let block_state = state.block_at(dimension, &self.location).await?;
+        if block_state == 0 {
+            return Ok(());
+        }
+        if matches!(game_mode, GameMode::Adventure | GameMode::Spectator)
+            || !in_reach(state, conn_id, &self.location).await?
+        {
+            debug!(
+                "Player {} can't break the block at {}",
+                conn_id, self.location
+            );
+            return resend_block(state, conn_id, &self.location, block_state).await;
+        }
+
+        if dig_time(block_state, game_mode).is_zero() {
+            return self.break_block(conn_id, state, block_state).await;
+        }
+        state
+            .world
+            .get_component_storage()
+            .insert(conn_id, Digging::new(self.location.clone(), block_state));
+        Ok(())
+    }
+
+    async fn finish_digging(
+        &self,
+        conn_id: ConnectionId,
+        state: &GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        let component_storage = state.world.get_component_storage();
+        let digging = component_storage
+            .get::<Digging>(conn_id)
+            .await
+            .map(|digging| digging.clone())
+            .ok();
+        let _ = component_storage.remove::<Digging>(conn_id);
+
+        let game_mode = *state.world.get_component::<GameMode>(conn_id).await?;
+        let dimension = state.dimension_of(conn_id).await;
+        let block_state = state.block_at(dimension, &self.location).await?;
+        let done = digging.is_some_and(|digging| {
+            digging.position == self.location
+                && digging.block_state == block_state
+                && digging.started.elapsed().div_f32(MIN_DIG_PROGRESS)
+                    >= dig_time(block_state, game_mode)
+        });
+        if !done {
+            debug!(
+                "Player {} finished digging the block at {} too early",
+                conn_id, self.location
+            );
+            return resend_block(state, conn_id, &self.location, block_state).await;
+        }
+        self.break_block(conn_id, state, block_state).await
+    }
+
+    async fn break_block(
+        &self,
+        conn_id: ConnectionId,
+        state: &GlobalState,
+        block_state: i32,
+    ) -> crate::utils::prelude::Result<()> {
+        let event = BlockBreakEvent::new(conn_id, self.location.clone(), block_state);
+        if !state.dispatch_cancellable_event(event).await {
+            return resend_block(state, conn_id, &self.location, block_state).await;
+        }
+
+        let dimension = state.dimension_of(conn_id).await;
+        state.block_entities.remove(dimension, &self.location);
+        state
+            .set_blocks(
+                dimension,
+                &[BlockChange {
+                    position: self.location.clone(),
+                    block_state: 0,
+                }],
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::tests::connections::add_play_connection;
+    use crate::world::block_entities::{
+        BlockEntityData, StructureBlock, StructureBlockMode, StructureMirror, StructureRotation,
+    };
+    use crate::world::dimension::Dimension;
+
+    #[tokio::test]
+    async fn test_decode_finished_digging() {
+        // Finished digging, at (1, 64, -1), top face, sequence 7
+        let mut data = vec![0x02];
+        data.extend_from_slice(&((1u64 << 38) | (0x3FFFFFF << 12) | 64).to_be_bytes());
+        data.extend_from_slice(&[0x01, 0x07]);
+        let packet = PlayerAction::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(packet.status, PlayerActionStatus::FinishedDigging);
+        assert_eq!(packet.location, Position::new(1, 64, -1));
+        assert_eq!(packet.face, 1);
+        assert_eq!(packet.sequence.get_val(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_breaking_leaves_other_dimensions_block_entities() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let (player, _client) = add_play_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(player, Dimension::Nether);
+
+        let location = Position::new(1, 64, -1);
+        let structure_block = BlockEntityData::StructureBlock(StructureBlock {
+            name: "minecraft:test".to_string(),
+            mode: StructureBlockMode::Save,
+            offset: (0, 1, 0),
+            size: (4, 4, 4),
+            mirror: StructureMirror::None,
+            rotation: StructureRotation::None,
+            metadata: String::new(),
+            integrity: 1.0,
+            seed: 0,
+            ignore_entities: true,
+            show_air: false,
+            show_bounding_box: true,
+        });
+        state.block_entities.insert(
+            Dimension::Overworld,
+            location.clone(),
+            structure_block.clone(),
+        );
+        state
+            .block_entities
+            .insert(Dimension::Nether, location.clone(), structure_block.clone());
+
+        let action = PlayerAction {
+            status: PlayerActionStatus::StartedDigging,
+            location: location.clone(),
+            face: 1,
+            sequence: VarInt::from(1),
+        };
+        action.break_block(player, &state, 1).await.unwrap();
+
+        assert_eq!(
+            state.block_entities.get(Dimension::Overworld, &location),
+            Some(structure_block)
+        );
+        assert_eq!(state.block_entities.get(Dimension::Nether, &location), None);
+    }
+}
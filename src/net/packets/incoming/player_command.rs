@@ -0,0 +1,193 @@
+use tracing::trace;
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::set_entity_metadata::SetEntityMetadata;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast_to_observers;
+use crate::state::GlobalState;
+use crate::utils::components::entity_flags::EntityFlags;
+
+/// Sent by the client when the player starts or stops sneaking or sprinting, along with a few
+/// other actions like leaving a bed.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x1E, state = "play")]
+pub struct PlayerCommand {
+    pub entity_id: VarInt,
+    pub action: PlayerCommandAction,
+    /// Only used when jumping with a horse, ranges from 0 to 100.
+    pub jump_boost: VarInt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerCommandAction {
+    StartSneaking,
+    StopSneaking,
+    LeaveBed,
+    StartSprinting,
+    StopSprinting,
+    StartHorseJump,
+    StopHorseJump,
+    OpenVehicleInventory,
+    StartFlyingWithElytra,
+}
+
+impl NetDecode for PlayerCommandAction {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let action = VarInt::read(bytes).await?.get_val();
+        let action = match action {
+            0 => PlayerCommandAction::StartSneaking,
+            1 => PlayerCommandAction::StopSneaking,
+            2 => PlayerCommandAction::LeaveBed,
+            3 => PlayerCommandAction::StartSprinting,
+            4 => PlayerCommandAction::StopSprinting,
+            5 => PlayerCommandAction::StartHorseJump,
+            6 => PlayerCommandAction::StopHorseJump,
+            7 => PlayerCommandAction::OpenVehicleInventory,
+            8 => PlayerCommandAction::StartFlyingWithElytra,
+            _ => {
+                return Err(Error::Generic(format!(
+                    "Invalid player command action: {}",
+                    action
+                )))
+            }
+        };
+        Ok(Box::new(action))
+    }
+}
+
+impl PlayerCommandAction {
+    /// Applies the action to the player's entity flags. Returns whether the flags changed, and
+    /// other players need to be told about it.
+    pub fn apply(self, flags: &mut EntityFlags) -> bool {
+        match self {
+            PlayerCommandAction::StartSneaking => flags.set(EntityFlags::CROUCHING, true),
+            PlayerCommandAction::StopSneaking => flags.set(EntityFlags::CROUCHING, false),
+            PlayerCommandAction::StartSprinting => flags.set(EntityFlags::SPRINTING, true),
+            PlayerCommandAction::StopSprinting => flags.set(EntityFlags::SPRINTING, false),
+            _ => false,
+        }
+    }
+}
+
+impl IncomingPacket for PlayerCommand {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("PlayerCommand packet received: {:?}", self.action);
+
+        let metadata = {
+            let component_storage = state.world.get_component_storage();
+            let mut flags = component_storage.get_mut::<EntityFlags>(conn_id).await?;
+            if !self.action.apply(&mut flags) {
+                return Ok(());
+            }
+            SetEntityMetadata::entity_flags(conn_id as i32, &flags)
+        };
+
+        broadcast_to_observers(&metadata, &state, conn_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::tests::connections::add_play_connection;
+    use crate::utils::components::entity_flags::Pose;
+    use crate::utils::components::visible_entities::VisibleEntities;
+
+    #[tokio::test]
+    async fn test_start_sneaking_sets_crouching_bit() {
+        // Entity id, action (start sneaking), jump boost
+        let mut data = Cursor::new(vec![0x07, 0x00, 0x00]);
+        let command = PlayerCommand::net_decode(&mut data).await.unwrap();
+        assert_eq!(command.action, PlayerCommandAction::StartSneaking);
+
+        let mut flags = EntityFlags::default();
+        assert!(command.action.apply(&mut flags));
+        assert!(flags.is_sneaking());
+        // Sneaking twice doesn't need another broadcast
+        assert!(!command.action.apply(&mut flags));
+
+        let packet = SetEntityMetadata::entity_flags(7, &flags);
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        // Length, packet id, entity id,
+        // flags (index 0, byte, crouching), pose (index 6, pose, sneaking), terminator
+        assert_eq!(
+            buffer,
+            vec![0x09, 0x52, 0x07, 0x00, 0x00, 0x02, 0x06, 0x14, 0x05, 0xFF]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metadata_only_goes_to_observers() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let (sneaker, _sneaker_client) = add_play_connection(&state).await;
+        let (observer, mut observer_client) = add_play_connection(&state).await;
+        let (_, mut far_away_client) = add_play_connection(&state).await;
+        let storage = state.world.get_component_storage();
+        storage.insert(sneaker, EntityFlags::default());
+        let mut visible = VisibleEntities::default();
+        visible.update(HashMap::from([(sneaker, 1)]));
+        storage.insert(observer, visible);
+
+        let command = PlayerCommand {
+            entity_id: VarInt::from(sneaker as i32),
+            action: PlayerCommandAction::StartSneaking,
+            jump_boost: VarInt::from(0),
+        };
+        command.handle(sneaker, state.clone()).await.unwrap();
+
+        let mut flags = EntityFlags::default();
+        PlayerCommandAction::StartSneaking.apply(&mut flags);
+        let mut expected = Vec::new();
+        SetEntityMetadata::entity_flags(sneaker as i32, &flags)
+            .net_encode(&mut expected, &EncodeOption::Default)
+            .await
+            .unwrap();
+        let mut received = vec![0u8; expected.len()];
+        observer_client.read_exact(&mut received).await.unwrap();
+        assert_eq!(received, expected);
+
+        let mut byte = [0u8];
+        let nothing = tokio::time::timeout(
+            Duration::from_millis(100),
+            far_away_client.read_exact(&mut byte),
+        )
+        .await;
+        assert!(nothing.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sprinting_keeps_standing_pose() {
+        let mut flags = EntityFlags::default();
+        assert!(PlayerCommandAction::StartSprinting.apply(&mut flags));
+        assert!(flags.is_sprinting());
+        assert_eq!(flags.pose(), Pose::Standing);
+
+        assert!(PlayerCommandAction::StopSprinting.apply(&mut flags));
+        assert_eq!(flags.flags, 0);
+    }
+}
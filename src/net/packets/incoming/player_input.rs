@@ -0,0 +1,62 @@
+use tracing::{debug, trace};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::outgoing::set_passengers::SetPassengers;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::net::utils::broadcast::broadcast_to_observers;
+use crate::state::GlobalState;
+use crate::utils::components::riding::Riding;
+use crate::utils::prelude::*;
+
+/// Sent while riding something, with the movement keys the player is holding.
+///
+/// Also known as Steer Vehicle.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x1F, state = "play")]
+pub struct PlayerInput {
+    /// Positive to the left of the player.
+    pub sideways: f32,
+    /// Positive forward.
+    pub forward: f32,
+    pub flags: u8,
+}
+
+impl PlayerInput {
+    pub const JUMP: u8 = 0x01;
+    pub const UNMOUNT: u8 = 0x02;
+
+    pub fn is_jumping(&self) -> bool {
+        self.flags & Self::JUMP != 0
+    }
+
+    pub fn is_unmounting(&self) -> bool {
+        self.flags & Self::UNMOUNT != 0
+    }
+}
+
+impl IncomingPacket for PlayerInput {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("PlayerInput packet received: {:?}", self);
+
+        if !self.is_unmounting() {
+            return Ok(());
+        }
+        let Ok(riding) = state.world.get_component::<Riding>(conn_id).await else {
+            return Ok(());
+        };
+        let vehicle_id = riding.vehicle_id;
+        drop(riding);
+
+        state
+            .world
+            .get_component_storage()
+            .remove::<Riding>(conn_id)?;
+        debug!("Player {} got out of vehicle {}", conn_id, vehicle_id);
+
+        let packet = SetPassengers::new(vehicle_id, &[]);
+        let conn = state.connections.get_connection(conn_id)?;
+        conn.read().await.send_packet(packet.clone()).await?;
+        broadcast_to_observers(&packet, &state, vehicle_id).await
+    }
+}
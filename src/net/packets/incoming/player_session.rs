@@ -0,0 +1,66 @@
+use tracing::debug;
+
+use ferrumc_macros::{packet, Component, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+
+/// The player session packet is sent by 1.19.1+ clients to set up a chat session for signed chat.
+///
+/// The key isn't verified against Mojang's signature yet, it's only stored so chat from these
+/// clients is accepted.
+#[derive(NetDecode, Component, Clone, Debug)]
+#[packet(packet_id = 0x06, state = "play")]
+pub struct PlayerSession {
+    pub session_id: u128,
+    /// When the key expires, in milliseconds since the epoch.
+    pub expires_at: i64,
+    /// DER encoded RSA public key.
+    pub public_key: Vec<u8>,
+    /// Mojang's signature over the player's UUID, the expiry and the key.
+    pub key_signature: Vec<u8>,
+}
+
+impl IncomingPacket for PlayerSession {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        debug!(
+            "PlayerSession packet received, session id: {}",
+            uuid::Uuid::from_u128(self.session_id)
+        );
+
+        // PlayerSession is a packet & also a component.
+        state.world.get_component_storage().insert(conn_id, self);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_player_session() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x0123456789abcdef_fedcba9876543210u128.to_be_bytes());
+        data.extend_from_slice(&1_700_000_000_000i64.to_be_bytes());
+        // Public key
+        data.extend_from_slice(&[0x03, 0x30, 0x82, 0x01]);
+        // Key signature
+        data.extend_from_slice(&[0x02, 0xAA, 0xBB]);
+
+        let packet = PlayerSession::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(packet.session_id, 0x0123456789abcdef_fedcba9876543210);
+        assert_eq!(packet.expires_at, 1_700_000_000_000);
+        assert_eq!(packet.public_key, vec![0x30, 0x82, 0x01]);
+        assert_eq!(packet.key_signature, vec![0xAA, 0xBB]);
+    }
+}
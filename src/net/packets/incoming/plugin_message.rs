@@ -0,0 +1,24 @@
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+/// Data a client sent on a plugin channel, like its brand or a mod's own messages. Passed on to
+/// whatever is registered for the channel, see [crate::state::ServerState::register_channel].
+#[derive(NetDecode)]
+#[packet(packet_id = 0x0D, state = "play")]
+pub struct PluginMessage {
+    pub channel: String,
+    /// The rest of the packet, in whatever format the channel uses.
+    #[decode(raw_bytes)]
+    pub data: Vec<u8>,
+}
+
+impl IncomingPacket for PluginMessage {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        state
+            .handle_plugin_message(conn_id, self.channel, self.data)
+            .await
+    }
+}
@@ -0,0 +1,104 @@
+use tracing::debug;
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::encoding::position::Position;
+use crate::utils::permissions::can_program_block;
+use crate::world::block_entities::{BlockEntityData, CommandBlock, CommandBlockMode};
+
+const FLAG_TRACK_OUTPUT: u8 = 0x01;
+const FLAG_CONDITIONAL: u8 = 0x02;
+const FLAG_AUTOMATIC: u8 = 0x04;
+
+/// The blocks whose block entity the packet edits.
+const COMMAND_BLOCKS: &[&str] = &[
+    "minecraft:command_block",
+    "minecraft:chain_command_block",
+    "minecraft:repeating_command_block",
+];
+
+/// Sent when a player edits a command block. Only operators are allowed to do this.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x29, state = "play")]
+pub struct ProgramCommandBlock {
+    pub location: Position,
+    pub command: String,
+    /// 0: sequence, 1: auto, 2: redstone
+    pub mode: VarInt,
+    pub flags: u8,
+}
+
+impl IncomingPacket for ProgramCommandBlock {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        if !can_program_block(
+            &state,
+            conn_id,
+            &self.location,
+            "command block",
+            COMMAND_BLOCKS,
+        )
+        .await?
+        {
+            return Ok(());
+        }
+
+        let command_block = CommandBlock {
+            command: self.command,
+            mode: CommandBlockMode::try_from(self.mode.get_val())?,
+            track_output: self.flags & FLAG_TRACK_OUTPUT != 0,
+            conditional: self.flags & FLAG_CONDITIONAL != 0,
+            automatic: self.flags & FLAG_AUTOMATIC != 0,
+        };
+
+        debug!(
+            "Updating command block at {}: {:?}",
+            self.location, command_block
+        );
+
+        let dimension = state.dimension_of(conn_id).await;
+        state.block_entities.insert(
+            dimension,
+            self.location,
+            BlockEntityData::CommandBlock(command_block),
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_program_command_block() {
+        let mut data = Vec::new();
+        // (1, 64, -1)
+        let position = ((1u64 & 0x3FFFFFF) << 38) | ((-1i64 as u64 & 0x3FFFFFF) << 12) | 64;
+        data.extend_from_slice(&position.to_be_bytes());
+        data.push(9);
+        data.extend_from_slice(b"/say hi!!");
+        // Redstone mode, conditional + automatic
+        data.extend_from_slice(&[0x02, 0x06]);
+
+        let packet = ProgramCommandBlock::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(packet.location, Position::new(1, 64, -1));
+        assert_eq!(packet.command, "/say hi!!");
+        assert_eq!(
+            CommandBlockMode::try_from(packet.mode.get_val()).unwrap(),
+            CommandBlockMode::Redstone
+        );
+        assert_eq!(packet.flags, FLAG_CONDITIONAL | FLAG_AUTOMATIC);
+    }
+}
@@ -0,0 +1,66 @@
+use tracing::debug;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::encoding::position::Position;
+use crate::utils::permissions::can_program_block;
+use crate::world::block_entities::{BlockEntityData, JigsawBlock};
+
+/// The blocks whose block entity the packet edits.
+const JIGSAW_BLOCKS: &[&str] = &["minecraft:jigsaw"];
+
+/// Sent when a player edits a jigsaw block. Only operators are allowed to do this.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x2C, state = "play")]
+pub struct ProgramJigsawBlock {
+    pub location: Position,
+    pub name: String,
+    pub target: String,
+    pub pool: String,
+    pub final_state: String,
+    pub joint_type: String,
+}
+
+impl IncomingPacket for ProgramJigsawBlock {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        if !can_program_block(
+            &state,
+            conn_id,
+            &self.location,
+            "jigsaw block",
+            JIGSAW_BLOCKS,
+        )
+        .await?
+        {
+            return Ok(());
+        }
+
+        let jigsaw_block = JigsawBlock {
+            name: self.name,
+            target: self.target,
+            pool: self.pool,
+            final_state: self.final_state,
+            joint_type: self.joint_type,
+        };
+
+        debug!(
+            "Updating jigsaw block at {}: {:?}",
+            self.location, jigsaw_block
+        );
+
+        let dimension = state.dimension_of(conn_id).await;
+        state.block_entities.insert(
+            dimension,
+            self.location,
+            BlockEntityData::JigsawBlock(jigsaw_block),
+        );
+
+        Ok(())
+    }
+}
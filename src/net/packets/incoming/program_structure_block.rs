@@ -0,0 +1,161 @@
+use tracing::debug;
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::network_types::varlong::Varlong;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::encoding::position::Position;
+use crate::utils::permissions::can_program_block;
+use crate::world::block_entities::{
+    BlockEntityData, StructureBlock, StructureBlockMode, StructureMirror, StructureRotation,
+};
+
+const FLAG_IGNORE_ENTITIES: u8 = 0x01;
+const FLAG_SHOW_AIR: u8 = 0x02;
+const FLAG_SHOW_BOUNDING_BOX: u8 = 0x04;
+
+/// The blocks whose block entity the packet edits.
+const STRUCTURE_BLOCKS: &[&str] = &["minecraft:structure_block"];
+
+/// Sent when a player edits a structure block, or presses one of its buttons. Only operators are
+/// allowed to do this.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x2D, state = "play")]
+pub struct ProgramStructureBlock {
+    pub location: Position,
+    /// 0: just update the settings, 1: save the structure, 2: load the structure, 3: detect size
+    pub action: VarInt,
+    /// 0: save, 1: load, 2: corner, 3: data
+    pub mode: VarInt,
+    pub name: String,
+    pub offset_x: i8,
+    pub offset_y: i8,
+    pub offset_z: i8,
+    pub size_x: i8,
+    pub size_y: i8,
+    pub size_z: i8,
+    /// 0: none, 1: left-right, 2: front-back
+    pub mirror: VarInt,
+    /// 0: none, 1: clockwise 90°, 2: clockwise 180°, 3: counter-clockwise 90°
+    pub rotation: VarInt,
+    pub metadata: String,
+    pub integrity: f32,
+    pub seed: Varlong,
+    pub flags: u8,
+}
+
+pub const ACTION_UPDATE: i32 = 0;
+pub const ACTION_SAVE: i32 = 1;
+
+impl IncomingPacket for ProgramStructureBlock {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        if !can_program_block(
+            &state,
+            conn_id,
+            &self.location,
+            "structure block",
+            STRUCTURE_BLOCKS,
+        )
+        .await?
+        {
+            return Ok(());
+        }
+
+        let structure_block = StructureBlock {
+            name: self.name,
+            mode: StructureBlockMode::try_from(self.mode.get_val())?,
+            offset: (self.offset_x, self.offset_y, self.offset_z),
+            size: (self.size_x, self.size_y, self.size_z),
+            mirror: StructureMirror::try_from(self.mirror.get_val())?,
+            rotation: StructureRotation::try_from(self.rotation.get_val())?,
+            metadata: self.metadata,
+            integrity: self.integrity.clamp(0.0, 1.0),
+            seed: self.seed.0,
+            ignore_entities: self.flags & FLAG_IGNORE_ENTITIES != 0,
+            show_air: self.flags & FLAG_SHOW_AIR != 0,
+            show_bounding_box: self.flags & FLAG_SHOW_BOUNDING_BOX != 0,
+        };
+
+        debug!(
+            "Updating structure block at {}: {:?}",
+            self.location, structure_block
+        );
+        if self.action.get_val() != ACTION_UPDATE {
+            // The settings are still kept, only the action itself is skipped
+            debug!(
+                "Structure block action {} isn't supported yet",
+                self.action.get_val()
+            );
+        }
+
+        let dimension = state.dimension_of(conn_id).await;
+        state.block_entities.insert(
+            dimension,
+            self.location,
+            BlockEntityData::StructureBlock(structure_block),
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_structure_block_save() {
+        let mut data = Vec::new();
+        // (10, 64, 20)
+        let position = ((10u64 & 0x3FFFFFF) << 38) | ((20u64 & 0x3FFFFFF) << 12) | 64;
+        data.extend_from_slice(&position.to_be_bytes());
+        // Save action, in save mode
+        data.extend_from_slice(&[0x01, 0x00]);
+        data.push(14);
+        data.extend_from_slice(b"minecraft:hut1");
+        // Offset, then size
+        data.extend_from_slice(&[0x00, 0x01, 0x00, 0x05, 0x04, 0x07]);
+        // No mirror, clockwise 90°
+        data.extend_from_slice(&[0x00, 0x01]);
+        // No metadata
+        data.push(0x00);
+        data.extend_from_slice(&1.0f32.to_be_bytes());
+        // Seed
+        data.push(0x2A);
+        data.push(FLAG_IGNORE_ENTITIES | FLAG_SHOW_BOUNDING_BOX);
+
+        let mut cursor = Cursor::new(data);
+        let packet = ProgramStructureBlock::net_decode(&mut cursor)
+            .await
+            .unwrap();
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+
+        assert_eq!(packet.location, Position::new(10, 64, 20));
+        assert_eq!(packet.action.get_val(), ACTION_SAVE);
+        assert_eq!(
+            StructureBlockMode::try_from(packet.mode.get_val()).unwrap(),
+            StructureBlockMode::Save
+        );
+        assert_eq!(packet.name, "minecraft:hut1");
+        assert_eq!(
+            (packet.offset_x, packet.offset_y, packet.offset_z),
+            (0, 1, 0)
+        );
+        assert_eq!((packet.size_x, packet.size_y, packet.size_z), (5, 4, 7));
+        assert_eq!(
+            StructureRotation::try_from(packet.rotation.get_val()).unwrap(),
+            StructureRotation::Clockwise90
+        );
+        assert_eq!(packet.integrity, 1.0);
+        assert_eq!(packet.seed.0, 42);
+        assert_eq!(packet.flags, FLAG_IGNORE_ENTITIES | FLAG_SHOW_BOUNDING_BOX);
+    }
+}
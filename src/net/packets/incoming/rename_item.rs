@@ -0,0 +1,59 @@
+use tracing::trace;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::open_container::OpenContainer;
+use crate::utils::prelude::*;
+
+/// Sent every time the player edits the name field of an anvil.
+///
+/// Also known as Name Item.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x23, state = "play")]
+pub struct RenameItem {
+    pub item_name: String,
+}
+
+impl IncomingPacket for RenameItem {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("RenameItem packet received: {:?}", self);
+
+        let component_storage = state.world.get_component_storage();
+        let Ok(mut container) = component_storage.get_mut::<OpenContainer>(conn_id).await else {
+            trace!("Player {} renamed an item without an anvil open", conn_id);
+            return Ok(());
+        };
+        container.rename(&self.item_name);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::utils::components::open_container::WindowType;
+
+    #[tokio::test]
+    async fn test_decode_anvil_rename() {
+        // String length, then "Excalibur"
+        let mut data = vec![0x09];
+        data.extend_from_slice(b"Excalibur");
+        let packet = RenameItem::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(packet.item_name, "Excalibur");
+
+        let mut container = OpenContainer::new(1, WindowType::Anvil);
+        container.rename(&packet.item_name);
+        assert_eq!(container.item_name.as_deref(), Some("Excalibur"));
+
+        // Clearing the field removes the custom name
+        container.rename("");
+        assert_eq!(container.item_name, None);
+    }
+}
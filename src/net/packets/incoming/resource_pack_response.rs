@@ -0,0 +1,93 @@
+use tracing::{debug, warn};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::disconnect;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+use crate::utils::text::TextComponent;
+
+/// Sent by the client as it goes through downloading the pack from a
+/// [crate::net::packets::outgoing::resource_pack::ResourcePack].
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x24, state = "play")]
+pub struct ResourcePackResponse {
+    pub result: ResourcePackResult,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourcePackResult {
+    SuccessfullyLoaded,
+    Declined,
+    FailedDownload,
+    /// The player said yes, and the download is starting. Another response follows once it's done.
+    Accepted,
+}
+
+impl NetDecode for ResourcePackResult {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let result = VarInt::read(bytes).await?.get_val();
+        match result {
+            0 => Ok(Box::new(ResourcePackResult::SuccessfullyLoaded)),
+            1 => Ok(Box::new(ResourcePackResult::Declined)),
+            2 => Ok(Box::new(ResourcePackResult::FailedDownload)),
+            3 => Ok(Box::new(ResourcePackResult::Accepted)),
+            _ => Err(Error::Generic(format!(
+                "Invalid resource pack result: {}",
+                result
+            ))),
+        }
+    }
+}
+
+impl IncomingPacket for ResourcePackResponse {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        debug!("Player {} resource pack status: {:?}", conn_id, self.result);
+
+        let config = &get_global_config().resource_pack;
+        match self.result {
+            // Like vanilla, only declining gets players kicked. A failed download isn't their fault.
+            ResourcePackResult::Declined if config.required => {
+                disconnect(conn_id, &TextComponent::text(&config.kick_message), state).await
+            }
+            ResourcePackResult::FailedDownload => {
+                warn!(
+                    "Player {} failed to download the resource pack from {}",
+                    conn_id, config.url
+                );
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_resource_pack_response() {
+        let packet = ResourcePackResponse::net_decode(&mut Cursor::new(vec![0x01]))
+            .await
+            .unwrap();
+        assert_eq!(packet.result, ResourcePackResult::Declined);
+
+        assert!(
+            ResourcePackResponse::net_decode(&mut Cursor::new(vec![0x04]))
+                .await
+                .is_err()
+        );
+    }
+}
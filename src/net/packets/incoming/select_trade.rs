@@ -0,0 +1,37 @@
+use tracing::trace;
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::open_container::OpenContainer;
+use crate::utils::prelude::*;
+
+/// Sent when the player picks a trade from a villager's trade list.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x26, state = "play")]
+pub struct SelectTrade {
+    /// The index of the trade in the list the villager offered.
+    pub selected_slot: VarInt,
+}
+
+impl IncomingPacket for SelectTrade {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("SelectTrade packet received: {:?}", self);
+
+        let slot = self.selected_slot.get_val();
+        if slot < 0 {
+            return Err(Error::Generic(format!("Invalid trade slot: {}", slot)));
+        }
+
+        let component_storage = state.world.get_component_storage();
+        let Ok(mut container) = component_storage.get_mut::<OpenContainer>(conn_id).await else {
+            trace!("Player {} selected a trade without trading", conn_id);
+            return Ok(());
+        };
+        container.selected_trade = Some(slot);
+
+        Ok(())
+    }
+}
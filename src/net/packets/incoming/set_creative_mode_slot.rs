@@ -0,0 +1,63 @@
+use tracing::{debug, trace};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::encoding::slot::Slot;
+use crate::utils::prelude::*;
+
+/// Sent when a player in creative mode takes an item out of the creative inventory, or puts one
+/// into their own inventory. Creative players are trusted to spawn in whatever they like.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x2B, state = "play")]
+pub struct SetCreativeModeSlot {
+    /// The inventory window slot, or -1 when the item is thrown out of the window.
+    pub slot: i16,
+    pub clicked_item: Slot,
+}
+
+impl IncomingPacket for SetCreativeModeSlot {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("SetCreativeModeSlot packet received: {:?}", self);
+
+        let game_mode = *state.world.get_component::<GameMode>(conn_id).await?;
+        if game_mode != GameMode::Creative {
+            debug!(
+                "Player {} set a creative slot while in {:?}",
+                conn_id, game_mode
+            );
+            return Ok(());
+        }
+
+        let component_storage = state.world.get_component_storage();
+        let mut inventory = component_storage.get_mut::<Inventory>(conn_id).await?;
+        inventory.set_slot(self.slot, self.clicked_item);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_creative_slot() {
+        // Slot 36, then a present item: id 1, count 64, no NBT
+        let data = vec![0x00, 0x24, 0x01, 0x01, 0x40, 0x00];
+        let packet = SetCreativeModeSlot::net_decode(&mut Cursor::new(data))
+            .await
+            .unwrap();
+        assert_eq!(packet.slot, 36);
+        assert_eq!(packet.clicked_item, Slot::new(1, 64));
+
+        let mut inventory = Inventory::default();
+        assert!(inventory.set_slot(packet.slot, packet.clicked_item));
+        assert_eq!(inventory.held_item(), &Slot::new(1, 64));
+    }
+}
@@ -0,0 +1,33 @@
+use tracing::{debug, trace};
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::inventory::Inventory;
+use crate::utils::prelude::*;
+
+/// Sent when the player selects a different hotbar slot.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x28, state = "play")]
+pub struct SetHeldItem {
+    /// 0 to 8.
+    pub slot: i16,
+}
+
+impl IncomingPacket for SetHeldItem {
+    async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
+        trace!("SetHeldItem packet received: {:?}", self);
+
+        let component_storage = state.world.get_component_storage();
+        let mut inventory = component_storage.get_mut::<Inventory>(conn_id).await?;
+        if !inventory.select(self.slot) {
+            debug!(
+                "Player {} selected hotbar slot {}, which doesn't exist",
+                conn_id, self.slot
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -1,8 +1,7 @@
 use crate::net::packets::{ConnectionId, IncomingPacket};
-use crate::net::systems::chunk_sender::ChunkSender;
 use crate::state::GlobalState;
+use crate::utils::components::precise_position::PrecisePosition;
 use crate::utils::components::rotation::Rotation;
-use crate::utils::encoding::position::Position;
 use crate::utils::prelude::*;
 use ferrumc_macros::{packet, NetDecode};
 use tracing::trace;
@@ -20,33 +19,12 @@ pub struct SetPlayerPosAndRotate {
 
 impl IncomingPacket for SetPlayerPosAndRotate {
     async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()> {
-        let my_entity_id = conn_id;
-
-        let component_storage = state.world.get_component_storage();
-
-        let mut position = component_storage.get_mut::<Position>(my_entity_id).await?;
-        let mut rotation = component_storage.get_mut::<Rotation>(my_entity_id).await?;
-
-        ChunkSender::send_chunks_to_player_if_needed(
-            state.clone(),
-            my_entity_id,
-            (position.x >> 4, position.z >> 4),
-        )
-        .await?;
-
-        *position = Position {
-            x: self.x as i32,
-            y: self.y as i16,
-            z: self.z as i32,
-        };
-
-        *rotation = Rotation {
-            yaw: self.yaw,
-            pitch: self.pitch,
-        };
-
         trace!("SetPlayerPosAndRotate packet received: {:?}", self);
 
-        Ok(())
+        let position = PrecisePosition::new(self.x, self.y, self.z);
+        let rotation = Rotation::new(self.yaw, self.pitch);
+        state
+            .move_player(conn_id, Some(position), Some(rotation), self.on_ground)
+            .await
     }
 }
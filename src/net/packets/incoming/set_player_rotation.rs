@@ -18,17 +18,9 @@ impl IncomingPacket for SetPlayerRotation {
         conn_id: ConnectionId,
         state: GlobalState,
     ) -> crate::utils::prelude::Result<()> {
-        let my_entity_id = conn_id;
-
-        let component_storage = state.world.get_component_storage();
-
-        let mut rotation = component_storage
-            .get_mut_or_insert_with(my_entity_id, || Rotation::new(0.0, 0.0))
-            .await;
-
-        rotation.yaw = self.yaw;
-        rotation.pitch = self.pitch;
-
-        Ok(())
+        let rotation = Rotation::new(self.yaw, self.pitch);
+        state
+            .move_player(conn_id, None, Some(rotation), self.on_ground)
+            .await
     }
 }
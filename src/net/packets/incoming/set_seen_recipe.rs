@@ -0,0 +1,21 @@
+use tracing::trace;
+
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+/// Sent when the player looks at a recipe that was highlighted as new in the recipe book.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x22, state = "play")]
+pub struct SetSeenRecipe {
+    pub recipe: String,
+}
+
+impl IncomingPacket for SetSeenRecipe {
+    async fn handle(self, conn_id: ConnectionId, _state: GlobalState) -> Result<()> {
+        trace!("Player {} has seen recipe {}", conn_id, self.recipe);
+        Ok(())
+    }
+}
@@ -0,0 +1,349 @@
+use tracing::{debug, trace};
+
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::{packet, NetDecode};
+
+use crate::events::creation::dispatcher::EventDispatcherExt;
+use crate::events::world_events::BlockPlaceEvent;
+use crate::net::packets::incoming::player_action::resend_block;
+use crate::net::packets::{ConnectionId, IncomingPacket};
+use crate::state::GlobalState;
+use crate::utils::components::entity_flags::EntityFlags;
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::inventory::{Inventory, HOTBAR_START, OFF_HAND_SLOT};
+use crate::utils::components::open_container::WindowType;
+use crate::utils::components::precise_position::PrecisePosition;
+use crate::utils::encoding::position::Position;
+use crate::utils::text::TextComponent;
+use crate::world::block_changes::BlockChange;
+use crate::world::block_registry::block_registry;
+use crate::world::chunk_format::Palette;
+use crate::world::dimension::Dimension;
+use crate::world::item_registry::item_registry;
+use crate::world::reach::in_reach;
+
+/// Blocks that get replaced when a block is placed against them, rather than the block going
+/// next to them.
+const REPLACEABLE_BLOCKS: &[&str] = &[
+    "minecraft:air",
+    "minecraft:cave_air",
+    "minecraft:void_air",
+    "minecraft:water",
+    "minecraft:lava",
+    "minecraft:grass",
+    "minecraft:tall_grass",
+    "minecraft:fern",
+    "minecraft:large_fern",
+    "minecraft:dead_bush",
+    "minecraft:seagrass",
+    "minecraft:tall_seagrass",
+    "minecraft:vine",
+    "minecraft:fire",
+    "minecraft:soul_fire",
+];
+/// Half the width of a player's hitbox.
+const PLAYER_HALF_WIDTH: f64 = 0.3;
+/// The height of a standing player's hitbox.
+const PLAYER_HEIGHT: f64 = 1.8;
+
+/// Sent when the player right clicks a block, which places the block they're holding.
+#[derive(NetDecode, Debug)]
+#[packet(packet_id = 0x31, state = "play")]
+pub struct UseItemOn {
+    pub hand: Hand,
+    /// The block that was clicked.
+    pub location: Position,
+    /// The face of the block that was clicked.
+    pub face: BlockFace,
+    /// Where on the face the block was clicked, from 0 to 1.
+    pub cursor_x: f32,
+    pub cursor_y: f32,
+    pub cursor_z: f32,
+    /// Whether the player's head is inside a block.
+    pub inside_block: bool,
+    /// Acknowledged once the block has been placed or refused.
+    pub sequence: VarInt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    MainHand,
+    OffHand,
+}
+
+impl NetDecode for Hand {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let hand = VarInt::read(bytes).await?.get_val();
+        match hand {
+            0 => Ok(Box::new(Hand::MainHand)),
+            1 => Ok(Box::new(Hand::OffHand)),
+            _ => Err(Error::Generic(format!("Invalid hand: {}", hand))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockFace {
+    Bottom,
+    Top,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl NetDecode for BlockFace {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let face = VarInt::read(bytes).await?.get_val();
+        match face {
+            0 => Ok(Box::new(BlockFace::Bottom)),
+            1 => Ok(Box::new(BlockFace::Top)),
+            2 => Ok(Box::new(BlockFace::North)),
+            3 => Ok(Box::new(BlockFace::South)),
+            4 => Ok(Box::new(BlockFace::West)),
+            5 => Ok(Box::new(BlockFace::East)),
+            _ => Err(Error::Generic(format!("Invalid block face: {}", face))),
+        }
+    }
+}
+
+impl BlockFace {
+    /// The block on this side of `position`.
+    pub fn offset(&self, position: &Position) -> Position {
+        let (x, y, z) = (position.x, position.y, position.z);
+        match self {
+            BlockFace::Bottom => Position::new(x, y - 1, z),
+            BlockFace::Top => Position::new(x, y + 1, z),
+            BlockFace::North => Position::new(x, y, z - 1),
+            BlockFace::South => Position::new(x, y, z + 1),
+            BlockFace::West => Position::new(x - 1, y, z),
+            BlockFace::East => Position::new(x + 1, y, z),
+        }
+    }
+}
+
+impl IncomingPacket for UseItemOn {
+    async fn handle(
+        self,
+        conn_id: ConnectionId,
+        state: GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        trace!("UseItemOn packet received: {:?}", self);
+
+        if !self.open_container(conn_id, &state).await? {
+            self.place_block(conn_id, &state).await?;
+        }
+
+        // Acknowledged with the tick's block changes, see PendingBlockChanges
+        state
+            .pending_block_changes
+            .acknowledge(conn_id, self.sequence.get_val());
+        Ok(())
+    }
+}
+
+/// The window a block opens when right clicked, along with its title's translation key.
+fn container_window(block_state: i32) -> Option<(WindowType, &'static str)> {
+    match block_registry().state(block_state)?.name.as_str() {
+        "minecraft:anvil" | "minecraft:chipped_anvil" | "minecraft:damaged_anvil" => {
+            Some((WindowType::Anvil, "container.repair"))
+        }
+        _ => None,
+    }
+}
+
+/// Whether placing a block against this one replaces it.
+fn is_replaceable(block_state: i32) -> bool {
+    block_registry()
+        .state(block_state)
+        .is_some_and(|state| REPLACEABLE_BLOCKS.contains(&state.name.as_str()))
+}
+
+/// The block state a held item places, if it's a block.
+fn block_for_item(item_id: i32) -> Option<i32> {
+    let name = item_registry().name(item_id)?;
+    block_registry().id(&Palette {
+        name: name.to_string(),
+        properties: None,
+    })
+}
+
+impl UseItemOn {
+    /// Opens the window of the block that was clicked, if it has one. Sneaking players place
+    /// their block against it instead, like in vanilla. Returns whether a window was opened.
+    async fn open_container(
+        &self,
+        conn_id: ConnectionId,
+        state: &GlobalState,
+    ) -> crate::utils::prelude::Result<bool> {
+        let sneaking = state
+            .world
+            .get_component::<EntityFlags>(conn_id)
+            .await
+            .is_ok_and(|flags| flags.is_sneaking());
+        if sneaking {
+            return Ok(false);
+        }
+
+        let dimension = state.dimension_of(conn_id).await;
+        let clicked = state.block_at(dimension, &self.location).await?;
+        let Some((window_type, title)) = container_window(clicked) else {
+            return Ok(false);
+        };
+        if !in_reach(state, conn_id, &self.location).await? {
+            return Ok(false);
+        }
+
+        state
+            .open_container(conn_id, window_type, &TextComponent::translate(title))
+            .await?;
+        Ok(true)
+    }
+
+    async fn place_block(
+        &self,
+        conn_id: ConnectionId,
+        state: &GlobalState,
+    ) -> crate::utils::prelude::Result<()> {
+        let (slot, item_id) = {
+            let inventory = state.world.get_component::<Inventory>(conn_id).await?;
+            let slot = match self.hand {
+                Hand::MainHand => HOTBAR_START + inventory.selected_slot as usize,
+                Hand::OffHand => OFF_HAND_SLOT,
+            };
+            (
+                slot,
+                inventory.slots[slot].item.as_ref().map(|item| item.item_id),
+            )
+        };
+        let Some(item_id) = item_id else {
+            return Ok(());
+        };
+        let Some(block_state) = block_for_item(item_id).filter(|id| *id != 0) else {
+            trace!("Item {} doesn't place a block", item_id);
+            return Ok(());
+        };
+
+        let dimension = state.dimension_of(conn_id).await;
+        let clicked = state.block_at(dimension, &self.location).await?;
+        let position = if is_replaceable(clicked) {
+            self.location.clone()
+        } else {
+            self.face.offset(&self.location)
+        };
+        let replaced = state.block_at(dimension, &position).await?;
+
+        let game_mode = *state.world.get_component::<GameMode>(conn_id).await?;
+        if matches!(game_mode, GameMode::Adventure | GameMode::Spectator)
+            || !(dimension.min_y()..=dimension.max_y()).contains(&(position.y as i32))
+            || !is_replaceable(replaced)
+            || !in_reach(state, conn_id, &position).await?
+            || player_in_the_way(state, dimension, &position).await
+        {
+            debug!("Player {} can't place a block at {}", conn_id, position);
+            return resend_block(state, conn_id, &position, replaced).await;
+        }
+
+        let event = BlockPlaceEvent::new(conn_id, position.clone(), block_state);
+        if !state.dispatch_cancellable_event(event).await {
+            return resend_block(state, conn_id, &position, replaced).await;
+        }
+
+        if game_mode != GameMode::Creative {
+            let mut inventory = state
+                .world
+                .get_component_storage()
+                .get_mut::<Inventory>(conn_id)
+                .await?;
+            if let Some(item) = inventory.slots[slot].item.as_mut() {
+                item.count -= 1;
+                if item.count <= 0 {
+                    inventory.slots[slot].item = None;
+                }
+            }
+        }
+
+        state
+            .set_blocks(
+                dimension,
+                &[BlockChange {
+                    position,
+                    block_state,
+                }],
+            )
+            .await
+    }
+}
+
+/// Whether a player that isn't spectating is standing where the block would go.
+async fn player_in_the_way(state: &GlobalState, dimension: Dimension, position: &Position) -> bool {
+    let query = state
+        .world
+        .query::<(&PrecisePosition, &GameMode, Option<&Dimension>)>();
+    let in_the_way = query
+        .iter()
+        .await
+        .filter(|(_, (_, game_mode, _))| **game_mode != GameMode::Spectator)
+        .filter(|(_, (_, _, in_dimension))| {
+            in_dimension.as_deref().copied().unwrap_or_default() == dimension
+        })
+        .any(|(_, (player, _, _))| overlaps(&player, position));
+    in_the_way
+}
+
+/// Whether a standing player's hitbox overlaps a block.
+fn overlaps(player: &PrecisePosition, position: &Position) -> bool {
+    let (x, y, z) = (position.x as f64, position.y as f64, position.z as f64);
+    player.x + PLAYER_HALF_WIDTH > x
+        && player.x - PLAYER_HALF_WIDTH < x + 1.0
+        && player.y + PLAYER_HEIGHT > y
+        && player.y < y + 1.0
+        && player.z + PLAYER_HALF_WIDTH > z
+        && player.z - PLAYER_HALF_WIDTH < z + 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode_use_item_on() {
+        // Main hand, at (1, 64, -1), east face, cursor in the middle, sequence 3
+        let mut data = vec![0x00];
+        data.extend_from_slice(&((1u64 << 38) | (0x3FFFFFF << 12) | 64).to_be_bytes());
+        data.push(0x05);
+        for _ in 0..3 {
+            data.extend_from_slice(&0.5f32.to_be_bytes());
+        }
+        data.extend_from_slice(&[0x00, 0x03]);
+        let packet = UseItemOn::net_decode(&mut Cursor::new(data)).await.unwrap();
+        assert_eq!(packet.hand, Hand::MainHand);
+        assert_eq!(packet.location, Position::new(1, 64, -1));
+        assert_eq!(packet.face, BlockFace::East);
+        assert_eq!(packet.cursor_y, 0.5);
+        assert!(!packet.inside_block);
+        assert_eq!(packet.sequence.get_val(), 3);
+        assert_eq!(
+            packet.face.offset(&packet.location),
+            Position::new(2, 64, -1)
+        );
+    }
+
+    #[test]
+    fn test_players_block_placement() {
+        let player = PrecisePosition::new(0.5, 64.0, 0.5);
+        assert!(overlaps(&player, &Position::new(0, 64, 0)));
+        assert!(overlaps(&player, &Position::new(0, 65, 0)));
+        assert!(!overlaps(&player, &Position::new(0, 66, 0)));
+        assert!(!overlaps(&player, &Position::new(0, 63, 0)));
+        assert!(!overlaps(&player, &Position::new(1, 64, 0)));
+    }
+}
@@ -13,4 +13,8 @@ pub trait IncomingPacket {
     async fn handle(self, conn_id: ConnectionId, state: GlobalState) -> Result<()>;
 }
 
+// Generates `handle_packet`, which decodes and handles every struct in the incoming directory
+// marked with `#[packet(packet_id = 0x.., state = "..")]`, and `decode_packet`, which only
+// decodes them. Adding a packet only takes the struct,
+// its IncomingPacket impl and a `pub mod` in incoming/mod.rs.
 bake_packet_registry!("\\src\\net\\packets\\incoming");
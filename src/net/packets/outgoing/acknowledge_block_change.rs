@@ -0,0 +1,18 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Tells the client the server has dealt with its block changes up to `sequence`. Until then, the
+/// client keeps what it predicted, and only takes the server's blocks once it's acknowledged.
+#[derive(NetEncode, Clone)]
+pub struct AcknowledgeBlockChange {
+    #[encode(default = VarInt::from(0x06))]
+    pub packet_id: VarInt,
+    pub sequence: VarInt,
+}
+
+impl AcknowledgeBlockChange {
+    pub fn new(sequence: i32) -> Self {
+        Self::new_auto(sequence.into())
+    }
+}
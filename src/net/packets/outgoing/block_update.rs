@@ -0,0 +1,21 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::encoding::position::Position;
+
+/// Changes a single block. Several changes in the same section are cheaper as an
+/// [UpdateSectionBlocks](super::update_section_blocks::UpdateSectionBlocks).
+#[derive(NetEncode, Clone)]
+pub struct BlockUpdate {
+    #[encode(default = VarInt::from(0x0A))]
+    pub packet_id: VarInt,
+    pub location: Position,
+    pub block_state: VarInt,
+}
+
+impl BlockUpdate {
+    pub fn new(location: Position, block_state: i32) -> Self {
+        Self::new_auto(location, block_state.into())
+    }
+}
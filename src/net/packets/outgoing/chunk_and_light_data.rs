@@ -1,14 +1,24 @@
 use crate::state::GlobalState;
-use crate::utils::config::get_global_config;
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::config::{get_global_config, WorldGenerator};
 use crate::utils::encoding::bitset::BitSet;
 use crate::utils::error::Error;
-use crate::world::chunk_format::Heightmaps;
+use crate::world::biome_registry::{biome_registry, DEFAULT_BIOME};
+use crate::world::chunk_format::{Biomes, Chunk, Heightmaps};
+use crate::world::dimension::Dimension;
+use crate::world::heightmap::compute_heightmaps;
+use crate::world::lighting::{has_light, light_chunk};
+use crate::world::palette::{pack_entries, unpack_entries};
+use crate::world::region::load_region_chunk;
 use crate::Result;
-use ferrumc_codec::enc::NetEncode;
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
 use ferrumc_codec::network_types::varint::VarInt;
 use ferrumc_macros::NetEncode;
-use nbt_lib::NBTTag;
-use std::io::Cursor; // Import the AsyncWrite trait
+use lazy_static::lazy_static;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tracing::warn;
 
 const _SECTION_WIDTH: usize = 16;
@@ -55,32 +65,211 @@ pub struct BlockEntity {
     pub packed_xz: u8,
     pub y: i16,
     pub type_id: VarInt,
-    pub data: NBTTag,
+    /// The block entity's NBT, as an unnamed root compound.
+    #[encode(raw_bytes(prepend_length = false))]
+    pub data: Vec<u8>,
 }
 
-#[derive(NetEncode, Clone)]
+/// The light levels of one section, two per byte.
+///
+/// Most sections are either completely dark or completely lit, so the bytes are shared: cloning a
+/// light array, or building one with the same levels as the previous one, doesn't allocate.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LightArray {
-    #[encode(raw_bytes(prepend_length = true))]
-    pub data: Vec<u8>,
+    pub data: Arc<[u8]>,
+}
+
+/// The size of a section's light data in bytes.
+pub const LIGHT_ARRAY_LEN: usize = 2048;
+
+lazy_static! {
+    static ref DARK: LightArray = LightArray {
+        data: Arc::from(vec![0; LIGHT_ARRAY_LEN]),
+    };
+    static ref FULL: LightArray = LightArray {
+        data: Arc::from(vec![0xFF; LIGHT_ARRAY_LEN]),
+    };
+}
+
+impl LightArray {
+    /// A section with no light at all. Always points at the same bytes.
+    pub fn dark() -> Self {
+        DARK.clone()
+    }
+
+    /// A section with every block at full light. Always points at the same bytes.
+    pub fn full() -> Self {
+        FULL.clone()
+    }
+
+    /// Builds the light array for a section from its stored light levels, reusing the bytes of
+    /// `previous` if the levels are identical.
+    pub fn from_stored(stored: Option<&Vec<i8>>, previous: Option<&LightArray>) -> Self {
+        let Some(stored) = stored else {
+            return Self::dark();
+        };
+        let bytes = stored
+            .iter()
+            .take(LIGHT_ARRAY_LEN)
+            .map(|&x| x as u8)
+            .collect::<Vec<_>>();
+
+        if *DARK.data == *bytes {
+            return Self::dark();
+        }
+        if *FULL.data == *bytes {
+            return Self::full();
+        }
+        match previous {
+            Some(previous) if *previous.data == *bytes => previous.clone(),
+            _ => LightArray {
+                data: Arc::from(bytes),
+            },
+        }
+    }
+}
+
+impl NetEncode for LightArray {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        VarInt::from(self.data.len() as i32)
+            .net_encode(writer, encode_option)
+            .await?;
+        writer.write_all(&self.data).await?;
+        Ok(())
+    }
 }
 
 impl ChunkDataAndUpdateLight {
-    pub async fn new(state: GlobalState, chunk_x: i32, chunk_z: i32) -> Result<Self> {
-        let chunk = state
-            .database
-            .get_chunk(chunk_x, chunk_z, "overworld".to_string())
-            .await?
-            .ok_or(Error::ChunkNotFound(chunk_x, chunk_z))?;
+    pub async fn new(
+        state: GlobalState,
+        dimension: Dimension,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> Result<Self> {
+        let chunk = Self::load_chunk(&state, dimension, chunk_x, chunk_z).await?;
+        Self::from_chunk(&chunk, dimension).await
+    }
+
+    /// Loads the chunk the packet would be built from, in network mode, from the chunk cache or
+    /// wherever the configured generator gets the dimension's chunks. Chunks that haven't been
+    /// stored yet are generated.
+    ///
+    /// A stored chunk that can't be read is logged and replaced by a generated one, which is never
+    /// saved so the broken one stays on disk.
+    ///
+    /// Only the overworld has terrain to generate, so the other dimensions are empty apart from
+    /// what's in their region files.
+    pub async fn load_chunk(
+        state: &GlobalState,
+        dimension: Dimension,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> Result<Arc<Chunk>> {
+        let config = get_global_config();
+        Self::load_chunk_from(
+            state,
+            config.generator,
+            Path::new(&config.region_dir),
+            dimension,
+            chunk_x,
+            chunk_z,
+        )
+        .await
+    }
+
+    /// [ChunkDataAndUpdateLight::load_chunk] with the generator and the overworld's region
+    /// directory given, instead of taken from the config.
+    async fn load_chunk_from(
+        state: &GlobalState,
+        generator: WorldGenerator,
+        region_dir: &Path,
+        dimension: Dimension,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> Result<Arc<Chunk>> {
+        let key = (dimension, chunk_x, chunk_z);
+        if let Some(chunk) = state.chunk_cache.get(key).await {
+            return Ok(chunk);
+        }
+
+        let mut fallback = false;
+        let stored = match generator {
+            WorldGenerator::Debug => None,
+            WorldGenerator::Anvil | WorldGenerator::Flat => {
+                let region_dir = dimension.region_dir(region_dir);
+                match load_region_chunk(&region_dir, chunk_x, chunk_z).await {
+                    Ok(stored) => stored,
+                    Err(e) => {
+                        warn!(
+                            "Failed to load chunk {:?}, generating one that won't be saved: {}",
+                            key, e
+                        );
+                        fallback = true;
+                        None
+                    }
+                }
+            }
+            WorldGenerator::Imported => {
+                match state
+                    .database
+                    .get_chunk(chunk_x, chunk_z, dimension.short_name().to_string())
+                    .await
+                {
+                    Ok(stored) => stored,
+                    Err(e) => {
+                        warn!(
+                            "Failed to load chunk {:?}, generating one that won't be saved: {}",
+                            key, e
+                        );
+                        fallback = true;
+                        None
+                    }
+                }
+            }
+        };
+
+        let mut chunk = stored.unwrap_or_else(|| match dimension {
+            Dimension::Overworld => state.chunk_generator.generate_chunk(chunk_x, chunk_z),
+            Dimension::Nether | Dimension::End => Chunk::empty_in(dimension, chunk_x, chunk_z),
+        });
+        // Region files don't say which dimension they're from
+        chunk.dimension = Some(dimension.short_name().to_string());
+        if !has_light(&chunk) {
+            light_chunk(&mut chunk);
+        }
+        let chunk = if fallback {
+            state.chunk_cache.insert_fallback(dimension, chunk).await
+        } else {
+            state.chunk_cache.insert(dimension, chunk).await
+        };
+        Ok(chunk)
+    }
+
+    /// Build the packet from an already loaded chunk, in network mode. Only the sections inside
+    /// the dimension are sent, since the client expects exactly as many as it's tall.
+    pub async fn from_chunk(chunk: &Chunk, dimension: Dimension) -> Result<Self> {
+        let (chunk_x, chunk_z) = (chunk.x_pos, chunk.z_pos);
 
         // Serialize the chunk data
         let mut data = Cursor::new(Vec::new());
 
+        let section_range = dimension.section_range();
         if let Some(sections) = &chunk.sections {
-            for section in sections {
+            for section in sections
+                .iter()
+                .filter(|section| section_range.contains(&section.y))
+            {
                 section
                     .net_encode(&mut data, &get_global_config().compression_and_encode_opt())
                     .await?;
-                serialize_biomes()
+                serialize_biomes(section.biomes.as_ref())
                     .await?
                     .net_encode(&mut data, &get_global_config().compression_and_encode_opt())
                     .await?;
@@ -93,78 +282,81 @@ impl ChunkDataAndUpdateLight {
             ));
         }
 
-        // 24 is the number of sections in a chunk
-
-        // -4 to 20
-        const SECTIONS: usize = 24;
+        // One light section for every section, plus one below and one above the world
+        let sections = dimension.section_count();
 
-        // let sky_light_mask = BitSet::from_iter((0..SECTIONS + 2).map(|_| 1));
-        // let block_light_mask = BitSet::from_iter((0..SECTIONS + 2).map(|_| 1));
-        // let empty_sky_light_mask = BitSet::from_iter((0..SECTIONS + 2).map(|_| 0));
-        // let empty_block_light_mask = BitSet::from_iter((0..SECTIONS + 2).map(|_| 0));
+        // Dimensions without a sky have no sky light at all, so every section is marked empty
+        // instead of sending arrays full of zeroes.
+        let has_skylight = dimension.has_skylight();
 
-        let mut sky_light_mask = BitSet::new(SECTIONS + 2);
-        sky_light_mask.set_all();
-        let mut block_light_mask = BitSet::new(SECTIONS + 2);
+        let mut sky_light_mask = BitSet::new(sections + 2);
+        let mut empty_sky_light_mask = BitSet::new(sections + 2);
+        if has_skylight {
+            sky_light_mask.set_all();
+        } else {
+            empty_sky_light_mask.set_all();
+        }
+        let mut block_light_mask = BitSet::new(sections + 2);
         block_light_mask.set_all();
-        let empty_sky_light_mask = BitSet::new(SECTIONS + 2);
-        let empty_block_light_mask = BitSet::new(SECTIONS + 2);
+        let empty_block_light_mask = BitSet::new(sections + 2);
 
-        // Create light arrays
+        // Create light arrays, starting with the section below the world
         let mut sky_light_arrays = Vec::new();
-        let mut block_light_arrays = Vec::new();
-
-        for section in chunk.sections.as_ref().unwrap() {
-            sky_light_arrays.push(if let Some(sky_light) = &section.sky_light {
-                LightArray {
-                    data: sky_light.iter().take(2048).map(|&x| x as u8).collect(),
-                }
-            } else {
-                LightArray {
-                    data: vec![0; 2048],
-                }
-            });
-            block_light_arrays.push(if let Some(block_light) = &section.block_light {
-                LightArray {
-                    data: block_light.iter().take(2048).map(|&x| x as u8).collect(),
-                }
-            } else {
-                LightArray {
-                    data: vec![0; 2048],
-                }
-            });
+        let mut block_light_arrays = vec![LightArray::dark()];
+        if has_skylight {
+            sky_light_arrays.push(LightArray::dark());
         }
-        block_light_arrays.push(LightArray {
-            data: vec![0; 2048],
-        });
-        sky_light_arrays.push(LightArray {
-            data: vec![0; 2048],
-        });
-        block_light_arrays.push(LightArray {
-            data: vec![0; 2048],
-        });
-        sky_light_arrays.push(LightArray {
-            data: vec![0; 2048],
-        });
 
-        let heightmaps = chunk.heightmaps.unwrap_or_else(|| {
-            warn!("Chunk is missing heightmaps, creating default heightmaps");
-            Heightmaps {
-                //motion_blocking_no_leaves: None,
-                motion_blocking: Some(vec![i64::MAX; 37]),
-                //ocean_floor: None,
-                world_surface: Some(vec![i64::MAX; 37]),
+        for section in chunk
+            .sections
+            .iter()
+            .flatten()
+            .filter(|section| section_range.contains(&section.y))
+        {
+            if has_skylight {
+                let sky_light =
+                    LightArray::from_stored(section.sky_light.as_ref(), sky_light_arrays.last());
+                sky_light_arrays.push(sky_light);
             }
+            let block_light =
+                LightArray::from_stored(section.block_light.as_ref(), block_light_arrays.last());
+            block_light_arrays.push(block_light);
+        }
+        // The section above the world, which is open to the sky
+        block_light_arrays.push(LightArray::dark());
+        if has_skylight {
+            sky_light_arrays.push(LightArray::full());
+        }
+
+        let heightmaps = chunk.heightmaps.clone().unwrap_or_else(|| {
+            warn!("Chunk is missing heightmaps, working them out from its blocks");
+            compute_heightmaps(chunk)
         });
 
+        let block_entities = chunk
+            .block_entities
+            .iter()
+            .flatten()
+            .filter_map(|block_entity| {
+                let entry = block_entity.to_network();
+                if entry.is_none() {
+                    warn!(
+                        "Skipping block entity of unknown type {} in chunk ({}, {})",
+                        block_entity.id, chunk_x, chunk_z
+                    );
+                }
+                entry
+            })
+            .collect::<Vec<_>>();
+
         let res = ChunkDataAndUpdateLight {
             packet_id: VarInt::from(0x24),
             chunk_x,
             chunk_z,
             heightmaps,
             data: data.into_inner(),
-            block_entities_count: VarInt::from(0),
-            block_entities: Vec::new(),
+            block_entities_count: VarInt::from(block_entities.len() as i32),
+            block_entities,
             light_data: LightData {
                 sky_light_mask,
                 block_light_mask,
@@ -178,163 +370,328 @@ impl ChunkDataAndUpdateLight {
         };
         Ok(res)
     }
-}
-/*
-async fn serialize_block_states(block_states: &BlockStates) -> Result<Vec<u8>> {
-    let mut data = Vec::new();
-    let bits_per_block = 15; // direct palette
-    data.push(bits_per_block);
-
-    // No palette serialization for direct palette
-
-    // Serialize the block data
-    let block_data = block_states.data.as_ref().unwrap();
-    VarInt::from(block_data.len() as i32)
-        .net_encode(&mut data)
-        .await?;
 
-    for long in block_data {
-        long.net_encode(&mut data).await?;
+    /// Drops all the light data, marking every section as having no light at all.
+    ///
+    /// Only meant for players that don't render light, like spectators.
+    pub fn without_light(mut self) -> Self {
+        let sections = self.light_data.sky_light_mask.len();
+        let mut empty_mask = BitSet::new(sections);
+        empty_mask.set_all();
+
+        self.light_data = LightData {
+            sky_light_mask: BitSet::new(sections),
+            block_light_mask: BitSet::new(sections),
+            empty_sky_light_mask: empty_mask.clone(),
+            empty_block_light_mask: empty_mask,
+            sky_light_array_count: VarInt::from(0),
+            sky_light_arrays: Vec::new(),
+            block_light_array_count: VarInt::from(0),
+            block_light_arrays: Vec::new(),
+        };
+        self
     }
 
-    Ok(data)
-}*/
-async fn serialize_biomes() -> Result<Vec<u8>> {
-    let mut data: Vec<u8> = Vec::new();
-    let bits_per_biome = 6;
-    data.push(bits_per_biome);
+    /// Strips the light data if the player is a spectator and the server is set to send them
+    /// reduced chunks.
+    pub fn for_game_mode(self, game_mode: GameMode, reduce_for_spectators: bool) -> Self {
+        if reduce_for_spectators && game_mode.is_spectator() {
+            self.without_light()
+        } else {
+            self
+        }
+    }
+}
 
-    // Direct biome encoding, no palette
-    let biome_data = vec![0u64; 64 * (bits_per_biome as usize) / 64]; // 64 biomes per section
-    VarInt::from(biome_data.len() as i32)
-        .net_encode(&mut data, &get_global_config().compression_and_encode_opt())
-        .await?;
+/// Biomes are stored per 4x4x4 cell, so there are 64 of them in a section.
+const BIOMES_PER_SECTION: usize = 64;
+/// Palettes with more biomes than fit in this many bits are sent directly as registry IDs.
+const MAX_INDIRECT_BIOME_BITS: u8 = 3;
+
+/// The bits needed to tell `len` palette entries apart.
+fn biome_bits(len: usize) -> u8 {
+    (usize::BITS - len.saturating_sub(1).leading_zeros()) as u8
+}
 
-    for long in biome_data {
-        long.net_encode(&mut data, &get_global_config().compression_and_encode_opt())
+/// Serializes a section's biomes as a paletted container, with the biome names swapped for their
+/// IDs in the registry codec. Sections without biomes, or with malformed ones, are all plains.
+async fn serialize_biomes(biomes: Option<&Biomes>) -> Result<Vec<u8>> {
+    let registry = biome_registry();
+    let encode_option = get_global_config().compression_and_encode_opt();
+    let mut data: Vec<u8> = Vec::new();
+
+    let palette = biomes
+        .map(|biomes| {
+            biomes
+                .palette
+                .iter()
+                .map(|name| registry.id_or_default(name))
+                .collect::<Vec<_>>()
+        })
+        .filter(|palette| !palette.is_empty())
+        .unwrap_or_else(|| vec![registry.id_or_default(DEFAULT_BIOME)]);
+    let bits = biome_bits(palette.len());
+    let cells = biomes
+        .and_then(|biomes| biomes.data.as_deref())
+        .filter(|cells| bits > 0 && cells.len() == BIOMES_PER_SECTION.div_ceil(64 / bits as usize));
+
+    let Some(cells) = cells else {
+        // A single biome, with an empty data array
+        data.push(0);
+        VarInt::from(palette[0])
+            .net_encode(&mut data, &encode_option)
+            .await?;
+        VarInt::from(0)
+            .net_encode(&mut data, &encode_option)
             .await?;
+        return Ok(data);
+    };
+
+    let longs = if bits <= MAX_INDIRECT_BIOME_BITS {
+        // The palette is sent as is, and the cells are packed the same way as on disk
+        data.push(bits);
+        VarInt::from(palette.len() as i32)
+            .net_encode(&mut data, &encode_option)
+            .await?;
+        for id in &palette {
+            VarInt::from(*id)
+                .net_encode(&mut data, &encode_option)
+                .await?;
+        }
+        cells.to_vec()
+    } else {
+        let direct_bits = biome_bits(registry.len());
+        let ids = unpack_entries(cells, bits, BIOMES_PER_SECTION)
+            .into_iter()
+            .map(|entry| palette.get(entry as usize).copied().unwrap_or(palette[0]) as u32)
+            .collect::<Vec<_>>();
+        data.push(direct_bits);
+        pack_entries(&ids, direct_bits)
+    };
+
+    VarInt::from(longs.len() as i32)
+        .net_encode(&mut data, &encode_option)
+        .await?;
+    for long in longs {
+        long.net_encode(&mut data, &encode_option).await?;
     }
 
     Ok(data)
 }
-/*
-fn create_basic_chunk(chunk_x: i32, chunk_z: i32) -> Chunk {
-    let _rng = rand::thread_rng();
-    let mut sections = Vec::with_capacity(24); // 24 sections for -64 to 320 world height
-    for y in -4..=20 {
-        // let possible_values = vec![1, 9, 131]; // stone, grass, oak log
-        let chunk_data = vec![9; 16 * 16 * 16];
-
-        /*if y > 1 && y < 6 {
-            for x in 0..16 {
-                for z in 0..16 {
-                    let random_block = rng.gen_range(0..20_000);
-                    // let random_value = possible_values[rng.gen_range(0..possible_values.len())];
-                    chunk_data[x * 16 + z * 256] = random_block;
-                }
-            }
-        }*/
-
-        let block_states = create_block_states(&chunk_data, 15);
-
-        let section = Section {
-            block_states: Some(block_states.clone()),
-            biomes: Some(Biomes {
-                palette: vec!["minecraft:plains".to_string()],
-            }),
-            y: y as i8,
-            block_light: Some(vec![0xf; 2048]),
-            sky_light: Some(vec![0xf; 2048]),
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_uniform_light_is_shared() {
+        let mut chunk = Chunk::empty(0, 0);
+        for section in chunk.sections.as_mut().unwrap() {
+            section.sky_light = Some(vec![-1; LIGHT_ARRAY_LEN]);
+        }
+
+        let packet = ChunkDataAndUpdateLight::from_chunk(&chunk, Dimension::Overworld)
+            .await
+            .unwrap();
+        let light = packet.light_data;
+
+        let lit = &light.sky_light_arrays[1];
+        assert!(lit.data.iter().all(|&level| level == 0xFF));
+        // The 24 lit sections and the one above the world share the fully lit bytes, and
+        // everything else the dark ones
+        for array in &light.sky_light_arrays[1..] {
+            assert!(Arc::ptr_eq(&array.data, &LightArray::full().data));
+        }
+        for array in light
+            .block_light_arrays
+            .iter()
+            .chain(&light.sky_light_arrays[..1])
+        {
+            assert!(Arc::ptr_eq(&array.data, &LightArray::dark().data));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_biomes_use_registry_ids() {
+        let plains = biome_registry().id("minecraft:plains").unwrap() as u8;
+        let desert = biome_registry().id("minecraft:desert").unwrap() as u8;
+        assert_eq!(serialize_biomes(None).await.unwrap(), vec![0, plains, 0]);
+
+        // Two biomes take one bit per cell, sent with the palette
+        let biomes = Biomes {
+            palette: vec!["minecraft:plains".to_string(), "desert".to_string()],
+            data: Some(vec![0b1010]),
+        };
+        let mut expected = vec![1, 2, plains, desert, 1];
+        expected.extend_from_slice(&0b1010i64.to_be_bytes());
+        assert_eq!(serialize_biomes(Some(&biomes)).await.unwrap(), expected);
+
+        // Malformed cells fall back to the first biome
+        let biomes = Biomes {
+            data: Some(vec![0; 3]),
+            ..biomes
         };
-        sections.push(section);
+        assert_eq!(
+            serialize_biomes(Some(&biomes)).await.unwrap(),
+            vec![0, plains, 0]
+        );
+
+        // Too many biomes for a palette are sent as registry IDs
+        let biomes = Biomes {
+            palette: vec!["minecraft:desert".to_string(); 9],
+            data: Some(vec![0; 4]),
+        };
+        let direct = serialize_biomes(Some(&biomes)).await.unwrap();
+        assert_eq!(direct[0], biome_bits(biome_registry().len()));
+        let longs = BIOMES_PER_SECTION.div_ceil(64 / direct[0] as usize);
+        assert_eq!(direct[1] as usize, longs);
+        assert_eq!(direct.len(), 2 + longs * 8);
+        assert_eq!(
+            unpack_entries(
+                &[i64::from_be_bytes(direct[2..10].try_into().unwrap())],
+                direct[0],
+                64 / direct[0] as usize
+            ),
+            vec![desert as u32; 64 / direct[0] as usize]
+        );
     }
 
-    // Set heightmap to the top of the world (320 + 1)
-    let heightmap = vec![321i64; 256];
-
-    Chunk {
-        dimension: Some("overworld".to_string()),
-        status: "full".to_string(),
-        data_version: 3465,
-        heightmaps: Some(Heightmaps {
-            // motion_blocking_no_leaves: None,
-            motion_blocking: Some(heightmap.clone()),
-            // ocean_floor: None,
-            world_surface: Some(heightmap),
-        }),
-        is_light_on: Some(1),
-        inhabited_time: Some(0),
-        y_pos: -4,
-        x_pos: chunk_x,
-        z_pos: chunk_z,
-        structures: Some(Structures {
-            starts: Starts {},
-            references: References {},
-        }),
-        last_update: Some(0),
-        sections: Some(sections),
+    #[tokio::test]
+    async fn test_spectators_get_reduced_chunks() {
+        let full = ChunkDataAndUpdateLight::from_chunk(&Chunk::empty(0, 0), Dimension::Overworld)
+            .await
+            .unwrap();
+        let full_len = full.light_data.sky_light_arrays.len();
+
+        let spectator =
+            ChunkDataAndUpdateLight::from_chunk(&Chunk::empty(0, 0), Dimension::Overworld)
+                .await
+                .unwrap()
+                .for_game_mode(GameMode::Spectator, true);
+        let light = &spectator.light_data;
+        assert!(light.sky_light_arrays.is_empty());
+        assert!(light.block_light_arrays.is_empty());
+        assert_eq!(light.sky_light_mask.count_ones(), 0);
+        assert_eq!(light.block_light_mask.count_ones(), 0);
+        assert_eq!(light.empty_sky_light_mask.count_ones(), full_len);
+        assert_eq!(light.empty_block_light_mask.count_ones(), full_len);
+
+        // Everyone else, or spectators with the option turned off, get the full chunk
+        let survival =
+            ChunkDataAndUpdateLight::from_chunk(&Chunk::empty(0, 0), Dimension::Overworld)
+                .await
+                .unwrap()
+                .for_game_mode(GameMode::Survival, true);
+        assert_eq!(survival.light_data.sky_light_arrays.len(), full_len);
+        let unreduced =
+            ChunkDataAndUpdateLight::from_chunk(&Chunk::empty(0, 0), Dimension::Overworld)
+                .await
+                .unwrap()
+                .for_game_mode(GameMode::Spectator, false);
+        assert_eq!(unreduced.light_data.block_light_arrays.len(), full_len);
     }
-}
-*/
-/*fn create_block_states(chunk_data: &[u32], bits_per_entry: u8) -> BlockStates {
-    let packed_data = pack_entries(chunk_data, bits_per_entry);
-
-    BlockStates {
-        non_air_blocks: None,
-        bits_per_block: None,
-        data: Some(packed_data),
-        palette: if bits_per_entry < 15 {
-            Some(Vec::new()) // We'll need to populate this for indirect encoding
-        } else {
-            None // Direct encoding, no palette
-        },
-        net_palette: None,
-        // default: None,
+
+    #[tokio::test]
+    async fn test_nether_has_no_sky_light() {
+        let chunk = Chunk::empty_in(Dimension::Nether, 0, 0);
+
+        let packet = ChunkDataAndUpdateLight::from_chunk(&chunk, Dimension::Nether)
+            .await
+            .unwrap();
+        let light = &packet.light_data;
+        assert!(light.sky_light_arrays.is_empty());
+        assert_eq!(light.sky_light_array_count.get_val(), 0);
+        assert_eq!(light.sky_light_mask.count_ones(), 0);
+        // The nether is 16 sections tall
+        assert_eq!(light.empty_sky_light_mask.count_ones(), 18);
+        // Block light is still there
+        assert_eq!(light.block_light_arrays.len(), 18);
     }
-}*//*
-fn pack_entries(entries: &[u32], bits_per_entry: u8) -> Vec<i64> {
-    let entries_per_long = 64 / bits_per_entry as usize;
-    let mask = (1u64 << bits_per_entry) - 1;
-    let total_longs = (entries.len() + entries_per_long - 1) / entries_per_long;
-
-    let mut packed_data = Vec::with_capacity(total_longs);
-    let mut current_long = 0u64;
-    let mut entries_in_long = 0;
-
-    for &entry in entries {
-        current_long |= (entry as u64 & mask) << (bits_per_entry as u64 * entries_in_long as u64);
-        entries_in_long += 1;
-
-        if entries_in_long == entries_per_long {
-            packed_data.push(current_long as i64);
-            current_long = 0;
-            entries_in_long = 0;
-        }
+
+    #[tokio::test]
+    async fn test_sections_outside_the_dimension_are_left_out() {
+        let end = Chunk::empty_in(Dimension::End, 0, 0);
+        // The same chunk with sections above and below the end, like an overworld chunk has
+        let mut tall = end.clone();
+        tall.sections.as_mut().unwrap().extend(
+            Chunk::empty(0, 0)
+                .sections
+                .unwrap()
+                .into_iter()
+                .filter(|section| !(0..16).contains(&section.y)),
+        );
+
+        let end = ChunkDataAndUpdateLight::from_chunk(&end, Dimension::End)
+            .await
+            .unwrap();
+        let tall = ChunkDataAndUpdateLight::from_chunk(&tall, Dimension::End)
+            .await
+            .unwrap();
+        assert_eq!(tall.data, end.data);
+        assert_eq!(tall.light_data.block_light_arrays.len(), 18);
     }
 
-    // Handle padding for the last long if necessary
-    if entries_in_long > 0 {
-        packed_data.push(current_long as i64);
+    #[tokio::test]
+    async fn test_light_array_is_length_prefixed() {
+        let mut buffer = Vec::new();
+        LightArray::dark()
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        assert_eq!(&buffer[..2], &[0x80, 0x10]);
+        assert_eq!(buffer.len(), 2 + LIGHT_ARRAY_LEN);
     }
 
-    packed_data
-}*/
-
-// fn get_block_state_id(block_name: &str) -> i32 {
-//     // This should be replaced with a proper block state registry lookup
-//     match block_name {
-//         "minecraft:air" => 0,
-//         "minecraft:stone" => 1,
-//         "minecraft:grass_block" => 9,
-//         "minecraft:oak_log" => 131,
-//         _ => 0,
-//     }
-// }
-
-// fn get_biome_id(biome: &str) -> i32 {
-//     // This should be replaced with a proper biome registry lookup
-//     match biome {
-//         "minecraft:plains" => 127,
-//         _ => 0,
-//     }
-// }
+    #[tokio::test]
+    async fn test_corrupt_chunk_is_generated_instead() {
+        use crate::world::region::region_file_path;
+        use crate::world::region::tests::region_with_chunk;
+
+        let state = crate::create_state(tokio::net::TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("ferrumc-corrupt-load-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let region = region_with_chunk(1, 2, 3, &[0x0A, 0x00, 0x00, 0x63, 0xFF, 0x12, 0x00]);
+        std::fs::write(region_file_path(&dir, 1, 2), region).unwrap();
+
+        let loaded = ChunkDataAndUpdateLight::load_chunk_from(
+            &state,
+            WorldGenerator::Anvil,
+            &dir,
+            Dimension::Overworld,
+            1,
+            2,
+        )
+        .await;
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let chunk = loaded.unwrap();
+        assert_eq!((chunk.x_pos, chunk.z_pos), (1, 2));
+        let mut generated = state.chunk_generator.generate_chunk(1, 2);
+        generated.dimension = Some(Dimension::Overworld.short_name().to_string());
+        light_chunk(&mut generated);
+        assert_eq!(
+            *chunk, generated,
+            "the corrupt chunk should be replaced by a generated one"
+        );
+
+        // Even once it's changed, the generated chunk isn't saved over the corrupt one
+        let key = (Dimension::Overworld, 1, 2);
+        assert!(state
+            .chunk_cache
+            .dirty_chunks()
+            .iter()
+            .all(|(k, _)| *k != key));
+        state
+            .chunk_cache
+            .update(Dimension::Overworld, (*chunk).clone())
+            .await;
+        assert!(state
+            .chunk_cache
+            .dirty_chunks()
+            .iter()
+            .all(|(k, _)| *k != key));
+    }
+}
@@ -0,0 +1,23 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::text::TextComponent;
+
+/// Shows the death screen to the player that died. The client stays on it until the player
+/// clicks respawn, which sends [crate::net::packets::incoming::client_status::ClientStatus].
+#[derive(NetEncode)]
+pub struct CombatDeath {
+    #[encode(default = VarInt::from(0x38))]
+    pub packet_id: VarInt,
+    /// The entity id of the player that died.
+    pub player_id: VarInt,
+    /// Shown as the death message.
+    pub message: TextComponent,
+}
+
+impl CombatDeath {
+    pub fn new(player_id: i32, message: &str) -> Self {
+        Self::new_auto(player_id.into(), TextComponent::text(message))
+    }
+}
@@ -0,0 +1,187 @@
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::AsyncWrite;
+
+use ferrumc_macros::NetEncode;
+
+use crate::commands::{ArgumentParser, CommandNode, NodeKind, StringKind};
+
+/// The node type, in the low two bits of the flags.
+const ROOT: u8 = 0;
+const LITERAL: u8 = 1;
+const ARGUMENT: u8 = 2;
+/// The input can end at this node.
+const EXECUTABLE: u8 = 0x04;
+
+/// Sends the whole command tree, so the client can tab-complete and highlight commands as
+/// they're typed. The tree is flattened into a list, with children referred to by index.
+#[derive(NetEncode)]
+pub struct Commands {
+    #[encode(default = VarInt::from(0x10))]
+    pub packet_id: VarInt,
+    #[encode(prepend_length = true)]
+    pub nodes: Vec<CommandNodeEntry>,
+    pub root_index: VarInt,
+}
+
+#[derive(NetEncode, Debug)]
+pub struct CommandNodeEntry {
+    pub flags: u8,
+    #[encode(prepend_length = true)]
+    pub children: Vec<VarInt>,
+    /// Only for literal and argument nodes.
+    pub name: Option<String>,
+    /// Only for argument nodes.
+    pub parser: Option<ArgumentParser>,
+}
+
+impl Commands {
+    pub fn new(root: &CommandNode) -> Self {
+        let mut nodes = Vec::new();
+        let root_index = flatten(root, &mut nodes);
+        Self::new_auto(nodes, VarInt::new(root_index))
+    }
+}
+
+/// Adds a node and everything below it to the list, returning the node's index.
+fn flatten(node: &CommandNode, nodes: &mut Vec<CommandNodeEntry>) -> i32 {
+    let index = nodes.len();
+    let (node_type, name, parser) = match &node.kind {
+        NodeKind::Root => (ROOT, None, None),
+        NodeKind::Literal(name) => (LITERAL, Some(name.clone()), None),
+        NodeKind::Argument { name, parser } => (ARGUMENT, Some(name.clone()), Some(parser.clone())),
+    };
+    let mut flags = node_type;
+    if node.executor.is_some() {
+        flags |= EXECUTABLE;
+    }
+    nodes.push(CommandNodeEntry {
+        flags,
+        children: Vec::new(),
+        name,
+        parser,
+    });
+
+    let children = node
+        .children
+        .iter()
+        .map(|child| VarInt::new(flatten(child, nodes)))
+        .collect();
+    nodes[index].children = children;
+    index as i32
+}
+
+impl ArgumentParser {
+    /// The parser's ID in the `minecraft:command_argument_type` registry.
+    fn id(&self) -> i32 {
+        match self {
+            ArgumentParser::Bool => 0,
+            ArgumentParser::Double { .. } => 2,
+            ArgumentParser::Integer { .. } => 3,
+            ArgumentParser::String(_) => 5,
+        }
+    }
+}
+
+/// Which of the bounds are sent, for number parsers.
+fn bound_flags(min: bool, max: bool) -> u8 {
+    (min as u8) | (max as u8) << 1
+}
+
+impl NetEncode for ArgumentParser {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        VarInt::new(self.id())
+            .net_encode(writer, encode_option)
+            .await?;
+        match self {
+            ArgumentParser::Bool => Ok(()),
+            ArgumentParser::Integer { min, max } => {
+                bound_flags(min.is_some(), max.is_some())
+                    .net_encode(writer, encode_option)
+                    .await?;
+                min.net_encode(writer, encode_option).await?;
+                max.net_encode(writer, encode_option).await
+            }
+            ArgumentParser::Double { min, max } => {
+                bound_flags(min.is_some(), max.is_some())
+                    .net_encode(writer, encode_option)
+                    .await?;
+                min.net_encode(writer, encode_option).await?;
+                max.net_encode(writer, encode_option).await
+            }
+            ArgumentParser::String(kind) => {
+                let kind = match kind {
+                    StringKind::Word => 0,
+                    StringKind::Quotable => 1,
+                    StringKind::Greedy => 2,
+                };
+                VarInt::new(kind).net_encode(writer, encode_option).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+
+    use super::*;
+    use crate::commands::CommandContext;
+    use crate::utils::prelude::*;
+
+    fn noop(_: CommandContext) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    #[test]
+    fn test_tree_is_flattened() {
+        let root = CommandNode::root()
+            .then(CommandNode::literal("help").executes(noop))
+            .then(
+                CommandNode::literal("kick").then(
+                    CommandNode::argument("player", ArgumentParser::String(StringKind::Word))
+                        .executes(noop),
+                ),
+            );
+        let packet = Commands::new(&root);
+
+        assert_eq!(packet.root_index, VarInt::new(0));
+        let flags = packet
+            .nodes
+            .iter()
+            .map(|node| node.flags)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flags,
+            vec![ROOT, LITERAL | EXECUTABLE, LITERAL, ARGUMENT | EXECUTABLE]
+        );
+        assert_eq!(
+            packet.nodes[0].children,
+            vec![VarInt::new(1), VarInt::new(2)]
+        );
+        assert_eq!(packet.nodes[2].children, vec![VarInt::new(3)]);
+        assert_eq!(packet.nodes[3].name.as_deref(), Some("player"));
+    }
+
+    #[tokio::test]
+    async fn test_integer_parser_encoding() {
+        let parser = ArgumentParser::Integer {
+            min: None,
+            max: Some(64),
+        };
+        let mut bytes = Vec::new();
+        parser
+            .net_encode(&mut bytes, &EncodeOption::Default)
+            .await
+            .unwrap();
+        assert_eq!(bytes, vec![3, 0x02, 0, 0, 0, 64]);
+    }
+}
@@ -5,7 +5,7 @@ use ferrumc_macros::NetEncode;
 use crate::utils::encoding::position::Position;
 
 /// The default spawn position packet is sent by the server to the client to set the player's spawn position.
-#[derive(NetEncode)]
+#[derive(NetEncode, Clone)]
 pub struct DefaultSpawnPosition {
     #[encode(default = VarInt::from(0x50))]
     pub packet_id: VarInt,
@@ -0,0 +1,62 @@
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::AsyncWrite;
+
+use ferrumc_macros::NetEncode;
+
+use crate::net::packets::outgoing::update_teams::TeamColor;
+
+/// Shows a scoreboard objective in one of the display slots, e.g. the sidebar.
+#[derive(NetEncode, Clone)]
+pub struct DisplayObjective {
+    #[encode(default = VarInt::from(0x51))]
+    pub packet_id: VarInt,
+    pub position: DisplaySlot,
+    /// The objective to show. An empty name clears the slot.
+    pub score_name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplaySlot {
+    List,
+    Sidebar,
+    BelowName,
+    /// The sidebar, but only for players on a team of this color. Only the 16 colors work here,
+    /// not the formatting codes.
+    TeamSidebar(TeamColor),
+}
+
+impl DisplaySlot {
+    pub fn id(&self) -> u8 {
+        match self {
+            DisplaySlot::List => 0,
+            DisplaySlot::Sidebar => 1,
+            DisplaySlot::BelowName => 2,
+            DisplaySlot::TeamSidebar(color) => 3 + *color as u8,
+        }
+    }
+}
+
+impl NetEncode for DisplaySlot {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.id().net_encode(writer, encode_option).await
+    }
+}
+
+impl DisplayObjective {
+    pub fn new(position: DisplaySlot, score_name: &str) -> Self {
+        Self::new_auto(position, score_name.to_string())
+    }
+
+    /// Empties a display slot.
+    pub fn clear(position: DisplaySlot) -> Self {
+        Self::new(position, "")
+    }
+}
@@ -0,0 +1,51 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+
+/// Sent in online mode in response to [crate::net::packets::incoming::login_start::LoginStart],
+/// asking the client to authenticate with Mojang and pick a shared secret.
+///
+/// The client answers with [crate::net::packets::incoming::encryption_response::EncryptionResponse].
+#[derive(NetEncode)]
+pub struct EncryptionRequest {
+    #[encode(default = VarInt::from(0x01))]
+    pub packet_id: VarInt,
+    /// Always empty since 1.7.
+    pub server_id: String,
+    pub public_key_length: VarInt,
+    /// The server's public key, DER encoded.
+    pub public_key: Vec<u8>,
+    pub verify_token_length: VarInt,
+    /// Random bytes the client has to send back encrypted with the public key.
+    pub verify_token: Vec<u8>,
+}
+
+impl EncryptionRequest {
+    pub fn new(public_key: &[u8], verify_token: &[u8]) -> Self {
+        Self::new_auto(
+            String::new(),
+            VarInt::from(public_key.len() as i32),
+            public_key.to_vec(),
+            VarInt::from(verify_token.len() as i32),
+            verify_token.to_vec(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_encryption_request() {
+        let packet = EncryptionRequest::new(&[0x30, 0x81], &[1, 2, 3, 4]);
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::AlwaysOmitSize)
+            .await
+            .unwrap();
+
+        assert_eq!(buffer, vec![0x01, 0x00, 0x02, 0x30, 0x81, 0x04, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,41 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Tells the client which feature packs are enabled. Without `minecraft:vanilla` some content is
+/// disabled client-side.
+///
+/// There's no configuration state in 1.20.1, so this is sent in play, right after login.
+#[derive(NetEncode)]
+pub struct FeatureFlags {
+    #[encode(default = VarInt::from(0x6B))]
+    pub packet_id: VarInt,
+    #[encode(prepend_length = true)]
+    pub flags: Vec<String>,
+}
+
+impl FeatureFlags {
+    pub fn vanilla() -> Self {
+        Self::new_auto(vec!["minecraft:vanilla".to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_vanilla_feature_flag() {
+        let mut buffer = Vec::new();
+        FeatureFlags::vanilla()
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        let mut expected = vec![20, 0x6B, 0x01, 17];
+        expected.extend_from_slice(b"minecraft:vanilla");
+        assert_eq!(buffer, expected);
+    }
+}
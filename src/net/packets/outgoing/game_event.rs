@@ -0,0 +1,70 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::game_mode::GameMode;
+
+/// Tells the client about a change to the game state, like the weather. What `value` means
+/// depends on the event.
+#[derive(NetEncode, Clone, Debug)]
+pub struct GameEvent {
+    #[encode(default = VarInt::from(0x1F))]
+    pub packet_id: VarInt,
+    pub event: u8,
+    pub value: f32,
+}
+
+impl GameEvent {
+    /// wiki.vg calls this one "End raining", but the client starts the rain when it gets it.
+    pub const BEGIN_RAINING: u8 = 1;
+    pub const END_RAINING: u8 = 2;
+    pub const CHANGE_GAME_MODE: u8 = 3;
+    pub const RAIN_LEVEL_CHANGE: u8 = 7;
+    pub const THUNDER_LEVEL_CHANGE: u8 = 8;
+
+    pub fn new(event: u8, value: f32) -> Self {
+        Self::new_auto(event, value)
+    }
+
+    pub fn change_game_mode(game_mode: GameMode) -> Self {
+        Self::new(Self::CHANGE_GAME_MODE, game_mode.id() as f32)
+    }
+
+    pub fn begin_raining() -> Self {
+        Self::new(Self::BEGIN_RAINING, 0.0)
+    }
+
+    pub fn end_raining() -> Self {
+        Self::new(Self::END_RAINING, 0.0)
+    }
+
+    /// How hard it's raining, from 0 to 1.
+    pub fn rain_level(level: f32) -> Self {
+        Self::new(Self::RAIN_LEVEL_CHANGE, level)
+    }
+
+    /// How dark the sky is from the thunder, from 0 to 1.
+    pub fn thunder_level(level: f32) -> Self {
+        Self::new(Self::THUNDER_LEVEL_CHANGE, level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_rain_level() {
+        let mut buffer = Vec::new();
+        GameEvent::rain_level(0.5)
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        let mut expected = vec![0x06, 0x1F, GameEvent::RAIN_LEVEL_CHANGE];
+        expected.extend_from_slice(&0.5f32.to_be_bytes());
+        assert_eq!(buffer, expected);
+    }
+}
@@ -0,0 +1,78 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::network_types::varlong::Varlong;
+
+use ferrumc_macros::NetEncode;
+
+use crate::world::border::BorderSettings;
+
+/// Vanilla's limit on how far away a portal can send a player, regardless of the border.
+pub const PORTAL_TELEPORT_BOUNDARY: i32 = 29_999_984;
+
+/// Tells the client everything about the world border at once, when it joins or changes
+/// dimension.
+#[derive(NetEncode, Clone)]
+pub struct InitializeWorldBorder {
+    #[encode(default = VarInt::from(0x22))]
+    pub packet_id: VarInt,
+    pub x: f64,
+    pub z: f64,
+    pub old_diameter: f64,
+    pub new_diameter: f64,
+    /// How long until the border reaches the new diameter, in milliseconds.
+    pub speed: Varlong,
+    pub portal_teleport_boundary: VarInt,
+    pub warning_blocks: VarInt,
+    /// In seconds.
+    pub warning_time: VarInt,
+}
+
+impl InitializeWorldBorder {
+    /// The border as it is right now, along with the rest of its resize if it's changing size.
+    pub fn new(border: &BorderSettings) -> Self {
+        let (new_diameter, remaining) = border
+            .resize
+            .map(|resize| (resize.to, resize.remaining().as_millis() as i64))
+            .unwrap_or((border.diameter, 0));
+        Self::new_auto(
+            border.center_x,
+            border.center_z,
+            border.diameter,
+            new_diameter,
+            Varlong::new(remaining),
+            VarInt::from(PORTAL_TELEPORT_BOUNDARY),
+            VarInt::from(border.warning_blocks),
+            VarInt::from(border.warning_time),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_still_border() {
+        let border = BorderSettings {
+            center_x: 1.0,
+            center_z: 2.0,
+            diameter: 100.0,
+            ..Default::default()
+        };
+
+        let mut buffer = Vec::new();
+        InitializeWorldBorder::new(&border)
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        let mut expected = vec![0x28, 0x22];
+        for value in [1.0f64, 2.0, 100.0, 100.0] {
+            expected.extend_from_slice(&value.to_be_bytes());
+        }
+        // Not resizing, the portal boundary, then the warnings
+        expected.extend_from_slice(&[0x00, 0xF0, 0x86, 0xA7, 0x0E, 5, 15]);
+        assert_eq!(buffer, expected);
+    }
+}
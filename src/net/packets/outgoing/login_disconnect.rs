@@ -2,11 +2,19 @@ use ferrumc_codec::network_types::varint::VarInt;
 
 use ferrumc_macros::NetEncode;
 
+use crate::utils::text::TextComponent;
+
 /// The login disconnect packet is sent by the server to the client to disconnect the client.
 /// Used to cancel the login process.
 #[derive(NetEncode)]
 pub struct LoginDisconnect {
     #[encode(default = VarInt::from(0x00))]
     pub packet_id: VarInt,
-    pub reason: String,
+    pub reason: TextComponent,
+}
+
+impl LoginDisconnect {
+    pub fn new(reason: &TextComponent) -> Self {
+        Self::new_auto(reason.clone())
+    }
 }
@@ -0,0 +1,25 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Asks the client something on a plugin channel during login, like Velocity's forwarding query.
+/// Vanilla clients answer that they don't understand it, proxies answer the ones they handle.
+///
+/// This is the login state's Login Plugin Request, unlike
+/// [crate::net::packets::outgoing::plugin_message::PluginMessage], which is sent during play.
+#[derive(NetEncode)]
+pub struct LoginQueryRequest {
+    #[encode(default = VarInt::from(0x04))]
+    pub packet_id: VarInt,
+    /// Picked by the server, and sent back in the response.
+    pub message_id: VarInt,
+    pub channel: String,
+    /// The rest of the packet, in whatever format the channel uses.
+    pub data: Vec<u8>,
+}
+
+impl LoginQueryRequest {
+    pub fn new(message_id: i32, channel: impl Into<String>, data: Vec<u8>) -> Self {
+        Self::new_auto(VarInt::from(message_id), channel.into(), data)
+    }
+}
@@ -2,6 +2,8 @@ use ferrumc_codec::network_types::varint::VarInt;
 
 use ferrumc_macros::NetEncode;
 
+use crate::utils::components::game_profile::{GameProfile, ProfileProperty};
+
 /// Sent by the server to the client to start the play state.
 #[derive(NetEncode)]
 pub struct LoginSuccess {
@@ -15,11 +17,40 @@ pub struct LoginSuccess {
     pub properties: Vec<Property>,
 }
 
-#[derive(NetEncode)]
+#[derive(NetEncode, Clone, Debug)]
 pub struct Property {
     pub name: String,
     pub value: String,
     pub is_signed: bool,
     // Only if is_signed is true
-    pub signature: String,
+    pub signature: Option<String>,
+}
+
+impl From<&ProfileProperty> for Property {
+    fn from(property: &ProfileProperty) -> Self {
+        Self {
+            name: property.name.clone(),
+            value: property.value.clone(),
+            is_signed: property.signature.is_some(),
+            signature: property.signature.clone(),
+        }
+    }
+}
+
+impl LoginSuccess {
+    /// Tells the client which profile the session servers verified, skin included.
+    pub fn from_profile(profile: &GameProfile) -> Self {
+        let properties = profile
+            .properties
+            .iter()
+            .map(Property::from)
+            .collect::<Vec<_>>();
+
+        Self::new_auto(
+            profile.id.as_bytes().into(),
+            profile.name.clone(),
+            VarInt::from(properties.len() as i32),
+            properties,
+        )
+    }
 }
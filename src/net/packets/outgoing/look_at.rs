@@ -0,0 +1,120 @@
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::AsyncWrite;
+
+use ferrumc_macros::NetEncode;
+
+/// Turns the player so the given anchor on them points at a target, either a fixed position or an
+/// entity. Used by `/teleport ... facing` and similar.
+#[derive(NetEncode, Clone)]
+pub struct LookAt {
+    #[encode(default = VarInt::from(0x3B))]
+    pub packet_id: VarInt,
+    /// The part of the player that gets pointed at the target.
+    pub anchor: LookAnchor,
+    pub target_x: f64,
+    pub target_y: f64,
+    pub target_z: f64,
+    pub is_entity: bool,
+    /// Only written if `is_entity` is set. The client then ignores the coordinates above.
+    pub entity: Option<LookAtEntity>,
+}
+
+/// Which part of an entity a look at is measured from or aimed at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookAnchor {
+    Feet = 0,
+    Eyes = 1,
+}
+
+#[derive(NetEncode, Clone)]
+pub struct LookAtEntity {
+    pub entity_id: VarInt,
+    /// The part of the target entity to look at.
+    pub anchor: LookAnchor,
+}
+
+impl NetEncode for LookAnchor {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        VarInt::from(*self as i32)
+            .net_encode(writer, encode_option)
+            .await
+    }
+}
+
+impl LookAt {
+    /// Points the player's `anchor` at a fixed position.
+    pub fn position(anchor: LookAnchor, (x, y, z): (f64, f64, f64)) -> Self {
+        Self::new_auto(anchor, x, y, z, false, None)
+    }
+
+    /// Points the player's `anchor` at the `target_anchor` of another entity.
+    ///
+    /// The position is only used by clients that don't know about the entity, so pass its last
+    /// known position.
+    pub fn entity(
+        anchor: LookAnchor,
+        (x, y, z): (f64, f64, f64),
+        entity_id: i32,
+        target_anchor: LookAnchor,
+    ) -> Self {
+        Self::new_auto(
+            anchor,
+            x,
+            y,
+            z,
+            true,
+            Some(LookAtEntity {
+                entity_id: VarInt::from(entity_id),
+                anchor: target_anchor,
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_face_position() {
+        let packet = LookAt::position(LookAnchor::Eyes, (1.0, 64.0, -2.5));
+
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        let mut expected = vec![27, 0x3B, 0x01];
+        expected.extend_from_slice(&1.0f64.to_be_bytes());
+        expected.extend_from_slice(&64.0f64.to_be_bytes());
+        expected.extend_from_slice(&(-2.5f64).to_be_bytes());
+        expected.push(0x00);
+        assert_eq!(buffer, expected);
+    }
+
+    #[tokio::test]
+    async fn test_encode_face_entity() {
+        let packet = LookAt::entity(LookAnchor::Feet, (0.0, 0.0, 0.0), 300, LookAnchor::Eyes);
+
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        // Entity id (2 byte VarInt) and anchor after the flag
+        assert_eq!(buffer[0], 30);
+        assert_eq!(&buffer[buffer.len() - 4..], &[0x01, 0xAC, 0x02, 0x01]);
+    }
+}
@@ -1,12 +1,56 @@
+pub mod acknowledge_block_change;
+pub mod block_update;
 pub mod chunk_and_light_data;
+pub mod combat_death;
+pub mod commands;
 pub mod default_spawn_position;
+pub mod display_objective;
+pub mod encryption_request;
+pub mod feature_flags;
+pub mod game_event;
+pub mod initialize_world_border;
 pub mod keep_alive;
 pub mod login_disconnect;
 pub mod login_play;
-pub mod login_plugin_request;
+pub mod login_query_request;
 pub mod login_success;
+pub mod look_at;
+pub mod open_screen;
+pub mod pickup_item;
 pub mod ping;
+pub mod play_disconnect;
+pub mod player_chat;
+pub mod player_info_remove;
+pub mod player_info_update;
+pub mod plugin_message;
+pub mod remove_entities;
+pub mod resource_pack;
+pub mod respawn;
+pub mod set_border_center;
+pub mod set_border_lerp_size;
+pub mod set_border_size;
+pub mod set_border_warning_delay;
+pub mod set_border_warning_distance;
 pub mod set_center_chunk;
 pub mod set_compression;
+pub mod set_container_content;
+pub mod set_container_property;
+pub mod set_cooldown;
+pub mod set_entity_metadata;
+pub mod set_health;
+pub mod set_held_item;
+pub mod set_passengers;
+pub mod spawn_entity;
+pub mod spawn_player;
 pub mod status;
+pub mod stop_sound;
 pub mod synchronize_player_position;
+pub mod system_chat;
+pub mod teleport_entity;
+pub mod unload_chunk;
+pub mod update_entity_position;
+pub mod update_recipes;
+pub mod update_section_blocks;
+pub mod update_tags;
+pub mod update_teams;
+pub mod update_time;
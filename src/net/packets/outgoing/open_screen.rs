@@ -0,0 +1,27 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::open_container::WindowType;
+use crate::utils::text::TextComponent;
+
+/// Opens a container window on the client, like a chest or an anvil. Its contents follow in a
+/// [SetContainerContent](crate::net::packets::outgoing::set_container_content::SetContainerContent).
+#[derive(NetEncode, Clone)]
+pub struct OpenScreen {
+    #[encode(default = VarInt::from(0x30))]
+    pub packet_id: VarInt,
+    pub window_id: VarInt,
+    pub window_type: VarInt,
+    pub title: TextComponent,
+}
+
+impl OpenScreen {
+    pub fn new(window_id: u8, window_type: WindowType, title: &TextComponent) -> Self {
+        Self::new_auto(
+            VarInt::from(window_id as i32),
+            VarInt::from(window_type as i32),
+            title.clone(),
+        )
+    }
+}
@@ -0,0 +1,47 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Plays the pickup animation of an item or XP orb flying to the entity that collected it.
+///
+/// This doesn't remove the collected entity, that has to be done separately.
+#[derive(NetEncode)]
+pub struct PickupItem {
+    #[encode(default = VarInt::from(0x67))]
+    pub packet_id: VarInt,
+    pub collected_entity_id: VarInt,
+    pub collector_entity_id: VarInt,
+    /// Only used for items, XP orbs always send 1.
+    pub pickup_count: VarInt,
+}
+
+impl PickupItem {
+    pub fn new(collected_entity_id: i32, collector_entity_id: i32, pickup_count: i32) -> Self {
+        Self::new_auto(
+            collected_entity_id.into(),
+            collector_entity_id.into(),
+            pickup_count.into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_single_item_pickup() {
+        let packet = PickupItem::new(42, 7, 1);
+
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        // Length, packet id, collected, collector, count
+        assert_eq!(buffer, vec![0x04, 0x67, 42, 7, 1]);
+    }
+}
@@ -0,0 +1,61 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::text::TextComponent;
+
+/// Disconnects a player that's in game, showing them the reason. The login state has its own
+/// version of this, [super::login_disconnect::LoginDisconnect].
+#[derive(NetEncode)]
+pub struct PlayDisconnect {
+    #[encode(default = VarInt::from(0x1A))]
+    pub packet_id: VarInt,
+    pub reason: TextComponent,
+}
+
+impl PlayDisconnect {
+    pub fn new(reason: &TextComponent) -> Self {
+        Self::new_auto(reason.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::*;
+    use crate::net::packets::outgoing::login_disconnect::LoginDisconnect;
+    use crate::net::{frame_packet, State};
+    use crate::tests::connections::add_play_connection;
+
+    async fn expect_frame(client: &mut TcpStream, expected: Vec<u8>) {
+        let mut received = vec![0u8; expected.len()];
+        client.read_exact(&mut received).await.unwrap();
+        assert_eq!(received, expected);
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_packet_follows_connection_state() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let (player, mut client) = add_play_connection(&state).await;
+        let conn = state.connections.get_connection(player).unwrap();
+        let reason = TextComponent::text("Bye");
+
+        conn.read().await.send_disconnect(&reason).await.unwrap();
+        let play = frame_packet(PlayDisconnect::new(&reason), None)
+            .await
+            .unwrap();
+        expect_frame(&mut client, play).await;
+
+        // Players that are still logging in get the login state's version
+        conn.write().await.state = State::Login;
+        conn.read().await.send_disconnect(&reason).await.unwrap();
+        let login = frame_packet(LoginDisconnect::new(&reason), None)
+            .await
+            .unwrap();
+        expect_frame(&mut client, login).await;
+    }
+}
@@ -0,0 +1,59 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::text::TextComponent;
+
+/// A chat message sent by a player, which the client formats itself with the chat type's
+/// decoration, like `<player> message`.
+///
+/// Messages are always sent unsigned, without a previous message chain, so they're shown as
+/// not secure by clients that care. The sender has to be on the client's tab list, or the
+/// message is dropped.
+#[derive(NetEncode, Clone)]
+pub struct PlayerChatMessage {
+    #[encode(default = VarInt::from(0x35))]
+    pub packet_id: VarInt,
+    pub sender: u128,
+    /// Where the message is in the sender's message chain, which unsigned messages don't have.
+    #[encode(default = VarInt::from(0))]
+    pub index: VarInt,
+    #[encode(default = false)]
+    pub has_signature: bool,
+    pub message: String,
+    pub timestamp: i64,
+    pub salt: i64,
+    #[encode(default = VarInt::from(0))]
+    pub previous_messages: VarInt,
+    #[encode(default = false)]
+    pub has_unsigned_content: bool,
+    /// 0 lets the message through unfiltered.
+    #[encode(default = VarInt::from(0))]
+    pub filter_type: VarInt,
+    /// The ID of the chat type in the registry codec.
+    pub chat_type: VarInt,
+    /// The sender's name.
+    pub sender_name: TextComponent,
+    #[encode(default = false)]
+    pub has_target_name: bool,
+}
+
+impl PlayerChatMessage {
+    pub fn new(
+        sender: u128,
+        sender_name: &str,
+        message: String,
+        timestamp: i64,
+        salt: i64,
+        chat_type: i32,
+    ) -> Self {
+        Self::new_auto(
+            sender,
+            message,
+            timestamp,
+            salt,
+            VarInt::from(chat_type),
+            TextComponent::text(sender_name),
+        )
+    }
+}
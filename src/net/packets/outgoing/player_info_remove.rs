@@ -0,0 +1,47 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use uuid::Uuid;
+
+/// Removes players from the tab list, e.g. once they've left the server.
+#[derive(NetEncode, Clone)]
+pub struct PlayerInfoRemove {
+    #[encode(default = VarInt::from(0x39))]
+    pub packet_id: VarInt,
+    pub uuid_count: VarInt,
+    pub uuids: Vec<u128>,
+}
+
+impl PlayerInfoRemove {
+    pub fn new(uuids: &[Uuid]) -> Self {
+        Self::new_auto(
+            VarInt::from(uuids.len() as i32),
+            uuids.iter().map(Uuid::as_u128).collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_two_players() {
+        let first = Uuid::from_u128(0x0123456789abcdef0123456789abcdef);
+        let second = Uuid::from_u128(1);
+        let packet = PlayerInfoRemove::new(&[first, second]);
+
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        // Length, packet id, number of players, then both UUIDs as two big endian longs each
+        let mut expected = vec![34, 0x39, 0x02];
+        expected.extend_from_slice(first.as_bytes());
+        expected.extend_from_slice(second.as_bytes());
+        assert_eq!(buffer, expected);
+    }
+}
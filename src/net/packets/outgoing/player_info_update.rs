@@ -0,0 +1,161 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_macros::NetEncode;
+use uuid::Uuid;
+
+use crate::net::packets::outgoing::login_success::Property;
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::game_profile::GameProfile;
+
+/// Adds the player to the tab list, with their name and skin.
+pub const ADD_PLAYER: u8 = 0x01;
+pub const UPDATE_GAME_MODE: u8 = 0x04;
+/// Whether the player shows up in the tab list at all.
+pub const UPDATE_LISTED: u8 = 0x08;
+pub const UPDATE_LATENCY: u8 = 0x10;
+
+/// Adds players to the tab list, or updates the ones already on it.
+///
+/// `actions` says which of the fields every entry has, so all the entries in one packet have to
+/// carry the same ones. The constructors take care of that.
+#[derive(NetEncode, Clone)]
+pub struct PlayerInfoUpdate {
+    #[encode(default = VarInt::from(0x3A))]
+    pub packet_id: VarInt,
+    pub actions: u8,
+    #[encode(prepend_length = true)]
+    pub players: Vec<PlayerInfoEntry>,
+}
+
+/// A single player's part of a [PlayerInfoUpdate]. The fields left out are the ones the actions
+/// don't cover.
+#[derive(NetEncode, Clone, Debug)]
+pub struct PlayerInfoEntry {
+    pub uuid: u128,
+    pub add_player: Option<AddPlayer>,
+    pub game_mode: Option<VarInt>,
+    pub listed: Option<bool>,
+    /// In milliseconds.
+    pub latency: Option<VarInt>,
+}
+
+#[derive(NetEncode, Clone, Debug)]
+pub struct AddPlayer {
+    pub name: String,
+    /// The player's skin and cape, among others.
+    #[encode(prepend_length = true)]
+    pub properties: Vec<Property>,
+}
+
+impl PlayerInfoEntry {
+    /// Everything the tab list shows about a player who's just been added to it.
+    pub fn new(profile: &GameProfile, game_mode: GameMode, latency_ms: i32) -> Self {
+        Self {
+            uuid: profile.id.as_u128(),
+            add_player: Some(AddPlayer {
+                name: profile.name.clone(),
+                properties: profile.properties.iter().map(Property::from).collect(),
+            }),
+            game_mode: Some(VarInt::from(game_mode.id() as i32)),
+            listed: Some(true),
+            latency: Some(VarInt::from(latency_ms)),
+        }
+    }
+
+    fn empty(uuid: Uuid) -> Self {
+        Self {
+            uuid: uuid.as_u128(),
+            add_player: None,
+            game_mode: None,
+            listed: None,
+            latency: None,
+        }
+    }
+}
+
+impl PlayerInfoUpdate {
+    /// Adds players to the tab list, from entries made with [PlayerInfoEntry::new].
+    pub fn add_players(players: Vec<PlayerInfoEntry>) -> Self {
+        Self::new_auto(
+            ADD_PLAYER | UPDATE_GAME_MODE | UPDATE_LISTED | UPDATE_LATENCY,
+            players,
+        )
+    }
+
+    pub fn update_game_mode(uuid: Uuid, game_mode: GameMode) -> Self {
+        let entry = PlayerInfoEntry {
+            game_mode: Some(VarInt::from(game_mode.id() as i32)),
+            ..PlayerInfoEntry::empty(uuid)
+        };
+        Self::new_auto(UPDATE_GAME_MODE, vec![entry])
+    }
+
+    /// Updates the ping bars of several players, with their latency in milliseconds.
+    pub fn update_latency(latencies: &[(Uuid, i32)]) -> Self {
+        let entries = latencies
+            .iter()
+            .map(|&(uuid, latency_ms)| PlayerInfoEntry {
+                latency: Some(VarInt::from(latency_ms)),
+                ..PlayerInfoEntry::empty(uuid)
+            })
+            .collect();
+        Self::new_auto(UPDATE_LATENCY, entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+    use crate::utils::components::game_profile::ProfileProperty;
+
+    #[tokio::test]
+    async fn test_encode_add_player() {
+        let uuid = Uuid::from_u128(0x0123456789abcdef0123456789abcdef);
+        let profile = GameProfile {
+            id: uuid,
+            name: "Steve".to_string(),
+            properties: vec![ProfileProperty {
+                name: "textures".to_string(),
+                value: "abc".to_string(),
+                signature: None,
+            }],
+        };
+        let packet = PlayerInfoUpdate::add_players(vec![PlayerInfoEntry::new(
+            &profile,
+            GameMode::Survival,
+            42,
+        )]);
+
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::AlwaysOmitSize)
+            .await
+            .unwrap();
+
+        let mut expected = vec![0x3A, 0x1D, 0x01];
+        expected.extend_from_slice(uuid.as_bytes());
+        // Name, then one unsigned property
+        expected.extend_from_slice(b"\x05Steve\x01\x08textures\x03abc\x00");
+        // Game mode, listed and latency
+        expected.extend_from_slice(&[0x00, 0x01, 42]);
+        assert_eq!(buffer, expected);
+    }
+
+    #[tokio::test]
+    async fn test_encode_latency_update() {
+        let uuid = Uuid::from_u128(1);
+        let packet = PlayerInfoUpdate::update_latency(&[(uuid, 300)]);
+
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::AlwaysOmitSize)
+            .await
+            .unwrap();
+
+        let mut expected = vec![0x3A, UPDATE_LATENCY, 0x01];
+        expected.extend_from_slice(uuid.as_bytes());
+        expected.extend_from_slice(&[0xAC, 0x02]);
+        assert_eq!(buffer, expected);
+    }
+}
@@ -2,25 +2,31 @@ use ferrumc_codec::enc::{EncodeOption, NetEncode};
 use ferrumc_codec::network_types::varint::VarInt;
 use ferrumc_macros::NetEncode;
 
+use crate::net::utils::plugin_channels::BRAND;
+
+/// Sends data on a plugin channel, like the server's brand on `minecraft:brand`.
 #[derive(NetEncode)]
-pub struct LoginPluginRequest {
+pub struct PluginMessage {
     #[encode(default = VarInt::from(0x17))]
     pub packet_id: VarInt,
     pub channel: String,
+    /// The rest of the packet, in whatever format the channel uses.
     pub data: Vec<u8>,
 }
 
-impl LoginPluginRequest {
+impl PluginMessage {
     pub fn new(channel: impl Into<String>, data: Vec<u8>) -> Self {
         Self::new_auto(channel.into(), data)
     }
 
-    pub async fn server_brand(data: impl Into<String>) -> Self {
+    /// The server's brand, shown in the client's debug screen.
+    pub async fn brand(brand: impl Into<String>) -> Self {
         let mut str_buffer = Vec::new();
-        data.into()
+        brand
+            .into()
             .net_encode(&mut str_buffer, &EncodeOption::Default)
             .await
-            .expect("tf");
-        Self::new("minecraft:brand", str_buffer)
+            .expect("Encoding into a Vec can't fail");
+        Self::new(BRAND, str_buffer)
     }
 }
@@ -0,0 +1,23 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Despawns entities on the client, e.g. once they're out of range.
+#[derive(NetEncode, Clone)]
+pub struct RemoveEntities {
+    #[encode(default = VarInt::from(0x3E))]
+    pub packet_id: VarInt,
+    #[encode(prepend_length = true)]
+    pub entity_ids: Vec<VarInt>,
+}
+
+impl RemoveEntities {
+    pub fn new(entity_ids: &[usize]) -> Self {
+        Self::new_auto(
+            entity_ids
+                .iter()
+                .map(|&id| VarInt::new(id as i32))
+                .collect(),
+        )
+    }
+}
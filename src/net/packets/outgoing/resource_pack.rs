@@ -0,0 +1,93 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::config::ResourcePackConfig;
+use crate::utils::text::TextComponent;
+
+/// Asks the client to download a resource pack and apply it. The client answers with a
+/// [crate::net::packets::incoming::resource_pack_response::ResourcePackResponse].
+#[derive(NetEncode)]
+pub struct ResourcePack {
+    #[encode(default = VarInt::from(0x40))]
+    pub packet_id: VarInt,
+    pub url: String,
+    /// The pack's SHA-1 hash as 40 lowercase hex digits, so the client can tell if it already has
+    /// it. Empty makes the client download it every time.
+    pub hash: String,
+    /// Only lets the player choose to accept the pack or leave, instead of declining it.
+    pub forced: bool,
+    pub has_prompt: bool,
+    /// Shown along with the question. Only written if `has_prompt` is set.
+    pub prompt: Option<TextComponent>,
+}
+
+impl ResourcePack {
+    pub fn new(
+        url: impl Into<String>,
+        hash: impl Into<String>,
+        forced: bool,
+        prompt: Option<&TextComponent>,
+    ) -> Self {
+        Self::new_auto(
+            url.into(),
+            hash.into(),
+            forced,
+            prompt.is_some(),
+            prompt.cloned(),
+        )
+    }
+
+    /// The pack from the config, if there's one set.
+    pub fn from_config(config: &ResourcePackConfig) -> Option<Self> {
+        if config.url.is_empty() {
+            return None;
+        }
+        let prompt = (!config.prompt.is_empty()).then(|| TextComponent::text(&config.prompt));
+        Some(Self::new(
+            &config.url,
+            config.sha1.to_lowercase(),
+            config.required,
+            prompt.as_ref(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_resource_pack() {
+        let packet = ResourcePack::new("http://a/p.zip", "", true, None);
+
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        let mut expected = vec![19, 0x40, 14];
+        expected.extend_from_slice(b"http://a/p.zip");
+        // Empty hash, forced, no prompt
+        expected.extend_from_slice(&[0, 1, 0]);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn test_pack_from_config() {
+        let mut config = ResourcePackConfig::default();
+        assert!(ResourcePack::from_config(&config).is_none());
+
+        config.url = "https://example.com/pack.zip".to_string();
+        config.sha1 = "DA39A3EE5E6B4B0D3255BFEF95601890AFD80709".to_string();
+        config.prompt = "Please accept".to_string();
+        let packet = ResourcePack::from_config(&config).unwrap();
+        assert_eq!(packet.hash, "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert!(!packet.forced);
+        assert!(packet.has_prompt);
+        assert_eq!(packet.prompt.unwrap().plain_text(), "Please accept");
+    }
+}
@@ -0,0 +1,76 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::game_mode::GameMode;
+use crate::world::dimension::Dimension;
+
+/// Sent by the server to respawn the player, or to move them to another dimension.
+///
+/// The client throws away all loaded chunks when it receives this, so they have to be sent again.
+#[derive(NetEncode)]
+pub struct Respawn {
+    #[encode(default = VarInt::from(0x41))]
+    pub packet_id: VarInt,
+    pub dimension_type: String,
+    pub dimension_name: String,
+    pub seed_hash: i64,
+    pub gamemode: u8,
+    pub previous_gamemode: i8,
+    pub is_debug: bool,
+    pub is_flat: bool,
+    /// Bit mask. 0x01: keep attributes, 0x02: keep metadata.
+    pub data_kept: u8,
+    pub has_death_location: bool,
+    // pub death_dimension_name: Option<String>,
+    // pub death_location: Option<Position>,
+    pub portal_cooldown: VarInt,
+}
+
+impl Respawn {
+    /// Respawns a dead player in the overworld, keeping the game mode they died in.
+    pub fn after_death(game_mode: GameMode) -> Self {
+        Self::new_auto(
+            Dimension::Overworld.name().to_string(),
+            Dimension::Overworld.name().to_string(),
+            0,
+            game_mode.id(),
+            -1,
+            false,
+            false,
+            0,
+            false,
+            VarInt::new(0),
+        )
+    }
+
+    /// Moves the player to another dimension. Unlike respawning after dying, they keep their
+    /// attributes and metadata.
+    pub fn change_dimension(dimension: Dimension, game_mode: GameMode) -> Self {
+        Self::new_auto(
+            dimension.name().to_string(),
+            dimension.name().to_string(),
+            0,
+            game_mode.id(),
+            -1,
+            false,
+            false,
+            0x03,
+            false,
+            VarInt::new(0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_respawn_keeps_game_mode() {
+        let respawn = Respawn::after_death(GameMode::Survival);
+        assert_eq!(respawn.gamemode, GameMode::Survival.id());
+        assert_eq!(respawn.dimension_name, "minecraft:overworld");
+        assert_eq!(respawn.data_kept, 0);
+    }
+}
@@ -0,0 +1,22 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::network_types::varlong::Varlong;
+
+use ferrumc_macros::NetEncode;
+
+/// Starts the world border growing or shrinking to a new diameter, which the client animates on
+/// its own.
+#[derive(NetEncode, Clone)]
+pub struct SetBorderLerpSize {
+    #[encode(default = VarInt::from(0x48))]
+    pub packet_id: VarInt,
+    pub old_diameter: f64,
+    pub new_diameter: f64,
+    /// How long until the border reaches the new diameter, in milliseconds.
+    pub speed: Varlong,
+}
+
+impl SetBorderLerpSize {
+    pub fn new(old_diameter: f64, new_diameter: f64, millis: i64) -> Self {
+        Self::new_auto(old_diameter, new_diameter, Varlong::new(millis))
+    }
+}
@@ -0,0 +1,18 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// How long before a shrinking world border reaches the player their screen starts turning red.
+#[derive(NetEncode, Clone)]
+pub struct SetBorderWarningDelay {
+    #[encode(default = VarInt::from(0x4A))]
+    pub packet_id: VarInt,
+    /// In seconds.
+    pub warning_time: VarInt,
+}
+
+impl SetBorderWarningDelay {
+    pub fn new(warning_time: i32) -> Self {
+        Self::new_auto(VarInt::from(warning_time))
+    }
+}
@@ -0,0 +1,18 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// How close to the world border a player has to be for their screen to start turning red.
+#[derive(NetEncode, Clone)]
+pub struct SetBorderWarningDistance {
+    #[encode(default = VarInt::from(0x4B))]
+    pub packet_id: VarInt,
+    /// In blocks.
+    pub warning_blocks: VarInt,
+}
+
+impl SetBorderWarningDistance {
+    pub fn new(warning_blocks: i32) -> Self {
+        Self::new_auto(VarInt::from(warning_blocks))
+    }
+}
@@ -0,0 +1,50 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::open_container::OpenContainer;
+use crate::utils::encoding::slot::Slot;
+
+/// Replaces every slot of a container window, along with the item held on the cursor.
+///
+/// Also used to bring a client back in sync after it acted on an outdated view of the container.
+#[derive(NetEncode, Clone)]
+pub struct SetContainerContent {
+    #[encode(default = VarInt::from(0x12))]
+    pub packet_id: VarInt,
+    pub window_id: u8,
+    /// The client sends this back with its next click, so the server can tell whether the click
+    /// was made against the latest contents.
+    pub state_id: VarInt,
+    pub slot_count: VarInt,
+    pub slots: Vec<Slot>,
+    pub carried_item: Slot,
+}
+
+impl SetContainerContent {
+    /// The full contents of an open container, with the player's inventory below it.
+    pub fn of(container: &OpenContainer, inventory: &Inventory) -> Self {
+        let mut slots = container.window_slots(inventory);
+        // The off hand comes last, and isn't part of the window
+        slots.pop();
+        Self::new_auto(
+            container.window_id,
+            VarInt::from(container.state_id),
+            VarInt::from(slots.len() as i32),
+            slots,
+            container.carried_item.clone(),
+        )
+    }
+
+    /// The full contents of the player's own inventory, which is always window 0.
+    pub fn inventory(inventory: &Inventory) -> Self {
+        Self::new_auto(
+            0,
+            VarInt::from(inventory.state_id),
+            VarInt::from(inventory.slots.len() as i32),
+            inventory.slots.clone(),
+            inventory.carried_item.clone(),
+        )
+    }
+}
@@ -0,0 +1,75 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Updates a single property of an open container, mostly used to drive progress bars such as
+/// furnace arrows or brewing stand bubbles.
+///
+/// What `property` and `value` mean depends entirely on the type of container.
+#[derive(NetEncode)]
+pub struct SetContainerProperty {
+    #[encode(default = VarInt::from(0x13))]
+    pub packet_id: VarInt,
+    pub window_id: u8,
+    pub property: i16,
+    pub value: i16,
+}
+
+/// The properties a furnace (or smoker / blast furnace) window understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i16)]
+pub enum FurnaceProperty {
+    /// Ticks of fuel left, drawn as the flame.
+    FuelLeft = 0,
+    /// How many ticks the current fuel item burns for in total.
+    MaxFuelBurnTime = 1,
+    /// Ticks the current item has been cooking for, drawn as the arrow.
+    CookProgress = 2,
+    /// Ticks it takes to cook an item, 200 for a regular furnace.
+    MaxCookProgress = 3,
+}
+
+impl SetContainerProperty {
+    pub fn new(window_id: u8, property: i16, value: i16) -> Self {
+        Self::new_auto(window_id, property, value)
+    }
+
+    pub fn furnace(window_id: u8, property: FurnaceProperty, value: i16) -> Self {
+        Self::new(window_id, property as i16, value)
+    }
+
+    /// Both updates needed to draw a furnace's cook arrow at `progress` out of `total` ticks.
+    pub fn furnace_cook_progress(window_id: u8, progress: i16, total: i16) -> [Self; 2] {
+        [
+            Self::furnace(window_id, FurnaceProperty::CookProgress, progress),
+            Self::furnace(window_id, FurnaceProperty::MaxCookProgress, total),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_furnace_cook_progress() {
+        let [progress, total] = SetContainerProperty::furnace_cook_progress(1, 100, 200);
+
+        let mut buffer = Vec::new();
+        progress
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+        // Length, packet id, window id, property, value
+        assert_eq!(buffer, vec![0x06, 0x13, 0x01, 0x00, 0x02, 0x00, 100]);
+
+        let mut buffer = Vec::new();
+        total
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+        assert_eq!(buffer, vec![0x06, 0x13, 0x01, 0x00, 0x03, 0x00, 200]);
+    }
+}
@@ -0,0 +1,62 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+
+/// How long vanilla keeps ender pearls on cooldown after one is thrown.
+pub const ENDER_PEARL_COOLDOWN_TICKS: i32 = 20;
+
+/// Greys out every item of the given type in the client's inventory for a number of ticks,
+/// preventing it from being used again until the cooldown runs out.
+///
+/// Sending a cooldown of 0 ticks clears any cooldown that is still running.
+#[derive(NetEncode)]
+pub struct SetCooldown {
+    #[encode(default = VarInt::from(0x15))]
+    pub packet_id: VarInt,
+    pub item_id: VarInt,
+    pub cooldown_ticks: VarInt,
+}
+
+impl SetCooldown {
+    pub fn new(item_id: i32, cooldown_ticks: i32) -> Self {
+        Self::new_auto(item_id.into(), cooldown_ticks.into())
+    }
+}
+
+/// Puts an item on cooldown for a single player, e.g. right after they used it.
+pub async fn trigger_item_cooldown(
+    conn_id: ConnectionId,
+    state: &GlobalState,
+    item_id: i32,
+    cooldown_ticks: i32,
+) -> Result<()> {
+    let conn = state.connections.get_connection(conn_id)?;
+    let conn = conn.read().await;
+    conn.send_packet(SetCooldown::new(item_id, cooldown_ticks))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_twenty_tick_cooldown() {
+        let packet = SetCooldown::new(200, 20);
+
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        // Length, packet id, item id (2 byte VarInt), ticks
+        assert_eq!(buffer, vec![0x04, 0x15, 0xC8, 0x01, 20]);
+    }
+}
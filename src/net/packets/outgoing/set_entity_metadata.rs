@@ -0,0 +1,104 @@
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::AsyncWrite;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::components::entity_flags::{EntityFlags, Pose};
+
+/// Metadata index of the entity flags byte, shared by every entity.
+pub const ENTITY_FLAGS_INDEX: u8 = 0;
+/// Metadata index of the entity's pose, shared by every entity.
+pub const POSE_INDEX: u8 = 6;
+
+/// Updates some of an entity's metadata. Only the entries that changed have to be sent, the
+/// client keeps the rest as they were.
+#[derive(NetEncode, Clone)]
+pub struct SetEntityMetadata {
+    #[encode(default = VarInt::from(0x52))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub entries: Vec<MetadataEntry>,
+    /// Marks the end of the entries.
+    #[encode(default = 0xFF)]
+    pub terminator: u8,
+}
+
+#[derive(NetEncode, Clone)]
+pub struct MetadataEntry {
+    pub index: u8,
+    pub value_type: VarInt,
+    pub value: MetadataValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetadataValue {
+    Byte(u8),
+    VarInt(VarInt),
+    Float(f32),
+    Boolean(bool),
+    Pose(Pose),
+}
+
+impl MetadataValue {
+    /// The id of this value's type in the protocol.
+    pub fn type_id(&self) -> i32 {
+        match self {
+            MetadataValue::Byte(_) => 0,
+            MetadataValue::VarInt(_) => 1,
+            MetadataValue::Float(_) => 3,
+            MetadataValue::Boolean(_) => 8,
+            MetadataValue::Pose(_) => 20,
+        }
+    }
+}
+
+impl NetEncode for MetadataValue {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        match self {
+            MetadataValue::Byte(value) => value.net_encode(writer, encode_option).await,
+            MetadataValue::VarInt(value) => value.net_encode(writer, encode_option).await,
+            MetadataValue::Float(value) => value.net_encode(writer, encode_option).await,
+            MetadataValue::Boolean(value) => value.net_encode(writer, encode_option).await,
+            MetadataValue::Pose(pose) => {
+                VarInt::from(*pose as i32)
+                    .net_encode(writer, encode_option)
+                    .await
+            }
+        }
+    }
+}
+
+impl MetadataEntry {
+    pub fn new(index: u8, value: MetadataValue) -> Self {
+        Self {
+            index,
+            value_type: VarInt::from(value.type_id()),
+            value,
+        }
+    }
+}
+
+impl SetEntityMetadata {
+    pub fn new(entity_id: i32, entries: Vec<MetadataEntry>) -> Self {
+        Self::new_auto(entity_id.into(), entries)
+    }
+
+    /// The flags byte and pose of an entity, e.g. after it started sneaking or sprinting.
+    pub fn entity_flags(entity_id: i32, flags: &EntityFlags) -> Self {
+        Self::new(
+            entity_id,
+            vec![
+                MetadataEntry::new(ENTITY_FLAGS_INDEX, MetadataValue::Byte(flags.flags)),
+                MetadataEntry::new(POSE_INDEX, MetadataValue::Pose(flags.pose())),
+            ],
+        )
+    }
+}
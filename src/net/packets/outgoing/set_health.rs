@@ -0,0 +1,55 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use tracing::debug;
+
+use ferrumc_macros::NetEncode;
+
+use crate::net::packets::outgoing::combat_death::CombatDeath;
+use crate::net::packets::ConnectionId;
+use crate::state::GlobalState;
+use crate::utils::components::health::Health;
+use crate::utils::prelude::*;
+
+/// Updates the health and hunger bars. The client shows the death screen on its own once health
+/// hits 0, but [CombatDeath] is what fills in the death message.
+#[derive(NetEncode)]
+pub struct SetHealth {
+    #[encode(default = VarInt::from(0x57))]
+    pub packet_id: VarInt,
+    pub health: f32,
+    pub food: VarInt,
+    pub food_saturation: f32,
+}
+
+impl SetHealth {
+    pub fn new(health: &Health) -> Self {
+        Self::new_auto(health.health, health.food.into(), health.saturation)
+    }
+}
+
+/// Changes a player's health and tells their client about it.
+///
+/// If this kills the player they're marked as dead and shown the death screen. Nothing else
+/// happens until they ask to respawn.
+pub async fn update_health(conn_id: ConnectionId, state: &GlobalState, health: f32) -> Result<()> {
+    let (packet, died) = {
+        let component_storage = state.world.get_component_storage();
+        let mut current = component_storage.get_mut::<Health>(conn_id).await?;
+        if current.is_dead() {
+            return Ok(());
+        }
+        let died = current.set_health(health);
+        (SetHealth::new(&current), died)
+    };
+
+    let conn = state.connections.get_connection(conn_id)?;
+    let conn = conn.read().await;
+    conn.send_packet(packet).await?;
+
+    if died {
+        debug!("Player {} died", conn_id);
+        conn.send_packet(CombatDeath::new(conn_id as i32, "You died!"))
+            .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Puts entities on another one, like a player in a boat. Replaces whatever was riding it before,
+/// so an empty list gets everyone off.
+#[derive(NetEncode, Clone)]
+pub struct SetPassengers {
+    #[encode(default = VarInt::from(0x59))]
+    pub packet_id: VarInt,
+    pub vehicle_id: VarInt,
+    #[encode(prepend_length = true)]
+    pub passenger_ids: Vec<VarInt>,
+}
+
+impl SetPassengers {
+    pub fn new(vehicle_id: usize, passenger_ids: &[usize]) -> Self {
+        Self::new_auto(
+            VarInt::new(vehicle_id as i32),
+            passenger_ids
+                .iter()
+                .map(|&id| VarInt::new(id as i32))
+                .collect(),
+        )
+    }
+}
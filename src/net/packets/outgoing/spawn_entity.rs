@@ -0,0 +1,56 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::net::packets::outgoing::teleport_entity::to_angle;
+use crate::utils::components::entity_info::EntityKind;
+
+/// Spawns any entity that isn't a player, which have [SpawnPlayer](super::spawn_player::SpawnPlayer)
+/// instead.
+#[derive(NetEncode, Clone)]
+pub struct SpawnEntity {
+    #[encode(default = VarInt::from(0x01))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub uuid: u128,
+    pub entity_type: VarInt,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub pitch: u8,
+    pub yaw: u8,
+    pub head_yaw: u8,
+    /// Depends on the entity type, e.g. the block state of a falling block.
+    pub data: VarInt,
+    /// In steps of 1/8000 of a block per tick.
+    pub velocity_x: i16,
+    pub velocity_y: i16,
+    pub velocity_z: i16,
+}
+
+impl SpawnEntity {
+    pub fn new(
+        entity_id: i32,
+        uuid: u128,
+        kind: EntityKind,
+        (x, y, z): (f64, f64, f64),
+        yaw: f32,
+        pitch: f32,
+    ) -> Self {
+        Self::new_auto(
+            entity_id.into(),
+            uuid,
+            kind.type_id().into(),
+            x,
+            y,
+            z,
+            to_angle(pitch),
+            to_angle(yaw),
+            to_angle(yaw),
+            VarInt::from(0),
+            0,
+            0,
+            0,
+        )
+    }
+}
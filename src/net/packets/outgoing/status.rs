@@ -1,7 +1,22 @@
+use base64::Engine;
 use ferrumc_codec::network_types::varint::VarInt;
+use rand::prelude::IndexedRandom;
+use serde::Serialize;
+use tokio::sync::OnceCell;
+use tracing::warn;
+use uuid::Uuid;
 
 use ferrumc_macros::NetEncode;
 
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
+use crate::utils::constants::{FAVICON_FILE, GAME_VERSION, LEGACY_FAVICON_FILE, PROTOCOL_VERSION};
+use crate::utils::text::TextComponent;
+
+/// How many of the online players are listed when hovering over the player count, like vanilla.
+const MAX_SAMPLE_PLAYERS: usize = 12;
+
 /// The outgoing status response packet is sent by the server to the client to respond to a status request.
 /// Contains the JSON response.
 #[derive(NetEncode)]
@@ -10,3 +25,168 @@ pub struct OutgoingStatusResponse {
     pub packet_id: VarInt,
     pub json_response: String,
 }
+
+impl OutgoingStatusResponse {
+    /// The status of the server right now, as shown in the server list.
+    pub async fn for_server(state: &GlobalState) -> Self {
+        let config = get_global_config();
+        let motd = config
+            .motd
+            .choose(&mut rand::thread_rng())
+            .cloned()
+            .unwrap_or_default();
+
+        let mut online = Vec::new();
+        let query = state.world.query::<&Player>();
+        for (_, player) in query.iter().await {
+            online.push(SamplePlayer {
+                name: player.username.clone(),
+                id: Uuid::from_u128(player.uuid).hyphenated().to_string(),
+            });
+        }
+
+        let status = StatusJson::new(motd, config.max_players, online, favicon().await.clone());
+        Self::new_auto(serde_json::to_string(&status).expect("Status JSON is always valid"))
+    }
+}
+
+/// The JSON the server list shows the server with.
+#[derive(Serialize, Debug)]
+pub struct StatusJson {
+    pub version: StatusVersion,
+    pub players: StatusPlayers,
+    /// The MOTD.
+    pub description: TextComponent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
+    #[serde(rename = "enforcesSecureChat")]
+    pub enforces_secure_chat: bool,
+}
+
+/// The version the server runs. Clients on a different protocol show it as incompatible.
+#[derive(Serialize, Debug)]
+pub struct StatusVersion {
+    pub name: String,
+    pub protocol: i32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StatusPlayers {
+    pub max: i32,
+    pub online: i32,
+    pub sample: Vec<SamplePlayer>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SamplePlayer {
+    pub name: String,
+    pub id: String,
+}
+
+impl StatusJson {
+    /// Builds the status from the players that are online, listing a random few of them.
+    pub fn new(
+        motd: String,
+        max_players: i32,
+        online: Vec<SamplePlayer>,
+        favicon: Option<String>,
+    ) -> Self {
+        let sample = online
+            .choose_multiple(&mut rand::thread_rng(), MAX_SAMPLE_PLAYERS)
+            .cloned()
+            .collect();
+        Self {
+            version: StatusVersion {
+                name: GAME_VERSION.to_string(),
+                protocol: PROTOCOL_VERSION,
+            },
+            players: StatusPlayers {
+                max: max_players,
+                online: online.len() as i32,
+                sample,
+            },
+            description: TextComponent::text(motd),
+            favicon,
+            enforces_secure_chat: false,
+        }
+    }
+}
+
+/// The server icon, as the data URL the server list wants. `None` if there's no usable icon.
+/// Servers set up before `server-icon.png` was used still have theirs in `icon-64.png`.
+///
+/// This is cached in a `OnceCell` to avoid reading the file every time.
+async fn favicon() -> &'static Option<String> {
+    static FAVICON: OnceCell<Option<String>> = OnceCell::const_new();
+    FAVICON
+        .get_or_init(|| async {
+            for file in [FAVICON_FILE, LEGACY_FAVICON_FILE] {
+                let Ok(data) = tokio::fs::read(file).await else {
+                    continue;
+                };
+                return encode_favicon(&data)
+                    .inspect_err(|e| warn!("Not using {} as the server icon: {}", file, e))
+                    .ok();
+            }
+            None
+        })
+        .await
+}
+
+/// Checks that the image is a 64x64 PNG, which is the only thing the client shows, and encodes it
+/// as a data URL.
+pub fn encode_favicon(png: &[u8]) -> Result<String, String> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    // The signature, then the IHDR chunk's length and type, then its width and height
+    if png.len() < 24 || !png.starts_with(SIGNATURE) || &png[12..16] != b"IHDR" {
+        return Err("it isn't a PNG".to_string());
+    }
+    let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+    if (width, height) != (64, 64) {
+        return Err(format!("it's {}x{}, not 64x64", width, height));
+    }
+
+    let data = base64::engine::general_purpose::STANDARD.encode(png);
+    Ok(format!("data:image/png;base64,{}", data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend_from_slice(&width.to_be_bytes());
+        png.extend_from_slice(&height.to_be_bytes());
+        png
+    }
+
+    #[test]
+    fn test_favicon_must_be_64x64_png() {
+        let icon = encode_favicon(&png(64, 64)).unwrap();
+        assert!(icon.starts_with("data:image/png;base64,iVBORw0KGgo"));
+        assert!(encode_favicon(&png(128, 128)).is_err());
+        assert!(encode_favicon(b"GIF89a").is_err());
+    }
+
+    #[test]
+    fn test_status_json() {
+        let online = (0..20)
+            .map(|i| SamplePlayer {
+                name: format!("player{}", i),
+                id: Uuid::from_u128(i).hyphenated().to_string(),
+            })
+            .collect();
+        let status = StatusJson::new("Hello".to_string(), 50, online, None);
+        let json = serde_json::to_value(&status).unwrap();
+
+        assert_eq!(json["version"]["protocol"], PROTOCOL_VERSION);
+        assert_eq!(json["version"]["name"], GAME_VERSION);
+        assert_eq!(json["players"]["max"], 50);
+        assert_eq!(json["players"]["online"], 20);
+        assert_eq!(json["players"]["sample"].as_array().unwrap().len(), 12);
+        assert_eq!(json["description"]["text"], "Hello");
+        assert!(json.get("favicon").is_none());
+    }
+}
@@ -0,0 +1,106 @@
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::AsyncWrite;
+
+use ferrumc_macros::NetEncode;
+
+/// Stops sounds that are playing on the client. Which ones depends on what's given: everything,
+/// everything in a category, or one sound, optionally only in one category.
+#[derive(NetEncode, Clone)]
+pub struct StopSound {
+    #[encode(default = VarInt::from(0x63))]
+    pub packet_id: VarInt,
+    /// Says which of the two fields below are present.
+    pub flags: u8,
+    pub source: Option<SoundCategory>,
+    pub sound: Option<String>,
+}
+
+/// The sound categories, each with their own volume slider in the client's settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundCategory {
+    Master = 0,
+    Music = 1,
+    Record = 2,
+    Weather = 3,
+    Block = 4,
+    Hostile = 5,
+    Neutral = 6,
+    Player = 7,
+    Ambient = 8,
+    Voice = 9,
+}
+
+impl NetEncode for SoundCategory {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        VarInt::from(*self as i32)
+            .net_encode(writer, encode_option)
+            .await
+    }
+}
+
+impl StopSound {
+    pub const HAS_SOURCE: u8 = 0x01;
+    pub const HAS_SOUND: u8 = 0x02;
+
+    /// `sound` is the sound's identifier, e.g. `minecraft:music_disc.cat`.
+    pub fn new(source: Option<SoundCategory>, sound: Option<String>) -> Self {
+        let mut flags = 0;
+        if source.is_some() {
+            flags |= Self::HAS_SOURCE;
+        }
+        if sound.is_some() {
+            flags |= Self::HAS_SOUND;
+        }
+        Self::new_auto(flags, source, sound)
+    }
+
+    /// Stops every sound, in every category.
+    pub fn all() -> Self {
+        Self::new(None, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn encode(packet: StopSound) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+        buffer
+    }
+
+    #[tokio::test]
+    async fn test_encode_stop_all_sounds() {
+        assert_eq!(encode(StopSound::all()).await, vec![0x02, 0x63, 0x00]);
+    }
+
+    #[tokio::test]
+    async fn test_encode_stop_specific_sound() {
+        let sound = "minecraft:music_disc.cat";
+        let packet = StopSound::new(Some(SoundCategory::Record), Some(sound.to_string()));
+
+        // Length, packet id, flags, source, then the identifier as a string
+        let mut expected = vec![0x1C, 0x63, 0x03, 0x02, sound.len() as u8];
+        expected.extend_from_slice(sound.as_bytes());
+        assert_eq!(encode(packet).await, expected);
+
+        // Just the sound, from any source
+        let packet = StopSound::new(None, Some(sound.to_string()));
+        assert_eq!(
+            encode(packet).await[..4],
+            [0x1B, 0x63, 0x02, sound.len() as u8]
+        );
+    }
+}
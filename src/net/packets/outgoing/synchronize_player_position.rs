@@ -1,3 +1,4 @@
+use crate::utils::components::precise_position::PrecisePosition;
 use crate::utils::components::rotation::Rotation;
 use crate::utils::encoding::position::Position;
 use ferrumc_codec::network_types::varint::VarInt;
@@ -29,4 +30,14 @@ impl SynchronizePlayerPosition {
             teleport_id: VarInt::from(0),
         }
     }
+
+    /// Same as [SynchronizePlayerPosition::new], but to a position that isn't on the block grid.
+    pub fn precise(position: &PrecisePosition, rotation: &Rotation) -> Self {
+        Self {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+            ..Self::new(&position.block(), rotation)
+        }
+    }
 }
@@ -0,0 +1,26 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::text::TextComponent;
+
+/// A message from the server itself, rather than from a player. Shown in chat, or above the
+/// hotbar if `overlay` is set.
+#[derive(NetEncode, Clone)]
+pub struct SystemChatMessage {
+    #[encode(default = VarInt::from(0x64))]
+    pub packet_id: VarInt,
+    pub content: TextComponent,
+    pub overlay: bool,
+}
+
+impl SystemChatMessage {
+    pub fn new(content: &TextComponent) -> Self {
+        Self::new_auto(content.clone(), false)
+    }
+
+    /// Shows the message above the hotbar instead of in chat.
+    pub fn action_bar(content: &TextComponent) -> Self {
+        Self::new_auto(content.clone(), true)
+    }
+}
@@ -0,0 +1,57 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Moves an entity to an exact position. Unlike the relative move packets, this works for any
+/// distance.
+#[derive(NetEncode, Clone)]
+pub struct TeleportEntity {
+    #[encode(default = VarInt::from(0x68))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    /// In steps of 1/256 of a full turn, see [to_angle].
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
+
+impl TeleportEntity {
+    pub fn new(
+        entity_id: i32,
+        (x, y, z): (f64, f64, f64),
+        yaw: f32,
+        pitch: f32,
+        on_ground: bool,
+    ) -> Self {
+        Self::new_auto(
+            entity_id.into(),
+            x,
+            y,
+            z,
+            to_angle(yaw),
+            to_angle(pitch),
+            on_ground,
+        )
+    }
+}
+
+/// Converts degrees to the protocol's angle type, which wraps around every 256 steps.
+pub fn to_angle(degrees: f32) -> u8 {
+    (degrees.rem_euclid(360.0) * 256.0 / 360.0) as i32 as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_angles_wrap() {
+        assert_eq!(to_angle(0.0), 0);
+        assert_eq!(to_angle(90.0), 64);
+        assert_eq!(to_angle(-90.0), 192);
+        assert_eq!(to_angle(450.0), 64);
+    }
+}
@@ -0,0 +1,95 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+use crate::net::packets::outgoing::teleport_entity::to_angle;
+
+/// Moves an entity by less than 8 blocks along each axis. Anything further needs a
+/// [TeleportEntity](super::teleport_entity::TeleportEntity).
+#[derive(NetEncode, Clone)]
+pub struct UpdateEntityPosition {
+    #[encode(default = VarInt::from(0x2B))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    /// In steps of 1/4096 of a block.
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub delta_z: i16,
+    pub on_ground: bool,
+}
+
+impl UpdateEntityPosition {
+    pub fn new(
+        entity_id: i32,
+        (delta_x, delta_y, delta_z): (i16, i16, i16),
+        on_ground: bool,
+    ) -> Self {
+        Self::new_auto(entity_id.into(), delta_x, delta_y, delta_z, on_ground)
+    }
+}
+
+/// [UpdateEntityPosition] and [UpdateEntityRotation] in one.
+#[derive(NetEncode, Clone)]
+pub struct UpdateEntityPositionAndRotation {
+    #[encode(default = VarInt::from(0x2C))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub delta_z: i16,
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
+
+impl UpdateEntityPositionAndRotation {
+    pub fn new(
+        entity_id: i32,
+        (delta_x, delta_y, delta_z): (i16, i16, i16),
+        yaw: f32,
+        pitch: f32,
+        on_ground: bool,
+    ) -> Self {
+        Self::new_auto(
+            entity_id.into(),
+            delta_x,
+            delta_y,
+            delta_z,
+            to_angle(yaw),
+            to_angle(pitch),
+            on_ground,
+        )
+    }
+}
+
+/// Turns an entity's body. Its head is turned separately, with [SetHeadRotation].
+#[derive(NetEncode, Clone)]
+pub struct UpdateEntityRotation {
+    #[encode(default = VarInt::from(0x2D))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
+
+impl UpdateEntityRotation {
+    pub fn new(entity_id: i32, yaw: f32, pitch: f32, on_ground: bool) -> Self {
+        Self::new_auto(entity_id.into(), to_angle(yaw), to_angle(pitch), on_ground)
+    }
+}
+
+/// Turns an entity's head, which is where players are actually looking.
+#[derive(NetEncode, Clone)]
+pub struct SetHeadRotation {
+    #[encode(default = VarInt::from(0x42))]
+    pub packet_id: VarInt,
+    pub entity_id: VarInt,
+    pub head_yaw: u8,
+}
+
+impl SetHeadRotation {
+    pub fn new(entity_id: i32, head_yaw: f32) -> Self {
+        Self::new_auto(entity_id.into(), to_angle(head_yaw))
+    }
+}
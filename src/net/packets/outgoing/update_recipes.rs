@@ -0,0 +1,17 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Sends the recipes the client knows about. Recipes aren't supported yet, so this is always empty.
+#[derive(NetEncode)]
+pub struct UpdateRecipes {
+    #[encode(default = VarInt::from(0x6D))]
+    pub packet_id: VarInt,
+    pub recipe_count: VarInt,
+}
+
+impl UpdateRecipes {
+    pub fn empty() -> Self {
+        Self::new_auto(VarInt::new(0))
+    }
+}
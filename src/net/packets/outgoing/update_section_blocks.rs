@@ -0,0 +1,65 @@
+use ferrumc_codec::network_types::varint::VarInt;
+use ferrumc_codec::network_types::varlong::Varlong;
+
+use ferrumc_macros::NetEncode;
+
+/// Changes any number of blocks within a single chunk section. Also known as multi block change.
+#[derive(NetEncode, Clone)]
+pub struct UpdateSectionBlocks {
+    #[encode(default = VarInt::from(0x43))]
+    pub packet_id: VarInt,
+    /// The section's coordinates, packed like a block position but with 22 bits for x and z and
+    /// 20 for y.
+    pub section_position: i64,
+    pub block_count: VarInt,
+    /// Each one is the new block state id, followed by the block's x, z and y within the section
+    /// in the low 12 bits.
+    pub blocks: Vec<Varlong>,
+}
+
+impl UpdateSectionBlocks {
+    /// `blocks` are `(x, y, z, block state id)`, with the coordinates relative to the section.
+    pub fn new(
+        section_x: i32,
+        section_y: i32,
+        section_z: i32,
+        blocks: &[(u8, u8, u8, i32)],
+    ) -> Self {
+        let section_position = ((section_x as i64 & 0x3FFFFF) << 42)
+            | ((section_z as i64 & 0x3FFFFF) << 20)
+            | (section_y as i64 & 0xFFFFF);
+        let blocks = blocks
+            .iter()
+            .map(|&(x, y, z, state)| {
+                let local = ((x as i64 & 0xF) << 8) | ((z as i64 & 0xF) << 4) | (y as i64 & 0xF);
+                Varlong::new(((state as i64) << 12) | local)
+            })
+            .collect::<Vec<_>>();
+        Self::new_auto(section_position, VarInt::from(blocks.len() as i32), blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_update_section_blocks() {
+        // Section (1, -4, -1), block state 1 at (2, 3, 4) within it
+        let packet = UpdateSectionBlocks::new(1, -4, -1, &[(2, 3, 4, 1)]);
+        assert_eq!(packet.section_position, 0x0000_07FF_FFFF_FFFC);
+        assert_eq!(packet.blocks, vec![Varlong::new(0x1243)]);
+
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+        // Length, packet id, the position as a long, the count and the entry as a 2 byte VarLong
+        assert_eq!(buffer[..2], [0x0C, 0x43]);
+        assert_eq!(buffer[2..10], 0x0000_07FF_FFFF_FFFCi64.to_be_bytes());
+        assert_eq!(buffer[10..], [0x01, 0xC3, 0x24]);
+    }
+}
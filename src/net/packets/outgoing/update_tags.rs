@@ -0,0 +1,108 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Sends the tags for each registry, e.g. which blocks are `minecraft:logs`.
+///
+/// Some client features misbehave without at least an empty tag set, so a minimal set is sent on
+/// join.
+#[derive(NetEncode)]
+pub struct UpdateTags {
+    #[encode(default = VarInt::from(0x6E))]
+    pub packet_id: VarInt,
+    #[encode(prepend_length = true)]
+    pub registries: Vec<TagRegistry>,
+}
+
+/// All the tags for a single registry, e.g. `minecraft:block`.
+#[derive(NetEncode)]
+pub struct TagRegistry {
+    pub registry: String,
+    #[encode(prepend_length = true)]
+    pub tags: Vec<Tag>,
+}
+
+#[derive(NetEncode)]
+pub struct Tag {
+    pub name: String,
+    /// The numeric IDs of the entries in the registry.
+    #[encode(prepend_length = true)]
+    pub entries: Vec<VarInt>,
+}
+
+impl Tag {
+    pub fn new(name: impl Into<String>, entries: &[i32]) -> Self {
+        Self {
+            name: name.into(),
+            entries: entries.iter().map(|&id| VarInt::new(id)).collect(),
+        }
+    }
+}
+
+impl TagRegistry {
+    pub fn new(registry: impl Into<String>, tags: Vec<Tag>) -> Self {
+        Self {
+            registry: registry.into(),
+            tags,
+        }
+    }
+}
+
+impl UpdateTags {
+    pub fn new(registries: Vec<TagRegistry>) -> Self {
+        Self::new_auto(registries)
+    }
+
+    /// The smallest set of tags the client needs to behave.
+    ///
+    /// The fluid tags are needed for swimming and lava physics, the other registries are sent empty.
+    pub fn minimal() -> Self {
+        Self::new(vec![
+            TagRegistry::new("minecraft:block", vec![]),
+            TagRegistry::new("minecraft:item", vec![]),
+            TagRegistry::new(
+                "minecraft:fluid",
+                vec![
+                    // flowing_water, water
+                    Tag::new("minecraft:water", &[1, 2]),
+                    // flowing_lava, lava
+                    Tag::new("minecraft:lava", &[3, 4]),
+                ],
+            ),
+            TagRegistry::new("minecraft:entity_type", vec![]),
+            TagRegistry::new("minecraft:game_event", vec![]),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_block_tag() {
+        let packet = UpdateTags::new(vec![TagRegistry::new(
+            "minecraft:block",
+            vec![Tag::new("minecraft:logs", &[1, 300])],
+        )]);
+
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::AlwaysOmitSize)
+            .await
+            .unwrap();
+
+        let mut expected = vec![0x6E, 0x01];
+        expected.push(15);
+        expected.extend_from_slice(b"minecraft:block");
+        expected.push(0x01);
+        expected.push(14);
+        expected.extend_from_slice(b"minecraft:logs");
+        // 2 entries: 1, 300
+        expected.extend_from_slice(&[0x02, 0x01, 0xAC, 0x02]);
+
+        assert_eq!(buffer, expected);
+    }
+}
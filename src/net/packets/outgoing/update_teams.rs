@@ -0,0 +1,322 @@
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::AsyncWrite;
+
+use ferrumc_macros::NetEncode;
+
+use crate::utils::text::TextComponent;
+
+/// Creates, changes or removes a scoreboard team. Teams decide the color of their members' names,
+/// whether they push each other around and who can see their name tags.
+///
+/// What's in the packet depends on the mode, so use the constructors rather than `new_auto`.
+#[derive(NetEncode, Clone)]
+pub struct UpdateTeams {
+    #[encode(default = VarInt::from(0x5A))]
+    pub packet_id: VarInt,
+    pub team_name: String,
+    pub mode: TeamMode,
+    /// Only written when creating a team or updating its info.
+    pub info: Option<TeamInfo>,
+    /// Only written when creating a team or adding or removing members.
+    pub members: Option<TeamMembers>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeamMode {
+    Create = 0,
+    Remove = 1,
+    UpdateInfo = 2,
+    AddEntities = 3,
+    RemoveEntities = 4,
+}
+
+/// Everything about a team except who's in it.
+#[derive(NetEncode, Clone)]
+pub struct TeamInfo {
+    pub display_name: TextComponent,
+    /// A mix of [TeamInfo::ALLOW_FRIENDLY_FIRE] and [TeamInfo::SEE_INVISIBLE_TEAMMATES].
+    pub friendly_flags: u8,
+    pub name_tag_visibility: NameTagVisibility,
+    pub collision_rule: CollisionRule,
+    pub color: TeamColor,
+    /// Shown in front of every member's name.
+    pub prefix: TextComponent,
+    /// Shown after every member's name.
+    pub suffix: TextComponent,
+}
+
+#[derive(NetEncode, Clone)]
+pub struct TeamMembers {
+    pub count: VarInt,
+    /// Usernames for players, stringified UUIDs for any other entity.
+    pub entities: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameTagVisibility {
+    #[default]
+    Always,
+    HideForOtherTeams,
+    HideForOwnTeam,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollisionRule {
+    #[default]
+    Always,
+    PushOtherTeams,
+    PushOwnTeam,
+    Never,
+}
+
+/// The chat formatting a team's names are shown with. Only the colors can be used for a team
+/// sidebar, see [crate::net::packets::outgoing::display_objective::DisplaySlot].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TeamColor {
+    Black = 0,
+    DarkBlue = 1,
+    DarkGreen = 2,
+    DarkAqua = 3,
+    DarkRed = 4,
+    DarkPurple = 5,
+    Gold = 6,
+    Gray = 7,
+    DarkGray = 8,
+    Blue = 9,
+    Green = 10,
+    Aqua = 11,
+    Red = 12,
+    LightPurple = 13,
+    Yellow = 14,
+    White = 15,
+    Obfuscated = 16,
+    Bold = 17,
+    Strikethrough = 18,
+    Underlined = 19,
+    Italic = 20,
+    #[default]
+    Reset = 21,
+}
+
+impl NameTagVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NameTagVisibility::Always => "always",
+            NameTagVisibility::HideForOtherTeams => "hideForOtherTeams",
+            NameTagVisibility::HideForOwnTeam => "hideForOwnTeam",
+            NameTagVisibility::Never => "never",
+        }
+    }
+}
+
+impl CollisionRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CollisionRule::Always => "always",
+            CollisionRule::PushOtherTeams => "pushOtherTeams",
+            CollisionRule::PushOwnTeam => "pushOwnTeam",
+            CollisionRule::Never => "never",
+        }
+    }
+}
+
+impl NetEncode for TeamMode {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        (*self as u8).net_encode(writer, encode_option).await
+    }
+}
+
+impl NetEncode for NameTagVisibility {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.as_str()
+            .to_string()
+            .net_encode(writer, encode_option)
+            .await
+    }
+}
+
+impl NetEncode for CollisionRule {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.as_str()
+            .to_string()
+            .net_encode(writer, encode_option)
+            .await
+    }
+}
+
+impl NetEncode for TeamColor {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        VarInt::from(*self as i32)
+            .net_encode(writer, encode_option)
+            .await
+    }
+}
+
+impl TeamInfo {
+    pub const ALLOW_FRIENDLY_FIRE: u8 = 0x01;
+    pub const SEE_INVISIBLE_TEAMMATES: u8 = 0x02;
+
+    /// A team with no prefix or suffix, that follows the vanilla defaults for everything else.
+    pub fn new(display_name: &str) -> Self {
+        Self {
+            display_name: TextComponent::text(display_name),
+            friendly_flags: 0,
+            name_tag_visibility: NameTagVisibility::default(),
+            collision_rule: CollisionRule::default(),
+            color: TeamColor::default(),
+            prefix: TextComponent::text(""),
+            suffix: TextComponent::text(""),
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = TextComponent::text(prefix);
+        self
+    }
+
+    pub fn with_suffix(mut self, suffix: &str) -> Self {
+        self.suffix = TextComponent::text(suffix);
+        self
+    }
+
+    pub fn with_color(mut self, color: TeamColor) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+impl TeamMembers {
+    pub fn new(entities: &[String]) -> Self {
+        Self {
+            count: VarInt::from(entities.len() as i32),
+            entities: entities.to_vec(),
+        }
+    }
+}
+
+impl UpdateTeams {
+    pub fn create(team_name: &str, info: TeamInfo, members: &[String]) -> Self {
+        Self::new_auto(
+            team_name.to_string(),
+            TeamMode::Create,
+            Some(info),
+            Some(TeamMembers::new(members)),
+        )
+    }
+
+    pub fn remove(team_name: &str) -> Self {
+        Self::new_auto(team_name.to_string(), TeamMode::Remove, None, None)
+    }
+
+    pub fn update_info(team_name: &str, info: TeamInfo) -> Self {
+        Self::new_auto(
+            team_name.to_string(),
+            TeamMode::UpdateInfo,
+            Some(info),
+            None,
+        )
+    }
+
+    pub fn add_entities(team_name: &str, members: &[String]) -> Self {
+        Self::new_auto(
+            team_name.to_string(),
+            TeamMode::AddEntities,
+            None,
+            Some(TeamMembers::new(members)),
+        )
+    }
+
+    pub fn remove_entities(team_name: &str, members: &[String]) -> Self {
+        Self::new_auto(
+            team_name.to_string(),
+            TeamMode::RemoveEntities,
+            None,
+            Some(TeamMembers::new(members)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn encode(packet: UpdateTeams) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        packet
+            .net_encode(&mut buffer, &EncodeOption::Default)
+            .await
+            .unwrap();
+        buffer
+    }
+
+    fn string(value: &str) -> Vec<u8> {
+        let mut bytes = vec![value.len() as u8];
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    #[tokio::test]
+    async fn test_encode_create_team_with_prefix() {
+        let members = ["Steve".to_string(), "Alex".to_string()];
+        let info = TeamInfo::new("Red")
+            .with_prefix("[R] ")
+            .with_color(TeamColor::Red);
+        let packet = UpdateTeams::create("red", info, &members);
+
+        let mut body = vec![0x5A];
+        body.extend(string("red"));
+        body.push(0x00);
+        body.extend(string(r#"{"text":"Red"}"#));
+        body.push(0x00);
+        body.extend(string("always"));
+        body.extend(string("always"));
+        body.push(0x0C);
+        body.extend(string(r#"{"text":"[R] "}"#));
+        body.extend(string(r#"{"text":""}"#));
+        body.push(0x02);
+        body.extend(string("Steve"));
+        body.extend(string("Alex"));
+
+        let mut expected = vec![body.len() as u8];
+        expected.extend(body);
+        assert_eq!(encode(packet).await, expected);
+    }
+
+    #[tokio::test]
+    async fn test_encode_remove_team() {
+        assert_eq!(
+            encode(UpdateTeams::remove("red")).await,
+            vec![0x06, 0x5A, 0x03, b'r', b'e', b'd', 0x01]
+        );
+    }
+}
@@ -0,0 +1,21 @@
+use ferrumc_codec::network_types::varint::VarInt;
+
+use ferrumc_macros::NetEncode;
+
+/// Sent by the server to sync the client's clock with the world age and time of day.
+///
+/// A negative `time_of_day` tells the client to stop advancing the sun, which is how
+/// the `do_daylight_cycle` gamerule is communicated.
+#[derive(NetEncode, Clone, Debug)]
+pub struct UpdateTime {
+    #[encode(default = VarInt::from(0x5E))]
+    pub packet_id: VarInt,
+    pub world_age: i64,
+    pub time_of_day: i64,
+}
+
+impl UpdateTime {
+    pub fn new(world_age: i64, time_of_day: i64) -> Self {
+        Self::new_auto(world_age, time_of_day)
+    }
+}
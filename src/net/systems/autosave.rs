@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::game_loop::{TickSystem, TICKS_PER_SECOND};
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+
+/// Saves the world every `autosave_interval_secs`, writing at most `autosave_chunks_per_tick`
+/// chunks at a time in the background so a big save doesn't stall the game loop.
+#[derive(AutoGenName)]
+pub struct AutosaveSystem;
+
+#[async_trait]
+impl TickSystem for AutosaveSystem {
+    async fn tick(&self, state: GlobalState, tick_number: u64) {
+        let config = get_global_config();
+        let interval_ticks = config.autosave_interval_secs * TICKS_PER_SECOND;
+        if interval_ticks != 0 && tick_number != 0 && tick_number.is_multiple_of(interval_ticks) {
+            let queued = state.start_autosave();
+            info!("Autosaving the world, {} changed chunks to save", queued);
+        }
+
+        if state.autosave.pending() > 0 {
+            let limit = config.autosave_chunks_per_tick.max(1);
+            let saving = state.clone();
+            // The next batch waits for the last one to be written
+            state.save_in_background(async move { saving.continue_autosave(limit).await });
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
@@ -0,0 +1,78 @@
+use std::cmp::Reverse;
+
+use async_trait::async_trait;
+use tracing::info;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::System;
+use crate::net::utils::bandwidth::{BandwidthSnapshot, GLOBAL_BANDWIDTH};
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+
+const REPORT_INTERVAL_SECS: u64 = 60;
+/// How many of the most bandwidth hungry connections to log.
+const TOP_N: usize = 5;
+
+/// Periodically logs the total bandwidth usage and the connections using the most of it.
+#[derive(AutoGenName)]
+pub struct BandwidthReporter;
+
+#[async_trait]
+impl System for BandwidthReporter {
+    async fn run(&self, state: GlobalState) {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(REPORT_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            let total = GLOBAL_BANDWIDTH.snapshot();
+            info!(
+                "Bandwidth: {} KiB sent, {} KiB received",
+                total.bytes_sent / 1024,
+                total.bytes_received / 1024
+            );
+
+            for (id, usage) in top_consumers(&state, TOP_N).await {
+                let username = state
+                    .world
+                    .get_component::<Player>(id)
+                    .await
+                    .map(|p| p.username.clone())
+                    .unwrap_or_else(|_| format!("<connection {}>", id));
+
+                info!(
+                    "  {}: {} KiB sent, {} KiB received",
+                    username,
+                    usage.bytes_sent / 1024,
+                    usage.bytes_received / 1024
+                );
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+/// The `n` connections that have been sent the most data, highest first.
+async fn top_consumers(state: &GlobalState, n: usize) -> Vec<(usize, BandwidthSnapshot)> {
+    let connections = state
+        .connections
+        .connections
+        .iter()
+        .map(|entry| entry.value().clone())
+        .collect::<Vec<_>>();
+
+    let mut usage = Vec::with_capacity(connections.len());
+    for conn in connections {
+        let conn = conn.read().await;
+        usage.push((conn.id, conn.metadata.bandwidth.snapshot()));
+    }
+
+    usage.sort_by_key(|(_, snapshot)| Reverse(snapshot.bytes_sent));
+    usage.truncate(n);
+    usage
+}
@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::game_loop::TickSystem;
+use crate::state::GlobalState;
+
+/// Sends out the block changes made during the tick, batched per section.
+#[derive(AutoGenName)]
+pub struct BlockChangeSender;
+
+#[async_trait]
+impl TickSystem for BlockChangeSender {
+    async fn tick(&self, state: GlobalState, _tick_number: u64) {
+        if let Err(e) = state.flush_block_changes().await {
+            warn!("Failed to send block changes: {}", e);
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::packets::outgoing::set_health::update_health;
+use crate::net::systems::game_loop::TickSystem;
+use crate::state::GlobalState;
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::health::Health;
+use crate::utils::components::player::Player;
+use crate::utils::encoding::position::Position;
+use crate::world::dimension::Dimension;
+
+/// How often players outside the border get hurt. Vanilla checks every tick, but damage can only
+/// land once every 10 ticks anyway.
+const DAMAGE_INTERVAL_TICKS: u64 = 10;
+
+/// Hurts players that are too far outside the world border of the dimension they're in.
+#[derive(AutoGenName)]
+pub struct BorderDamageSystem;
+
+#[async_trait]
+impl TickSystem for BorderDamageSystem {
+    async fn tick(&self, state: GlobalState, tick_number: u64) {
+        if !tick_number.is_multiple_of(DAMAGE_INTERVAL_TICKS) {
+            return;
+        }
+
+        let mut damaged = Vec::new();
+        {
+            let mut query = state
+                .world
+                .query::<(&Player, &Position, &Health, Option<&Dimension>)>();
+            while let Some((entity_id, (_, position, health, dimension))) = query.next().await {
+                if health.is_dead() {
+                    continue;
+                }
+                let border = state
+                    .world_border
+                    .settings(dimension.as_deref().copied().unwrap_or_default());
+                let damage = border.damage_at(position.x as f64, position.z as f64);
+                if damage > 0.0 {
+                    damaged.push((entity_id, health.health - damage));
+                }
+            }
+        }
+
+        for (entity_id, health) in damaged {
+            let game_mode = state
+                .world
+                .get_component::<GameMode>(entity_id)
+                .await
+                .map(|game_mode| *game_mode)
+                .unwrap_or_default();
+            if matches!(game_mode, GameMode::Creative | GameMode::Spectator) {
+                continue;
+            }
+            if let Err(e) = update_health(entity_id, &state, health).await {
+                warn!("Failed to apply border damage to {}: {}", entity_id, e);
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::{TcpListener, TcpStream};
+
+    use crate::tests::connections::add_play_connection;
+    use crate::world::border::BorderSettings;
+
+    use super::*;
+
+    async fn add_player(state: &GlobalState, x: i32, z: i32) -> (usize, TcpStream) {
+        let (entity_id, client) = add_play_connection(state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(entity_id, Position::new(x, 64, z))
+            .insert(entity_id, Health::default())
+            .insert(entity_id, GameMode::Survival)
+            .insert(
+                entity_id,
+                Player::new(entity_id as u128, "Steve".to_string()),
+            );
+        (entity_id, client)
+    }
+
+    async fn health(state: &GlobalState, entity_id: usize) -> f32 {
+        state
+            .world
+            .get_component::<Health>(entity_id)
+            .await
+            .unwrap()
+            .health
+    }
+
+    #[tokio::test]
+    async fn test_only_players_outside_the_border_take_damage() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        state.world_border.update(Dimension::Overworld, |border| {
+            *border = BorderSettings {
+                diameter: 100.0,
+                ..Default::default()
+            }
+        });
+
+        let (inside, _inside_client) = add_player(&state, 10, -10).await;
+        let (outside, _outside_client) = add_player(&state, 80, 0).await;
+
+        BorderDamageSystem.tick(state.clone(), 0).await;
+
+        assert_eq!(health(&state, inside).await, Health::MAX_HEALTH);
+        // 25 blocks past the safe zone at 0.2 damage per block
+        assert_eq!(health(&state, outside).await, Health::MAX_HEALTH - 5.0);
+    }
+}
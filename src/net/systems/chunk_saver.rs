@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::game_loop::TickSystem;
+use crate::state::GlobalState;
+
+/// How often changed chunks evicted from the chunk cache are saved, every 5 seconds.
+const SAVE_INTERVAL_TICKS: u64 = 100;
+
+/// Saves the changed chunks that were evicted from the chunk cache, in the background so the tick
+/// doesn't wait on the disk.
+#[derive(AutoGenName)]
+pub struct ChunkSaver;
+
+#[async_trait]
+impl TickSystem for ChunkSaver {
+    async fn tick(&self, state: GlobalState, tick_number: u64) {
+        if !tick_number.is_multiple_of(SAVE_INTERVAL_TICKS) {
+            return;
+        }
+
+        // If another save is still going, the evicted chunks stay queued for the next interval
+        let saving = state.clone();
+        state.save_in_background(async move {
+            if let Err(e) = saving.save_evicted_chunks().await {
+                warn!("Failed to save evicted chunks: {}", e);
+            }
+        });
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
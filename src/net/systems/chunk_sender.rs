@@ -1,51 +1,53 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use ferrumc_codec::enc::NetEncode;
 use tokio::sync::RwLock;
 use tracing::{debug, error, warn};
 
 use crate::net::packets::incoming::client_info::ClientInfo;
 use crate::net::packets::outgoing::chunk_and_light_data::ChunkDataAndUpdateLight;
 use crate::net::packets::outgoing::set_center_chunk::SetCenterChunk;
-use crate::net::systems::System;
-use crate::net::{Connection, ConnectionWrapper};
+use crate::net::systems::game_loop::TickSystem;
+use crate::net::utils::chunk_pipeline::spawn_chunk_pipeline;
+use crate::net::utils::packet_queue::PacketQueue;
+use crate::net::{frame_packet, Connection, ConnectionWrapper};
 use crate::state::GlobalState;
-use crate::utils::components::last_chunk_tx_pos::LastChunkTxPos;
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::loaded_chunks::{chunks_in_view, sort_by_distance, LoadedChunks};
 use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
 use crate::utils::encoding::position::Position;
 use crate::utils::prelude::*;
 use ferrumc_macros::AutoGenName;
 
 pub const DEFAULT_CHUNK_RADIUS: i8 = 16;
-const CHUNK_TX_INTERVAL_MS: u64 = 50000;
+/// Every 50 seconds.
+const CHUNK_TX_INTERVAL_TICKS: u64 = 1000;
 
 #[derive(AutoGenName)]
 pub struct ChunkSender;
 
 #[async_trait]
-impl System for ChunkSender {
-    async fn run(&self, state: GlobalState) {
-        let mut interval =
-            tokio::time::interval(std::time::Duration::from_millis(CHUNK_TX_INTERVAL_MS));
-        loop {
-            interval.tick().await;
-
-            // Get all the Players, instead of all the *entities*. The player is just a filter.
-            let query = state.world.query::<&Player>();
-            let send_to = query.iter().await.collect::<Vec<_>>();
-
-            send_to.into_iter().for_each(|(entity_id, player)| {
-                debug!("Sending chunk to player: {}", player.get_username());
-                drop(player);
-                let state = state.clone();
-                tokio::spawn(async move {
-                    if let Err(e) = ChunkSender::send_chunks_to_player(state, entity_id).await {
-                        error!("Failed to send chunk to player: {}", e);
-                    }
-                });
-            });
+impl TickSystem for ChunkSender {
+    async fn tick(&self, state: GlobalState, tick_number: u64) {
+        if !tick_number.is_multiple_of(CHUNK_TX_INTERVAL_TICKS) {
+            return;
         }
+
+        // Get all the Players, instead of all the *entities*. The player is just a filter.
+        let query = state.world.query::<&Player>();
+        let send_to = query.iter().await.collect::<Vec<_>>();
+
+        send_to.into_iter().for_each(|(entity_id, player)| {
+            debug!("Sending chunk to player: {}", player.get_username());
+            drop(player);
+            let state = state.clone();
+            tokio::spawn(async move {
+                if let Err(e) = ChunkSender::send_chunks_to_player(state, entity_id).await {
+                    error!("Failed to send chunk to player: {}", e);
+                }
+            });
+        });
     }
 
     fn name(&self) -> &'static str {
@@ -54,29 +56,25 @@ impl System for ChunkSender {
 }
 
 impl ChunkSender {
+    /// Streams chunks to the player if they've moved into a different chunk since the view was
+    /// last updated.
     pub async fn send_chunks_to_player_if_needed(
         state: GlobalState,
         entity_id: impl TryInto<usize>,
-        current_pos: (i32, i32),
+        current_chunk: (i32, i32),
     ) -> Result<()> {
         let entity_id = entity_id.try_into().map_err(|_| Error::ConversionError)?;
 
-        let mut last_chunk_tx_pos = state
+        let center = state
             .world
             .get_component_storage()
-            .get_mut_or_insert_with::<LastChunkTxPos>(entity_id, Default::default)
-            .await;
-
-        let client_info = state.world.get_component::<ClientInfo>(entity_id).await?;
-
-        let distance = last_chunk_tx_pos.distance_to(current_pos.0, current_pos.1);
-
-        if distance < (client_info.view_distance as f64 / 5f64) {
+            .get_mut_or_insert_with::<LoadedChunks>(entity_id, Default::default)
+            .await
+            .center();
+        if center == Some(current_chunk) {
             return Ok(());
         }
 
-        last_chunk_tx_pos.set_last_chunk_tx_pos(current_pos.0, current_pos.1);
-
         let state_clone = state.clone();
         tokio::spawn(async move {
             if let Err(e) = ChunkSender::send_chunks_to_player(state_clone, entity_id).await {
@@ -86,6 +84,10 @@ impl ChunkSender {
 
         Ok(())
     }
+
+    /// Brings the player's view up to date with where they are: moves the center chunk if they
+    /// crossed into another one, and sends the chunks that came into view. Chunks that left the
+    /// view are unloaded later by the ChunkUnloader, once their grace period is over.
     pub async fn send_chunks_to_player(
         state: GlobalState,
         entity_id: impl TryInto<usize>,
@@ -106,7 +108,8 @@ impl ChunkSender {
         let pos = c_pos.clone();
         let view_distance: i8 = c_info
             .as_ref()
-            .map_or(DEFAULT_CHUNK_RADIUS, |c| c.view_distance);
+            .map_or(DEFAULT_CHUNK_RADIUS, |c| c.view_distance)
+            .min(get_global_config().view_distance);
         let conn = c_conn.0.clone();
 
         drop(c_pos);
@@ -120,66 +123,140 @@ impl ChunkSender {
 
         drop(player);
 
-        ChunkSender::send_set_center_chunk(&pos, conn.clone()).await?;
-        ChunkSender::send_chunk_data_to_player(state.clone(), &pos, view_distance, conn.clone())
-            .await?;
+        let (center_x, center_z) = (pos.x >> 4, pos.z >> 4);
+        let (center_moved, new_chunks) = {
+            let mut loaded_chunks = state
+                .world
+                .get_component_storage()
+                .get_mut_or_insert_with::<LoadedChunks>(entity_id, Default::default)
+                .await;
+            let center_moved = loaded_chunks.set_center(center_x, center_z);
+            let in_view = chunks_in_view(center_x, center_z, view_distance as i32);
+            let mut new_chunks = loaded_chunks.update_view(in_view, std::time::Instant::now());
+            sort_by_distance(&mut new_chunks, center_x, center_z);
+            (center_moved, new_chunks)
+        };
 
-        Ok(())
+        // The client drops chunks too far from the center, so it has to move before they're sent
+        let mut sent = Ok(());
+        if center_moved {
+            sent = ChunkSender::send_set_center_chunk(center_x, center_z, conn.clone()).await;
+        }
+        if sent.is_ok() && !new_chunks.is_empty() {
+            sent = ChunkSender::send_chunk_data_to_player(
+                state.clone(),
+                entity_id,
+                new_chunks.clone(),
+                conn,
+            )
+            .await;
+        }
+
+        // Whatever didn't make it is sent again the next time the view is updated
+        if let Ok(mut loaded_chunks) = state
+            .world
+            .get_component_mut::<LoadedChunks>(entity_id)
+            .await
+        {
+            loaded_chunks.stop_sending(&new_chunks);
+        }
+
+        sent
     }
 
     async fn send_chunk_data_to_player(
         state: GlobalState,
-        pos: &Position,
-        player_view_distance: i8,
+        entity_id: usize,
+        chunks: Vec<(i32, i32)>,
         conn: Arc<RwLock<Connection>>,
     ) -> Result<()> {
         let start = std::time::Instant::now();
+        let chunk_count = chunks.len();
 
-        let pos_x = pos.x;
-        let pos_z = pos.z;
-
-        let chunk_radius = player_view_distance as i32;
+        // Chunks are loaded and framed ahead of time by the pipeline, then written out a batch at
+        // a time, so a full view doesn't cost one syscall per chunk.
+        let batch_size = get_global_config().chunk_batch_size.max(1);
+        let reduce_for_spectators = get_global_config().reduced_spectator_chunks;
+        let game_mode = state
+            .world
+            .get_component::<GameMode>(entity_id)
+            .await
+            .map(|game_mode| *game_mode)
+            .unwrap_or_default();
+        let dimension = state.dimension_of(entity_id).await;
+        let compression_threshold = conn.read().await.compression_threshold();
+        let loader_state = state.clone();
+        let mut frames = spawn_chunk_pipeline(
+            chunks,
+            move |chunk_x, chunk_z| {
+                let state = loader_state.clone();
+                async move {
+                    ChunkDataAndUpdateLight::load_chunk(&state, dimension, chunk_x, chunk_z).await
+                }
+            },
+            move |chunk| async move {
+                let coords = (chunk.x_pos, chunk.z_pos);
+                let packet = ChunkDataAndUpdateLight::from_chunk(&chunk, dimension)
+                    .await?
+                    .for_game_mode(game_mode, reduce_for_spectators);
+                Ok((coords, frame_packet(packet, compression_threshold).await?))
+            },
+        );
+        let mut batch = PacketQueue::new();
+        let mut batch_coords = Vec::new();
+        let mut bytes_sent = 0;
 
-        'x: for x in -chunk_radius..=chunk_radius {
-            for z in -chunk_radius..=chunk_radius {
-                let Ok(packet) =
-                    ChunkDataAndUpdateLight::new(state.clone(), (pos_x >> 4) + x, (pos_z >> 4) + z)
-                        .await
-                else {
+        loop {
+            let frame = frames.recv().await;
+            if let Some((coords, frame)) = &frame {
+                bytes_sent += frame.len();
+                batch.queue_frame(frame);
+                batch_coords.push(*coords);
+                if batch.len() < batch_size {
                     continue;
-                };
-                let conn_read = conn.read().await;
-                if let Err(e) = conn_read.send_packet(packet).await {
-                    warn!("Failed to send chunk to player: {} ; Cancelling.", e);
-                    break 'x;
                 }
             }
+            // Sends full batches, and whatever didn't fill up a whole one at the end
+            if let Err(e) = conn
+                .read()
+                .await
+                .send_packets(std::mem::take(&mut batch))
+                .await
+            {
+                warn!("Failed to send chunk to player: {} ; Cancelling.", e);
+                break;
+            }
+            // Only chunks that were sent count as loaded, the rest are tried again later
+            if let Ok(mut loaded_chunks) = state
+                .world
+                .get_component_mut::<LoadedChunks>(entity_id)
+                .await
+            {
+                loaded_chunks.mark_sent(&batch_coords);
+            }
+            batch_coords.clear();
+            if frame.is_none() {
+                break;
+            }
         }
 
-        // check the size of a single chunk and multiply it by the number of chunks sent
-        let sample_chunk =
-            ChunkDataAndUpdateLight::new(state.clone(), pos_x >> 4, pos_z >> 4).await?;
-        let mut vec = vec![];
-        let encode_option = match conn.read().await.metadata.compressed {
-            true => ferrumc_codec::enc::EncodeOption::AlwaysOmitSize,
-            false => ferrumc_codec::enc::EncodeOption::Default,
-        };
-        sample_chunk.net_encode(&mut vec, &encode_option).await?;
-        let chunk_rad_axis = chunk_radius * 2 + 1;
         debug!(
-                "Send {}({}x{}) chunks to player in {:?}. Approximately {} kb of data (~{} kb per chunk)",
-                chunk_rad_axis * chunk_rad_axis,
-                chunk_rad_axis,
-                chunk_rad_axis,
-                start.elapsed(),
-                vec.len() as i32 * chunk_rad_axis * chunk_rad_axis / 1024,
-                vec.len() as i32 / 1024
-            );
+            "Sent {} chunks to player in {:?}. Approximately {} kb of data (~{} kb per chunk)",
+            chunk_count,
+            start.elapsed(),
+            bytes_sent / 1024,
+            bytes_sent / chunk_count / 1024
+        );
 
         Ok(())
     }
-    async fn send_set_center_chunk(pos: &Position, conn: Arc<RwLock<Connection>>) -> Result<()> {
-        let packet = SetCenterChunk::new(pos.x >> 4, pos.z >> 4);
+
+    async fn send_set_center_chunk(
+        chunk_x: i32,
+        chunk_z: i32,
+        conn: Arc<RwLock<Connection>>,
+    ) -> Result<()> {
+        let packet = SetCenterChunk::new(chunk_x, chunk_z);
 
         let read_guard = conn.read().await;
 
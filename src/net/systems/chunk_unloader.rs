@@ -0,0 +1,54 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tracing::{trace, warn};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::packets::outgoing::unload_chunk::UnloadChunk;
+use crate::net::systems::game_loop::TickSystem;
+use crate::net::ConnectionWrapper;
+use crate::state::GlobalState;
+use crate::utils::components::loaded_chunks::LoadedChunks;
+use crate::utils::config::get_global_config;
+
+/// How often expired chunks are looked for, once a second.
+const UNLOAD_INTERVAL_TICKS: u64 = 20;
+
+/// Unloads the chunks that have been out of a player's view for longer than the grace period.
+#[derive(AutoGenName)]
+pub struct ChunkUnloader;
+
+#[async_trait]
+impl TickSystem for ChunkUnloader {
+    async fn tick(&self, state: GlobalState, tick_number: u64) {
+        if !tick_number.is_multiple_of(UNLOAD_INTERVAL_TICKS) {
+            return;
+        }
+
+        let grace_period = Duration::from_secs(get_global_config().chunk_unload_grace_secs);
+        let mut query = state
+            .world
+            .query::<(&mut LoadedChunks, &ConnectionWrapper)>();
+
+        while let Some((_, (mut loaded_chunks, conn))) = query.next().await {
+            let expired = loaded_chunks.take_expired(Instant::now(), grace_period);
+            if expired.is_empty() {
+                continue;
+            }
+
+            let conn = conn.0.read().await;
+            trace!("Unloading {} chunks for {}", expired.len(), conn.id);
+            for (chunk_x, chunk_z) in expired {
+                if let Err(e) = conn.send_packet(UnloadChunk::new(chunk_x, chunk_z)).await {
+                    warn!("Failed to unload chunk: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
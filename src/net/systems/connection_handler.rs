@@ -1,5 +1,9 @@
+use std::time::{Duration, Instant};
+
 use crate::net::systems::System;
+use crate::net::utils::throttle::ConnectionThrottle;
 use crate::state::GlobalState;
+use crate::utils::config::{get_global_config, ForwardingMode};
 use crate::utils::prelude::*;
 use async_trait::async_trait;
 use ferrumc_macros::AutoGenName;
@@ -25,10 +29,33 @@ impl System for ConnectionHandler {
 
 impl ConnectionHandler {
     async fn handle_connections(state: GlobalState) -> Result<()> {
+        let config = get_global_config();
+        // Behind a proxy every connection comes from the proxy, so limiting them per address
+        // would limit everyone at once
+        let mut throttle = if config.forwarding.mode == ForwardingMode::None {
+            ConnectionThrottle::from_config(&config.throttle)
+        } else {
+            ConnectionThrottle::new(0, Duration::ZERO)
+        };
+
         loop {
-            let (stream, _) = state.server_stream.accept().await?;
-            debug!("Accepted connection from {:?}", stream.peer_addr()?);
+            let (stream, _) = tokio::select! {
+                accepted = state.server_stream.accept() => accepted?,
+                _ = state.shutdown_requested() => {
+                    debug!("No longer accepting connections, the server is stopping");
+                    return Ok(());
+                }
+            };
             let addy = stream.peer_addr()?;
+            if !throttle.try_acquire(addy.ip(), Instant::now()) {
+                debug!(
+                    "Dropping connection from {:?}, too many recent connections",
+                    addy
+                );
+                drop(stream);
+                continue;
+            }
+            debug!("Accepted connection from {:?}", addy);
             tokio::task::spawn(
                 Self::handle_connection(state.clone(), stream)
                     .instrument(info_span!("conn", %addy).or_current()),
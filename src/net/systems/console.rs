@@ -0,0 +1,73 @@
+use std::io::BufRead;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::{debug, error, info};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::commands::CommandSender;
+use crate::net::systems::System;
+use crate::state::GlobalState;
+
+/// Runs the commands typed into the terminal the server was started from, one per line, like
+/// `stop` or `/kick Steve`.
+///
+/// Log lines are written whole, so they don't mangle what's typed, they only show up in between.
+/// If the server has no terminal to read from, e.g. when it runs as a service, this stops as soon
+/// as it sees the end of the input.
+///
+/// Reading from the terminal blocks until a line is typed, so it's done on a thread of its own
+/// rather than one of the runtime's. That way nothing is left waiting on it when the server
+/// stops, and the process can exit.
+#[derive(AutoGenName)]
+pub struct ConsoleInput;
+
+#[async_trait]
+impl System for ConsoleInput {
+    async fn run(&self, state: GlobalState) {
+        let (lines_tx, mut lines) = mpsc::unbounded_channel();
+        let reader = std::thread::Builder::new()
+            .name("console-input".to_string())
+            .spawn(move || {
+                for line in std::io::stdin().lock().lines() {
+                    let stop = line.is_err();
+                    if lines_tx.send(line).is_err() || stop {
+                        return;
+                    }
+                }
+            });
+        if let Err(e) = reader {
+            error!("Failed to start reading console input: {:?}", e);
+            return;
+        }
+
+        loop {
+            let line = match lines.recv().await {
+                Some(Ok(line)) => line,
+                None => {
+                    debug!("Console input closed, no longer reading commands from it");
+                    return;
+                }
+                Some(Err(e)) => {
+                    error!("Failed to read console input: {:?}", e);
+                    return;
+                }
+            };
+
+            let command = line.trim();
+            let command = command.strip_prefix('/').unwrap_or(command);
+            if command.is_empty() {
+                continue;
+            }
+            info!("Console ran /{}", command);
+            if let Err(e) = state.execute_command(CommandSender::Console, command).await {
+                error!("Failed to run console command /{}: {:?}", command, e);
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
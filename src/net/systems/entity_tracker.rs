@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use tracing::{trace, warn};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::packets::outgoing::remove_entities::RemoveEntities;
+use crate::net::packets::outgoing::spawn_entity::SpawnEntity;
+use crate::net::packets::outgoing::spawn_player::SpawnPlayer;
+use crate::net::systems::game_loop::TickSystem;
+use crate::state::GlobalState;
+use crate::utils::components::entity_info::{EntityInfo, EntityKind};
+use crate::utils::components::precise_position::PrecisePosition;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::components::visible_entities::VisibleEntities;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::dimension::Dimension;
+
+/// Who's in range of whom is worked out every half a second.
+const TRACK_INTERVAL_TICKS: u64 = 10;
+
+/// Spawns entities for players once they're within view distance in the same dimension, and
+/// removes them again once they're out of it or are gone.
+#[derive(AutoGenName)]
+pub struct EntityTracker;
+
+/// Where an entity is, as far as the tracker cares.
+struct TrackedEntity {
+    entity_id: usize,
+    info: EntityInfo,
+    dimension: Dimension,
+    position: (f64, f64, f64),
+    rotation: (f32, f32),
+}
+
+impl TrackedEntity {
+    fn chunk(&self) -> (i32, i32) {
+        (
+            (self.position.0.floor() as i32) >> 4,
+            (self.position.2.floor() as i32) >> 4,
+        )
+    }
+}
+
+#[async_trait]
+impl TickSystem for EntityTracker {
+    async fn tick(&self, state: GlobalState, tick_number: u64) {
+        if !tick_number.is_multiple_of(TRACK_INTERVAL_TICKS) {
+            return;
+        }
+
+        let entities = Self::entities(&state).await;
+        // Only players have anything to be shown to
+        for observer in entities
+            .iter()
+            .filter(|entity| entity.info.kind == EntityKind::Player)
+        {
+            if let Err(e) = Self::update_observer(&state, observer, &entities).await {
+                warn!(
+                    "Failed to update the entities visible to {}: {}",
+                    observer.entity_id, e
+                );
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+impl EntityTracker {
+    async fn entities(state: &GlobalState) -> Vec<TrackedEntity> {
+        let query = state.world.query::<(
+            &EntityInfo,
+            &Position,
+            &Rotation,
+            Option<&PrecisePosition>,
+            Option<&Dimension>,
+        )>();
+        query
+            .iter()
+            .await
+            .map(
+                |(entity_id, (info, position, rotation, precise, dimension))| TrackedEntity {
+                    entity_id,
+                    info: *info,
+                    dimension: dimension.map(|dimension| *dimension).unwrap_or_default(),
+                    // Entities that are only known to the block are put in the middle of it
+                    position: precise
+                        .map(|precise| *precise)
+                        .unwrap_or_else(|| PrecisePosition::centered(&position))
+                        .as_tuple(),
+                    rotation: (rotation.yaw, rotation.pitch),
+                },
+            )
+            .collect()
+    }
+
+    async fn update_observer(
+        state: &GlobalState,
+        observer: &TrackedEntity,
+        entities: &[TrackedEntity],
+    ) -> Result<()> {
+        let range = state.config.view_distance as i32;
+        let (chunk_x, chunk_z) = observer.chunk();
+        let in_view = entities
+            .iter()
+            .filter(|other| other.entity_id != observer.entity_id)
+            .filter(|other| other.dimension == observer.dimension)
+            .filter(|other| {
+                let (other_x, other_z) = other.chunk();
+                (other_x - chunk_x).abs() <= range && (other_z - chunk_z).abs() <= range
+            })
+            .map(|other| (other.entity_id, other.info.uuid))
+            .collect::<HashMap<_, _>>();
+
+        let (spawned, removed) = {
+            let Ok(mut visible) = state
+                .world
+                .get_component_storage()
+                .get_mut::<VisibleEntities>(observer.entity_id)
+                .await
+            else {
+                return Ok(());
+            };
+            visible.update(in_view)
+        };
+        if spawned.is_empty() && removed.is_empty() {
+            return Ok(());
+        }
+        trace!(
+            "Entity {} now sees {:?} and no longer sees {:?}",
+            observer.entity_id,
+            spawned,
+            removed
+        );
+
+        let conn = state.connections.get_connection(observer.entity_id)?;
+        let conn = conn.read().await;
+        if !removed.is_empty() {
+            conn.send_packet(RemoveEntities::new(&removed)).await?;
+        }
+        for entity_id in spawned {
+            let Some(other) = entities.iter().find(|other| other.entity_id == entity_id) else {
+                continue;
+            };
+            let (yaw, pitch) = other.rotation;
+            match other.info.kind {
+                EntityKind::Player => {
+                    conn.send_packet(SpawnPlayer::new(
+                        other.entity_id as i32,
+                        other.info.uuid,
+                        other.position,
+                        yaw,
+                        pitch,
+                    ))
+                    .await?
+                }
+                kind => {
+                    conn.send_packet(SpawnEntity::new(
+                        other.entity_id as i32,
+                        other.info.uuid,
+                        kind,
+                        other.position,
+                        yaw,
+                        pitch,
+                    ))
+                    .await?
+                }
+            }
+        }
+        Ok(())
+    }
+}
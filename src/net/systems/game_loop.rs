@@ -0,0 +1,260 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::time::Instant;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::autosave::AutosaveSystem;
+use crate::net::systems::block_change_sender::BlockChangeSender;
+use crate::net::systems::border_damage::BorderDamageSystem;
+use crate::net::systems::chunk_saver::ChunkSaver;
+use crate::net::systems::chunk_sender::ChunkSender;
+use crate::net::systems::chunk_unloader::ChunkUnloader;
+use crate::net::systems::entity_tracker::EntityTracker;
+use crate::net::systems::keep_alive_system::KeepAliveSystem;
+use crate::net::systems::server_brand::ServerBrandAnimation;
+use crate::net::systems::tab_list::TabListLatency;
+use crate::net::systems::time_system::TimeSystem;
+use crate::net::systems::weather_system::WeatherSystem;
+use crate::net::systems::System;
+use crate::state::{GlobalState, ServerState};
+
+pub const TICKS_PER_SECOND: u64 = 20;
+/// How long a tick takes when the server keeps up.
+pub const TICK_DURATION: Duration = Duration::from_millis(1000 / TICKS_PER_SECOND);
+/// How far the game loop may fall behind before it gives up on catching up. Until then, missed
+/// ticks are run back to back.
+const MAX_CATCH_UP: Duration = Duration::from_secs(2);
+/// How much each tick counts towards the averages in [TickTimings].
+const TIMING_SMOOTHING: f64 = 1.0 / TICKS_PER_SECOND as f64;
+
+/// Something that runs once per game tick, as part of the [GameLoop].
+///
+/// Tick systems run one after the other, in the order they were registered, so a system can rely
+/// on everything registered before it having finished its work for the tick.
+#[async_trait]
+pub trait TickSystem: Send + Sync {
+    /// `tick_number` counts up from 0, one per tick. Systems that don't need to run every tick
+    /// can use it to skip ticks.
+    async fn tick(&self, state: GlobalState, tick_number: u64);
+    fn name(&self) -> &'static str;
+}
+
+/// How long ticks and each tick system take, averaged over roughly the last second.
+#[derive(Debug, Default)]
+pub struct TickTimings {
+    /// Time between the starts of two ticks.
+    pub interval: Duration,
+    /// Time spent running a whole tick.
+    pub tick: Duration,
+    /// Time spent in each system, in the order they run.
+    pub systems: Vec<(&'static str, Duration)>,
+}
+
+impl TickTimings {
+    /// Ticks per second, going by how far apart the recent ticks started.
+    pub fn tps(&self) -> f64 {
+        if self.interval.is_zero() {
+            return TICKS_PER_SECOND as f64;
+        }
+        (1.0 / self.interval.as_secs_f64()).min(TICKS_PER_SECOND as f64)
+    }
+
+    pub fn record_interval(&mut self, interval: Duration) {
+        self.interval = smooth(self.interval, interval);
+    }
+
+    pub fn record_tick(&mut self, elapsed: Duration) {
+        self.tick = smooth(self.tick, elapsed);
+    }
+
+    pub fn record_system(&mut self, name: &'static str, elapsed: Duration) {
+        match self.systems.iter_mut().find(|(system, _)| *system == name) {
+            Some((_, average)) => *average = smooth(*average, elapsed),
+            None => self.systems.push((name, elapsed)),
+        }
+    }
+}
+
+fn smooth(average: Duration, sample: Duration) -> Duration {
+    if average.is_zero() {
+        return sample;
+    }
+    average.mul_f64(1.0 - TIMING_SMOOTHING) + sample.mul_f64(TIMING_SMOOTHING)
+}
+
+/// Runs every registered [TickSystem], 20 times a second.
+///
+/// Ticks that are late because the previous ones ran long are caught up on straight away, unless
+/// the loop fell more than [MAX_CATCH_UP] behind, in which case those ticks are skipped.
+#[derive(AutoGenName)]
+pub struct GameLoop;
+
+#[async_trait]
+impl System for GameLoop {
+    async fn run(&self, state: GlobalState) {
+        let mut next_tick = Instant::now();
+        let mut last_tick: Option<Instant> = None;
+        let mut tick_number = 0;
+
+        loop {
+            tokio::time::sleep_until(next_tick).await;
+            // Only between ticks, so nothing is stopped halfway through
+            if state.is_stopping() {
+                return;
+            }
+            let started = Instant::now();
+            if let Some(last_tick) = last_tick {
+                state
+                    .tick_timings
+                    .lock()
+                    .unwrap()
+                    .record_interval(started - last_tick);
+            }
+            last_tick = Some(started);
+
+            Self::tick(&state, tick_number).await;
+            tick_number += 1;
+
+            next_tick += TICK_DURATION;
+            let behind = Instant::now().saturating_duration_since(next_tick);
+            if behind > MAX_CATCH_UP {
+                warn!(
+                    "Can't keep up! Is the server overloaded? Skipping {} ticks ({}ms behind)",
+                    behind.as_millis() / TICK_DURATION.as_millis(),
+                    behind.as_millis()
+                );
+                next_tick = Instant::now();
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+impl GameLoop {
+    /// Runs a single tick of every registered system, timing each of them.
+    pub async fn tick(state: &GlobalState, tick_number: u64) {
+        let tick_started = Instant::now();
+        for system in state.tick_systems() {
+            let started = Instant::now();
+            system.tick(state.clone(), tick_number).await;
+            let elapsed = started.elapsed();
+            if elapsed > TICK_DURATION {
+                warn!(
+                    "{} took {}ms, longer than a whole tick",
+                    system.name(),
+                    elapsed.as_millis()
+                );
+            }
+            state
+                .tick_timings
+                .lock()
+                .unwrap()
+                .record_system(system.name(), elapsed);
+        }
+        state
+            .tick_timings
+            .lock()
+            .unwrap()
+            .record_tick(tick_started.elapsed());
+    }
+}
+
+impl ServerState {
+    /// Adds a system to the end of the game loop.
+    pub fn register_tick_system(&self, system: Box<dyn TickSystem>) {
+        self.tick_systems.write().unwrap().push(Arc::from(system));
+    }
+
+    /// The registered tick systems, in order. Cloned out so the lock isn't held during a tick.
+    pub fn tick_systems(&self) -> Vec<Arc<dyn TickSystem>> {
+        self.tick_systems.read().unwrap().clone()
+    }
+}
+
+/// Registers the systems every server runs.
+pub fn register_default_tick_systems(state: &ServerState) {
+    state.register_tick_system(Box::new(TimeSystem));
+    state.register_tick_system(Box::new(WeatherSystem));
+    state.register_tick_system(Box::new(KeepAliveSystem));
+    state.register_tick_system(Box::new(ChunkSender));
+    state.register_tick_system(Box::new(ChunkUnloader));
+    state.register_tick_system(Box::new(ChunkSaver));
+    state.register_tick_system(Box::new(AutosaveSystem));
+    state.register_tick_system(Box::new(EntityTracker));
+    state.register_tick_system(Box::new(BlockChangeSender));
+    state.register_tick_system(Box::new(BorderDamageSystem));
+    state.register_tick_system(Box::new(ServerBrandAnimation));
+    state.register_tick_system(Box::new(TabListLatency));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordTicks(Arc<Mutex<Vec<u64>>>);
+
+    #[async_trait]
+    impl TickSystem for RecordTicks {
+        async fn tick(&self, _state: GlobalState, tick_number: u64) {
+            self.0.lock().unwrap().push(tick_number);
+        }
+
+        fn name(&self) -> &'static str {
+            "RecordTicks"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registered_system_ticks_once_per_iteration() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+
+        let system = RecordTicks::default();
+        let ticks = system.0.clone();
+        state.register_tick_system(Box::new(system));
+
+        for tick_number in 0..3 {
+            GameLoop::tick(&state, tick_number).await;
+        }
+
+        assert_eq!(*ticks.lock().unwrap(), vec![0, 1, 2]);
+
+        let timings = state.tick_timings.lock().unwrap();
+        assert!(timings
+            .systems
+            .iter()
+            .any(|(name, _)| *name == "RecordTicks"));
+    }
+
+    #[test]
+    fn test_timings_are_averaged() {
+        let mut timings = TickTimings::default();
+        assert_eq!(timings.tps(), 20.0);
+
+        timings.record_system("Slow", Duration::from_millis(10));
+        assert_eq!(timings.systems, vec![("Slow", Duration::from_millis(10))]);
+        timings.record_system("Slow", Duration::from_millis(30));
+        let average = timings.systems[0].1;
+        assert!(average > Duration::from_millis(10) && average < Duration::from_millis(12));
+
+        // Ticks coming in slower than they should are reflected in the TPS, faster ones aren't
+        timings.record_interval(Duration::from_millis(100));
+        assert_eq!(timings.tps(), 10.0);
+        let mut timings = TickTimings::default();
+        timings.record_interval(Duration::from_millis(10));
+        assert_eq!(timings.tps(), 20.0);
+    }
+}
@@ -1,25 +1,36 @@
+use std::time::{Duration, Instant};
+
 use async_trait::async_trait;
-use tokio::sync::RwLockReadGuard;
 use tracing::{trace, warn};
 
 use ferrumc_macros::AutoGenName;
 
 use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
-use crate::net::systems::System;
-use crate::net::{Connection, ConnectionWrapper};
+use crate::net::systems::game_loop::TickSystem;
+use crate::net::{disconnect, ConnectionWrapper};
 use crate::state::GlobalState;
 use crate::utils::components::keep_alive::KeepAlive;
 use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
+use crate::utils::text::TextComponent;
 
 #[derive(AutoGenName)]
 pub struct KeepAliveSystem;
 
+/// Keep alives go out every 15 seconds.
+const SEND_INTERVAL_TICKS: u64 = 300;
+/// Timed out connections are looked for every 5 seconds.
+const TIMEOUT_CHECK_INTERVAL_TICKS: u64 = 100;
+
 #[async_trait]
-impl System for KeepAliveSystem {
-    async fn run(&self, state: GlobalState) {
-        let sender = KeepAliveSystem::sender(state.clone());
-        let receiver = KeepAliveSystem::receiver(state.clone());
-        tokio::join!(sender, receiver);
+impl TickSystem for KeepAliveSystem {
+    async fn tick(&self, state: GlobalState, tick_number: u64) {
+        if tick_number.is_multiple_of(SEND_INTERVAL_TICKS) {
+            KeepAliveSystem::sender(state.clone()).await;
+        }
+        if tick_number.is_multiple_of(TIMEOUT_CHECK_INTERVAL_TICKS) {
+            KeepAliveSystem::receiver(state).await;
+        }
     }
 
     fn name(&self) -> &'static str {
@@ -28,79 +39,64 @@ impl System for KeepAliveSystem {
 }
 impl KeepAliveSystem {
     async fn sender(state: GlobalState) {
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(15));
+        let timeout = keep_alive_timeout();
         let mut query = state
             .world
             .query::<(&Player, &mut KeepAlive, &ConnectionWrapper)>();
 
-        loop {
-            interval.tick().await;
-
-            while let Some((_, (player, mut keep_alive, conn))) = query.next().await {
-                if keep_alive.last_sent.elapsed().as_secs() > 30 {
-                    let conn = conn.0.read().await;
-                    warn!("Dropping connection {} due to inactivity", conn.id);
-                    if let Err(err) = conn.drop_connection(state.clone()).await {
-                        warn!(
-                            "Error dropping connection {:?}: {:?}",
-                            conn.player_uuid, err
-                        );
-                    }
-                    continue;
-                }
-
-                keep_alive.data += 1;
-                keep_alive.last_sent = std::time::Instant::now();
-
-                let keep_alive_out = KeepAlivePacketOut::new_auto(keep_alive.data);
-                let conn = conn.0.write().await;
-
-                trace!("Sending keep alive packet to player: {:?}", player);
-                if let Err(e) = conn.send_packet(keep_alive_out).await {
-                    warn!("Error sending keep alive packet: {:?}", e);
-                }
+        while let Some((_, (player, mut keep_alive, conn))) = query.next().await {
+            let now = Instant::now();
+            if keep_alive.timed_out(now, timeout) {
+                let conn_id = conn.0.read().await.id;
+                Self::drop_connection(conn_id, &player.username, state.clone()).await;
+                continue;
+            }
+            // Still waiting on the last one, which hasn't timed out yet
+            if keep_alive.pending {
+                continue;
+            }
+
+            let keep_alive_out = KeepAlivePacketOut::new_auto(keep_alive.next(now));
+            let conn = conn.0.write().await;
+
+            trace!("Sending keep alive packet to player: {:?}", player);
+            if let Err(e) = conn.send_urgent_packet(keep_alive_out).await {
+                warn!("Error sending keep alive packet: {:?}", e);
             }
         }
     }
     async fn receiver(state: GlobalState) {
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+        let timeout = keep_alive_timeout();
         let mut query = state.world.query::<(&KeepAlive, &ConnectionWrapper)>();
 
-        loop {
-            interval.tick().await;
-
-            while let Some((_, (keep_alive, conn_wrapper))) = query.next().await {
-                if keep_alive.last_sent.elapsed().as_secs() <= 30 {
-                    continue;
-                }
+        while let Some((_, (keep_alive, conn_wrapper))) = query.next().await {
+            if !keep_alive.timed_out(Instant::now(), timeout) {
+                continue;
+            }
 
-                let conn = conn_wrapper.0.read().await;
-                let player = state.world.get_component::<Player>(conn.id).await;
+            let conn_id = conn_wrapper.0.read().await.id;
+            let player = state.world.get_component::<Player>(conn_id).await;
 
-                let username = player
-                    .as_ref()
-                    .map(|p| p.username.as_str())
-                    .unwrap_or("Unknown<!>Player");
+            let username = player
+                .as_ref()
+                .map(|p| p.username.as_str())
+                .unwrap_or("Unknown<!>Player");
 
-                Self::drop_connection(conn, username, state.clone()).await;
-            }
+            Self::drop_connection(conn_id, username, state.clone()).await;
         }
     }
 
-    async fn drop_connection(
-        conn: RwLockReadGuard<'_, Connection>,
-        username: &str,
-        state: GlobalState,
-    ) {
+    async fn drop_connection(conn_id: usize, username: &str, state: GlobalState) {
         warn!(
-            "Dropping player `{}`'s connection due to inactivity",
+            "Dropping player `{}`'s connection, it didn't answer a keep alive in time",
             username
         );
-        if let Err(err) = conn.drop_connection(state).await {
-            warn!(
-                "Error dropping connection {:?}: {:?}",
-                conn.player_uuid, err
-            );
+        if let Err(err) = disconnect(conn_id, &TextComponent::text("Timed out"), state).await {
+            warn!("Error dropping connection {}: {:?}", conn_id, err);
         }
     }
 }
+
+fn keep_alive_timeout() -> Duration {
+    Duration::from_secs(get_global_config().keep_alive_timeout_secs)
+}
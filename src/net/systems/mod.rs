@@ -1,14 +1,32 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
-use futures::stream::FuturesUnordered;
-use tracing::{debug_span, info, Instrument};
+use tracing::{debug_span, info, warn, Instrument};
 
-use crate::state::GlobalState;
+use crate::net::disconnect;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::components::player::Player;
 use crate::utils::prelude::*;
+use crate::utils::text::TextComponent;
 
+pub mod autosave;
+pub mod bandwidth_reporter;
+pub mod block_change_sender;
+pub mod border_damage;
+pub mod chunk_saver;
 pub mod chunk_sender;
+pub mod chunk_unloader;
 pub mod connection_handler;
+pub mod console;
+pub mod entity_tracker;
+pub mod game_loop;
 pub mod keep_alive_system;
-pub mod tick_system;
+pub mod query;
+pub mod rcon;
+pub mod server_brand;
+pub mod tab_list;
+pub mod time_system;
+pub mod weather_system;
 
 #[async_trait]
 pub trait System: Send + Sync {
@@ -18,14 +36,24 @@ pub trait System: Send + Sync {
 }
 
 pub static ALL_SYSTEMS: &[&dyn System] = &[
-    &tick_system::TickSystem,
-    &keep_alive_system::KeepAliveSystem,
-    &chunk_sender::ChunkSender,
+    &game_loop::GameLoop,
     &connection_handler::ConnectionHandler,
+    &bandwidth_reporter::BandwidthReporter,
+    &console::ConsoleInput,
+    &rcon::RconServer,
+    &query::QueryServer,
 ];
 
+/// How long the game loop gets to finish the tick it's in once the server is stopping.
+const GAME_LOOP_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs every system until they all finish, or until the server is stopped by `/stop`, Ctrl+C
+/// or SIGTERM.
+///
+/// Stopping lets the game loop finish its tick and the connection handler stop accepting, then
+/// kicks everyone and saves the world before the remaining systems are aborted.
 pub async fn start_all_systems(state: GlobalState) -> Result<()> {
-    let handles = FuturesUnordered::new();
+    let mut handles = Vec::new();
     for system in ALL_SYSTEMS {
         let name = system.name();
 
@@ -34,14 +62,113 @@ pub async fn start_all_systems(state: GlobalState) -> Result<()> {
                 .run(state.clone())
                 .instrument(debug_span!("sys", %name)),
         );
-        handles.push(handle);
+        handles.push((name, handle));
     }
 
-    futures::future::join_all(handles).await;
+    tokio::select! {
+        _ = futures::future::join_all(handles.iter_mut().map(|(_, handle)| handle)) => {
+            return Ok(());
+        }
+        _ = state.shutdown_requested() => {
+            info!("Shutdown requested, stopping the server...");
+        }
+        _ = shutdown_signal() => {
+            info!("Received a shutdown signal, stopping the server...");
+            state.request_shutdown();
+        }
+    }
+
+    for (name, handle) in handles {
+        if name != game_loop::GameLoop.name() {
+            handle.abort();
+            continue;
+        }
+        // Systems that are mid-tick could be halfway through writing something
+        if tokio::time::timeout(GAME_LOOP_STOP_TIMEOUT, handle)
+            .await
+            .is_err()
+        {
+            warn!("The game loop didn't finish its tick in time, stopping anyway");
+        }
+    }
 
+    state.disconnect_and_save_all().await;
+    info!("Saved the world, the server has stopped");
     Ok(())
 }
 
+/// Resolves on Ctrl+C, or SIGTERM on unix. Never resolves if the signals can't be listened for.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Can't listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                warn!("Can't listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+impl ServerState {
+    /// Stops the server, e.g. from the `/stop` command.
+    pub fn request_shutdown(&self) {
+        // Kept in the channel, so the request isn't lost if nothing is waiting on it yet
+        self.shutdown.send_replace(true);
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+
+    /// Resolves once the server is asked to stop, straight away if it already was.
+    pub async fn shutdown_requested(&self) {
+        let mut stopping = self.shutdown.subscribe();
+        // Can't fail, the sender lives as long as the state this borrows
+        let _ = stopping.wait_for(|stopping| *stopping).await;
+    }
+
+    /// Kicks every player with the configured shutdown message, which saves their data, then
+    /// saves every changed chunk.
+    pub async fn disconnect_and_save_all(self: &GlobalState) {
+        let reason = TextComponent::text(&self.config.shutdown_message);
+        let players = self
+            .world
+            .query::<&Player>()
+            .iter()
+            .await
+            .map(|(entity_id, _)| entity_id)
+            .collect::<Vec<_>>();
+        for entity_id in players {
+            if let Err(e) = disconnect(entity_id, &reason, self.clone()).await {
+                warn!("Failed to kick {} while stopping: {}", entity_id, e);
+            }
+        }
+
+        if let Err(e) = self.save_all_chunks().await {
+            warn!("Failed to save chunks while stopping: {}", e);
+        }
+    }
+}
+
 pub async fn kill_all_systems() -> Result<()> {
     info!("Killing all systems...");
     for system in ALL_SYSTEMS {
@@ -0,0 +1,106 @@
+use std::time::Instant;
+
+use async_trait::async_trait;
+use tokio::net::UdpSocket;
+use tracing::{debug, error, info, trace};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::System;
+use crate::net::utils::query::{
+    basic_stat_response, full_stat_response, handshake_response, ChallengeTokens, QueryInfo,
+    QueryRequest,
+};
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use crate::utils::config::get_global_config;
+use crate::utils::constants::GAME_VERSION;
+use crate::utils::prelude::*;
+
+/// Requests are tiny, anything bigger than this isn't one.
+const MAX_REQUEST_SIZE: usize = 64;
+
+/// Answers GameSpy4 queries over UDP, when they're enabled in the config.
+#[derive(AutoGenName)]
+pub struct QueryServer;
+
+#[async_trait]
+impl System for QueryServer {
+    async fn run(&self, state: GlobalState) {
+        let config = get_global_config();
+        if !config.query.enabled {
+            return;
+        }
+
+        if let Err(e) = Self::listen(state, &config.host, config.query.port).await {
+            error!("There was an error in the query server: {:?}", e);
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+impl QueryServer {
+    async fn listen(state: GlobalState, host: &str, port: u16) -> Result<()> {
+        let socket = UdpSocket::bind((host, port)).await?;
+        info!("Query listening on {}", socket.local_addr()?);
+
+        let mut tokens = ChallengeTokens::default();
+        let mut buffer = [0; MAX_REQUEST_SIZE];
+        loop {
+            let (length, addr) = socket.recv_from(&mut buffer).await?;
+            let Some(request) = QueryRequest::parse(&buffer[..length]) else {
+                trace!("Ignoring a malformed query from {}", addr);
+                continue;
+            };
+
+            let now = Instant::now();
+            let response = match request {
+                QueryRequest::Handshake { session_id } => {
+                    handshake_response(session_id, tokens.issue(addr, now))
+                }
+                QueryRequest::BasicStat { session_id, token }
+                | QueryRequest::FullStat { session_id, token }
+                    if !tokens.check(addr, token, now) =>
+                {
+                    debug!("Query session {} from {} has a bad token", session_id, addr);
+                    continue;
+                }
+                QueryRequest::BasicStat { session_id, .. } => {
+                    basic_stat_response(session_id, &Self::info(&state).await)
+                }
+                QueryRequest::FullStat { session_id, .. } => {
+                    full_stat_response(session_id, &Self::info(&state).await)
+                }
+            };
+
+            // A client that went away isn't worth stopping for
+            if let Err(e) = socket.send_to(&response, addr).await {
+                debug!("Failed to answer query from {}: {:?}", addr, e);
+            }
+        }
+    }
+
+    async fn info(state: &GlobalState) -> QueryInfo {
+        let config = get_global_config();
+        let mut players = Vec::new();
+        let query = state.world.query::<&Player>();
+        for (_, player) in query.iter().await {
+            players.push(player.username.clone());
+        }
+
+        QueryInfo {
+            motd: config.motd.first().cloned().unwrap_or_default(),
+            version: GAME_VERSION.to_string(),
+            // No plugins yet, so just the server software
+            plugins: "FerrumC".to_string(),
+            map: config.world.clone(),
+            players,
+            max_players: config.max_players,
+            host_ip: config.host.clone(),
+            host_port: config.port as u16,
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, error, info, info_span, warn, Instrument};
+
+use ferrumc_macros::AutoGenName;
+
+use crate::commands::{CommandOutput, CommandSender};
+use crate::net::systems::System;
+use crate::net::utils::rcon::{responses, RconPacket, AUTH_RESPONSE, COMMAND, LOGIN, RESPONSE};
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+/// Lets admin tools run commands over RCON, when it's enabled in the config.
+#[derive(AutoGenName)]
+pub struct RconServer;
+
+#[async_trait]
+impl System for RconServer {
+    async fn run(&self, state: GlobalState) {
+        let config = get_global_config();
+        if !config.rcon.enabled {
+            return;
+        }
+        if config.rcon.password.is_empty() {
+            warn!("RCON is enabled but has no password set, so it's staying off");
+            return;
+        }
+
+        if let Err(e) = Self::listen(state, &config.host, config.rcon.port).await {
+            error!("There was an error in the RCON server: {:?}", e);
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
+
+impl RconServer {
+    async fn listen(state: GlobalState, host: &str, port: u16) -> Result<()> {
+        let listener = TcpListener::bind((host, port)).await?;
+        info!("RCON listening on {}", listener.local_addr()?);
+
+        loop {
+            let (stream, addy) = listener.accept().await?;
+            debug!("Accepted RCON connection from {:?}", addy);
+            let state = state.clone();
+            tokio::spawn(
+                async move {
+                    if let Err(e) = Self::handle_client(state, stream).await {
+                        debug!("RCON connection closed: {:?}", e);
+                    }
+                }
+                .instrument(info_span!("rcon", %addy)),
+            );
+        }
+    }
+
+    async fn handle_client(state: GlobalState, mut stream: TcpStream) -> Result<()> {
+        let password = &get_global_config().rcon.password;
+        let mut authenticated = false;
+
+        while let Some(packet) = RconPacket::read(&mut stream).await? {
+            match packet.kind {
+                LOGIN => {
+                    authenticated = packet.body == *password;
+                    let request_id = if authenticated { packet.request_id } else { -1 };
+                    let response = RconPacket::new(request_id, AUTH_RESPONSE, "");
+                    stream.write_all(&response.encode()).await?;
+                    if !authenticated {
+                        warn!("RCON login with the wrong password");
+                        return Ok(());
+                    }
+                }
+                COMMAND if authenticated => {
+                    let command = packet.body.trim();
+                    let command = command.strip_prefix('/').unwrap_or(command);
+                    info!("RCON ran /{}", command);
+
+                    let output = CommandOutput::default();
+                    let sender = CommandSender::Rcon(output.clone());
+                    state.execute_command(sender, command).await?;
+
+                    for response in responses(packet.request_id, &output.take()) {
+                        stream.write_all(&response.encode()).await?;
+                    }
+                }
+                COMMAND => {
+                    return Err(Error::Generic("RCON command before logging in".to_string()));
+                }
+                kind => {
+                    let body = format!("Unknown request {:x}", kind);
+                    let response = RconPacket::new(packet.request_id, RESPONSE, body);
+                    stream.write_all(&response.encode()).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
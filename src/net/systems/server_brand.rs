@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+
+use crate::net::packets::outgoing::plugin_message::PluginMessage;
+use crate::net::systems::game_loop::TickSystem;
+use crate::net::ConnectionWrapper;
+use crate::state::GlobalState;
+use crate::utils::components::player::Player;
+use ferrumc_macros::AutoGenName;
+use tracing::warn;
+
+/// The wave only moves every other tick, so it scrolls at 10 frames a second.
+const TICKS_PER_FRAME: u64 = 2;
+
+#[derive(AutoGenName)]
+pub struct ServerBrandAnimation;
+
+#[async_trait]
+impl TickSystem for ServerBrandAnimation {
+    async fn tick(&self, state: GlobalState, tick_number: u64) {
+        if !state.config.animated_brand || !tick_number.is_multiple_of(TICKS_PER_FRAME) {
+            return;
+        }
+
+        let width = 40;
+        let total_width = width * 2;
+        let offset = (tick_number / TICKS_PER_FRAME) as usize % total_width;
+
+        let mut crab_wave = vec![" "; total_width];
+
+        for (index, wave) in crab_wave.iter_mut().enumerate().take(total_width) {
+            let wave_height = ((index as f64 * 0.2).sin() + 1.0) * 2.0;
+            if wave_height.round() as usize == 2 {
+                *wave = "🦀";
+            }
+        }
+
+        let visible_wave: String = crab_wave
+            .iter()
+            .cycle()
+            .skip(offset)
+            .take(width)
+            .cloned()
+            .collect();
+
+        let mut query = state.world.query::<(&ConnectionWrapper, &Player)>();
+        while let Some((_, (conn, _))) = query.next().await {
+            let packet = PluginMessage::brand(&visible_wave).await;
+            let conn = conn.0.read().await;
+            if let Err(e) = conn.send_packet(packet).await {
+                warn!("Failed to send packet: {}", e);
+                continue;
+            }
+            // trace!("Ticked connection for player `{}`", player.get_username());
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
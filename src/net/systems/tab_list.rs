@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::game_loop::TickSystem;
+use crate::state::GlobalState;
+
+/// How often everyone's ping bars are updated, every 30 seconds like vanilla.
+const LATENCY_INTERVAL_TICKS: u64 = 600;
+
+/// Keeps the latency shown on the tab list up to date.
+#[derive(AutoGenName)]
+pub struct TabListLatency;
+
+#[async_trait]
+impl TickSystem for TabListLatency {
+    async fn tick(&self, state: GlobalState, tick_number: u64) {
+        if !tick_number.is_multiple_of(LATENCY_INTERVAL_TICKS) {
+            return;
+        }
+
+        if let Err(e) = state.broadcast_latencies().await {
+            warn!("Failed to update the tab list latencies: {}", e);
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::game_loop::TickSystem;
+use crate::net::utils::broadcast::broadcast;
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+
+/// How many ticks to wait between each time sync with the clients. Vanilla syncs once a second.
+const TIME_SYNC_INTERVAL: u64 = 20;
+
+#[derive(AutoGenName)]
+pub struct TimeSystem;
+
+#[async_trait]
+impl TickSystem for TimeSystem {
+    async fn tick(&self, state: GlobalState, tick_number: u64) {
+        let do_daylight_cycle = get_global_config().gamerules.do_daylight_cycle;
+        state.world_time.tick(do_daylight_cycle);
+
+        if !(tick_number + 1).is_multiple_of(TIME_SYNC_INTERVAL) {
+            return;
+        }
+
+        let packet = state.world_time.update_time_packet(do_daylight_cycle);
+        if let Err(e) = broadcast(&packet, &state).await {
+            warn!("Failed to broadcast time update: {:?}", e);
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use ferrumc_macros::AutoGenName;
+
+use crate::net::systems::game_loop::TickSystem;
+use crate::net::utils::broadcast::broadcast_in;
+use crate::state::GlobalState;
+use crate::utils::config::get_global_config;
+
+#[derive(AutoGenName)]
+pub struct WeatherSystem;
+
+#[async_trait]
+impl TickSystem for WeatherSystem {
+    async fn tick(&self, state: GlobalState, _tick_number: u64) {
+        let do_weather_cycle = get_global_config().gamerules.do_weather_cycle;
+        for (dimension, events) in state.weather.tick(do_weather_cycle) {
+            for event in events {
+                if let Err(e) = broadcast_in(&event, &state, dimension).await {
+                    warn!("Failed to broadcast weather change: {:?}", e);
+                }
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::type_name()
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Bytes sent and received across every connection since the server started.
+pub static GLOBAL_BANDWIDTH: BandwidthMeter = BandwidthMeter::new();
+
+/// Counts the bytes going over a connection, including the packet framing.
+///
+/// Every update is also added to [GLOBAL_BANDWIDTH].
+#[derive(Debug, Default)]
+pub struct BandwidthMeter {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+/// A point-in-time copy of a [BandwidthMeter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BandwidthSnapshot {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+impl BandwidthMeter {
+    pub const fn new() -> Self {
+        Self {
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+        if !std::ptr::eq(self, &GLOBAL_BANDWIDTH) {
+            GLOBAL_BANDWIDTH.record_sent(bytes);
+        }
+    }
+
+    pub fn record_received(&self, bytes: usize) {
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        if !std::ptr::eq(self, &GLOBAL_BANDWIDTH) {
+            GLOBAL_BANDWIDTH.record_received(bytes);
+        }
+    }
+
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> BandwidthSnapshot {
+        BandwidthSnapshot {
+            bytes_sent: self.bytes_sent(),
+            bytes_received: self.bytes_received(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::{TcpListener, TcpStream};
+
+    use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
+    use crate::net::{Connection, State};
+
+    #[tokio::test]
+    async fn test_sent_bytes_include_framing() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (socket, _) = listener.accept().await.unwrap();
+
+        let conn = Connection::new(0, socket, State::Play);
+
+        // 1 byte length + 1 byte packet id + 8 byte keep alive id
+        conn.send_packet(KeepAlivePacketOut::new_auto(1234))
+            .await
+            .unwrap();
+        conn.flush().await.unwrap();
+        assert_eq!(conn.metadata.bandwidth.bytes_sent(), 10);
+        assert_eq!(conn.metadata.bandwidth.bytes_received(), 0);
+
+        drop(client);
+    }
+}
@@ -0,0 +1,94 @@
+use ferrumc_codec::enc::NetEncode;
+use tracing::warn;
+
+use crate::net::packets::ConnectionId;
+use crate::net::State;
+use crate::state::GlobalState;
+use crate::utils::prelude::*;
+use crate::world::dimension::Dimension;
+
+/// Send a packet to every connection that is currently in the play state.
+///
+/// Failing to send to a single connection is logged and doesn't stop the broadcast.
+pub async fn broadcast<P: NetEncode + Clone>(packet: &P, state: &GlobalState) -> Result<()> {
+    broadcast_filtered(packet, state, None).await
+}
+
+/// Same as [broadcast], but skips one connection. Mostly used to keep a player's own movement
+/// from being echoed back to them.
+pub async fn broadcast_except<P: NetEncode + Clone>(
+    packet: &P,
+    state: &GlobalState,
+    except: ConnectionId,
+) -> Result<()> {
+    broadcast_filtered(packet, state, Some(except)).await
+}
+
+/// Same as [broadcast], but only to the players in a dimension.
+pub async fn broadcast_in<P: NetEncode + Clone>(
+    packet: &P,
+    state: &GlobalState,
+    dimension: Dimension,
+) -> Result<()> {
+    let connections = state
+        .connections
+        .connections
+        .iter()
+        .map(|entry| entry.value().clone())
+        .collect::<Vec<_>>();
+
+    for conn in connections {
+        let conn = conn.read().await;
+        if conn.state != State::Play || state.dimension_of(conn.id).await != dimension {
+            continue;
+        }
+        if let Err(e) = conn.send_packet(packet.clone()).await {
+            warn!("Failed to broadcast packet to {}: {:?}", conn.id, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Same as [broadcast], but only to the players whose clients have an entity spawned, so the ones
+/// that can see it. The entity's own player isn't one of them.
+pub async fn broadcast_to_observers<P: NetEncode + Clone>(
+    packet: &P,
+    state: &GlobalState,
+    entity_id: usize,
+) -> Result<()> {
+    for conn in state.observers(entity_id).await {
+        let conn = conn.read().await;
+        if let Err(e) = conn.send_packet(packet.clone()).await {
+            warn!("Failed to broadcast packet to {}: {:?}", conn.id, e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn broadcast_filtered<P: NetEncode + Clone>(
+    packet: &P,
+    state: &GlobalState,
+    except: Option<ConnectionId>,
+) -> Result<()> {
+    // Collect first, so we don't hold the DashMap shards while awaiting on the locks.
+    let connections = state
+        .connections
+        .connections
+        .iter()
+        .map(|entry| entry.value().clone())
+        .collect::<Vec<_>>();
+
+    for conn in connections {
+        let conn = conn.read().await;
+        if conn.state != State::Play || Some(conn.id) == except {
+            continue;
+        }
+        if let Err(e) = conn.send_packet(packet.clone()).await {
+            warn!("Failed to broadcast packet to {}: {:?}", conn.id, e);
+        }
+    }
+
+    Ok(())
+}
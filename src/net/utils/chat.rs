@@ -0,0 +1,159 @@
+//! Chat between players, and messages from the server to everyone online.
+
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::net::packets::outgoing::player_chat::PlayerChatMessage;
+use crate::net::packets::outgoing::system_chat::SystemChatMessage;
+use crate::net::utils::broadcast::broadcast;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::prelude::*;
+use crate::utils::text::TextComponent;
+use crate::world::biome_registry::REGISTRY_CODEC;
+
+/// The longest chat message the vanilla client lets players send.
+pub const MAX_MESSAGE_LENGTH: usize = 256;
+
+#[derive(Deserialize)]
+struct RegistryCodec {
+    #[serde(rename = "minecraft:chat_type")]
+    chat_types: ChatTypeEntries,
+}
+
+#[derive(Deserialize)]
+struct ChatTypeEntries {
+    value: Vec<ChatTypeEntry>,
+}
+
+#[derive(Deserialize)]
+struct ChatTypeEntry {
+    name: String,
+    id: i32,
+}
+
+lazy_static! {
+    /// The ID of the `minecraft:chat` chat type, the one player chat is decorated with.
+    static ref CHAT_TYPE_ID: i32 = fastnbt::from_bytes::<RegistryCodec>(REGISTRY_CODEC)
+        .ok()
+        .and_then(|codec| {
+            codec
+                .chat_types
+                .value
+                .into_iter()
+                .find(|chat_type| chat_type.name == "minecraft:chat")
+        })
+        .map(|chat_type| chat_type.id)
+        .unwrap_or(0);
+}
+
+/// Checks a chat message the same way vanilla does, turning away ones that are too long or
+/// contain formatting codes or control characters.
+pub fn validate_message(message: &str) -> Result<()> {
+    if message.chars().count() > MAX_MESSAGE_LENGTH {
+        return Err(Error::Generic(format!(
+            "Chat message is longer than {} characters",
+            MAX_MESSAGE_LENGTH
+        )));
+    }
+    if message.chars().any(|c| c == '§' || c.is_control()) {
+        return Err(Error::Generic(
+            "Chat message contains illegal characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Fills a player's name and message into a chat format like `<{player}> {message}`.
+pub fn format_message(format: &str, player: &str, message: &str) -> String {
+    // Filled in one placeholder at a time, so a message containing `{player}` stays as typed
+    match format.split_once("{message}") {
+        Some((before, after)) => format!(
+            "{}{}{}",
+            before.replace("{player}", player),
+            message,
+            after.replace("{player}", player)
+        ),
+        None => format.replace("{player}", player),
+    }
+}
+
+impl ServerState {
+    /// Sends a message from the server to every player online.
+    pub async fn broadcast_message(self: &GlobalState, message: &TextComponent) -> Result<()> {
+        broadcast(&SystemChatMessage::new(message), self).await
+    }
+
+    /// Sends a chat message from a player to every player online, formatted with the configured
+    /// chat format.
+    pub async fn broadcast_chat(
+        self: &GlobalState,
+        sender: u128,
+        username: &str,
+        message: &str,
+        timestamp: i64,
+        salt: i64,
+    ) -> Result<()> {
+        if let Err(e) = validate_message(message) {
+            warn!("Ignoring chat message from {}: {}", username, e);
+            return Ok(());
+        }
+        info!("<{}> {}", username, message);
+
+        let format = &self.config.chat_format;
+        if format.is_empty() {
+            let packet = PlayerChatMessage::new(
+                sender,
+                username,
+                message.to_string(),
+                timestamp,
+                salt,
+                *CHAT_TYPE_ID,
+            );
+            return broadcast(&packet, self).await;
+        }
+
+        let line = format_message(format, username, message);
+        self.broadcast_message(&TextComponent::text(line)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_message() {
+        assert_eq!(
+            format_message("<{player}> {message}", "Steve", "hello"),
+            "<Steve> hello"
+        );
+        assert_eq!(
+            format_message("{player}: {message} ({player})", "Alex", "{player}"),
+            "Alex: {player} (Alex)"
+        );
+        assert_eq!(
+            format_message("{player} says hi", "Steve", "x"),
+            "Steve says hi"
+        );
+    }
+
+    #[test]
+    fn test_validate_message() {
+        assert!(validate_message("hello there").is_ok());
+        assert!(validate_message(&"a".repeat(MAX_MESSAGE_LENGTH)).is_ok());
+        assert!(validate_message(&"a".repeat(MAX_MESSAGE_LENGTH + 1)).is_err());
+        assert!(validate_message("§cred").is_err());
+        assert!(validate_message("line\nbreak").is_err());
+    }
+
+    #[test]
+    fn test_chat_type_is_in_registry() {
+        let codec: RegistryCodec = fastnbt::from_bytes(REGISTRY_CODEC).unwrap();
+        assert!(codec
+            .chat_types
+            .value
+            .iter()
+            .any(|chat_type| chat_type.name == "minecraft:chat"));
+    }
+}
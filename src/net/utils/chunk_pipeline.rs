@@ -0,0 +1,100 @@
+use std::future::Future;
+
+use tokio::sync::mpsc;
+use tracing::trace;
+
+use crate::utils::prelude::*;
+
+/// How many chunks can be waiting between two stages before the earlier stage has to wait.
+pub const STAGE_CAPACITY: usize = 16;
+
+/// Streams chunks through loading and serialization ahead of whoever is sending them.
+///
+/// Each stage runs in its own task, connected to the next by a bounded channel, so a slow disk
+/// read doesn't hold up serializing the chunks that were already loaded, and serialization keeps
+/// going while the previous batch is being written to the socket. The last stage is left to the
+/// caller, who reads the serialized chunks from the returned receiver.
+///
+/// Every stage handles chunks one at a time, so they come out in the same order as `coords`.
+/// Chunks that fail to load or serialize are skipped. Dropping the receiver stops the pipeline.
+pub fn spawn_chunk_pipeline<T, U, L, LFut, S, SFut>(
+    coords: Vec<(i32, i32)>,
+    load: L,
+    serialize: S,
+) -> mpsc::Receiver<U>
+where
+    T: Send + 'static,
+    U: Send + 'static,
+    L: Fn(i32, i32) -> LFut + Send + 'static,
+    LFut: Future<Output = Result<T>> + Send,
+    S: Fn(T) -> SFut + Send + 'static,
+    SFut: Future<Output = Result<U>> + Send,
+{
+    let (loaded_tx, mut loaded_rx) = mpsc::channel(STAGE_CAPACITY);
+    let (serialized_tx, serialized_rx) = mpsc::channel(STAGE_CAPACITY);
+
+    tokio::spawn(async move {
+        for (chunk_x, chunk_z) in coords {
+            match load(chunk_x, chunk_z).await {
+                Ok(chunk) => {
+                    if loaded_tx.send(chunk).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => trace!("Skipping chunk ({}, {}): {}", chunk_x, chunk_z, e),
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        while let Some(chunk) = loaded_rx.recv().await {
+            match serialize(chunk).await {
+                Ok(serialized) => {
+                    if serialized_tx.send(serialized).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => trace!("Skipping chunk that failed to serialize: {}", e),
+            }
+        }
+    });
+
+    serialized_rx
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pipeline_preserves_order() {
+        let coords = (0..40).map(|i| (i, -i)).collect::<Vec<_>>();
+
+        let mut serialized = spawn_chunk_pipeline(
+            coords.clone(),
+            |chunk_x, chunk_z| async move {
+                // Make the earlier chunks the slowest to load
+                tokio::time::sleep(Duration::from_millis((40 - chunk_x) as u64 / 8)).await;
+                if chunk_x == 7 {
+                    return Err(Error::ChunkNotFound(chunk_x, chunk_z));
+                }
+                Ok((chunk_x, chunk_z))
+            },
+            |(chunk_x, chunk_z)| async move { Ok(format!("{chunk_x},{chunk_z}")) },
+        );
+
+        let mut received = Vec::new();
+        while let Some(chunk) = serialized.recv().await {
+            received.push(chunk);
+        }
+
+        let expected = coords
+            .iter()
+            .filter(|(chunk_x, _)| *chunk_x != 7)
+            .map(|(chunk_x, chunk_z)| format!("{chunk_x},{chunk_z}"))
+            .collect::<Vec<_>>();
+        assert_eq!(received, expected);
+    }
+}
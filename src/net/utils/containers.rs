@@ -0,0 +1,130 @@
+use tracing::debug;
+
+use crate::net::packets::outgoing::open_screen::OpenScreen;
+use crate::net::packets::outgoing::set_container_content::SetContainerContent;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::components::inventory::Inventory;
+use crate::utils::components::open_container::{OpenContainer, WindowType};
+use crate::utils::prelude::*;
+use crate::utils::text::TextComponent;
+use crate::world::item_registry::item_registry;
+
+/// Window ids go from 1 to this and then wrap around, 0 is the player's inventory.
+const MAX_WINDOW_ID: u8 = 100;
+
+impl ServerState {
+    /// Opens a container window for a player, replacing the one they have open, and returns its
+    /// window id.
+    pub async fn open_container(
+        self: &GlobalState,
+        player: usize,
+        window_type: WindowType,
+        title: &TextComponent,
+    ) -> Result<u8> {
+        let component_storage = self.world.get_component_storage();
+        let window_id = match component_storage.get::<OpenContainer>(player).await {
+            Ok(open) => open.window_id % MAX_WINDOW_ID + 1,
+            Err(_) => 1,
+        };
+        let container = OpenContainer::new(window_id, window_type);
+        let content = {
+            let inventory = component_storage
+                .get_mut_or_insert_with::<Inventory>(player, Default::default)
+                .await;
+            SetContainerContent::of(&container, &inventory)
+        };
+        component_storage.insert(player, container);
+        debug!(
+            "Player {} opened {:?} window {}",
+            player, window_type, window_id
+        );
+
+        let conn = self.connections.get_connection(player)?;
+        let conn = conn.read().await;
+        conn.send_packet(OpenScreen::new(window_id, window_type, title))
+            .await?;
+        conn.send_packet(content).await?;
+        Ok(window_id)
+    }
+
+    /// Forgets about a player's container window, if it's the one they have open, and gives
+    /// them back the items they left in it. Returns whether it was.
+    pub async fn close_container(self: &GlobalState, player: usize, window_id: u8) -> Result<bool> {
+        let component_storage = self.world.get_component_storage();
+        let container = match component_storage.get::<OpenContainer>(player).await {
+            Ok(container) if container.window_id == window_id => container.clone(),
+            _ => return Ok(false),
+        };
+        component_storage.remove::<OpenContainer>(player)?;
+        debug!("Player {} closed window {}", player, window_id);
+
+        let content = {
+            let mut inventory = component_storage
+                .get_mut_or_insert_with::<Inventory>(player, Default::default)
+                .await;
+            if !container.close(&mut inventory, item_registry()) {
+                return Ok(true);
+            }
+            inventory.next_state_id();
+            SetContainerContent::inventory(&inventory)
+        };
+        let conn = self.connections.get_connection(player)?;
+        let conn = conn.read().await;
+        conn.send_packet(content).await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::tests::connections::add_play_connection;
+
+    #[tokio::test]
+    async fn test_open_and_close_container() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let (player, _client) = add_play_connection(&state).await;
+
+        let title = TextComponent::translate("container.repair");
+        let window_id = state
+            .open_container(player, WindowType::Anvil, &title)
+            .await
+            .unwrap();
+        assert_eq!(window_id, 1);
+        {
+            let container = state
+                .world
+                .get_component::<OpenContainer>(player)
+                .await
+                .unwrap();
+            assert_eq!(container.window_id, 1);
+            assert_eq!(container.slots.len(), 3);
+        }
+
+        // Opening another one replaces it under the next id
+        let window_id = state
+            .open_container(player, WindowType::Generic9x3, &title)
+            .await
+            .unwrap();
+        assert_eq!(window_id, 2);
+
+        // Closing a window that isn't open does nothing
+        assert!(!state.close_container(player, 1).await.unwrap());
+        assert!(state
+            .world
+            .get_component::<OpenContainer>(player)
+            .await
+            .is_ok());
+
+        assert!(state.close_container(player, 2).await.unwrap());
+        assert!(state
+            .world
+            .get_component::<OpenContainer>(player)
+            .await
+            .is_err());
+    }
+}
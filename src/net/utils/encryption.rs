@@ -0,0 +1,173 @@
+use std::fmt::Debug;
+use std::sync::OnceLock;
+
+use aes::Aes128;
+use cfb8::cipher::generic_array::GenericArray;
+use cfb8::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use num_bigint::BigInt;
+use rsa::pkcs8::EncodePublicKey;
+use rsa::rand_core::OsRng;
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey};
+use sha1::{Digest, Sha1};
+
+use crate::utils::prelude::*;
+
+/// Vanilla uses 1024 bit keys, and clients don't accept anything else.
+const KEY_BITS: usize = 1024;
+
+static SERVER_KEYS: OnceLock<ServerKeys> = OnceLock::new();
+
+/// The key pair clients encrypt the shared secret with during login.
+pub struct ServerKeys {
+    private_key: RsaPrivateKey,
+    public_key_der: Vec<u8>,
+}
+
+impl ServerKeys {
+    pub fn generate() -> Result<Self> {
+        let private_key = RsaPrivateKey::new(&mut OsRng, KEY_BITS)
+            .map_err(|e| Error::Generic(format!("Failed to generate the server key: {}", e)))?;
+        let public_key_der = private_key
+            .to_public_key()
+            .to_public_key_der()
+            .map_err(|e| Error::Generic(format!("Failed to encode the server key: {}", e)))?
+            .as_bytes()
+            .to_vec();
+
+        Ok(Self {
+            private_key,
+            public_key_der,
+        })
+    }
+
+    /// The public key, as the DER encoded SubjectPublicKeyInfo clients expect.
+    pub fn public_key_der(&self) -> &[u8] {
+        &self.public_key_der
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.private_key
+            .decrypt(Pkcs1v15Encrypt, data)
+            .map_err(|e| Error::Generic(format!("Failed to decrypt login data: {}", e)))
+    }
+}
+
+/// The server's key pair, generated the first time a player logs in.
+///
+/// If generating it fails, that player's login fails and the next one tries again.
+pub fn server_keys() -> Result<&'static ServerKeys> {
+    if let Some(keys) = SERVER_KEYS.get() {
+        return Ok(keys);
+    }
+    let keys = ServerKeys::generate()?;
+    Ok(SERVER_KEYS.get_or_init(|| keys))
+}
+
+/// What the server remembers about a player between sending the encryption request and getting
+/// the response.
+#[derive(Debug, Clone)]
+pub struct PendingLogin {
+    pub username: String,
+    pub verify_token: [u8; 4],
+}
+
+/// Encrypts everything sent to a client once encryption is enabled.
+pub struct PacketEncryptor(cfb8::Encryptor<Aes128>);
+
+/// Decrypts everything received from a client once encryption is enabled.
+pub struct PacketDecryptor(cfb8::Decryptor<Aes128>);
+
+impl PacketEncryptor {
+    /// Encrypts `data` in place. AES/CFB8 works a byte at a time, so the stream can be fed in
+    /// pieces of any size.
+    pub fn encrypt(&mut self, data: &mut [u8]) {
+        for byte in data.chunks_mut(1) {
+            self.0.encrypt_block_mut(GenericArray::from_mut_slice(byte));
+        }
+    }
+}
+
+impl PacketDecryptor {
+    pub fn decrypt(&mut self, data: &mut [u8]) {
+        for byte in data.chunks_mut(1) {
+            self.0.decrypt_block_mut(GenericArray::from_mut_slice(byte));
+        }
+    }
+}
+
+impl Debug for PacketEncryptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PacketEncryptor")
+    }
+}
+
+impl Debug for PacketDecryptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PacketDecryptor")
+    }
+}
+
+/// Sets up both directions of the stream. The shared secret is used as both the key and the IV.
+pub fn ciphers(shared_secret: &[u8]) -> Result<(PacketEncryptor, PacketDecryptor)> {
+    let invalid = |_| Error::Generic("The shared secret must be 16 bytes".to_string());
+    let encryptor =
+        cfb8::Encryptor::new_from_slices(shared_secret, shared_secret).map_err(invalid)?;
+    let decryptor =
+        cfb8::Decryptor::new_from_slices(shared_secret, shared_secret).map_err(invalid)?;
+    Ok((PacketEncryptor(encryptor), PacketDecryptor(decryptor)))
+}
+
+/// The server hash sent to Mojang's session servers: a SHA-1 digest printed as a signed hex
+/// number, the way Java's `BigInteger` does.
+pub fn minecraft_digest(server_id: &str, shared_secret: &[u8], public_key: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(server_id.as_bytes());
+    hasher.update(shared_secret);
+    hasher.update(public_key);
+    BigInt::from_signed_bytes_be(&hasher.finalize()).to_str_radix(16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minecraft_digest() {
+        assert_eq!(
+            minecraft_digest("Notch", &[], &[]),
+            "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"
+        );
+        assert_eq!(
+            minecraft_digest("jeb_", &[], &[]),
+            "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"
+        );
+        assert_eq!(
+            minecraft_digest("simon", &[], &[]),
+            "88e16a1019277b15d58faf0541e11910eb756f6"
+        );
+    }
+
+    #[test]
+    fn test_stream_decrypts_in_different_pieces() {
+        let secret = [7u8; 16];
+        let (mut encryptor, _) = ciphers(&secret).unwrap();
+        let (_, mut decryptor) = ciphers(&secret).unwrap();
+
+        let message = b"Hello from the other side of the socket".to_vec();
+        let mut data = message.clone();
+        let (first, second) = data.split_at_mut(5);
+        encryptor.encrypt(first);
+        encryptor.encrypt(second);
+        assert_ne!(data, message);
+
+        let (first, second) = data.split_at_mut(20);
+        decryptor.decrypt(first);
+        decryptor.decrypt(second);
+        assert_eq!(data, message);
+    }
+
+    #[test]
+    fn test_short_secret_is_rejected() {
+        assert!(ciphers(&[0u8; 8]).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use std::io::Cursor;
+use std::net::IpAddr;
+
+use ferrumc_codec::network_types::varint::VarInt;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::io::AsyncRead;
+use uuid::Uuid;
+
+use crate::utils::components::game_profile::{GameProfile, ProfileProperty};
+use crate::utils::impls::packet_impls::NetDecode;
+use crate::utils::prelude::*;
+
+/// The channel Velocity answers modern forwarding queries on.
+pub const VELOCITY_CHANNEL: &str = "velocity:player_info";
+/// The modern forwarding version the server asks for. Later versions only add chat signing keys,
+/// which the server doesn't use, and proxies fall back to this one.
+pub const VELOCITY_FORWARDING_VERSION: u8 = 1;
+/// Velocity signs what it forwards with HMAC-SHA256, which puts this many bytes in front of it.
+const SIGNATURE_LEN: usize = 32;
+
+/// Shown to players that connect straight to a server expecting BungeeCord's forwarding.
+pub const LEGACY_FORWARDING_MISSING: &str =
+    "If you wish to use IP forwarding, please enable it in your BungeeCord config as well!";
+/// Shown to players that connect straight to a server expecting Velocity's forwarding.
+pub const MODERN_FORWARDING_MISSING: &str = "This server requires you to connect with Velocity.";
+
+/// What a proxy in front of the server told it about a player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwardedPlayer {
+    /// The address the player connected to the proxy from.
+    pub ip: IpAddr,
+    pub uuid: Uuid,
+    /// The name the proxy knows the player by. BungeeCord leaves it to the login start.
+    pub name: Option<String>,
+    /// The profile properties from the proxy's authentication, like the player's skin.
+    pub properties: Vec<ProfileProperty>,
+}
+
+impl ForwardedPlayer {
+    /// The player's profile, going by the name they logged in with unless the proxy sent one.
+    pub fn profile(&self, username: &str) -> GameProfile {
+        GameProfile {
+            id: self.uuid,
+            name: self.name.clone().unwrap_or_else(|| username.to_string()),
+            properties: self.properties.clone(),
+        }
+    }
+}
+
+/// What the server remembers about a player between asking Velocity about them and getting the
+/// answer.
+#[derive(Debug, Clone)]
+pub struct PendingForwarding {
+    pub message_id: i32,
+    pub username: String,
+}
+
+/// Reads what BungeeCord puts in the handshake's server address:
+/// `host\0ip\0uuid` with a JSON array of profile properties optionally added as a fourth field.
+pub fn parse_legacy(server_address: &str) -> Result<ForwardedPlayer> {
+    let invalid = |what: &str| Error::Generic(format!("Invalid BungeeCord forwarding: {}", what));
+
+    let mut fields = server_address.split('\0');
+    let _host = fields.next();
+    let ip = fields
+        .next()
+        .ok_or_else(|| invalid("no address"))?
+        .parse::<IpAddr>()
+        .map_err(|_| invalid("bad address"))?;
+    let uuid = fields
+        .next()
+        .ok_or_else(|| invalid("no UUID"))
+        .and_then(|uuid| Uuid::parse_str(uuid).map_err(|_| invalid("bad UUID")))?;
+    let properties = match fields.next() {
+        Some(properties) => {
+            serde_json::from_str(properties).map_err(|_| invalid("bad profile properties"))?
+        }
+        None => Vec::new(),
+    };
+
+    Ok(ForwardedPlayer {
+        ip,
+        uuid,
+        name: None,
+        properties,
+    })
+}
+
+/// Checks the signature in front of Velocity's answer to a forwarding query against the shared
+/// secret, then reads the player's address, UUID, name and profile properties out of it.
+pub async fn parse_modern(data: &[u8], secret: &[u8]) -> Result<ForwardedPlayer> {
+    if data.len() < SIGNATURE_LEN {
+        return Err(Error::Generic(
+            "Velocity forwarding data is too short".to_string(),
+        ));
+    }
+    let (signature, payload) = data.split_at(SIGNATURE_LEN);
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+        .map_err(|e| Error::Generic(format!("Invalid forwarding secret: {}", e)))?;
+    mac.update(payload);
+    mac.verify_slice(signature).map_err(|_| {
+        Error::Generic("Velocity forwarding data has an invalid signature".to_string())
+    })?;
+
+    let mut bytes = Cursor::new(payload);
+    let version = read::<VarInt, _>(&mut bytes).await?.get_val();
+    if version < VELOCITY_FORWARDING_VERSION as i32 {
+        return Err(Error::Generic(format!(
+            "Unsupported Velocity forwarding version {}",
+            version
+        )));
+    }
+    let address = read::<String, _>(&mut bytes).await?;
+    // IPv6 addresses may come in brackets
+    let ip = address
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .map_err(|_| Error::Generic(format!("Invalid forwarded address: {}", address)))?;
+    let uuid = Uuid::from_u128(read::<u128, _>(&mut bytes).await?);
+    let name = read::<String, _>(&mut bytes).await?;
+
+    let count = read::<VarInt, _>(&mut bytes).await?.get_val();
+    let mut properties = Vec::new();
+    for _ in 0..count {
+        let name = read::<String, _>(&mut bytes).await?;
+        let value = read::<String, _>(&mut bytes).await?;
+        let signature = match read::<bool, _>(&mut bytes).await? {
+            true => Some(read::<String, _>(&mut bytes).await?),
+            false => None,
+        };
+        properties.push(ProfileProperty {
+            name,
+            value,
+            signature,
+        });
+    }
+    // Anything after the properties belongs to newer versions, and isn't needed
+
+    Ok(ForwardedPlayer {
+        ip,
+        uuid,
+        name: Some(name),
+        properties,
+    })
+}
+
+async fn read<V: NetDecode, R: AsyncRead + Unpin>(bytes: &mut R) -> Result<V> {
+    Ok(*V::net_decode(bytes).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(bytes: &mut Vec<u8>, value: &str) {
+        bytes.push(value.len() as u8);
+        bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn sign(payload: &[u8], secret: &[u8]) -> Vec<u8> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(payload);
+        let mut data = mac.finalize().into_bytes().to_vec();
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    fn test_parse_legacy_forwarding() {
+        let forwarded = parse_legacy(
+            "play.example.com\x00203.0.113.7\x00069a79f444e94726a5befca90e38aaf5\x00\
+             [{\"name\":\"textures\",\"value\":\"e30=\",\"signature\":\"c2ln\"}]",
+        )
+        .unwrap();
+        assert_eq!(forwarded.ip, "203.0.113.7".parse::<IpAddr>().unwrap());
+        assert_eq!(
+            forwarded.uuid,
+            Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap()
+        );
+        assert_eq!(forwarded.properties[0].signature.as_deref(), Some("c2ln"));
+        assert_eq!(forwarded.profile("Notch").name, "Notch");
+
+        let without_properties =
+            parse_legacy("localhost\x00::1\x00069a79f444e94726a5befca90e38aaf5").unwrap();
+        assert!(without_properties.properties.is_empty());
+
+        // Connecting straight to the server only sends the host
+        assert!(parse_legacy("localhost").is_err());
+        assert!(parse_legacy("localhost\x00nonsense\x00069a79f4").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_modern_forwarding() {
+        let uuid = Uuid::new_v4();
+        let mut payload = vec![1];
+        string(&mut payload, "198.51.100.2");
+        payload.extend_from_slice(&uuid.as_u128().to_be_bytes());
+        string(&mut payload, "Notch");
+        payload.push(1);
+        string(&mut payload, "textures");
+        string(&mut payload, "e30=");
+        payload.push(0);
+
+        let forwarded = parse_modern(&sign(&payload, b"secret"), b"secret")
+            .await
+            .unwrap();
+        assert_eq!(forwarded.ip, "198.51.100.2".parse::<IpAddr>().unwrap());
+        assert_eq!(forwarded.uuid, uuid);
+        assert_eq!(forwarded.name.as_deref(), Some("Notch"));
+        assert_eq!(forwarded.properties.len(), 1);
+        assert_eq!(forwarded.properties[0].signature, None);
+
+        assert!(parse_modern(&sign(&payload, b"other"), b"secret")
+            .await
+            .is_err());
+        assert!(parse_modern(&payload[..8], b"secret").await.is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use std::io::ErrorKind;
+
+use ferrumc_codec::error::CodecError;
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::net::utils::encryption::PacketDecryptor;
+use crate::utils::prelude::*;
+
+/// The largest packet length the vanilla client and server accept (a 3 byte VarInt).
+pub const MAX_FRAME_LENGTH: i32 = 2097151;
+
+/// Splits the incoming byte stream into length-prefixed packet frames.
+///
+/// Bytes are only ever pulled from the socket with a single `read` call, and are moved into the
+/// internal buffer before anything else is awaited. That makes [FrameReader::read_frame]
+/// cancellation safe: if the future is dropped halfway through a packet, the bytes that did arrive
+/// stay buffered and the next call picks up exactly where the previous one left off.
+///
+/// Once encryption is enabled, bytes are decrypted as they come off the socket, so the buffer
+/// only ever holds plain bytes.
+#[derive(Debug, Default)]
+pub struct FrameReader {
+    buffer: Vec<u8>,
+    decryptor: Option<PacketDecryptor>,
+}
+
+impl FrameReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decrypts everything read from now on. Anything that was already read past the last frame
+    /// was sent encrypted too, so it's decrypted straight away.
+    pub fn enable_decryption(&mut self, mut decryptor: PacketDecryptor) {
+        decryptor.decrypt(&mut self.buffer);
+        self.decryptor = Some(decryptor);
+    }
+
+    /// Number of bytes that have been read from the socket but not returned as a frame yet.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The bytes that have been read from the socket but not returned as a frame yet.
+    pub fn buffered_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Reads whatever the socket has ready into the buffer, waiting until there's at least a
+    /// byte. Just as cancellation safe as [FrameReader::read_frame].
+    pub async fn fill<R>(&mut self, reader: &mut R) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut chunk = [0u8; 4096];
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+        if let Some(decryptor) = self.decryptor.as_mut() {
+            decryptor.decrypt(&mut chunk[..read]);
+        }
+        self.buffer.extend_from_slice(&chunk[..read]);
+        Ok(())
+    }
+
+    /// Waits until a whole frame has arrived and returns its length prefix and body.
+    ///
+    /// The body is everything after the length, so it still starts with the data length if the
+    /// connection is compressed.
+    pub async fn read_frame<R>(&mut self, reader: &mut R) -> Result<(VarInt, Vec<u8>)>
+    where
+        R: AsyncRead + Unpin,
+    {
+        loop {
+            if let Some(frame) = self.take_frame()? {
+                return Ok(frame);
+            }
+            self.fill(reader).await?;
+        }
+    }
+
+    /// Removes the first frame from the buffer, if it has fully arrived.
+    fn take_frame(&mut self) -> Result<Option<(VarInt, Vec<u8>)>> {
+        let (length, header_len) = match VarInt::from_bytes(&self.buffer) {
+            Ok(header) => header,
+            Err(CodecError::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        if !(0..=MAX_FRAME_LENGTH).contains(&length.get_val()) {
+            return Err(Error::Generic(format!(
+                "Invalid packet length: {}",
+                length.get_val()
+            )));
+        }
+
+        let frame_end = header_len + length.get_val() as usize;
+        if self.buffer.len() < frame_end {
+            return Ok(None);
+        }
+
+        let body = self.buffer[header_len..frame_end].to_vec();
+        self.buffer.drain(..frame_end);
+        Ok(Some((length, body)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::*;
+    use crate::net::utils::encryption::ciphers;
+
+    async fn socket_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_read_keeps_partial_frame() {
+        let (mut client, mut server) = socket_pair().await;
+        let mut frames = FrameReader::new();
+
+        // Length 4, packet id 0x01, then only half of the body
+        client.write_all(&[0x04, 0x01, 0xAA]).await.unwrap();
+
+        // Drop the read while it is still waiting on the rest of the frame
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(50), frames.read_frame(&mut server)).await;
+        assert!(cancelled.is_err());
+        assert_eq!(frames.buffered(), 3);
+
+        // The rest of the frame, followed by a second one
+        client
+            .write_all(&[0xBB, 0xCC, 0x02, 0x00, 0x2A])
+            .await
+            .unwrap();
+
+        let (length, body) = frames.read_frame(&mut server).await.unwrap();
+        assert_eq!(length.get_val(), 4);
+        assert_eq!(body, vec![0x01, 0xAA, 0xBB, 0xCC]);
+
+        let (length, body) = frames.read_frame(&mut server).await.unwrap();
+        assert_eq!(length.get_val(), 2);
+        assert_eq!(body, vec![0x00, 0x2A]);
+        assert_eq!(frames.buffered(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_closed_socket_is_an_error() {
+        let (client, mut server) = socket_pair().await;
+        drop(client);
+
+        let mut frames = FrameReader::new();
+        assert!(frames.read_frame(&mut server).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_decryption_covers_buffered_bytes() {
+        let (mut client, mut server) = socket_pair().await;
+        let (mut encryptor, _) = ciphers(&[9u8; 16]).unwrap();
+        let (_, decryptor) = ciphers(&[9u8; 16]).unwrap();
+
+        // A plain frame, followed by two encrypted ones that arrive in the same read
+        let mut encrypted = vec![0x02, 0x00, 0x2A, 0x02, 0x01, 0x2B];
+        encryptor.encrypt(&mut encrypted);
+        let mut bytes = vec![0x01, 0x00];
+        bytes.extend_from_slice(&encrypted);
+        client.write_all(&bytes).await.unwrap();
+
+        let mut frames = FrameReader::new();
+        let (_, body) = frames.read_frame(&mut server).await.unwrap();
+        assert_eq!(body, vec![0x00]);
+        frames.enable_decryption(decryptor);
+
+        let (_, body) = frames.read_frame(&mut server).await.unwrap();
+        assert_eq!(body, vec![0x00, 0x2A]);
+        let (_, body) = frames.read_frame(&mut server).await.unwrap();
+        assert_eq!(body, vec![0x01, 0x2B]);
+    }
+
+    #[tokio::test]
+    async fn test_negative_length_is_rejected() {
+        let (mut client, mut server) = socket_pair().await;
+        client
+            .write_all(&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F])
+            .await
+            .unwrap();
+
+        let mut frames = FrameReader::new();
+        assert!(frames.read_frame(&mut server).await.is_err());
+    }
+}
@@ -0,0 +1,73 @@
+//! Changing a player's game mode.
+
+use uuid::Uuid;
+
+use crate::net::packets::outgoing::game_event::GameEvent;
+use crate::net::ConnectionWrapper;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::player::Player;
+use crate::utils::prelude::*;
+
+impl ServerState {
+    /// Puts a player in another game mode. Their client is told to switch, and everyone's tab list
+    /// is updated, since it shows spectators differently.
+    pub async fn set_game_mode(
+        self: &GlobalState,
+        entity_id: usize,
+        game_mode: GameMode,
+    ) -> Result<()> {
+        let uuid = Uuid::from_u128(self.world.get_component::<Player>(entity_id).await?.uuid);
+        self.world
+            .get_component_storage()
+            .insert(entity_id, game_mode);
+
+        let conn = self
+            .world
+            .get_component::<ConnectionWrapper>(entity_id)
+            .await?
+            .0
+            .clone();
+        conn.read()
+            .await
+            .send_packet(GameEvent::change_game_mode(game_mode))
+            .await?;
+
+        self.broadcast_game_mode(uuid, game_mode).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::tests::connections::add_play_connection;
+
+    #[tokio::test]
+    async fn test_set_game_mode() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let (player, _client) = add_play_connection(&state).await;
+
+        // Only players have a game mode to change
+        assert!(state
+            .set_game_mode(player, GameMode::Survival)
+            .await
+            .is_err());
+
+        state
+            .world
+            .get_component_storage()
+            .insert(player, Player::new(1, "Steve".to_string()));
+        state
+            .set_game_mode(player, GameMode::Spectator)
+            .await
+            .unwrap();
+        assert_eq!(
+            *state.world.get_component::<GameMode>(player).await.unwrap(),
+            GameMode::Spectator
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! The server list ping from before 1.7, which old clients and some server list crawlers still
+//! send instead of a handshake.
+//!
+//! It starts with `0xFE`, and is answered with a kick packet whose reason holds the server's
+//! status. See <https://wiki.vg/Server_List_Ping#1.6>. A modern handshake can start with `0xFE`
+//! too, when it's 126 bytes more than a multiple of 128 long, so like vanilla only the exact byte
+//! sequences legacy clients send are taken as a ping.
+
+/// The first byte of every legacy ping.
+pub const LEGACY_PING: u8 = 0xFE;
+/// Follows [LEGACY_PING] in pings from 1.4 onwards.
+const EXTENDED_PING: u8 = 0x01;
+/// The plugin message 1.6 clients send after [EXTENDED_PING].
+const PING_HOST: u8 = 0xFA;
+/// The protocol version vanilla reports to legacy pings. Old clients don't know it, so they show
+/// the server as outdated, but still show its status.
+const LEGACY_PROTOCOL_VERSION: i32 = 127;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyPing {
+    /// Beta 1.8 to 1.3, just the `0xFE` on its own.
+    Beta,
+    /// 1.4 to 1.6, `0xFE 0x01`, optionally followed by a plugin message with the host.
+    Extended,
+}
+
+impl LegacyPing {
+    /// Works out which legacy ping the connection starts with, if any. `bytes` is everything
+    /// the client has sent so far, so a Beta ping is only ever the one byte.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [LEGACY_PING] => Some(LegacyPing::Beta),
+            [LEGACY_PING, EXTENDED_PING] | [LEGACY_PING, EXTENDED_PING, PING_HOST, ..] => {
+                Some(LegacyPing::Extended)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `bytes` could still turn out to be a legacy ping once the client sends the rest.
+    pub fn is_partial(bytes: &[u8]) -> bool {
+        matches!(bytes, [LEGACY_PING] | [LEGACY_PING, EXTENDED_PING])
+    }
+
+    /// The kick packet that answers the ping.
+    pub fn response(self, game_version: &str, motd: &str, online: usize, max: i32) -> Vec<u8> {
+        let reason = match self {
+            // The fields are split by section signs, so the MOTD can't have any in it
+            LegacyPing::Beta => format!(
+                "{}\u{a7}{}\u{a7}{}",
+                motd.replace('\u{a7}', ""),
+                online,
+                max
+            ),
+            LegacyPing::Extended => format!(
+                "\u{a7}1\0{}\0{}\0{}\0{}\0{}",
+                LEGACY_PROTOCOL_VERSION, game_version, motd, online, max
+            ),
+        };
+
+        let chars = reason.encode_utf16().collect::<Vec<_>>();
+        let mut packet = Vec::with_capacity(3 + chars.len() * 2);
+        packet.push(0xFF);
+        packet.extend_from_slice(&(chars.len() as u16).to_be_bytes());
+        for char in chars {
+            packet.extend_from_slice(&char.to_be_bytes());
+        }
+        packet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(packet: &[u8]) -> String {
+        assert_eq!(packet[0], 0xFF);
+        let len = u16::from_be_bytes([packet[1], packet[2]]) as usize;
+        let chars = packet[3..]
+            .chunks(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!(chars.len(), len);
+        String::from_utf16(&chars).unwrap()
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(LegacyPing::detect(&[0xFE]), Some(LegacyPing::Beta));
+        assert_eq!(
+            LegacyPing::detect(&[0xFE, 0x01, 0xFA, 0x00]),
+            Some(LegacyPing::Extended)
+        );
+        assert_eq!(
+            LegacyPing::detect(&[0xFE, 0x01]),
+            Some(LegacyPing::Extended)
+        );
+        // A modern handshake starts with its length
+        assert_eq!(LegacyPing::detect(&[0x10, 0x00, 0xFB, 0x05]), None);
+    }
+
+    #[test]
+    fn test_long_handshake_is_not_a_ping() {
+        // 766 bytes long, so the length's VarInt starts with 0xFE
+        assert_eq!(LegacyPing::detect(&[0xFE, 0x05, 0x00, 0xFB, 0x05]), None);
+        // 254 bytes long, which starts like a 1.4 ping until the packet id
+        assert_eq!(LegacyPing::detect(&[0xFE, 0x01, 0x00, 0xFB, 0x05]), None);
+        assert!(!LegacyPing::is_partial(&[0xFE, 0x05, 0x00]));
+        assert!(LegacyPing::is_partial(&[0xFE]));
+    }
+
+    #[test]
+    fn test_responses() {
+        let beta = LegacyPing::Beta.response("1.20.1", "A \u{a7}cred server", 3, 20);
+        assert_eq!(decode(&beta), "A cred server\u{a7}3\u{a7}20");
+
+        let extended = LegacyPing::Extended.response("1.20.1", "Hello", 0, 20);
+        assert_eq!(
+            decode(&extended),
+            "\u{a7}1\u{0}127\u{0}1.20.1\u{0}Hello\u{0}0\u{0}20"
+        );
+    }
+}
@@ -1 +1,18 @@
+pub mod bandwidth;
+pub mod broadcast;
+pub mod chat;
+pub mod chunk_pipeline;
+pub mod containers;
+pub mod encryption;
+pub mod forwarding;
+pub mod frame_reader;
+pub mod game_mode;
+pub mod legacy_ping;
+pub mod movement;
+pub mod outbound;
 pub mod packet_queue;
+pub mod plugin_channels;
+pub mod query;
+pub mod rcon;
+pub mod tab_list;
+pub mod throttle;
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use ferrumc_codec::enc::NetEncode;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition;
+use crate::net::packets::outgoing::teleport_entity::TeleportEntity;
+use crate::net::packets::outgoing::update_entity_position::{
+    SetHeadRotation, UpdateEntityPosition, UpdateEntityPositionAndRotation, UpdateEntityRotation,
+};
+use crate::net::systems::chunk_sender::ChunkSender;
+use crate::net::{Connection, ConnectionWrapper};
+use crate::state::{GlobalState, ServerState};
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::grounded::Grounded;
+use crate::utils::components::precise_position::PrecisePosition;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::components::visible_entities::VisibleEntities;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::border::BorderSettings;
+
+impl ServerState {
+    /// Moves and turns a player to where their client says they are, and shows the other players
+    /// that can see them. Leave out whatever the client didn't send.
+    ///
+    /// Crossing into another chunk sends the chunks that came into view. Players can't walk out
+    /// through the world border, and get put back on it if they try, apart from spectators.
+    pub async fn move_player(
+        self: &GlobalState,
+        entity_id: usize,
+        position: Option<PrecisePosition>,
+        rotation: Option<Rotation>,
+        on_ground: bool,
+    ) -> Result<()> {
+        let component_storage = self.world.get_component_storage();
+        let border = self.border_for(entity_id).await;
+        let block_position = component_storage.get::<Position>(entity_id).await?.clone();
+
+        // The old position is read and the new one written under the same lock, so two moves
+        // handled at once can't both start from the same old position
+        let (old_rotation, new_rotation) = {
+            let mut current = component_storage.get_mut::<Rotation>(entity_id).await?;
+            let old_rotation = current.clone();
+            if let Some(rotation) = rotation {
+                *current = rotation;
+            }
+            (old_rotation, current.clone())
+        };
+        let (old_position, new_position, stopped) = {
+            let mut current = component_storage
+                .get_mut_or_insert_with::<PrecisePosition>(entity_id, || {
+                    PrecisePosition::centered(&block_position)
+                })
+                .await;
+            let old_position = *current;
+            let mut new_position = position.unwrap_or(old_position);
+            let stopped = border.and_then(|border| {
+                border.stop_at_border(
+                    (old_position.x, old_position.z),
+                    (new_position.x, new_position.z),
+                )
+            });
+            if let Some((x, z)) = stopped {
+                new_position = PrecisePosition::new(x, new_position.y, z);
+            }
+            *current = new_position;
+            (old_position, new_position, stopped.is_some())
+        };
+
+        if stopped {
+            let conn = self.connections.get_connection(entity_id)?;
+            let conn = conn.read().await;
+            conn.send_packet(SynchronizePlayerPosition::precise(
+                &new_position,
+                &new_rotation,
+            ))
+            .await?;
+        }
+        let moved = new_position != old_position;
+        let rotated =
+            new_rotation.yaw != old_rotation.yaw || new_rotation.pitch != old_rotation.pitch;
+
+        component_storage
+            .insert(entity_id, new_position.block())
+            .insert(entity_id, Grounded::new(on_ground));
+
+        if new_position.chunk() != old_position.chunk() {
+            ChunkSender::send_chunks_to_player_if_needed(
+                self.clone(),
+                entity_id,
+                new_position.chunk(),
+            )
+            .await?;
+        }
+
+        if !moved && !rotated {
+            return Ok(());
+        }
+        let observers = self.observers(entity_id).await;
+        if observers.is_empty() {
+            return Ok(());
+        }
+
+        let id = entity_id as i32;
+        let (yaw, pitch) = (new_rotation.yaw, new_rotation.pitch);
+        if moved {
+            match new_position.delta_from(&old_position) {
+                Some(delta) if rotated => {
+                    let packet =
+                        UpdateEntityPositionAndRotation::new(id, delta, yaw, pitch, on_ground);
+                    send_to_all(&packet, &observers).await;
+                }
+                Some(delta) => {
+                    send_to_all(&UpdateEntityPosition::new(id, delta, on_ground), &observers).await;
+                }
+                None => {
+                    let packet =
+                        TeleportEntity::new(id, new_position.as_tuple(), yaw, pitch, on_ground);
+                    send_to_all(&packet, &observers).await;
+                }
+            }
+        } else {
+            send_to_all(
+                &UpdateEntityRotation::new(id, yaw, pitch, on_ground),
+                &observers,
+            )
+            .await;
+        }
+        if rotated {
+            send_to_all(&SetHeadRotation::new(id, yaw), &observers).await;
+        }
+
+        Ok(())
+    }
+
+    /// The world border that keeps the player in, or `None` for spectators, who can go through it.
+    async fn border_for(self: &GlobalState, entity_id: usize) -> Option<BorderSettings> {
+        let game_mode = self
+            .world
+            .get_component::<GameMode>(entity_id)
+            .await
+            .map(|game_mode| *game_mode)
+            .unwrap_or_default();
+        if game_mode.is_spectator() {
+            return None;
+        }
+        Some(
+            self.world_border
+                .settings(self.dimension_of(entity_id).await),
+        )
+    }
+
+    /// The connections of the players whose clients have the entity spawned.
+    pub async fn observers(&self, entity_id: usize) -> Vec<Arc<RwLock<Connection>>> {
+        let query = self.world.query::<(&VisibleEntities, &ConnectionWrapper)>();
+        query
+            .iter()
+            .await
+            .filter(|(_, (visible, _))| visible.contains(entity_id))
+            .map(|(_, (_, conn))| conn.0.clone())
+            .collect()
+    }
+}
+
+async fn send_to_all<P: NetEncode + Clone>(packet: &P, connections: &[Arc<RwLock<Connection>>]) {
+    for conn in connections {
+        let conn = conn.read().await;
+        if let Err(e) = conn.send_packet(packet.clone()).await {
+            warn!("Failed to send movement to {}: {:?}", conn.id, e);
+        }
+    }
+}
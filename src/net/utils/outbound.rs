@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::Notify;
+use tracing::debug;
+
+use crate::net::utils::bandwidth::BandwidthMeter;
+use crate::net::utils::encryption::PacketEncryptor;
+use crate::Result;
+
+/// Which lane of the [OutboundQueue] a packet goes in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+    /// Packets that are timed by the client, like keep alives and pings, or that end the
+    /// connection. These skip ahead of everything else.
+    High,
+    /// Everything else: chunks, entities, chat...
+    #[default]
+    Normal,
+}
+
+/// Frames waiting to be written to a connection, in two lanes.
+///
+/// Whoever holds the connection's write half drains the queue one frame at a time, and checks the
+/// high priority lane before every frame. So a keep alive queued while a player is being sent a
+/// whole view of chunks goes out after the frame currently being written, instead of after all of
+/// them, and the client doesn't time out or report a skewed ping.
+///
+/// Frames are encrypted as they're written rather than as they're queued, since the encryption
+/// depends on everything that went before it and the lanes can reorder frames.
+///
+/// Each connection has a task in [OutboundQueue::write_until_closed] writing frames as they're
+/// queued, so sending a packet never waits on the client, and a slow one can't hold up the game
+/// loop.
+#[derive(Debug, Default)]
+pub struct OutboundQueue {
+    lanes: Mutex<Lanes>,
+    encryptor: Mutex<Option<PacketEncryptor>>,
+    queued: Notify,
+    closed: AtomicBool,
+}
+
+#[derive(Debug, Default)]
+struct Lanes {
+    high: VecDeque<Vec<u8>>,
+    normal: VecDeque<Vec<u8>>,
+}
+
+impl OutboundQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an already framed packet, or several back to back, to the end of its lane.
+    pub fn push(&self, frame: Vec<u8>, priority: Priority) {
+        let mut lanes = self.lanes.lock().unwrap();
+        match priority {
+            Priority::High => lanes.high.push_back(frame),
+            Priority::Normal => lanes.normal.push_back(frame),
+        }
+        self.queued.notify_one();
+    }
+
+    /// Stops the writing task once it's written what's queued. Nothing can be queued after.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        self.queued.notify_one();
+    }
+
+    /// Whether the queue was closed, either by the connection going away or a write failing.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Takes the next frame to write, high priority first.
+    pub fn pop(&self) -> Option<Vec<u8>> {
+        let mut lanes = self.lanes.lock().unwrap();
+        lanes.high.pop_front().or_else(|| lanes.normal.pop_front())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let lanes = self.lanes.lock().unwrap();
+        lanes.high.is_empty() && lanes.normal.is_empty()
+    }
+
+    /// Encrypts every frame written from now on, including the ones already queued.
+    pub fn enable_encryption(&self, encryptor: PacketEncryptor) {
+        *self.encryptor.lock().unwrap() = Some(encryptor);
+    }
+
+    /// Writes out everything that's queued, including frames queued while this is running.
+    /// Returns the number of bytes written.
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<usize> {
+        let mut written = 0;
+        while let Some(mut frame) = self.pop() {
+            if let Some(encryptor) = self.encryptor.lock().unwrap().as_mut() {
+                encryptor.encrypt(&mut frame);
+            }
+            writer.write_all(&frame).await?;
+            written += frame.len();
+        }
+        Ok(written)
+    }
+
+    /// Writes frames out as they're queued, until the queue is closed or a write fails, which
+    /// closes it too.
+    pub async fn write_until_closed<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &tokio::sync::Mutex<W>,
+        bandwidth: &BandwidthMeter,
+    ) {
+        loop {
+            self.queued.notified().await;
+            // Counted before letting go of the writer, so a flush that waited on it sees the
+            // bytes counted too
+            let mut writer = writer.lock().await;
+            match self.write_to(&mut *writer).await {
+                Ok(written) => bandwidth.record_sent(written),
+                Err(e) => {
+                    debug!("Failed to write to a connection, closing it: {}", e);
+                    self.close();
+                    return;
+                }
+            }
+            if self.is_closed() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::frame_packet;
+    use crate::net::packets::outgoing::chunk_and_light_data::ChunkDataAndUpdateLight;
+    use crate::net::packets::outgoing::keep_alive::KeepAlivePacketOut;
+    use crate::net::utils::encryption::ciphers;
+    use crate::world::chunk_format::Chunk;
+    use crate::world::dimension::Dimension;
+
+    #[tokio::test]
+    async fn test_keep_alive_skips_queued_chunks() {
+        let queue = OutboundQueue::new();
+
+        let chunk = ChunkDataAndUpdateLight::from_chunk(&Chunk::empty(0, 0), Dimension::Overworld)
+            .await
+            .unwrap();
+        let chunk = frame_packet(chunk, None).await.unwrap();
+        for _ in 0..100 {
+            queue.push(chunk.clone(), Priority::Normal);
+        }
+
+        let keep_alive = frame_packet(KeepAlivePacketOut::new_auto(1234), None)
+            .await
+            .unwrap();
+        queue.push(keep_alive.clone(), Priority::High);
+
+        let mut written = Vec::new();
+        let len = queue.write_to(&mut written).await.unwrap();
+        assert_eq!(len, keep_alive.len() + chunk.len() * 100);
+        assert_eq!(written.len(), len);
+        assert!(written.starts_with(&keep_alive));
+        assert!(written[keep_alive.len()..].starts_with(&chunk));
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_writer_drains_the_queue_until_closed() {
+        let queue = std::sync::Arc::new(OutboundQueue::new());
+        let writer = std::sync::Arc::new(tokio::sync::Mutex::new(Vec::new()));
+        let bandwidth = std::sync::Arc::new(BandwidthMeter::new());
+        let task = tokio::spawn({
+            let (queue, writer, bandwidth) = (queue.clone(), writer.clone(), bandwidth.clone());
+            async move { queue.write_until_closed(&writer, &bandwidth).await }
+        });
+
+        queue.push(vec![1, 2, 3], Priority::Normal);
+        queue.push(vec![4], Priority::High);
+        queue.close();
+        task.await.unwrap();
+
+        let written = writer.lock().await;
+        assert_eq!(written.len(), 4);
+        assert_eq!(bandwidth.bytes_sent(), 4);
+        assert!(queue.is_empty());
+        assert!(queue.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_encryption_applies_to_queued_frames() {
+        let secret = [3u8; 16];
+        let (encryptor, mut decryptor) = ciphers(&secret).unwrap();
+
+        let queue = OutboundQueue::new();
+        let frame = frame_packet(KeepAlivePacketOut::new_auto(1234), None)
+            .await
+            .unwrap();
+        queue.push(frame.clone(), Priority::Normal);
+        queue.enable_encryption(encryptor);
+
+        let mut written = Vec::new();
+        queue.write_to(&mut written).await.unwrap();
+        assert_ne!(written, frame);
+
+        decryptor.decrypt(&mut written);
+        assert_eq!(written, frame);
+    }
+}
@@ -1,32 +1,129 @@
+use crate::net::frame_packet;
+use crate::utils::config::get_global_config;
 use crate::Result;
-use ferrumc_codec::enc::{EncodeOption, NetEncode};
-use ferrumc_macros::NetEncode;
+use ferrumc_codec::enc::NetEncode;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
-#[derive(Debug, NetEncode)]
+/// Packets that are framed up front and then written out together, in a single write.
+#[derive(Debug, Default)]
 pub struct PacketQueue {
     queue: Vec<u8>,
+    packets: usize,
 }
 
 impl PacketQueue {
     pub fn new() -> Self {
-        Self { queue: Vec::new() }
+        Self::default()
     }
 
     /// Queue a packet to be sent.
+    ///
+    /// Each packet is framed on its own, so `compression` has to match the connection's state.
     pub async fn queue(&mut self, packet: impl NetEncode, compression: bool) -> Result<()> {
-        let encode_option = match compression {
-            true => EncodeOption::AlwaysOmitSize,
-            false => EncodeOption::Default,
-        };
-        packet
-            .net_encode(&mut self.queue, &encode_option)
-            .await
-            .map_err(Into::into)
+        let threshold = compression.then(|| get_global_config().network_compression_threshold);
+        let frame = frame_packet(packet, threshold).await?;
+        self.queue.extend_from_slice(&frame);
+        self.packets += 1;
+        Ok(())
+    }
+
+    /// Queue a packet that was already framed with [frame_packet].
+    pub fn queue_frame(&mut self, frame: &[u8]) {
+        self.queue.extend_from_slice(frame);
+        self.packets += 1;
+    }
+
+    /// Number of packets queued.
+    pub fn len(&self) -> usize {
+        self.packets
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets == 0
+    }
+
+    /// The queued frames, back to back.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.queue
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.queue
+    }
+
+    /// Writes every queued frame with a single `write_all`. Returns the number of bytes written.
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<usize> {
+        writer.write_all(&self.queue).await?;
+        Ok(self.queue.len())
     }
 }
 
-impl Default for PacketQueue {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use super::*;
+    use crate::net::packets::outgoing::chunk_and_light_data::ChunkDataAndUpdateLight;
+    use crate::world::chunk_format::Chunk;
+    use crate::world::dimension::Dimension;
+
+    /// Accepts everything it's given, counting how many writes it took.
+    #[derive(Default)]
+    struct CountingWriter {
+        writes: usize,
+        bytes: usize,
+    }
+
+    impl AsyncWrite for CountingWriter {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.writes += 1;
+            self.bytes += buf.len();
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    async fn chunk_packet(x: i32, z: i32) -> ChunkDataAndUpdateLight {
+        ChunkDataAndUpdateLight::from_chunk(&Chunk::empty(x, z), Dimension::Overworld)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_chunk_batch_is_a_single_write() {
+        const CHUNKS: i32 = 100;
+
+        // One write per chunk, the way chunks used to be sent
+        let mut unbatched = CountingWriter::default();
+        for i in 0..CHUNKS {
+            let frame = frame_packet(chunk_packet(i, 0).await, None).await.unwrap();
+            unbatched.write_all(&frame).await.unwrap();
+        }
+
+        let mut batch = PacketQueue::new();
+        for i in 0..CHUNKS {
+            batch.queue(chunk_packet(i, 0).await, false).await.unwrap();
+        }
+        assert_eq!(batch.len(), CHUNKS as usize);
+
+        let mut batched = CountingWriter::default();
+        let written = batch.write_to(&mut batched).await.unwrap();
+
+        assert_eq!(unbatched.writes, CHUNKS as usize);
+        assert_eq!(batched.writes, 1);
+        assert_eq!(written, unbatched.bytes);
+        assert_eq!(batched.bytes, unbatched.bytes);
     }
 }
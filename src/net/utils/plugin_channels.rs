@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Cursor;
+use std::pin::Pin;
+
+use tracing::{debug, trace};
+
+use crate::net::packets::outgoing::plugin_message::PluginMessage;
+use crate::net::packets::ConnectionId;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::components::plugin_channels::PluginChannels;
+use crate::utils::impls::packet_impls::NetDecode;
+use crate::utils::prelude::*;
+
+/// The channel the client and the server tell each other what software they're running on.
+pub const BRAND: &str = "minecraft:brand";
+/// Lists the channels whoever sends it is listening on.
+pub const REGISTER: &str = "minecraft:register";
+/// Lists the channels whoever sends it stopped listening on.
+pub const UNREGISTER: &str = "minecraft:unregister";
+
+/// Handles a plugin message a player sent on a channel. Gets everything it needs from the message.
+pub type ChannelHandler =
+    fn(ChannelMessage) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>;
+
+/// A plugin message a player sent, handed to the handler registered for its channel.
+pub struct ChannelMessage {
+    pub state: GlobalState,
+    pub conn_id: ConnectionId,
+    pub channel: String,
+    /// The rest of the packet, in whatever format the channel uses.
+    pub data: Vec<u8>,
+}
+
+/// The handlers for the plugin channels the server listens on, by channel name.
+#[derive(Default)]
+pub struct PluginChannelRegistry {
+    handlers: HashMap<String, ChannelHandler>,
+}
+
+impl PluginChannelRegistry {
+    /// Adds a handler for a channel, replacing the one that was registered for it before.
+    pub fn register(&mut self, channel: impl Into<String>, handler: ChannelHandler) {
+        self.handlers.insert(channel.into(), handler);
+    }
+
+    pub fn get(&self, channel: &str) -> Option<ChannelHandler> {
+        self.handlers.get(channel).copied()
+    }
+
+    /// The channels the server listens on, sorted so they're announced in the same order every
+    /// time.
+    pub fn channels(&self) -> Vec<&str> {
+        let mut channels = self.handlers.keys().map(String::as_str).collect::<Vec<_>>();
+        channels.sort_unstable();
+        channels
+    }
+}
+
+impl ServerState {
+    /// Starts listening on a plugin channel. Players that join afterwards are told about it with
+    /// `minecraft:register`, so mods only send on channels the server understands.
+    pub fn register_channel(&self, channel: impl Into<String>, handler: ChannelHandler) {
+        self.plugin_channels
+            .write()
+            .unwrap()
+            .register(channel, handler);
+    }
+
+    /// Passes a plugin message on to the handler for its channel. Messages on channels nothing
+    /// listens on are ignored, like vanilla does.
+    pub async fn handle_plugin_message(
+        self: &GlobalState,
+        conn_id: ConnectionId,
+        channel: String,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        // Not held across the await, so handlers can register channels themselves
+        let handler = self.plugin_channels.read().unwrap().get(&channel);
+        let Some(handler) = handler else {
+            trace!("Ignoring plugin message on unknown channel {}", channel);
+            return Ok(());
+        };
+        handler(ChannelMessage {
+            state: self.clone(),
+            conn_id,
+            channel,
+            data,
+        })
+        .await
+    }
+
+    /// Sends a player data on a plugin channel.
+    pub async fn send_plugin_message(
+        &self,
+        conn_id: ConnectionId,
+        channel: impl Into<String>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let conn = self.connections.get_connection(conn_id)?;
+        let conn = conn.read().await;
+        conn.send_packet(PluginMessage::new(channel, data)).await
+    }
+
+    /// The `minecraft:register` message announcing the channels the server listens on, if it
+    /// listens on any besides the vanilla ones.
+    pub fn channel_registration(&self) -> Option<PluginMessage> {
+        let registry = self.plugin_channels.read().unwrap();
+        let channels = registry
+            .channels()
+            .into_iter()
+            .filter(|channel| !channel.starts_with("minecraft:"))
+            .collect::<Vec<_>>();
+        if channels.is_empty() {
+            return None;
+        }
+        Some(PluginMessage::new(REGISTER, encode_channel_list(&channels)))
+    }
+}
+
+/// Listens on the vanilla channels, keeping track of each player's brand and the channels their
+/// client listens on.
+pub fn register_default_channels(state: &ServerState) {
+    state.register_channel(BRAND, |message| Box::pin(brand(message)));
+    state.register_channel(REGISTER, |message| Box::pin(register(message)));
+    state.register_channel(UNREGISTER, |message| Box::pin(unregister(message)));
+}
+
+async fn brand(message: ChannelMessage) -> Result<()> {
+    let brand = *String::net_decode(&mut Cursor::new(message.data)).await?;
+    debug!("Player {} is using {}", message.conn_id, brand);
+    let mut channels = message
+        .state
+        .world
+        .get_component_mut::<PluginChannels>(message.conn_id)
+        .await?;
+    channels.brand = Some(brand);
+    Ok(())
+}
+
+async fn register(message: ChannelMessage) -> Result<()> {
+    let mut channels = message
+        .state
+        .world
+        .get_component_mut::<PluginChannels>(message.conn_id)
+        .await?;
+    channels
+        .registered
+        .extend(decode_channel_list(&message.data));
+    Ok(())
+}
+
+async fn unregister(message: ChannelMessage) -> Result<()> {
+    let mut channels = message
+        .state
+        .world
+        .get_component_mut::<PluginChannels>(message.conn_id)
+        .await?;
+    for channel in decode_channel_list(&message.data) {
+        channels.registered.remove(&channel);
+    }
+    Ok(())
+}
+
+/// Joins channel names the way `minecraft:register` and `minecraft:unregister` expect them,
+/// separated by null bytes.
+pub fn encode_channel_list(channels: &[&str]) -> Vec<u8> {
+    channels.join("\0").into_bytes()
+}
+
+/// Splits the channel names out of a `minecraft:register` or `minecraft:unregister` message.
+pub fn decode_channel_list(data: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(data)
+        .split('\0')
+        .filter(|channel| !channel.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ignore(_: ChannelMessage) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    #[test]
+    fn test_channel_list_round_trip() {
+        let data = encode_channel_list(&["voicechat:request_secret", "bungeecord:main"]);
+        assert_eq!(data, b"voicechat:request_secret\0bungeecord:main");
+        assert_eq!(
+            decode_channel_list(&data),
+            vec!["voicechat:request_secret", "bungeecord:main"]
+        );
+        // Some clients end the list with a null byte too
+        assert_eq!(decode_channel_list(b"a:b\0"), vec!["a:b"]);
+        assert!(decode_channel_list(b"").is_empty());
+    }
+
+    #[test]
+    fn test_registry_replaces_handlers() {
+        let mut registry = PluginChannelRegistry::default();
+        registry.register("voicechat:request_secret", ignore);
+        registry.register(BRAND, ignore);
+        registry.register(BRAND, ignore);
+        assert_eq!(registry.channels(), vec![BRAND, "voicechat:request_secret"]);
+        assert!(registry.get("voicechat:request_secret").is_some());
+        assert!(registry.get("unknown:channel").is_none());
+    }
+}
@@ -0,0 +1,240 @@
+//! The GameSpy4 query protocol, which server list sites and hosting panels poll over UDP for the
+//! server's MOTD, map and players.
+//!
+//! Clients first do a handshake to get a challenge token, then send it back with their stat
+//! requests, so the server can't be used to bounce big responses at spoofed addresses. The
+//! listener itself lives in [crate::net::systems::query].
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// Every request starts with these two bytes.
+pub const MAGIC: [u8; 2] = [0xFE, 0xFD];
+pub const HANDSHAKE: u8 = 9;
+pub const STAT: u8 = 0;
+
+/// How long a challenge token stays valid, like vanilla.
+pub const TOKEN_LIFETIME: Duration = Duration::from_secs(30);
+
+/// Goes before the key/value section of a full stat response.
+const FULL_STAT_PADDING: &[u8] = b"splitnum\0\x80\0";
+/// Goes between the key/value section and the player list.
+const PLAYER_PADDING: &[u8] = b"\x01player_\0\0";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryRequest {
+    Handshake {
+        session_id: i32,
+    },
+    BasicStat {
+        session_id: i32,
+        token: i32,
+    },
+    /// Same as the basic stat, with four bytes of padding after the token.
+    FullStat {
+        session_id: i32,
+        token: i32,
+    },
+}
+
+impl QueryRequest {
+    /// Reads a request, or `None` if it isn't one.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let rest = bytes.strip_prefix(&MAGIC)?;
+        let (&kind, rest) = rest.split_first()?;
+        // Only the low four bits of each byte are used
+        let session_id = i32::from_be_bytes(rest.get(..4)?.try_into().ok()?) & 0x0F0F0F0F;
+        let rest = &rest[4..];
+
+        match (kind, rest.len()) {
+            (HANDSHAKE, _) => Some(Self::Handshake { session_id }),
+            (STAT, 4 | 8) => {
+                let token = i32::from_be_bytes(rest[..4].try_into().ok()?);
+                match rest.len() {
+                    4 => Some(Self::BasicStat { session_id, token }),
+                    _ => Some(Self::FullStat { session_id, token }),
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Everything a stat response reports about the server.
+#[derive(Debug, Clone)]
+pub struct QueryInfo {
+    pub motd: String,
+    pub version: String,
+    /// The server software and its plugins, like `FerrumC: plugin1; plugin2`.
+    pub plugins: String,
+    pub map: String,
+    pub players: Vec<String>,
+    pub max_players: i32,
+    pub host_ip: String,
+    pub host_port: u16,
+}
+
+fn push_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(value.as_bytes());
+    bytes.push(0);
+}
+
+fn response_header(kind: u8, session_id: i32) -> Vec<u8> {
+    let mut bytes = vec![kind];
+    bytes.extend_from_slice(&session_id.to_be_bytes());
+    bytes
+}
+
+/// Answers a handshake with a challenge token, which is sent as text.
+pub fn handshake_response(session_id: i32, token: i32) -> Vec<u8> {
+    let mut bytes = response_header(HANDSHAKE, session_id);
+    push_string(&mut bytes, &token.to_string());
+    bytes
+}
+
+pub fn basic_stat_response(session_id: i32, info: &QueryInfo) -> Vec<u8> {
+    let mut bytes = response_header(STAT, session_id);
+    push_string(&mut bytes, &info.motd);
+    push_string(&mut bytes, "SMP");
+    push_string(&mut bytes, &info.map);
+    push_string(&mut bytes, &info.players.len().to_string());
+    push_string(&mut bytes, &info.max_players.to_string());
+    // The one little endian number in the protocol
+    bytes.extend_from_slice(&info.host_port.to_le_bytes());
+    push_string(&mut bytes, &info.host_ip);
+    bytes
+}
+
+pub fn full_stat_response(session_id: i32, info: &QueryInfo) -> Vec<u8> {
+    let mut bytes = response_header(STAT, session_id);
+    bytes.extend_from_slice(FULL_STAT_PADDING);
+
+    let values = [
+        ("hostname", info.motd.clone()),
+        ("gametype", "SMP".to_string()),
+        ("game_id", "MINECRAFT".to_string()),
+        ("version", info.version.clone()),
+        ("plugins", info.plugins.clone()),
+        ("map", info.map.clone()),
+        ("numplayers", info.players.len().to_string()),
+        ("maxplayers", info.max_players.to_string()),
+        ("hostport", info.host_port.to_string()),
+        ("hostip", info.host_ip.clone()),
+    ];
+    for (key, value) in values {
+        push_string(&mut bytes, key);
+        push_string(&mut bytes, &value);
+    }
+    bytes.push(0);
+
+    bytes.extend_from_slice(PLAYER_PADDING);
+    for player in &info.players {
+        push_string(&mut bytes, player);
+    }
+    bytes.push(0);
+    bytes
+}
+
+/// The challenge tokens handed out to each address, which expire after [TOKEN_LIFETIME].
+#[derive(Debug, Default)]
+pub struct ChallengeTokens {
+    tokens: HashMap<SocketAddr, (i32, Instant)>,
+}
+
+impl ChallengeTokens {
+    /// Hands out a new token to an address, replacing the one it had.
+    pub fn issue(&mut self, addr: SocketAddr, now: Instant) -> i32 {
+        self.tokens
+            .retain(|_, (_, issued)| now.duration_since(*issued) < TOKEN_LIFETIME);
+        let token = rand::random::<i32>();
+        self.tokens.insert(addr, (token, now));
+        token
+    }
+
+    /// Whether a token is the one the address was handed, and hasn't expired.
+    pub fn check(&self, addr: SocketAddr, token: i32, now: Instant) -> bool {
+        self.tokens
+            .get(&addr)
+            .is_some_and(|&(issued_token, issued)| {
+                issued_token == token && now.duration_since(issued) < TOKEN_LIFETIME
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(kind: u8, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(kind);
+        bytes.extend_from_slice(&0x11223344i32.to_be_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_requests() {
+        assert_eq!(
+            QueryRequest::parse(&request(HANDSHAKE, &[])),
+            Some(QueryRequest::Handshake {
+                session_id: 0x01020304
+            })
+        );
+        assert_eq!(
+            QueryRequest::parse(&request(STAT, &7i32.to_be_bytes())),
+            Some(QueryRequest::BasicStat {
+                session_id: 0x01020304,
+                token: 7
+            })
+        );
+        let mut full = 7i32.to_be_bytes().to_vec();
+        full.extend_from_slice(&[0; 4]);
+        assert!(matches!(
+            QueryRequest::parse(&request(STAT, &full)),
+            Some(QueryRequest::FullStat { token: 7, .. })
+        ));
+        assert_eq!(QueryRequest::parse(&request(STAT, &[1, 2])), None);
+        assert_eq!(QueryRequest::parse(&[0xFE, 0xFD, HANDSHAKE]), None);
+        assert_eq!(QueryRequest::parse(b"hello"), None);
+    }
+
+    #[test]
+    fn test_stat_responses() {
+        let info = QueryInfo {
+            motd: "A server".to_string(),
+            version: "1.20.1".to_string(),
+            plugins: "FerrumC".to_string(),
+            map: "world".to_string(),
+            players: vec!["Steve".to_string(), "Alex".to_string()],
+            max_players: 20,
+            host_ip: "127.0.0.1".to_string(),
+            host_port: 25565,
+        };
+
+        let basic = basic_stat_response(1, &info);
+        assert_eq!(
+            basic,
+            b"\0\0\0\0\x01A server\0SMP\0world\x002\x0020\0\xdd\x63127.0.0.1\0".to_vec()
+        );
+
+        let full = full_stat_response(1, &info);
+        assert!(full.starts_with(b"\0\0\0\0\x01splitnum\0\x80\0hostname\0A server\0"));
+        assert!(full.ends_with(b"\0\0\x01player_\0\0Steve\0Alex\0\0"));
+    }
+
+    #[test]
+    fn test_challenge_tokens_expire() {
+        let mut tokens = ChallengeTokens::default();
+        let addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+        let other: SocketAddr = "127.0.0.2:1234".parse().unwrap();
+        let now = Instant::now();
+
+        let token = tokens.issue(addr, now);
+        assert!(tokens.check(addr, token, now));
+        assert!(!tokens.check(other, token, now));
+        assert!(!tokens.check(addr, token.wrapping_add(1), now));
+        assert!(!tokens.check(addr, token, now + TOKEN_LIFETIME));
+    }
+}
@@ -0,0 +1,137 @@
+//! The Source RCON protocol, which admin tools use to run commands on the server remotely.
+//!
+//! Every packet is a little endian length, request ID and type, followed by a null terminated body
+//! and an extra null byte. The server itself lives in [crate::net::systems::rcon].
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::utils::prelude::*;
+
+/// Logs in with the password in the body.
+pub const LOGIN: i32 = 3;
+/// Runs the command in the body.
+pub const COMMAND: i32 = 2;
+/// Answers a login, with the request ID of the login or -1 if the password was wrong.
+pub const AUTH_RESPONSE: i32 = 2;
+/// Answers a command with its output.
+pub const RESPONSE: i32 = 0;
+
+/// The longest body a client may send, like vanilla.
+pub const MAX_REQUEST_BODY: usize = 1446;
+/// Longer output is split over several responses.
+pub const MAX_RESPONSE_BODY: usize = 4096;
+
+/// The request ID and type, and the two null bytes.
+const HEADER_AND_PADDING: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RconPacket {
+    pub request_id: i32,
+    pub kind: i32,
+    pub body: String,
+}
+
+impl RconPacket {
+    pub fn new(request_id: i32, kind: i32, body: impl Into<String>) -> Self {
+        Self {
+            request_id,
+            kind,
+            body: body.into(),
+        }
+    }
+
+    /// Reads the next packet, or `None` if the client closed the connection in between packets.
+    pub async fn read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Self>> {
+        let length = match reader.read_i32_le().await {
+            Ok(length) => length,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let length = usize::try_from(length).unwrap_or(0);
+        if !(HEADER_AND_PADDING..=HEADER_AND_PADDING + MAX_REQUEST_BODY).contains(&length) {
+            return Err(Error::Generic(format!(
+                "Invalid RCON packet length: {}",
+                length
+            )));
+        }
+
+        let request_id = reader.read_i32_le().await?;
+        let kind = reader.read_i32_le().await?;
+        let mut body = vec![0; length - 8];
+        reader.read_exact(&mut body).await?;
+        // Drop the null terminator and the padding byte
+        body.truncate(body.len() - 2);
+
+        Ok(Some(Self {
+            request_id,
+            kind,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        }))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let length = (self.body.len() + HEADER_AND_PADDING) as i32;
+        let mut bytes = Vec::with_capacity(self.body.len() + HEADER_AND_PADDING + 4);
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(&self.request_id.to_le_bytes());
+        bytes.extend_from_slice(&self.kind.to_le_bytes());
+        bytes.extend_from_slice(self.body.as_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+}
+
+/// The responses to a command, with its output split up so no body is too long.
+pub fn responses(request_id: i32, output: &str) -> Vec<RconPacket> {
+    let mut responses = Vec::new();
+    let mut rest = output;
+    while rest.len() > MAX_RESPONSE_BODY {
+        let mut end = MAX_RESPONSE_BODY;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        responses.push(RconPacket::new(request_id, RESPONSE, &rest[..end]));
+        rest = &rest[end..];
+    }
+    responses.push(RconPacket::new(request_id, RESPONSE, rest));
+    responses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_packet_round_trip() {
+        let packet = RconPacket::new(7, COMMAND, "list");
+        let bytes = packet.encode();
+        assert_eq!(&bytes[..4], &14i32.to_le_bytes());
+
+        let mut reader = bytes.as_slice();
+        assert_eq!(RconPacket::read(&mut reader).await.unwrap(), Some(packet));
+        assert_eq!(RconPacket::read(&mut reader).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_packets_are_rejected() {
+        let packet = RconPacket::new(1, COMMAND, "a".repeat(MAX_REQUEST_BODY + 1));
+        let bytes = packet.encode();
+        assert!(RconPacket::read(&mut bytes.as_slice()).await.is_err());
+    }
+
+    #[test]
+    fn test_long_output_is_split() {
+        let output = "é".repeat(MAX_RESPONSE_BODY);
+        let split = responses(3, &output);
+        assert_eq!(split.len(), 2);
+        assert!(split.iter().all(|r| r.body.len() <= MAX_RESPONSE_BODY));
+        assert_eq!(
+            split
+                .iter()
+                .map(|r| r.body.as_str())
+                .collect::<String>(),
+            output
+        );
+        assert_eq!(responses(3, "").len(), 1);
+    }
+}
@@ -0,0 +1,90 @@
+//! Keeping everyone's tab list in sync with who's online.
+//!
+//! Players are added when they join, and taken off again in [crate::net::drop_conn] when they
+//! leave. Their ping bars are kept up to date by [crate::net::systems::tab_list::TabListLatency].
+
+use uuid::Uuid;
+
+use crate::net::packets::outgoing::player_info_update::{PlayerInfoEntry, PlayerInfoUpdate};
+use crate::net::packets::ConnectionId;
+use crate::net::utils::broadcast::{broadcast, broadcast_except};
+use crate::state::{GlobalState, ServerState};
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::game_profile::GameProfile;
+use crate::utils::components::keep_alive::KeepAlive;
+use crate::utils::components::player::Player;
+use crate::utils::prelude::*;
+
+impl ServerState {
+    /// Puts a player that just joined on everyone's tab list, and sends them everyone who's
+    /// already online, themselves included.
+    pub async fn add_to_tab_list(self: &GlobalState, conn_id: ConnectionId) -> Result<()> {
+        let Some(entry) = self.tab_list_entry(conn_id).await else {
+            return Ok(());
+        };
+
+        let mut entries = Vec::new();
+        for id in self.player_ids().await {
+            if let Some(entry) = self.tab_list_entry(id).await {
+                entries.push(entry);
+            }
+        }
+        let conn = self.connections.get_connection(conn_id)?;
+        conn.read()
+            .await
+            .send_packet(PlayerInfoUpdate::add_players(entries))
+            .await?;
+
+        broadcast_except(&PlayerInfoUpdate::add_players(vec![entry]), self, conn_id).await
+    }
+
+    /// Updates everyone's ping bars with the latency measured from the keep alives.
+    pub async fn broadcast_latencies(self: &GlobalState) -> Result<()> {
+        let mut latencies = Vec::new();
+        let query = self.world.query::<(&Player, &KeepAlive)>();
+        for (_, (player, keep_alive)) in query.iter().await {
+            latencies.push((
+                Uuid::from_u128(player.uuid),
+                keep_alive.latency().as_millis() as i32,
+            ));
+        }
+        if latencies.is_empty() {
+            return Ok(());
+        }
+
+        broadcast(&PlayerInfoUpdate::update_latency(&latencies), self).await
+    }
+
+    /// Tells everyone a player's game mode changed, which the tab list shows for spectators.
+    pub async fn broadcast_game_mode(
+        self: &GlobalState,
+        uuid: Uuid,
+        game_mode: GameMode,
+    ) -> Result<()> {
+        broadcast(&PlayerInfoUpdate::update_game_mode(uuid, game_mode), self).await
+    }
+
+    async fn player_ids(&self) -> Vec<usize> {
+        let query = self.world.query::<&Player>();
+        query.iter().await.map(|(id, _)| id).collect()
+    }
+
+    /// Everything the tab list shows about a player, or `None` if they haven't finished joining.
+    async fn tab_list_entry(&self, entity: usize) -> Option<PlayerInfoEntry> {
+        let profile = self.world.get_component::<GameProfile>(entity).await.ok()?;
+        let game_mode = self
+            .world
+            .get_component::<GameMode>(entity)
+            .await
+            .map(|game_mode| *game_mode)
+            .unwrap_or_default();
+        let latency = self
+            .world
+            .get_component::<KeepAlive>(entity)
+            .await
+            .map(|keep_alive| keep_alive.latency().as_millis() as i32)
+            .unwrap_or(0);
+
+        Some(PlayerInfoEntry::new(&profile, game_mode, latency))
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use crate::utils::config::ThrottleConfig;
+
+/// Past this many tracked addresses, the ones that have fully recovered are forgotten.
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Limits how often a single address can open a new connection.
+///
+/// Every address gets a token bucket holding up to `max_connections` tokens, which refills
+/// completely over `window`. Each accepted connection takes a token, and a connection that finds
+/// the bucket empty is dropped before it gets to the handshake.
+#[derive(Debug)]
+pub struct ConnectionThrottle {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: HashMap<IpAddr, Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl ConnectionThrottle {
+    /// A `max_connections` of 0 turns the throttle off.
+    pub fn new(max_connections: u32, window: Duration) -> Self {
+        let capacity = max_connections as f64;
+        Self {
+            capacity,
+            refill_per_sec: capacity / window.as_secs_f64().max(f64::EPSILON),
+            buckets: HashMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &ThrottleConfig) -> Self {
+        Self::new(
+            config.max_connections,
+            Duration::from_secs(config.window_secs),
+        )
+    }
+
+    /// Takes a token for a new connection from `ip`. Returns false if the connection should be
+    /// dropped.
+    pub fn try_acquire(&mut self, ip: IpAddr, now: Instant) -> bool {
+        if self.capacity == 0.0 {
+            return true;
+        }
+
+        if self.buckets.len() >= PRUNE_THRESHOLD {
+            self.prune(now);
+        }
+
+        let capacity = self.capacity;
+        let bucket = self.buckets.entry(ip).or_insert(Bucket {
+            tokens: capacity,
+            last_refill: now,
+        });
+        bucket.refill(capacity, self.refill_per_sec, now);
+
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+
+    /// Forgets every address whose bucket has refilled, since it would start out full anyway.
+    pub fn prune(&mut self, now: Instant) {
+        let (capacity, refill_per_sec) = (self.capacity, self.refill_per_sec);
+        self.buckets.retain(|_, bucket| {
+            bucket.refill(capacity, refill_per_sec, now);
+            bucket.tokens < capacity
+        });
+    }
+}
+
+impl Bucket {
+    fn refill(&mut self, capacity: f64, refill_per_sec: f64, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * refill_per_sec).min(capacity);
+        self.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rapid_connects_from_one_ip_are_throttled() {
+        let mut throttle = ConnectionThrottle::new(3, Duration::from_secs(9));
+        let flooder: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(throttle.try_acquire(flooder, now));
+        }
+        assert!(!throttle.try_acquire(flooder, now));
+
+        // Someone else connecting at the same moment isn't affected
+        assert!(throttle.try_acquire(other, now));
+
+        // One token comes back every 3 seconds
+        assert!(!throttle.try_acquire(flooder, now + Duration::from_secs(2)));
+        assert!(throttle.try_acquire(flooder, now + Duration::from_secs(3)));
+        assert!(!throttle.try_acquire(flooder, now + Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_zero_max_connections_disables_throttle() {
+        let mut throttle = ConnectionThrottle::new(0, Duration::from_secs(10));
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let now = Instant::now();
+        assert!((0..100).all(|_| throttle.try_acquire(ip, now)));
+    }
+}
@@ -2,6 +2,7 @@ use std::env;
 use std::env::current_exe;
 
 use crate::setup;
+use crate::utils::constants::{DEFAULT_CONFIG_FILE, LEGACY_CONFIG_FILE};
 use crate::utils::error::Error;
 use tokio::fs;
 use tracing::{error, info};
@@ -32,8 +33,8 @@ pub async fn handle_setup() -> crate::utils::prelude::Result<bool> {
         let dir = exe.parent();
         match dir {
             Some(dir) => {
-                let config_path = dir.join("config.toml");
-                if !config_path.exists() {
+                let config_path = dir.join(DEFAULT_CONFIG_FILE);
+                if !config_path.exists() && !dir.join(LEGACY_CONFIG_FILE).exists() {
                     setup::setup().await?;
                 }
                 Ok(false)
@@ -55,7 +56,7 @@ pub(crate) async fn setup() -> Result<(), Error> {
     info!("Creating files...");
     let exe = current_exe()?;
     let dir = exe.parent().unwrap();
-    fs::write(dir.join("config.toml"), BASE_CONFIG.as_bytes()).await?;
+    fs::write(dir.join(DEFAULT_CONFIG_FILE), BASE_CONFIG.as_bytes()).await?;
     fs::create_dir(dir.join("logs")).await?;
     fs::create_dir(dir.join("plugins")).await?;
     fs::write(
@@ -83,8 +84,86 @@ max_players = 20
 # This is the number of times per second the server will send updates to the client.
 # Having this too low will cause noticable lag for clients but may improve server performance.
 network_tick_rate = 0
+# Packets at least this many bytes long are compressed. 0 compresses everything, -1 turns compression off.
+network_compression_threshold = 256
 # The default world name. You can switch between mutliple worlds by changing this value.
 world = "world"
+# How many chunks around them players get sent. Players with a lower render distance get sent fewer.
+view_distance = 10
+# The least important log messages that get printed: trace, debug, info, warn or error.
+# Starting the server with --log=<level> overrides this.
+log_level = "debug"
+# How many seconds a chunk that left a player's view stays loaded before it's unloaded.
+# Stops players moving back and forth over a chunk border from reloading the same chunks.
+chunk_unload_grace_secs = 5
+# How many chunks are bundled into a single socket write when sending the world to a player.
+# Higher values mean fewer syscalls, but bigger bursts of data. 1 writes every chunk on its own.
+chunk_batch_size = 16
+# Leave the light data out of the chunks sent to spectators, who see everything fully lit anyway.
+# Saves a good chunk of bandwidth, at the cost of the light being wrong if they change game mode.
+reduced_spectator_chunks = false
+# When a lot of blocks in a chunk change at once, the whole chunk is sent again instead of listing
+# every changed block. This is the share of the blocks in the changed sections it takes.
+chunk_resend_density = 0.25
+# How many chunks are kept in memory once they've been loaded or generated, so they don't have to
+# be read or generated again for the next player. Each one takes roughly 10 to 50 KB.
+chunk_cache_capacity = 4096
+# How many seconds a cached chunk that nobody has asked for stays in memory. Changed chunks are
+# saved when they're dropped from the cache.
+chunk_cache_ttl_secs = 300
+# How many seconds a player has to answer a keep alive before they're disconnected. Keep alives are
+# sent every 15 seconds, as long as the last one was answered.
+keep_alive_timeout_secs = 30
+# How chat messages are shown, with {player} and {message} filled in. Set to "" to send them as
+# player chat instead, which the client formats itself and lets players hide.
+chat_format = "<{player}> {message}"
+# What players see on the disconnect screen when the server stops.
+shutdown_message = "Server closed"
+# How often the world and player data are saved, in seconds. Set to 0 to only save when stopping.
+autosave_interval_secs = 300
+# How many chunks an autosave writes per tick. Lower spreads saves out more, to avoid lag spikes.
+autosave_chunks_per_tick = 16
+# Where the parsed block state registry is cached, to speed up startup. It's rebuilt automatically
+# when the server's bundled registry changes. Set to "" to turn the cache off.
+block_registry_cache = "block_registry.bin"
+# Vanilla's blocks.json report, generated with the server jar's --reports option, to load the block
+# registry from instead of the one bundled with the server. Leave empty to use the bundled one.
+blocks_report = ""
+# Vanilla's registries.json report, generated the same way, to look up which block a held item
+# places. Without it only a few basic building blocks can be placed.
+registries_report = ""
+# Where players' positions and game modes are saved when they leave, one NBT file per player.
+player_data_dir = "playerdata"
+# Where chunks come from. "imported" serves the world imported into the database, "debug" shows
+# every block state on a grid instead, which is handy for checking block rendering. "anvil" reads
+# chunks straight from the region files of a vanilla world, without importing it first. Chunks
+# missing from the database or region files are generated from the seed below. "flat" makes a
+# superflat world out of the layers in flat_layers, and saves the chunks players change to region
+# files in region_dir.
+generator = "imported"
+# The region directory of the vanilla world served by the "anvil" generator, and where the "flat"
+# generator saves its chunks.
+region_dir = "world/region"
+# The layers of the "flat" generator, from the bottom of the world up. Prefix a block with a number
+# and * for a thicker layer, e.g. "3*minecraft:dirt".
+flat_layers = ["minecraft:bedrock", "2*minecraft:dirt", "minecraft:grass_block"]
+# Seed for the terrain generated for chunks that aren't in the database or region files yet.
+seed = 0
+# Usernames of the players allowed to use operator-only features, like editing command blocks.
+operators = []
+# Operators and their permission levels, in the same format as vanilla's ops.json.
+# Changed by /op and /deop.
+ops_file = "ops.json"
+# Check with Mojang that players own the account they log in with, and encrypt their connections.
+# Needs the server to be able to reach sessionserver.mojang.com.
+online_mode = false
+# In offline mode, look up the skin of the Mojang account with the same name as the player, so
+# not everyone shows up as Steve. Needs the server to be able to reach api.mojang.com.
+offline_skins = false
+# The server software players see in their debug screen (F3).
+server_brand = "FerrumC"
+# Scroll a wave of crabs through the debug screen's brand instead of showing server_brand.
+animated_brand = false
 
 [database]
 # The cache size in KB. We recommend leaving this at the default value.
@@ -92,4 +171,68 @@ cache_size = 1024
 # The compression algorithm to use. "fast" is recommended for most use cases.
 # "best" is slower but may provide better compression ratio.
 compression = "fast"
+
+[whitelist]
+# Only let the players listed in the whitelist file join.
+enabled = false
+# The whitelist file, in the same format as vanilla's whitelist.json.
+file = "whitelist.json"
+# The message shown to players that aren't whitelisted.
+kick_message = "You are not whitelisted on this server!"
+
+[bans]
+# Banned players, as a list of {"uuid", "name", "reason", "expires"} objects.
+# "expires" is a unix timestamp in seconds, leave it out for a permanent ban.
+players_file = "banned-players.json"
+# Banned addresses, as a list of {"ip", "reason", "expires"} objects.
+# "ip" can be a single address or a range like "10.0.0.0/8".
+ips_file = "banned-ips.json"
+
+[forwarding]
+# How a proxy in front of the server passes on players' real UUIDs, addresses and skins.
+# "none" for players connecting directly, "legacy" for BungeeCord's ip_forward, or "modern" for
+# Velocity's modern forwarding. Online mode is left to the proxy when this is on.
+mode = "none"
+# The forwarding secret from Velocity's config, for the "modern" mode.
+secret = ""
+
+[resource_pack]
+# Where players download the server's resource pack from when they join. Empty to not send one.
+url = ""
+# The pack's SHA-1 hash, as 40 hex digits, so players only download it again when it changes.
+sha1 = ""
+# Kick players that decline the pack.
+required = false
+# Shown along with the question whether to download the pack. Empty for the client's default.
+prompt = ""
+# The message shown to players that decline a required pack.
+kick_message = "This server requires a custom resource pack"
+
+[throttle]
+# How many connections a single address can open within the window. Anything past that is
+# dropped before the handshake. Set to 0 to turn the limit off. It's always off when forwarding
+# from a proxy, since every connection comes from the proxy's address then.
+max_connections = 5
+# The window, in seconds.
+window_secs = 10
+
+[rcon]
+# Let admin tools and hosting panels run commands remotely over the RCON protocol.
+enabled = false
+# The port RCON listens on, on the same host as the server.
+port = 25575
+# The password RCON clients log in with. RCON stays off until one is set.
+password = ""
+
+[query]
+# Answer GameSpy4 queries, which server list sites and panels use to show the MOTD and who's online.
+enabled = false
+# The UDP port queries are answered on. It can be the same as the server's port.
+port = 25565
+
+[gamerules]
+# Whether the time of day advances. Set to false to freeze the sun in place.
+do_daylight_cycle = true
+# Whether the weather changes by itself. Set to false to keep the current weather.
+do_weather_cycle = true
 "#;
@@ -1,15 +1,71 @@
+use crate::commands::CommandDispatcher;
 use crate::database::Database;
 use crate::ecs::world::World;
 use crate::net::ConnectionList;
-use std::sync::Arc;
+use crate::net::utils::plugin_channels::PluginChannelRegistry;
+use crate::net::systems::game_loop::{TickSystem, TickTimings};
+use std::sync::{Arc, Mutex, RwLock};
 use crate::events::creation::dispatcher::EventDispatcher;
+use crate::utils::config::ServerConfig;
+use crate::world::block_changes::PendingBlockChanges;
+use crate::world::autosave::Autosave;
+use crate::world::block_entities::BlockEntityStore;
+use crate::world::block_registry::BlockRegistry;
+use crate::world::border::WorldBorder;
+use crate::world::chunk_cache::ChunkCache;
+use crate::world::generation::ChunkGenerator;
+use crate::world::player_data::PlayerDataStore;
+use crate::world::spawn::WorldSpawn;
+use crate::world::time::WorldTime;
+use crate::world::weather::Weather;
+use crate::auth::skins::SkinCache;
+use crate::utils::bans::BanList;
+use crate::utils::permissions::OpList;
+use crate::utils::whitelist::Whitelist;
 
 pub struct ServerState {
+    /// The server configuration, loaded from the config file at startup.
+    pub config: &'static ServerConfig,
     pub world: Arc<World>,
     pub connections: ConnectionList,
     pub database: Database,
     pub server_stream: tokio::net::TcpListener,
     pub event_dispatcher: Arc<EventDispatcher>,
+    pub world_time: WorldTime,
+    pub world_spawn: WorldSpawn,
+    pub world_border: WorldBorder,
+    pub weather: Weather,
+    pub block_entities: BlockEntityStore,
+    /// Lookups between block state IDs and block states.
+    pub block_registry: &'static BlockRegistry,
+    pub whitelist: Whitelist,
+    pub bans: BanList,
+    pub operators: OpList,
+    pub player_data: PlayerDataStore,
+    /// Skins looked up for players in offline mode.
+    pub skins: SkinCache,
+    /// Makes up the chunks that aren't stored anywhere yet.
+    pub chunk_generator: Box<dyn ChunkGenerator>,
+    /// The chunks that were loaded or generated recently.
+    pub chunk_cache: ChunkCache,
+    /// The changed chunks the running autosave hasn't written yet.
+    pub autosave: Autosave,
+    /// Held while chunks are saved in the background. See [ServerState::save_in_background].
+    pub chunk_writes: Arc<tokio::sync::Mutex<()>>,
+    /// Held while blocks are being changed. See [ServerState::set_blocks].
+    pub block_edits: tokio::sync::Mutex<()>,
+    /// Block changes that haven't been sent to players yet.
+    pub pending_block_changes: PendingBlockChanges,
+    /// Everything the game loop runs each tick, in order. See [ServerState::register_tick_system].
+    pub tick_systems: RwLock<Vec<Arc<dyn TickSystem>>>,
+    /// How long recent ticks took, per system.
+    pub tick_timings: Mutex<TickTimings>,
+    /// Every command players can run. See [ServerState::register_command].
+    pub commands: RwLock<CommandDispatcher>,
+    /// What handles the plugin channels players send on. See [ServerState::register_channel].
+    pub plugin_channels: RwLock<PluginChannelRegistry>,
+    /// Set once the server should stop. See [ServerState::request_shutdown].
+    pub shutdown: tokio::sync::watch::Sender<bool>,
 }
 
 pub type GlobalState = Arc<ServerState>;
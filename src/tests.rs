@@ -1,7 +1,10 @@
 mod chunk_stuff;
+mod compression;
+pub mod connections;
 mod nbt_de;
 mod nbt_ser;
 pub mod query;
+mod test_vectors;
 
 use std::io::Cursor;
 
@@ -27,6 +30,46 @@ async fn test_macro_decode() {
     assert_eq!(handshake.server_port, 25565);
     assert_eq!(handshake.next_state, VarInt::new(1));
 }
+
+#[tokio::test]
+async fn test_macro_decode_varint_attribute() {
+    #[derive(NetDecode)]
+    struct Handshake {
+        #[decode(varint)]
+        protocol_version: i32,
+        server_address: String,
+        server_port: u16,
+        #[decode(varint)]
+        next_state: u8,
+    }
+    let mut data = Cursor::new(vec![
+        0xFB, 0x05, 0x09, 0x31, 0x32, 0x37, 0x2E, 0x30, 0x2E, 0x30, 0x2E, 0x31, 0x63, 0xDD, 0x01,
+    ]);
+    let handshake = Handshake::net_decode(&mut data).await.unwrap();
+    assert_eq!(handshake.protocol_version, 763);
+    assert_eq!(handshake.server_address, "127.0.0.1".to_string());
+    assert_eq!(handshake.server_port, 25565);
+    assert_eq!(handshake.next_state, 1);
+}
+
+#[tokio::test]
+async fn test_macro_decode_raw_bytes_attribute() {
+    #[derive(NetDecode)]
+    struct PluginMessage {
+        #[decode(raw_bytes(prepend_length = true))]
+        header: Vec<u8>,
+        #[decode(raw_bytes)]
+        data: Vec<u8>,
+    }
+    let mut data = Cursor::new(vec![0x02, 0xAA, 0xBB, 0x01, 0x02, 0x03]);
+    let message = PluginMessage::net_decode(&mut data).await.unwrap();
+    assert_eq!(message.header, vec![0xAA, 0xBB]);
+    assert_eq!(message.data, vec![0x01, 0x02, 0x03]);
+
+    // The length says there are more bytes than the packet holds
+    let mut data = Cursor::new(vec![0x05, 0xAA, 0xBB]);
+    assert!(PluginMessage::net_decode(&mut data).await.is_err());
+}
 /*
 #[tokio::test]
 async fn test_nbt_decode() {
@@ -0,0 +1,126 @@
+use std::io::{Cursor, Read};
+
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
+use ferrumc_codec::network_types::varint::VarInt;
+use flate2::read::ZlibDecoder;
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::net::packets::outgoing::status::OutgoingStatusResponse;
+use crate::net::{decode_frame, frame_packet, Connection, State, MAX_DECOMPRESSED_LENGTH};
+
+async fn test_connection(state: State, compressed: bool) -> (Connection, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap())
+        .await
+        .unwrap();
+    let (socket, _) = listener.accept().await.unwrap();
+
+    let mut conn = Connection::new(0, socket, state);
+    conn.metadata.compressed = compressed;
+
+    (conn, client)
+}
+
+#[tokio::test]
+async fn test_status_response_is_never_compressed() {
+    // Even if the connection was somehow flagged as compressed, status packets must go out plain
+    let (conn, _client) = test_connection(State::Status, true).await;
+    assert!(!conn.should_compress());
+    assert_eq!(conn.compression_threshold(), None);
+
+    let response = || OutgoingStatusResponse::new_auto("{}".repeat(256));
+
+    let mut expected = Vec::new();
+    response()
+        .net_encode(&mut expected, &EncodeOption::Default)
+        .await
+        .unwrap();
+
+    let frame = frame_packet(response(), conn.compression_threshold())
+        .await
+        .unwrap();
+    assert_eq!(frame, expected);
+}
+
+#[tokio::test]
+async fn test_play_packet_over_threshold_is_compressed() {
+    let (conn, _client) = test_connection(State::Play, true).await;
+    assert!(conn.should_compress());
+
+    let json = "{}".repeat(256);
+    let frame = frame_packet(OutgoingStatusResponse::new_auto(json.clone()), Some(64))
+        .await
+        .unwrap();
+
+    let mut cursor = Cursor::new(frame);
+    let packet_length = VarInt::read(&mut cursor).await.unwrap();
+    let data_length = VarInt::read(&mut cursor).await.unwrap();
+    assert_eq!(
+        packet_length.get_val() as usize + packet_length.get_len(),
+        cursor.get_ref().len()
+    );
+    assert!(data_length.get_val() >= 64);
+
+    let mut decompressed = Vec::new();
+    ZlibDecoder::new(cursor)
+        .read_to_end(&mut decompressed)
+        .unwrap();
+    assert_eq!(decompressed.len(), data_length.get_val() as usize);
+
+    let mut expected = Vec::new();
+    OutgoingStatusResponse::new_auto(json)
+        .net_encode(&mut expected, &EncodeOption::AlwaysOmitSize)
+        .await
+        .unwrap();
+    assert_eq!(decompressed, expected);
+}
+
+#[tokio::test]
+async fn test_decode_frame_round_trips_compressed_packet() {
+    let json = "{}".repeat(256);
+    let frame = frame_packet(OutgoingStatusResponse::new_auto(json.clone()), Some(64))
+        .await
+        .unwrap();
+
+    // Strip the packet length, the frame reader hands out everything after it
+    let (_, header_len) = VarInt::from_bytes(&frame).unwrap();
+    let (packet_id, mut cursor) = decode_frame(frame[header_len..].to_vec(), true).unwrap();
+    assert_eq!(packet_id, 0x00);
+
+    let mut body = Vec::new();
+    cursor.read_to_end(&mut body).unwrap();
+
+    let mut expected = Vec::new();
+    json.net_encode(&mut expected, &EncodeOption::Default)
+        .await
+        .unwrap();
+    assert_eq!(body, expected);
+}
+
+#[tokio::test]
+async fn test_decode_frame_rejects_wrong_data_length() {
+    let frame = frame_packet(OutgoingStatusResponse::new_auto("{}".repeat(256)), Some(64))
+        .await
+        .unwrap();
+    let (_, header_len) = VarInt::from_bytes(&frame).unwrap();
+    let (data_length, data_length_len) = VarInt::from_bytes(&frame[header_len..]).unwrap();
+    let compressed = &frame[header_len + data_length_len..];
+
+    // Claim the packet is one byte shorter than it really is
+    let mut lying = Vec::new();
+    VarInt::from(data_length.get_val() - 1)
+        .net_encode(&mut lying, &EncodeOption::AlwaysOmitSize)
+        .await
+        .unwrap();
+    lying.extend_from_slice(compressed);
+    assert!(decode_frame(lying, true).is_err());
+
+    // And one that claims more than any packet may be
+    let mut oversized = Vec::new();
+    VarInt::from(MAX_DECOMPRESSED_LENGTH + 1)
+        .net_encode(&mut oversized, &EncodeOption::AlwaysOmitSize)
+        .await
+        .unwrap();
+    oversized.extend_from_slice(compressed);
+    assert!(decode_frame(oversized, true).is_err());
+}
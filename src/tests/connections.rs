@@ -0,0 +1,30 @@
+//! Helpers for tests that need players connected to a [GlobalState].
+
+use std::sync::Arc;
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+
+use crate::net::{Connection, ConnectionWrapper, State};
+use crate::state::GlobalState;
+
+/// Creates an entity with a connection in the play state, the same way a real connection would
+/// be set up. Returns the entity id and the client's end of the socket.
+pub async fn add_play_connection(state: &GlobalState) -> (usize, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap())
+        .await
+        .unwrap();
+    let (socket, _) = listener.accept().await.unwrap();
+
+    let entity_id = state.world.create_entity().await.build();
+    let conn = Arc::new(RwLock::new(Connection::new(entity_id, socket, State::Play)));
+
+    state
+        .world
+        .get_component_storage()
+        .insert(entity_id, ConnectionWrapper(conn.clone()));
+    state.connections.connections.insert(entity_id, conn);
+
+    (entity_id, client)
+}
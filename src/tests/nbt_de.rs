@@ -1,5 +1,5 @@
 use crate::tests::nbt_de::test_de_data::Player;
-use nbt_lib::{NBTDeserialize, NBTDeserializeBytes, NBTSerialize};
+use nbt_lib::{read_root_tag, NBTDeserialize, NBTDeserializeBytes, NBTSerialize};
 use std::io::Cursor;
 
 pub mod test_de_data {
@@ -440,3 +440,36 @@ fn test_byte_array() {
     let deserialized_byte_array = ByteArray::read_from_bytes(&mut Cursor::new(buffer)).unwrap();
     println!("{:?}", deserialized_byte_array);
 }
+
+#[test]
+fn test_trailing_bytes_after_root_are_ignored() {
+    #[derive(NBTSerialize, NBTDeserialize)]
+    #[nbt(is_root)]
+    struct Sample {
+        name: String,
+        count: i32,
+    }
+
+    let sample = Sample {
+        name: "Steve".to_string(),
+        count: 3,
+    };
+
+    let mut buffer = Vec::new();
+    sample.nbt_serialize(&mut buffer).unwrap();
+    let nbt_len = buffer.len();
+
+    // Sector padding, and junk that would fail to parse as another tag
+    buffer.extend_from_slice(&[0x00, 0x00, 0x0A, 0xDE, 0xAD, 0xBE, 0xEF]);
+
+    let mut cursor = Cursor::new(buffer.clone());
+    let (_, consumed) = read_root_tag(&mut cursor).unwrap();
+    assert_eq!(consumed, nbt_len);
+    assert_eq!(cursor.position() as usize, nbt_len);
+
+    let mut cursor = Cursor::new(buffer);
+    let deserialized = Sample::read_from_bytes(&mut cursor).unwrap();
+    assert_eq!(deserialized.name, "Steve");
+    assert_eq!(deserialized.count, 3);
+    assert_eq!(cursor.position() as usize, nbt_len);
+}
@@ -0,0 +1,206 @@
+//! Round-trip tests against captured packets.
+//!
+//! Every `.bin` file in `src/tests/vectors` is a sequence of records, each laid out as:
+//!
+//! | Field     | Type   | Notes                                              |
+//! |-----------|--------|----------------------------------------------------|
+//! | state     | u8     | 0 = handshake, 1 = status, 2 = login, 3 = play     |
+//! | direction | u8     | 0 = serverbound, 1 = clientbound                   |
+//! | packet id | VarInt |                                                    |
+//! | length    | VarInt | Length of the body in bytes                        |
+//! | body      | bytes  | The packet body, without the length and packet id  |
+//!
+//! Serverbound records are fed through the packet registry's decoder for their id, which must consume
+//! the whole body. Clientbound records are decoded, re-encoded with the matching outgoing packet
+//! and compared byte for byte against the capture.
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
+use ferrumc_codec::network_types::varint::VarInt;
+
+use crate::net::packets::decode_packet;
+use crate::net::packets::outgoing::ping::OutgoingPing;
+use crate::net::packets::outgoing::status::OutgoingStatusResponse;
+use crate::net::State;
+use crate::utils::impls::packet_impls::NetDecode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Serverbound,
+    Clientbound,
+}
+
+#[derive(Debug)]
+struct PacketRecord {
+    state: State,
+    direction: Direction,
+    packet_id: i32,
+    body: Vec<u8>,
+}
+
+fn vectors_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/vectors")
+}
+
+fn read_varint(bytes: &[u8], offset: &mut usize) -> i32 {
+    let (value, len) = VarInt::from_bytes(&bytes[*offset..]).expect("Malformed VarInt in vector");
+    *offset += len;
+    value.get_val()
+}
+
+/// Splits a vector file into its records, panicking on anything malformed.
+fn parse_records(bytes: &[u8]) -> Vec<PacketRecord> {
+    let mut records = Vec::new();
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        assert!(offset + 2 <= bytes.len(), "Truncated record header");
+        let state = match bytes[offset] {
+            0 => State::Handshake,
+            1 => State::Status,
+            2 => State::Login,
+            3 => State::Play,
+            other => panic!("Unknown state {other} in vector"),
+        };
+        let direction = match bytes[offset + 1] {
+            0 => Direction::Serverbound,
+            1 => Direction::Clientbound,
+            other => panic!("Unknown direction {other} in vector"),
+        };
+        offset += 2;
+
+        let packet_id = read_varint(bytes, &mut offset);
+        let len = read_varint(bytes, &mut offset) as usize;
+        assert!(offset + len <= bytes.len(), "Truncated record body");
+        let body = bytes[offset..offset + len].to_vec();
+        offset += len;
+
+        records.push(PacketRecord {
+            state,
+            direction,
+            packet_id,
+            body,
+        });
+    }
+
+    records
+}
+
+fn load_vectors() -> Vec<(String, Vec<PacketRecord>)> {
+    let mut files = std::fs::read_dir(vectors_dir())
+        .expect("Missing test vector directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "bin"))
+        .collect::<Vec<_>>();
+    files.sort();
+
+    files
+        .into_iter()
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            let bytes = std::fs::read(&path).unwrap();
+            (name, parse_records(&bytes))
+        })
+        .collect()
+}
+
+/// Decodes a serverbound record, returning the number of body bytes the decoder consumed.
+async fn decode_serverbound(record: &PacketRecord) -> u64 {
+    let mut cursor = Cursor::new(record.body.clone());
+    let found = match u8::try_from(record.packet_id) {
+        Ok(id) => decode_packet(id, &record.state, &mut cursor)
+            .await
+            .expect("Failed to decode serverbound vector"),
+        Err(_) => false,
+    };
+    assert!(
+        found,
+        "No packet registered for serverbound {:?} packet {:#04x}",
+        record.state, record.packet_id
+    );
+    cursor.position()
+}
+
+/// Decodes a clientbound record and encodes it again, returning the encoded bytes: the packet id
+/// as a VarInt followed by the body, without a length prefix.
+async fn reencode_clientbound(record: &PacketRecord) -> Vec<u8> {
+    let mut cursor = Cursor::new(record.body.clone());
+    let mut out = Vec::new();
+    match (&record.state, record.packet_id) {
+        (State::Status, 0x00) => {
+            let json = String::net_decode(&mut cursor).await.unwrap();
+            OutgoingStatusResponse::new_auto(*json)
+                .net_encode(&mut out, &EncodeOption::AlwaysOmitSize)
+                .await
+                .unwrap();
+        }
+        (State::Status, 0x01) => {
+            let payload = i64::net_decode(&mut cursor).await.unwrap();
+            OutgoingPing {
+                packet_id: VarInt::from(0x01),
+                payload: *payload,
+            }
+            .net_encode(&mut out, &EncodeOption::AlwaysOmitSize)
+            .await
+            .unwrap();
+        }
+        (state, id) => panic!("No encoder registered for clientbound {state:?} packet {id:#04x}"),
+    }
+    assert_eq!(
+        cursor.position() as usize,
+        record.body.len(),
+        "Trailing bytes in clientbound vector"
+    );
+    out
+}
+
+#[test]
+fn test_parse_records() {
+    let bytes = [0x01, 0x00, 0x00, 0x00, 0x01, 0x01, 0x01, 0x02, 0xAB, 0xCD];
+    let records = parse_records(&bytes);
+    assert_eq!(records.len(), 2);
+    assert!(matches!(records[0].state, State::Status));
+    assert_eq!(records[0].direction, Direction::Serverbound);
+    assert!(records[0].body.is_empty());
+    assert_eq!(records[1].direction, Direction::Clientbound);
+    assert_eq!(records[1].packet_id, 0x01);
+    assert_eq!(records[1].body, vec![0xAB, 0xCD]);
+}
+
+#[tokio::test]
+async fn test_captured_vectors_round_trip() {
+    let vectors = load_vectors();
+    assert!(!vectors.is_empty(), "No test vectors found");
+
+    for (name, records) in vectors {
+        assert!(!records.is_empty(), "{name} contains no records");
+        for record in records {
+            match record.direction {
+                Direction::Serverbound => {
+                    let consumed = decode_serverbound(&record).await;
+                    assert_eq!(
+                        consumed as usize,
+                        record.body.len(),
+                        "{name}: {:?} packet {:#04x} left trailing bytes",
+                        record.state,
+                        record.packet_id
+                    );
+                }
+                Direction::Clientbound => {
+                    let reencoded = reencode_clientbound(&record).await;
+                    let (id, id_len) = VarInt::from_bytes(&reencoded).unwrap();
+                    assert_eq!(id.get_val(), record.packet_id);
+                    assert_eq!(
+                        &reencoded[id_len..],
+                        record.body.as_slice(),
+                        "{name}: {:?} packet {:#04x} did not re-encode identically",
+                        record.state,
+                        record.packet_id
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,488 @@
+use std::fmt::Display;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::utils::config::BanConfig;
+use crate::utils::prelude::*;
+
+fn default_reason() -> String {
+    "Banned by an operator.".to_string()
+}
+
+/// A banned player, identified by their UUID.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerBan {
+    /// Nil for players that were banned by name while they weren't online, who are matched on
+    /// their name instead.
+    pub uuid: Uuid,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_reason")]
+    pub reason: String,
+    /// Unix timestamp (in seconds) the ban runs out at. Bans without one are permanent.
+    #[serde(default)]
+    pub expires: Option<u64>,
+}
+
+/// A banned address, or a whole range of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpBan {
+    pub ip: IpRange,
+    #[serde(default = "default_reason")]
+    pub reason: String,
+    /// Unix timestamp (in seconds) the ban runs out at. Bans without one are permanent.
+    #[serde(default)]
+    pub expires: Option<u64>,
+}
+
+/// A single address or a CIDR block, e.g. `203.0.113.7` or `10.0.0.0/8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpRange {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpRange {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // Dual-stack sockets report IPv4 peers as IPv4-mapped IPv6 addresses
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::Generic(format!("Invalid IP range: {}", s));
+
+        let (ip, prefix_len) = match s.split_once('/') {
+            Some((ip, prefix_len)) => (ip, Some(prefix_len)),
+            None => (s, None),
+        };
+        let network = IpAddr::from_str(ip.trim()).map_err(|_| invalid())?;
+        let max_prefix_len = if network.is_ipv4() { 32 } else { 128 };
+        let mut prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len.trim().parse::<u8>().map_err(|_| invalid())?,
+            None => max_prefix_len,
+        };
+        if prefix_len > max_prefix_len {
+            return Err(invalid());
+        }
+
+        // IPv4-mapped addresses are stored as IPv4, so the prefix has to leave out the 96 bits of
+        // the mapping as well
+        let canonical = network.to_canonical();
+        if network.is_ipv6() && canonical.is_ipv4() {
+            prefix_len = prefix_len.checked_sub(96).ok_or_else(invalid)?;
+        }
+
+        Ok(Self {
+            network: canonical,
+            prefix_len,
+        })
+    }
+}
+
+impl TryFrom<String> for IpRange {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<IpRange> for String {
+    fn from(value: IpRange) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for IpRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let max_prefix_len = if self.network.is_ipv4() { 32 } else { 128 };
+        if self.prefix_len == max_prefix_len {
+            write!(f, "{}", self.network)
+        } else {
+            write!(f, "{}/{}", self.network, self.prefix_len)
+        }
+    }
+}
+
+impl PlayerBan {
+    /// Bans with a UUID only match that player, since names can change hands. The name is only
+    /// used for players banned before their UUID was known.
+    fn matches(&self, uuid: Uuid, username: &str) -> bool {
+        if self.uuid.is_nil() {
+            self.name.eq_ignore_ascii_case(username)
+        } else {
+            self.uuid == uuid
+        }
+    }
+}
+
+/// Why a player was turned away, and until when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BanReason {
+    pub reason: String,
+    pub expires: Option<u64>,
+}
+
+impl BanReason {
+    /// The message shown on the disconnect screen.
+    pub fn message(&self) -> String {
+        match self.expires {
+            Some(expires) => format!(
+                "You are banned from this server.\nReason: {}\nYour ban expires in {}.",
+                self.reason,
+                format_remaining(expires.saturating_sub(unix_now()))
+            ),
+            None => format!("You are banned from this server.\nReason: {}", self.reason),
+        }
+    }
+}
+
+fn format_remaining(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{} seconds", secs),
+        60..=3599 => format!("{} minutes", secs / 60),
+        3600..=86399 => format!("{} hours", secs / 3600),
+        _ => format!("{} days", secs / 86400),
+    }
+}
+
+fn is_active(expires: Option<u64>, now: u64) -> bool {
+    expires.is_none_or(|expires| expires > now)
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// The banned players and addresses, loaded from the files named in the config.
+#[derive(Debug, Default)]
+pub struct BanList {
+    /// Where the lists are saved whenever they change. `None` if they aren't kept in files.
+    files: Option<(PathBuf, PathBuf)>,
+    players: RwLock<Vec<PlayerBan>>,
+    ips: RwLock<Vec<IpBan>>,
+}
+
+impl BanList {
+    /// Loads both ban files. Missing files just mean nobody is banned.
+    pub fn load(config: &BanConfig) -> Result<Self> {
+        let mut bans = Self::new(
+            load_json_list(&config.players_file)?,
+            load_json_list(&config.ips_file)?,
+        );
+        bans.files = Some((
+            PathBuf::from(&config.players_file),
+            PathBuf::from(&config.ips_file),
+        ));
+        info!(
+            "Loaded {} player bans and {} IP bans",
+            bans.players().len(),
+            bans.ips().len()
+        );
+        Ok(bans)
+    }
+
+    pub fn new(players: Vec<PlayerBan>, ips: Vec<IpBan>) -> Self {
+        Self {
+            files: None,
+            players: RwLock::new(players),
+            ips: RwLock::new(ips),
+        }
+    }
+
+    pub fn players(&self) -> Vec<PlayerBan> {
+        self.players.read().unwrap().clone()
+    }
+
+    pub fn ips(&self) -> Vec<IpBan> {
+        self.ips.read().unwrap().clone()
+    }
+
+    /// Finds a ban that's still running for a player or their address. Expired bans are ignored,
+    /// but left in the list.
+    pub fn find(
+        &self,
+        uuid: Uuid,
+        username: &str,
+        ip: Option<IpAddr>,
+        now: u64,
+    ) -> Option<BanReason> {
+        let players = self.players.read().unwrap();
+        let player_ban = players
+            .iter()
+            .filter(|ban| ban.matches(uuid, username) && is_active(ban.expires, now))
+            .map(|ban| BanReason {
+                reason: ban.reason.clone(),
+                expires: ban.expires,
+            });
+        let ips = self.ips.read().unwrap();
+        let ip_ban = ips
+            .iter()
+            .filter(|ban| ip.is_some_and(|ip| ban.ip.contains(ip)) && is_active(ban.expires, now))
+            .map(|ban| BanReason {
+                reason: ban.reason.clone(),
+                expires: ban.expires,
+            });
+
+        player_ban.chain(ip_ban).next()
+    }
+
+    /// Bans a player and saves the list, replacing any ban they already had.
+    pub fn ban_player(&self, ban: PlayerBan) -> Result<()> {
+        let mut players = self.players.write().unwrap();
+        players.retain(|existing| !existing.matches(ban.uuid, &ban.name));
+        players.push(ban);
+        self.save(self.files.as_ref().map(|(players, _)| players), &players)
+    }
+
+    /// Lifts a player's ban by name and saves the list. Returns the ban that was lifted, if there
+    /// was one.
+    pub fn pardon_player(&self, name: &str) -> Result<Option<PlayerBan>> {
+        let mut players = self.players.write().unwrap();
+        let Some(index) = players
+            .iter()
+            .position(|ban| ban.name.eq_ignore_ascii_case(name))
+        else {
+            return Ok(None);
+        };
+        let pardoned = players.remove(index);
+        self.save(self.files.as_ref().map(|(players, _)| players), &players)?;
+        Ok(Some(pardoned))
+    }
+
+    /// Bans an address or range and saves the list, replacing any ban it already had.
+    pub fn ban_ip(&self, ban: IpBan) -> Result<()> {
+        let mut ips = self.ips.write().unwrap();
+        ips.retain(|existing| existing.ip != ban.ip);
+        ips.push(ban);
+        self.save(self.files.as_ref().map(|(_, ips)| ips), &ips)
+    }
+
+    /// Lifts the ban on an address or range and saves the list. Returns false if it wasn't banned.
+    pub fn pardon_ip(&self, ip: IpRange) -> Result<bool> {
+        let mut ips = self.ips.write().unwrap();
+        let count = ips.len();
+        ips.retain(|ban| ban.ip != ip);
+        if ips.len() == count {
+            return Ok(false);
+        }
+        self.save(self.files.as_ref().map(|(_, ips)| ips), &ips)?;
+        Ok(true)
+    }
+
+    fn save<T: Serialize>(&self, file: Option<&PathBuf>, list: &[T]) -> Result<()> {
+        match file {
+            Some(file) => save_json_list(file, list),
+            None => Ok(()),
+        }
+    }
+}
+
+pub(crate) fn load_json_list<T: DeserializeOwned>(file: &str) -> Result<Vec<T>> {
+    let path = Path::new(file);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = std::fs::read_to_string(path)?;
+    serde_json::from_str(&json)
+        .map_err(|e| Error::DeserializationError(format!("Invalid list {}: {}", file, e)))
+}
+
+pub(crate) fn save_json_list<T: Serialize>(file: &Path, list: &[T]) -> Result<()> {
+    let json = serde_json::to_string_pretty(list)
+        .map_err(|e| Error::Generic(format!("Failed to serialize {}: {}", file.display(), e)))?;
+    std::fs::write(file, json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn bans() -> BanList {
+        let players = serde_json::from_str(
+            r#"[
+                {"uuid": "069a79f4-44e9-4726-a5be-fca90e38aaf5", "name": "Notch", "reason": "Griefing"},
+                {"uuid": "853c80ef-3c37-49fd-aa49-938b674adae6", "name": "jeb_", "expires": 1600000000}
+            ]"#,
+        )
+        .unwrap();
+        let ips = serde_json::from_str(
+            r#"[
+                {"ip": "203.0.113.7", "reason": "Spam bot"},
+                {"ip": "10.0.0.0/8", "reason": "Whole network", "expires": 1800000000}
+            ]"#,
+        )
+        .unwrap();
+        BanList::new(players, ips)
+    }
+
+    #[test]
+    fn test_uuid_ban() {
+        let notch = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+        let ban = bans()
+            .find(notch, "Notch", None, NOW)
+            .map(|ban| ban.reason.to_string());
+        assert_eq!(ban.as_deref(), Some("Griefing"));
+
+        assert!(bans()
+            .find(Uuid::new_v4(), "Dinnerbone", None, NOW)
+            .is_none());
+        // Someone else who has taken the name since isn't banned
+        assert!(bans().find(Uuid::new_v4(), "Notch", None, NOW).is_none());
+    }
+
+    #[test]
+    fn test_ip_ban() {
+        let bans = bans();
+        let player = Uuid::new_v4();
+
+        let single = bans.find(
+            player,
+            "Dinnerbone",
+            Some("203.0.113.7".parse().unwrap()),
+            NOW,
+        );
+        assert_eq!(single.unwrap().reason, "Spam bot");
+
+        let range = bans.find(
+            player,
+            "Dinnerbone",
+            Some("10.42.0.1".parse().unwrap()),
+            NOW,
+        );
+        assert_eq!(range.unwrap().expires, Some(1800000000));
+
+        let mapped = bans.find(
+            player,
+            "Dinnerbone",
+            Some("::ffff:10.0.0.1".parse().unwrap()),
+            NOW,
+        );
+        assert!(mapped.is_some());
+
+        assert!(bans
+            .find(
+                player,
+                "Dinnerbone",
+                Some("203.0.113.8".parse().unwrap()),
+                NOW
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_expired_ban_is_ignored() {
+        let jeb = Uuid::parse_str("853c80ef-3c37-49fd-aa49-938b674adae6").unwrap();
+        assert!(bans().find(jeb, "jeb_", None, NOW).is_none());
+        assert!(bans().find(jeb, "jeb_", None, 1_500_000_000).is_some());
+
+        // The range ban runs out too
+        let ip = Some("10.42.0.1".parse().unwrap());
+        assert!(bans()
+            .find(Uuid::new_v4(), "Dinnerbone", ip, 1_900_000_000)
+            .is_none());
+    }
+
+    #[test]
+    fn test_bans_are_saved() {
+        let dir = std::env::temp_dir().join(format!("ferrumc-bans-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = BanConfig {
+            players_file: dir.join("players.json").to_string_lossy().to_string(),
+            ips_file: dir.join("ips.json").to_string_lossy().to_string(),
+        };
+        let bans = BanList::load(&config).unwrap();
+
+        // Banned by name while offline
+        bans.ban_player(PlayerBan {
+            uuid: Uuid::nil(),
+            name: "Grumm".to_string(),
+            reason: "Upside down".to_string(),
+            expires: None,
+        })
+        .unwrap();
+        bans.ban_ip(IpBan {
+            ip: "192.0.2.1".parse().unwrap(),
+            reason: default_reason(),
+            expires: None,
+        })
+        .unwrap();
+
+        let reloaded = BanList::load(&config).unwrap();
+        let ban = reloaded.find(Uuid::new_v4(), "grumm", None, NOW).unwrap();
+        assert_eq!(ban.reason, "Upside down");
+        // A nil UUID only matches on the name
+        assert!(reloaded.find(Uuid::nil(), "jeb_", None, NOW).is_none());
+        let ip = Some("192.0.2.1".parse().unwrap());
+        assert!(reloaded.find(Uuid::new_v4(), "jeb_", ip, NOW).is_some());
+
+        assert!(reloaded.pardon_player("GRUMM").unwrap().is_some());
+        assert!(reloaded.pardon_player("Grumm").unwrap().is_none());
+        assert!(reloaded.pardon_ip("192.0.2.1".parse().unwrap()).unwrap());
+        assert!(!reloaded.pardon_ip("192.0.2.1".parse().unwrap()).unwrap());
+
+        let reloaded = BanList::load(&config).unwrap();
+        assert!(reloaded.players().is_empty());
+        assert!(reloaded.ips().is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_ip_range_parsing() {
+        assert_eq!(
+            "10.0.0.0/8".parse::<IpRange>().unwrap().to_string(),
+            "10.0.0.0/8"
+        );
+        assert_eq!("::1".parse::<IpRange>().unwrap().to_string(), "::1");
+        assert!("10.0.0.0/33".parse::<IpRange>().is_err());
+        assert!("not an ip".parse::<IpRange>().is_err());
+    }
+
+    #[test]
+    fn test_ipv4_mapped_ranges() {
+        let range = "::ffff:10.1.2.3/104".parse::<IpRange>().unwrap();
+        assert_eq!(range.to_string(), "10.1.2.3/8");
+        assert!(range.contains("10.200.0.1".parse().unwrap()));
+        assert!(!range.contains("11.0.0.1".parse().unwrap()));
+        assert_eq!(
+            "::ffff:10.1.2.3".parse::<IpRange>().unwrap().to_string(),
+            "10.1.2.3"
+        );
+        // Shorter prefixes would cover more than the mapped addresses
+        assert!("::ffff:10.1.2.3/80".parse::<IpRange>().is_err());
+    }
+}
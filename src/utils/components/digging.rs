@@ -0,0 +1,24 @@
+use std::time::Instant;
+
+use ferrumc_macros::Component;
+
+use crate::utils::encoding::position::Position;
+
+/// Added to a player while they're digging a block that takes a while to break.
+#[derive(Debug, Clone, Component)]
+pub struct Digging {
+    pub position: Position,
+    /// The block state they started digging.
+    pub block_state: i32,
+    pub started: Instant,
+}
+
+impl Digging {
+    pub fn new(position: Position, block_state: i32) -> Self {
+        Self {
+            position,
+            block_state,
+            started: Instant::now(),
+        }
+    }
+}
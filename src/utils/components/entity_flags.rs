@@ -0,0 +1,58 @@
+use ferrumc_macros::Component;
+
+/// The pose an entity is drawn in, as sent in its metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pose {
+    #[default]
+    Standing = 0,
+    FallFlying = 1,
+    Sleeping = 2,
+    Swimming = 3,
+    SpinAttack = 4,
+    Sneaking = 5,
+}
+
+/// The shared entity flags byte (metadata index 0) of a player, along with its pose.
+#[derive(Debug, Clone, Copy, Default, Component)]
+pub struct EntityFlags {
+    pub flags: u8,
+}
+
+impl EntityFlags {
+    pub const ON_FIRE: u8 = 0x01;
+    pub const CROUCHING: u8 = 0x02;
+    pub const SPRINTING: u8 = 0x08;
+    pub const SWIMMING: u8 = 0x10;
+    pub const INVISIBLE: u8 = 0x20;
+    pub const GLOWING: u8 = 0x40;
+    pub const FALL_FLYING: u8 = 0x80;
+
+    /// Sets or clears a flag. Returns whether anything changed.
+    pub fn set(&mut self, flag: u8, enabled: bool) -> bool {
+        let old = self.flags;
+        if enabled {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+        old != self.flags
+    }
+
+    pub fn is_sneaking(&self) -> bool {
+        self.flags & Self::CROUCHING != 0
+    }
+
+    pub fn is_sprinting(&self) -> bool {
+        self.flags & Self::SPRINTING != 0
+    }
+
+    pub fn pose(&self) -> Pose {
+        if self.flags & Self::FALL_FLYING != 0 {
+            Pose::FallFlying
+        } else if self.is_sneaking() {
+            Pose::Sneaking
+        } else {
+            Pose::Standing
+        }
+    }
+}
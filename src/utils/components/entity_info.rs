@@ -0,0 +1,48 @@
+use ferrumc_macros::Component;
+
+/// The kinds of entity the server spawns, by their id in the protocol's entity type registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityKind {
+    Boat = 9,
+    ExperienceOrb = 34,
+    FallingBlock = 36,
+    Item = 54,
+    Minecart = 64,
+    Player = 122,
+}
+
+impl EntityKind {
+    /// The entity's id in the entity type registry.
+    pub fn type_id(&self) -> i32 {
+        *self as i32
+    }
+
+    /// Looks up an entity that can be summoned on its own, with or without the `minecraft:`
+    /// namespace. The others need more to go on than a name, like which item or block they are.
+    pub fn summonable(name: &str) -> Option<Self> {
+        match name.strip_prefix("minecraft:").unwrap_or(name) {
+            "boat" => Some(EntityKind::Boat),
+            "minecart" => Some(EntityKind::Minecart),
+            _ => None,
+        }
+    }
+
+    /// Whether players can get in it by right clicking it.
+    pub fn is_rideable(&self) -> bool {
+        matches!(self, EntityKind::Boat | EntityKind::Minecart)
+    }
+}
+
+/// What every entity in the world has, whether it's a player or not. The entity's id in the ECS
+/// is the id it's known by on the clients too.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct EntityInfo {
+    pub kind: EntityKind,
+    pub uuid: u128,
+}
+
+impl EntityInfo {
+    pub fn new(kind: EntityKind, uuid: u128) -> Self {
+        Self { kind, uuid }
+    }
+}
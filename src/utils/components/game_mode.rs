@@ -0,0 +1,42 @@
+use ferrumc_macros::Component;
+
+/// A player's game mode. The discriminants are the ids the protocol uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Component)]
+pub enum GameMode {
+    Survival = 0,
+    /// Everyone joins in creative for now.
+    #[default]
+    Creative = 1,
+    Adventure = 2,
+    Spectator = 3,
+}
+
+impl GameMode {
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(GameMode::Survival),
+            1 => Some(GameMode::Creative),
+            2 => Some(GameMode::Adventure),
+            3 => Some(GameMode::Spectator),
+            _ => None,
+        }
+    }
+
+    /// The name commands use for it, like `survival`.
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+            GameMode::Adventure => "adventure",
+            GameMode::Spectator => "spectator",
+        }
+    }
+
+    pub fn is_spectator(self) -> bool {
+        self == GameMode::Spectator
+    }
+}
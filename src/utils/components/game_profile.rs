@@ -0,0 +1,61 @@
+use md5::{Digest, Md5};
+use serde::Deserialize;
+use uuid::{Builder, Uuid};
+
+use ferrumc_macros::Component;
+
+/// A player's profile, as verified by Mojang's session servers when the server is in online mode.
+/// Every player has one, see [GameProfile::offline] for offline mode.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Component)]
+pub struct GameProfile {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub properties: Vec<ProfileProperty>,
+}
+
+impl GameProfile {
+    /// The profile of a player that logged in while the server is in offline mode, which has
+    /// nothing to it but the name and UUID they logged in with.
+    pub fn offline(id: Uuid, name: impl Into<String>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            properties: Vec::new(),
+        }
+    }
+
+    /// The UUID vanilla gives a player in offline mode: an MD5 based version 3 UUID of
+    /// `OfflinePlayer:<name>`. It only depends on the name, so it's the same every time they join.
+    pub fn offline_uuid(name: &str) -> Uuid {
+        let hash = Md5::digest(format!("OfflinePlayer:{}", name).as_bytes());
+        Builder::from_md5_bytes(hash.into()).into_uuid()
+    }
+}
+
+/// Extra data attached to a profile, like the `textures` property holding the player's skin.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ProfileProperty {
+    pub name: String,
+    pub value: String,
+    /// Mojang's signature over the value, so clients can tell it wasn't tampered with.
+    pub signature: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_uuid_matches_vanilla() {
+        assert_eq!(
+            GameProfile::offline_uuid("Notch"),
+            Uuid::parse_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap()
+        );
+        assert_eq!(GameProfile::offline_uuid("Notch").get_version_num(), 3);
+        assert_ne!(
+            GameProfile::offline_uuid("Notch"),
+            GameProfile::offline_uuid("notch")
+        );
+    }
+}
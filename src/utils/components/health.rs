@@ -0,0 +1,53 @@
+use ferrumc_macros::Component;
+
+/// A player's health and hunger, along with whether they're currently dead.
+///
+/// A dead player stays dead until their client asks to respawn, any damage or healing in the
+/// meantime is ignored.
+#[derive(Debug, Clone, Component)]
+pub struct Health {
+    pub health: f32,
+    pub food: i32,
+    pub saturation: f32,
+    dead: bool,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            health: Self::MAX_HEALTH,
+            food: Self::MAX_FOOD,
+            saturation: Self::SPAWN_SATURATION,
+            dead: false,
+        }
+    }
+}
+
+impl Health {
+    pub const MAX_HEALTH: f32 = 20.0;
+    pub const MAX_FOOD: i32 = 20;
+    pub const SPAWN_SATURATION: f32 = 5.0;
+
+    pub fn is_dead(&self) -> bool {
+        self.dead
+    }
+
+    /// Sets the player's health, clamped to `0..=MAX_HEALTH`. Returns true if this killed them.
+    pub fn set_health(&mut self, health: f32) -> bool {
+        if self.dead {
+            return false;
+        }
+        self.health = health.clamp(0.0, Self::MAX_HEALTH);
+        self.dead = self.health <= 0.0;
+        self.dead
+    }
+
+    /// Brings a dead player back with full health and hunger. Returns false if they weren't dead.
+    pub fn respawn(&mut self) -> bool {
+        if !self.dead {
+            return false;
+        }
+        *self = Self::default();
+        true
+    }
+}
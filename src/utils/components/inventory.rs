@@ -0,0 +1,804 @@
+use std::ops::Range;
+
+use ferrumc_macros::Component;
+
+use crate::net::packets::incoming::click_container::ClickContainer;
+use crate::net::packets::outgoing::set_container_content::SetContainerContent;
+use crate::utils::components::open_container::WindowType;
+use crate::utils::encoding::slot::{ItemStack, Slot};
+use crate::world::item_registry::{item_registry, ItemRegistry};
+
+/// Slots in the player's own inventory window: the crafting grid, armor, main inventory,
+/// hotbar and off hand.
+pub const INVENTORY_SLOTS: usize = 46;
+/// Window slot of the crafting grid's output.
+pub const CRAFTING_RESULT_SLOT: usize = 0;
+/// Window slot of the helmet, followed by the rest of the armor from the head down.
+pub const ARMOR_START: usize = 5;
+/// Window slot of the first main inventory slot, above the hotbar.
+pub const MAIN_START: usize = 9;
+/// Window slot of the first hotbar slot.
+pub const HOTBAR_START: usize = 36;
+/// Window slot of the off hand.
+pub const OFF_HAND_SLOT: usize = 45;
+/// The slots of the inventory that every container window shows below its own: the main
+/// inventory, then the hotbar.
+pub const PLAYER_SLOTS: Range<usize> = MAIN_START..OFF_HAND_SLOT;
+
+/// The slot number of clicks outside the window.
+const OUTSIDE_SLOT: i16 = -999;
+
+/// Click modes, see [ClickContainer::mode].
+const PICKUP: i32 = 0;
+const QUICK_MOVE: i32 = 1;
+const SWAP: i32 = 2;
+const CLONE: i32 = 3;
+const THROW: i32 = 4;
+const QUICK_CRAFT: i32 = 5;
+const PICKUP_ALL: i32 = 6;
+
+/// A drag across slots that's still going. The carried item gets spread over the slots once it
+/// ends.
+#[derive(Debug, Clone, Default)]
+pub struct Drag {
+    /// 0 splits the item evenly, 1 puts one in each slot.
+    pub kind: i8,
+    pub slots: Vec<usize>,
+}
+
+/// The items a player carries, laid out like the slots of their inventory window, which is
+/// always window 0.
+#[derive(Debug, Clone, Component)]
+pub struct Inventory {
+    pub slots: Vec<Slot>,
+    /// Which of the 9 hotbar slots is selected.
+    pub selected_slot: u8,
+    /// Bumped whenever the server sends the window again, see [OpenContainer::state_id].
+    ///
+    /// [OpenContainer::state_id]: crate::utils::components::open_container::OpenContainer::state_id
+    pub state_id: i32,
+    /// The item the player is dragging around with their cursor.
+    pub carried_item: Slot,
+    pub drag: Option<Drag>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            slots: vec![Slot::empty(); INVENTORY_SLOTS],
+            selected_slot: 0,
+            state_id: 0,
+            carried_item: Slot::empty(),
+            drag: None,
+        }
+    }
+}
+
+impl Inventory {
+    /// The item in the selected hotbar slot.
+    pub fn held_item(&self) -> &Slot {
+        &self.slots[HOTBAR_START + self.selected_slot as usize]
+    }
+
+    /// Selects a hotbar slot. Returns false if there's no such slot.
+    pub fn select(&mut self, hotbar_slot: i16) -> bool {
+        match u8::try_from(hotbar_slot) {
+            Ok(slot) if slot < 9 => {
+                self.selected_slot = slot;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Puts an item into a window slot. Returns false if there's no such slot.
+    pub fn set_slot(&mut self, slot: i16, item: Slot) -> bool {
+        match usize::try_from(slot)
+            .ok()
+            .and_then(|index| self.slots.get_mut(index))
+        {
+            Some(existing) => {
+                *existing = item;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves to a new state id, wrapping around like vanilla does.
+    pub fn next_state_id(&mut self) -> i32 {
+        self.state_id = (self.state_id + 1) & 0x7FFF;
+        self.state_id
+    }
+
+    /// Puts an item wherever it fits, the way picked up items go in: onto stacks of the same
+    /// item first, then into the first empty slot, the hotbar before the main inventory.
+    /// Returns whatever didn't fit.
+    pub fn give(&mut self, item: Slot, items: &ItemRegistry) -> Slot {
+        let Some(mut item) = item.item else {
+            return Slot::empty();
+        };
+        let order = (HOTBAR_START..OFF_HAND_SLOT).chain(MAIN_START..HOTBAR_START);
+        let max = items.max_stack_size(item.item_id);
+        for slot in order.clone() {
+            if let Some(existing) = self.slots[slot]
+                .item
+                .as_mut()
+                .filter(|existing| same_item(existing, &item))
+            {
+                let moved = (max - existing.count).max(0).min(item.count);
+                existing.count += moved;
+                item.count -= moved;
+            }
+        }
+        for slot in order {
+            if item.count > 0 && self.slots[slot].is_empty() {
+                let moved = item.count.min(max);
+                self.slots[slot] = with_count(&item, moved);
+                item.count -= moved;
+            }
+        }
+        with_count(&item, item.count)
+    }
+
+    /// Applies a click in the inventory window.
+    ///
+    /// The server works out what the click does itself. Only in creative mode, where the player
+    /// can take any item anyway, are the slots the client sends taken as they are. If the client
+    /// predicted something else, or the click was made against an outdated state like with
+    /// [OpenContainer::click], the whole inventory has to be sent again under a new state id.
+    ///
+    /// [OpenContainer::click]: crate::utils::components::open_container::OpenContainer::click
+    pub fn click(
+        &mut self,
+        click: &ClickContainer,
+        creative: bool,
+    ) -> Result<(), SetContainerContent> {
+        if click.state_id.get_val() != self.state_id {
+            return Err(self.resync());
+        }
+
+        let before = self.slots.clone();
+        if !self.window().click(click, &before, creative) {
+            return Err(self.resync());
+        }
+        Ok(())
+    }
+
+    fn resync(&mut self) -> SetContainerContent {
+        self.next_state_id();
+        SetContainerContent::inventory(self)
+    }
+
+    /// The inventory window, for working out clicks in it.
+    fn window(&mut self) -> Window<'_> {
+        Window {
+            layout: WindowLayout::Inventory,
+            slots: &mut self.slots,
+            carried_item: &mut self.carried_item,
+            drag: &mut self.drag,
+        }
+    }
+}
+
+/// Which window a click is made in, which decides where items may go and where shift clicks
+/// send them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowLayout {
+    /// The player's own inventory, laid out like [Inventory::slots].
+    Inventory,
+    /// A container's own slots, followed by [PLAYER_SLOTS]. The off hand comes last, where the
+    /// client can't click it but the F key still swaps with it.
+    Container(WindowType),
+}
+
+impl WindowLayout {
+    /// How many slots the client sees in the window.
+    pub fn slot_count(self) -> usize {
+        match self {
+            WindowLayout::Inventory => INVENTORY_SLOTS,
+            WindowLayout::Container(window_type) => window_type.slot_count() + PLAYER_SLOTS.len(),
+        }
+    }
+
+    fn hotbar_start(self) -> usize {
+        match self {
+            WindowLayout::Inventory => HOTBAR_START,
+            WindowLayout::Container(window_type) => {
+                window_type.slot_count() + HOTBAR_START - MAIN_START
+            }
+        }
+    }
+
+    fn off_hand(self) -> usize {
+        match self {
+            WindowLayout::Inventory => OFF_HAND_SLOT,
+            WindowLayout::Container(_) => self.slot_count(),
+        }
+    }
+
+    /// Whether a slot holds the result of a recipe, a repair or a trade. The server doesn't work
+    /// those out, so they can't be taken.
+    fn is_output(self, slot: usize) -> bool {
+        match self {
+            WindowLayout::Inventory => slot == CRAFTING_RESULT_SLOT,
+            WindowLayout::Container(WindowType::Anvil | WindowType::Merchant) => slot == 2,
+            WindowLayout::Container(WindowType::Generic9x3) => false,
+        }
+    }
+
+    /// How many of an item fit in a window slot, or 0 if it can't go there at all, like a sword
+    /// in the helmet slot.
+    fn capacity(self, slot: usize, item_id: i32, items: &ItemRegistry) -> i8 {
+        if self.is_output(slot) {
+            0
+        } else if self == WindowLayout::Inventory && (ARMOR_START..MAIN_START).contains(&slot) {
+            (items.armor_slot(item_id) == Some(slot - ARMOR_START)) as i8
+        } else {
+            items.max_stack_size(item_id)
+        }
+    }
+}
+
+/// The slots of an open window along with what the player is doing with their cursor, which is
+/// everything a click needs to be worked out.
+pub struct Window<'a> {
+    pub layout: WindowLayout,
+    pub slots: &'a mut [Slot],
+    pub carried_item: &'a mut Slot,
+    pub drag: &'a mut Option<Drag>,
+}
+
+impl Window<'_> {
+    /// Applies a click, given the slots as they were before it. Returns false if the client
+    /// didn't come to the same result as the server, in which case it needs the whole window
+    /// again.
+    ///
+    /// Creative players can take any item anyway, so the slots they send are taken as they are.
+    pub fn click(&mut self, click: &ClickContainer, before: &[Slot], creative: bool) -> bool {
+        if creative {
+            for changed in &click.changed_slots {
+                if let Some(slot) = usize::try_from(changed.slot)
+                    .ok()
+                    .filter(|slot| *slot < self.layout.slot_count())
+                {
+                    self.slots[slot] = changed.item.clone();
+                }
+            }
+            *self.carried_item = click.carried_item.clone();
+            return true;
+        }
+
+        self.simulate(click, item_registry()) && self.predicted(click, before)
+    }
+
+    /// Whether the client came to the same result as the server.
+    fn predicted(&self, click: &ClickContainer, before: &[Slot]) -> bool {
+        let len = self.layout.slot_count();
+        let mut reported = vec![false; len];
+        for changed in &click.changed_slots {
+            let Some(index) = usize::try_from(changed.slot)
+                .ok()
+                .filter(|index| *index < len)
+            else {
+                return false;
+            };
+            if self.slots[index] != changed.item {
+                return false;
+            }
+            reported[index] = true;
+        }
+        let unreported = self.slots[..len]
+            .iter()
+            .zip(before)
+            .zip(&reported)
+            .any(|((now, before), reported)| !reported && now != before);
+        !unreported && *self.carried_item == click.carried_item
+    }
+
+    /// Works out what a click does, the way vanilla does. Returns false for clicks the server
+    /// can't work out, like taking from the crafting grid's output, which get refused.
+    ///
+    /// There are no item entities, so thrown items are gone.
+    fn simulate(&mut self, click: &ClickContainer, items: &ItemRegistry) -> bool {
+        let mode = click.mode.get_val();
+        if mode != QUICK_CRAFT {
+            *self.drag = None;
+        }
+        let slot = if click.slot == OUTSIDE_SLOT {
+            None
+        } else {
+            match usize::try_from(click.slot)
+                .ok()
+                .filter(|slot| *slot < self.layout.slot_count() && !self.layout.is_output(*slot))
+            {
+                Some(slot) => Some(slot),
+                None => return false,
+            }
+        };
+
+        match (mode, slot) {
+            (PICKUP, _) if !matches!(click.button, 0 | 1) => return false,
+            (PICKUP, Some(slot)) => self.pickup(slot, click.button == 1, items),
+            (PICKUP, None) => {
+                let dropped = if click.button == 0 { i8::MAX } else { 1 };
+                *self.carried_item = take(self.carried_item, dropped).1;
+            }
+            (QUICK_MOVE, Some(slot)) => self.quick_move(slot, items),
+            (QUICK_MOVE, None) => {}
+            (SWAP, Some(slot)) => {
+                let target = match click.button {
+                    0..=8 => self.layout.hotbar_start() + click.button as usize,
+                    40 => self.layout.off_hand(),
+                    _ => return false,
+                };
+                return self.swap(slot, target, items);
+            }
+            (CLONE, _) => {}
+            (THROW, Some(slot)) if self.carried_item.is_empty() => {
+                let thrown = if click.button == 0 { 1 } else { i8::MAX };
+                self.slots[slot] = take(&self.slots[slot], thrown).1;
+            }
+            (THROW, _) => {}
+            (QUICK_CRAFT, _) => self.quick_craft(slot, click.button, items),
+            (PICKUP_ALL, Some(slot)) => self.pickup_all(slot, click.button, items),
+            (PICKUP_ALL, None) => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// A left click picks up or puts down the whole stack, a right click half of it or a single
+    /// item. Different items get swapped.
+    fn pickup(&mut self, slot: usize, right_click: bool, items: &ItemRegistry) {
+        let capacity = |item_id| self.layout.capacity(slot, item_id, items);
+        match (
+            self.carried_item.item.clone(),
+            self.slots[slot].item.clone(),
+        ) {
+            (None, None) => {}
+            (None, Some(in_slot)) => {
+                let taken = if right_click {
+                    (in_slot.count + 1) / 2
+                } else {
+                    in_slot.count
+                };
+                (*self.carried_item, self.slots[slot]) = take(&self.slots[slot], taken);
+            }
+            (Some(carried), Some(in_slot)) if !same_item(&carried, &in_slot) => {
+                if carried.count <= capacity(carried.item_id) {
+                    std::mem::swap(self.carried_item, &mut self.slots[slot]);
+                }
+            }
+            (Some(carried), in_slot) => {
+                let in_slot = in_slot.map_or(0, |item| item.count);
+                let room = (capacity(carried.item_id) - in_slot).max(0);
+                let placed = if right_click { 1 } else { carried.count }.min(room);
+                self.slots[slot] = with_count(&carried, in_slot + placed);
+                *self.carried_item = with_count(&carried, carried.count - placed);
+            }
+        }
+    }
+
+    /// Shift clicking moves a stack to the other part of the window, see
+    /// [Window::quick_move_targets].
+    fn quick_move(&mut self, slot: usize, items: &ItemRegistry) {
+        // Whatever doesn't fit in an armor slot goes on to the next place
+        while let Some(item) = self.slots[slot].item.clone() {
+            let targets = self.quick_move_targets(slot, &item, items);
+            if !self.move_stack(slot, &targets, items) {
+                break;
+            }
+        }
+    }
+
+    /// Where a shift click sends the item in a slot, in the order the slots are filled.
+    ///
+    /// In the inventory, stacks move between the hotbar and the rest of the inventory, or are
+    /// put on if they can be worn. In a container they move between the container and the
+    /// player, except that the player's items only go into an anvil's inputs, and between the
+    /// hotbar and the main inventory when trading.
+    fn quick_move_targets(
+        &self,
+        slot: usize,
+        item: &ItemStack,
+        items: &ItemRegistry,
+    ) -> Vec<usize> {
+        let window_type = match self.layout {
+            WindowLayout::Container(window_type) => window_type,
+            WindowLayout::Inventory => {
+                let armor = items
+                    .armor_slot(item.item_id)
+                    .map(|armor| ARMOR_START + armor);
+                let shield = items.name(item.item_id) == Some("minecraft:shield");
+                let targets = if slot < MAIN_START {
+                    MAIN_START..OFF_HAND_SLOT
+                } else if let Some(armor) = armor.filter(|armor| self.slots[*armor].is_empty()) {
+                    armor..armor + 1
+                } else if shield && self.slots[OFF_HAND_SLOT].is_empty() {
+                    OFF_HAND_SLOT..INVENTORY_SLOTS
+                } else if slot < HOTBAR_START {
+                    HOTBAR_START..OFF_HAND_SLOT
+                } else if slot < OFF_HAND_SLOT {
+                    MAIN_START..HOTBAR_START
+                } else {
+                    MAIN_START..OFF_HAND_SLOT
+                };
+                return targets.collect();
+            }
+        };
+
+        let container = window_type.slot_count();
+        let hotbar_start = self.layout.hotbar_start();
+        let player = container..self.layout.slot_count();
+        match window_type {
+            // Chests fill the player's inventory from the end of the hotbar
+            WindowType::Generic9x3 if slot < container => player.rev().collect(),
+            WindowType::Generic9x3 => (0..container).collect(),
+            _ if slot < container => player.collect(),
+            WindowType::Anvil => (0..2).collect(),
+            WindowType::Merchant if slot < hotbar_start => (hotbar_start..player.end).collect(),
+            WindowType::Merchant => (container..hotbar_start).collect(),
+        }
+    }
+
+    /// Moves as much of a slot's stack as fits into the target slots, topping up stacks of the
+    /// same item before using the first empty slot. Returns whether anything moved.
+    fn move_stack(&mut self, from: usize, targets: &[usize], items: &ItemRegistry) -> bool {
+        let Some(mut item) = self.slots[from].item.clone() else {
+            return false;
+        };
+        let count = item.count;
+        if items.max_stack_size(item.item_id) > 1 {
+            for target in targets.iter().copied() {
+                let capacity = self.layout.capacity(target, item.item_id, items);
+                if let Some(existing) = self.slots[target]
+                    .item
+                    .as_mut()
+                    .filter(|existing| same_item(existing, &item))
+                {
+                    let moved = (capacity - existing.count).max(0).min(item.count);
+                    existing.count += moved;
+                    item.count -= moved;
+                }
+            }
+        }
+        if item.count > 0 {
+            let empty = targets.iter().copied().find(|target| {
+                self.slots[*target].is_empty()
+                    && self.layout.capacity(*target, item.item_id, items) > 0
+            });
+            if let Some(target) = empty {
+                let moved = item
+                    .count
+                    .min(self.layout.capacity(target, item.item_id, items));
+                self.slots[target] = with_count(&item, moved);
+                item.count -= moved;
+            }
+        }
+        self.slots[from] = with_count(&item, item.count);
+        item.count != count
+    }
+
+    /// Swaps a slot with a hotbar slot or the off hand, which is what the number keys and F
+    /// do. Returns false if the server can't work out where the items end up.
+    fn swap(&mut self, slot: usize, target: usize, items: &ItemRegistry) -> bool {
+        if slot == target {
+            return true;
+        }
+        let Some(item) = self.slots[target].item.clone() else {
+            self.slots.swap(slot, target);
+            return true;
+        };
+        let capacity = self.layout.capacity(slot, item.item_id, items);
+        if capacity == 0 {
+            return true;
+        }
+        if item.count <= capacity {
+            self.slots.swap(slot, target);
+        } else if self.slots[slot].is_empty() {
+            (self.slots[slot], self.slots[target]) = take(&self.slots[target], capacity);
+        } else {
+            // Vanilla finds the item that was in the slot a new place in the inventory
+            return false;
+        }
+        true
+    }
+
+    /// Dragging the carried item across slots takes a packet to start, one for each slot and
+    /// one to end it, where the item is spread over the slots.
+    fn quick_craft(&mut self, slot: Option<usize>, button: i8, items: &ItemRegistry) {
+        let kind = (button >> 2) & 3;
+        let Some(carried) = self.carried_item.item.clone() else {
+            *self.drag = None;
+            return;
+        };
+        match (button & 3, self.drag.as_mut()) {
+            // A new drag can't start while one is going, that just cancels it
+            (0, None) if kind < 2 => {
+                *self.drag = Some(Drag {
+                    kind,
+                    slots: Vec::new(),
+                })
+            }
+            (1, Some(drag)) => {
+                let Some(slot) = slot else {
+                    return;
+                };
+                let fits = self.slots[slot].item.as_ref().is_none_or(|item| {
+                    same_item(item, &carried) && item.count <= items.max_stack_size(carried.item_id)
+                });
+                if fits
+                    && self.layout.capacity(slot, carried.item_id, items) > 0
+                    && carried.count as usize > drag.slots.len()
+                    && !drag.slots.contains(&slot)
+                {
+                    drag.slots.push(slot);
+                }
+            }
+            (2, Some(_)) => {
+                let drag = self.drag.take().unwrap_or_default();
+                if let [slot] = drag.slots[..] {
+                    return self.pickup(slot, drag.kind == 1, items);
+                }
+                let mut remaining = carried.count;
+                for slot in drag.slots.iter().copied() {
+                    let in_slot = self.slots[slot].item.as_ref().map_or(0, |item| item.count);
+                    let per_slot = if drag.kind == 0 {
+                        carried.count / drag.slots.len() as i8
+                    } else {
+                        1
+                    };
+                    let count = (in_slot + per_slot).min(self.layout.capacity(
+                        slot,
+                        carried.item_id,
+                        items,
+                    ));
+                    remaining -= count - in_slot;
+                    self.slots[slot] = with_count(&carried, count);
+                }
+                *self.carried_item = with_count(&carried, remaining);
+            }
+            _ => *self.drag = None,
+        }
+    }
+
+    /// Double clicking an empty slot gathers up items like the carried one from the whole
+    /// window, going through partial stacks before full ones.
+    fn pickup_all(&mut self, slot: usize, button: i8, items: &ItemRegistry) {
+        let Some(mut carried) = self.carried_item.item.clone() else {
+            return;
+        };
+        if !self.slots[slot].is_empty() {
+            return;
+        }
+        let max = items.max_stack_size(carried.item_id);
+        let mut order = (0..self.layout.slot_count())
+            .filter(|slot| !self.layout.is_output(*slot))
+            .collect::<Vec<_>>();
+        if button != 0 {
+            order.reverse();
+        }
+        for full_stacks in [false, true] {
+            for slot in order.iter().copied() {
+                if carried.count >= max {
+                    break;
+                }
+                let Some(item) = self.slots[slot]
+                    .item
+                    .as_ref()
+                    .filter(|item| same_item(item, &carried) && item.count <= max)
+                else {
+                    continue;
+                };
+                if full_stacks || item.count != max {
+                    let taken;
+                    (taken, self.slots[slot]) = take(&self.slots[slot], max - carried.count);
+                    carried.count += taken.item.map_or(0, |item| item.count);
+                }
+            }
+        }
+        *self.carried_item = Slot {
+            item: Some(carried),
+        };
+    }
+}
+
+/// Whether two items stack, which they do if they're the same and have the same NBT.
+fn same_item(a: &ItemStack, b: &ItemStack) -> bool {
+    a.item_id == b.item_id && a.nbt == b.nbt
+}
+
+/// The item with a different count, or an empty slot if there's none left.
+fn with_count(item: &ItemStack, count: i8) -> Slot {
+    Slot {
+        item: (count > 0).then(|| ItemStack {
+            count,
+            ..item.clone()
+        }),
+    }
+}
+
+/// Splits up to `count` items off a slot. Returns what was taken and what's left.
+fn take(slot: &Slot, count: i8) -> (Slot, Slot) {
+    match &slot.item {
+        Some(item) => {
+            let taken = count.min(item.count);
+            (
+                with_count(item, taken),
+                with_count(item, item.count - taken),
+            )
+        }
+        None => (Slot::empty(), Slot::empty()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::network_types::varint::VarInt;
+
+    use super::*;
+    use crate::net::packets::incoming::click_container::ChangedSlot;
+
+    fn click(
+        slot: i16,
+        button: i8,
+        mode: i32,
+        changed: &[(i16, Slot)],
+        carried: Slot,
+    ) -> ClickContainer {
+        ClickContainer {
+            window_id: 0,
+            state_id: VarInt::from(0),
+            slot,
+            button,
+            mode: VarInt::from(mode),
+            changed_slots: changed
+                .iter()
+                .map(|(slot, item)| ChangedSlot {
+                    slot: *slot,
+                    item: item.clone(),
+                })
+                .collect(),
+            carried_item: carried,
+        }
+    }
+
+    #[test]
+    fn test_held_item_follows_selected_slot() {
+        let mut inventory = Inventory::default();
+        assert!(inventory.set_slot(HOTBAR_START as i16 + 2, Slot::new(1, 64)));
+        assert!(inventory.held_item().is_empty());
+
+        assert!(inventory.select(2));
+        assert_eq!(inventory.held_item(), &Slot::new(1, 64));
+
+        // Out of range slots are ignored
+        assert!(!inventory.select(9));
+        assert!(!inventory.select(-1));
+        assert!(!inventory.set_slot(INVENTORY_SLOTS as i16, Slot::new(1, 1)));
+        assert!(!inventory.set_slot(-1, Slot::new(1, 1)));
+        assert_eq!(inventory.selected_slot, 2);
+    }
+
+    #[test]
+    fn test_clicks_are_worked_out_by_the_server() {
+        let mut inventory = Inventory::default();
+        inventory.slots[36] = Slot::new(1, 64);
+
+        // Picking up the stack and putting one down elsewhere
+        let pick_up = click(36, 0, PICKUP, &[(36, Slot::empty())], Slot::new(1, 64));
+        assert!(inventory.click(&pick_up, false).is_ok());
+        let put_one = click(9, 1, PICKUP, &[(9, Slot::new(1, 1))], Slot::new(1, 63));
+        assert!(inventory.click(&put_one, false).is_ok());
+        assert_eq!(inventory.slots[9], Slot::new(1, 1));
+        assert_eq!(inventory.carried_item, Slot::new(1, 63));
+
+        // A client that makes up items gets the server's view back
+        let made_up = click(10, 0, PICKUP, &[(10, Slot::new(2, 64))], Slot::new(1, 63));
+        let resync = inventory.click(&made_up, false).unwrap_err();
+        assert_eq!(resync.state_id.get_val(), 1);
+        assert_eq!(resync.slots[10], Slot::new(1, 63));
+        assert_eq!(resync.carried_item, Slot::empty());
+        assert_eq!(inventory.slots[10], Slot::new(1, 63));
+
+        // Creative players can take whatever they like
+        let mut made_up = click(11, 0, PICKUP, &[(11, Slot::new(2, 64))], Slot::empty());
+        made_up.state_id = VarInt::from(1);
+        assert!(inventory.click(&made_up, true).is_ok());
+        assert_eq!(inventory.slots[11], Slot::new(2, 64));
+    }
+
+    #[test]
+    fn test_shift_click_drag_and_double_click() {
+        let items = ItemRegistry::bundled();
+        let mut inventory = Inventory::default();
+        inventory.slots[36] = Slot::new(1, 10);
+        inventory.slots[37] = Slot::new(1, 60);
+
+        // Shift clicking moves stacks out of the hotbar, into the first empty slot
+        assert!(inventory
+            .window()
+            .simulate(&click(36, 0, QUICK_MOVE, &[], Slot::empty()), &items));
+        assert_eq!(inventory.slots[9], Slot::new(1, 10));
+        assert!(inventory.slots[36].is_empty());
+        // and back, topping up the stack already there first
+        assert!(inventory
+            .window()
+            .simulate(&click(9, 0, QUICK_MOVE, &[], Slot::empty()), &items));
+        assert_eq!(inventory.slots[37], Slot::new(1, 64));
+        assert_eq!(inventory.slots[36], Slot::new(1, 6));
+
+        // Dragging 6 items evenly across 4 slots leaves 2 on the cursor
+        inventory.slots[36] = Slot::empty();
+        inventory.carried_item = Slot::new(1, 6);
+        assert!(inventory.window().simulate(
+            &click(OUTSIDE_SLOT, 0, QUICK_CRAFT, &[], Slot::empty()),
+            &items
+        ));
+        for slot in 10..14 {
+            assert!(inventory
+                .window()
+                .simulate(&click(slot, 1, QUICK_CRAFT, &[], Slot::empty()), &items));
+        }
+        assert!(inventory.window().simulate(
+            &click(OUTSIDE_SLOT, 2, QUICK_CRAFT, &[], Slot::empty()),
+            &items
+        ));
+        for slot in 10..14 {
+            assert_eq!(inventory.slots[slot], Slot::new(1, 1));
+        }
+        assert_eq!(inventory.carried_item, Slot::new(1, 2));
+        assert!(inventory.drag.is_none());
+
+        // Double clicking gathers the partial stacks before taking from the full one
+        assert!(inventory
+            .window()
+            .simulate(&click(20, 0, PICKUP_ALL, &[], Slot::empty()), &items));
+        assert_eq!(inventory.carried_item, Slot::new(1, 64));
+        assert!(inventory.slots[10].is_empty());
+        assert_eq!(inventory.slots[37], Slot::new(1, 6));
+
+        // The crafting output isn't worked out, so taking from it is refused
+        assert!(!inventory
+            .window()
+            .simulate(&click(0, 0, PICKUP, &[], Slot::empty()), &items));
+    }
+
+    #[test]
+    fn test_unstackable_items_and_armor() {
+        let report = r#"{
+            "minecraft:item": {
+                "entries": {
+                    "minecraft:diamond_sword": {"protocol_id": 1},
+                    "minecraft:iron_helmet": {"protocol_id": 2}
+                }
+            }
+        }"#;
+        let items = ItemRegistry::from_registries_report(report).unwrap();
+        let mut inventory = Inventory::default();
+        inventory.slots[9] = Slot::new(1, 1);
+        inventory.slots[10] = Slot::new(2, 1);
+        inventory.carried_item = Slot::new(1, 1);
+
+        // Swords don't stack, so putting one on another swaps them
+        assert!(inventory
+            .window()
+            .simulate(&click(9, 0, PICKUP, &[], Slot::empty()), &items));
+        assert_eq!(inventory.slots[9], Slot::new(1, 1));
+        assert_eq!(inventory.carried_item, Slot::new(1, 1));
+
+        // and it can't go in the helmet slot
+        assert!(inventory
+            .window()
+            .simulate(&click(5, 0, PICKUP, &[], Slot::empty()), &items));
+        assert!(inventory.slots[5].is_empty());
+
+        // but the helmet goes on with a shift click
+        assert!(inventory
+            .window()
+            .simulate(&click(10, 0, QUICK_MOVE, &[], Slot::empty()), &items));
+        assert_eq!(inventory.slots[5], Slot::new(2, 1));
+        assert!(inventory.slots[10].is_empty());
+    }
+}
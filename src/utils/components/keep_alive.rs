@@ -1,8 +1,96 @@
-use ferrumc_macros::{Component, Constructor};
+use std::time::{Duration, Instant};
 
-#[derive(Component, Constructor, Debug, Clone)]
+use ferrumc_macros::Component;
+
+/// Tracks the keep alives sent to a player, and how long they take to answer them.
+#[derive(Component, Debug, Clone)]
 pub struct KeepAlive {
-    pub last_received: std::time::Instant,
-    pub last_sent: std::time::Instant,
+    pub last_received: Instant,
+    pub last_sent: Instant,
+    /// The ID of the last keep alive sent.
     pub data: i64,
+    /// Whether the last keep alive is still waiting for an answer.
+    pub pending: bool,
+    latency: Duration,
+}
+
+impl KeepAlive {
+    /// Starts tracking a player that was just sent the keep alive with the ID `data`.
+    pub fn new(now: Instant, data: i64) -> Self {
+        Self {
+            last_received: now,
+            last_sent: now,
+            data,
+            pending: true,
+            latency: Duration::ZERO,
+        }
+    }
+
+    /// Moves on to the next keep alive, returning the ID to send it with.
+    pub fn next(&mut self, now: Instant) -> i64 {
+        self.data = self.data.wrapping_add(1);
+        self.last_sent = now;
+        self.pending = true;
+        self.data
+    }
+
+    /// Records the player's answer to a keep alive. Returns false if it isn't the one that's
+    /// waiting for an answer.
+    ///
+    /// The latency is smoothed like vanilla does, so a single slow answer doesn't make it jump.
+    pub fn answer(&mut self, id: i64, now: Instant) -> bool {
+        if !self.pending || id != self.data {
+            return false;
+        }
+        let round_trip = now.duration_since(self.last_sent);
+        self.latency = (self.latency * 3 + round_trip) / 4;
+        self.last_received = now;
+        self.pending = false;
+        true
+    }
+
+    /// Whether the last keep alive went unanswered for longer than `timeout`.
+    pub fn timed_out(&self, now: Instant, timeout: Duration) -> bool {
+        self.pending && now.duration_since(self.last_sent) >= timeout
+    }
+
+    /// The round trip time to the player, as shown in the tab list.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers_measure_latency() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start, 7);
+
+        assert!(!keep_alive.answer(6, start));
+        assert!(keep_alive.answer(7, start + Duration::from_millis(400)));
+        assert_eq!(keep_alive.latency(), Duration::from_millis(100));
+        // Answering twice doesn't count
+        assert!(!keep_alive.answer(7, start + Duration::from_millis(400)));
+
+        let sent_at = start + Duration::from_secs(15);
+        assert_eq!(keep_alive.next(sent_at), 8);
+        assert!(keep_alive.answer(8, sent_at + Duration::from_millis(100)));
+        assert_eq!(keep_alive.latency(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_unanswered_keep_alive_times_out() {
+        let timeout = Duration::from_secs(30);
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start, 0);
+
+        assert!(!keep_alive.timed_out(start + Duration::from_secs(29), timeout));
+        assert!(keep_alive.timed_out(start + Duration::from_secs(30), timeout));
+
+        keep_alive.answer(0, start + Duration::from_secs(1));
+        assert!(!keep_alive.timed_out(start + Duration::from_secs(60), timeout));
+    }
 }
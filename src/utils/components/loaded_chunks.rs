@@ -0,0 +1,222 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use ferrumc_macros::Component;
+
+/// The chunks a player's client has loaded.
+///
+/// Chunks that leave the view radius aren't unloaded straight away. They're held for a grace
+/// period instead, so a player jittering across a chunk border doesn't cause a flood of
+/// unload/load packets for the edge chunks.
+#[derive(Debug, Component, Default)]
+pub struct LoadedChunks {
+    /// The chunk the client was last told it's in, with Set Center Chunk.
+    center: Option<(i32, i32)>,
+    loaded: HashSet<(i32, i32)>,
+    /// Chunks on their way to the client, which only count as loaded once they've been sent.
+    sending: HashSet<(i32, i32)>,
+    /// Chunks that left the view radius, and when they left it.
+    pending_unload: HashMap<(i32, i32), Instant>,
+}
+
+impl LoadedChunks {
+    /// Moves the center of the view. Returns whether it's a different chunk than before, and so
+    /// whether the client needs to be told about it.
+    pub fn set_center(&mut self, chunk_x: i32, chunk_z: i32) -> bool {
+        self.center.replace((chunk_x, chunk_z)) != Some((chunk_x, chunk_z))
+    }
+
+    pub fn center(&self) -> Option<(i32, i32)> {
+        self.center
+    }
+
+    /// Forgets every chunk, for when the client throws its chunks away itself, like on respawn.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Updates the chunks in view. Returns the chunks that weren't loaded yet, which are now
+    /// being sent until they're passed to [LoadedChunks::mark_sent] or
+    /// [LoadedChunks::stop_sending].
+    ///
+    /// Loaded chunks that aren't in view anymore start their grace period, and chunks coming back
+    /// into view before it's over are kept as if they never left.
+    pub fn update_view(
+        &mut self,
+        in_view: impl IntoIterator<Item = (i32, i32)>,
+        now: Instant,
+    ) -> Vec<(i32, i32)> {
+        let in_view = in_view.into_iter().collect::<HashSet<_>>();
+
+        for chunk in &self.loaded {
+            if !in_view.contains(chunk) {
+                self.pending_unload.entry(*chunk).or_insert(now);
+            }
+        }
+
+        let mut new_chunks = Vec::new();
+        for chunk in in_view {
+            self.pending_unload.remove(&chunk);
+            if !self.loaded.contains(&chunk) && self.sending.insert(chunk) {
+                new_chunks.push(chunk);
+            }
+        }
+        new_chunks
+    }
+
+    /// Counts chunks as loaded now that they've made it to the client. Chunks that aren't being
+    /// sent anymore, like ones from before a [LoadedChunks::clear], are left alone.
+    pub fn mark_sent(&mut self, chunks: &[(i32, i32)]) {
+        for chunk in chunks {
+            if self.sending.remove(chunk) {
+                self.loaded.insert(*chunk);
+            }
+        }
+    }
+
+    /// Gives up on sending chunks. The ones that weren't sent are picked up again by the next
+    /// view update.
+    pub fn stop_sending(&mut self, chunks: &[(i32, i32)]) {
+        for chunk in chunks {
+            self.sending.remove(chunk);
+        }
+    }
+
+    /// Removes and returns the chunks that have been out of view for longer than `grace_period`.
+    pub fn take_expired(&mut self, now: Instant, grace_period: Duration) -> Vec<(i32, i32)> {
+        let expired = self
+            .pending_unload
+            .iter()
+            .filter(|(_, left_at)| now.duration_since(**left_at) >= grace_period)
+            .map(|(chunk, _)| *chunk)
+            .collect::<Vec<_>>();
+
+        for chunk in &expired {
+            self.pending_unload.remove(chunk);
+            self.loaded.remove(chunk);
+        }
+        expired
+    }
+
+    pub fn is_loaded(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        self.loaded.contains(&(chunk_x, chunk_z))
+    }
+}
+
+/// The chunks within `radius` of the center chunk, as a square like the client loads them.
+pub fn chunks_in_view(center_x: i32, center_z: i32, radius: i32) -> Vec<(i32, i32)> {
+    (-radius..=radius)
+        .flat_map(|x| (-radius..=radius).map(move |z| (center_x + x, center_z + z)))
+        .collect()
+}
+
+/// Sorts chunks so the ones closest to the center come first, which is the order the player
+/// wants to see them in.
+pub fn sort_by_distance(chunks: &mut [(i32, i32)], center_x: i32, center_z: i32) {
+    chunks.sort_by_key(|(x, z)| {
+        let (dx, dz) = (x - center_x, z - center_z);
+        dx * dx + dz * dz
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reentering_within_grace_period_is_never_unloaded() {
+        let grace = Duration::from_secs(5);
+        let start = Instant::now();
+        let mut chunks = LoadedChunks::default();
+
+        let new = chunks.update_view(chunks_in_view(0, 0, 1), start);
+        chunks.mark_sent(&new);
+
+        // Step over the border, so the x = -1 column leaves the view...
+        let new = chunks.update_view(chunks_in_view(1, 0, 1), start + Duration::from_secs(1));
+        chunks.mark_sent(&new);
+        assert!(chunks
+            .take_expired(start + Duration::from_secs(2), grace)
+            .is_empty());
+
+        // ...and step back before the grace period is over
+        let new = chunks.update_view(chunks_in_view(0, 0, 1), start + Duration::from_secs(3));
+        assert!(new.is_empty());
+
+        // The x = 2 column left at 3s, but x = -1 should never expire
+        let expired = chunks.take_expired(start + Duration::from_secs(10), grace);
+        assert!(expired.iter().all(|(x, _)| *x == 2));
+        assert_eq!(expired.len(), 3);
+        assert!(chunks.is_loaded(-1, 0));
+        assert!(!chunks.is_loaded(2, 0));
+    }
+
+    #[test]
+    fn test_only_entered_chunks_are_new() {
+        let now = Instant::now();
+        let mut chunks = LoadedChunks::default();
+
+        assert!(chunks.set_center(0, 0));
+        let new = chunks.update_view(chunks_in_view(0, 0, 2), now);
+        assert_eq!(new.len(), 25);
+        chunks.mark_sent(&new);
+        assert!(!chunks.set_center(0, 0));
+        assert!(chunks.update_view(chunks_in_view(0, 0, 2), now).is_empty());
+
+        // Crossing into the next chunk only brings in the column on that side
+        assert!(chunks.set_center(1, 0));
+        let mut new = chunks.update_view(chunks_in_view(1, 0, 2), now);
+        sort_by_distance(&mut new, 1, 0);
+        assert_eq!(new.len(), 5);
+        assert!(new.iter().all(|(x, _)| *x == 3));
+        assert_eq!(new[0], (3, 0));
+        chunks.mark_sent(&new);
+
+        chunks.clear();
+        assert_eq!(chunks.center(), None);
+        assert_eq!(chunks.update_view(chunks_in_view(1, 0, 2), now).len(), 25);
+    }
+
+    #[test]
+    fn test_chunks_only_count_as_loaded_once_sent() {
+        let now = Instant::now();
+        let mut chunks = LoadedChunks::default();
+
+        let new = chunks.update_view(chunks_in_view(0, 0, 1), now);
+        assert_eq!(new.len(), 9);
+        assert!(!chunks.is_loaded(0, 0));
+        // They're already on their way, so they aren't sent twice
+        assert!(chunks.update_view(chunks_in_view(0, 0, 1), now).is_empty());
+
+        // Only the first few made it before the connection failed
+        chunks.mark_sent(&new[..4]);
+        chunks.stop_sending(&new);
+        assert!(chunks.is_loaded(new[0].0, new[0].1));
+        assert!(!chunks.is_loaded(new[4].0, new[4].1));
+        let resent = chunks.update_view(chunks_in_view(0, 0, 1), now);
+        assert_eq!(
+            resent.into_iter().collect::<HashSet<_>>(),
+            new[4..].iter().copied().collect()
+        );
+    }
+
+    #[test]
+    fn test_chunks_out_of_view_expire_after_grace_period() {
+        let grace = Duration::from_secs(5);
+        let start = Instant::now();
+        let mut chunks = LoadedChunks::default();
+
+        let new = chunks.update_view(chunks_in_view(0, 0, 0), start);
+        chunks.mark_sent(&new);
+        let new = chunks.update_view(chunks_in_view(10, 10, 0), start);
+        chunks.mark_sent(&new);
+
+        assert!(chunks
+            .take_expired(start + Duration::from_secs(4), grace)
+            .is_empty());
+        assert_eq!(
+            chunks.take_expired(start + Duration::from_secs(5), grace),
+            vec![(0, 0)]
+        );
+    }
+}
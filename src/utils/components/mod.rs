@@ -1,5 +1,17 @@
+pub mod digging;
+pub mod entity_flags;
+pub mod entity_info;
+pub mod game_mode;
+pub mod game_profile;
 pub mod grounded;
+pub mod health;
+pub mod inventory;
 pub mod keep_alive;
-pub mod last_chunk_tx_pos;
+pub mod loaded_chunks;
+pub mod open_container;
 pub mod player;
+pub mod plugin_channels;
+pub mod precise_position;
+pub mod riding;
 pub mod rotation;
+pub mod visible_entities;
@@ -0,0 +1,152 @@
+use ferrumc_macros::Component;
+
+use crate::net::packets::incoming::click_container::ClickContainer;
+use crate::net::packets::outgoing::set_container_content::SetContainerContent;
+use crate::utils::components::inventory::{
+    Drag, Inventory, Window, WindowLayout, OFF_HAND_SLOT, PLAYER_SLOTS,
+};
+use crate::utils::encoding::slot::Slot;
+use crate::world::item_registry::ItemRegistry;
+
+/// The longest custom name an anvil accepts, in characters.
+pub const MAX_ITEM_NAME_LENGTH: usize = 50;
+
+/// The kinds of container windows the server opens, by their id in the client's menu registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowType {
+    /// A chest's 3 rows of 9 slots.
+    Generic9x3 = 2,
+    Anvil = 7,
+    Merchant = 18,
+}
+
+impl WindowType {
+    /// How many slots the container itself has, not counting the player's inventory.
+    pub fn slot_count(&self) -> usize {
+        match self {
+            WindowType::Generic9x3 => 27,
+            WindowType::Anvil | WindowType::Merchant => 3,
+        }
+    }
+}
+
+/// Added to a player while they have a container window open, like a chest, an anvil or a
+/// villager's trades.
+#[derive(Debug, Clone, Component)]
+pub struct OpenContainer {
+    pub window_id: u8,
+    pub window_type: WindowType,
+    /// Bumped whenever the server changes the contents, so stale clicks can be told apart.
+    pub state_id: i32,
+    /// The container's own slots. The rest of the window is the player's [Inventory].
+    pub slots: Vec<Slot>,
+    /// The item the player is dragging around with their cursor.
+    pub carried_item: Slot,
+    pub drag: Option<Drag>,
+    /// The name typed into an anvil's text field, `None` if it's left blank.
+    pub item_name: Option<String>,
+    /// The trade picked from a villager's trade list.
+    pub selected_trade: Option<i32>,
+}
+
+impl OpenContainer {
+    pub fn new(window_id: u8, window_type: WindowType) -> Self {
+        Self {
+            window_id,
+            window_type,
+            state_id: 0,
+            slots: vec![Slot::empty(); window_type.slot_count()],
+            carried_item: Slot::empty(),
+            drag: None,
+            item_name: None,
+            selected_trade: None,
+        }
+    }
+
+    /// Sets the anvil output's name. Anything past [MAX_ITEM_NAME_LENGTH] is cut off, like
+    /// vanilla does.
+    pub fn rename(&mut self, name: &str) {
+        let name = name.chars().take(MAX_ITEM_NAME_LENGTH).collect::<String>();
+        self.item_name = (!name.trim().is_empty()).then_some(name);
+    }
+
+    /// Moves to a new state id, wrapping around like vanilla does.
+    pub fn next_state_id(&mut self) -> i32 {
+        self.state_id = (self.state_id + 1) & 0x7FFF;
+        self.state_id
+    }
+
+    /// Every slot of the window, laid out like [WindowLayout::Container]: the container's, then
+    /// the player's, then their off hand.
+    pub fn window_slots(&self, inventory: &Inventory) -> Vec<Slot> {
+        let mut slots = self.slots.clone();
+        slots.extend_from_slice(&inventory.slots[PLAYER_SLOTS]);
+        slots.push(inventory.slots[OFF_HAND_SLOT].clone());
+        slots
+    }
+
+    /// Puts the slots of the window back into the container and the player's inventory, see
+    /// [OpenContainer::window_slots].
+    fn store_window_slots(&mut self, inventory: &mut Inventory, mut slots: Vec<Slot>) {
+        inventory.slots[OFF_HAND_SLOT] = slots.pop().unwrap_or_default();
+        let player = slots.split_off(self.slots.len());
+        inventory.slots[PLAYER_SLOTS].clone_from_slice(&player);
+        self.slots = slots;
+    }
+
+    /// Applies a click to the container, which moves items in the player's inventory too.
+    ///
+    /// Clicks are worked out by the server like in [Inventory::click]. If the client came to a
+    /// different result, or the click was made against an outdated state, the whole window has
+    /// to be sent again under a new state id, to undo whatever the client predicted.
+    pub fn click(
+        &mut self,
+        click: &ClickContainer,
+        inventory: &mut Inventory,
+        creative: bool,
+    ) -> Result<(), SetContainerContent> {
+        if click.state_id.get_val() != self.state_id {
+            return Err(self.resync(inventory));
+        }
+
+        let mut slots = self.window_slots(inventory);
+        let before = slots.clone();
+        let agreed = Window {
+            layout: WindowLayout::Container(self.window_type),
+            slots: &mut slots,
+            carried_item: &mut self.carried_item,
+            drag: &mut self.drag,
+        }
+        .click(click, &before, creative);
+        self.store_window_slots(inventory, slots);
+
+        if !agreed {
+            return Err(self.resync(inventory));
+        }
+        Ok(())
+    }
+
+    fn resync(&mut self, inventory: &Inventory) -> SetContainerContent {
+        self.next_state_id();
+        SetContainerContent::of(self, inventory)
+    }
+
+    /// Gives the player back what they left in the container when closing it, like the items in
+    /// an anvil, along with the item on their cursor. Anything that doesn't fit is lost, as
+    /// there are no item entities to drop. Returns whether their inventory changed.
+    pub fn close(self, inventory: &mut Inventory, items: &ItemRegistry) -> bool {
+        let left = match self.window_type {
+            // Chests don't keep their items, so they're given back too
+            WindowType::Generic9x3 => &self.slots[..],
+            WindowType::Anvil | WindowType::Merchant => &self.slots[..2],
+        };
+        let mut changed = false;
+        for item in left.iter().chain([&self.carried_item]) {
+            if !item.is_empty() {
+                inventory.give(item.clone(), items);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
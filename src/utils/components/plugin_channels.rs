@@ -0,0 +1,18 @@
+use std::collections::HashSet;
+
+use ferrumc_macros::Component;
+
+/// What a player's client told the server about itself over plugin channels.
+#[derive(Debug, Component, Default)]
+pub struct PluginChannels {
+    /// The client's brand, like `vanilla` or `fabric`, once it has sent it.
+    pub brand: Option<String>,
+    /// The channels the client said it listens on with `minecraft:register`.
+    pub registered: HashSet<String>,
+}
+
+impl PluginChannels {
+    pub fn is_registered(&self, channel: &str) -> bool {
+        self.registered.contains(channel)
+    }
+}
@@ -0,0 +1,82 @@
+use ferrumc_macros::Component;
+
+use crate::utils::encoding::position::Position;
+
+/// Where an entity is exactly. [Position] only keeps the block it's in, which isn't enough to
+/// tell other clients how far it moved.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct PrecisePosition {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl PrecisePosition {
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    /// The corner of a block, which is where
+    /// [SynchronizePlayerPosition](crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition)
+    /// puts players.
+    pub fn corner(position: &Position) -> Self {
+        Self::new(position.x as f64, position.y as f64, position.z as f64)
+    }
+
+    /// The middle of a block, for entities that only have a [Position] so far.
+    pub fn centered(position: &Position) -> Self {
+        Self::new(
+            position.x as f64 + 0.5,
+            position.y as f64,
+            position.z as f64 + 0.5,
+        )
+    }
+
+    pub fn block(&self) -> Position {
+        Position::new(
+            self.x.floor() as i32,
+            self.y.floor() as i16,
+            self.z.floor() as i32,
+        )
+    }
+
+    pub fn chunk(&self) -> (i32, i32) {
+        ((self.x.floor() as i32) >> 4, (self.z.floor() as i32) >> 4)
+    }
+
+    pub fn as_tuple(&self) -> (f64, f64, f64) {
+        (self.x, self.y, self.z)
+    }
+
+    /// How far the entity moved since `old`, in the 1/4096 block steps the relative move packets
+    /// use. `None` if it moved too far for those, 8 blocks or more along any axis.
+    pub fn delta_from(&self, old: &PrecisePosition) -> Option<(i16, i16, i16)> {
+        let delta = |new: f64, old: f64| {
+            let delta = (new * 4096.0).round() as i64 - (old * 4096.0).round() as i64;
+            i16::try_from(delta).ok()
+        };
+        Some((
+            delta(self.x, old.x)?,
+            delta(self.y, old.y)?,
+            delta(self.z, old.z)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_moves_are_relative() {
+        let old = PrecisePosition::new(0.5, 64.0, 0.5);
+        let new = PrecisePosition::new(1.5, 63.5, -2.5);
+        assert_eq!(new.delta_from(&old), Some((4096, -2048, -12288)));
+        assert_eq!(new.chunk(), (0, -1));
+        assert_eq!(new.block(), Position::new(1, 63, -3));
+
+        // 8 blocks is too far for a relative move
+        let far = PrecisePosition::new(8.5, 64.0, 0.5);
+        assert_eq!(far.delta_from(&old), None);
+    }
+}
@@ -0,0 +1,13 @@
+use ferrumc_macros::Component;
+
+/// Added to a player while they're riding another entity, like a boat or a minecart.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Riding {
+    pub vehicle_id: usize,
+}
+
+impl Riding {
+    pub fn new(vehicle_id: usize) -> Self {
+        Self { vehicle_id }
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use ferrumc_macros::Component;
+
+/// The entities a player's client has been told about, and so has spawned, with their UUIDs.
+///
+/// Entity ids are reused once an entity is gone, so an id that now belongs to a different UUID
+/// counts as a different entity.
+#[derive(Debug, Component, Default)]
+pub struct VisibleEntities {
+    visible: HashMap<usize, u128>,
+}
+
+impl VisibleEntities {
+    /// Replaces the visible entities with the ones in view now. Returns the entities that have to
+    /// be spawned, and the ones that have to be removed.
+    pub fn update(&mut self, in_view: HashMap<usize, u128>) -> (Vec<usize>, Vec<usize>) {
+        let mut spawned = in_view
+            .iter()
+            .filter(|(id, uuid)| self.visible.get(id) != Some(uuid))
+            .map(|(&id, _)| id)
+            .collect::<Vec<_>>();
+        let mut removed = self
+            .visible
+            .iter()
+            .filter(|(id, uuid)| in_view.get(id) != Some(uuid))
+            .map(|(&id, _)| id)
+            .collect::<Vec<_>>();
+        spawned.sort_unstable();
+        removed.sort_unstable();
+        self.visible = in_view;
+        (spawned, removed)
+    }
+
+    /// Forgets an entity that was removed from the client. Returns whether it was visible.
+    pub fn remove(&mut self, entity_id: usize) -> bool {
+        self.visible.remove(&entity_id).is_some()
+    }
+
+    pub fn contains(&self, entity_id: usize) -> bool {
+        self.visible.contains_key(&entity_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_spawns_and_removes() {
+        let mut visible = VisibleEntities::default();
+        let (spawned, removed) = visible.update(HashMap::from([(1, 10), (2, 20)]));
+        assert_eq!((spawned, removed), (vec![1, 2], vec![]));
+
+        let (spawned, removed) = visible.update(HashMap::from([(2, 20), (3, 30)]));
+        assert_eq!((spawned, removed), (vec![3], vec![1]));
+        assert!(visible.contains(2));
+        assert!(!visible.contains(1));
+
+        let (spawned, removed) = visible.update(HashMap::from([(2, 20), (3, 30)]));
+        assert!(spawned.is_empty() && removed.is_empty());
+    }
+
+    #[test]
+    fn test_reused_ids_are_respawned() {
+        let mut visible = VisibleEntities::default();
+        visible.update(HashMap::from([(1, 10)]));
+
+        let (spawned, removed) = visible.update(HashMap::from([(1, 11)]));
+        assert_eq!((spawned, removed), (vec![1], vec![1]));
+
+        assert!(visible.remove(1));
+        assert!(!visible.remove(1));
+    }
+}
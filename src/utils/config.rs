@@ -1,10 +1,18 @@
 use std::io::ErrorKind::NotFound;
 use std::io::Write;
+use std::path::PathBuf;
 use std::sync::OnceLock;
 
 use crate::utils::constants::{
-    DEFAULT_CONFIG_FILE, DEFAULT_MAX_PLAYERS, DEFAULT_MOTD, DEFAULT_SERVER_HOST,
-    DEFAULT_SERVER_PORT,
+    DEFAULT_AUTOSAVE_CHUNKS_PER_TICK, DEFAULT_AUTOSAVE_INTERVAL_SECS,
+    DEFAULT_BANNED_IPS_FILE, DEFAULT_BANNED_PLAYERS_FILE, DEFAULT_BLOCK_REGISTRY_CACHE,
+    DEFAULT_CHAT_FORMAT, DEFAULT_CHUNK_BATCH_SIZE, DEFAULT_CHUNK_CACHE_CAPACITY,
+    DEFAULT_CHUNK_CACHE_TTL_SECS, DEFAULT_CHUNK_RESEND_DENSITY, DEFAULT_CHUNK_UNLOAD_GRACE_SECS,
+    DEFAULT_CONFIG_FILE, DEFAULT_FLAT_LAYERS, DEFAULT_KEEP_ALIVE_TIMEOUT_SECS, DEFAULT_LOG_LEVEL,
+    DEFAULT_MAX_PLAYERS, DEFAULT_MOTD, DEFAULT_OPS_FILE, DEFAULT_PLAYER_DATA_DIR, DEFAULT_QUERY_PORT,
+    DEFAULT_RCON_PORT, DEFAULT_REGION_DIR, DEFAULT_RESOURCE_PACK_KICK_MESSAGE, DEFAULT_SERVER_BRAND, DEFAULT_SERVER_HOST, DEFAULT_SHUTDOWN_MESSAGE, DEFAULT_SERVER_PORT, DEFAULT_THROTTLE_MAX_CONNECTIONS,
+    DEFAULT_THROTTLE_WINDOW_SECS, DEFAULT_VIEW_DISTANCE, DEFAULT_WHITELIST_FILE,
+    DEFAULT_WHITELIST_KICK_MESSAGE, LEGACY_CONFIG_FILE,
 };
 use crate::utils::error::Error;
 use config::{Config, ConfigError};
@@ -22,7 +30,367 @@ pub struct ServerConfig {
     pub network_tick_rate: u32,
     pub database: Database,
     pub world: String,
+    /// How many chunks around them players get sent, at most. Players with a lower render
+    /// distance get sent fewer.
+    #[serde(default = "default_view_distance")]
+    pub view_distance: i8,
+    /// The least important log messages that get printed: trace, debug, info, warn or error.
+    /// The `--log=` command line flag takes precedence.
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
     pub network_compression_threshold: i32, // -1, no compression. 0, compress everything, n > 0, compress packets larger than n size in bytes.
+    #[serde(default)]
+    pub gamerules: GameRules,
+    /// Usernames of the players with operator permissions, on top of the ones in `ops_file`.
+    /// They get the highest permission level.
+    #[serde(default)]
+    pub operators: Vec<String>,
+    /// JSON file with the operators and their permission levels, in the same format as vanilla's
+    /// `ops.json`. Changed by `/op` and `/deop`.
+    #[serde(default = "default_ops_file")]
+    pub ops_file: String,
+    /// How long a chunk that left a player's view is kept loaded, in case they come back.
+    #[serde(default = "default_chunk_unload_grace_secs")]
+    pub chunk_unload_grace_secs: u64,
+    /// How many chunks are written to the socket at once when sending the world to a player.
+    #[serde(default = "default_chunk_batch_size")]
+    pub chunk_batch_size: usize,
+    /// Send spectators chunks without any light data, since they see in fullbright anyway.
+    #[serde(default)]
+    pub reduced_spectator_chunks: bool,
+    /// Once more than this share of the blocks in the changed sections of a chunk change at once,
+    /// the whole chunk is resent instead of sending multi block changes.
+    #[serde(default = "default_chunk_resend_density")]
+    pub chunk_resend_density: f64,
+    /// How many chunks are kept in memory after they've been loaded or generated.
+    #[serde(default = "default_chunk_cache_capacity")]
+    pub chunk_cache_capacity: u64,
+    /// How long a cached chunk nobody asks for stays in memory, in seconds.
+    #[serde(default = "default_chunk_cache_ttl_secs")]
+    pub chunk_cache_ttl_secs: u64,
+    /// How long a player has to answer a keep alive before they're disconnected, in seconds.
+    #[serde(default = "default_keep_alive_timeout_secs")]
+    pub keep_alive_timeout_secs: u64,
+    /// How chat messages are shown, with `{player}` and `{message}` filled in. Empty sends them as
+    /// player chat instead, formatted by the client.
+    #[serde(default = "default_chat_format")]
+    pub chat_format: String,
+    /// What players are kicked with when the server stops.
+    #[serde(default = "default_shutdown_message")]
+    pub shutdown_message: String,
+    /// How often the world is saved while the server runs, in seconds. 0 turns autosaving off.
+    #[serde(default = "default_autosave_interval_secs")]
+    pub autosave_interval_secs: u64,
+    /// How many chunks an autosave writes per tick, so saving doesn't cause lag spikes.
+    #[serde(default = "default_autosave_chunks_per_tick")]
+    pub autosave_chunks_per_tick: usize,
+    /// Check with Mojang's session servers that players own their accounts, and encrypt their
+    /// connections.
+    #[serde(default)]
+    pub online_mode: bool,
+    /// In offline mode, give players the skin of the Mojang account with the same name.
+    #[serde(default)]
+    pub offline_skins: bool,
+    /// What players see as the server's software in their debug screen (F3).
+    #[serde(default = "default_server_brand")]
+    pub server_brand: String,
+    /// Scroll a wave of crabs through the brand in the debug screen instead of showing
+    /// `server_brand`.
+    #[serde(default)]
+    pub animated_brand: bool,
+    #[serde(default)]
+    pub whitelist: WhitelistConfig,
+    #[serde(default)]
+    pub bans: BanConfig,
+    #[serde(default)]
+    pub forwarding: ForwardingConfig,
+    #[serde(default)]
+    pub resource_pack: ResourcePackConfig,
+    #[serde(default)]
+    pub throttle: ThrottleConfig,
+    #[serde(default)]
+    pub rcon: RconConfig,
+    #[serde(default)]
+    pub query: QueryConfig,
+    /// Binary cache of the block state registry, so it isn't rebuilt from JSON on every start.
+    /// Empty to turn the cache off.
+    #[serde(default = "default_block_registry_cache")]
+    pub block_registry_cache: String,
+    /// Vanilla's `blocks.json` report to load the block registry from instead of the bundled one.
+    /// Empty to use the bundled registry.
+    #[serde(default)]
+    pub blocks_report: String,
+    /// Vanilla's `registries.json` report to load item names from, so that any held block can be
+    /// placed. Empty to only know the bundled items.
+    #[serde(default)]
+    pub registries_report: String,
+    /// The directory players' positions and game modes are saved to when they leave.
+    #[serde(default = "default_player_data_dir")]
+    pub player_data_dir: String,
+    /// Where the chunks sent to players come from.
+    #[serde(default)]
+    pub generator: WorldGenerator,
+    /// The `region` directory of the vanilla world the anvil generator serves, and where the
+    /// flat generator saves its chunks.
+    #[serde(default = "default_region_dir")]
+    pub region_dir: String,
+    /// The layers of the superflat generator, bottom to top, e.g. `3*minecraft:dirt`.
+    #[serde(default = "default_flat_layers")]
+    pub flat_layers: Vec<String>,
+    /// Seed for the terrain generated in place of chunks that haven't been imported or saved.
+    #[serde(default)]
+    pub seed: i64,
+}
+
+fn default_view_distance() -> i8 {
+    DEFAULT_VIEW_DISTANCE
+}
+
+fn default_log_level() -> String {
+    DEFAULT_LOG_LEVEL.to_string()
+}
+
+fn default_chunk_unload_grace_secs() -> u64 {
+    DEFAULT_CHUNK_UNLOAD_GRACE_SECS
+}
+
+fn default_chunk_batch_size() -> usize {
+    DEFAULT_CHUNK_BATCH_SIZE
+}
+
+fn default_chunk_resend_density() -> f64 {
+    DEFAULT_CHUNK_RESEND_DENSITY
+}
+
+fn default_chunk_cache_capacity() -> u64 {
+    DEFAULT_CHUNK_CACHE_CAPACITY
+}
+
+fn default_chunk_cache_ttl_secs() -> u64 {
+    DEFAULT_CHUNK_CACHE_TTL_SECS
+}
+
+fn default_keep_alive_timeout_secs() -> u64 {
+    DEFAULT_KEEP_ALIVE_TIMEOUT_SECS
+}
+
+fn default_chat_format() -> String {
+    DEFAULT_CHAT_FORMAT.to_string()
+}
+
+fn default_server_brand() -> String {
+    DEFAULT_SERVER_BRAND.to_string()
+}
+
+fn default_shutdown_message() -> String {
+    DEFAULT_SHUTDOWN_MESSAGE.to_string()
+}
+
+fn default_autosave_interval_secs() -> u64 {
+    DEFAULT_AUTOSAVE_INTERVAL_SECS
+}
+
+fn default_autosave_chunks_per_tick() -> usize {
+    DEFAULT_AUTOSAVE_CHUNKS_PER_TICK
+}
+
+fn default_ops_file() -> String {
+    DEFAULT_OPS_FILE.to_string()
+}
+
+fn default_player_data_dir() -> String {
+    DEFAULT_PLAYER_DATA_DIR.to_string()
+}
+
+fn default_region_dir() -> String {
+    DEFAULT_REGION_DIR.to_string()
+}
+
+fn default_flat_layers() -> Vec<String> {
+    DEFAULT_FLAT_LAYERS.iter().map(|layer| layer.to_string()).collect()
+}
+
+fn default_block_registry_cache() -> String {
+    DEFAULT_BLOCK_REGISTRY_CACHE.to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameRules {
+    /// Whether the time of day advances. When false, the sun is frozen for all clients.
+    pub do_daylight_cycle: bool,
+    /// Whether the weather changes by itself. `/weather` still works when it doesn't.
+    pub do_weather_cycle: bool,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            do_daylight_cycle: true,
+            do_weather_cycle: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorldGenerator {
+    /// Serve the chunks imported into the database, generating terrain for any that are missing.
+    #[default]
+    Imported,
+    /// Lay every block state out on a grid, like vanilla's debug world.
+    Debug,
+    /// Read chunks straight from the region files of a vanilla world, see `region_dir`. Missing
+    /// chunks are generated.
+    Anvil,
+    /// A superflat world made of `flat_layers`. Changed chunks are saved to region files in
+    /// `region_dir`, like with `Anvil`.
+    Flat,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WhitelistConfig {
+    /// Only let the players listed in `file` join.
+    pub enabled: bool,
+    /// JSON file with the whitelisted players, in the same format as vanilla's `whitelist.json`.
+    pub file: String,
+    /// Shown to players that get turned away.
+    pub kick_message: String,
+}
+
+impl Default for WhitelistConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: DEFAULT_WHITELIST_FILE.to_string(),
+            kick_message: DEFAULT_WHITELIST_KICK_MESSAGE.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BanConfig {
+    /// JSON file with the banned player UUIDs.
+    pub players_file: String,
+    /// JSON file with the banned addresses and address ranges.
+    pub ips_file: String,
+}
+
+impl Default for BanConfig {
+    fn default() -> Self {
+        Self {
+            players_file: DEFAULT_BANNED_PLAYERS_FILE.to_string(),
+            ips_file: DEFAULT_BANNED_IPS_FILE.to_string(),
+        }
+    }
+}
+
+/// How a proxy in front of the server passes on the players' real UUIDs, addresses and skins.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardingMode {
+    /// Players connect straight to the server.
+    #[default]
+    None,
+    /// BungeeCord's forwarding, in extra fields of the handshake's server address. Anyone who can
+    /// reach the server directly can fake it, so only the proxy should be able to.
+    Legacy,
+    /// Velocity's modern forwarding, signed with `secret` so it can't be faked.
+    Modern,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForwardingConfig {
+    pub mode: ForwardingMode,
+    /// The secret shared with Velocity, for the modern mode.
+    pub secret: String,
+}
+
+/// The resource pack players get asked to download when they join.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourcePackConfig {
+    /// Where the pack can be downloaded from. Empty to not send one.
+    pub url: String,
+    /// The pack's SHA-1 hash, so players only download it again when it changes.
+    pub sha1: String,
+    /// Kick players that decline the pack.
+    pub required: bool,
+    /// Shown to players along with the question. Empty for the client's default text.
+    pub prompt: String,
+    /// Shown to players that get kicked for declining the pack.
+    pub kick_message: String,
+}
+
+impl Default for ResourcePackConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            sha1: String::new(),
+            required: false,
+            prompt: String::new(),
+            kick_message: DEFAULT_RESOURCE_PACK_KICK_MESSAGE.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThrottleConfig {
+    /// How many connections one address can open within `window_secs`. 0 turns the limit off.
+    /// It's always off with forwarding on, since every connection comes from the proxy then.
+    pub max_connections: u32,
+    pub window_secs: u64,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_THROTTLE_MAX_CONNECTIONS,
+            window_secs: DEFAULT_THROTTLE_WINDOW_SECS,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RconConfig {
+    /// Let admin tools run commands over the RCON protocol.
+    pub enabled: bool,
+    pub port: u16,
+    /// RCON stays off while this is empty, even if it's enabled.
+    pub password: String,
+}
+
+impl Default for RconConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_RCON_PORT,
+            password: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryConfig {
+    /// Answer GameSpy4 queries, which server list sites use to show the players online.
+    pub enabled: bool,
+    /// UDP, so it can be the same port as the server's.
+    pub port: u16,
+}
+
+impl Default for QueryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_QUERY_PORT,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -40,14 +408,14 @@ impl ServerConfig {
     /// Load the server configuration from the config file
     pub fn new() -> Result<Self, Error> {
         let settings = Config::builder()
-            .add_source(config::File::with_name("config"))
+            .add_source(config::File::from(config_file_path()))
             .build()
             .or_else(|err| {
                 if is_not_found(&err) {
                     info!("Config file wasn't found, creating a new one.");
                     create_config_file()?;
                     return Config::builder()
-                        .add_source(config::File::with_name("config"))
+                        .add_source(config::File::from(config_file_path()))
                         .build()
                         .map_err(Error::from);
                 }
@@ -65,7 +433,7 @@ impl ServerConfig {
                     info!("Creating new config file...");
                     create_config_file()?;
                     Config::builder()
-                        .add_source(config::File::with_name("config"))
+                        .add_source(config::File::from(config_file_path()))
                         .build()
                         .map_err(Error::from)
                         .and_then(|settings| settings.try_deserialize().map_err(Error::from))
@@ -86,6 +454,23 @@ impl ServerConfig {
             // All logic for compression always does <= -1 anyways. The warning exists since its not compliant with the server.properties.
         }
 
+        if de_settings.forwarding.mode == ForwardingMode::Modern
+            && de_settings.forwarding.secret.is_empty()
+        {
+            return Err(Error::Generic(
+                "Modern forwarding needs the forwarding secret from Velocity's config".to_string(),
+            ));
+        }
+
+        let sha1 = &de_settings.resource_pack.sha1;
+        if !sha1.is_empty()
+            && (sha1.len() != 40 || !sha1.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Err(Error::Generic(
+                "The resource pack's sha1 has to be 40 hex digits".to_string(),
+            ));
+        }
+
         Ok(de_settings)
     }
 
@@ -102,6 +487,24 @@ impl ServerConfig {
     }
 }
 
+/// The config file to load. Servers set up before it was renamed keep using their old one.
+pub fn config_file_path() -> PathBuf {
+    let path = PathBuf::from(DEFAULT_CONFIG_FILE);
+    let legacy = PathBuf::from(LEGACY_CONFIG_FILE);
+    if !path.exists() && legacy.exists() {
+        return legacy;
+    }
+    path
+}
+
+/// The log level set in the config file, if there is one. Only meant for setting up the logger,
+/// which happens before the config is loaded, so it can't rely on the logger itself.
+pub fn configured_log_level() -> Option<String> {
+    let contents = std::fs::read_to_string(config_file_path()).ok()?;
+    let config = contents.parse::<toml::Table>().ok()?;
+    config.get("log_level")?.as_str().map(String::from)
+}
+
 /// Check if the error is a not found error
 fn is_not_found(err: &ConfigError) -> bool {
     let ConfigError::Foreign(foreign_error) = err else {
@@ -152,11 +555,46 @@ impl Default for ServerConfig {
             max_players: DEFAULT_MAX_PLAYERS as i32,
             network_tick_rate: 0,
             world: "world".to_string(),
+            view_distance: DEFAULT_VIEW_DISTANCE,
+            log_level: DEFAULT_LOG_LEVEL.to_string(),
             database: Database {
                 cache_size: 1024,
                 compression: "fast".to_string(),
             },
             network_compression_threshold: 256,
+            gamerules: GameRules::default(),
+            operators: Vec::new(),
+            ops_file: DEFAULT_OPS_FILE.to_string(),
+            chunk_unload_grace_secs: DEFAULT_CHUNK_UNLOAD_GRACE_SECS,
+            chunk_batch_size: DEFAULT_CHUNK_BATCH_SIZE,
+            reduced_spectator_chunks: false,
+            chunk_resend_density: DEFAULT_CHUNK_RESEND_DENSITY,
+            chunk_cache_capacity: DEFAULT_CHUNK_CACHE_CAPACITY,
+            chunk_cache_ttl_secs: DEFAULT_CHUNK_CACHE_TTL_SECS,
+            keep_alive_timeout_secs: DEFAULT_KEEP_ALIVE_TIMEOUT_SECS,
+            chat_format: DEFAULT_CHAT_FORMAT.to_string(),
+            shutdown_message: DEFAULT_SHUTDOWN_MESSAGE.to_string(),
+            autosave_interval_secs: DEFAULT_AUTOSAVE_INTERVAL_SECS,
+            autosave_chunks_per_tick: DEFAULT_AUTOSAVE_CHUNKS_PER_TICK,
+            online_mode: false,
+            offline_skins: false,
+            server_brand: DEFAULT_SERVER_BRAND.to_string(),
+            animated_brand: false,
+            whitelist: WhitelistConfig::default(),
+            bans: BanConfig::default(),
+            forwarding: ForwardingConfig::default(),
+            resource_pack: ResourcePackConfig::default(),
+            throttle: ThrottleConfig::default(),
+            rcon: RconConfig::default(),
+            query: QueryConfig::default(),
+            block_registry_cache: DEFAULT_BLOCK_REGISTRY_CACHE.to_string(),
+            blocks_report: String::new(),
+            registries_report: String::new(),
+            player_data_dir: DEFAULT_PLAYER_DATA_DIR.to_string(),
+            generator: WorldGenerator::default(),
+            region_dir: DEFAULT_REGION_DIR.to_string(),
+            flat_layers: default_flat_layers(),
+            seed: 0,
         }
     }
 }
@@ -1,10 +1,49 @@
 pub const DEFAULT_LOG_LEVEL: &str = "debug";
-pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+pub const DEFAULT_CONFIG_FILE: &str = "ferrumc.toml";
+/// Where the config file used to live, still read if there's no [DEFAULT_CONFIG_FILE].
+pub const LEGACY_CONFIG_FILE: &str = "config.toml";
+/// The Minecraft version the server speaks the protocol of.
+pub const GAME_VERSION: &str = "1.20.1";
+pub const PROTOCOL_VERSION: i32 = 763;
+/// Shown next to the server in the server list, if it exists.
+pub const FAVICON_FILE: &str = "server-icon.png";
+/// Where the server icon used to live, still used if there's no [FAVICON_FILE].
+pub const LEGACY_FAVICON_FILE: &str = "icon-64.png";
 pub const DEFAULT_SERVER_HOST: &str = "localhost";
 // Default port for a Minecraft server
 pub const DEFAULT_SERVER_PORT: u32 = 25565;
 pub const DEFAULT_MOTD: &str = "A FerrumC Server";
 pub const DEFAULT_MAX_PLAYERS: u32 = 20;
+pub const DEFAULT_VIEW_DISTANCE: i8 = 10;
+pub const DEFAULT_CHUNK_UNLOAD_GRACE_SECS: u64 = 5;
+pub const DEFAULT_CHUNK_BATCH_SIZE: usize = 16;
+pub const DEFAULT_CHUNK_RESEND_DENSITY: f64 = 0.25;
+pub const DEFAULT_CHUNK_CACHE_CAPACITY: u64 = 4096;
+pub const DEFAULT_CHUNK_CACHE_TTL_SECS: u64 = 300;
+pub const DEFAULT_KEEP_ALIVE_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_CHAT_FORMAT: &str = "<{player}> {message}";
+pub const DEFAULT_SHUTDOWN_MESSAGE: &str = "Server closed";
+pub const DEFAULT_SERVER_BRAND: &str = "FerrumC";
+pub const DEFAULT_RESOURCE_PACK_KICK_MESSAGE: &str = "This server requires a custom resource pack";
+pub const DEFAULT_AUTOSAVE_INTERVAL_SECS: u64 = 300;
+pub const DEFAULT_AUTOSAVE_CHUNKS_PER_TICK: usize = 16;
+pub const DEFAULT_WHITELIST_FILE: &str = "whitelist.json";
+pub const DEFAULT_BANNED_PLAYERS_FILE: &str = "banned-players.json";
+pub const DEFAULT_BANNED_IPS_FILE: &str = "banned-ips.json";
+pub const DEFAULT_OPS_FILE: &str = "ops.json";
+pub const DEFAULT_PLAYER_DATA_DIR: &str = "playerdata";
+pub const DEFAULT_REGION_DIR: &str = "world/region";
+pub const DEFAULT_FLAT_LAYERS: &[&str] = &[
+    "minecraft:bedrock",
+    "2*minecraft:dirt",
+    "minecraft:grass_block",
+];
+pub const DEFAULT_BLOCK_REGISTRY_CACHE: &str = "block_registry.bin";
+pub const DEFAULT_THROTTLE_MAX_CONNECTIONS: u32 = 5;
+pub const DEFAULT_THROTTLE_WINDOW_SECS: u64 = 10;
+pub const DEFAULT_RCON_PORT: u16 = 25575;
+pub const DEFAULT_QUERY_PORT: u16 = 25565;
+pub const DEFAULT_WHITELIST_KICK_MESSAGE: &str = "You are not whitelisted on this server!";
 
 pub mod init {
     pub const DEFAULT_SPAWN_X_POS: i32 = 0;
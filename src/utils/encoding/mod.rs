@@ -1,5 +1,6 @@
 pub mod bitset;
 pub mod position;
+pub mod slot;
 pub mod velocity;
 
 /*impl<S: NBTSerialize> Encode for &S {
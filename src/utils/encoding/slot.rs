@@ -0,0 +1,310 @@
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
+use ferrumc_codec::network_types::varint::VarInt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::utils::error::Error;
+use crate::utils::impls::packet_impls::NetDecode;
+
+const TAG_END: u8 = 0;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+
+/// The contents of an inventory slot, as sent over the network.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Slot {
+    pub item: Option<ItemStack>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item_id: i32,
+    pub count: i8,
+    /// The item's NBT, like its name or enchantments, kept exactly as the client sent it.
+    pub nbt: Option<Vec<u8>>,
+}
+
+impl Slot {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn new(item_id: i32, count: i8) -> Self {
+        Self {
+            item: Some(ItemStack {
+                item_id,
+                count,
+                nbt: None,
+            }),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.item.is_none()
+    }
+}
+
+impl NetEncode for Slot {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let Some(item) = &self.item else {
+            return false.net_encode(writer, encode_option).await;
+        };
+
+        true.net_encode(writer, encode_option).await?;
+        VarInt::from(item.item_id)
+            .net_encode(writer, encode_option)
+            .await?;
+        item.count.net_encode(writer, encode_option).await?;
+        match &item.nbt {
+            Some(nbt) => writer.write_all(nbt).await?,
+            None => writer.write_all(&[TAG_END]).await?,
+        }
+        Ok(())
+    }
+}
+
+impl NetDecode for Slot {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>, Error>
+    where
+        T: AsyncRead + Unpin,
+    {
+        if !*bool::net_decode(bytes).await? {
+            return Ok(Box::new(Slot::empty()));
+        }
+
+        let item_id = VarInt::net_decode(bytes).await?.get_val();
+        let count = *i8::net_decode(bytes).await?;
+        let nbt = read_raw_nbt(bytes).await?;
+
+        Ok(Box::new(Slot {
+            item: Some(ItemStack {
+                item_id,
+                count,
+                nbt,
+            }),
+        }))
+    }
+}
+
+/// A compound or list that's still being read.
+enum Nesting {
+    Compound,
+    List { tag: u8, remaining: i32 },
+}
+
+/// Reads a whole NBT tag without parsing it, returning its bytes. `None` if it's just an end tag,
+/// which is how items without any NBT are sent.
+///
+/// Nested tags are tracked with an explicit stack rather than recursion, so the future stays
+/// `Send` and deeply nested NBT can't overflow the stack.
+async fn read_raw_nbt<T>(bytes: &mut T) -> Result<Option<Vec<u8>>, Error>
+where
+    T: AsyncRead + Unpin,
+{
+    let mut raw = Vec::new();
+    let tag = read_exact(bytes, &mut raw, 1).await?[0];
+    if tag == TAG_END {
+        return Ok(None);
+    }
+    read_name(bytes, &mut raw).await?;
+
+    let mut stack = Vec::new();
+    read_payload(bytes, &mut raw, tag, &mut stack).await?;
+
+    while let Some(nesting) = stack.last_mut() {
+        let tag = match nesting {
+            Nesting::Compound => {
+                let tag = read_exact(bytes, &mut raw, 1).await?[0];
+                if tag == TAG_END {
+                    stack.pop();
+                    continue;
+                }
+                read_name(bytes, &mut raw).await?;
+                tag
+            }
+            Nesting::List { remaining, .. } if *remaining <= 0 => {
+                stack.pop();
+                continue;
+            }
+            Nesting::List { tag, remaining } => {
+                *remaining -= 1;
+                *tag
+            }
+        };
+        read_payload(bytes, &mut raw, tag, &mut stack).await?;
+    }
+
+    Ok(Some(raw))
+}
+
+/// Reads the payload of a tag. Compounds and lists are only opened, their contents are read by
+/// the caller.
+async fn read_payload<T>(
+    bytes: &mut T,
+    raw: &mut Vec<u8>,
+    tag: u8,
+    stack: &mut Vec<Nesting>,
+) -> Result<(), Error>
+where
+    T: AsyncRead + Unpin,
+{
+    match tag {
+        1 => read_exact(bytes, raw, 1).await.map(|_| ()),
+        2 => read_exact(bytes, raw, 2).await.map(|_| ()),
+        3 | 5 => read_exact(bytes, raw, 4).await.map(|_| ()),
+        4 | 6 => read_exact(bytes, raw, 8).await.map(|_| ()),
+        TAG_BYTE_ARRAY | TAG_INT_ARRAY | TAG_LONG_ARRAY => {
+            let len = read_length(bytes, raw).await?;
+            let width = match tag {
+                TAG_BYTE_ARRAY => 1,
+                TAG_INT_ARRAY => 4,
+                _ => 8,
+            };
+            read_exact(bytes, raw, len * width).await.map(|_| ())
+        }
+        TAG_STRING => read_name(bytes, raw).await,
+        TAG_LIST => {
+            let tag = read_exact(bytes, raw, 1).await?[0];
+            let remaining = read_length(bytes, raw).await? as i32;
+            stack.push(Nesting::List { tag, remaining });
+            Ok(())
+        }
+        TAG_COMPOUND => {
+            stack.push(Nesting::Compound);
+            Ok(())
+        }
+        _ => Err(Error::InvalidNbt(format!("Unknown tag type {}", tag))),
+    }
+}
+
+/// Reads a tag name or string: a u16 length, then that many bytes.
+async fn read_name<T>(bytes: &mut T, raw: &mut Vec<u8>) -> Result<(), Error>
+where
+    T: AsyncRead + Unpin,
+{
+    let len = read_exact(bytes, raw, 2).await?;
+    let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+    read_exact(bytes, raw, len).await.map(|_| ())
+}
+
+/// Reads the i32 length of an array or list.
+async fn read_length<T>(bytes: &mut T, raw: &mut Vec<u8>) -> Result<usize, Error>
+where
+    T: AsyncRead + Unpin,
+{
+    let len = read_exact(bytes, raw, 4).await?;
+    let len = i32::from_be_bytes([len[0], len[1], len[2], len[3]]);
+    usize::try_from(len).map_err(|_| Error::InvalidNbt(format!("Negative length {}", len)))
+}
+
+/// Appends exactly `len` bytes to `raw`, returning them.
+///
+/// The bytes are pulled through `take`, so a bogus length fails at the end of the packet instead
+/// of allocating it up front.
+async fn read_exact<'a, T>(
+    bytes: &mut T,
+    raw: &'a mut Vec<u8>,
+    len: usize,
+) -> Result<&'a [u8], Error>
+where
+    T: AsyncRead + Unpin,
+{
+    let start = raw.len();
+    let read = bytes.take(len as u64).read_to_end(raw).await?;
+    if read != len {
+        return Err(Error::InvalidNbt("Item NBT ended early".to_string()));
+    }
+    Ok(&raw[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slot_nbt_round_trips() {
+        // Present, item id 1, count 64, then {display: {Name: "a"}, list: [1, 2]}
+        let mut data = vec![0x01, 0x01, 0x40];
+        let nbt = [
+            TAG_COMPOUND,
+            0,
+            0, //
+            TAG_COMPOUND,
+            0,
+            7,
+            b'd',
+            b'i',
+            b's',
+            b'p',
+            b'l',
+            b'a',
+            b'y', //
+            TAG_STRING,
+            0,
+            4,
+            b'N',
+            b'a',
+            b'm',
+            b'e',
+            0,
+            1,
+            b'a',    //
+            TAG_END, //
+            TAG_LIST,
+            0,
+            4,
+            b'l',
+            b'i',
+            b's',
+            b't',
+            3,
+            0,
+            0,
+            0,
+            2,
+            0,
+            0,
+            0,
+            1,
+            0,
+            0,
+            0,
+            2, //
+            TAG_END,
+        ];
+        data.extend_from_slice(&nbt);
+        // Whatever comes after the slot
+        data.push(0xAB);
+
+        let mut cursor = Cursor::new(data.clone());
+        let slot = Slot::net_decode(&mut cursor).await.unwrap();
+        let item = slot.item.as_ref().unwrap();
+        assert_eq!((item.item_id, item.count), (1, 64));
+        assert_eq!(item.nbt.as_deref(), Some(&nbt[..]));
+        assert_eq!(cursor.position() as usize, data.len() - 1);
+
+        let mut encoded = Vec::new();
+        slot.net_encode(&mut encoded, &EncodeOption::Default)
+            .await
+            .unwrap();
+        assert_eq!(encoded, data[..data.len() - 1]);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_nbt_is_an_error() {
+        let data = vec![0x01, 0x01, 0x01, TAG_COMPOUND, 0, 0, TAG_STRING, 0, 1];
+        assert!(Slot::net_decode(&mut Cursor::new(data)).await.is_err());
+    }
+}
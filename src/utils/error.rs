@@ -47,6 +47,8 @@ pub enum Error {
     InvalidChunk(i32, i32, String),
     #[error("Chunk already exists at ({0}, {1})")]
     ChunkExists(i32, i32),
+    #[error("Chunk data ended after {0} of {1} sections")]
+    ChunkDataUnderrun(usize, usize),
 
     #[error(transparent)]
     SimdNbtError(#[from] simdnbt::Error),
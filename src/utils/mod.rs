@@ -1,9 +1,11 @@
+use crate::utils::config::configured_log_level;
 use crate::utils::constants::DEFAULT_LOG_LEVEL;
 use crate::utils::prelude::*;
 use tracing_subscriber::filter::Directive;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
+pub mod bans;
 pub mod binary_utils;
 pub mod components;
 pub mod config;
@@ -12,13 +14,17 @@ pub mod encoding;
 pub mod error;
 pub mod hash;
 pub mod impls;
+pub mod permissions;
 pub mod prelude;
+pub mod text;
+pub mod whitelist;
 
 /// Sets up the logger. Needs to be run before anything else in order for logging to run end.
 pub fn setup_logger() -> Result<()> {
     let trace_level = std::env::args()
         .find(|arg| arg.starts_with("--log="))
-        .map(|arg| arg.replace("--log=", ""));
+        .map(|arg| arg.replace("--log=", ""))
+        .or_else(configured_log_level);
 
     let mut trace_level: &str = trace_level.as_deref().unwrap_or("");
     if trace_level.is_empty() {
@@ -0,0 +1,266 @@
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::state::GlobalState;
+use crate::utils::bans::{load_json_list, save_json_list};
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::player::Player;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::block_registry::block_registry;
+use crate::world::reach::in_reach;
+
+/// The highest permission level, which the console and the operators in the config have.
+pub const MAX_PERMISSION_LEVEL: u8 = 4;
+/// The level operator-only commands and features need, like vanilla's game master commands.
+pub const OPERATOR_LEVEL: u8 = 2;
+
+/// An operator, in the same format vanilla uses for `ops.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpEntry {
+    /// Nil for players that were opped by name while they weren't online.
+    pub uuid: Uuid,
+    pub name: String,
+    pub level: u8,
+    #[serde(rename = "bypassesPlayerLimit", default)]
+    pub bypasses_player_limit: bool,
+}
+
+impl OpEntry {
+    /// Entries with a UUID only match that player, since names can change hands. The name is
+    /// only used for players opped before their UUID was known.
+    fn matches(&self, uuid: Uuid, username: &str) -> bool {
+        if self.uuid.is_nil() {
+            self.name.eq_ignore_ascii_case(username)
+        } else {
+            self.uuid == uuid
+        }
+    }
+}
+
+/// The operators in `ops.json`, along with their permission levels.
+#[derive(Debug, Default)]
+pub struct OpList {
+    /// Where the list is saved whenever it changes. `None` if it isn't kept in a file.
+    file: Option<PathBuf>,
+    entries: RwLock<Vec<OpEntry>>,
+}
+
+impl OpList {
+    /// Loads the ops file. A missing file just means nobody is opped yet.
+    pub fn load(file: &str) -> Result<Self> {
+        let mut ops = Self::new(load_json_list(file)?);
+        ops.file = Some(PathBuf::from(file));
+        info!("Loaded {} operators from {}", ops.entries().len(), file);
+        Ok(ops)
+    }
+
+    pub fn new(entries: Vec<OpEntry>) -> Self {
+        Self {
+            file: None,
+            entries: RwLock::new(entries),
+        }
+    }
+
+    pub fn entries(&self) -> Vec<OpEntry> {
+        self.entries.read().unwrap().clone()
+    }
+
+    /// The permission level a player has from the list, 0 if they aren't on it.
+    pub fn level(&self, uuid: Uuid, username: &str) -> u8 {
+        self.entries
+            .read()
+            .unwrap()
+            .iter()
+            .find(|entry| entry.matches(uuid, username))
+            .map_or(0, |entry| entry.level)
+    }
+
+    /// Makes a player an operator and saves the list. Returns false if they already were one at
+    /// that level.
+    pub fn op(&self, entry: OpEntry) -> Result<bool> {
+        let mut entries = self.entries.write().unwrap();
+        if entries.contains(&entry) {
+            return Ok(false);
+        }
+        entries.retain(|existing| !existing.matches(entry.uuid, &entry.name));
+        entries.push(entry);
+        self.save(&entries)?;
+        Ok(true)
+    }
+
+    /// Takes a player off the list by name and saves it. Returns the entry that was removed, if
+    /// there was one.
+    pub fn deop(&self, name: &str) -> Result<Option<OpEntry>> {
+        let mut entries = self.entries.write().unwrap();
+        let Some(index) = entries
+            .iter()
+            .position(|entry| entry.name.eq_ignore_ascii_case(name))
+        else {
+            return Ok(None);
+        };
+        let removed = entries.remove(index);
+        self.save(&entries)?;
+        Ok(Some(removed))
+    }
+
+    fn save(&self, entries: &[OpEntry]) -> Result<()> {
+        match &self.file {
+            Some(file) => save_json_list(file, entries),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The permission level of a player, from `ops.json` or the `operators` in the config.
+pub fn permission_level_of(state: &GlobalState, uuid: Uuid, username: &str) -> u8 {
+    if is_operator_name(username, &state.config.operators) {
+        return MAX_PERMISSION_LEVEL;
+    }
+    state.operators.level(uuid, username)
+}
+
+/// The permission level of the player behind an entity. Entities that aren't players have none.
+pub async fn permission_level(state: &GlobalState, entity_id: usize) -> u8 {
+    let Ok(player) = state.world.get_component::<Player>(entity_id).await else {
+        return 0;
+    };
+    permission_level_of(state, Uuid::from_u128(player.get_uuid()), &player.username)
+}
+
+/// Check if the player behind an entity is an operator, i.e. has at least [OPERATOR_LEVEL].
+///
+/// Entities that aren't players are never operators.
+pub async fn is_operator(state: &GlobalState, entity_id: usize) -> bool {
+    permission_level(state, entity_id).await >= OPERATOR_LEVEL
+}
+
+/// Whether a player may program the operator-only block at `location`, like a command block.
+/// Like in vanilla, only operators in creative mode can, and only within reach. The block also
+/// has to be one of `blocks`, so the edit can't leave a block entity where there's no such block.
+///
+/// Refused edits are logged, with `what` naming the block.
+pub async fn can_program_block(
+    state: &GlobalState,
+    entity_id: usize,
+    location: &Position,
+    what: &str,
+    blocks: &[&str],
+) -> Result<bool> {
+    let creative = state
+        .world
+        .get_component::<GameMode>(entity_id)
+        .await
+        .is_ok_and(|game_mode| *game_mode == GameMode::Creative);
+    let refused = if !is_operator(state, entity_id).await {
+        "without being an operator"
+    } else if !creative {
+        "outside of creative mode"
+    } else if !in_reach(state, entity_id, location).await? {
+        "out of reach"
+    } else {
+        let dimension = state.dimension_of(entity_id).await;
+        let block = state.block_at(dimension, location).await?;
+        let name = block_registry()
+            .state(block)
+            .map(|block| block.name.as_str());
+        if name.is_some_and(|name| blocks.contains(&name)) {
+            return Ok(true);
+        }
+        "where there isn't one"
+    };
+
+    warn!(
+        "Connection {} tried to edit the {} at {} {}",
+        entity_id, what, location, refused
+    );
+    Ok(false)
+}
+
+/// Usernames are case-insensitive, so the check is too.
+pub fn is_operator_name(username: &str, operators: &[String]) -> bool {
+    operators.iter().any(|op| op.eq_ignore_ascii_case(username))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operator_names_ignore_case() {
+        let operators = vec!["Notch".to_string()];
+        assert!(is_operator_name("notch", &operators));
+        assert!(!is_operator_name("jeb_", &operators));
+    }
+
+    #[test]
+    fn test_op_levels_are_saved() {
+        let file = std::env::temp_dir().join(format!("ferrumc-ops-{}.json", Uuid::new_v4()));
+        let file = file.to_string_lossy().to_string();
+        let ops = OpList::load(&file).unwrap();
+        let notch = Uuid::new_v4();
+
+        let entry = OpEntry {
+            uuid: notch,
+            name: "Notch".to_string(),
+            level: 4,
+            bypasses_player_limit: false,
+        };
+        assert!(ops.op(entry.clone()).unwrap());
+        assert!(!ops.op(entry.clone()).unwrap());
+        // Opping again at another level replaces the entry
+        assert!(ops.op(OpEntry { level: 2, ..entry }).unwrap());
+
+        let reloaded = OpList::load(&file).unwrap();
+        assert_eq!(reloaded.level(notch, "Notch"), 2);
+        // Someone else who has taken the name since isn't an operator
+        assert_eq!(reloaded.level(Uuid::new_v4(), "notch"), 0);
+        assert_eq!(reloaded.level(Uuid::new_v4(), "jeb_"), 0);
+
+        // Players opped by name match whoever has it
+        let jeb = OpEntry {
+            uuid: Uuid::nil(),
+            name: "jeb_".to_string(),
+            level: 3,
+            bypasses_player_limit: false,
+        };
+        assert!(reloaded.op(jeb).unwrap());
+        assert_eq!(reloaded.level(Uuid::new_v4(), "Jeb_"), 3);
+        assert!(reloaded.deop("jeb_").unwrap().is_some());
+
+        assert_eq!(reloaded.deop("NOTCH").unwrap().unwrap().uuid, notch);
+        assert!(reloaded.deop("Notch").unwrap().is_none());
+        assert_eq!(OpList::load(&file).unwrap().level(notch, "Notch"), 0);
+
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_players_cant_program_blocks() {
+        let state = crate::create_state(tokio::net::TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let (player, _client) = crate::tests::connections::add_play_connection(&state).await;
+        state
+            .world
+            .get_component_storage()
+            .insert(player, Player::new(1, "Steve".to_string()))
+            .insert(player, GameMode::Creative)
+            .insert(player, Position::new(0, 64, 0));
+
+        let allowed = can_program_block(
+            &state,
+            player,
+            &Position::new(1, 64, 0),
+            "command block",
+            &["minecraft:command_block"],
+        )
+        .await
+        .unwrap();
+        assert!(!allowed);
+    }
+}
@@ -0,0 +1,521 @@
+//! Text components, the JSON the client renders chat messages, kick reasons and the like from.
+
+use std::collections::HashMap;
+
+use ferrumc_codec::enc::{EncodeOption, NetEncode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncWrite};
+use uuid::Uuid;
+
+use crate::utils::impls::packet_impls::NetDecode;
+use crate::utils::prelude::*;
+
+/// A piece of formatted text, along with the pieces that follow it in `extra`, which inherit its
+/// formatting.
+///
+/// Sent as a JSON string over the network, see [TextComponent::to_json], and stored as a compound
+/// in NBT, see [TextComponent::to_nbt].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct TextComponent {
+    /// Literal text. Takes precedence over `translate` if both are set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// A key the client looks up in its language file, like `multiplayer.disconnect.kicked`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translate: Option<String>,
+    /// What the translation's `%s` placeholders are filled in with.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub with: Vec<TextComponent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underlined: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obfuscated: Option<bool>,
+    /// Put into the chat box when the text is shift-clicked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insertion: Option<String>,
+    #[serde(
+        rename = "clickEvent",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub click_event: Option<ClickEvent>,
+    #[serde(
+        rename = "hoverEvent",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hover_event: Option<HoverEvent>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<TextComponent>,
+}
+
+/// What happens when a player clicks the text.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClickEvent {
+    pub action: ClickAction,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickAction {
+    OpenUrl,
+    /// Runs the value as if the player typed it, so it needs the leading slash for commands.
+    RunCommand,
+    /// Puts the value into the chat box for the player to send.
+    SuggestCommand,
+    /// Turns to the page in the value, only in books.
+    ChangePage,
+    CopyToClipboard,
+}
+
+/// What's shown when a player hovers over the text.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "action", content = "contents", rename_all = "snake_case")]
+pub enum HoverEvent {
+    ShowText(Box<TextComponent>),
+    ShowItem(HoverItem),
+    ShowEntity(HoverEntity),
+}
+
+/// An item's tooltip.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HoverItem {
+    /// The item's id, like `minecraft:diamond_sword`.
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<i32>,
+    /// The item's NBT, as SNBT.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+/// An entity's name, type and UUID.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HoverEntity {
+    /// The entity type, like `minecraft:pig`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<Box<TextComponent>>,
+}
+
+impl TextComponent {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Text the client translates into the player's language. Fill in the placeholders with
+    /// [TextComponent::arg].
+    pub fn translate(key: impl Into<String>) -> Self {
+        Self {
+            translate: Some(key.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Fills in the next `%s` placeholder of the translation.
+    pub fn arg(mut self, arg: impl Into<TextComponent>) -> Self {
+        self.with.push(arg.into());
+        self
+    }
+
+    /// Sets the color, either a named one like `yellow` or a hex one like `#FF8800`.
+    pub fn color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    pub fn bold(mut self, bold: bool) -> Self {
+        self.bold = Some(bold);
+        self
+    }
+
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.italic = Some(italic);
+        self
+    }
+
+    pub fn underlined(mut self, underlined: bool) -> Self {
+        self.underlined = Some(underlined);
+        self
+    }
+
+    pub fn strikethrough(mut self, strikethrough: bool) -> Self {
+        self.strikethrough = Some(strikethrough);
+        self
+    }
+
+    pub fn obfuscated(mut self, obfuscated: bool) -> Self {
+        self.obfuscated = Some(obfuscated);
+        self
+    }
+
+    pub fn insertion(mut self, insertion: impl Into<String>) -> Self {
+        self.insertion = Some(insertion.into());
+        self
+    }
+
+    pub fn on_click(mut self, action: ClickAction, value: impl Into<String>) -> Self {
+        self.click_event = Some(ClickEvent {
+            action,
+            value: value.into(),
+        });
+        self
+    }
+
+    pub fn on_hover(mut self, event: HoverEvent) -> Self {
+        self.hover_event = Some(event);
+        self
+    }
+
+    /// Shows another piece of text when the player hovers over this one.
+    pub fn hover_text(self, text: impl Into<TextComponent>) -> Self {
+        self.on_hover(HoverEvent::ShowText(Box::new(text.into())))
+    }
+
+    /// Appends a piece of text after this one.
+    pub fn append(mut self, extra: impl Into<TextComponent>) -> Self {
+        self.extra.push(extra.into());
+        self
+    }
+
+    /// Just the text, without any formatting, e.g. for logging. Translations show up as their
+    /// key, since the server doesn't know what they translate to.
+    pub fn plain_text(&self) -> String {
+        let mut text = self
+            .text
+            .clone()
+            .or_else(|| self.translate.clone())
+            .unwrap_or_default();
+        for extra in &self.extra {
+            text.push_str(&extra.plain_text());
+        }
+        text
+    }
+
+    /// The component as the JSON string it's sent to the client as.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Text components always serialize")
+    }
+
+    /// Reads a component from JSON. Besides objects, plain strings and arrays are accepted
+    /// anywhere a component is, like vanilla does, with an array's first element being the parent
+    /// of the rest.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value = serde_json::from_str(json)
+            .map_err(|e| Error::DeserializationError(format!("Invalid text component: {}", e)))?;
+        serde_json::from_value(normalize(value))
+            .map_err(|e| Error::DeserializationError(format!("Invalid text component: {}", e)))
+    }
+
+    /// The component as an NBT compound, like it's stored in signs and books. It's laid out like
+    /// the JSON, see [json_to_nbt].
+    pub fn to_nbt(&self) -> Result<Vec<u8>> {
+        let json =
+            serde_json::to_value(self).map_err(|e| Error::SerializationError(e.to_string()))?;
+        fastnbt::to_bytes(&json_to_nbt(json)?).map_err(|e| Error::SerializationError(e.to_string()))
+    }
+
+    pub fn from_nbt(nbt: &[u8]) -> Result<Self> {
+        let nbt =
+            fastnbt::from_bytes(nbt).map_err(|e| Error::DeserializationError(e.to_string()))?;
+        serde_json::from_value(normalize(nbt_to_json(nbt)))
+            .map_err(|e| Error::DeserializationError(format!("Invalid text component: {}", e)))
+    }
+}
+
+/// Converts a component's JSON to NBT. Booleans are stored as bytes, and the UUID of an entity
+/// shown on hover as four ints, like vanilla does.
+fn json_to_nbt(json: Value) -> Result<fastnbt::Value> {
+    Ok(match json {
+        Value::Null => {
+            return Err(Error::SerializationError(
+                "NBT has no null values".to_string(),
+            ))
+        }
+        Value::Bool(value) => fastnbt::Value::Byte(value as i8),
+        Value::Number(number) => match number.as_i64() {
+            Some(number) => {
+                i32::try_from(number).map_or(fastnbt::Value::Long(number), fastnbt::Value::Int)
+            }
+            None => fastnbt::Value::Double(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(string) => fastnbt::Value::String(string),
+        Value::Array(values) => fastnbt::Value::List(
+            values
+                .into_iter()
+                .map(json_to_nbt)
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        Value::Object(object) => {
+            let shows_entity = object.get("action").and_then(Value::as_str) == Some("show_entity");
+            let mut compound = HashMap::new();
+            for (key, value) in object {
+                let mut value = json_to_nbt(value)?;
+                if let fastnbt::Value::Compound(contents) = &mut value {
+                    if let Some(fastnbt::Value::String(id)) = contents
+                        .get("id")
+                        .filter(|_| shows_entity && key == "contents")
+                    {
+                        let uuid = Uuid::parse_str(id)
+                            .map_err(|e| Error::SerializationError(e.to_string()))?
+                            .as_u128();
+                        let ints = [uuid >> 96, uuid >> 64, uuid >> 32, uuid]
+                            .map(|int| int as u32 as i32)
+                            .to_vec();
+                        contents.insert(
+                            "id".to_string(),
+                            fastnbt::Value::IntArray(fastnbt::IntArray::new(ints)),
+                        );
+                    }
+                }
+                compound.insert(key, value);
+            }
+            fastnbt::Value::Compound(compound)
+        }
+    })
+}
+
+/// The other way around from [json_to_nbt]. Bytes are taken to be booleans, since components
+/// have no other use for them.
+fn nbt_to_json(nbt: fastnbt::Value) -> Value {
+    match nbt {
+        fastnbt::Value::Byte(value) => Value::Bool(value != 0),
+        fastnbt::Value::Short(value) => json!(value),
+        fastnbt::Value::Int(value) => json!(value),
+        fastnbt::Value::Long(value) => json!(value),
+        fastnbt::Value::Float(value) => json!(value),
+        fastnbt::Value::Double(value) => json!(value),
+        fastnbt::Value::String(string) => Value::String(string),
+        fastnbt::Value::IntArray(ints) if ints.len() == 4 => {
+            let uuid = ints
+                .iter()
+                .fold(0u128, |uuid, int| (uuid << 32) | *int as u32 as u128);
+            Value::String(Uuid::from_u128(uuid).to_string())
+        }
+        fastnbt::Value::ByteArray(bytes) => json!(bytes.to_vec()),
+        fastnbt::Value::IntArray(ints) => json!(ints.to_vec()),
+        fastnbt::Value::LongArray(longs) => json!(longs.to_vec()),
+        fastnbt::Value::List(values) => Value::Array(values.into_iter().map(nbt_to_json).collect()),
+        fastnbt::Value::Compound(compound) => Value::Object(
+            compound
+                .into_iter()
+                .map(|(key, value)| (key, nbt_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Turns the shorthand forms of components into objects, so they deserialize as one.
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::String(text) => json!({ "text": text }),
+        Value::Array(parts) => {
+            let mut parts = parts.into_iter().map(normalize);
+            let Some(mut parent) = parts.next() else {
+                return json!({ "text": "" });
+            };
+            if let Some(Value::Array(extra)) = parent
+                .as_object_mut()
+                .map(|parent| parent.entry("extra").or_insert_with(|| json!([])))
+            {
+                extra.extend(parts);
+            }
+            parent
+        }
+        Value::Object(mut object) => {
+            for key in ["with", "extra"] {
+                if let Some(Value::Array(children)) = object.get_mut(key) {
+                    let normalized = children.drain(..).map(normalize).collect();
+                    *children = normalized;
+                }
+            }
+            if let Some(Value::Object(hover)) = object.get_mut("hoverEvent") {
+                if hover.get("action").and_then(Value::as_str) == Some("show_text") {
+                    if let Some(contents) = hover.remove("contents") {
+                        hover.insert("contents".to_string(), normalize(contents));
+                    }
+                }
+            }
+            Value::Object(object)
+        }
+        // Numbers and booleans show up as they're written
+        other => json!({ "text": other.to_string() }),
+    }
+}
+
+impl From<&str> for TextComponent {
+    fn from(text: &str) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<String> for TextComponent {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+impl NetEncode for TextComponent {
+    async fn net_encode<W>(
+        &self,
+        writer: &mut W,
+        encode_option: &EncodeOption,
+    ) -> ferrumc_codec::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.to_json().net_encode(writer, encode_option).await
+    }
+}
+
+impl NetDecode for TextComponent {
+    async fn net_decode<T>(bytes: &mut T) -> Result<Box<Self>>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let json = String::net_decode(bytes).await?;
+        Ok(Box::new(Self::from_json(&json)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_json() {
+        let component = TextComponent::text("Hello ")
+            .color("yellow")
+            .append("world");
+        assert_eq!(
+            component.to_json(),
+            r#"{"text":"Hello ","color":"yellow","extra":[{"text":"world"}]}"#
+        );
+        assert_eq!(
+            TextComponent::text("plain").to_json(),
+            r#"{"text":"plain"}"#
+        );
+        assert_eq!(component.plain_text(), "Hello world");
+    }
+
+    #[test]
+    fn test_styles_and_events() {
+        let component = TextComponent::translate("chat.type.text")
+            .arg("Notch")
+            .arg(TextComponent::text("hi").bold(true))
+            .on_click(ClickAction::SuggestCommand, "/msg Notch ")
+            .hover_text("Click to reply");
+        assert_eq!(
+            component.to_json(),
+            r#"{"translate":"chat.type.text","with":[{"text":"Notch"},{"text":"hi","bold":true}],"#
+                .to_string()
+                + r#""clickEvent":{"action":"suggest_command","value":"/msg Notch "},"#
+                + r#""hoverEvent":{"action":"show_text","contents":{"text":"Click to reply"}}}"#
+        );
+        assert_eq!(component.plain_text(), "chat.type.text");
+        assert_eq!(
+            TextComponent::from_json(&component.to_json()).unwrap(),
+            component
+        );
+    }
+
+    #[test]
+    fn test_shorthand_json() {
+        assert_eq!(
+            TextComponent::from_json(r#""plain""#).unwrap(),
+            TextComponent::text("plain")
+        );
+        // The first element is the parent of the rest
+        assert_eq!(
+            TextComponent::from_json(r#"[{"text":"a","color":"red"},"b",["c"]]"#).unwrap(),
+            TextComponent::text("a")
+                .color("red")
+                .append("b")
+                .append("c")
+        );
+        assert_eq!(
+            TextComponent::from_json(
+                r#"{"text":"","extra":["x"],"hoverEvent":{"action":"show_text","contents":"y"}}"#
+            )
+            .unwrap(),
+            TextComponent::text("").append("x").hover_text("y")
+        );
+        assert!(TextComponent::from_json("{").is_err());
+    }
+
+    #[test]
+    fn test_nbt_round_trip() {
+        let component = TextComponent::text("Sign")
+            .italic(false)
+            .append(TextComponent::translate("block.minecraft.oak_sign").underlined(true));
+        let nbt = component.to_nbt().unwrap();
+        assert_eq!(TextComponent::from_nbt(&nbt).unwrap(), component);
+    }
+
+    #[test]
+    fn test_nbt_entity_ids_are_int_arrays() {
+        let id = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+        let component = TextComponent {
+            hover_event: Some(HoverEvent::ShowEntity(HoverEntity {
+                kind: "minecraft:player".to_string(),
+                id,
+                name: Some(Box::new(TextComponent::text("Notch"))),
+            })),
+            ..TextComponent::text("Notch")
+        };
+        let nbt = component.to_nbt().unwrap();
+
+        let value: fastnbt::Value = fastnbt::from_bytes(&nbt).unwrap();
+        let fastnbt::Value::Compound(root) = value else {
+            panic!("Not a compound: {:?}", value);
+        };
+        let Some(fastnbt::Value::Compound(hover)) = root.get("hoverEvent") else {
+            panic!("No hover event in {:?}", root);
+        };
+        let Some(fastnbt::Value::Compound(contents)) = hover.get("contents") else {
+            panic!("No contents in {:?}", hover);
+        };
+        let Some(fastnbt::Value::IntArray(ints)) = contents.get("id") else {
+            panic!("No int array id in {:?}", contents);
+        };
+        assert_eq!(
+            ints.to_vec(),
+            vec![0x069a79f4, 0x44e94726, 0xa5befca9_u32 as i32, 0x0e38aaf5]
+        );
+
+        assert_eq!(TextComponent::from_nbt(&nbt).unwrap(), component);
+    }
+
+    #[tokio::test]
+    async fn test_network_round_trip() {
+        let component = TextComponent::text("Kicked").color("red");
+        let mut bytes = Vec::new();
+        component
+            .net_encode(&mut bytes, &EncodeOption::Default)
+            .await
+            .unwrap();
+        assert_eq!(bytes[0] as usize, component.to_json().len());
+
+        let decoded = TextComponent::net_decode(&mut Cursor::new(bytes))
+            .await
+            .unwrap();
+        assert_eq!(*decoded, component);
+    }
+}
@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::utils::config::WhitelistConfig;
+use crate::utils::prelude::*;
+
+/// A single player on the whitelist, in the same format vanilla uses for `whitelist.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    /// Nil for players that were added by name while they weren't online.
+    pub uuid: Uuid,
+    pub name: String,
+}
+
+/// The players allowed to join while the whitelist is enabled.
+#[derive(Debug, Default)]
+pub struct Whitelist {
+    enabled: bool,
+    /// Where the whitelist is saved whenever it changes. `None` if it isn't kept in a file.
+    file: Option<PathBuf>,
+    entries: RwLock<Vec<WhitelistEntry>>,
+}
+
+impl Whitelist {
+    /// Loads the whitelist file named in the config.
+    ///
+    /// A missing file is treated as an empty whitelist, so turning it on without a file locks
+    /// everyone out instead of letting everyone in.
+    pub fn load(config: &WhitelistConfig) -> Result<Self> {
+        let path = Path::new(&config.file);
+        if !path.exists() && config.enabled {
+            warn!(
+                "Whitelist is enabled but {} doesn't exist, nobody will be able to join",
+                config.file
+            );
+        }
+
+        let mut whitelist = Self::new(config.enabled, read_file(path)?);
+        whitelist.file = Some(path.to_path_buf());
+        info!(
+            "Loaded {} whitelisted players from {}",
+            whitelist.entries().len(),
+            config.file
+        );
+        Ok(whitelist)
+    }
+
+    pub fn new(enabled: bool, entries: Vec<WhitelistEntry>) -> Self {
+        Self {
+            enabled,
+            file: None,
+            entries: RwLock::new(entries),
+        }
+    }
+
+    pub fn from_json(enabled: bool, json: &str) -> Result<Self> {
+        Ok(Self::new(enabled, parse(json)?))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn entries(&self) -> Vec<WhitelistEntry> {
+        self.entries.read().unwrap().clone()
+    }
+
+    /// Reads the whitelist file again, e.g. after it was edited by hand. Returns how many players
+    /// are on it now.
+    pub fn reload(&self) -> Result<usize> {
+        let Some(file) = &self.file else {
+            return Ok(self.entries.read().unwrap().len());
+        };
+        let entries = read_file(file)?;
+        let count = entries.len();
+        *self.entries.write().unwrap() = entries;
+        Ok(count)
+    }
+
+    /// Adds a player and saves the whitelist. Returns false if they were already on it.
+    pub fn add(&self, entry: WhitelistEntry) -> Result<bool> {
+        let mut entries = self.entries.write().unwrap();
+        if entries
+            .iter()
+            .any(|existing| existing.matches(entry.uuid, &entry.name))
+        {
+            return Ok(false);
+        }
+        entries.push(entry);
+        self.save(&entries)?;
+        Ok(true)
+    }
+
+    /// Takes a player off the whitelist by name and saves it. Returns the entry that was removed,
+    /// if there was one.
+    pub fn remove(&self, name: &str) -> Result<Option<WhitelistEntry>> {
+        let mut entries = self.entries.write().unwrap();
+        let Some(index) = entries
+            .iter()
+            .position(|entry| entry.name.eq_ignore_ascii_case(name))
+        else {
+            return Ok(None);
+        };
+        let removed = entries.remove(index);
+        self.save(&entries)?;
+        Ok(Some(removed))
+    }
+
+    fn save(&self, entries: &[WhitelistEntry]) -> Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(entries)
+            .map_err(|e| Error::Generic(format!("Failed to serialize the whitelist: {}", e)))?;
+        std::fs::write(file, json)?;
+        Ok(())
+    }
+
+    /// Whether a player may join. Always true while the whitelist is disabled.
+    ///
+    /// Players match on their UUID, or on their (case-insensitive) username for entries that
+    /// were added by name and have no UUID.
+    pub fn is_allowed(&self, uuid: Uuid, username: &str) -> bool {
+        !self.enabled
+            || self
+                .entries
+                .read()
+                .unwrap()
+                .iter()
+                .any(|entry| entry.matches(uuid, username))
+    }
+}
+
+impl WhitelistEntry {
+    /// Entries with a UUID only match that player, since names can change hands.
+    fn matches(&self, uuid: Uuid, username: &str) -> bool {
+        if self.uuid.is_nil() {
+            self.name.eq_ignore_ascii_case(username)
+        } else {
+            self.uuid == uuid
+        }
+    }
+}
+
+/// Reads a whitelist file. A missing file is an empty whitelist.
+fn read_file(path: &Path) -> Result<Vec<WhitelistEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    parse(&std::fs::read_to_string(path)?)
+}
+
+fn parse(json: &str) -> Result<Vec<WhitelistEntry>> {
+    serde_json::from_str(json)
+        .map_err(|e| Error::DeserializationError(format!("Invalid whitelist: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITELIST: &str = r#"[
+        {"uuid": "069a79f4-44e9-4726-a5be-fca90e38aaf5", "name": "Notch"}
+    ]"#;
+
+    #[test]
+    fn test_whitelist_enforced_when_enabled() {
+        let whitelist = Whitelist::from_json(true, WHITELIST).unwrap();
+        let notch = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+
+        assert!(whitelist.is_allowed(notch, "Notch"));
+        // Someone else who has taken the name since isn't let in
+        assert!(!whitelist.is_allowed(Uuid::new_v4(), "Notch"));
+        assert!(!whitelist.is_allowed(Uuid::new_v4(), "jeb_"));
+    }
+
+    #[test]
+    fn test_disabled_whitelist_allows_everyone() {
+        let whitelist = Whitelist::from_json(false, WHITELIST).unwrap();
+        assert!(whitelist.is_allowed(Uuid::new_v4(), "jeb_"));
+    }
+
+    #[test]
+    fn test_changes_are_saved() {
+        let file = std::env::temp_dir().join(format!("ferrumc-whitelist-{}.json", Uuid::new_v4()));
+        std::fs::write(&file, WHITELIST).unwrap();
+        let config = WhitelistConfig {
+            enabled: true,
+            file: file.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let whitelist = Whitelist::load(&config).unwrap();
+
+        let jeb = WhitelistEntry {
+            uuid: Uuid::nil(),
+            name: "jeb_".to_string(),
+        };
+        assert!(whitelist.add(jeb.clone()).unwrap());
+        assert!(!whitelist.add(jeb).unwrap());
+        assert!(whitelist.is_allowed(Uuid::new_v4(), "jeb_"));
+        // Players added by name don't let in everyone without a UUID
+        assert!(!whitelist.is_allowed(Uuid::nil(), "Dinnerbone"));
+
+        let removed = whitelist.remove("NOTCH").unwrap().unwrap();
+        assert_eq!(removed.name, "Notch");
+        assert_eq!(whitelist.remove("Notch").unwrap(), None);
+
+        let reloaded = Whitelist::load(&config).unwrap();
+        let names = reloaded
+            .entries()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["jeb_"]);
+
+        std::fs::write(&file, "[]").unwrap();
+        assert_eq!(whitelist.reload().unwrap(), 0);
+        assert!(!whitelist.is_allowed(Uuid::new_v4(), "jeb_"));
+
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_whitelist_is_an_error() {
+        assert!(Whitelist::from_json(true, r#"[{"name": "Notch"}]"#).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tracing::{debug, warn};
+
+use crate::state::{GlobalState, ServerState};
+use crate::utils::prelude::*;
+use crate::world::chunk_cache::ChunkKey;
+use crate::world::chunk_format::Chunk;
+
+/// The changed chunks an autosave still has to write, so a save can be spread over several ticks.
+#[derive(Debug, Default)]
+pub struct Autosave {
+    pending: Mutex<PendingChunks>,
+}
+
+#[derive(Debug, Default)]
+struct PendingChunks {
+    /// The order the chunks were queued in.
+    order: VecDeque<ChunkKey>,
+    chunks: HashMap<ChunkKey, Arc<Chunk>>,
+}
+
+impl Autosave {
+    /// Queues up chunks to be saved, skipping the ones that are already queued.
+    pub fn queue(&self, chunks: Vec<(ChunkKey, Arc<Chunk>)>) {
+        let mut pending = self.pending.lock().unwrap();
+        for (key, chunk) in chunks {
+            // The newer version replaces the one that was queued
+            if pending.chunks.insert(key, chunk).is_none() {
+                pending.order.push_back(key);
+            }
+        }
+    }
+
+    /// Takes up to `limit` chunks off the front of the queue.
+    pub fn take(&self, limit: usize) -> Vec<(ChunkKey, Arc<Chunk>)> {
+        let mut pending = self.pending.lock().unwrap();
+        let count = limit.min(pending.order.len());
+        let keys = pending.order.drain(..count).collect::<Vec<_>>();
+        keys.into_iter()
+            .filter_map(|key| pending.chunks.remove(&key).map(|chunk| (key, chunk)))
+            .collect()
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.lock().unwrap().order.len()
+    }
+
+    pub fn clear(&self) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.order.clear();
+        pending.chunks.clear();
+    }
+}
+
+impl ServerState {
+    /// Saves every player's data in the background, and queues up every changed chunk to be
+    /// saved by [ServerState::continue_autosave]. Returns how many chunks were queued.
+    pub fn start_autosave(self: &GlobalState) -> usize {
+        let state = self.clone();
+        tokio::spawn(async move { state.save_all_player_data().await });
+        let chunks = self.chunk_cache.dirty_chunks();
+        let count = chunks.len();
+        self.autosave.queue(chunks);
+        count
+    }
+
+    /// Saves up to `limit` of the chunks queued by [ServerState::start_autosave]. Chunks that
+    /// fail to save stay marked as changed, so the next autosave tries them again.
+    pub async fn continue_autosave(self: &GlobalState, limit: usize) {
+        for (key, chunk) in self.autosave.take(limit) {
+            match self.save_chunk(&chunk).await {
+                Ok(()) => self.chunk_cache.mark_saved(key, &chunk).await,
+                Err(e) => warn!("Failed to save chunk {:?}: {}", key, e),
+            }
+        }
+        if self.autosave.pending() == 0 {
+            debug!("Autosave finished");
+        }
+    }
+
+    /// Saves chunks off the game loop, so a slow disk doesn't hold up the tick.
+    ///
+    /// Only one save runs in the background at a time, so chunks are written in the order they
+    /// were handed over and an older version can't end up on disk after a newer one. Returns
+    /// false, without starting the save, if the last one is still going.
+    pub fn save_in_background(
+        self: &GlobalState,
+        save: impl Future<Output = ()> + Send + 'static,
+    ) -> bool {
+        let Ok(writing) = self.chunk_writes.clone().try_lock_owned() else {
+            return false;
+        };
+        tokio::spawn(async move {
+            save.await;
+            drop(writing);
+        });
+        true
+    }
+
+    /// Waits for the save running in the background to finish, if there is one.
+    pub async fn wait_for_background_save(&self) {
+        let _writing = self.chunk_writes.lock().await;
+    }
+
+    /// Saves every player and every changed chunk right away, e.g. for `/save-all`. Returns how
+    /// many chunks were saved.
+    pub async fn save_all(self: &GlobalState) -> Result<usize> {
+        // Everything queued is saved below anyway
+        self.autosave.clear();
+        self.save_all_player_data().await;
+        let count = self.chunk_cache.dirty_chunks().len();
+        self.save_all_chunks().await?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::dimension::Dimension;
+
+    #[test]
+    fn test_chunks_are_taken_in_batches() {
+        let autosave = Autosave::default();
+        let chunks = (0..5)
+            .map(|x| ((Dimension::Overworld, x, 0), Arc::new(Chunk::empty(x, 0))))
+            .collect::<Vec<_>>();
+        autosave.queue(chunks);
+
+        let first = autosave.take(2);
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].0, (Dimension::Overworld, 0, 0));
+        assert_eq!(autosave.pending(), 3);
+
+        // Queueing a chunk again replaces it in place instead of saving it twice
+        let newer = Arc::new(Chunk::empty(3, 0));
+        autosave.queue(vec![((Dimension::Overworld, 3, 0), newer.clone())]);
+        assert_eq!(autosave.pending(), 3);
+
+        let rest = autosave.take(10);
+        assert_eq!(rest.len(), 3);
+        assert!(Arc::ptr_eq(&rest[1].1, &newer));
+        assert_eq!(autosave.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_one_background_save_at_a_time() {
+        let state = crate::create_state(tokio::net::TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let saved = Arc::new(Mutex::new(Vec::new()));
+        let first = saved.clone();
+        assert!(state.save_in_background(async move {
+            let _ = released.await;
+            first.lock().unwrap().push(1);
+        }));
+        // Still writing, so the next one has to wait its turn
+        assert!(!state.save_in_background(async {}));
+
+        release.send(()).unwrap();
+        state.wait_for_background_save().await;
+        assert_eq!(*saved.lock().unwrap(), vec![1]);
+
+        let second = saved.clone();
+        assert!(state.save_in_background(async move { second.lock().unwrap().push(2) }));
+        state.wait_for_background_save().await;
+        assert_eq!(*saved.lock().unwrap(), vec![1, 2]);
+    }
+}
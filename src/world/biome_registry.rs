@@ -0,0 +1,127 @@
+//! The mapping between biome names and the IDs they're sent to the client with.
+//!
+//! The IDs are read from the biome registry inside the registry codec that's sent in Login (play),
+//! so the biomes in chunk data always line up with what the client was told.
+
+use hashbrown::HashMap;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use crate::utils::prelude::*;
+
+/// The registry codec sent to clients when they join, as NBT.
+pub const REGISTRY_CODEC: &[u8] = include_bytes!("../../.etc/nbt_codec.nbt");
+
+/// Used for biomes the registry doesn't know, e.g. ones added by data packs.
+pub const DEFAULT_BIOME: &str = "minecraft:plains";
+
+#[derive(Deserialize)]
+struct RegistryCodec {
+    #[serde(rename = "minecraft:worldgen/biome")]
+    biomes: BiomeEntries,
+}
+
+#[derive(Deserialize)]
+struct BiomeEntries {
+    value: Vec<BiomeEntry>,
+}
+
+#[derive(Deserialize)]
+struct BiomeEntry {
+    name: String,
+    id: i64,
+}
+
+#[derive(Debug)]
+pub struct BiomeRegistry {
+    ids: HashMap<String, i32>,
+    names: HashMap<i32, String>,
+    default_id: i32,
+}
+
+impl BiomeRegistry {
+    /// Reads the biome registry out of a registry codec.
+    pub fn from_codec(nbt: &[u8]) -> Result<Self> {
+        let codec: RegistryCodec =
+            fastnbt::from_bytes(nbt).map_err(|e| Error::DeserializationError(e.to_string()))?;
+
+        let mut ids = HashMap::new();
+        let mut names = HashMap::new();
+        for biome in codec.biomes.value {
+            let id = i32::try_from(biome.id).map_err(|_| {
+                Error::DeserializationError(format!("Invalid biome id: {}", biome.id))
+            })?;
+            names.insert(id, biome.name.clone());
+            ids.insert(biome.name, id);
+        }
+        let default_id = *ids.get(DEFAULT_BIOME).ok_or_else(|| {
+            Error::DeserializationError(format!("{} is missing from the registry", DEFAULT_BIOME))
+        })?;
+
+        Ok(Self {
+            ids,
+            names,
+            default_id,
+        })
+    }
+
+    /// The network ID of a biome. The namespace defaults to `minecraft`.
+    pub fn id(&self, name: &str) -> Option<i32> {
+        match name.contains(':') {
+            true => self.ids.get(name).copied(),
+            false => self.ids.get(&format!("minecraft:{}", name)).copied(),
+        }
+    }
+
+    /// The network ID of a biome, or of [DEFAULT_BIOME] if it isn't in the registry.
+    pub fn id_or_default(&self, name: &str) -> i32 {
+        self.id(name).unwrap_or(self.default_id)
+    }
+
+    /// The name of the biome with the given network ID.
+    pub fn name(&self, id: i32) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+
+    /// The number of biomes. Biomes sent without a palette take enough bits for every one of
+    /// them.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+lazy_static! {
+    static ref BIOME_REGISTRY: BiomeRegistry =
+        BiomeRegistry::from_codec(REGISTRY_CODEC).expect("The bundled registry codec is invalid");
+}
+
+/// The biome registry matching the registry codec sent to clients.
+pub fn biome_registry() -> &'static BiomeRegistry {
+    &BIOME_REGISTRY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_match_the_codec() {
+        let registry = biome_registry();
+        assert_eq!(registry.id("minecraft:plains"), Some(39));
+        assert_eq!(registry.id("plains"), Some(39));
+        assert_eq!(registry.name(39), Some("minecraft:plains"));
+
+        // Every biome round-trips and the IDs are contiguous
+        for id in 0..registry.len() as i32 {
+            let name = registry.name(id).unwrap();
+            assert_eq!(registry.id(name), Some(id));
+        }
+
+        assert_eq!(registry.id("mypack:glowing_forest"), None);
+        assert_eq!(registry.id_or_default("mypack:glowing_forest"), 39);
+    }
+}
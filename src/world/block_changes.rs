@@ -0,0 +1,485 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use hashbrown::HashMap;
+use tracing::warn;
+
+use crate::net::packets::outgoing::acknowledge_block_change::AcknowledgeBlockChange;
+use crate::net::packets::outgoing::block_update::BlockUpdate;
+use crate::net::packets::outgoing::chunk_and_light_data::ChunkDataAndUpdateLight;
+use crate::net::packets::outgoing::update_section_blocks::UpdateSectionBlocks;
+use crate::net::ConnectionWrapper;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::loaded_chunks::LoadedChunks;
+use crate::utils::config::get_global_config;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::chunk_cache::ChunkKey;
+use crate::world::chunk_format::Chunk;
+use crate::world::dimension::Dimension;
+use crate::world::generation::block_index;
+use crate::world::heightmap::compute_heightmaps;
+use crate::world::lighting::relight_changes;
+use crate::world::palette::SECTION_VOLUME;
+
+/// A block that was changed to a new block state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockChange {
+    pub position: Position,
+    pub block_state: i32,
+}
+
+/// The block changes made since the last tick, which are sent out together at the end of it.
+///
+/// Changing lots of blocks in one section over a tick makes one multi block change instead of a
+/// block update each.
+///
+/// Block change sequences are acknowledged at the same time, after the changes, since clients
+/// put back the blocks they predicted a change to when the acknowledgement comes in before the
+/// server's version of them.
+/// The changes to a chunk.
+pub type ChunkChanges = (ChunkKey, Vec<BlockChange>);
+/// The changed blocks in a section: their position in it and their new block state.
+type SectionChanges = Vec<(u8, u8, u8, i32)>;
+
+#[derive(Default)]
+pub struct PendingBlockChanges {
+    inner: Mutex<PendingInner>,
+}
+
+#[derive(Default)]
+struct PendingInner {
+    chunks: BTreeMap<ChunkKey, Vec<BlockChange>>,
+    /// The highest sequence to acknowledge, per connection.
+    acknowledgements: HashMap<usize, i32>,
+}
+
+impl PendingBlockChanges {
+    /// Queues changes to a chunk, which has to be changed in the chunk cache already.
+    pub fn push(&self, chunk: ChunkKey, changes: Vec<BlockChange>) {
+        self.inner
+            .lock()
+            .unwrap()
+            .chunks
+            .entry(chunk)
+            .or_default()
+            .extend(changes);
+    }
+
+    /// Queues an acknowledgement of a connection's block change sequence. Sequences only go up,
+    /// so acknowledging the highest one covers the rest.
+    pub fn acknowledge(&self, conn_id: usize, sequence: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        let highest = inner.acknowledgements.entry(conn_id).or_insert(sequence);
+        *highest = (*highest).max(sequence);
+    }
+
+    /// Takes everything queued so far: the changes per chunk, with only the last change to each
+    /// block kept, and the sequences to acknowledge.
+    pub fn take(&self) -> (Vec<ChunkChanges>, Vec<(usize, i32)>) {
+        let inner = std::mem::take(&mut *self.inner.lock().unwrap());
+        let chunks = inner
+            .chunks
+            .into_iter()
+            .map(|(chunk, changes)| (chunk, merge_changes(changes)))
+            .collect();
+        (chunks, inner.acknowledgements.into_iter().collect())
+    }
+}
+
+/// Keeps only the last change to each block, in the order the blocks were first changed.
+fn merge_changes(changes: Vec<BlockChange>) -> Vec<BlockChange> {
+    let mut merged: Vec<BlockChange> = Vec::with_capacity(changes.len());
+    let mut indices: HashMap<Position, usize> = HashMap::new();
+    for change in changes {
+        match indices.get(&change.position) {
+            Some(index) => merged[*index].block_state = change.block_state,
+            None => {
+                indices.insert(change.position.clone(), merged.len());
+                merged.push(change);
+            }
+        }
+    }
+    merged
+}
+
+/// How players that have a chunk loaded get told about changes to it.
+#[derive(Clone)]
+pub enum ChunkUpdate {
+    /// Just the one block changed.
+    Single(BlockUpdate),
+    /// One multi block change per section with changes in it.
+    Sections(Vec<UpdateSectionBlocks>),
+    /// So much changed that sending the whole chunk again is cheaper.
+    FullResend,
+}
+
+/// Picks the cheaper way of sending changes to a single chunk.
+///
+/// A section's density is the share of its blocks that changed. Once any section's goes over
+/// `resend_density`, its multi block change would be about as big as the section itself, so the
+/// whole chunk is resent instead. A few changes spread over many sections don't add up to that.
+pub fn plan_chunk_update(changes: &[BlockChange], resend_density: f64) -> ChunkUpdate {
+    if let [change] = changes {
+        return ChunkUpdate::Single(BlockUpdate::new(
+            change.position.clone(),
+            change.block_state,
+        ));
+    }
+
+    let mut sections: BTreeMap<(i32, i32, i32), SectionChanges> = BTreeMap::new();
+    for change in changes {
+        let Position { x, y, z } = change.position;
+        let y = y as i32;
+        sections.entry((x >> 4, y >> 4, z >> 4)).or_default().push((
+            (x & 0xF) as u8,
+            (y & 0xF) as u8,
+            (z & 0xF) as u8,
+            change.block_state,
+        ));
+    }
+
+    let densest = sections.values().map(Vec::len).max().unwrap_or_default();
+    if densest as f64 / SECTION_VOLUME as f64 > resend_density {
+        return ChunkUpdate::FullResend;
+    }
+
+    ChunkUpdate::Sections(
+        sections
+            .into_iter()
+            .map(|((section_x, section_y, section_z), blocks)| {
+                UpdateSectionBlocks::new(section_x, section_y, section_z, &blocks)
+            })
+            .collect(),
+    )
+}
+
+/// Tells every player in the dimension that has the chunk loaded about changes to it.
+///
+/// The changed chunk has to be relit with [crate::world::lighting::relight_changes] and put in the
+/// chunk cache with [crate::world::chunk_cache::ChunkCache::update] already, since a full resend
+/// loads it again.
+pub async fn send_block_changes(
+    state: &GlobalState,
+    (dimension, chunk_x, chunk_z): ChunkKey,
+    changes: &[BlockChange],
+) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let update = plan_chunk_update(changes, get_global_config().chunk_resend_density);
+    let chunk = match update {
+        ChunkUpdate::FullResend => {
+            Some(ChunkDataAndUpdateLight::load_chunk(state, dimension, chunk_x, chunk_z).await?)
+        }
+        ChunkUpdate::Single(_) | ChunkUpdate::Sections(_) => None,
+    };
+    let reduce_for_spectators = get_global_config().reduced_spectator_chunks;
+
+    let mut recipients = Vec::new();
+    {
+        let mut query = state
+            .world
+            .query::<(&LoadedChunks, &ConnectionWrapper, Option<&Dimension>)>();
+        while let Some((entity_id, (loaded_chunks, conn, in_dimension))) = query.next().await {
+            if in_dimension.as_deref().copied().unwrap_or_default() == dimension
+                && loaded_chunks.is_loaded(chunk_x, chunk_z)
+            {
+                recipients.push((entity_id, conn.0.clone()));
+            }
+        }
+    }
+
+    // One player not getting the changes mustn't keep them from the rest
+    for (entity_id, conn) in recipients {
+        let conn = conn.read().await;
+        let sent = async {
+            match (&update, &chunk) {
+                (ChunkUpdate::Single(packet), _) => conn.send_packet(packet.clone()).await,
+                (ChunkUpdate::Sections(packets), _) => {
+                    for packet in packets {
+                        conn.send_packet(packet.clone()).await?;
+                    }
+                    Ok(())
+                }
+                (ChunkUpdate::FullResend, Some(chunk)) => {
+                    let game_mode = state
+                        .world
+                        .get_component::<GameMode>(entity_id)
+                        .await
+                        .map(|game_mode| *game_mode)
+                        .unwrap_or_default();
+                    let packet = ChunkDataAndUpdateLight::from_chunk(chunk, dimension)
+                        .await?
+                        .for_game_mode(game_mode, reduce_for_spectators);
+                    conn.send_packet(packet).await
+                }
+                (ChunkUpdate::FullResend, None) => unreachable!(),
+            }
+        };
+        if let Err(e) = sent.await {
+            warn!("Failed to send block changes to {}: {}", entity_id, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// The section a block is in, and its index within the section.
+fn section_index(position: &Position) -> (i8, usize) {
+    let y = position.y as i32;
+    let index = block_index(
+        position.x.rem_euclid(16) as usize,
+        y,
+        position.z.rem_euclid(16) as usize,
+    );
+    ((y >> 4) as i8, index)
+}
+
+impl Chunk {
+    /// The block state at a position in this chunk, in world coordinates. Air if there's no
+    /// section there.
+    pub fn block_at(&self, position: &Position) -> i32 {
+        let (section_y, index) = section_index(position);
+        self.sections
+            .iter()
+            .flatten()
+            .find(|section| section.y == section_y)
+            .and_then(|section| section.block_states.as_ref())
+            .map_or(0, |block_states| block_states.block_ids()[index])
+    }
+
+    /// Changes a block at a position in this chunk, in world coordinates, and returns the block
+    /// state it replaced. `None` if there's no section there, e.g. above the build limit.
+    ///
+    /// A block entity at the position goes with the block it belonged to.
+    pub fn set_block(&mut self, position: &Position, block_state: i32) -> Option<i32> {
+        let (section_y, index) = section_index(position);
+        let section = self
+            .sections
+            .iter_mut()
+            .flatten()
+            .find(|section| section.y == section_y)?;
+        let block_states = section.block_states.as_mut()?;
+        let old = block_states.set_block(index, block_state);
+        if old != block_state {
+            if let Some(block_entities) = self.block_entities.as_mut() {
+                block_entities.retain(|block_entity| block_entity.position() != *position);
+            }
+        }
+        Some(old)
+    }
+}
+
+impl ServerState {
+    /// The block state at a position in a dimension, loading its chunk if needed.
+    pub async fn block_at(
+        self: &GlobalState,
+        dimension: Dimension,
+        position: &Position,
+    ) -> Result<i32> {
+        let chunk =
+            ChunkDataAndUpdateLight::load_chunk(self, dimension, position.x >> 4, position.z >> 4)
+                .await?;
+        Ok(chunk.block_at(position))
+    }
+
+    /// Changes blocks in a dimension. Each chunk with changes in it is relit, gets its heightmaps
+    /// worked out again and is put back in the chunk cache to be saved. The players that have it
+    /// loaded are told at the end of the tick, see [PendingBlockChanges].
+    ///
+    /// Changes outside the dimension are left out.
+    pub async fn set_blocks(
+        self: &GlobalState,
+        dimension: Dimension,
+        changes: &[BlockChange],
+    ) -> Result<()> {
+        let mut chunks: BTreeMap<(i32, i32), Vec<BlockChange>> = BTreeMap::new();
+        for change in changes {
+            chunks
+                .entry((change.position.x >> 4, change.position.z >> 4))
+                .or_default()
+                .push(change.clone());
+        }
+
+        // Changing a chunk means loading it, changing it and putting it back, which mustn't
+        // overlap with another change to it
+        let _guard = self.block_edits.lock().await;
+        for ((chunk_x, chunk_z), changes) in chunks {
+            // Changes are made to a copy, so the cached chunk stays as it was for anyone still
+            // reading it
+            let mut chunk = Arc::unwrap_or_clone(
+                ChunkDataAndUpdateLight::load_chunk(self, dimension, chunk_x, chunk_z).await?,
+            );
+            let changes = changes
+                .into_iter()
+                .filter(|change| {
+                    chunk
+                        .set_block(&change.position, change.block_state)
+                        .is_some_and(|old| old != change.block_state)
+                })
+                .collect::<Vec<_>>();
+            if changes.is_empty() {
+                continue;
+            }
+
+            relight_changes(&mut chunk, &changes);
+            chunk.heightmaps = Some(compute_heightmaps(&chunk));
+            self.chunk_cache.update(dimension, chunk).await;
+            self.pending_block_changes
+                .push((dimension, chunk_x, chunk_z), changes);
+        }
+        Ok(())
+    }
+
+    /// Sends out the block changes made since the last flush, then acknowledges the block change
+    /// sequences that came with them.
+    pub async fn flush_block_changes(self: &GlobalState) -> Result<()> {
+        let (chunks, acknowledgements) = self.pending_block_changes.take();
+        for (chunk, changes) in chunks {
+            // The chunk has changed already, so the rest still need to hear about theirs
+            if let Err(e) = send_block_changes(self, chunk, &changes).await {
+                warn!("Failed to send block changes in chunk {:?}: {}", chunk, e);
+            }
+        }
+
+        for (conn_id, sequence) in acknowledgements {
+            // They might have left since
+            let Ok(conn) = self.connections.get_connection(conn_id) else {
+                continue;
+            };
+            let conn = conn.read().await;
+            if let Err(e) = conn
+                .send_packet(AcknowledgeBlockChange::new(sequence))
+                .await
+            {
+                warn!("Failed to acknowledge block changes for {}: {}", conn_id, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `count` changes in the section at the origin, filling it up one layer at a time.
+    fn changes(count: usize) -> Vec<BlockChange> {
+        (0..count)
+            .map(|index| BlockChange {
+                position: Position::new(
+                    (index % 16) as i32,
+                    (index / 256) as i16,
+                    (index / 16 % 16) as i32,
+                ),
+                block_state: 1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_single_changes_use_block_update() {
+        assert!(matches!(
+            plan_chunk_update(&changes(1), 0.25),
+            ChunkUpdate::Single(_)
+        ));
+    }
+
+    #[test]
+    fn test_blocks_are_changed_in_their_section() {
+        let mut chunk = Chunk::empty(-1, 0);
+        let position = Position::new(-3, -60, 5);
+        assert_eq!(chunk.block_at(&position), 0);
+        assert_eq!(chunk.set_block(&position, 1), Some(0));
+        assert_eq!(chunk.block_at(&position), 1);
+        assert_eq!(chunk.block_at(&Position::new(-3, -59, 5)), 0);
+
+        // Nothing above the build limit
+        assert_eq!(chunk.set_block(&Position::new(-3, 320, 5), 1), None);
+    }
+
+    #[test]
+    fn test_few_changes_use_multi_block_change() {
+        let ChunkUpdate::Sections(packets) = plan_chunk_update(&changes(10), 0.25) else {
+            panic!("Expected a multi block change");
+        };
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].blocks.len(), 10);
+    }
+
+    #[test]
+    fn test_pending_changes_are_merged() {
+        let pending = PendingBlockChanges::default();
+        let position = Position::new(1, 2, 3);
+        pending.push((Dimension::Overworld, 0, 0), changes(2));
+        pending.push(
+            (Dimension::Overworld, 0, 0),
+            vec![BlockChange {
+                position: position.clone(),
+                block_state: 5,
+            }],
+        );
+        pending.push(
+            (Dimension::Overworld, 0, 0),
+            vec![BlockChange {
+                position: Position::new(0, 0, 0),
+                block_state: 7,
+            }],
+        );
+        pending.acknowledge(4, 10);
+        pending.acknowledge(4, 8);
+
+        let (chunks, acknowledgements) = pending.take();
+        assert_eq!(chunks.len(), 1);
+        let changes = &chunks[0].1;
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].position, Position::new(0, 0, 0));
+        assert_eq!(changes[0].block_state, 7);
+        assert_eq!(changes[2].position, position);
+        assert_eq!(acknowledgements, vec![(4, 10)]);
+
+        // Taking empties the queue
+        let (chunks, acknowledgements) = pending.take();
+        assert!(chunks.is_empty() && acknowledgements.is_empty());
+    }
+
+    #[test]
+    fn test_many_changes_resend_the_chunk() {
+        assert!(matches!(
+            plan_chunk_update(&changes(3000), 0.25),
+            ChunkUpdate::FullResend
+        ));
+    }
+
+    #[test]
+    fn test_density_is_per_section() {
+        // A full section next to 23 nearly empty ones averages out under the threshold, but the
+        // full one alone is worth resending the chunk for
+        let mut dense = changes(SECTION_VOLUME);
+        dense.extend((1..24).map(|section| BlockChange {
+            position: Position::new(0, section * 16, 0),
+            block_state: 1,
+        }));
+        assert!(matches!(
+            plan_chunk_update(&dense, 0.25),
+            ChunkUpdate::FullResend
+        ));
+
+        // While a few changes in every section don't add up to a resend
+        let sparse = (0..24)
+            .flat_map(|section| {
+                changes(100).into_iter().map(move |mut change| {
+                    change.position.y += section * 16;
+                    change
+                })
+            })
+            .collect::<Vec<_>>();
+        let ChunkUpdate::Sections(packets) = plan_chunk_update(&sparse, 0.25) else {
+            panic!("Expected multi block changes");
+        };
+        assert_eq!(packets.len(), 24);
+    }
+}
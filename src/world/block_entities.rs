@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+
+use bincode::{Decode, Encode};
+use dashmap::DashMap;
+use ferrumc_codec::network_types::varint::VarInt;
+use nbt_lib::nbt_spec::serializer::impls::NBTFieldType;
+use nbt_lib::nbt_spec::serializer::tag_types::{TAG_COMPOUND, TAG_INT, TAG_STRING};
+use nbt_lib::nbt_spec::serializer::NBTAnonymousType;
+use nbt_lib::{NBTDeserialize, NBTError, NBTResult, NBTSerialize, NBTTag};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::net::packets::outgoing::chunk_and_light_data::BlockEntity;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::dimension::Dimension;
+
+/// The block entity types the client knows, in the order of their network IDs.
+const BLOCK_ENTITY_TYPES: &[&str] = &[
+    "minecraft:furnace",
+    "minecraft:chest",
+    "minecraft:trapped_chest",
+    "minecraft:ender_chest",
+    "minecraft:jukebox",
+    "minecraft:dispenser",
+    "minecraft:dropper",
+    "minecraft:sign",
+    "minecraft:hanging_sign",
+    "minecraft:mob_spawner",
+    "minecraft:piston",
+    "minecraft:brewing_stand",
+    "minecraft:enchanting_table",
+    "minecraft:end_portal",
+    "minecraft:beacon",
+    "minecraft:skull",
+    "minecraft:daylight_detector",
+    "minecraft:hopper",
+    "minecraft:comparator",
+    "minecraft:banner",
+    "minecraft:structure_block",
+    "minecraft:end_gateway",
+    "minecraft:command_block",
+    "minecraft:shulker_box",
+    "minecraft:bed",
+    "minecraft:conduit",
+    "minecraft:barrel",
+    "minecraft:smoker",
+    "minecraft:blast_furnace",
+    "minecraft:lectern",
+    "minecraft:bell",
+    "minecraft:jigsaw",
+    "minecraft:campfire",
+    "minecraft:beehive",
+    "minecraft:sculk_sensor",
+    "minecraft:calibrated_sculk_sensor",
+    "minecraft:sculk_catalyst",
+    "minecraft:sculk_shrieker",
+    "minecraft:chiseled_bookshelf",
+    "minecraft:brushable_block",
+    "minecraft:decorated_pot",
+];
+
+/// The network ID of a block entity type, e.g. 1 for `minecraft:chest`.
+pub fn block_entity_type_id(id: &str) -> Option<i32> {
+    BLOCK_ENTITY_TYPES
+        .iter()
+        .position(|known| *known == id)
+        .map(|index| index as i32)
+}
+
+/// Holds the data of the blocks that have more state than a block state ID, e.g. command blocks.
+/// Each dimension has its own, like chunks do.
+#[derive(Debug, Default)]
+pub struct BlockEntityStore {
+    entities: DashMap<(Dimension, Position), BlockEntityData>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockEntityData {
+    CommandBlock(CommandBlock),
+    StructureBlock(StructureBlock),
+    JigsawBlock(JigsawBlock),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandBlock {
+    pub command: String,
+    pub mode: CommandBlockMode,
+    pub track_output: bool,
+    pub conditional: bool,
+    /// Whether the command block runs without needing a redstone signal.
+    pub automatic: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandBlockMode {
+    Sequence,
+    Auto,
+    Redstone,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureBlock {
+    /// The structure's identifier, e.g. `minecraft:village/plains/houses/plains_small_house_1`.
+    pub name: String,
+    pub mode: StructureBlockMode,
+    /// Where the structure starts, relative to the structure block.
+    pub offset: (i8, i8, i8),
+    pub size: (i8, i8, i8),
+    pub mirror: StructureMirror,
+    pub rotation: StructureRotation,
+    /// Only used in data mode, where it's the data marker's name.
+    pub metadata: String,
+    /// How much of the structure gets placed when loading, from 0 to 1.
+    pub integrity: f32,
+    /// Decides which blocks are left out when integrity is below 1.
+    pub seed: i64,
+    pub ignore_entities: bool,
+    pub show_air: bool,
+    pub show_bounding_box: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructureBlockMode {
+    Save,
+    Load,
+    Corner,
+    Data,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructureMirror {
+    None,
+    LeftRight,
+    FrontBack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructureRotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    CounterClockwise90,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JigsawBlock {
+    pub name: String,
+    /// The name of the jigsaw block this one connects to.
+    pub target: String,
+    /// The template pool the connected piece is picked from.
+    pub pool: String,
+    /// What the jigsaw block turns into once the structure is generated.
+    pub final_state: String,
+    /// Either `rollable` or `aligned`.
+    pub joint_type: String,
+}
+
+/// A block entity as it's stored in a chunk, like a chest with its items or a sign with its text.
+///
+/// The server only needs to know what kind of block entity it is and where it is, so everything
+/// else is kept as the NBT it was read from and written back out untouched.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize, deepsize::DeepSizeOf,
+)]
+pub struct ChunkBlockEntity {
+    /// The block entity type, e.g. `minecraft:chest`.
+    pub id: String,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    /// The rest of the compound's entries, followed by its end tag.
+    pub data: Vec<u8>,
+}
+
+impl ChunkBlockEntity {
+    pub fn position(&self) -> Position {
+        Position::new(self.x, self.y as i16, self.z)
+    }
+
+    /// The block entity's entry in the chunk data packet, or `None` if the client doesn't know
+    /// its type.
+    ///
+    /// The client gets the same NBT that's saved, minus the type and position, which it knows
+    /// from the rest of the entry.
+    pub fn to_network(&self) -> Option<BlockEntity> {
+        let type_id = block_entity_type_id(&self.id)?;
+        // An unnamed root compound
+        let mut data = vec![TAG_COMPOUND, 0, 0];
+        data.extend_from_slice(&self.data);
+        Some(BlockEntity {
+            packed_xz: (((self.x & 0xF) << 4) | (self.z & 0xF)) as u8,
+            y: self.y as i16,
+            type_id: VarInt::from(type_id),
+            data,
+        })
+    }
+}
+
+/// Takes one of a block entity's entries out of its compound.
+fn take_entry<T: NBTDeserialize>(entries: &mut HashMap<String, NBTTag>, key: &str) -> NBTResult<T> {
+    let entry = entries.remove(key).ok_or_else(|| {
+        NBTError::DeserializeError(format!("Block entity is missing its {}", key))
+    })?;
+    T::read_from(entry)
+}
+
+impl NBTDeserialize for ChunkBlockEntity {
+    fn read_from(nbt: NBTTag) -> NBTResult<Self> {
+        let NBTTag::Compound(mut entries) = nbt else {
+            return Err(NBTError::InvalidType("ChunkBlockEntity", nbt.my_type()));
+        };
+        let id = take_entry(&mut entries, "id")?;
+        let x = take_entry(&mut entries, "x")?;
+        let y = take_entry(&mut entries, "y")?;
+        let z = take_entry(&mut entries, "z")?;
+        // Only matters for block entities in proto-chunks, which are never loaded
+        entries.remove("keepPacked");
+
+        let mut data = Vec::new();
+        NBTTag::Compound(entries).nbt_serialize(&mut data)?;
+        Ok(Self { id, x, y, z, data })
+    }
+}
+
+impl NBTSerialize for ChunkBlockEntity {
+    fn nbt_serialize<W: std::io::Write>(&self, writer: &mut W) -> NBTResult<()> {
+        TAG_STRING.nbt_serialize(writer)?;
+        "id".nbt_serialize(writer)?;
+        self.id.nbt_serialize(writer)?;
+        for (key, value) in [("x", self.x), ("y", self.y), ("z", self.z)] {
+            TAG_INT.nbt_serialize(writer)?;
+            key.nbt_serialize(writer)?;
+            value.nbt_serialize(writer)?;
+        }
+        // Ends with the compound's end tag
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+impl NBTFieldType for ChunkBlockEntity {
+    fn tag_type(&self) -> u8 {
+        TAG_COMPOUND
+    }
+}
+
+impl NBTAnonymousType for ChunkBlockEntity {
+    fn tag_type() -> u8 {
+        TAG_COMPOUND
+    }
+}
+
+impl BlockEntityStore {
+    pub fn get(&self, dimension: Dimension, position: &Position) -> Option<BlockEntityData> {
+        self.entities
+            .get(&(dimension, position.clone()))
+            .map(|entry| entry.value().clone())
+    }
+
+    pub fn insert(
+        &self,
+        dimension: Dimension,
+        position: Position,
+        data: BlockEntityData,
+    ) -> Option<BlockEntityData> {
+        self.entities.insert((dimension, position), data)
+    }
+
+    pub fn remove(&self, dimension: Dimension, position: &Position) -> Option<BlockEntityData> {
+        self.entities
+            .remove(&(dimension, position.clone()))
+            .map(|(_, data)| data)
+    }
+}
+
+impl TryFrom<i32> for CommandBlockMode {
+    type Error = Error;
+
+    fn try_from(mode: i32) -> Result<Self> {
+        match mode {
+            0 => Ok(CommandBlockMode::Sequence),
+            1 => Ok(CommandBlockMode::Auto),
+            2 => Ok(CommandBlockMode::Redstone),
+            _ => Err(Error::Generic(format!(
+                "Invalid command block mode: {}",
+                mode
+            ))),
+        }
+    }
+}
+
+impl TryFrom<i32> for StructureBlockMode {
+    type Error = Error;
+
+    fn try_from(mode: i32) -> Result<Self> {
+        match mode {
+            0 => Ok(StructureBlockMode::Save),
+            1 => Ok(StructureBlockMode::Load),
+            2 => Ok(StructureBlockMode::Corner),
+            3 => Ok(StructureBlockMode::Data),
+            _ => Err(Error::Generic(format!(
+                "Invalid structure block mode: {}",
+                mode
+            ))),
+        }
+    }
+}
+
+impl TryFrom<i32> for StructureMirror {
+    type Error = Error;
+
+    fn try_from(mirror: i32) -> Result<Self> {
+        match mirror {
+            0 => Ok(StructureMirror::None),
+            1 => Ok(StructureMirror::LeftRight),
+            2 => Ok(StructureMirror::FrontBack),
+            _ => Err(Error::Generic(format!(
+                "Invalid structure mirror: {}",
+                mirror
+            ))),
+        }
+    }
+}
+
+impl TryFrom<i32> for StructureRotation {
+    type Error = Error;
+
+    fn try_from(rotation: i32) -> Result<Self> {
+        match rotation {
+            0 => Ok(StructureRotation::None),
+            1 => Ok(StructureRotation::Clockwise90),
+            2 => Ok(StructureRotation::Clockwise180),
+            3 => Ok(StructureRotation::CounterClockwise90),
+            _ => Err(Error::Generic(format!(
+                "Invalid structure rotation: {}",
+                rotation
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn sign() -> NBTTag {
+        NBTTag::Compound(HashMap::from([
+            (
+                "id".to_string(),
+                NBTTag::String("minecraft:sign".to_string()),
+            ),
+            ("x".to_string(), NBTTag::Int(-17)),
+            ("y".to_string(), NBTTag::Int(70)),
+            ("z".to_string(), NBTTag::Int(35)),
+            ("keepPacked".to_string(), NBTTag::Byte(0)),
+            ("is_waxed".to_string(), NBTTag::Byte(1)),
+        ]))
+    }
+
+    #[test]
+    fn test_block_entity_nbt_round_trip() {
+        let block_entity = ChunkBlockEntity::read_from(sign()).unwrap();
+        assert_eq!(block_entity.id, "minecraft:sign");
+        assert_eq!(block_entity.position(), Position::new(-17, 70, 35));
+
+        let mut bytes = Vec::new();
+        block_entity.nbt_serialize(&mut bytes).unwrap();
+        let tag = nbt_lib::read_tag(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(ChunkBlockEntity::read_from(tag).unwrap(), block_entity);
+    }
+
+    #[test]
+    fn test_block_entity_network_entry() {
+        let block_entity = ChunkBlockEntity::read_from(sign()).unwrap();
+        let entry = block_entity.to_network().unwrap();
+        assert_eq!(entry.packed_xz, 0xF3);
+        assert_eq!(entry.y, 70);
+        assert_eq!(entry.type_id.get_val(), 7);
+        assert_eq!(&entry.data[..3], &[TAG_COMPOUND, 0, 0]);
+
+        // Only what's left after the type and position
+        let tag = nbt_lib::read_tag(&mut Cursor::new(entry.data[3..].to_vec())).unwrap();
+        let NBTTag::Compound(entries) = tag else {
+            panic!("Expected a compound");
+        };
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(entries.get("is_waxed"), Some(NBTTag::Byte(1))));
+
+        let unknown = ChunkBlockEntity {
+            id: "minecraft:mystery".to_string(),
+            ..block_entity
+        };
+        assert!(unknown.to_network().is_none());
+    }
+}
@@ -0,0 +1,430 @@
+//! The mapping between block state ids and block states.
+//!
+//! The server runs off a single [BlockRegistry], reachable through [block_registry] or
+//! `GlobalState::block_registry`, so chunk conversion and block placement always agree on IDs. It
+//! can be loaded from vanilla's `blocks.json` report by pointing `blocks_report` in the config at
+//! it, otherwise the registry bundled with the server is used.
+//!
+//! The registry ships as a compressed JSON file, which takes a while to parse. To speed up
+//! startup, the parsed registry is written to a small binary cache the first time it's built and
+//! read back from there afterwards. The cache remembers which JSON it came from, so it gets rebuilt
+//! whenever the bundled registry changes.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use bincode::config::standard;
+use bincode::{Decode, Encode};
+use hashbrown::HashMap;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+use crate::world::chunk_format::Palette;
+
+const BLOCKSFILE: &[u8] = include_bytes!("../../.etc/blockmappings.bz2");
+
+const CACHE_MAGIC: &[u8; 4] = b"FBRC";
+/// Bump this whenever the layout of the cache changes.
+const CACHE_FORMAT_VERSION: u32 = 2;
+const CACHE_HEADER_LEN: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct BlockStateRegistry {
+    states: Vec<(i32, Palette)>,
+    /// The default state of each block, where the source says which one it is.
+    defaults: Vec<i32>,
+}
+
+impl BlockStateRegistry {
+    /// Builds the registry from the bundled JSON.
+    pub fn bundled() -> Self {
+        Self::from_json(BLOCKSFILE).expect("The bundled block registry is invalid")
+    }
+
+    /// Builds the registry from a bzip2 compressed JSON object of ids to block states.
+    pub fn from_json(compressed: &[u8]) -> Result<Self> {
+        let mut json = String::new();
+        bzip2::read::BzDecoder::new(compressed).read_to_string(&mut json)?;
+        let string_keys: HashMap<String, Palette> =
+            serde_json::from_str(&json).map_err(|e| Error::DeserializationError(e.to_string()))?;
+
+        let mut states = string_keys
+            .into_iter()
+            .map(|(id, state)| {
+                id.parse::<i32>()
+                    .map(|id| (id, state))
+                    .map_err(|_| Error::DeserializationError(format!("Invalid block id: {}", id)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        states.sort_unstable_by_key(|(id, _)| *id);
+
+        Ok(Self {
+            states,
+            defaults: Vec::new(),
+        })
+    }
+
+    /// Builds the registry from vanilla's `blocks.json` report, which lists every state of every
+    /// block along with its ID.
+    pub fn from_blocks_report(json: &str) -> Result<Self> {
+        let blocks: HashMap<String, ReportBlock> =
+            serde_json::from_str(json).map_err(|e| Error::DeserializationError(e.to_string()))?;
+
+        let mut defaults = blocks
+            .values()
+            .flat_map(|block| &block.states)
+            .filter(|state| state.default)
+            .map(|state| state.id)
+            .collect::<Vec<_>>();
+        defaults.sort_unstable();
+
+        let mut states = blocks
+            .into_iter()
+            .flat_map(|(name, block)| {
+                block.states.into_iter().map(move |state| {
+                    let properties = state.properties.filter(|p| !p.is_empty());
+                    (
+                        state.id,
+                        Palette {
+                            name: name.clone(),
+                            properties,
+                        },
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        states.sort_unstable_by_key(|(id, _)| *id);
+
+        Ok(Self { states, defaults })
+    }
+
+    /// Loads the registry the server runs with: the blocks report at `blocks_report` if it's set,
+    /// otherwise the bundled registry, going through the cache.
+    ///
+    /// A report that can't be read is logged and the bundled registry is used instead.
+    pub fn load_configured() -> Self {
+        let report = &get_global_config().blocks_report;
+        if !report.is_empty() {
+            match std::fs::read_to_string(report)
+                .map_err(Error::from)
+                .and_then(|json| Self::from_blocks_report(&json))
+            {
+                Ok(registry) => {
+                    debug!("Loaded the block registry from {}", report);
+                    return registry;
+                }
+                Err(e) => warn!(
+                    "Failed to load the blocks report {}: {}. Using the bundled registry instead.",
+                    report, e
+                ),
+            }
+        }
+        Self::load(cache_path().as_deref())
+    }
+
+    /// Reads the registry from `cache` if it's there and up to date, otherwise builds it from the
+    /// bundled JSON and writes the cache for next time.
+    pub fn load(cache: Option<&Path>) -> Self {
+        let Some(cache) = cache else {
+            return Self::bundled();
+        };
+
+        if let Some(registry) = std::fs::read(cache)
+            .ok()
+            .and_then(|bytes| Self::from_cache_bytes(&bytes))
+        {
+            debug!("Loaded the block registry from {}", cache.display());
+            return registry;
+        }
+
+        debug!("Block registry cache is missing or stale, rebuilding it");
+        let registry = Self::bundled();
+        if let Err(e) = registry
+            .to_cache_bytes()
+            .and_then(|bytes| std::fs::write(cache, bytes).map_err(Error::from))
+        {
+            warn!("Failed to write the block registry cache: {}", e);
+        }
+        registry
+    }
+
+    /// Serializes the registry for the cache, tagged with the bundled JSON it was built from.
+    pub fn to_cache_bytes(&self) -> Result<Vec<u8>> {
+        self.to_cache_bytes_for(BLOCKSFILE)
+    }
+
+    /// Reads a cached registry, returning `None` if it's malformed or wasn't built from the
+    /// bundled JSON.
+    pub fn from_cache_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::from_cache_bytes_for(bytes, BLOCKSFILE)
+    }
+
+    fn to_cache_bytes_for(&self, source: &[u8]) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(CACHE_HEADER_LEN);
+        bytes.extend_from_slice(CACHE_MAGIC);
+        bytes.extend_from_slice(&CACHE_FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&source_hash(source).to_le_bytes());
+        bincode::encode_into_std_write(self, &mut bytes, standard())?;
+        Ok(bytes)
+    }
+
+    fn from_cache_bytes_for(bytes: &[u8], source: &[u8]) -> Option<Self> {
+        if bytes.len() < CACHE_HEADER_LEN || &bytes[..4] != CACHE_MAGIC {
+            return None;
+        }
+        let version = u32::from_le_bytes(bytes[4..8].try_into().ok()?);
+        let hash = u64::from_le_bytes(bytes[8..16].try_into().ok()?);
+        if version != CACHE_FORMAT_VERSION || hash != source_hash(source) {
+            return None;
+        }
+
+        let (registry, _) =
+            bincode::decode_from_slice::<Self, _>(&bytes[CACHE_HEADER_LEN..], standard()).ok()?;
+        Some(registry)
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn into_map(self) -> HashMap<i32, Palette> {
+        self.states.into_iter().collect()
+    }
+}
+
+/// One block in vanilla's `blocks.json` report.
+#[derive(Deserialize)]
+struct ReportBlock {
+    states: Vec<ReportState>,
+}
+
+#[derive(Deserialize)]
+struct ReportState {
+    id: i32,
+    #[serde(default)]
+    default: bool,
+    #[serde(default)]
+    properties: Option<BTreeMap<String, String>>,
+}
+
+/// Lookups between block state IDs and block states, in both directions.
+#[derive(Debug)]
+pub struct BlockRegistry {
+    states: HashMap<i32, Palette>,
+    ids: HashMap<Palette, i32>,
+    ids_by_name: HashMap<String, Vec<i32>>,
+    defaults: HashMap<String, i32>,
+}
+
+impl From<BlockStateRegistry> for BlockRegistry {
+    fn from(registry: BlockStateRegistry) -> Self {
+        let defaults = registry.defaults.clone();
+        let states = registry.into_map();
+        let defaults = defaults
+            .into_iter()
+            .filter_map(|id| Some((states.get(&id)?.name.clone(), id)))
+            .collect();
+        let ids = states
+            .iter()
+            .map(|(id, state)| (state.clone(), *id))
+            .collect();
+        let mut ids_by_name: HashMap<String, Vec<i32>> = HashMap::new();
+        for (id, state) in states.iter() {
+            ids_by_name.entry(state.name.clone()).or_default().push(*id);
+        }
+        ids_by_name.values_mut().for_each(|ids| ids.sort_unstable());
+
+        Self {
+            states,
+            ids,
+            ids_by_name,
+            defaults,
+        }
+    }
+}
+
+impl BlockRegistry {
+    /// The block state with the given ID.
+    pub fn state(&self, id: i32) -> Option<&Palette> {
+        self.states.get(&id)
+    }
+
+    /// Looks up the ID of a block state.
+    ///
+    /// Properties that aren't given are filled in, so `minecraft:oak_stairs[facing=east]` still
+    /// resolves. They're taken from the block's default state when the registry came from a
+    /// blocks report. The bundled registry doesn't say which state is the default, so there unset
+    /// boolean properties prefer `false` and anything else takes the lowest ID, which lines up
+    /// with vanilla's defaults for nearly every block.
+    pub fn id(&self, state: &Palette) -> Option<i32> {
+        if let Some(id) = self.ids.get(state) {
+            return Some(*id);
+        }
+
+        if let Some(default) = self.defaults.get(&state.name) {
+            let mut properties = self.states[default].properties.clone().unwrap_or_default();
+            for (key, value) in state.properties.iter().flatten() {
+                // Properties the block doesn't have can't match any of its states
+                properties.get_mut(key)?.clone_from(value);
+            }
+            let filled = Palette {
+                name: state.name.clone(),
+                properties: (!properties.is_empty()).then_some(properties),
+            };
+            return self.ids.get(&filled).copied();
+        }
+
+        let wanted = state.properties.as_ref();
+        self.ids_by_name
+            .get(&state.name)?
+            .iter()
+            .filter_map(|id| {
+                let properties = self.states[id].properties.as_ref();
+                let matches = wanted
+                    .into_iter()
+                    .flatten()
+                    .all(|(key, value)| properties.and_then(|p| p.get(key)) == Some(value));
+                if !matches {
+                    return None;
+                }
+                let unset_true = properties.map_or(0, |properties| {
+                    properties
+                        .iter()
+                        .filter(|(key, value)| {
+                            *value == "true" && !wanted.is_some_and(|w| w.contains_key(*key))
+                        })
+                        .count()
+                });
+                Some((unset_true, *id))
+            })
+            .min()
+            .map(|(_, id)| id)
+    }
+
+    /// The number of block states, including air. IDs are contiguous, so every ID below this is a
+    /// valid block state.
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+#[cfg(not(test))]
+lazy_static! {
+    static ref BLOCK_REGISTRY: BlockRegistry = BlockStateRegistry::load_configured().into();
+}
+
+// Tests always run against the bundled registry, so a config or a stale cache on the machine
+// running them can't change the IDs they see
+#[cfg(test)]
+lazy_static! {
+    static ref BLOCK_REGISTRY: BlockRegistry = BlockStateRegistry::bundled().into();
+}
+
+/// The block registry the server runs with, loaded the first time it's needed.
+pub fn block_registry() -> &'static BlockRegistry {
+    &BLOCK_REGISTRY
+}
+
+/// Where the registry cache lives, if caching is turned on.
+pub fn cache_path() -> Option<PathBuf> {
+    let cache = &get_global_config().block_registry_cache;
+    (!cache.is_empty()).then(|| PathBuf::from(cache))
+}
+
+/// FNV-1a, which unlike the std hasher is guaranteed to stay the same between builds.
+fn source_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_registry_matches_json() {
+        let registry = BlockStateRegistry::bundled();
+        let bytes = registry.to_cache_bytes().unwrap();
+        let cached = BlockStateRegistry::from_cache_bytes(&bytes).unwrap();
+        assert_eq!(cached, registry);
+
+        let json_ids = registry.into_map();
+        let cached_ids = cached.into_map();
+        assert_eq!(cached_ids.len(), json_ids.len());
+        for id in [0, 1, 132, 2935] {
+            assert_eq!(cached_ids.get(&id), json_ids.get(&id));
+        }
+        assert_eq!(cached_ids[&0].name, "minecraft:air");
+    }
+
+    #[test]
+    fn test_blocks_report_lookups() {
+        let report = r#"{
+            "minecraft:air": {"states": [{"id": 0, "default": true}]},
+            "minecraft:oak_log": {
+                "properties": {"axis": ["x", "y", "z"]},
+                "states": [
+                    {"id": 2, "properties": {"axis": "x"}},
+                    {"id": 3, "default": true, "properties": {"axis": "y"}},
+                    {"id": 4, "properties": {"axis": "z"}}
+                ]
+            },
+            "minecraft:stone": {"states": [{"id": 1, "default": true}]}
+        }"#;
+        let registry = BlockRegistry::from(BlockStateRegistry::from_blocks_report(report).unwrap());
+        assert_eq!(registry.len(), 5);
+
+        let log = Palette::parse("minecraft:oak_log[axis=z]").unwrap();
+        assert_eq!(registry.id(&log), Some(4));
+        assert_eq!(registry.state(4), Some(&log));
+        assert_eq!(
+            registry.state(1),
+            Some(&Palette::parse("minecraft:stone").unwrap())
+        );
+        assert_eq!(registry.id(&Palette::parse("oak_log").unwrap()), Some(3));
+        assert_eq!(
+            registry.id(&Palette::parse("minecraft:oak_log[facing=up]").unwrap()),
+            None
+        );
+        assert_eq!(
+            registry.id(&Palette::parse("minecraft:dirt").unwrap()),
+            None
+        );
+        assert_eq!(registry.state(5), None);
+    }
+
+    #[test]
+    fn test_stale_cache_is_rejected() {
+        let registry = BlockStateRegistry {
+            states: vec![(0, Palette::parse("minecraft:air").unwrap())],
+            defaults: vec![0],
+        };
+        let bytes = registry.to_cache_bytes_for(b"old registry").unwrap();
+
+        assert_eq!(
+            BlockStateRegistry::from_cache_bytes_for(&bytes, b"old registry"),
+            Some(registry)
+        );
+        assert_eq!(
+            BlockStateRegistry::from_cache_bytes_for(&bytes, b"new registry"),
+            None
+        );
+        assert_eq!(
+            BlockStateRegistry::from_cache_bytes_for(&bytes[..10], b"old registry"),
+            None
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use crate::net::packets::outgoing::set_border_center::SetBorderCenter;
+use crate::net::packets::outgoing::set_border_lerp_size::SetBorderLerpSize;
+use crate::net::packets::outgoing::set_border_size::SetBorderSize;
+use crate::net::packets::outgoing::set_border_warning_delay::SetBorderWarningDelay;
+use crate::net::packets::outgoing::set_border_warning_distance::SetBorderWarningDistance;
+use crate::net::utils::broadcast::broadcast_in;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::prelude::*;
+use crate::world::dimension::Dimension;
+
+/// The world border's shape, along with how it warns and hurts players. Defaults to vanilla's.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BorderSettings {
+    pub center_x: f64,
+    pub center_z: f64,
+    /// The length of each side of the border.
+    pub diameter: f64,
+    /// The diameter the border is growing or shrinking to, if it's changing size.
+    pub resize: Option<BorderResize>,
+    /// Damage taken for every block past the safe zone.
+    pub damage_per_block: f64,
+    /// How far past the border players can go before they start taking damage.
+    pub damage_safe_zone: f64,
+    pub warning_blocks: i32,
+    /// In seconds.
+    pub warning_time: i32,
+}
+
+impl Default for BorderSettings {
+    fn default() -> Self {
+        Self {
+            center_x: 0.0,
+            center_z: 0.0,
+            diameter: 59_999_968.0,
+            resize: None,
+            damage_per_block: 0.2,
+            damage_safe_zone: 5.0,
+            warning_blocks: 5,
+            warning_time: 15,
+        }
+    }
+}
+
+/// A border growing or shrinking from one diameter to another at a steady pace.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BorderResize {
+    pub from: f64,
+    pub to: f64,
+    pub started: Instant,
+    pub duration: Duration,
+}
+
+impl BorderResize {
+    /// How long is left until the border is at its new diameter.
+    pub fn remaining(&self) -> Duration {
+        self.duration.saturating_sub(self.started.elapsed())
+    }
+}
+
+impl BorderSettings {
+    /// The border as it is at `now`: partway through its resize, or done with it.
+    pub fn at(mut self, now: Instant) -> Self {
+        let Some(resize) = self.resize else {
+            return self;
+        };
+        let elapsed = now.saturating_duration_since(resize.started);
+        if elapsed >= resize.duration {
+            self.diameter = resize.to;
+            self.resize = None;
+        } else {
+            let progress = elapsed.as_secs_f64() / resize.duration.as_secs_f64();
+            self.diameter = resize.from + (resize.to - resize.from) * progress;
+        }
+        self
+    }
+
+    /// How far inside the border a point is, negative once it's outside.
+    pub fn distance_inside(&self, x: f64, z: f64) -> f64 {
+        let radius = self.diameter / 2.0;
+        let to_west = x - (self.center_x - radius);
+        let to_east = (self.center_x + radius) - x;
+        let to_north = z - (self.center_z - radius);
+        let to_south = (self.center_z + radius) - z;
+        to_west.min(to_east).min(to_north).min(to_south)
+    }
+
+    /// The damage a player standing at the given point takes, 0 if they're close enough to the
+    /// border not to take any. Like vanilla, any damage is at least 1.
+    pub fn damage_at(&self, x: f64, z: f64) -> f32 {
+        let past_safe_zone = -(self.distance_inside(x, z) + self.damage_safe_zone);
+        if past_safe_zone <= 0.0 || self.damage_per_block <= 0.0 {
+            return 0.0;
+        }
+        (past_safe_zone * self.damage_per_block).floor().max(1.0) as f32
+    }
+
+    /// The closest point to `x`, `z` that's inside the border.
+    pub fn clamp(&self, x: f64, z: f64) -> (f64, f64) {
+        let radius = self.diameter / 2.0;
+        (
+            x.clamp(self.center_x - radius, self.center_x + radius),
+            z.clamp(self.center_z - radius, self.center_z + radius),
+        )
+    }
+
+    /// Where a move from `from` to `to` has to stop instead, if it goes out through the border.
+    /// Moves that start outside aren't stopped, so players the border moved past can get back in.
+    pub fn stop_at_border(&self, from: (f64, f64), to: (f64, f64)) -> Option<(f64, f64)> {
+        if self.distance_inside(from.0, from.1) < 0.0 || self.distance_inside(to.0, to.1) >= 0.0 {
+            return None;
+        }
+        Some(self.clamp(to.0, to.1))
+    }
+}
+
+/// The world border of every dimension. Each one has its own, which starts out as vanilla's.
+#[derive(Debug, Default)]
+pub struct WorldBorder {
+    dimensions: RwLock<HashMap<Dimension, BorderSettings>>,
+}
+
+impl WorldBorder {
+    /// A dimension's border as it is right now.
+    pub fn settings(&self, dimension: Dimension) -> BorderSettings {
+        self.dimensions
+            .read()
+            .unwrap()
+            .get(&dimension)
+            .copied()
+            .unwrap_or_default()
+            .at(Instant::now())
+    }
+
+    pub fn update(&self, dimension: Dimension, update: impl FnOnce(&mut BorderSettings)) {
+        let mut dimensions = self.dimensions.write().unwrap();
+        let settings = dimensions.entry(dimension).or_default();
+        *settings = settings.at(Instant::now());
+        update(settings);
+    }
+}
+
+impl ServerState {
+    /// Moves the center of a dimension's border, and tells the players in it.
+    pub async fn set_border_center(
+        self: &GlobalState,
+        dimension: Dimension,
+        x: f64,
+        z: f64,
+    ) -> Result<()> {
+        self.world_border.update(dimension, |settings| {
+            settings.center_x = x;
+            settings.center_z = z;
+        });
+        broadcast_in(&SetBorderCenter::new(x, z), self, dimension).await
+    }
+
+    /// Changes the diameter of a dimension's border, and tells the players in it. Over `duration`
+    /// if it isn't zero, otherwise straight away.
+    pub async fn set_border_diameter(
+        self: &GlobalState,
+        dimension: Dimension,
+        diameter: f64,
+        duration: Duration,
+    ) -> Result<()> {
+        let mut old_diameter = diameter;
+        self.world_border.update(dimension, |settings| {
+            old_diameter = settings.diameter;
+            if duration.is_zero() {
+                settings.diameter = diameter;
+                settings.resize = None;
+            } else {
+                settings.resize = Some(BorderResize {
+                    from: settings.diameter,
+                    to: diameter,
+                    started: Instant::now(),
+                    duration,
+                });
+            }
+        });
+
+        if duration.is_zero() {
+            broadcast_in(&SetBorderSize::new(diameter), self, dimension).await
+        } else {
+            let packet =
+                SetBorderLerpSize::new(old_diameter, diameter, duration.as_millis() as i64);
+            broadcast_in(&packet, self, dimension).await
+        }
+    }
+
+    /// Changes how close to a dimension's border players get warned, and tells the players in it.
+    pub async fn set_border_warning_distance(
+        self: &GlobalState,
+        dimension: Dimension,
+        warning_blocks: i32,
+    ) -> Result<()> {
+        self.world_border.update(dimension, |settings| {
+            settings.warning_blocks = warning_blocks
+        });
+        broadcast_in(
+            &SetBorderWarningDistance::new(warning_blocks),
+            self,
+            dimension,
+        )
+        .await
+    }
+
+    /// Changes how early players get warned about a dimension's border shrinking, and tells the
+    /// players in it.
+    pub async fn set_border_warning_time(
+        self: &GlobalState,
+        dimension: Dimension,
+        warning_time: i32,
+    ) -> Result<()> {
+        self.world_border
+            .update(dimension, |settings| settings.warning_time = warning_time);
+        broadcast_in(&SetBorderWarningDelay::new(warning_time), self, dimension).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_damage_starts_past_the_safe_zone() {
+        let border = BorderSettings {
+            diameter: 100.0,
+            ..Default::default()
+        };
+
+        assert_eq!(border.distance_inside(0.0, 0.0), 50.0);
+        assert_eq!(border.distance_inside(60.0, 0.0), -10.0);
+
+        assert_eq!(border.damage_at(0.0, 0.0), 0.0);
+        assert_eq!(border.damage_at(54.0, 0.0), 0.0);
+        // Just past the safe zone still hurts
+        assert_eq!(border.damage_at(56.0, 0.0), 1.0);
+        assert_eq!(border.damage_at(0.0, -80.0), 5.0);
+    }
+
+    #[test]
+    fn test_resize_moves_at_a_steady_pace() {
+        let started = Instant::now();
+        let border = BorderSettings {
+            diameter: 100.0,
+            resize: Some(BorderResize {
+                from: 100.0,
+                to: 20.0,
+                started,
+                duration: Duration::from_secs(10),
+            }),
+            ..Default::default()
+        };
+
+        let halfway = border.at(started + Duration::from_secs(5));
+        assert_eq!(halfway.diameter, 60.0);
+        assert!(halfway.resize.is_some());
+
+        let done = border.at(started + Duration::from_secs(11));
+        assert_eq!(done.diameter, 20.0);
+        assert_eq!(done.resize, None);
+    }
+
+    #[test]
+    fn test_clamp_and_dimensions() {
+        let border = BorderSettings {
+            center_x: 10.0,
+            diameter: 20.0,
+            ..Default::default()
+        };
+        assert_eq!(border.clamp(25.0, -3.0), (20.0, -3.0));
+        assert_eq!(border.clamp(-1.0, 14.0), (0.0, 10.0));
+
+        // Only moves from the inside out are stopped
+        assert_eq!(
+            border.stop_at_border((15.0, 0.0), (22.0, 1.0)),
+            Some((20.0, 1.0))
+        );
+        assert_eq!(border.stop_at_border((15.0, 0.0), (18.0, 1.0)), None);
+        assert_eq!(border.stop_at_border((25.0, 0.0), (30.0, 1.0)), None);
+
+        // Each dimension has a border of its own
+        let world_border = WorldBorder::default();
+        world_border.update(Dimension::Nether, |settings| *settings = border);
+        assert_eq!(world_border.settings(Dimension::Nether), border);
+        assert_eq!(
+            world_border.settings(Dimension::Overworld),
+            BorderSettings::default()
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use moka::future::Cache;
+use moka::ops::compute::Op;
+use tracing::{debug, warn};
+
+use crate::state::{GlobalState, ServerState};
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+use crate::world::chunk_format::Chunk;
+use crate::world::dimension::Dimension;
+
+/// A chunk's dimension and coordinates.
+pub type ChunkKey = (Dimension, i32, i32);
+
+#[derive(Clone)]
+struct CachedChunk {
+    chunk: Arc<Chunk>,
+    /// Changed since it was loaded, so it has to be saved before it's dropped.
+    dirty: bool,
+}
+
+/// The chunks that were loaded or generated recently, so the next player asking for them doesn't
+/// have to wait for the disk or the generator again.
+///
+/// Chunks are evicted once the cache is full, or once nobody has asked for them in a while.
+/// Evicted chunks that were changed are queued up, to be saved by [ServerState::save_evicted_chunks].
+pub struct ChunkCache {
+    chunks: Cache<ChunkKey, CachedChunk>,
+    evicted: Mutex<Receiver<Arc<Chunk>>>,
+    /// Chunks standing in for stored ones that couldn't be read. They're never saved, so the
+    /// originals stay on disk for someone to recover.
+    fallbacks: Arc<Mutex<HashSet<ChunkKey>>>,
+}
+
+impl ChunkCache {
+    pub fn new(capacity: u64, time_to_idle: Duration) -> Self {
+        let (evicted_tx, evicted_rx) = mpsc::channel();
+        let evicted_tx = Mutex::new(evicted_tx);
+        let fallbacks = Arc::new(Mutex::new(HashSet::new()));
+        let unsaved = Arc::clone(&fallbacks);
+        let chunks = Cache::builder()
+            .max_capacity(capacity)
+            .time_to_idle(time_to_idle)
+            .eviction_listener(move |key: Arc<ChunkKey>, value: CachedChunk, cause| {
+                if !value.dirty || !cause.was_evicted() {
+                    return;
+                }
+                if unsaved.lock().unwrap().contains(&*key) {
+                    debug!(
+                        "Evicted fallback chunk {:?}, leaving the stored one alone",
+                        key
+                    );
+                    return;
+                }
+                debug!("Evicted changed chunk {:?}, queueing it to be saved", key);
+                let _ = evicted_tx.lock().unwrap().send(value.chunk);
+            })
+            .build();
+
+        Self {
+            chunks,
+            evicted: Mutex::new(evicted_rx),
+            fallbacks,
+        }
+    }
+
+    /// A cache sized by the server config.
+    pub fn configured() -> Self {
+        let config = get_global_config();
+        Self::new(
+            config.chunk_cache_capacity,
+            Duration::from_secs(config.chunk_cache_ttl_secs),
+        )
+    }
+
+    pub async fn get(&self, key: ChunkKey) -> Option<Arc<Chunk>> {
+        self.chunks.get(&key).await.map(|cached| cached.chunk)
+    }
+
+    /// Caches a chunk exactly as it was loaded or generated.
+    ///
+    /// A changed chunk that's still cached is left alone, so reloading it can't throw the
+    /// changes away.
+    pub async fn insert(&self, dimension: Dimension, chunk: Chunk) -> Arc<Chunk> {
+        let key = (dimension, chunk.x_pos, chunk.z_pos);
+        let chunk = Arc::new(chunk);
+        self.chunks
+            .entry(key)
+            .or_insert_with(async {
+                CachedChunk {
+                    chunk: chunk.clone(),
+                    dirty: false,
+                }
+            })
+            .await
+            .into_value()
+            .chunk
+    }
+
+    /// Caches a chunk standing in for a stored one that couldn't be read. It can be changed like
+    /// any other chunk, but it's never saved, so it can't overwrite what's on disk.
+    pub async fn insert_fallback(&self, dimension: Dimension, chunk: Chunk) -> Arc<Chunk> {
+        let key = (dimension, chunk.x_pos, chunk.z_pos);
+        self.fallbacks.lock().unwrap().insert(key);
+        self.insert(dimension, chunk).await
+    }
+
+    /// Replaces a cached chunk with a changed version of it, which gets saved once it's evicted.
+    pub async fn update(&self, dimension: Dimension, chunk: Chunk) {
+        let key = (dimension, chunk.x_pos, chunk.z_pos);
+        let cached = CachedChunk {
+            chunk: Arc::new(chunk),
+            dirty: true,
+        };
+        self.chunks.insert(key, cached).await;
+    }
+
+    /// Evicts whatever is due to be evicted, and takes the changed chunks among them.
+    pub async fn take_evicted(&self) -> Vec<Arc<Chunk>> {
+        self.chunks.run_pending_tasks().await;
+        self.evicted.lock().unwrap().try_iter().collect()
+    }
+
+    /// Every changed chunk that's still in the cache, apart from the fallback ones.
+    pub fn dirty_chunks(&self) -> Vec<(ChunkKey, Arc<Chunk>)> {
+        let fallbacks = self.fallbacks.lock().unwrap();
+        self.chunks
+            .iter()
+            .filter(|(key, cached)| cached.dirty && !fallbacks.contains(&**key))
+            .map(|(key, cached)| (*key, cached.chunk))
+            .collect()
+    }
+
+    /// Marks a chunk from [ChunkCache::dirty_chunks] as saved, unless it changed again since.
+    pub async fn mark_saved(&self, key: ChunkKey, saved: &Arc<Chunk>) {
+        let saved = saved.clone();
+        self.chunks
+            .entry(key)
+            .and_compute_with(|entry| async move {
+                match entry {
+                    Some(entry) if Arc::ptr_eq(&entry.value().chunk, &saved) => {
+                        Op::Put(CachedChunk {
+                            chunk: saved,
+                            dirty: false,
+                        })
+                    }
+                    _ => Op::Nop,
+                }
+            })
+            .await;
+    }
+
+    pub fn len(&self) -> u64 {
+        self.chunks.entry_count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ServerState {
+    /// Saves the changed chunks that were evicted from the chunk cache since the last call.
+    /// Chunks that fail to save go back into the cache, to be tried again once they're evicted.
+    pub async fn save_evicted_chunks(self: &GlobalState) -> Result<()> {
+        for chunk in self.chunk_cache.take_evicted().await {
+            if let Err(e) = self.save_chunk(&chunk).await {
+                warn!(
+                    "Failed to save chunk {}, {}: {}",
+                    chunk.x_pos, chunk.z_pos, e
+                );
+                let dimension = chunk
+                    .dimension
+                    .as_deref()
+                    .map(Dimension::from_name)
+                    .unwrap_or_default();
+                self.chunk_cache
+                    .update(dimension, Arc::unwrap_or_clone(chunk))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves every changed chunk, whether it's still in the chunk cache or not. Chunks that fail
+    /// to save stay marked as changed, so they're tried again next time.
+    pub async fn save_all_chunks(self: &GlobalState) -> Result<()> {
+        // Anything being written in the background is older than what's saved here
+        self.wait_for_background_save().await;
+        self.save_evicted_chunks().await?;
+        for (key, chunk) in self.chunk_cache.dirty_chunks() {
+            match self.save_chunk(&chunk).await {
+                Ok(()) => self.chunk_cache.mark_saved(key, &chunk).await,
+                Err(e) => warn!("Failed to save chunk {:?}: {}", key, e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_loaded_chunks_are_cached() {
+        let cache = ChunkCache::new(16, Duration::from_secs(60));
+        assert!(cache.get((Dimension::Overworld, 1, 2)).await.is_none());
+
+        let chunk = cache.insert(Dimension::Overworld, Chunk::empty(1, 2)).await;
+        let cached = cache.get((Dimension::Overworld, 1, 2)).await.unwrap();
+        assert!(Arc::ptr_eq(&chunk, &cached));
+        assert!(cache.get((Dimension::Nether, 1, 2)).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_changed_chunks_are_kept_until_saved() {
+        let cache = ChunkCache::new(16, Duration::from_secs(60));
+        let mut changed = Chunk::empty(0, 0);
+        changed.inhabited_time = Some(1);
+        cache.update(Dimension::Overworld, changed.clone()).await;
+
+        // Loading it again doesn't replace the changed version
+        cache.insert(Dimension::Overworld, Chunk::empty(0, 0)).await;
+        let cached = cache.get((Dimension::Overworld, 0, 0)).await.unwrap();
+        assert_eq!(*cached, changed);
+
+        let dirty = cache.dirty_chunks();
+        assert_eq!(dirty.len(), 1);
+
+        // Changing it again while it's being saved keeps the new change
+        let (key, saved) = dirty.into_iter().next().unwrap();
+        cache.update(Dimension::Overworld, changed).await;
+        cache.mark_saved(key, &saved).await;
+        assert_eq!(cache.dirty_chunks().len(), 1);
+
+        let (key, saved) = cache.dirty_chunks().into_iter().next().unwrap();
+        cache.mark_saved(key, &saved).await;
+        assert!(cache.dirty_chunks().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_idle_changed_chunks_are_evicted_for_saving() {
+        let cache = ChunkCache::new(16, Duration::from_millis(20));
+        cache.insert(Dimension::Overworld, Chunk::empty(0, 0)).await;
+        cache.update(Dimension::Overworld, Chunk::empty(1, 0)).await;
+        assert!(cache.take_evicted().await.is_empty());
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let evicted = cache.take_evicted().await;
+        assert_eq!(evicted.len(), 1);
+        assert_eq!((evicted[0].x_pos, evicted[0].z_pos), (1, 0));
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fallback_chunks_are_never_saved() {
+        let cache = ChunkCache::new(16, Duration::from_millis(20));
+        cache
+            .insert_fallback(Dimension::Overworld, Chunk::empty(0, 0))
+            .await;
+        cache.update(Dimension::Overworld, Chunk::empty(0, 0)).await;
+        cache.update(Dimension::Overworld, Chunk::empty(1, 0)).await;
+        assert_eq!(cache.dirty_chunks().len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let evicted = cache.take_evicted().await;
+        assert_eq!(evicted.len(), 1);
+        assert_eq!((evicted[0].x_pos, evicted[0].z_pos), (1, 0));
+    }
+}
@@ -0,0 +1,190 @@
+//! Decoding of the `data` field of the chunk data packet.
+//!
+//! The field is a single length-prefixed blob holding every section of the chunk back to back,
+//! with nothing marking where one section ends and the next starts. The only way to split it up is
+//! to parse each section in turn, so the number of sections has to come from the dimension.
+
+use std::io::ErrorKind;
+
+use ferrumc_codec::error::CodecError;
+use ferrumc_codec::network_types::varint::VarInt;
+
+use crate::utils::prelude::*;
+
+/// Block palettes with more bits per entry than this use global ids directly.
+const MAX_INDIRECT_BLOCK_BITS: u8 = 8;
+/// Biome palettes with more bits per entry than this use global ids directly.
+const MAX_INDIRECT_BIOME_BITS: u8 = 3;
+
+/// A block state or biome container as sent over the network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PalettedContainer {
+    pub bits_per_entry: u8,
+    /// One entry for a single valued container, empty if the data holds global ids.
+    pub palette: Vec<i32>,
+    pub data: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetSection {
+    pub block_count: i16,
+    pub block_states: PalettedContainer,
+    pub biomes: PalettedContainer,
+}
+
+/// Splits a chunk data blob into exactly `section_count` sections.
+///
+/// Returns [Error::ChunkDataUnderrun] if the blob runs out before all the sections were read.
+/// Anything after the last section is left alone.
+pub fn decode_sections(data: &[u8], section_count: usize) -> Result<Vec<NetSection>> {
+    let mut reader = SectionReader {
+        data,
+        offset: 0,
+        sections_read: 0,
+        section_count,
+    };
+
+    let mut sections = Vec::with_capacity(section_count);
+    while sections.len() < section_count {
+        let block_count = i16::from_be_bytes(reader.array()?);
+        let block_states = reader.paletted_container(MAX_INDIRECT_BLOCK_BITS)?;
+        let biomes = reader.paletted_container(MAX_INDIRECT_BIOME_BITS)?;
+        sections.push(NetSection {
+            block_count,
+            block_states,
+            biomes,
+        });
+        reader.sections_read += 1;
+    }
+
+    Ok(sections)
+}
+
+struct SectionReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+    sections_read: usize,
+    section_count: usize,
+}
+
+impl<'a> SectionReader<'a> {
+    fn underrun(&self) -> Error {
+        Error::ChunkDataUnderrun(self.sections_read, self.section_count)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| self.underrun())?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    fn varint(&mut self) -> Result<i32> {
+        match VarInt::from_bytes(&self.data[self.offset..]) {
+            Ok((value, len)) => {
+                self.offset += len;
+                Ok(value.get_val())
+            }
+            Err(CodecError::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => Err(self.underrun()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Reads a length prefix, making sure it isn't negative.
+    fn length(&mut self) -> Result<usize> {
+        let length = self.varint()?;
+        usize::try_from(length)
+            .map_err(|_| Error::Generic(format!("Invalid length in chunk data: {}", length)))
+    }
+
+    fn paletted_container(&mut self, max_indirect_bits: u8) -> Result<PalettedContainer> {
+        let [bits_per_entry] = self.array()?;
+        let palette = match bits_per_entry {
+            0 => vec![self.varint()?],
+            bits if bits <= max_indirect_bits => {
+                let len = self.length()?;
+                // Every entry takes at least a byte, so a bogus length can't allocate much
+                let mut palette = Vec::with_capacity(len.min(self.data.len() - self.offset));
+                for _ in 0..len {
+                    palette.push(self.varint()?);
+                }
+                palette
+            }
+            _ => Vec::new(),
+        };
+
+        let len = self.length()?;
+        let data = self
+            .bytes(len.checked_mul(8).ok_or_else(|| self.underrun())?)?
+            .chunks_exact(8)
+            .map(|long| i64::from_be_bytes(long.try_into().unwrap()))
+            .collect();
+
+        Ok(PalettedContainer {
+            bits_per_entry,
+            palette,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty section: no blocks, all air, all plains.
+    const AIR_SECTION: [u8; 8] = [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00];
+
+    #[test]
+    fn test_decodes_exact_section_count() {
+        let mut data = AIR_SECTION.to_vec();
+        // One stone block: indirect palette [air, stone] and a single long of data
+        data.extend_from_slice(&[0x00, 0x01, 0x04, 0x02, 0x00, 0x01, 0x01]);
+        data.extend_from_slice(&16i64.to_be_bytes());
+        data.extend_from_slice(&[0x00, 0x01, 0x00]);
+        // Not part of the chunk
+        data.push(0xAB);
+
+        let sections = decode_sections(&data, 2).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].block_states.palette, vec![0]);
+        assert_eq!(sections[1].block_count, 1);
+        assert_eq!(sections[1].block_states.bits_per_entry, 4);
+        assert_eq!(sections[1].block_states.palette, vec![0, 1]);
+        assert_eq!(sections[1].block_states.data, vec![16]);
+        assert_eq!(sections[1].biomes.palette, vec![1]);
+    }
+
+    #[test]
+    fn test_truncated_chunk_data_underruns() {
+        let mut data = AIR_SECTION.repeat(2);
+        data.truncate(AIR_SECTION.len() + 5);
+        assert!(matches!(
+            decode_sections(&data, 2),
+            Err(Error::ChunkDataUnderrun(1, 2))
+        ));
+
+        // Cut off in the middle of a long
+        let mut data = vec![0x00, 0x01, 0x04, 0x01, 0x01, 0x01];
+        data.extend_from_slice(&[0x00; 5]);
+        assert!(matches!(
+            decode_sections(&data, 1),
+            Err(Error::ChunkDataUnderrun(0, 1))
+        ));
+
+        assert!(matches!(
+            decode_sections(&AIR_SECTION, 24),
+            Err(Error::ChunkDataUnderrun(1, 24))
+        ));
+    }
+}
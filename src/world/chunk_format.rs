@@ -4,6 +4,8 @@ use ferrumc_codec::network_types::varint::VarInt;
 use serde_derive::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+use crate::world::block_entities::ChunkBlockEntity;
+
 attribute_alias! {
     #[apply(ChunkDerives)] = #[derive(nbt_lib::NBTSerialize, nbt_lib::NBTDeserialize,
     Debug,
@@ -43,6 +45,8 @@ pub struct Chunk {
     #[nbt(rename = "LastUpdate")]
     pub last_update: Option<i64>,
     pub sections: Option<Vec<Section>>,
+    /// Chests, signs, banners and the like, which need more than a block state.
+    pub block_entities: Option<Vec<ChunkBlockEntity>>,
 }
 
 #[apply(ChunkDerives)]
@@ -103,6 +107,10 @@ pub struct BlockStates {
     pub net_palette: Option<Vec<VarInt>>,
 }
 
+/// A single block state, e.g. `minecraft:oak_stairs` with `facing=east` and `half=top`.
+///
+/// Any property a block can have is stored as a plain key-value pair, the same way vanilla
+/// stores them on disk.
 #[apply(ChunkDerives)]
 #[derive(deepsize::DeepSizeOf, Hash)]
 pub struct Palette {
@@ -112,25 +120,10 @@ pub struct Palette {
     pub properties: Option<BTreeMap<String, String>>,
 }
 
-#[apply(ChunkDerives)]
-#[derive(deepsize::DeepSizeOf)]
-pub struct Properties {
-    pub snowy: Option<String>,
-    pub level: Option<String>,
-    pub east: Option<String>,
-    pub waterlogged: Option<String>,
-    pub north: Option<String>,
-    pub west: Option<String>,
-    pub up: Option<String>,
-    pub down: Option<String>,
-    pub south: Option<String>,
-    pub drag: Option<String>,
-    pub lit: Option<String>,
-    pub axis: Option<String>,
-}
-
 #[apply(ChunkDerives)]
 #[derive(deepsize::DeepSizeOf)]
 pub struct Biomes {
     pub palette: Vec<String>,
+    /// Which palette entry each 4x4x4 cell uses. Missing if the palette only has one biome.
+    pub data: Option<Vec<i64>>,
 }
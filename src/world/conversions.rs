@@ -1,28 +1,106 @@
 use crate::utils::error::Error;
-use crate::world::chunk_format::{BlockStates, Chunk, Palette, Section};
+use crate::world::block_registry::block_registry;
+use crate::world::chunk_format::{Biomes, BlockStates, Chunk, Heightmaps, Palette, Section};
+use crate::world::dimension::Dimension;
+use crate::world::heightmap::{pack_heightmap_in, HEIGHTMAP_COLUMNS};
+use crate::world::palette::NetContainer;
 use ferrumc_codec::enc::{EncodeOption, NetEncode};
 use ferrumc_codec::network_types::varint::VarInt;
-use hashbrown::HashMap;
-use lazy_static::lazy_static;
-use std::io::Read;
+use nbt_lib::NBTDeserializeBytes;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::Cursor;
 use tokio::io::AsyncWrite;
 use tracing::trace;
 
-const BLOCKSFILE: &[u8] = include_bytes!("../../.etc/blockmappings.bz2");
-
-lazy_static! {
-    static ref ID2BLOCK: HashMap<i32, Palette> = {
-        let mut bzipreader = bzip2::read::BzDecoder::new(BLOCKSFILE);
-        let mut output = String::new();
-        bzipreader.read_to_string(&mut output).unwrap();
-        let string_keys: HashMap<String, Palette> = serde_json::from_str(&output).unwrap();
-        string_keys
-            .iter()
-            .map(|(k, v)| (k.parse::<i32>().unwrap(), v.clone()))
-            .collect()
-    };
-    static ref BLOCK2ID: HashMap<Palette, i32> =
-        ID2BLOCK.iter().map(|(k, v)| (v.clone(), *k)).collect();
+/// The number of block states in the registry, including air.
+///
+/// Block state ids are contiguous, so every id below this is a valid block state.
+pub fn block_state_count() -> usize {
+    block_registry().len()
+}
+
+/// Like [Palette::is_kind], for a block name that's already had its namespace taken off.
+pub fn is_kind(name: &str, kinds: &[&str]) -> bool {
+    kinds.iter().any(|kind| {
+        name.strip_suffix(kind)
+            .is_some_and(|rest| rest.is_empty() || rest.ends_with('_'))
+    })
+}
+
+impl Palette {
+    /// Parses a block state in the same format as commands use, e.g.
+    /// `minecraft:oak_stairs[facing=east,half=top]`. The namespace defaults to `minecraft`.
+    pub fn parse(block_state: &str) -> Result<Self, Error> {
+        let invalid = || Error::Generic(format!("Invalid block state: {}", block_state));
+
+        let (name, properties) = match block_state.split_once('[') {
+            Some((name, rest)) => (name, Some(rest.strip_suffix(']').ok_or_else(invalid)?)),
+            None => (block_state, None),
+        };
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        let name = if name.contains(':') {
+            name.to_string()
+        } else {
+            format!("minecraft:{}", name)
+        };
+
+        let properties = match properties {
+            Some(properties) if !properties.trim().is_empty() => Some(
+                properties
+                    .split(',')
+                    .map(|pair| {
+                        let (key, value) = pair.split_once('=').ok_or_else(invalid)?;
+                        Ok((key.trim().to_string(), value.trim().to_string()))
+                    })
+                    .collect::<Result<BTreeMap<_, _>, Error>>()?,
+            ),
+            _ => None,
+        };
+
+        Ok(Palette { name, properties })
+    }
+
+    /// The block's name without the `minecraft:` namespace.
+    pub fn short_name(&self) -> &str {
+        self.name.strip_prefix("minecraft:").unwrap_or(&self.name)
+    }
+
+    /// Whether the block is one of `kinds` or a variant of one, going by its name. Variants are
+    /// matched on whole words at the end of the name, so `oak_wall_sign` is a `sign` but
+    /// `dried_kelp_block` isn't `kelp`.
+    pub fn is_kind(&self, kinds: &[&str]) -> bool {
+        is_kind(self.short_name(), kinds)
+    }
+
+    /// Looks up the network ID of this block state, see [BlockRegistry::id].
+    ///
+    /// [BlockRegistry::id]: crate::world::block_registry::BlockRegistry::id
+    pub fn block_id(&self) -> Option<i32> {
+        block_registry().id(self)
+    }
+
+    /// Looks up the block state with the given network ID.
+    pub fn from_block_id(id: i32) -> Option<Self> {
+        block_registry().state(id).cloned()
+    }
+}
+
+impl Display for Palette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(properties) = self.properties.as_ref().filter(|p| !p.is_empty()) {
+            let properties = properties
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(",");
+            write!(f, "[{}]", properties)?;
+        }
+        Ok(())
+    }
 }
 
 impl Section {
@@ -38,6 +116,62 @@ impl Section {
 }
 
 impl Chunk {
+    /// Creates an overworld chunk made entirely of air, already in network mode.
+    ///
+    /// Used in place of chunks that couldn't be read from disk.
+    pub fn empty(x_pos: i32, z_pos: i32) -> Self {
+        Self::empty_in(Dimension::Overworld, x_pos, z_pos)
+    }
+
+    /// Creates a chunk made entirely of air in a dimension, with a section for every 16 blocks of
+    /// its height.
+    pub fn empty_in(dimension: Dimension, x_pos: i32, z_pos: i32) -> Self {
+        let sections = dimension
+            .section_range()
+            .map(|y| {
+                let mut section = Section {
+                    block_states: None,
+                    biomes: Some(Biomes {
+                        palette: vec![dimension.default_biome().to_string()],
+                        data: None,
+                    }),
+                    y,
+                    block_light: None,
+                    sky_light: None,
+                };
+                section.set_empty();
+                section
+            })
+            .collect();
+
+        let heightmap = pack_heightmap_in(&[dimension.min_y(); HEIGHTMAP_COLUMNS], dimension);
+        Chunk {
+            dimension: Some(dimension.short_name().to_string()),
+            status: "full".to_string(),
+            data_version: 3465,
+            heightmaps: Some(Heightmaps {
+                motion_blocking: Some(heightmap.clone()),
+                world_surface: Some(heightmap),
+            }),
+            is_light_on: Some(1),
+            inhabited_time: Some(0),
+            y_pos: dimension.min_y() >> 4,
+            x_pos,
+            z_pos,
+            structures: None,
+            last_update: Some(0),
+            sections: Some(sections),
+            block_entities: None,
+        }
+    }
+
+    /// Reads a chunk from its disk NBT and converts it to network mode.
+    pub fn from_nbt(data: Vec<u8>) -> Result<Self, Error> {
+        let mut chunk = Chunk::read_from_bytes(&mut Cursor::new(data))?;
+        chunk.convert_to_net_mode()?;
+        Ok(chunk)
+    }
+
     /// Converts a chunk in the disk format to the network format
     pub fn convert_to_net_mode(&mut self) -> Result<(), Error> {
         // This looks ugly, but it's the best way I could think of to do the error checking
@@ -75,6 +209,11 @@ impl Chunk {
                     let mut non_air_blocks = 4096i16;
                     // This is just for readability
                     let air_id = 0i32;
+                    // Sections that are already in network mode, like the ones in generated
+                    // chunks, are left as they are
+                    if block_states.palette.is_none() && block_states.net_palette.is_some() {
+                        continue;
+                    }
                     // If the palette is missing, we can't do anything and it's actually fucked
                     if block_states.palette.is_none() {
                         return Err(Error::InvalidChunk(
@@ -84,12 +223,21 @@ impl Chunk {
                         ));
                     }
 
+                    // Merge duplicate palette entries before working out the bits per block
+                    block_states.dedup_palette();
+
                     let palette = block_states.palette.as_mut().unwrap();
 
-                    // TODO: Adapt this for single block sections
                     if block_states.data.is_some() {
                         let bits_per_entry = (palette.len() as f32).log2().ceil() as i8;
                         block_states.bits_per_block = Some(bits_per_entry.max(4));
+                    } else if palette.len() == 1 && palette[0].block_id() == Some(air_id) {
+                        // All air sections look the same however they were stored, so they
+                        // compare equal with the ones made by `set_empty`
+                        set_empty = true;
+                    } else if palette.len() == 1 {
+                        // The whole section is one block, which doesn't need any data
+                        block_states.bits_per_block = Some(0);
                     } else {
                         trace!("No data found in section at {}", section.y);
                         set_empty = true;
@@ -103,11 +251,8 @@ impl Chunk {
                     // in place cos of type differences so we'll just make a new vec, iterate over the
                     // block states and push the block IDs to the new vec, then clear the old one.
                     for palette_entry in palette.iter() {
-                        if BLOCK2ID.contains_key(palette_entry) {
+                        if let Some(block_id) = palette_entry.block_id() {
                             if let Some(checked_palette) = block_states.net_palette.as_mut() {
-                                let block_id = *BLOCK2ID
-                                    .get(palette_entry)
-                                    .expect("Block not found in block mappings");
                                 // If the block is air, decrease the non-air blocks count
                                 if block_id == air_id {
                                     non_air_blocks -= 1;
@@ -120,10 +265,14 @@ impl Chunk {
                             return Err(Error::InvalidChunk(
                                 self.x_pos,
                                 self.z_pos,
-                                format!("Block {} not found in block mappings", palette_entry.name),
+                                format!("Block {} not found in block mappings", palette_entry),
                             ));
                         }
                     }
+                    // A section of a single block is either all air or has no air at all
+                    if block_states.data.is_none() && non_air_blocks < 4096 {
+                        non_air_blocks = 0;
+                    }
                     // This should never happen but if it does, we got some major problems to sort out
                     if non_air_blocks < 0 {
                         return Err(Error::InvalidChunk(
@@ -143,6 +292,58 @@ impl Chunk {
 
         Ok(())
     }
+
+    /// Makes a copy of this chunk in the form it's stored on disk.
+    ///
+    /// Sections built in network mode only have a network palette, so their disk palette is
+    /// looked up from the block IDs. The packed data is the same in both forms, so it's kept as
+    /// is. The network-only fields are dropped so they don't end up in the saved NBT.
+    pub fn to_disk_mode(&self) -> Result<Chunk, Error> {
+        let mut chunk = self.clone();
+        for section in chunk.sections.iter_mut().flatten() {
+            let Some(block_states) = section.block_states.as_mut() else {
+                continue;
+            };
+            if block_states.palette.is_none() {
+                let net_palette = block_states.net_palette.as_deref().unwrap_or_default();
+                let palette = net_palette
+                    .iter()
+                    .map(|id| {
+                        Palette::from_block_id(id.get_val()).ok_or_else(|| {
+                            Error::InvalidChunk(
+                                self.x_pos,
+                                self.z_pos,
+                                format!("Block ID {} not found in block mappings", id.get_val()),
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                block_states.palette = Some(palette);
+            }
+            block_states.non_air_blocks = None;
+            block_states.bits_per_block = None;
+            block_states.net_palette = None;
+        }
+        Ok(chunk)
+    }
+}
+
+/// Writes a data array: its length, then the longs.
+async fn write_longs<W>(
+    data: &[i64],
+    writer: &mut W,
+    encode_option: &EncodeOption,
+) -> ferrumc_codec::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    VarInt::from(data.len() as i32)
+        .net_encode(writer, encode_option)
+        .await?;
+    for long in data {
+        long.net_encode(writer, encode_option).await?;
+    }
+    Ok(())
 }
 
 impl NetEncode for Section {
@@ -166,28 +367,31 @@ impl NetEncode for Section {
             }
 
             // Blocks
-            let bpe = block_states.bits_per_block.unwrap_or(15);
-            bpe.net_encode(writer, encode_option).await?;
-
-            VarInt::from(block_states.net_palette.as_ref().unwrap().len() as i32)
-                .net_encode(writer, encode_option)
-                .await?;
-            block_states
-                .net_palette
-                .as_ref()
-                .expect("Palette is missing")
-                .net_encode(writer, encode_option)
-                .await?;
-
-            if let Some(data) = &block_states.data {
-                VarInt::from(data.len() as i32)
-                    .net_encode(writer, encode_option)
-                    .await?;
-                for long in block_states.data.as_ref().unwrap() {
-                    long.net_encode(writer, encode_option).await?;
+            match block_states.net_container() {
+                Some(NetContainer::SingleValue { id }) => {
+                    0u8.net_encode(writer, encode_option).await?;
+                    id.net_encode(writer, encode_option).await?;
+                    VarInt::from(0).net_encode(writer, encode_option).await?;
                 }
-            } else {
-                VarInt::from(0).net_encode(writer, encode_option).await?;
+                Some(NetContainer::Indirect {
+                    bits,
+                    palette,
+                    data,
+                }) => {
+                    bits.net_encode(writer, encode_option).await?;
+                    VarInt::from(palette.len() as i32)
+                        .net_encode(writer, encode_option)
+                        .await?;
+                    for id in palette {
+                        id.net_encode(writer, encode_option).await?;
+                    }
+                    write_longs(data, writer, encode_option).await?;
+                }
+                Some(NetContainer::Direct { bits, data }) => {
+                    bits.net_encode(writer, encode_option).await?;
+                    write_longs(&data, writer, encode_option).await?;
+                }
+                None => panic!("Palette is missing"),
             }
 
             /*// Biomes
@@ -197,3 +401,53 @@ impl NetEncode for Section {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_block_state_resolves() {
+        let stairs = Palette::parse("minecraft:oak_stairs[facing=east,half=top]").unwrap();
+        assert_eq!(
+            stairs.to_string(),
+            "minecraft:oak_stairs[facing=east,half=top]"
+        );
+
+        let id = stairs.block_id().expect("oak stairs should resolve");
+        assert_eq!(id, 2935);
+
+        let resolved = Palette::from_block_id(id).unwrap();
+        assert_eq!(
+            resolved.to_string(),
+            "minecraft:oak_stairs[facing=east,half=top,shape=straight,waterlogged=false]"
+        );
+        assert_eq!(resolved.block_id(), Some(id));
+    }
+
+    #[test]
+    fn test_block_state_lookup() {
+        assert_eq!(Palette::parse("air").unwrap().block_id(), Some(0));
+        assert_eq!(
+            Palette::parse("minecraft:oak_log[axis=z]")
+                .unwrap()
+                .block_id(),
+            Some(132)
+        );
+        assert_eq!(
+            Palette::parse("minecraft:oak_stairs[facing=up]")
+                .unwrap()
+                .block_id(),
+            None
+        );
+        assert!(Palette::parse("minecraft:oak_stairs[facing=east").is_err());
+        assert!(Palette::parse("minecraft:oak_stairs[facing]").is_err());
+    }
+
+    #[test]
+    fn test_malformed_chunk_is_an_error() {
+        // A compound tag header followed by garbage
+        let malformed = vec![0x0A, 0x00, 0x00, 0x63, 0xFF, 0x12, 0x00];
+        assert!(Chunk::from_nbt(malformed).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use std::path::{Path, PathBuf};
+
+use ferrumc_macros::Component;
+
+use crate::net::packets::outgoing::initialize_world_border::InitializeWorldBorder;
+use crate::net::packets::outgoing::respawn::Respawn;
+use crate::net::packets::outgoing::synchronize_player_position::SynchronizePlayerPosition;
+use crate::net::systems::chunk_sender::ChunkSender;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::loaded_chunks::LoadedChunks;
+use crate::utils::components::precise_position::PrecisePosition;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::components::visible_entities::VisibleEntities;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// The vanilla dimensions, along with the parts of their dimension type the server cares about.
+///
+/// Also a component, for the dimension a player is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Component)]
+pub enum Dimension {
+    #[default]
+    Overworld,
+    Nether,
+    End,
+}
+
+impl Dimension {
+    pub const ALL: [Dimension; 3] = [Dimension::Overworld, Dimension::Nether, Dimension::End];
+
+    /// Looks a dimension up by name, with or without the `minecraft:` namespace. Unknown names
+    /// are treated as the overworld.
+    pub fn from_name(name: &str) -> Self {
+        Self::lookup(name).unwrap_or_default()
+    }
+
+    /// Looks a dimension up by name, with or without the `minecraft:` namespace. `None` if there's
+    /// no dimension with that name.
+    pub fn lookup(name: &str) -> Option<Self> {
+        match name.strip_prefix("minecraft:").unwrap_or(name) {
+            "overworld" => Some(Dimension::Overworld),
+            "the_nether" => Some(Dimension::Nether),
+            "the_end" => Some(Dimension::End),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dimension::Overworld => "minecraft:overworld",
+            Dimension::Nether => "minecraft:the_nether",
+            Dimension::End => "minecraft:the_end",
+        }
+    }
+
+    /// The name without the namespace, which is what chunks are stored under.
+    pub fn short_name(self) -> &'static str {
+        self.name().trim_start_matches("minecraft:")
+    }
+
+    /// Whether the sky lights up the dimension. When it doesn't, there's no sky light to send.
+    pub fn has_skylight(self) -> bool {
+        self != Dimension::Nether
+    }
+
+    /// Whether it can rain in the dimension.
+    pub fn has_weather(self) -> bool {
+        self == Dimension::Overworld
+    }
+
+    /// The lowest block in the dimension.
+    pub fn min_y(self) -> i32 {
+        match self {
+            Dimension::Overworld => -64,
+            Dimension::Nether | Dimension::End => 0,
+        }
+    }
+
+    /// How many blocks tall the dimension is.
+    pub fn height(self) -> i32 {
+        match self {
+            Dimension::Overworld => 384,
+            Dimension::Nether | Dimension::End => 256,
+        }
+    }
+
+    /// The highest block in the dimension.
+    pub fn max_y(self) -> i32 {
+        self.min_y() + self.height() - 1
+    }
+
+    /// The number of sections in a chunk column.
+    pub fn section_count(self) -> usize {
+        self.height() as usize / 16
+    }
+
+    /// The `y` of the lowest and highest sections in a chunk column.
+    pub fn section_range(self) -> std::ops::RangeInclusive<i8> {
+        (self.min_y() >> 4) as i8..=(self.max_y() >> 4) as i8
+    }
+
+    /// The biome chunks that are missing theirs get.
+    pub fn default_biome(self) -> &'static str {
+        match self {
+            Dimension::Overworld => "minecraft:plains",
+            Dimension::Nether => "minecraft:nether_wastes",
+            Dimension::End => "minecraft:the_end",
+        }
+    }
+
+    /// Where the dimension's region files are, given the overworld's. Vanilla keeps the nether and
+    /// the end next to it, in `DIM-1/region` and `DIM1/region`.
+    pub fn region_dir(self, overworld_dir: &Path) -> PathBuf {
+        let world_dir = overworld_dir.parent().unwrap_or(Path::new(""));
+        match self {
+            Dimension::Overworld => overworld_dir.to_path_buf(),
+            Dimension::Nether => world_dir.join("DIM-1").join("region"),
+            Dimension::End => world_dir.join("DIM1").join("region"),
+        }
+    }
+}
+
+impl ServerState {
+    /// The dimension an entity is in. Entities that haven't been put in one are in the overworld.
+    pub async fn dimension_of(self: &GlobalState, entity_id: usize) -> Dimension {
+        self.world
+            .get_component::<Dimension>(entity_id)
+            .await
+            .map(|dimension| *dimension)
+            .unwrap_or_default()
+    }
+
+    /// Moves a player to `position` in another dimension.
+    ///
+    /// Their client throws away its chunks and entities when it's told, so the chunks around the
+    /// new position are sent again, along with the dimension's world border and weather, and the
+    /// entities they can see are tracked from scratch. Other players stop seeing them the next time
+    /// the entity tracker runs.
+    pub async fn change_dimension(
+        self: &GlobalState,
+        entity_id: usize,
+        dimension: Dimension,
+        position: Position,
+    ) -> Result<()> {
+        let game_mode = self
+            .world
+            .get_component::<GameMode>(entity_id)
+            .await
+            .map(|game_mode| *game_mode)
+            .unwrap_or_default();
+        let rotation = self
+            .world
+            .get_component::<Rotation>(entity_id)
+            .await?
+            .clone();
+
+        let component_storage = self.world.get_component_storage();
+        component_storage
+            .get_mut_or_insert_with::<LoadedChunks>(entity_id, Default::default)
+            .await
+            .clear();
+        component_storage
+            .insert(entity_id, dimension)
+            .insert(entity_id, PrecisePosition::corner(&position))
+            .insert(entity_id, position.clone())
+            .insert(entity_id, VisibleEntities::default());
+
+        {
+            let conn = self.connections.get_connection(entity_id)?;
+            let conn = conn.read().await;
+            conn.send_packet(Respawn::change_dimension(dimension, game_mode))
+                .await?;
+            conn.send_packet(SynchronizePlayerPosition::new(&position, &rotation))
+                .await?;
+            let border = self.world_border.settings(dimension);
+            conn.send_packet(InitializeWorldBorder::new(&border))
+                .await?;
+            for packet in self.weather.state(dimension).join_packets() {
+                conn.send_packet(packet).await?;
+            }
+        }
+
+        ChunkSender::send_chunks_to_player(self.clone(), entity_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        for dimension in Dimension::ALL {
+            assert_eq!(Dimension::lookup(dimension.name()), Some(dimension));
+            assert_eq!(Dimension::lookup(dimension.short_name()), Some(dimension));
+        }
+        assert_eq!(Dimension::lookup("minecraft:the_moon"), None);
+        assert_eq!(Dimension::from_name("the_moon"), Dimension::Overworld);
+    }
+
+    #[test]
+    fn test_heights() {
+        assert_eq!(Dimension::Overworld.max_y(), 319);
+        assert_eq!(Dimension::Overworld.section_count(), 24);
+        assert_eq!(Dimension::Overworld.section_range(), -4..=19);
+        assert_eq!(Dimension::Nether.max_y(), 255);
+        assert_eq!(Dimension::End.section_count(), 16);
+        assert_eq!(Dimension::End.section_range(), 0..=15);
+    }
+
+    #[test]
+    fn test_region_dirs() {
+        let overworld = Path::new("world/region");
+        assert_eq!(Dimension::Overworld.region_dir(overworld), overworld);
+        assert_eq!(
+            Dimension::Nether.region_dir(overworld),
+            Path::new("world/DIM-1/region")
+        );
+        assert_eq!(
+            Dimension::End.region_dir(overworld),
+            Path::new("world/DIM1/region")
+        );
+    }
+}
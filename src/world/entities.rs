@@ -0,0 +1,118 @@
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::net::packets::outgoing::remove_entities::RemoveEntities;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::components::entity_info::{EntityInfo, EntityKind};
+use crate::utils::components::rotation::Rotation;
+use crate::utils::components::visible_entities::VisibleEntities;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+use crate::world::dimension::Dimension;
+
+impl ServerState {
+    /// Spawns a new entity into the world and returns its id, which is the one the clients know
+    /// it by too. The [EntityTracker](crate::net::systems::entity_tracker::EntityTracker) spawns
+    /// it for the players in range.
+    ///
+    /// Players aren't spawned through here, they get their id when they connect.
+    pub async fn spawn_entity(
+        self: &GlobalState,
+        kind: EntityKind,
+        dimension: Dimension,
+        position: Position,
+        rotation: Rotation,
+    ) -> usize {
+        let uuid = Uuid::new_v4().as_u128();
+        let entity_id = self
+            .world
+            .create_entity()
+            .await
+            .with(EntityInfo::new(kind, uuid))
+            .with(dimension)
+            .with(position)
+            .with(rotation)
+            .build();
+        debug!("Spawned {:?} entity {}", kind, entity_id);
+        entity_id
+    }
+
+    /// Takes an entity out of the world, and removes it from the clients that can see it right
+    /// away, before its id can be handed out again.
+    pub async fn despawn_entity(self: &GlobalState, entity_id: usize) -> Result<()> {
+        // Nobody can be left seeing it by the time the id is free, or whoever gets the id next
+        // would start out already spawned for them
+        let observers = {
+            let query = self.world.query::<&mut VisibleEntities>();
+            let mut observers = Vec::new();
+            for (observer, mut visible) in query.iter().await {
+                if visible.remove(entity_id) {
+                    observers.push(observer);
+                }
+            }
+            observers
+        };
+        self.world.delete_entity(entity_id).await?;
+
+        for observer in observers {
+            let Ok(conn) = self.connections.get_connection(observer) else {
+                continue;
+            };
+            let removed = conn
+                .read()
+                .await
+                .send_packet(RemoveEntities::new(&[entity_id]))
+                .await;
+            if let Err(e) = removed {
+                warn!(
+                    "Failed to remove entity {} for {}: {}",
+                    entity_id, observer, e
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::tests::connections::add_play_connection;
+
+    #[tokio::test]
+    async fn test_despawned_entities_are_forgotten_by_observers() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let (observer, _client) = add_play_connection(&state).await;
+        let boat = state
+            .spawn_entity(
+                EntityKind::Boat,
+                Dimension::Nether,
+                Position::new(2, 64, 0),
+                Rotation::new(0.0, 0.0),
+            )
+            .await;
+        assert_eq!(state.dimension_of(boat).await, Dimension::Nether);
+
+        let mut visible = VisibleEntities::default();
+        visible.update(HashMap::from([(boat, 1)]));
+        state
+            .world
+            .get_component_storage()
+            .insert(observer, visible);
+
+        state.despawn_entity(boat).await.unwrap();
+        assert!(state.world.get_component::<EntityInfo>(boat).await.is_err());
+        assert!(!state
+            .world
+            .get_component::<VisibleEntities>(observer)
+            .await
+            .unwrap()
+            .contains(boat));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::world::chunk_format::{Chunk, Palette};
+use crate::world::conversions::block_state_count;
+use crate::world::generation::{block_index, fill_section, ChunkGenerator};
+use crate::world::heightmap::compute_heightmaps;
+
+/// The height the block states are placed at.
+pub const STATE_Y: i32 = 70;
+/// The height of the barrier floor underneath the grid.
+pub const BARRIER_Y: i32 = 60;
+
+/// Generates vanilla's debug world: every block state in the registry, one per column.
+///
+/// The states are laid out on a grid with a block of air between them. A barrier floor runs
+/// underneath, and a barrier wall goes around the grid from the floor up to just above the states.
+///
+/// State `n` (skipping air) sits at `x = 2 * (n % width) + 1`, `z = 2 * (n / width) + 1`, where
+/// `width` is the square root of the number of states, rounded up.
+pub struct DebugWorldGenerator {
+    state_count: i32,
+    grid_width: i32,
+    grid_height: i32,
+    barrier: i32,
+}
+
+impl Default for DebugWorldGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DebugWorldGenerator {
+    pub fn new() -> Self {
+        // Air isn't worth showing off
+        let state_count = block_state_count() as i32 - 1;
+        let grid_width = (state_count as f64).sqrt().ceil() as i32;
+        let grid_height = (state_count + grid_width - 1) / grid_width;
+        let barrier = Palette::parse("minecraft:barrier")
+            .ok()
+            .and_then(|barrier| barrier.block_id())
+            .expect("Barrier is missing from the block mappings");
+
+        Self {
+            state_count,
+            grid_width,
+            grid_height,
+            barrier,
+        }
+    }
+
+    /// The block state placed in the given column at [STATE_Y], if any.
+    pub fn state_at(&self, x: i32, z: i32) -> Option<i32> {
+        if x <= 0 || z <= 0 || x % 2 == 0 || z % 2 == 0 {
+            return None;
+        }
+        let (grid_x, grid_z) = (x / 2, z / 2);
+        if grid_x >= self.grid_width || grid_z >= self.grid_height {
+            return None;
+        }
+
+        let index = grid_z * self.grid_width + grid_x;
+        (index < self.state_count).then_some(index + 1)
+    }
+
+    /// Whether the column is part of the barrier wall around the grid.
+    pub fn is_wall(&self, x: i32, z: i32) -> bool {
+        let (max_x, max_z) = (2 * self.grid_width, 2 * self.grid_height);
+        let on_x_edge = (x == 0 || x == max_x) && (0..=max_z).contains(&z);
+        let on_z_edge = (z == 0 || z == max_z) && (0..=max_x).contains(&x);
+        on_x_edge || on_z_edge
+    }
+}
+
+impl ChunkGenerator for DebugWorldGenerator {
+    fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
+        let mut chunk = Chunk::empty(chunk_x, chunk_z);
+        let Some(sections) = chunk.sections.as_mut() else {
+            return chunk;
+        };
+
+        for section in sections.iter_mut() {
+            let section_y = section.y as i32;
+            let section_ys = section_y * 16..section_y * 16 + 16;
+            let mut blocks = Vec::new();
+            for local_z in 0..16 {
+                for local_x in 0..16 {
+                    let (x, z) = (chunk_x * 16 + local_x as i32, chunk_z * 16 + local_z as i32);
+                    if section_ys.contains(&BARRIER_Y) {
+                        blocks.push((block_index(local_x, BARRIER_Y, local_z), self.barrier));
+                    }
+                    if self.is_wall(x, z) {
+                        for y in (BARRIER_Y + 1..=STATE_Y + 1).filter(|y| section_ys.contains(y)) {
+                            blocks.push((block_index(local_x, y, local_z), self.barrier));
+                        }
+                    }
+                    if section_ys.contains(&STATE_Y) {
+                        if let Some(state) = self.state_at(x, z) {
+                            blocks.push((block_index(local_x, STATE_Y, local_z), state));
+                        }
+                    }
+                }
+            }
+            if !blocks.is_empty() {
+                fill_section(section, &blocks);
+            }
+        }
+
+        chunk.heightmaps = Some(compute_heightmaps(&chunk));
+
+        chunk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::generation::block_at;
+
+    #[test]
+    fn test_grid_places_distinct_states() {
+        let generator = DebugWorldGenerator::new();
+        let width = generator.grid_width;
+
+        assert_eq!(generator.state_at(1, 1), Some(1));
+        assert_eq!(generator.state_at(3, 1), Some(2));
+        assert_eq!(generator.state_at(1, 3), Some(width + 1));
+        assert_eq!(generator.state_at(2, 1), None);
+        assert_eq!(generator.state_at(-1, 1), None);
+        assert_eq!(generator.state_at(2 * width + 1, 1), None);
+
+        let chunk = generator.generate_chunk(0, 0);
+        assert_eq!(block_at(&chunk, 1, STATE_Y, 1), 1);
+        assert_eq!(block_at(&chunk, 3, STATE_Y, 1), 2);
+        assert_eq!(block_at(&chunk, 1, STATE_Y, 3), width + 1);
+        assert_eq!(block_at(&chunk, 2, STATE_Y, 1), 0);
+        assert_eq!(block_at(&chunk, 0, BARRIER_Y, 0), generator.barrier);
+        assert_eq!(block_at(&chunk, 7, BARRIER_Y, 12), generator.barrier);
+        assert_eq!(block_at(&chunk, 1, STATE_Y + 1, 1), 0);
+
+        // The wall goes around the grid, up to one block above the states
+        assert!(generator.is_wall(0, 5));
+        assert!(generator.is_wall(5, 0));
+        assert!(generator.is_wall(2 * width, 2 * generator.grid_height));
+        assert!(!generator.is_wall(1, 1));
+        assert!(!generator.is_wall(-1, 0));
+        assert_eq!(block_at(&chunk, 0, STATE_Y, 5), generator.barrier);
+        assert_eq!(block_at(&chunk, 5, STATE_Y + 1, 0), generator.barrier);
+        assert_eq!(block_at(&chunk, 0, BARRIER_Y + 1, 0), generator.barrier);
+        assert_eq!(block_at(&chunk, 0, STATE_Y + 2, 0), 0);
+
+        // Every column in the grid shows a different state
+        let mut seen = std::collections::HashSet::new();
+        for z in (1..16).step_by(2) {
+            for x in (1..16).step_by(2) {
+                assert!(seen.insert(block_at(&chunk, x, STATE_Y, z)));
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Superflat worlds, made of the same stack of layers everywhere.
+
+use crate::utils::prelude::*;
+use crate::world::chunk_format::{Chunk, Palette};
+use crate::world::generation::{block_index, fill_section, ChunkGenerator};
+use crate::world::heightmap::{compute_heightmaps, MAX_Y, MIN_Y};
+use crate::world::lighting::light_chunk;
+
+/// Generates a superflat world. Every chunk is identical, so one is built up front and copied.
+pub struct FlatWorldGenerator {
+    template: Chunk,
+}
+
+impl FlatWorldGenerator {
+    /// Builds the generator from its layers, listed bottom to top. Each layer is a block state,
+    /// optionally prefixed by how many blocks thick it is, e.g. `3*minecraft:dirt`.
+    pub fn new<S: AsRef<str>>(layers: &[S]) -> Result<Self> {
+        let mut blocks = Vec::new();
+        for layer in layers {
+            let (thickness, block) = parse_layer(layer.as_ref())?;
+            blocks.extend(std::iter::repeat_n(block, thickness));
+        }
+        if blocks.len() > (MAX_Y - MIN_Y + 1) as usize {
+            return Err(Error::Generic(format!(
+                "Superflat layers are {} blocks tall, which doesn't fit in the world",
+                blocks.len()
+            )));
+        }
+
+        let mut template = Chunk::empty(0, 0);
+        if let Some(sections) = template.sections.as_mut() {
+            for section in sections.iter_mut() {
+                let bottom = section.y as i32 * 16;
+                let mut section_blocks = Vec::new();
+                for y in bottom..bottom + 16 {
+                    let Some(&block) = blocks.get((y - MIN_Y) as usize) else {
+                        break;
+                    };
+                    if block == 0 {
+                        continue;
+                    }
+                    for local_z in 0..16 {
+                        for local_x in 0..16 {
+                            section_blocks.push((block_index(local_x, y, local_z), block));
+                        }
+                    }
+                }
+                if !section_blocks.is_empty() {
+                    fill_section(section, &section_blocks);
+                }
+            }
+        }
+
+        template.heightmaps = Some(compute_heightmaps(&template));
+        light_chunk(&mut template);
+
+        Ok(Self { template })
+    }
+}
+
+/// Splits a layer like `3*minecraft:dirt` into its thickness and block ID.
+fn parse_layer(layer: &str) -> Result<(usize, i32)> {
+    let (thickness, block) = match layer.split_once('*') {
+        Some((thickness, block)) => {
+            let thickness = thickness
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|thickness| *thickness > 0)
+                .ok_or_else(|| {
+                    Error::Generic(format!("Invalid superflat layer thickness: {}", layer))
+                })?;
+            (thickness, block)
+        }
+        None => (1, layer),
+    };
+
+    let block_id = Palette::parse(block.trim())?
+        .block_id()
+        .ok_or_else(|| Error::Generic(format!("Unknown block in superflat layer: {}", layer)))?;
+    Ok((thickness, block_id))
+}
+
+impl ChunkGenerator for FlatWorldGenerator {
+    fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
+        let mut chunk = self.template.clone();
+        chunk.x_pos = chunk_x;
+        chunk.z_pos = chunk_z;
+        chunk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::constants::DEFAULT_FLAT_LAYERS;
+    use crate::world::generation::block_at;
+    use crate::world::heightmap::unpack_heightmap;
+
+    fn block(name: &str) -> i32 {
+        Palette::parse(name).unwrap().block_id().unwrap()
+    }
+
+    #[test]
+    fn test_layers_stack_from_the_bottom() {
+        let generator = FlatWorldGenerator::new(&[
+            "minecraft:bedrock",
+            "3*minecraft:dirt",
+            "minecraft:air",
+            "minecraft:grass_block",
+        ])
+        .unwrap();
+        let chunk = generator.generate_chunk(-7, 12);
+        assert_eq!((chunk.x_pos, chunk.z_pos), (-7, 12));
+
+        for (x, z) in [(0, 0), (15, 15), (3, 11)] {
+            assert_eq!(block_at(&chunk, x, MIN_Y, z), block("minecraft:bedrock"));
+            for y in MIN_Y + 1..=MIN_Y + 3 {
+                assert_eq!(block_at(&chunk, x, y, z), block("minecraft:dirt"));
+            }
+            assert_eq!(block_at(&chunk, x, MIN_Y + 4, z), 0);
+            assert_eq!(
+                block_at(&chunk, x, MIN_Y + 5, z),
+                block("minecraft:grass_block")
+            );
+            assert_eq!(block_at(&chunk, x, MIN_Y + 6, z), 0);
+        }
+
+        let heightmap = chunk.heightmaps.unwrap().motion_blocking.unwrap();
+        assert!(unpack_heightmap(&heightmap).iter().all(|y| *y == MIN_Y + 6));
+    }
+
+    #[test]
+    fn test_default_layers_parse() {
+        assert!(FlatWorldGenerator::new(DEFAULT_FLAT_LAYERS).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_layers_are_rejected() {
+        assert!(FlatWorldGenerator::new(&["minecraft:not_a_block"]).is_err());
+        assert!(FlatWorldGenerator::new(&["0*minecraft:dirt"]).is_err());
+        assert!(FlatWorldGenerator::new(&["two*minecraft:dirt"]).is_err());
+        assert!(FlatWorldGenerator::new(&["400*minecraft:stone"]).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! Chunks that are made up on the fly instead of being read from the database.
+
+use ferrumc_codec::network_types::varint::VarInt;
+
+use crate::utils::config::{get_global_config, WorldGenerator};
+use crate::utils::prelude::*;
+use crate::world::chunk_format::{BlockStates, Chunk, Section};
+use crate::world::generation::debug::DebugWorldGenerator;
+use crate::world::generation::flat::FlatWorldGenerator;
+use crate::world::generation::noise::NoiseWorldGenerator;
+use crate::world::palette::{bits_for_palette_len, pack_entries, SECTION_VOLUME};
+
+pub mod debug;
+pub mod flat;
+pub mod noise;
+
+/// Something that can make up a chunk from nothing but its coordinates.
+pub trait ChunkGenerator: Send + Sync {
+    /// Generates the chunk at the given chunk coordinates, already in network mode.
+    fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk;
+}
+
+/// The generator for the configured world. The debug and superflat worlds are generated in full,
+/// anything else falls back to noise terrain for chunks that haven't been stored yet.
+///
+/// Fails if the superflat layers in the config are invalid.
+pub fn configured_generator() -> Result<Box<dyn ChunkGenerator>> {
+    let config = get_global_config();
+    Ok(match config.generator {
+        WorldGenerator::Debug => Box::new(DebugWorldGenerator::new()),
+        WorldGenerator::Flat => Box::new(FlatWorldGenerator::new(&config.flat_layers)?),
+        WorldGenerator::Imported | WorldGenerator::Anvil => {
+            Box::new(NoiseWorldGenerator::new(config.seed))
+        }
+    })
+}
+
+/// The index of a block within its section's block data.
+pub fn block_index(local_x: usize, y: i32, local_z: usize) -> usize {
+    let local_y = y.rem_euclid(16) as usize;
+    (local_y * 16 + local_z) * 16 + local_x
+}
+
+/// Fills an otherwise empty section with the given `(index, block id)` pairs.
+pub fn fill_section(section: &mut Section, blocks: &[(usize, i32)]) {
+    let mut palette = vec![0];
+    let mut entries = vec![0u32; SECTION_VOLUME];
+    for &(index, block) in blocks {
+        let entry = match palette.iter().position(|id| *id == block) {
+            Some(entry) => entry,
+            None => {
+                palette.push(block);
+                palette.len() - 1
+            }
+        };
+        entries[index] = entry as u32;
+    }
+
+    let bits_per_block = bits_for_palette_len(palette.len());
+    section.block_states = Some(BlockStates {
+        non_air_blocks: Some(entries.iter().filter(|entry| **entry != 0).count() as i16),
+        bits_per_block: Some(bits_per_block as i8),
+        data: Some(pack_entries(&entries, bits_per_block)),
+        palette: None,
+        net_palette: Some(palette.into_iter().map(VarInt::from).collect()),
+    });
+}
+
+/// Reads back the block id at the given position from a generated chunk.
+#[cfg(test)]
+pub fn block_at(chunk: &Chunk, x: usize, y: i32, z: usize) -> i32 {
+    use crate::world::palette::unpack_entries;
+
+    let section = chunk
+        .sections
+        .as_ref()
+        .unwrap()
+        .iter()
+        .find(|section| section.y as i32 == y >> 4)
+        .unwrap();
+    let states = section.block_states.as_ref().unwrap();
+    let Some(data) = states.data.as_ref() else {
+        return 0;
+    };
+    let entries = unpack_entries(data, states.bits_per_block.unwrap() as u8, SECTION_VOLUME);
+    let palette = states.net_palette.as_ref().unwrap();
+    palette[entries[block_index(x, y, z)] as usize].get_val()
+}
@@ -0,0 +1,289 @@
+//! Rolling hills of stone, dirt and grass, with water filling everything below sea level.
+
+use crate::world::chunk_format::{Chunk, Palette};
+use crate::world::generation::{block_index, fill_section, ChunkGenerator};
+use crate::world::heightmap::{compute_heightmaps, HEIGHTMAP_COLUMNS, MIN_Y};
+
+/// The top of the water in oceans and lakes.
+pub const SEA_LEVEL: i32 = 62;
+/// The height the terrain averages out at.
+const BASE_HEIGHT: i32 = 68;
+/// How far above or below [BASE_HEIGHT] the terrain reaches.
+const HEIGHT_VARIATION: f64 = 28.0;
+/// Roughly how many blocks across the largest hills are.
+const HILL_SCALE: f64 = 160.0;
+/// How many layers of noise the terrain is built from, each adding finer detail.
+const OCTAVES: u32 = 4;
+/// How many blocks of dirt or sand sit between the surface and the stone.
+const FILLER_DEPTH: i32 = 3;
+
+/// Classic 2D Perlin noise, with the permutation table shuffled by the seed.
+pub struct PerlinNoise {
+    permutation: [u8; 512],
+}
+
+impl PerlinNoise {
+    pub fn new(seed: i64) -> Self {
+        // SplitMix64 is plenty for a shuffle, and means a seed always gives the same world
+        let mut state = seed as u64;
+        let mut next = || {
+            state = state.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            z ^ (z >> 31)
+        };
+
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        for i in (1..table.len()).rev() {
+            let j = (next() % (i as u64 + 1)) as usize;
+            table.swap(i, j);
+        }
+
+        // Doubled up so lookups never need to wrap
+        Self {
+            permutation: std::array::from_fn(|i| table[i % 256]),
+        }
+    }
+
+    /// Samples the noise at a point, giving a value between -1 and 1. Whole coordinates always
+    /// give 0.
+    pub fn sample(&self, x: f64, z: f64) -> f64 {
+        let (x0, z0) = (x.floor(), z.floor());
+        let (fx, fz) = (x - x0, z - z0);
+        let xi = (x0 as i64 & 255) as usize;
+        let zi = (z0 as i64 & 255) as usize;
+
+        let p = &self.permutation;
+        let hash = |dx: usize, dz: usize| p[p[xi + dx] as usize + zi + dz];
+        let (u, v) = (fade(fx), fade(fz));
+
+        let near = lerp(
+            u,
+            gradient(hash(0, 0), fx, fz),
+            gradient(hash(1, 0), fx - 1.0, fz),
+        );
+        let far = lerp(
+            u,
+            gradient(hash(0, 1), fx, fz - 1.0),
+            gradient(hash(1, 1), fx - 1.0, fz - 1.0),
+        );
+        lerp(v, near, far).clamp(-1.0, 1.0)
+    }
+
+    /// Adds up `octaves` samples, each at twice the frequency and half the strength of the last,
+    /// so there's finer detail on top of the broad shapes. Still between -1 and 1.
+    pub fn fractal(&self, x: f64, z: f64, octaves: u32) -> f64 {
+        let mut total = 0.0;
+        let mut max = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        for _ in 0..octaves {
+            total += self.sample(x * frequency, z * frequency) * amplitude;
+            max += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        total / max
+    }
+}
+
+/// Smooths the position within a cell so the noise has no creases at the cell edges.
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dot product of the offset from a corner with one of 8 gradients, picked by the corner's hash.
+fn gradient(hash: u8, x: f64, z: f64) -> f64 {
+    match hash & 7 {
+        0 => x + z,
+        1 => -x + z,
+        2 => x - z,
+        3 => -x - z,
+        4 => x,
+        5 => -x,
+        6 => z,
+        _ => -z,
+    }
+}
+
+/// Generates terrain from layered Perlin noise: stone, topped with dirt and grass, or sand near and
+/// under the water, with water filling everything below [SEA_LEVEL] and bedrock at the bottom.
+pub struct NoiseWorldGenerator {
+    noise: PerlinNoise,
+    bedrock: i32,
+    stone: i32,
+    dirt: i32,
+    grass: i32,
+    sand: i32,
+    water: i32,
+}
+
+impl NoiseWorldGenerator {
+    pub fn new(seed: i64) -> Self {
+        let block = |name: &str| {
+            Palette::parse(name)
+                .ok()
+                .and_then(|block| block.block_id())
+                .unwrap_or_else(|| panic!("{} is missing from the block mappings", name))
+        };
+
+        Self {
+            noise: PerlinNoise::new(seed),
+            bedrock: block("minecraft:bedrock"),
+            stone: block("minecraft:stone"),
+            dirt: block("minecraft:dirt"),
+            grass: block("minecraft:grass_block"),
+            sand: block("minecraft:sand"),
+            water: block("minecraft:water"),
+        }
+    }
+
+    /// The y of the topmost solid block in the given column.
+    pub fn surface_height(&self, x: i32, z: i32) -> i32 {
+        let noise = self
+            .noise
+            .fractal(x as f64 / HILL_SCALE, z as f64 / HILL_SCALE, OCTAVES);
+        BASE_HEIGHT + (noise * HEIGHT_VARIATION).round() as i32
+    }
+
+    /// The block at `y` in a column with its surface at `surface`.
+    fn block_in_column(&self, y: i32, surface: i32) -> i32 {
+        let (top, filler) = if surface <= SEA_LEVEL + 1 {
+            (self.sand, self.sand)
+        } else {
+            (self.grass, self.dirt)
+        };
+
+        if y == MIN_Y {
+            self.bedrock
+        } else if y > surface {
+            if y <= SEA_LEVEL {
+                self.water
+            } else {
+                0
+            }
+        } else if y == surface {
+            top
+        } else if y >= surface - FILLER_DEPTH {
+            filler
+        } else {
+            self.stone
+        }
+    }
+}
+
+impl ChunkGenerator for NoiseWorldGenerator {
+    fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
+        let mut surface = [0; HEIGHTMAP_COLUMNS];
+        for (column, surface_y) in surface.iter_mut().enumerate() {
+            let (local_x, local_z) = ((column % 16) as i32, (column / 16) as i32);
+            *surface_y = self.surface_height(chunk_x * 16 + local_x, chunk_z * 16 + local_z);
+        }
+
+        let mut chunk = Chunk::empty(chunk_x, chunk_z);
+        let Some(sections) = chunk.sections.as_mut() else {
+            return chunk;
+        };
+
+        for section in sections.iter_mut() {
+            let bottom = section.y as i32 * 16;
+            let mut blocks = Vec::new();
+            for y in bottom..bottom + 16 {
+                for (column, surface_y) in surface.iter().enumerate() {
+                    let block = self.block_in_column(y, *surface_y);
+                    if block != 0 {
+                        blocks.push((block_index(column % 16, y, column / 16), block));
+                    }
+                }
+            }
+            if !blocks.is_empty() {
+                fill_section(section, &blocks);
+            }
+        }
+
+        chunk.heightmaps = Some(compute_heightmaps(&chunk));
+
+        chunk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::generation::block_at;
+    use crate::world::heightmap::unpack_heightmap;
+
+    #[test]
+    fn test_noise_is_seeded_and_bounded() {
+        let noise = PerlinNoise::new(42);
+        let same = PerlinNoise::new(42);
+        let other = PerlinNoise::new(43);
+
+        let mut differs = false;
+        for i in -50..50 {
+            let (x, z) = (i as f64 * 0.37, i as f64 * -0.21 + 3.0);
+            let value = noise.sample(x, z);
+            assert!((-1.0..=1.0).contains(&value));
+            assert_eq!(value, same.sample(x, z));
+            differs |= value != other.sample(x, z);
+
+            assert_eq!(noise.sample(i as f64, -i as f64), 0.0);
+            assert!((-1.0..=1.0).contains(&noise.fractal(x, z, OCTAVES)));
+        }
+        assert!(differs);
+    }
+
+    #[test]
+    fn test_columns_are_layered() {
+        let generator = NoiseWorldGenerator::new(1234);
+        let (chunk_x, chunk_z) = (3, -2);
+        let chunk = generator.generate_chunk(chunk_x, chunk_z);
+        let heightmap = unpack_heightmap(
+            chunk
+                .heightmaps
+                .as_ref()
+                .unwrap()
+                .motion_blocking
+                .as_ref()
+                .unwrap(),
+        );
+
+        for (x, z) in [(0, 0), (5, 9), (15, 15), (8, 2)] {
+            let surface =
+                generator.surface_height(chunk_x * 16 + x as i32, chunk_z * 16 + z as i32);
+            // Water counts for the heightmap too
+            assert_eq!(heightmap[z * 16 + x], surface.max(SEA_LEVEL) + 1);
+
+            assert_eq!(block_at(&chunk, x, MIN_Y, z), generator.bedrock);
+            assert_eq!(block_at(&chunk, x, 0, z), generator.stone);
+            let top = block_at(&chunk, x, surface, z);
+            assert!(top == generator.grass || top == generator.sand);
+            assert_eq!(
+                block_at(&chunk, x, surface - FILLER_DEPTH - 1, z),
+                generator.stone
+            );
+
+            let above = if surface < SEA_LEVEL {
+                generator.water
+            } else {
+                0
+            };
+            assert_eq!(block_at(&chunk, x, surface + 1, z), above);
+            assert_eq!(block_at(&chunk, x, surface.max(SEA_LEVEL) + 1, z), 0);
+        }
+    }
+
+    #[test]
+    fn test_terrain_is_not_flat() {
+        let generator = NoiseWorldGenerator::new(0);
+        let heights = (0..64)
+            .map(|i| generator.surface_height(i * 16, i * 7))
+            .collect::<std::collections::HashSet<_>>();
+        assert!(heights.len() > 5);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::world::chunk_format::Palette;
+use crate::world::conversions::is_kind;
+
+/// How long a block takes to break by hand, as in vanilla.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hardness {
+    pub value: f32,
+    /// Blocks that need a tool to drop anything break a lot slower without one.
+    pub needs_tool: bool,
+}
+
+impl Hardness {
+    const fn hand(value: f32) -> Self {
+        Self {
+            value,
+            needs_tool: false,
+        }
+    }
+
+    const fn tool(value: f32) -> Self {
+        Self {
+            value,
+            needs_tool: true,
+        }
+    }
+
+    /// How many ticks the block takes to break by hand.
+    pub fn ticks(&self) -> u32 {
+        let per_hardness = if self.needs_tool { 100.0 } else { 30.0 };
+        (self.value * per_hardness).ceil() as u32
+    }
+}
+
+/// Blocks that can't be broken in survival.
+const UNBREAKABLE: &[&str] = &[
+    "bedrock",
+    "barrier",
+    "light",
+    "command_block",
+    "chain_command_block",
+    "repeating_command_block",
+    "structure_block",
+    "jigsaw",
+    "end_portal",
+    "end_portal_frame",
+    "end_gateway",
+    "nether_portal",
+    "moving_piston",
+];
+
+/// Blocks that break straight away.
+const INSTANT: &[&str] = &[
+    "air",
+    "short_grass",
+    "grass",
+    "tall_grass",
+    "fern",
+    "large_fern",
+    "dead_bush",
+    "seagrass",
+    "kelp",
+    "kelp_plant",
+    "sugar_cane",
+    "wheat",
+    "carrots",
+    "potatoes",
+    "beetroots",
+    "sapling",
+    "mushroom",
+    "fungus",
+    "roots",
+    "torch",
+    "redstone_wire",
+    "repeater",
+    "comparator",
+    "tripwire",
+    "tripwire_hook",
+    "lily_pad",
+    "flower_pot",
+    "tnt",
+    "slime_block",
+    "honey_block",
+    "scaffolding",
+    "dandelion",
+    "poppy",
+    "blue_orchid",
+    "allium",
+    "azure_bluet",
+    "tulip",
+    "oxeye_daisy",
+    "cornflower",
+    "lily_of_the_valley",
+    "sunflower",
+    "lilac",
+    "rose_bush",
+    "peony",
+];
+
+/// Exact names, checked first since some of them end like one of the kinds.
+const EXACT: &[(&str, Hardness)] = &[
+    ("grass_block", Hardness::hand(0.6)),
+    ("dirt_path", Hardness::hand(0.65)),
+    ("farmland", Hardness::hand(0.6)),
+    ("mycelium", Hardness::hand(0.6)),
+    ("clay", Hardness::hand(0.6)),
+    ("mangrove_roots", Hardness::hand(0.7)),
+    ("muddy_mangrove_roots", Hardness::hand(0.7)),
+    ("gravel", Hardness::hand(0.6)),
+    ("snow", Hardness::tool(0.1)),
+    ("snow_block", Hardness::tool(0.2)),
+    ("packed_ice", Hardness::hand(0.5)),
+    ("blue_ice", Hardness::hand(2.8)),
+    ("netherrack", Hardness::tool(0.4)),
+    ("glowstone", Hardness::hand(0.3)),
+    ("sea_lantern", Hardness::hand(0.3)),
+    ("cactus", Hardness::hand(0.4)),
+    ("pumpkin", Hardness::hand(1.0)),
+    ("carved_pumpkin", Hardness::hand(1.0)),
+    ("jack_o_lantern", Hardness::hand(1.0)),
+    ("melon", Hardness::hand(1.0)),
+    ("bookshelf", Hardness::hand(1.5)),
+    ("crafting_table", Hardness::hand(2.5)),
+    ("chest", Hardness::hand(2.5)),
+    ("trapped_chest", Hardness::hand(2.5)),
+    ("barrel", Hardness::hand(2.5)),
+    ("furnace", Hardness::tool(3.5)),
+    ("blast_furnace", Hardness::tool(3.5)),
+    ("smoker", Hardness::tool(3.5)),
+    ("cobweb", Hardness::tool(4.0)),
+    ("stone", Hardness::tool(1.5)),
+    ("cobblestone", Hardness::tool(2.0)),
+    ("mossy_cobblestone", Hardness::tool(2.0)),
+    ("deepslate", Hardness::tool(3.0)),
+    ("cobbled_deepslate", Hardness::tool(3.5)),
+    ("end_stone", Hardness::tool(3.0)),
+    ("obsidian", Hardness::tool(50.0)),
+    ("crying_obsidian", Hardness::tool(50.0)),
+    ("ancient_debris", Hardness::tool(30.0)),
+    ("iron_block", Hardness::tool(5.0)),
+    ("gold_block", Hardness::tool(3.0)),
+    ("diamond_block", Hardness::tool(5.0)),
+    ("emerald_block", Hardness::tool(5.0)),
+    ("netherite_block", Hardness::tool(50.0)),
+];
+
+/// Whole families of blocks, matched like [Palette::is_kind].
+const KINDS: &[(&str, Hardness)] = &[
+    ("dirt", Hardness::hand(0.5)),
+    ("podzol", Hardness::hand(0.5)),
+    ("sand", Hardness::hand(0.5)),
+    ("soul_sand", Hardness::hand(0.5)),
+    ("soul_soil", Hardness::hand(0.5)),
+    ("ice", Hardness::hand(0.5)),
+    ("concrete_powder", Hardness::hand(0.5)),
+    ("carpet", Hardness::hand(0.1)),
+    ("leaves", Hardness::hand(0.2)),
+    ("glass", Hardness::hand(0.3)),
+    ("glass_pane", Hardness::hand(0.3)),
+    ("wool", Hardness::hand(0.8)),
+    ("planks", Hardness::hand(2.0)),
+    ("log", Hardness::hand(2.0)),
+    ("wood", Hardness::hand(2.0)),
+    ("stem", Hardness::hand(2.0)),
+    ("hyphae", Hardness::hand(2.0)),
+    ("fence", Hardness::hand(2.0)),
+    ("fence_gate", Hardness::hand(2.0)),
+    ("door", Hardness::hand(3.0)),
+    ("trapdoor", Hardness::hand(3.0)),
+    ("sign", Hardness::hand(1.0)),
+    ("button", Hardness::hand(0.5)),
+    ("pressure_plate", Hardness::hand(0.5)),
+    ("rail", Hardness::hand(0.7)),
+    ("sandstone", Hardness::tool(0.8)),
+    ("terracotta", Hardness::tool(1.25)),
+    ("concrete", Hardness::tool(1.8)),
+    ("stone_bricks", Hardness::tool(1.5)),
+    ("bricks", Hardness::tool(2.0)),
+    ("granite", Hardness::tool(1.5)),
+    ("diorite", Hardness::tool(1.5)),
+    ("andesite", Hardness::tool(1.5)),
+];
+
+/// How hard the block is to break, going by its name. `None` for blocks that can't be broken.
+/// Blocks that aren't known break straight away, so players are never held back by the server.
+pub fn hardness(state: &Palette) -> Option<Hardness> {
+    let name = state.short_name();
+    if UNBREAKABLE.contains(&name) {
+        return None;
+    }
+    if let Some((_, hardness)) = EXACT.iter().find(|(exact, _)| *exact == name) {
+        return Some(*hardness);
+    }
+    if name.starts_with("potted_") || is_kind(name, INSTANT) {
+        return Some(Hardness::hand(0.0));
+    }
+    if is_kind(name, &["ore"]) {
+        let value = if name.starts_with("deepslate_") {
+            4.5
+        } else {
+            3.0
+        };
+        return Some(Hardness::tool(value));
+    }
+    // Slabs, stairs and walls are as hard as what they're made of, which is sometimes plural,
+    // e.g. `stone_brick_stairs` are made of `stone_bricks`
+    let base = ["_slab", "_stairs", "_wall"]
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name);
+    KINDS
+        .iter()
+        .filter(|(kind, _)| is_kind(base, &[kind]) || is_kind(&format!("{}s", base), &[kind]))
+        .max_by_key(|(kind, _)| kind.len())
+        .map_or(Some(Hardness::hand(0.0)), |(_, hardness)| Some(*hardness))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hardness_of(name: &str) -> Option<Hardness> {
+        hardness(&Palette::parse(name).unwrap())
+    }
+
+    #[test]
+    fn test_hardness() {
+        assert_eq!(hardness_of("bedrock"), None);
+        assert_eq!(hardness_of("poppy").unwrap().ticks(), 0);
+        assert_eq!(hardness_of("dirt").unwrap().ticks(), 15);
+        assert_eq!(hardness_of("grass_block").unwrap().ticks(), 18);
+        assert_eq!(hardness_of("oak_log[axis=y]").unwrap().ticks(), 60);
+        assert_eq!(hardness_of("stone").unwrap().ticks(), 150);
+        assert_eq!(hardness_of("deepslate_iron_ore").unwrap().ticks(), 450);
+    }
+
+    #[test]
+    fn test_longest_kind_wins() {
+        // Not glass, and not sand
+        assert_eq!(hardness_of("white_stained_glass_pane").unwrap().value, 0.3);
+        assert_eq!(hardness_of("red_sandstone").unwrap(), Hardness::tool(0.8));
+        assert_eq!(hardness_of("cyan_concrete_powder").unwrap().value, 0.5);
+        assert_eq!(hardness_of("stone_brick_stairs").unwrap().value, 1.5);
+    }
+}
@@ -0,0 +1,403 @@
+//! Heightmaps: the height of the top block in every column of a chunk, by a couple of rules for
+//! which blocks count. The client uses them to decide where rain and snow fall.
+//!
+//! Only the two heightmaps the client needs are kept. WORLD_SURFACE counts every block but air,
+//! MOTION_BLOCKING counts blocks that are solid enough to stop movement, and fluids.
+
+use std::cmp::Reverse;
+
+use lazy_static::lazy_static;
+
+use crate::world::block_registry::block_registry;
+use crate::world::chunk_format::{Chunk, Heightmaps, Palette};
+use crate::world::conversions::is_kind;
+use crate::world::dimension::Dimension;
+use crate::world::palette::{pack_entries, unpack_entries};
+
+/// The lowest block in the overworld.
+pub const MIN_Y: i32 = -64;
+/// The highest block in the overworld.
+pub const MAX_Y: i32 = 319;
+/// Enough for every height in the 384 block tall overworld, and so in the other dimensions too.
+pub const HEIGHTMAP_BITS: u8 = 9;
+/// One entry per column in a chunk.
+pub const HEIGHTMAP_COLUMNS: usize = 16 * 16;
+
+/// Set for blocks that count for WORLD_SURFACE.
+const SURFACE: u8 = 1;
+/// Set for blocks that count for MOTION_BLOCKING.
+const MOTION_BLOCKING: u8 = 2;
+
+lazy_static! {
+    /// Which heightmaps every block state counts for, indexed by block state ID.
+    static ref HEIGHTMAP_FLAGS: Vec<u8> = (0..block_registry().len() as i32)
+        .map(|id| block_registry().state(id).map_or(0, heightmap_flags))
+        .collect();
+}
+
+/// The value a column with surface height `height` is stored as in a heightmap.
+///
+/// A column's height is the lowest y that has nothing counted above it, so one above its top block,
+/// or the bottom of the world if there's nothing in it at all. Heights are offset by the bottom of
+/// the world, so that they're never negative: `y = -64` is stored as 0 and `y = 320`, above a
+/// block at the top of the world, as 384. Anything outside the world is clamped to it.
+pub fn packed_height(height: i32) -> u32 {
+    packed_height_in(height, Dimension::Overworld)
+}
+
+/// [packed_height] for a dimension other than the overworld, whose heights are offset by its own
+/// bottom instead.
+pub fn packed_height_in(height: i32, dimension: Dimension) -> u32 {
+    (height.clamp(dimension.min_y(), dimension.max_y() + 1) - dimension.min_y()) as u32
+}
+
+/// Packs a height per column, indexed `z * 16 + x`, into the longs a heightmap is sent as.
+pub fn pack_heightmap(surface: &[i32]) -> Vec<i64> {
+    pack_heightmap_in(surface, Dimension::Overworld)
+}
+
+/// [pack_heightmap] for any dimension.
+pub fn pack_heightmap_in(surface: &[i32], dimension: Dimension) -> Vec<i64> {
+    debug_assert_eq!(surface.len(), HEIGHTMAP_COLUMNS);
+    let heights = surface
+        .iter()
+        .map(|y| packed_height_in(*y, dimension))
+        .collect::<Vec<_>>();
+    pack_entries(&heights, HEIGHTMAP_BITS)
+}
+
+/// The opposite of [pack_heightmap], giving back the height of every column.
+pub fn unpack_heightmap(data: &[i64]) -> Vec<i32> {
+    unpack_entries(data, HEIGHTMAP_BITS, HEIGHTMAP_COLUMNS)
+        .into_iter()
+        .map(|height| height as i32 + MIN_Y)
+        .collect()
+}
+
+/// A heightmap with every column at the same height.
+pub fn flat_heightmap(height: i32) -> Vec<i64> {
+    pack_heightmap(&[height; HEIGHTMAP_COLUMNS])
+}
+
+/// Works out the heightmaps of a chunk in network mode from its blocks, relative to the bottom of
+/// the dimension it's in.
+///
+/// Sections are read from the top down, stopping as soon as the top of every column has been
+/// found, so mostly empty chunks are cheap.
+pub fn compute_heightmaps(chunk: &Chunk) -> Heightmaps {
+    let dimension = chunk
+        .dimension
+        .as_deref()
+        .map(Dimension::from_name)
+        .unwrap_or_default();
+    let mut sections = chunk
+        .sections
+        .iter()
+        .flatten()
+        .filter_map(|section| Some((section.y, section.block_states.as_ref()?)))
+        .collect::<Vec<_>>();
+    sections.sort_unstable_by_key(|(y, _)| Reverse(*y));
+
+    let mut surface = [None; HEIGHTMAP_COLUMNS];
+    let mut motion_blocking = [None; HEIGHTMAP_COLUMNS];
+    for (section_y, block_states) in sections {
+        if surface.iter().chain(&motion_blocking).all(Option::is_some) {
+            break;
+        }
+        let ids = block_states.block_ids();
+        for local_y in (0..16).rev() {
+            let height = section_y as i32 * 16 + local_y as i32 + 1;
+            for column in 0..HEIGHTMAP_COLUMNS {
+                let flags = HEIGHTMAP_FLAGS
+                    .get(ids[local_y * HEIGHTMAP_COLUMNS + column] as usize)
+                    .copied()
+                    .unwrap_or(SURFACE | MOTION_BLOCKING);
+                if flags & SURFACE != 0 {
+                    surface[column].get_or_insert(height);
+                }
+                if flags & MOTION_BLOCKING != 0 {
+                    motion_blocking[column].get_or_insert(height);
+                }
+            }
+        }
+    }
+
+    let heights =
+        |tops: [Option<i32>; HEIGHTMAP_COLUMNS]| tops.map(|top| top.unwrap_or(dimension.min_y()));
+    Heightmaps {
+        motion_blocking: Some(pack_heightmap_in(&heights(motion_blocking), dimension)),
+        world_surface: Some(pack_heightmap_in(&heights(surface), dimension)),
+    }
+}
+
+/// Which heightmaps a block counts for.
+fn heightmap_flags(state: &Palette) -> u8 {
+    let name = state.name.strip_prefix("minecraft:").unwrap_or(&state.name);
+    if matches!(name, "air" | "cave_air" | "void_air") {
+        return 0;
+    }
+    if blocks_motion(state) || has_fluid(state) {
+        SURFACE | MOTION_BLOCKING
+    } else {
+        SURFACE
+    }
+}
+
+/// Whether the block has water or lava in it.
+pub fn has_fluid(state: &Palette) -> bool {
+    let name = state.name.strip_prefix("minecraft:").unwrap_or(&state.name);
+    const FLUIDS: &[&str] = &[
+        "water",
+        "lava",
+        "bubble_column",
+        "kelp",
+        "kelp_plant",
+        "seagrass",
+        "tall_seagrass",
+    ];
+    FLUIDS.contains(&name)
+        || state
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.get("waterlogged"))
+            .is_some_and(|waterlogged| waterlogged == "true")
+}
+
+/// Whether the block is solid enough to stop things moving through it. Blocks without a hitbox
+/// don't, and neither do ones that are too flat or small, like carpets and flower pots.
+pub fn blocks_motion(state: &Palette) -> bool {
+    let name = state.short_name();
+    if name == "snow" {
+        // The thinnest snow layers are walked through
+        return state
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.get("layers"))
+            .and_then(|layers| layers.parse::<u8>().ok())
+            .is_some_and(|layers| layers >= 3);
+    }
+    const PASSABLE_EXACT: &[&str] = &["light", "structure_void", "end_gateway"];
+    const PASSABLE: &[&str] = &[
+        "sapling",
+        "torch",
+        "sign",
+        "banner",
+        "button",
+        "pressure_plate",
+        "rail",
+        "redstone_wire",
+        "tripwire",
+        "tripwire_hook",
+        "lever",
+        "vine",
+        "vines",
+        "vines_plant",
+        "lichen",
+        "fire",
+        "grass",
+        "fern",
+        "dead_bush",
+        "poppy",
+        "dandelion",
+        "orchid",
+        "allium",
+        "bluet",
+        "tulip",
+        "daisy",
+        "cornflower",
+        "lily_of_the_valley",
+        "wither_rose",
+        "sunflower",
+        "lilac",
+        "peony",
+        "rose_bush",
+        "pitcher_plant",
+        "pitcher_crop",
+        "mushroom",
+        "fungus",
+        "roots",
+        "sprouts",
+        "wheat",
+        "carrots",
+        "potatoes",
+        "beetroots",
+        "nether_wart",
+        "sugar_cane",
+        "berry_bush",
+        "cocoa",
+        "stem",
+        "carpet",
+        "flower_pot",
+        "candle",
+        "head",
+        "skull",
+        "repeater",
+        "comparator",
+        "daylight_detector",
+        "lily_pad",
+        "sea_pickle",
+        "turtle_egg",
+        "frogspawn",
+        "petals",
+        "spore_blossom",
+        "small_dripleaf",
+        "coral",
+        "coral_fan",
+        "coral_wall_fan",
+        "amethyst_cluster",
+        "bud",
+        "lantern",
+        "conduit",
+        "portal",
+        "kelp",
+        "kelp_plant",
+        "seagrass",
+    ];
+    // Full blocks whose names end like one of the passable ones
+    const SOLID: &[&str] = &[
+        "mushroom_stem",
+        "crimson_stem",
+        "warped_stem",
+        "mangrove_roots",
+        "muddy_mangrove_roots",
+        "sea_lantern",
+        "jack_o_lantern",
+        "piston_head",
+    ];
+    if SOLID.contains(&name) {
+        return true;
+    }
+    !(PASSABLE_EXACT.contains(&name) || name.starts_with("potted_") || is_kind(name, PASSABLE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heights_are_offset_by_the_world_bottom() {
+        assert_eq!(packed_height(-64), 0);
+        assert_eq!(packed_height(-60), 4);
+        assert_eq!(packed_height(0), 64);
+        assert_eq!(packed_height(319), 383);
+    }
+
+    #[test]
+    fn test_heights_fit_in_nine_bits() {
+        for y in [i32::MIN, -65, -64, 319, 320, 1000, i32::MAX] {
+            assert!(packed_height(y) < 1 << HEIGHTMAP_BITS, "y = {y}");
+        }
+        assert_eq!(packed_height(-65), 0);
+        assert_eq!(packed_height(320), 384);
+        assert_eq!(packed_height(321), 384);
+
+        // The other dimensions start at 0
+        assert_eq!(packed_height_in(0, Dimension::Nether), 0);
+        assert_eq!(packed_height_in(256, Dimension::End), 256);
+        assert_eq!(packed_height_in(-10, Dimension::Nether), 0);
+    }
+
+    fn block(name: &str) -> i32 {
+        Palette::parse(name).unwrap().block_id().unwrap()
+    }
+
+    #[test]
+    fn test_heightmap_rules() {
+        let state = |name: &str| Palette::parse(name).unwrap();
+        assert_eq!(heightmap_flags(&state("minecraft:air")), 0);
+        assert_eq!(
+            heightmap_flags(&state("minecraft:stone")),
+            SURFACE | MOTION_BLOCKING
+        );
+        assert_eq!(heightmap_flags(&state("minecraft:poppy")), SURFACE);
+        assert_eq!(heightmap_flags(&state("minecraft:grass")), SURFACE);
+        assert_eq!(
+            heightmap_flags(&state("minecraft:grass_block")),
+            SURFACE | MOTION_BLOCKING
+        );
+        assert_eq!(
+            heightmap_flags(&state("minecraft:water")),
+            SURFACE | MOTION_BLOCKING
+        );
+        assert_eq!(heightmap_flags(&state("minecraft:oak_sign")), SURFACE);
+        assert_eq!(
+            heightmap_flags(&state("minecraft:oak_sign[waterlogged=true]")),
+            SURFACE | MOTION_BLOCKING
+        );
+        assert_eq!(heightmap_flags(&state("minecraft:snow[layers=1]")), SURFACE);
+        assert_eq!(
+            heightmap_flags(&state("minecraft:snow[layers=5]")),
+            SURFACE | MOTION_BLOCKING
+        );
+    }
+
+    #[test]
+    fn test_motion_matches_whole_names() {
+        let blocks = |name: &str| blocks_motion(&Palette::parse(name).unwrap());
+        assert!(!blocks("kelp_plant"));
+        assert!(!blocks("soul_lantern"));
+        assert!(!blocks("potted_poppy"));
+        assert!(!blocks("brain_coral_wall_fan"));
+        assert!(blocks("dried_kelp_block"));
+        assert!(blocks("campfire"));
+        assert!(blocks("jack_o_lantern"));
+        assert!(blocks("crimson_stem"));
+        // Not a kind of grass
+        assert!(blocks("grass_block"));
+    }
+
+    #[test]
+    fn test_heightmaps_follow_the_top_blocks() {
+        use crate::world::generation::block_index;
+
+        let mut chunk = Chunk::empty(0, 0);
+        let heightmaps = compute_heightmaps(&chunk);
+        assert_eq!(heightmaps.motion_blocking, Some(flat_heightmap(MIN_Y)));
+        assert_eq!(heightmaps.world_surface, Some(flat_heightmap(MIN_Y)));
+
+        // Stone at the bottom everywhere, a flower on top of it in one column and a block at the
+        // very top of the world in another
+        let sections = chunk.sections.as_mut().unwrap();
+        let bottom = (0..HEIGHTMAP_COLUMNS)
+            .map(|column| {
+                (
+                    block_index(column % 16, MIN_Y, column / 16),
+                    block("minecraft:stone"),
+                )
+            })
+            .chain([(block_index(3, MIN_Y + 1, 2), block("minecraft:poppy"))])
+            .collect::<Vec<_>>();
+        crate::world::generation::fill_section(&mut sections[0], &bottom);
+        let top = [(block_index(0, MAX_Y, 0), block("minecraft:stone"))];
+        crate::world::generation::fill_section(sections.last_mut().unwrap(), &top);
+
+        let heightmaps = compute_heightmaps(&chunk);
+        let motion_blocking = unpack_heightmap(&heightmaps.motion_blocking.unwrap());
+        let surface = unpack_heightmap(&heightmaps.world_surface.unwrap());
+        assert_eq!(motion_blocking[2 * 16 + 3], MIN_Y + 1);
+        assert_eq!(surface[2 * 16 + 3], MIN_Y + 2);
+        assert_eq!(motion_blocking[0], MAX_Y + 1);
+        assert_eq!(surface[0], MAX_Y + 1);
+        assert_eq!(motion_blocking[HEIGHTMAP_COLUMNS - 1], MIN_Y + 1);
+        assert_eq!(surface[HEIGHTMAP_COLUMNS - 1], MIN_Y + 1);
+    }
+
+    #[test]
+    fn test_pack_heightmap_boundaries() {
+        let mut surface = [-60; HEIGHTMAP_COLUMNS];
+        surface[0] = MIN_Y;
+        surface[HEIGHTMAP_COLUMNS - 1] = MAX_Y;
+
+        let packed = pack_heightmap(&surface);
+        // 7 entries per long, so 256 columns take 37 longs
+        assert_eq!(packed.len(), 37);
+        // The first long holds the bottom of the world, then six columns at y = -60
+        let first = packed[0] as u64;
+        assert_eq!(first & 0x1FF, 0);
+        assert_eq!((first >> 9) & 0x1FF, 4);
+        // Only the low 63 bits of a long are used
+        assert_eq!(first >> 63, 0);
+
+        assert_eq!(unpack_heightmap(&packed), surface.to_vec());
+    }
+}
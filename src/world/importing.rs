@@ -5,11 +5,9 @@ use crate::utils::prelude::*;
 use crate::world::chunk_format::Chunk;
 use fastanvil::{ChunkData, Region};
 use indicatif::{ProgressBar, ProgressStyle};
-use nbt_lib::NBTDeserializeBytes;
 use rayon::prelude::*;
 use std::env;
 use std::fs::File;
-use std::io::Cursor;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
@@ -89,24 +87,24 @@ async fn get_total_chunks(dir: &PathBuf) -> Result<usize> {
         .sum())
 }
 
+/// Get the region coordinates from a region file name, e.g. `r.-1.2.mca` -> `(-1, 2)`
+fn region_coords(file_name: &str) -> Option<(i32, i32)> {
+    let mut parts = file_name.strip_prefix("r.")?.strip_suffix(".mca")?.split('.');
+    let x = parts.next()?.parse().ok()?;
+    let z = parts.next()?.parse().ok()?;
+    Some((x, z))
+}
+
 async fn process_chunk(
     chunk_data: Vec<u8>,
-    file_name: &str,
-    bar: Arc<ProgressBar>,
+    (chunk_x, chunk_z): (i32, i32),
 ) -> Result<SerializedChunk> {
-    let mut chunk = Chunk::read_from_bytes(&mut Cursor::new(chunk_data)).map_err(|e| {
-        bar.abandon_with_message(format!("Chunk {} failed to import", file_name));
-        Error::Generic(format!("Could not read chunk {} {}", e, file_name))
-    })?;
-
-    chunk.convert_to_net_mode().map_err(|e| {
-        bar.abandon_with_message(format!(
-            "Chunk {} {} failed to import",
-            chunk.x_pos, chunk.z_pos
-        ));
+    // A chunk that fails to parse is skipped rather than imported as air, so the database never
+    // holds a stand-in in place of the real chunk
+    let mut chunk = Chunk::from_nbt(chunk_data).map_err(|e| {
         Error::Generic(format!(
-            "Could not convert chunk {} {} to network mode: {}",
-            chunk.x_pos, chunk.z_pos, e
+            "Could not parse chunk at ({}, {}): {}",
+            chunk_x, chunk_z, e
         ))
     })?;
 
@@ -149,6 +147,10 @@ pub async fn import_regions(state: GlobalState) -> Result<()> {
     while let Some(dir_file) = region_files.next_entry().await? {
         let file_name = dir_file.file_name();
         let file_name = file_name.to_str().unwrap_or("unknown file");
+        let Some((region_x, region_z)) = region_coords(file_name) else {
+            warn!("(Skipped) Not a region file: {}", file_name);
+            continue;
+        };
         let file = File::open(dir_file.path())?;
         let mut region = Region::from_stream(file)?;
 
@@ -162,10 +164,13 @@ pub async fn import_regions(state: GlobalState) -> Result<()> {
                 .into_iter()
                 .map(|chunk| {
                     let data = chunk.data.clone();
+                    let coords = (
+                        region_x * 32 + chunk.x as i32,
+                        region_z * 32 + chunk.z as i32,
+                    );
                     let bar_clone = Arc::clone(&bar);
-                    let file_name = file_name.to_string();
                     tokio::spawn(async move {
-                        match process_chunk(data, &file_name, Arc::clone(&bar_clone)).await {
+                        match process_chunk(data, coords).await {
                             Ok(processed) => {
                                 bar_clone.inc(1);
                                 Some(processed)
@@ -0,0 +1,464 @@
+//! The mapping between item IDs and item names.
+//!
+//! Items need names so that held blocks can be placed and inventories can be saved, but the server
+//! doesn't bundle the full item registry. Pointing `registries_report` in the config at vanilla's
+//! `registries.json` report loads every item from it, otherwise only the handful of building blocks
+//! in [BUNDLED_ITEMS] are known.
+
+use hashbrown::HashMap;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+/// The first items of the 1.20.1 item registry, which are all blocks.
+const BUNDLED_ITEMS: &[&str] = &[
+    "minecraft:air",
+    "minecraft:stone",
+    "minecraft:granite",
+    "minecraft:polished_granite",
+    "minecraft:diorite",
+    "minecraft:polished_diorite",
+    "minecraft:andesite",
+    "minecraft:polished_andesite",
+    "minecraft:deepslate",
+    "minecraft:cobbled_deepslate",
+    "minecraft:polished_deepslate",
+    "minecraft:calcite",
+    "minecraft:tuff",
+    "minecraft:dripstone_block",
+    "minecraft:grass_block",
+    "minecraft:dirt",
+    "minecraft:coarse_dirt",
+    "minecraft:podzol",
+    "minecraft:rooted_dirt",
+    "minecraft:mud",
+    "minecraft:crimson_nylium",
+    "minecraft:warped_nylium",
+    "minecraft:cobblestone",
+    "minecraft:oak_planks",
+    "minecraft:spruce_planks",
+    "minecraft:birch_planks",
+    "minecraft:jungle_planks",
+    "minecraft:acacia_planks",
+    "minecraft:cherry_planks",
+    "minecraft:dark_oak_planks",
+    "minecraft:mangrove_planks",
+    "minecraft:bamboo_planks",
+    "minecraft:crimson_planks",
+    "minecraft:warped_planks",
+    "minecraft:bamboo_mosaic",
+];
+
+/// What tools and armor are made of, as the first part of their names.
+const TOOL_MATERIALS: &[&str] = &["wooden", "stone", "iron", "golden", "diamond", "netherite"];
+const TOOLS: &[&str] = &["sword", "shovel", "pickaxe", "axe", "hoe"];
+const ARMOR_MATERIALS: &[&str] = &[
+    "leather",
+    "chainmail",
+    "iron",
+    "golden",
+    "diamond",
+    "netherite",
+];
+/// The pieces of armor, from the head down.
+const ARMOR_PIECES: &[&str] = &["helmet", "chestplate", "leggings", "boots"];
+const DYE_COLORS: &[&str] = &[
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "light_gray",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+/// The woods boats come in. Bamboo makes rafts instead.
+const BOAT_WOODS: &[&str] = &[
+    "oak", "spruce", "birch", "jungle", "acacia", "cherry", "dark_oak", "mangrove",
+];
+const SIGN_WOODS: &[&str] = &[
+    "oak", "spruce", "birch", "jungle", "acacia", "cherry", "dark_oak", "mangrove", "bamboo",
+    "crimson", "warped",
+];
+/// Items that don't stack, other than tools, armor, boats, beds and shulker boxes.
+const UNSTACKABLE_ITEMS: &[&str] = &[
+    "turtle_helmet",
+    "elytra",
+    "shield",
+    "bow",
+    "crossbow",
+    "trident",
+    "fishing_rod",
+    "carrot_on_a_stick",
+    "warped_fungus_on_a_stick",
+    "flint_and_steel",
+    "shears",
+    "brush",
+    "spyglass",
+    "goat_horn",
+    "potion",
+    "splash_potion",
+    "lingering_potion",
+    "water_bucket",
+    "lava_bucket",
+    "milk_bucket",
+    "powder_snow_bucket",
+    "pufferfish_bucket",
+    "salmon_bucket",
+    "cod_bucket",
+    "tropical_fish_bucket",
+    "axolotl_bucket",
+    "tadpole_bucket",
+    "mushroom_stew",
+    "rabbit_stew",
+    "beetroot_soup",
+    "suspicious_stew",
+    "cake",
+    "saddle",
+    "minecart",
+    "chest_minecart",
+    "furnace_minecart",
+    "tnt_minecart",
+    "hopper_minecart",
+    "command_block_minecart",
+    "bamboo_raft",
+    "bamboo_chest_raft",
+    "leather_horse_armor",
+    "iron_horse_armor",
+    "golden_horse_armor",
+    "diamond_horse_armor",
+    "enchanted_book",
+    "writable_book",
+    "written_book",
+    "knowledge_book",
+    "debug_stick",
+    "totem_of_undying",
+    "shulker_box",
+    "flower_banner_pattern",
+    "creeper_banner_pattern",
+    "skull_banner_pattern",
+    "mojang_banner_pattern",
+    "globe_banner_pattern",
+    "piglin_banner_pattern",
+    "music_disc_13",
+    "music_disc_cat",
+    "music_disc_blocks",
+    "music_disc_chirp",
+    "music_disc_far",
+    "music_disc_mall",
+    "music_disc_mellohi",
+    "music_disc_stal",
+    "music_disc_strad",
+    "music_disc_ward",
+    "music_disc_11",
+    "music_disc_wait",
+    "music_disc_otherside",
+    "music_disc_5",
+    "music_disc_pigstep",
+    "music_disc_relic",
+];
+/// Items that stack to 16, other than signs and banners.
+const ITEMS_STACKING_TO_16: &[&str] = &[
+    "ender_pearl",
+    "snowball",
+    "egg",
+    "bucket",
+    "honey_bottle",
+    "armor_stand",
+];
+/// Items worn on the head that aren't armor.
+const HEAD_ITEMS: &[&str] = &[
+    "turtle_helmet",
+    "carved_pumpkin",
+    "skeleton_skull",
+    "wither_skeleton_skull",
+    "player_head",
+    "zombie_head",
+    "creeper_head",
+    "dragon_head",
+    "piglin_head",
+];
+
+lazy_static! {
+    /// The stack size of every item that doesn't stack to 64, by name.
+    static ref STACK_SIZES: HashMap<String, i8> = {
+        let mut unstackable = UNSTACKABLE_ITEMS
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        unstackable.extend(
+            TOOL_MATERIALS
+                .iter()
+                .flat_map(|material| TOOLS.iter().map(move |tool| format!("{material}_{tool}"))),
+        );
+        unstackable.extend(ARMOR_MATERIALS.iter().flat_map(|material| {
+            ARMOR_PIECES
+                .iter()
+                .map(move |piece| format!("{material}_{piece}"))
+        }));
+        unstackable.extend(
+            BOAT_WOODS
+                .iter()
+                .flat_map(|wood| [format!("{wood}_boat"), format!("{wood}_chest_boat")]),
+        );
+        unstackable.extend(
+            DYE_COLORS
+                .iter()
+                .flat_map(|color| [format!("{color}_bed"), format!("{color}_shulker_box")]),
+        );
+
+        let mut stacking_to_16 = ITEMS_STACKING_TO_16
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        stacking_to_16.extend(
+            SIGN_WOODS
+                .iter()
+                .flat_map(|wood| [format!("{wood}_sign"), format!("{wood}_hanging_sign")]),
+        );
+        stacking_to_16.extend(DYE_COLORS.iter().map(|color| format!("{color}_banner")));
+
+        unstackable
+            .into_iter()
+            .map(|name| (format!("minecraft:{name}"), 1))
+            .chain(
+                stacking_to_16
+                    .into_iter()
+                    .map(|name| (format!("minecraft:{name}"), 16)),
+            )
+            .collect()
+    };
+}
+
+/// Lookups between item IDs and item names.
+#[derive(Debug)]
+pub struct ItemRegistry {
+    names: HashMap<i32, String>,
+    ids: HashMap<String, i32>,
+}
+
+/// The part of vanilla's `registries.json` report the item registry comes from.
+#[derive(Deserialize)]
+struct RegistriesReport {
+    #[serde(rename = "minecraft:item")]
+    item: ReportRegistry,
+}
+
+#[derive(Deserialize)]
+struct ReportRegistry {
+    entries: HashMap<String, ReportEntry>,
+}
+
+#[derive(Deserialize)]
+struct ReportEntry {
+    protocol_id: i32,
+}
+
+impl ItemRegistry {
+    /// The items the server knows about without a report.
+    pub fn bundled() -> Self {
+        let names = BUNDLED_ITEMS
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (id as i32, name.to_string()))
+            .collect();
+        Self::from_names(names)
+    }
+
+    /// Builds the registry from vanilla's `registries.json` report, which lists every registry
+    /// along with the protocol ID of each entry.
+    pub fn from_registries_report(json: &str) -> Result<Self> {
+        let report: RegistriesReport =
+            serde_json::from_str(json).map_err(|e| Error::DeserializationError(e.to_string()))?;
+        let names = report
+            .item
+            .entries
+            .into_iter()
+            .map(|(name, entry)| (entry.protocol_id, name))
+            .collect();
+        Ok(Self::from_names(names))
+    }
+
+    fn from_names(names: HashMap<i32, String>) -> Self {
+        let ids = names.iter().map(|(id, name)| (name.clone(), *id)).collect();
+        Self { names, ids }
+    }
+
+    /// Loads the registry the server runs with: the report at `registries_report` if it's set,
+    /// otherwise the bundled items.
+    ///
+    /// A report that can't be read is logged and the bundled items are used instead.
+    pub fn load_configured() -> Self {
+        let report = &get_global_config().registries_report;
+        if !report.is_empty() {
+            match std::fs::read_to_string(report)
+                .map_err(Error::from)
+                .and_then(|json| Self::from_registries_report(&json))
+            {
+                Ok(registry) => {
+                    debug!("Loaded the item registry from {}", report);
+                    return registry;
+                }
+                Err(e) => warn!(
+                    "Failed to load the registries report {}: {}. Only the bundled items can be placed.",
+                    report, e
+                ),
+            }
+        }
+        Self::bundled()
+    }
+
+    /// The name of the item with the given ID.
+    pub fn name(&self, id: i32) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+
+    /// The ID of the item with the given name, which must include its namespace.
+    pub fn id(&self, name: &str) -> Option<i32> {
+        self.ids.get(name).copied()
+    }
+
+    /// How many of the item fit in one slot. Unknown items are taken to stack to 64.
+    pub fn max_stack_size(&self, id: i32) -> i8 {
+        self.name(id)
+            .and_then(|name| STACK_SIZES.get(name))
+            .copied()
+            .unwrap_or(64)
+    }
+
+    /// Which armor slot the item goes in, from 0 for the head to 3 for the feet, if it can be
+    /// worn.
+    pub fn armor_slot(&self, id: i32) -> Option<usize> {
+        let name = self.name(id)?.strip_prefix("minecraft:")?;
+        if HEAD_ITEMS.contains(&name) {
+            return Some(0);
+        }
+        if name == "elytra" {
+            return Some(1);
+        }
+        let (material, piece) = name.split_once('_')?;
+        if !ARMOR_MATERIALS.contains(&material) {
+            return None;
+        }
+        ARMOR_PIECES.iter().position(|armor| *armor == piece)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[cfg(not(test))]
+lazy_static! {
+    static ref ITEM_REGISTRY: ItemRegistry = ItemRegistry::load_configured();
+}
+
+// Like the block registry, tests always see the bundled items
+#[cfg(test)]
+lazy_static! {
+    static ref ITEM_REGISTRY: ItemRegistry = ItemRegistry::bundled();
+}
+
+/// The item registry the server runs with, loaded the first time it's needed.
+pub fn item_registry() -> &'static ItemRegistry {
+    &ITEM_REGISTRY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registries_report_lookups() {
+        let report = r#"{
+            "minecraft:block": {
+                "default": "minecraft:air",
+                "entries": {"minecraft:air": {"protocol_id": 0}},
+                "protocol_id": 4
+            },
+            "minecraft:item": {
+                "default": "minecraft:air",
+                "entries": {
+                    "minecraft:air": {"protocol_id": 0},
+                    "minecraft:diamond_sword": {"protocol_id": 800},
+                    "minecraft:stone": {"protocol_id": 1}
+                },
+                "protocol_id": 7
+            }
+        }"#;
+        let registry = ItemRegistry::from_registries_report(report).unwrap();
+        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.name(1), Some("minecraft:stone"));
+        assert_eq!(registry.name(800), Some("minecraft:diamond_sword"));
+        assert_eq!(registry.name(2), None);
+        assert_eq!(registry.id("minecraft:diamond_sword"), Some(800));
+        assert_eq!(registry.id("diamond_sword"), None);
+
+        assert!(ItemRegistry::from_registries_report("{}").is_err());
+    }
+
+    #[test]
+    fn test_stack_sizes_and_armor_slots() {
+        let report = r#"{
+            "minecraft:item": {
+                "entries": {
+                    "minecraft:stone": {"protocol_id": 1},
+                    "minecraft:ender_pearl": {"protocol_id": 2},
+                    "minecraft:cherry_hanging_sign": {"protocol_id": 3},
+                    "minecraft:diamond_sword": {"protocol_id": 4},
+                    "minecraft:chainmail_leggings": {"protocol_id": 5},
+                    "minecraft:carved_pumpkin": {"protocol_id": 6},
+                    "minecraft:leather_horse_armor": {"protocol_id": 7}
+                }
+            }
+        }"#;
+        let registry = ItemRegistry::from_registries_report(report).unwrap();
+        assert_eq!(registry.max_stack_size(1), 64);
+        assert_eq!(registry.max_stack_size(2), 16);
+        assert_eq!(registry.max_stack_size(3), 16);
+        assert_eq!(registry.max_stack_size(4), 1);
+        assert_eq!(registry.max_stack_size(5), 1);
+        assert_eq!(registry.max_stack_size(99), 64);
+
+        assert_eq!(registry.armor_slot(1), None);
+        assert_eq!(registry.armor_slot(4), None);
+        assert_eq!(registry.armor_slot(5), Some(2));
+        assert_eq!(registry.armor_slot(6), Some(0));
+        assert_eq!(registry.armor_slot(7), None);
+    }
+
+    #[test]
+    fn test_bundled_items_are_blocks() {
+        let registry = ItemRegistry::bundled();
+        assert_eq!(registry.name(0), Some("minecraft:air"));
+        assert_eq!(registry.name(23), Some("minecraft:oak_planks"));
+        for id in 1..registry.len() as i32 {
+            let name = registry.name(id).unwrap();
+            assert!(
+                crate::world::block_registry::block_registry()
+                    .id(&crate::world::chunk_format::Palette {
+                        name: name.to_string(),
+                        properties: None,
+                    })
+                    .is_some(),
+                "{} isn't a block",
+                name
+            );
+        }
+    }
+}
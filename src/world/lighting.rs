@@ -0,0 +1,596 @@
+//! Sky and block light, worked out from the blocks in a chunk.
+//!
+//! Light spreads one block at a time, losing at least one level per block and more through
+//! blocks that filter it, like water and leaves. Sky light starts at 15 in every block that can
+//! see the sky straight up and keeps that level all the way down until something filters it. Block
+//! light starts at the blocks that give off light, like torches and glowstone.
+//!
+//! Light is only spread within a chunk. Light from neighbouring chunks doesn't leak in, so the
+//! edges can be a little darker than vanilla.
+
+use std::collections::VecDeque;
+
+use lazy_static::lazy_static;
+
+use crate::world::block_changes::BlockChange;
+use crate::world::block_registry::block_registry;
+use crate::world::chunk_format::{Chunk, Palette};
+use crate::world::conversions::is_kind;
+use crate::world::dimension::Dimension;
+use crate::world::heightmap::{MAX_Y, MIN_Y};
+use crate::world::palette::SECTION_VOLUME;
+
+/// The brightest light can be.
+pub const MAX_LIGHT: u8 = 15;
+/// How tall the world is, in blocks.
+const HEIGHT: usize = (MAX_Y - MIN_Y + 1) as usize;
+/// The number of sections in a chunk.
+const SECTIONS: usize = HEIGHT / 16;
+/// The number of bytes in a section's light array, with two levels per byte.
+const NIBBLES_LEN: usize = SECTION_VOLUME / 2;
+
+lazy_static! {
+    /// How much light every block state takes away and gives off, indexed by block state ID.
+    static ref LIGHT_PROPERTIES: Vec<(u8, u8)> = (0..block_registry().len() as i32)
+        .map(|id| {
+            block_registry()
+                .state(id)
+                .map_or((MAX_LIGHT, 0), |state| (opacity(state), emission(state)))
+        })
+        .collect();
+}
+
+/// How many light levels are lost passing into the block. Light loses at least one level per
+/// block anyway, so see-through blocks are 0. Anything that isn't known to let light through blocks
+/// it entirely.
+pub fn opacity(state: &Palette) -> u8 {
+    let name = state.short_name();
+    // The colour doesn't change how much light gets through
+    let name = name
+        .strip_prefix("light_blue_")
+        .or_else(|| name.strip_prefix("light_gray_"))
+        .unwrap_or(name);
+    const CLEAR: &[&str] = &[
+        "air",
+        "glass",
+        "torch",
+        "lantern",
+        "sign",
+        "rail",
+        "button",
+        "pressure_plate",
+        "fence",
+        "fence_gate",
+        "door",
+        "trapdoor",
+        "sapling",
+        "propagule",
+        "carpet",
+        "ladder",
+        "vine",
+        "vines",
+        "vines_plant",
+        "lever",
+        "redstone_wire",
+        "repeater",
+        "comparator",
+        "tripwire",
+        "tripwire_hook",
+        "banner",
+        "head",
+        "skull",
+        "candle",
+        "flower_pot",
+        "slab",
+        "stairs",
+        "wall",
+        "chain",
+        "bars",
+        "pane",
+        "bed",
+        "chest",
+        "cake",
+        "anvil",
+        "cactus",
+        "scaffolding",
+        "lily_pad",
+        "kelp",
+        "kelp_plant",
+        "bamboo",
+        "bamboo_sapling",
+        "coral",
+        "coral_fan",
+        "coral_wall_fan",
+        "campfire",
+        "hopper",
+        "cauldron",
+        "bell",
+        "lectern",
+        "stonecutter",
+        "grindstone",
+        "conduit",
+        "end_rod",
+        "lightning_rod",
+        "pointed_dripstone",
+        "amethyst_cluster",
+        "bud",
+        "wheat",
+        "carrots",
+        "potatoes",
+        "beetroots",
+        "sugar_cane",
+        "nether_wart",
+        "berry_bush",
+        "cocoa",
+        "pumpkin_stem",
+        "melon_stem",
+        "roots",
+        "fungus",
+        "sprouts",
+        "dripleaf",
+        "dripleaf_stem",
+        "spore_blossom",
+        "lichen",
+        "vein",
+        "frogspawn",
+        "pitcher_plant",
+        "pitcher_crop",
+        "petals",
+        "egg",
+        "sea_pickle",
+        "structure_void",
+        "light",
+        "barrier",
+        "beacon",
+        "brewing_stand",
+        "enchanting_table",
+        "daylight_detector",
+        "fire",
+        "grass",
+        "fern",
+        "dead_bush",
+        "seagrass",
+        "poppy",
+        "dandelion",
+        "orchid",
+        "allium",
+        "bluet",
+        "tulip",
+        "daisy",
+        "cornflower",
+        "torchflower",
+        "torchflower_crop",
+        "lily_of_the_valley",
+        "wither_rose",
+        "sunflower",
+        "lilac",
+        "peony",
+        "rose_bush",
+        "mushroom",
+        "azalea",
+        "cobweb",
+        "snow",
+        "portal",
+    ];
+    const FILTERING: &[&str] = &["water", "leaves", "ice", "slime_block", "honey_block"];
+
+    // Full blocks whose names end like one of the see-through ones
+    const SOLID: &[&str] = &[
+        "packed_ice",
+        "blue_ice",
+        "sea_lantern",
+        "jack_o_lantern",
+        "tinted_glass",
+        "muddy_mangrove_roots",
+    ];
+    if SOLID.contains(&name) {
+        return MAX_LIGHT;
+    }
+    if is_kind(name, FILTERING) {
+        return 1;
+    }
+    if name.starts_with("potted_") || is_kind(name, CLEAR) {
+        return 0;
+    }
+    MAX_LIGHT
+}
+
+/// The light level the block gives off.
+pub fn emission(state: &Palette) -> u8 {
+    let name = state.short_name();
+    let property = |key: &str| {
+        state
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.get(key))
+            .map(String::as_str)
+    };
+    let lit = property("lit") != Some("false");
+
+    match name {
+        "glowstone"
+        | "sea_lantern"
+        | "jack_o_lantern"
+        | "lantern"
+        | "beacon"
+        | "shroomlight"
+        | "ochre_froglight"
+        | "verdant_froglight"
+        | "pearlescent_froglight"
+        | "lava"
+        | "fire"
+        | "conduit"
+        | "end_gateway"
+        | "end_portal" => 15,
+        "campfire" if lit => 15,
+        "redstone_lamp" if property("lit") == Some("true") => 15,
+        "torch" | "wall_torch" | "end_rod" => 14,
+        "furnace" | "blast_furnace" | "smoker" if property("lit") == Some("true") => 13,
+        "nether_portal" => 11,
+        "soul_torch" | "soul_wall_torch" | "soul_lantern" | "soul_fire" | "crying_obsidian" => 10,
+        "soul_campfire" if lit => 10,
+        "redstone_ore" | "deepslate_redstone_ore" if property("lit") == Some("true") => 9,
+        "redstone_torch" | "redstone_wall_torch" if lit => 7,
+        "glow_lichen" | "enchanting_table" | "ender_chest" => 7,
+        "amethyst_cluster" => 5,
+        "large_amethyst_bud" => 4,
+        "magma_block" => 3,
+        "medium_amethyst_bud" => 2,
+        "small_amethyst_bud" | "brewing_stand" | "dragon_egg" | "sculk_sensor" => 1,
+        "light" => property("level")
+            .and_then(|level| level.parse().ok())
+            .unwrap_or(MAX_LIGHT),
+        "respawn_anchor" => property("charges")
+            .and_then(|charges| charges.parse::<u8>().ok())
+            .map_or(0, |charges| (charges * 4).saturating_sub(1).min(MAX_LIGHT)),
+        "sea_pickle" if property("waterlogged") == Some("true") => property("pickles")
+            .and_then(|pickles| pickles.parse::<u8>().ok())
+            .map_or(0, |pickles| 3 + pickles * 3),
+        _ if is_kind(name, &["candle"]) && property("lit") == Some("true") => property("candles")
+            .and_then(|candles| candles.parse::<u8>().ok())
+            .map_or(0, |candles| candles * 3),
+        _ => 0,
+    }
+}
+
+/// The block at `id` takes away this much light, and gives off this much.
+fn light_properties(id: i32) -> (u8, u8) {
+    LIGHT_PROPERTIES
+        .get(id as usize)
+        .copied()
+        .unwrap_or((MAX_LIGHT, 0))
+}
+
+/// Index of a block in a chunk, counting `y` up from the bottom of the world.
+fn index(x: usize, y: usize, z: usize) -> usize {
+    (y * 16 + z) * 16 + x
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LightKind {
+    Sky,
+    Block,
+}
+
+/// The blocks and light levels of a whole chunk, bottom to top.
+struct ChunkLight {
+    opacity: Vec<u8>,
+    emission: Vec<u8>,
+    sky: Vec<u8>,
+    block: Vec<u8>,
+    has_skylight: bool,
+}
+
+impl ChunkLight {
+    /// Reads the blocks, and the light that's already there, out of a chunk in network mode.
+    fn read(chunk: &Chunk) -> Self {
+        let mut opacity = vec![0; HEIGHT * 256];
+        let mut emission = vec![0; HEIGHT * 256];
+        let mut sky = vec![0; HEIGHT * 256];
+        let mut block = vec![0; HEIGHT * 256];
+
+        for section in chunk.sections.iter().flatten() {
+            let Some(offset) = section_offset(section.y) else {
+                continue;
+            };
+            if let Some(block_states) = &section.block_states {
+                for (i, id) in block_states.block_ids().into_iter().enumerate() {
+                    (opacity[offset + i], emission[offset + i]) = light_properties(id);
+                }
+            }
+            unpack_nibbles(
+                section.sky_light.as_deref(),
+                &mut sky[offset..offset + SECTION_VOLUME],
+            );
+            unpack_nibbles(
+                section.block_light.as_deref(),
+                &mut block[offset..offset + SECTION_VOLUME],
+            );
+        }
+
+        let has_skylight = chunk
+            .dimension
+            .as_deref()
+            .map(Dimension::from_name)
+            .unwrap_or_default()
+            .has_skylight();
+
+        Self {
+            opacity,
+            emission,
+            sky,
+            block,
+            has_skylight,
+        }
+    }
+
+    /// Stores the light levels back into the chunk's sections.
+    fn write(&self, chunk: &mut Chunk) {
+        for section in chunk.sections.iter_mut().flatten() {
+            let Some(offset) = section_offset(section.y) else {
+                continue;
+            };
+            let range = offset..offset + SECTION_VOLUME;
+            section.block_light = Some(pack_nibbles(&self.block[range.clone()]));
+            section.sky_light = self.has_skylight.then(|| pack_nibbles(&self.sky[range]));
+        }
+    }
+
+    /// Works out the light between layers `low` and `high` again, from the bottom of the world.
+    /// The light just outside of them is left alone, and spreads back in.
+    fn relight(&mut self, kind: LightKind, low: usize, high: usize) {
+        let light = match kind {
+            LightKind::Sky => &mut self.sky,
+            LightKind::Block => &mut self.block,
+        };
+        light[index(0, low, 0)..index(0, high + 1, 0)].fill(0);
+
+        let mut queue = VecDeque::new();
+        let outside = [low.checked_sub(1), Some(high + 1).filter(|y| *y < HEIGHT)];
+        for y in outside.into_iter().flatten() {
+            let layer = light.iter().enumerate().take(index(0, y + 1, 0));
+            for (i, level) in layer.skip(index(0, y, 0)) {
+                if *level > 1 {
+                    queue.push_back(i);
+                }
+            }
+        }
+
+        match kind {
+            LightKind::Block => {
+                let layers = self.emission.iter().enumerate().take(index(0, high + 1, 0));
+                for (i, emission) in layers.skip(index(0, low, 0)) {
+                    if *emission > 0 {
+                        light[i] = *emission;
+                        queue.push_back(i);
+                    }
+                }
+            }
+            LightKind::Sky => {
+                for z in 0..16 {
+                    for x in 0..16 {
+                        // Everything above the first block that filters light sees the sky
+                        for y in (low..HEIGHT).rev() {
+                            let i = index(x, y, z);
+                            if self.opacity[i] > 0 {
+                                break;
+                            }
+                            if y <= high {
+                                light[i] = MAX_LIGHT;
+                                queue.push_back(i);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        while let Some(i) = queue.pop_front() {
+            let level = light[i];
+            let (x, z, y) = (i % 16, (i / 16) % 16, i / 256);
+            let neighbours = [
+                (x > 0).then(|| i - 1),
+                (x < 15).then(|| i + 1),
+                (z > 0).then(|| i - 16),
+                (z < 15).then(|| i + 16),
+                (y > 0).then(|| i - 256),
+                (y < HEIGHT - 1).then(|| i + 256),
+            ];
+            for neighbour in neighbours.into_iter().flatten() {
+                let cost = self.opacity[neighbour].max(1);
+                if level > cost && level - cost > light[neighbour] {
+                    light[neighbour] = level - cost;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+}
+
+/// Where a section's blocks start in a [ChunkLight], if the section is inside the world.
+fn section_offset(section_y: i8) -> Option<usize> {
+    let section = section_y as i32 - MIN_Y / 16;
+    (0..SECTIONS as i32)
+        .contains(&section)
+        .then(|| section as usize * SECTION_VOLUME)
+}
+
+fn unpack_nibbles(stored: Option<&[i8]>, levels: &mut [u8]) {
+    let Some(stored) = stored else {
+        return;
+    };
+    for (i, byte) in stored.iter().take(NIBBLES_LEN).enumerate() {
+        let byte = *byte as u8;
+        levels[i * 2] = byte & 0xF;
+        levels[i * 2 + 1] = byte >> 4;
+    }
+}
+
+fn pack_nibbles(levels: &[u8]) -> Vec<i8> {
+    levels
+        .chunks(2)
+        .map(|pair| (pair[0] | (pair[1] << 4)) as i8)
+        .collect()
+}
+
+/// Works out all the light in a chunk from scratch and stores it in its sections.
+pub fn light_chunk(chunk: &mut Chunk) {
+    let mut light = ChunkLight::read(chunk);
+    light.relight(LightKind::Block, 0, HEIGHT - 1);
+    if light.has_skylight {
+        light.relight(LightKind::Sky, 0, HEIGHT - 1);
+    }
+    light.write(chunk);
+}
+
+/// Updates the light in a chunk after blocks in it changed. The changes have to be saved to the
+/// chunk already.
+///
+/// Only the layers the changes can reach are worked out again: 15 blocks above and below them for
+/// block light, and everything underneath as well for sky light, since a new roof can shade the
+/// whole column.
+pub fn relight_changes(chunk: &mut Chunk, changes: &[BlockChange]) {
+    let layers = changes
+        .iter()
+        .map(|change| change.position.y as i32 - MIN_Y)
+        .filter(|y| (0..HEIGHT as i32).contains(y))
+        .map(|y| y as usize);
+    let (Some(low), Some(high)) = (layers.clone().min(), layers.max()) else {
+        return;
+    };
+    let reach = MAX_LIGHT as usize;
+    let high = (high + reach).min(HEIGHT - 1);
+
+    let mut light = ChunkLight::read(chunk);
+    light.relight(LightKind::Block, low.saturating_sub(reach), high);
+    if light.has_skylight {
+        light.relight(LightKind::Sky, 0, high);
+    }
+    light.write(chunk);
+}
+
+/// Whether any section in the chunk has light stored.
+pub fn has_light(chunk: &Chunk) -> bool {
+    chunk
+        .sections
+        .iter()
+        .flatten()
+        .any(|section| section.sky_light.is_some() || section.block_light.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::encoding::position::Position;
+    use crate::world::generation::flat::FlatWorldGenerator;
+    use crate::world::generation::{block_index, fill_section, ChunkGenerator};
+
+    fn block(name: &str) -> i32 {
+        Palette::parse(name).unwrap().block_id().unwrap()
+    }
+
+    /// The light level stored for the block at the given position.
+    fn level(chunk: &Chunk, kind: LightKind, x: usize, y: i32, z: usize) -> u8 {
+        let light = ChunkLight::read(chunk);
+        let levels = match kind {
+            LightKind::Sky => light.sky,
+            LightKind::Block => light.block,
+        };
+        levels[index(x, (y - MIN_Y) as usize, z)]
+    }
+
+    /// Replaces every block in the chunk, leaving air everywhere but the given blocks.
+    fn place(chunk: &mut Chunk, blocks: &[(usize, i32, usize, i32)]) {
+        for section in chunk.sections.as_mut().unwrap() {
+            let in_section = blocks
+                .iter()
+                .filter(|(_, y, _, _)| y >> 4 == section.y as i32)
+                .map(|(x, y, z, id)| (block_index(*x, *y, *z), *id))
+                .collect::<Vec<_>>();
+            fill_section(section, &in_section);
+        }
+    }
+
+    #[test]
+    fn test_properties() {
+        let state = |name: &str| Palette::parse(name).unwrap();
+        assert_eq!(opacity(&state("minecraft:air")), 0);
+        assert_eq!(opacity(&state("minecraft:stone")), 15);
+        assert_eq!(opacity(&state("minecraft:grass_block")), 15);
+        assert_eq!(opacity(&state("minecraft:oak_leaves")), 1);
+        assert_eq!(opacity(&state("minecraft:water")), 1);
+        assert_eq!(opacity(&state("minecraft:glass")), 0);
+        assert_eq!(opacity(&state("minecraft:bedrock")), 15);
+        assert_eq!(opacity(&state("minecraft:light_gray_wool")), 15);
+        assert_eq!(opacity(&state("minecraft:kelp_plant")), 0);
+        assert_eq!(opacity(&state("minecraft:dried_kelp_block")), 15);
+        assert_eq!(opacity(&state("minecraft:oak_wall_sign")), 0);
+        assert_eq!(opacity(&state("minecraft:mushroom_stem")), 15);
+        assert_eq!(opacity(&state("minecraft:packed_ice")), 15);
+        assert_eq!(emission(&state("minecraft:torch")), 14);
+        assert_eq!(emission(&state("minecraft:glowstone")), 15);
+        assert_eq!(emission(&state("minecraft:furnace[lit=false]")), 0);
+        assert_eq!(emission(&state("minecraft:stone")), 0);
+    }
+
+    #[test]
+    fn test_sky_light_stops_at_the_ground() {
+        let mut chunk = FlatWorldGenerator::new(&["minecraft:stone"])
+            .unwrap()
+            .generate_chunk(0, 0);
+        light_chunk(&mut chunk);
+
+        assert_eq!(level(&chunk, LightKind::Sky, 3, MAX_Y, 3), 15);
+        assert_eq!(level(&chunk, LightKind::Sky, 3, MIN_Y + 1, 3), 15);
+        assert_eq!(level(&chunk, LightKind::Sky, 3, MIN_Y, 3), 0);
+        assert_eq!(level(&chunk, LightKind::Block, 3, MIN_Y + 1, 3), 0);
+    }
+
+    #[test]
+    fn test_light_spreads_and_updates() {
+        let mut chunk = Chunk::empty(0, 0);
+        let stone = block("minecraft:stone");
+        let torch = block("minecraft:torch");
+
+        // A stone roof over the whole chunk at y = 100, with a torch under it
+        let mut blocks = (0..256)
+            .map(|column| (column % 16, 100, column / 16, stone))
+            .collect::<Vec<_>>();
+        blocks.push((8, 90, 8, torch));
+        place(&mut chunk, &blocks);
+        light_chunk(&mut chunk);
+
+        assert_eq!(level(&chunk, LightKind::Sky, 8, 101, 8), 15);
+        assert_eq!(level(&chunk, LightKind::Sky, 8, 99, 8), 0);
+        assert_eq!(level(&chunk, LightKind::Block, 8, 90, 8), 14);
+        assert_eq!(level(&chunk, LightKind::Block, 8, 93, 8), 11);
+        assert_eq!(level(&chunk, LightKind::Block, 10, 89, 9), 10);
+        assert_eq!(level(&chunk, LightKind::Block, 8, 110, 8), 0);
+
+        // Knocking a hole in the roof lets the sky in, and taking the torch away darkens it
+        blocks.retain(|(x, y, z, _)| !(*x == 8 && *z == 8 && (*y == 100 || *y == 90)));
+        place(&mut chunk, &blocks);
+        let changes = [
+            BlockChange {
+                position: Position::new(8, 100, 8),
+                block_state: 0,
+            },
+            BlockChange {
+                position: Position::new(8, 90, 8),
+                block_state: 0,
+            },
+        ];
+        relight_changes(&mut chunk, &changes);
+
+        assert_eq!(level(&chunk, LightKind::Sky, 8, 100, 8), 15);
+        assert_eq!(level(&chunk, LightKind::Sky, 8, 50, 8), 15);
+        assert_eq!(level(&chunk, LightKind::Sky, 9, 99, 8), 14);
+        assert_eq!(level(&chunk, LightKind::Block, 8, 90, 8), 0);
+        assert_eq!(level(&chunk, LightKind::Block, 8, 93, 8), 0);
+
+        // The same as working it all out again
+        let mut relit = chunk.clone();
+        light_chunk(&mut relit);
+        assert_eq!(relit, chunk);
+    }
+}
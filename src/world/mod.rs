@@ -1,7 +1,29 @@
+pub mod autosave;
+pub mod biome_registry;
+pub mod block_changes;
+pub mod block_entities;
+pub mod border;
+pub mod block_registry;
 pub mod blocks;
+pub mod chunk_cache;
+pub mod chunk_data;
 pub mod chunk_format;
 pub mod conversions;
+pub mod dimension;
+pub mod entities;
+pub mod generation;
+pub mod hardness;
+pub mod heightmap;
 pub mod importing;
+pub mod item_registry;
+pub mod lighting;
+pub mod palette;
+pub mod player_data;
+pub mod reach;
+pub mod region;
+pub mod spawn;
+pub mod time;
+pub mod weather;
 
 /// Since we don't know the exact amount of bytes, the first byte is the number of u8s in the last i64,
 /// so we know when to stop reading bytes from the last i64
@@ -0,0 +1,526 @@
+use std::borrow::Cow;
+
+use ferrumc_codec::network_types::varint::VarInt;
+
+use crate::world::chunk_format::BlockStates;
+use crate::world::conversions::block_state_count;
+
+/// The number of blocks in a section.
+pub const SECTION_VOLUME: usize = 16 * 16 * 16;
+/// Palettes that need more bits per entry than this can't be sent to the client, so those
+/// sections are sent with block state IDs in place of palette indices.
+pub const MAX_INDIRECT_BITS: u8 = 8;
+
+/// The number of bits used per block for a palette of the given length.
+///
+/// Block palettes never go below 4 bits per entry.
+pub fn bits_for_palette_len(len: usize) -> u8 {
+    let bits = (usize::BITS - len.saturating_sub(1).leading_zeros()) as u8;
+    bits.max(4)
+}
+
+/// The bits per entry of sections sent without a palette: enough for every block state ID.
+pub fn direct_bits() -> u8 {
+    (usize::BITS - block_state_count().saturating_sub(1).leading_zeros()) as u8
+}
+
+/// The blocks of a section as they're laid out on the wire.
+#[derive(Debug, PartialEq)]
+pub enum NetContainer<'a> {
+    /// Every block is the same, so only its ID is sent, with no data.
+    SingleValue { id: VarInt },
+    /// Palette indices, packed with `bits` bits each.
+    Indirect {
+        bits: u8,
+        palette: &'a [VarInt],
+        data: &'a [i64],
+    },
+    /// Block state IDs, packed with [direct_bits] bits each.
+    Direct { bits: u8, data: Cow<'a, [i64]> },
+}
+
+/// Unpacks `count` entries of `bits_per_entry` bits each. Entries never span multiple longs.
+pub fn unpack_entries(data: &[i64], bits_per_entry: u8, count: usize) -> Vec<u32> {
+    let entries_per_long = 64 / bits_per_entry as usize;
+    let mask = (1u64 << bits_per_entry) - 1;
+
+    let mut entries = Vec::with_capacity(count);
+    'longs: for &long in data {
+        let long = long as u64;
+        for i in 0..entries_per_long {
+            if entries.len() == count {
+                break 'longs;
+            }
+            entries.push(((long >> (i * bits_per_entry as usize)) & mask) as u32);
+        }
+    }
+    entries
+}
+
+/// Packs entries into longs, `bits_per_entry` bits each. Entries never span multiple longs.
+pub fn pack_entries(entries: &[u32], bits_per_entry: u8) -> Vec<i64> {
+    let entries_per_long = 64 / bits_per_entry as usize;
+    let mask = (1u64 << bits_per_entry) - 1;
+
+    entries
+        .chunks(entries_per_long)
+        .map(|chunk| {
+            chunk.iter().enumerate().fold(0u64, |long, (i, &entry)| {
+                long | ((entry as u64 & mask) << (i * bits_per_entry as usize))
+            }) as i64
+        })
+        .collect()
+}
+
+/// The entry at `index` of entries packed like [pack_entries].
+fn get_entry(data: &[i64], bits_per_entry: u8, index: usize) -> u32 {
+    let entries_per_long = 64 / bits_per_entry as usize;
+    let mask = (1u64 << bits_per_entry) - 1;
+    let shift = (index % entries_per_long) * bits_per_entry as usize;
+    data.get(index / entries_per_long)
+        .map_or(0, |&long| ((long as u64 >> shift) & mask) as u32)
+}
+
+/// Changes the entry at `index` of entries packed like [pack_entries].
+fn set_entry(data: &mut [i64], bits_per_entry: u8, index: usize, entry: u32) {
+    let entries_per_long = 64 / bits_per_entry as usize;
+    let mask = (1u64 << bits_per_entry) - 1;
+    let shift = (index % entries_per_long) * bits_per_entry as usize;
+    if let Some(long) = data.get_mut(index / entries_per_long) {
+        let cleared = *long as u64 & !(mask << shift);
+        *long = (cleared | ((entry as u64 & mask) << shift)) as i64;
+    }
+}
+
+/// The block that fills the whole section, if there's only one.
+fn single_block(palette: &[VarInt], data: &[i64], bits: u8) -> Option<VarInt> {
+    if palette.len() == 1 || data.is_empty() || bits == 0 {
+        return palette.first().cloned();
+    }
+
+    let mut entries = unpack_entries(data, bits, SECTION_VOLUME).into_iter();
+    let first = entries.next()?;
+    if entries.all(|entry| entry == first) {
+        palette.get(first as usize).cloned()
+    } else {
+        None
+    }
+}
+
+impl BlockStates {
+    /// A section in network mode from the block state ID of every block in it, indexed like
+    /// [BlockStates::block_ids].
+    pub fn from_block_ids(ids: &[i32]) -> Self {
+        let mut palette = Vec::new();
+        let entries = ids
+            .iter()
+            .map(|id| match palette.iter().position(|entry| entry == id) {
+                Some(entry) => entry as u32,
+                None => {
+                    palette.push(*id);
+                    palette.len() as u32 - 1
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let bits_per_block = bits_for_palette_len(palette.len());
+        BlockStates {
+            non_air_blocks: Some(ids.iter().filter(|id| **id != 0).count() as i16),
+            bits_per_block: Some(bits_per_block as i8),
+            data: Some(pack_entries(&entries, bits_per_block)),
+            palette: None,
+            net_palette: Some(palette.into_iter().map(VarInt::from).collect()),
+        }
+    }
+
+    /// Changes a single block, returning the block state it replaced. Only works for sections in
+    /// network mode.
+    ///
+    /// Only the block's own entry is changed, unless the section was a single block or its
+    /// palette outgrows the bits per block, in which case the section is packed again.
+    pub fn set_block(&mut self, index: usize, id: i32) -> i32 {
+        let palette = self.net_palette.as_deref().unwrap_or_default();
+        let data = self.data.as_deref().unwrap_or_default();
+        let bits = self
+            .bits_per_block
+            .map_or_else(|| bits_for_palette_len(palette.len()), |bits| bits as u8);
+
+        let packed = !data.is_empty() && bits != 0 && palette.len() > 1;
+        let old = if packed {
+            let entry = get_entry(data, bits, index);
+            palette.get(entry as usize).map_or(0, |old| old.get_val())
+        } else {
+            palette.first().map_or(0, |old| old.get_val())
+        };
+        if old == id {
+            return old;
+        }
+
+        let entry = match palette.iter().position(|entry| entry.get_val() == id) {
+            Some(entry) => entry,
+            None => palette.len(),
+        };
+        if !packed || bits_for_palette_len(entry + 1) > bits {
+            let mut ids = self.block_ids();
+            ids[index] = id;
+            *self = Self::from_block_ids(&ids);
+            return old;
+        }
+
+        let palette = self.net_palette.get_or_insert_with(Vec::new);
+        if entry == palette.len() {
+            palette.push(VarInt::from(id));
+        }
+        if let Some(data) = self.data.as_mut() {
+            set_entry(data, bits, index, entry as u32);
+        }
+        if let Some(non_air_blocks) = self.non_air_blocks.as_mut() {
+            *non_air_blocks += (id != 0) as i16 - (old != 0) as i16;
+        }
+        old
+    }
+
+    /// The block state ID of every block in the section, indexed `(y * 16 + z) * 16 + x`.
+    ///
+    /// Only works for sections in network mode. Anything that can't be read is air.
+    pub fn block_ids(&self) -> Vec<i32> {
+        let palette = self.net_palette.as_deref().unwrap_or_default();
+        let data = self.data.as_deref().unwrap_or_default();
+        let bits = self
+            .bits_per_block
+            .map_or_else(|| bits_for_palette_len(palette.len()), |bits| bits as u8);
+
+        if let Some(id) = single_block(palette, data, bits) {
+            return vec![id.get_val(); SECTION_VOLUME];
+        }
+        if bits == 0 {
+            return vec![0; SECTION_VOLUME];
+        }
+        let mut ids = unpack_entries(data, bits, SECTION_VOLUME)
+            .into_iter()
+            .map(|index| palette.get(index as usize).map_or(0, |id| id.get_val()))
+            .collect::<Vec<_>>();
+        ids.resize(SECTION_VOLUME, 0);
+        ids
+    }
+
+    /// How this section is sent to the client, from its network palette and block data.
+    ///
+    /// Sections made of a single block, most often air, only send that block's ID. Palettes of up
+    /// to [MAX_INDIRECT_BITS] bits are sent as they are. Anything bigger is unpacked and repacked
+    /// as block state IDs, which takes [direct_bits] bits per block. Returns `None` if the section
+    /// isn't in network mode.
+    pub fn net_container(&self) -> Option<NetContainer<'_>> {
+        let palette = self.net_palette.as_deref()?;
+        let data = self.data.as_deref().unwrap_or_default();
+        let bits = self
+            .bits_per_block
+            .map_or_else(|| bits_for_palette_len(palette.len()), |bits| bits as u8);
+
+        if let Some(id) = single_block(palette, data, bits) {
+            return Some(NetContainer::SingleValue { id });
+        }
+
+        if bits <= MAX_INDIRECT_BITS {
+            return Some(NetContainer::Indirect {
+                bits,
+                palette,
+                data,
+            });
+        }
+
+        let ids = unpack_entries(data, bits, SECTION_VOLUME)
+            .into_iter()
+            .map(|index| {
+                palette
+                    .get(index as usize)
+                    .map_or(0, |id| id.get_val() as u32)
+            })
+            .collect::<Vec<_>>();
+        let bits = direct_bits();
+        Some(NetContainer::Direct {
+            bits,
+            data: Cow::Owned(pack_entries(&ids, bits)),
+        })
+    }
+
+    /// Merges identical entries in the disk palette, remapping and repacking the block data to
+    /// match.
+    ///
+    /// Duplicate entries waste bits and can confuse the client, so this should run before the
+    /// palette is converted to network form.
+    pub fn dedup_palette(&mut self) {
+        let Some(palette) = self.palette.as_mut() else {
+            return;
+        };
+
+        // Maps each old palette index to its index in the deduplicated palette
+        let mut remap = Vec::with_capacity(palette.len());
+        let mut deduped = Vec::with_capacity(palette.len());
+        for entry in palette.drain(..) {
+            match deduped.iter().position(|e| *e == entry) {
+                Some(index) => remap.push(index as u32),
+                None => {
+                    remap.push(deduped.len() as u32);
+                    deduped.push(entry);
+                }
+            }
+        }
+
+        let old_len = remap.len();
+        let new_len = deduped.len();
+        *palette = deduped;
+
+        if old_len == new_len {
+            return;
+        }
+
+        let Some(data) = self.data.as_mut() else {
+            return;
+        };
+
+        let entries = unpack_entries(data, bits_for_palette_len(old_len), SECTION_VOLUME)
+            .into_iter()
+            .map(|index| remap.get(index as usize).copied().unwrap_or(0))
+            .collect::<Vec<_>>();
+
+        *data = pack_entries(&entries, bits_for_palette_len(new_len));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+
+    use super::*;
+    use crate::world::chunk_format::{Chunk, Palette};
+
+    fn palette(name: &str) -> Palette {
+        Palette {
+            name: name.to_string(),
+            properties: None,
+        }
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let entries = (0..SECTION_VOLUME as u32)
+            .map(|i| i % 17)
+            .collect::<Vec<_>>();
+        let packed = pack_entries(&entries, 5);
+        assert_eq!(packed.len(), SECTION_VOLUME.div_ceil(12));
+        assert_eq!(unpack_entries(&packed, 5, SECTION_VOLUME), entries);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_palette_entries_are_merged() {
+        // Stone shows up twice, e.g. from two generator layers being merged
+        let entries = (0..SECTION_VOLUME as u32)
+            .map(|i| i % 3)
+            .collect::<Vec<_>>();
+        let mut chunk = Chunk::empty(0, 0);
+        let section = &mut chunk.sections.as_mut().unwrap()[0];
+        section.block_states = Some(BlockStates {
+            non_air_blocks: None,
+            bits_per_block: None,
+            data: Some(pack_entries(&entries, 4)),
+            palette: Some(vec![
+                palette("minecraft:air"),
+                palette("minecraft:stone"),
+                palette("minecraft:stone"),
+            ]),
+            net_palette: None,
+        });
+
+        chunk.convert_to_net_mode().unwrap();
+
+        let section = &chunk.sections.as_ref().unwrap()[0];
+        let block_states = section.block_states.as_ref().unwrap();
+        assert_eq!(block_states.palette.as_ref().unwrap().len(), 2);
+        assert_eq!(block_states.net_palette.as_ref().unwrap().len(), 2);
+
+        let expected = entries.iter().map(|&i| i.min(1)).collect::<Vec<_>>();
+        assert_eq!(
+            unpack_entries(block_states.data.as_ref().unwrap(), 4, SECTION_VOLUME),
+            expected
+        );
+
+        // Non-air blocks count, bits per entry, palette length
+        let mut encoded = Vec::new();
+        section
+            .net_encode(&mut encoded, &EncodeOption::Default)
+            .await
+            .unwrap();
+        assert_eq!(encoded[2], 4);
+        assert_eq!(encoded[3], 2);
+    }
+
+    #[test]
+    fn test_small_palettes_are_sent_indirectly() {
+        let entries = (0..SECTION_VOLUME as u32)
+            .map(|i| i % 2)
+            .collect::<Vec<_>>();
+        let data = pack_entries(&entries, 4);
+        let block_states = BlockStates {
+            non_air_blocks: Some(2048),
+            bits_per_block: Some(4),
+            data: Some(data.clone()),
+            palette: None,
+            net_palette: Some(vec![VarInt::from(0), VarInt::from(1)]),
+        };
+
+        assert_eq!(
+            block_states.net_container(),
+            Some(NetContainer::Indirect {
+                bits: 4,
+                palette: &[VarInt::from(0), VarInt::from(1)],
+                data: &data,
+            })
+        );
+    }
+
+    #[test]
+    fn test_set_block() {
+        let mut chunk = Chunk::empty(0, 0);
+        let block_states = chunk.sections.as_mut().unwrap()[0]
+            .block_states
+            .as_mut()
+            .unwrap();
+
+        assert_eq!(block_states.set_block(17, 1), 0);
+        assert_eq!(block_states.non_air_blocks, Some(1));
+        let ids = block_states.block_ids();
+        assert_eq!(ids[17], 1);
+        assert_eq!(ids.iter().filter(|id| **id != 0).count(), 1);
+
+        // Breaking it again leaves nothing but air
+        assert_eq!(block_states.set_block(17, 0), 1);
+        assert_eq!(block_states.non_air_blocks, Some(0));
+        assert_eq!(
+            block_states.net_container(),
+            Some(NetContainer::SingleValue {
+                id: VarInt::from(0)
+            })
+        );
+    }
+
+    #[test]
+    fn test_set_block_keeps_other_blocks() {
+        let mut ids = (0..SECTION_VOLUME as i32)
+            .map(|i| i % 16)
+            .collect::<Vec<_>>();
+        let mut block_states = BlockStates::from_block_ids(&ids);
+        assert_eq!(block_states.bits_per_block, Some(4));
+
+        // Already in the palette, then a new entry that needs another bit
+        for (index, id) in [(100, 3), (200, 16), (4095, 0)] {
+            assert_eq!(block_states.set_block(index, id), ids[index]);
+            ids[index] = id;
+            assert_eq!(block_states.block_ids(), ids);
+        }
+        assert_eq!(block_states.bits_per_block, Some(5));
+        assert_eq!(
+            block_states.non_air_blocks,
+            Some(ids.iter().filter(|id| **id != 0).count() as i16)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_uniform_sections_send_a_single_value() {
+        // An empty section is just the non-air count, 0 bits, air and an empty data array
+        let mut chunk = Chunk::empty(0, 0);
+        let mut encoded = Vec::new();
+        chunk.sections.as_ref().unwrap()[0]
+            .net_encode(&mut encoded, &EncodeOption::Default)
+            .await
+            .unwrap();
+        assert_eq!(encoded, vec![0, 0, 0, 0, 0]);
+
+        // Palettes with more than one entry still count if only one of them is used
+        let block_states = BlockStates {
+            non_air_blocks: Some(4096),
+            bits_per_block: Some(4),
+            data: Some(pack_entries(&[1; SECTION_VOLUME], 4)),
+            palette: None,
+            net_palette: Some(vec![VarInt::from(0), VarInt::from(1)]),
+        };
+        assert_eq!(
+            block_states.net_container(),
+            Some(NetContainer::SingleValue {
+                id: VarInt::from(1)
+            })
+        );
+
+        let section = &mut chunk.sections.as_mut().unwrap()[0];
+        section.block_states = Some(block_states);
+        let mut encoded = Vec::new();
+        section
+            .net_encode(&mut encoded, &EncodeOption::Default)
+            .await
+            .unwrap();
+        assert_eq!(encoded, vec![0x10, 0x00, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_single_block_disk_sections_are_kept() {
+        let mut chunk = Chunk::empty(0, 0);
+        chunk.sections.as_mut().unwrap()[0].block_states = Some(BlockStates {
+            non_air_blocks: None,
+            bits_per_block: None,
+            data: None,
+            palette: Some(vec![palette("minecraft:stone")]),
+            net_palette: None,
+        });
+
+        chunk.convert_to_net_mode().unwrap();
+
+        let block_states = chunk.sections.as_ref().unwrap()[0]
+            .block_states
+            .as_ref()
+            .unwrap();
+        assert_eq!(block_states.non_air_blocks, Some(4096));
+        assert_eq!(block_states.bits_per_block, Some(0));
+        assert_eq!(
+            block_states.net_container(),
+            Some(NetContainer::SingleValue {
+                id: VarInt::from(1)
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_large_palettes_are_sent_directly() {
+        // 300 different blocks take 9 bits, more than an indirect palette can have
+        let entries = (0..SECTION_VOLUME as u32)
+            .map(|i| i % 300)
+            .collect::<Vec<_>>();
+        let net_palette = (0..300).map(|i| VarInt::from(i * 10)).collect::<Vec<_>>();
+        let block_states = BlockStates {
+            non_air_blocks: Some(4096 - 14),
+            bits_per_block: Some(9),
+            data: Some(pack_entries(&entries, 9)),
+            palette: None,
+            net_palette: Some(net_palette),
+        };
+
+        let Some(NetContainer::Direct { bits, data }) = block_states.net_container() else {
+            panic!("Expected a direct palette");
+        };
+        assert_eq!(bits, 15);
+        assert_eq!(data.len(), SECTION_VOLUME.div_ceil(4));
+        let ids = entries.iter().map(|i| i * 10).collect::<Vec<_>>();
+        assert_eq!(unpack_entries(&data, 15, SECTION_VOLUME), ids);
+
+        // Vanilla's layout: bits per entry, then the data length and the longs, with no palette
+        let mut chunk = Chunk::empty(0, 0);
+        let section = &mut chunk.sections.as_mut().unwrap()[0];
+        section.block_states = Some(block_states.clone());
+        let mut encoded = Vec::new();
+        section
+            .net_encode(&mut encoded, &EncodeOption::Default)
+            .await
+            .unwrap();
+        assert_eq!(&encoded[..5], &[0x0F, 0xF2, 15, 0x80, 0x08]);
+        assert_eq!(&encoded[5..13], &data[0].to_be_bytes());
+    }
+}
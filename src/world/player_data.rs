@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use nbt_lib::nbt_spec::serializer::impls::NBTFieldType;
+use nbt_lib::nbt_spec::serializer::tag_types::{TAG_BYTE, TAG_COMPOUND, TAG_END, TAG_STRING};
+use nbt_lib::nbt_spec::serializer::NBTAnonymousType;
+use nbt_lib::{NBTDeserialize, NBTDeserializeBytes, NBTError, NBTResult, NBTSerialize, NBTTag};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::state::{GlobalState, ServerState};
+use crate::utils::components::game_mode::GameMode;
+use crate::utils::components::inventory::{Inventory, HOTBAR_START, OFF_HAND_SLOT};
+use crate::utils::components::player::Player;
+use crate::utils::components::precise_position::PrecisePosition;
+use crate::utils::components::rotation::Rotation;
+use crate::utils::encoding::slot::{ItemStack, Slot};
+use crate::utils::prelude::*;
+use crate::world::dimension::Dimension;
+use crate::world::item_registry::item_registry;
+
+/// What's remembered about a player between sessions, laid out like vanilla's `playerdata` files
+/// so they can be opened with any NBT editor.
+#[derive(Debug, Clone, PartialEq, NBTSerialize, NBTDeserialize)]
+#[nbt(rename = "")]
+#[nbt(is_root)]
+pub struct PlayerData {
+    #[nbt(rename = "Pos")]
+    pub pos: Vec<f64>,
+    #[nbt(rename = "Rotation")]
+    pub rotation: Vec<f32>,
+    #[nbt(rename = "playerGameType")]
+    pub player_game_type: i32,
+    #[nbt(rename = "Dimension")]
+    pub dimension: Option<String>,
+    #[nbt(rename = "Inventory")]
+    pub inventory: Option<Vec<SavedItem>>,
+    #[nbt(rename = "SelectedItemSlot")]
+    pub selected_item_slot: Option<i32>,
+}
+
+impl PlayerData {
+    pub fn new(
+        position: &PrecisePosition,
+        rotation: &Rotation,
+        game_mode: GameMode,
+        dimension: Dimension,
+        inventory: &Inventory,
+    ) -> Self {
+        let items = inventory
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(window_slot, slot)| SavedItem::from_slot(window_slot, slot))
+            .collect();
+        Self {
+            pos: vec![position.x, position.y, position.z],
+            rotation: vec![rotation.yaw, rotation.pitch],
+            player_game_type: game_mode.id() as i32,
+            dimension: Some(dimension.name().to_string()),
+            inventory: Some(items),
+            selected_item_slot: Some(inventory.selected_slot as i32),
+        }
+    }
+
+    /// Exactly where the player was, if the saved position is complete.
+    pub fn position(&self) -> Option<PrecisePosition> {
+        match self.pos[..] {
+            [x, y, z] => Some(PrecisePosition::new(x, y, z)),
+            _ => None,
+        }
+    }
+
+    pub fn rotation(&self) -> Option<Rotation> {
+        match self.rotation[..] {
+            [yaw, pitch] => Some(Rotation::new(yaw, pitch)),
+            _ => None,
+        }
+    }
+
+    pub fn game_mode(&self) -> Option<GameMode> {
+        GameMode::from_id(self.player_game_type)
+    }
+
+    /// The dimension the player was in. Files saved before dimensions were, or from a dimension
+    /// the server doesn't have, put the player back in the overworld.
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+            .as_deref()
+            .map(Dimension::from_name)
+            .unwrap_or_default()
+    }
+
+    /// The items the player was carrying. Items the server doesn't know about are left out.
+    pub fn inventory(&self) -> Inventory {
+        let mut inventory = Inventory::default();
+        for item in self.inventory.iter().flatten() {
+            if let Some((window_slot, slot)) = item.to_slot() {
+                inventory.slots[window_slot] = slot;
+            }
+        }
+        if let Some(selected_slot) = self.selected_item_slot {
+            inventory.select(selected_slot as i16);
+        }
+        inventory
+    }
+}
+
+/// An item in a saved inventory. Vanilla numbers the slots differently to the inventory window,
+/// see [saved_slot].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedItem {
+    pub slot: i8,
+    /// The item's name, e.g. `minecraft:diamond_sword`.
+    pub id: String,
+    pub count: i8,
+    /// The entries of the item's `tag` compound, like its name or enchantments, followed by an end
+    /// tag.
+    pub tag: Option<Vec<u8>>,
+}
+
+impl SavedItem {
+    /// The item in a slot of the inventory window, or `None` if the slot is empty or isn't saved.
+    pub fn from_slot(window_slot: usize, slot: &Slot) -> Option<Self> {
+        let item = slot.item.as_ref()?;
+        let saved_slot = saved_slot(window_slot)?;
+        let Some(id) = item_registry().name(item.item_id) else {
+            warn!(
+                "Item {} isn't in the item registry, so it can't be saved",
+                item.item_id
+            );
+            return None;
+        };
+        Some(Self {
+            slot: saved_slot,
+            id: id.to_string(),
+            count: item.count,
+            tag: item.nbt.as_deref().and_then(compound_payload),
+        })
+    }
+
+    /// The window slot the item goes back into, along with the item.
+    pub fn to_slot(&self) -> Option<(usize, Slot)> {
+        let window_slot = window_slot(self.slot)?;
+        let item_id = item_registry().id(&self.id)?;
+        // Items are sent with an unnamed root compound
+        let nbt = self.tag.as_ref().map(|tag| {
+            let mut nbt = vec![TAG_COMPOUND, 0, 0];
+            nbt.extend_from_slice(tag);
+            nbt
+        });
+        let slot = Slot {
+            item: Some(ItemStack {
+                item_id,
+                count: self.count,
+                nbt,
+            }),
+        };
+        Some((window_slot, slot))
+    }
+}
+
+/// Where vanilla saves a slot of the inventory window: the hotbar is 0-8, the rest of the main
+/// inventory keeps its number, armor is 100-103 from the feet up and the off hand is -106. The
+/// crafting grid isn't saved.
+fn saved_slot(window_slot: usize) -> Option<i8> {
+    match window_slot {
+        5..=8 => Some(108 - window_slot as i8),
+        9..=35 => Some(window_slot as i8),
+        HOTBAR_START..=44 => Some((window_slot - HOTBAR_START) as i8),
+        OFF_HAND_SLOT => Some(-106),
+        _ => None,
+    }
+}
+
+/// The inventory window slot a saved slot goes back into.
+fn window_slot(saved_slot: i8) -> Option<usize> {
+    match saved_slot {
+        0..=8 => Some(HOTBAR_START + saved_slot as usize),
+        9..=35 => Some(saved_slot as usize),
+        100..=103 => Some(108 - saved_slot as usize),
+        -106 => Some(OFF_HAND_SLOT),
+        _ => None,
+    }
+}
+
+/// The entries of a compound sent over the network, without its tag type and name.
+fn compound_payload(nbt: &[u8]) -> Option<Vec<u8>> {
+    let [TAG_COMPOUND, high, low, rest @ ..] = nbt else {
+        return None;
+    };
+    let name_length = u16::from_be_bytes([*high, *low]) as usize;
+    rest.get(name_length..).map(<[u8]>::to_vec)
+}
+
+/// Takes one of a saved item's entries out of its compound.
+fn take_entry<T: NBTDeserialize>(entries: &mut HashMap<String, NBTTag>, key: &str) -> NBTResult<T> {
+    let entry = entries
+        .remove(key)
+        .ok_or_else(|| NBTError::DeserializeError(format!("Saved item is missing its {}", key)))?;
+    T::read_from(entry)
+}
+
+impl NBTDeserialize for SavedItem {
+    fn read_from(nbt: NBTTag) -> NBTResult<Self> {
+        let NBTTag::Compound(mut entries) = nbt else {
+            return Err(NBTError::InvalidType("SavedItem", nbt.my_type()));
+        };
+        let slot = take_entry(&mut entries, "Slot")?;
+        let id = take_entry(&mut entries, "id")?;
+        let count = take_entry(&mut entries, "Count")?;
+        let tag = match entries.remove("tag") {
+            Some(tag @ NBTTag::Compound(_)) => {
+                let mut payload = Vec::new();
+                tag.nbt_serialize(&mut payload)?;
+                Some(payload)
+            }
+            _ => None,
+        };
+        Ok(Self {
+            slot,
+            id,
+            count,
+            tag,
+        })
+    }
+}
+
+impl NBTSerialize for SavedItem {
+    fn nbt_serialize<W: Write>(&self, writer: &mut W) -> NBTResult<()> {
+        TAG_BYTE.nbt_serialize(writer)?;
+        "Slot".nbt_serialize(writer)?;
+        self.slot.nbt_serialize(writer)?;
+        TAG_STRING.nbt_serialize(writer)?;
+        "id".nbt_serialize(writer)?;
+        self.id.nbt_serialize(writer)?;
+        TAG_BYTE.nbt_serialize(writer)?;
+        "Count".nbt_serialize(writer)?;
+        self.count.nbt_serialize(writer)?;
+        if let Some(tag) = &self.tag {
+            TAG_COMPOUND.nbt_serialize(writer)?;
+            "tag".nbt_serialize(writer)?;
+            // Ends with the tag's own end tag
+            writer.write_all(tag)?;
+        }
+        TAG_END.nbt_serialize(writer)?;
+        Ok(())
+    }
+}
+
+impl NBTFieldType for SavedItem {
+    fn tag_type(&self) -> u8 {
+        TAG_COMPOUND
+    }
+}
+
+impl NBTAnonymousType for SavedItem {
+    fn tag_type() -> u8 {
+        TAG_COMPOUND
+    }
+}
+
+/// Player data files, one gzipped NBT file per player named after their UUID.
+#[derive(Debug)]
+pub struct PlayerDataStore {
+    dir: PathBuf,
+}
+
+impl PlayerDataStore {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, uuid: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.dat", uuid.hyphenated()))
+    }
+
+    /// Loads a player's saved data. Players that never joined before have none, and a file that
+    /// can't be read is logged and ignored, so the player just starts over at spawn.
+    pub async fn load_player(&self, uuid: Uuid) -> Option<PlayerData> {
+        let path = self.path(uuid);
+        let compressed = match tokio::fs::read(&path).await {
+            Ok(compressed) => compressed,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                warn!("Failed to read {}: {}", path.display(), e);
+                return None;
+            }
+        };
+
+        let mut data = Vec::new();
+        let parsed = GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut data)
+            .map_err(Error::from)
+            .and_then(|_| PlayerData::read_from_bytes(&mut Cursor::new(data)).map_err(Error::from));
+
+        match parsed {
+            Ok(player_data) => Some(player_data),
+            Err(e) => {
+                warn!("Failed to parse {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub async fn save_player(&self, uuid: Uuid, data: &PlayerData) -> Result<()> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        let mut nbt = Vec::new();
+        data.nbt_serialize(&mut nbt)?;
+        encoder.write_all(&nbt)?;
+        let compressed = encoder.finish()?;
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // Written next to the old file first, so a crash halfway through doesn't lose the save
+        let path = self.path(uuid);
+        let temp_path = path.with_extension("dat_tmp");
+        tokio::fs::write(&temp_path, compressed).await?;
+        tokio::fs::rename(&temp_path, &path).await?;
+        Ok(())
+    }
+}
+
+impl ServerState {
+    /// Saves where a player is, what game mode they're in and what they're carrying, so they get
+    /// it back when they rejoin. Players that never finished joining have nothing worth saving.
+    pub async fn save_player_data(self: &GlobalState, entity_id: usize, uuid: Uuid) -> Result<()> {
+        let data = {
+            let position = self.world.get_component::<PrecisePosition>(entity_id).await;
+            let rotation = self.world.get_component::<Rotation>(entity_id).await;
+            let game_mode = self.world.get_component::<GameMode>(entity_id).await;
+            let inventory = self.world.get_component::<Inventory>(entity_id).await;
+            let (Ok(position), Ok(rotation), Ok(game_mode), Ok(inventory)) =
+                (position, rotation, game_mode, inventory)
+            else {
+                return Ok(());
+            };
+            let dimension = self.dimension_of(entity_id).await;
+            PlayerData::new(&position, &rotation, *game_mode, dimension, &inventory)
+        };
+
+        self.player_data.save_player(uuid, &data).await
+    }
+
+    /// Saves every player that's online. One player failing to save doesn't stop the rest, so
+    /// errors are only logged.
+    pub async fn save_all_player_data(self: &GlobalState) {
+        let players = self
+            .world
+            .query::<&Player>()
+            .iter()
+            .await
+            .map(|(entity_id, player)| (entity_id, Uuid::from_u128(player.get_uuid())))
+            .collect::<Vec<_>>();
+
+        for (entity_id, uuid) in players {
+            if let Err(e) = self.save_player_data(entity_id, uuid).await {
+                warn!("Failed to save player data for {}: {}", uuid, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_player_position_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ferrumc-playerdata-{}", Uuid::new_v4()));
+        let store = PlayerDataStore::new(&dir);
+        let uuid = Uuid::new_v4();
+        assert_eq!(store.load_player(uuid).await, None);
+
+        let data = PlayerData::new(
+            &PrecisePosition::new(120.3, -11.5, -3399.75),
+            &Rotation::new(90.0, -15.5),
+            GameMode::Survival,
+            Dimension::Nether,
+            &Inventory::default(),
+        );
+        store.save_player(uuid, &data).await.unwrap();
+
+        let loaded = store.load_player(uuid).await.unwrap();
+        assert_eq!(loaded, data);
+        // Players come back exactly where they were, not at the corner of the block
+        assert_eq!(
+            loaded.position(),
+            Some(PrecisePosition::new(120.3, -11.5, -3399.75))
+        );
+        assert_eq!(loaded.game_mode(), Some(GameMode::Survival));
+        assert_eq!(loaded.dimension(), Dimension::Nether);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_inventory_round_trip() {
+        let mut inventory = Inventory::default();
+        // The hotbar, the main inventory, a helmet and the off hand
+        inventory.set_slot(HOTBAR_START as i16 + 3, Slot::new(1, 64));
+        inventory.set_slot(20, Slot::new(23, 5));
+        inventory.set_slot(5, Slot::new(14, 1));
+        inventory.set_slot(OFF_HAND_SLOT as i16, Slot::new(22, 32));
+        // A named item, which keeps its tag
+        let mut named = Slot::new(2, 1);
+        named.item.as_mut().unwrap().nbt =
+            Some(vec![TAG_COMPOUND, 0, 0, TAG_BYTE, 0, 1, b'a', 7, TAG_END]);
+        inventory.set_slot(HOTBAR_START as i16, named);
+        // The crafting grid isn't saved
+        inventory.set_slot(1, Slot::new(1, 1));
+        inventory.select(3);
+
+        let data = PlayerData::new(
+            &PrecisePosition::new(0.5, 64.0, 0.5),
+            &Rotation::new(0.0, 0.0),
+            GameMode::Creative,
+            Dimension::Overworld,
+            &inventory,
+        );
+        let items = data.inventory.as_ref().unwrap();
+        let mut slots = items.iter().map(|item| item.slot).collect::<Vec<_>>();
+        slots.sort();
+        assert_eq!(slots, vec![-106, 0, 3, 20, 103]);
+
+        let mut nbt = Vec::new();
+        data.nbt_serialize(&mut nbt).unwrap();
+        let loaded = PlayerData::read_from_bytes(&mut Cursor::new(nbt)).unwrap();
+        assert_eq!(loaded, data);
+
+        let loaded_inventory = loaded.inventory();
+        inventory.slots[1] = Slot::empty();
+        assert_eq!(loaded_inventory.slots, inventory.slots);
+        assert_eq!(loaded_inventory.selected_slot, 3);
+    }
+
+    #[test]
+    fn test_old_files_have_no_inventory() {
+        let data = PlayerData {
+            pos: vec![0.0, 64.0, 0.0],
+            rotation: vec![0.0, 0.0],
+            player_game_type: 0,
+            dimension: None,
+            inventory: None,
+            selected_item_slot: None,
+        };
+        assert_eq!(data.dimension(), Dimension::Overworld);
+        assert!(data.inventory().slots.iter().all(Slot::is_empty));
+    }
+}
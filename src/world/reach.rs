@@ -0,0 +1,23 @@
+use crate::state::GlobalState;
+use crate::utils::components::precise_position::PrecisePosition;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// How far from their eyes players can reach blocks, squared.
+pub const MAX_REACH_SQUARED: f64 = 6.0 * 6.0;
+/// How high a standing player's eyes are above their feet.
+pub const EYE_HEIGHT: f64 = 1.62;
+
+/// Whether a block is close enough to the player's eyes for them to reach it.
+pub async fn in_reach(state: &GlobalState, entity_id: usize, block: &Position) -> Result<bool> {
+    let player = *state
+        .world
+        .get_component::<PrecisePosition>(entity_id)
+        .await?;
+    let (dx, dy, dz) = (
+        block.x as f64 + 0.5 - player.x,
+        block.y as f64 + 0.5 - (player.y + EYE_HEIGHT),
+        block.z as f64 + 0.5 - player.z,
+    );
+    Ok(dx * dx + dy * dy + dz * dz <= MAX_REACH_SQUARED)
+}
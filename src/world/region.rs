@@ -0,0 +1,445 @@
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use nbt_lib::NBTSerialize;
+
+use crate::state::{GlobalState, ServerState};
+use crate::utils::config::{get_global_config, WorldGenerator};
+use crate::utils::prelude::*;
+use crate::world::chunk_format::Chunk;
+use crate::world::dimension::Dimension;
+
+/// Regions are 32x32 chunks, and the header is laid out in 4KiB sectors.
+const REGION_WIDTH: i32 = 32;
+const SECTOR_SIZE: u64 = 4096;
+
+/// How a chunk's NBT is compressed inside a region file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionCompression {
+    Gzip = 1,
+    Zlib = 2,
+    Uncompressed = 3,
+}
+
+impl TryFrom<u8> for RegionCompression {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            1 => Ok(Self::Gzip),
+            2 => Ok(Self::Zlib),
+            3 => Ok(Self::Uncompressed),
+            // The high bit means the chunk was too big for the region and lives in its own
+            // .mcc file, which isn't supported
+            _ => Err(Error::Generic(format!(
+                "Unsupported chunk compression type: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// The region file the chunk at `chunk_x`, `chunk_z` is stored in, e.g. `r.-1.2.mca`.
+pub fn region_file_path(dir: &Path, chunk_x: i32, chunk_z: i32) -> PathBuf {
+    dir.join(format!(
+        "r.{}.{}.mca",
+        chunk_x.div_euclid(REGION_WIDTH),
+        chunk_z.div_euclid(REGION_WIDTH)
+    ))
+}
+
+/// Reads the NBT of one chunk out of a region file, decompressed.
+///
+/// `local_x` and `local_z` are the chunk's position inside the region, 0 to 31. Returns `None`
+/// if the chunk was never generated.
+pub fn read_chunk_nbt<R: Read + Seek>(
+    region: &mut R,
+    local_x: usize,
+    local_z: usize,
+) -> Result<Option<Vec<u8>>> {
+    // The header starts with a table of where each chunk is: a 3 byte sector offset and a 1 byte
+    // sector count
+    let mut location = [0u8; 4];
+    region.seek(SeekFrom::Start(4 * (local_x + local_z * 32) as u64))?;
+    region.read_exact(&mut location)?;
+
+    let sector_offset = u32::from_be_bytes([0, location[0], location[1], location[2]]) as u64;
+    if sector_offset == 0 || location[3] == 0 {
+        return Ok(None);
+    }
+
+    // Each chunk starts with its length, which includes the compression type byte
+    let mut header = [0u8; 5];
+    region.seek(SeekFrom::Start(sector_offset * SECTOR_SIZE))?;
+    region.read_exact(&mut header)?;
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    if length == 0 || length as u64 > location[3] as u64 * SECTOR_SIZE {
+        return Err(Error::Generic(format!("Invalid chunk length: {}", length)));
+    }
+    let compression = RegionCompression::try_from(header[4])?;
+
+    let mut compressed = vec![0u8; length - 1];
+    region.read_exact(&mut compressed)?;
+
+    let mut nbt = Vec::new();
+    match compression {
+        RegionCompression::Gzip => {
+            GzDecoder::new(compressed.as_slice()).read_to_end(&mut nbt)?;
+        }
+        RegionCompression::Zlib => {
+            ZlibDecoder::new(compressed.as_slice()).read_to_end(&mut nbt)?;
+        }
+        RegionCompression::Uncompressed => nbt = compressed,
+    }
+
+    Ok(Some(nbt))
+}
+
+/// Writes the NBT of one chunk into a region file, zlib compressed, and stamps it with
+/// `timestamp` in seconds.
+///
+/// The chunk goes back into its old sectors if it still fits. Otherwise it's moved to the first
+/// free run of sectors that's big enough, growing the file if there isn't one. An empty `region`
+/// gets a fresh header.
+pub fn write_chunk_nbt<F: Read + Write + Seek>(
+    region: &mut F,
+    local_x: usize,
+    local_z: usize,
+    nbt: &[u8],
+    timestamp: u32,
+) -> Result<()> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(nbt)?;
+    let compressed = encoder.finish()?;
+
+    // The length includes the compression type byte, and is followed by it
+    let length = compressed.len() as u32 + 1;
+    let sector_count = (length as u64 + 4).div_ceil(SECTOR_SIZE);
+    if sector_count > u8::MAX as u64 {
+        return Err(Error::Generic(format!(
+            "Chunk is too big for a region file: {} bytes",
+            length
+        )));
+    }
+
+    let file_len = region.seek(SeekFrom::End(0))?;
+    let mut locations = [0u8; SECTOR_SIZE as usize];
+    region.seek(SeekFrom::Start(0))?;
+    if file_len < 2 * SECTOR_SIZE {
+        // Both the location and timestamp tables start out zeroed
+        region.write_all(&[0u8; 2 * SECTOR_SIZE as usize])?;
+    } else {
+        region.read_exact(&mut locations)?;
+    }
+
+    let index = 4 * (local_x + local_z * 32);
+    let (old_offset, old_count) = location_entry(&locations, index);
+    let sector_offset = if old_offset != 0 && old_count as u64 >= sector_count {
+        old_offset
+    } else {
+        find_free_sectors(&locations, index, sector_count, file_len)
+    };
+
+    // Write the chunk before pointing the header at it, so a chunk that's being moved is never
+    // left pointing at half-written sectors
+    let padding = (sector_count * SECTOR_SIZE) as usize - compressed.len() - 5;
+    region.seek(SeekFrom::Start(sector_offset * SECTOR_SIZE))?;
+    region.write_all(&length.to_be_bytes())?;
+    region.write_all(&[RegionCompression::Zlib as u8])?;
+    region.write_all(&compressed)?;
+    region.write_all(&vec![0u8; padding])?;
+
+    let offset_bytes = (sector_offset as u32).to_be_bytes();
+    region.seek(SeekFrom::Start(index as u64))?;
+    region.write_all(&[
+        offset_bytes[1],
+        offset_bytes[2],
+        offset_bytes[3],
+        sector_count as u8,
+    ])?;
+    region.seek(SeekFrom::Start(SECTOR_SIZE + index as u64))?;
+    region.write_all(&timestamp.to_be_bytes())?;
+    region.flush()?;
+
+    Ok(())
+}
+
+/// The sector offset and sector count stored at `index` in the location table.
+fn location_entry(locations: &[u8], index: usize) -> (u64, u8) {
+    let offset = u32::from_be_bytes([
+        0,
+        locations[index],
+        locations[index + 1],
+        locations[index + 2],
+    ]);
+    (offset as u64, locations[index + 3])
+}
+
+/// Finds the first run of `count` sectors that no chunk other than the one at `index` uses. The
+/// run may reach past the end of the file.
+fn find_free_sectors(locations: &[u8], index: usize, count: u64, file_len: u64) -> u64 {
+    // The first two sectors are the header
+    let mut used = vec![false; file_len.div_ceil(SECTOR_SIZE).max(2) as usize];
+    used[0] = true;
+    used[1] = true;
+    for other in (0..locations.len())
+        .step_by(4)
+        .filter(|other| *other != index)
+    {
+        let (offset, sectors) = location_entry(locations, other);
+        if offset == 0 {
+            continue;
+        }
+        for sector in offset..offset + sectors as u64 {
+            if sector as usize >= used.len() {
+                used.resize(sector as usize + 1, false);
+            }
+            used[sector as usize] = true;
+        }
+    }
+
+    let mut run_start = 2;
+    for (sector, in_use) in used.iter().enumerate().skip(2) {
+        if *in_use {
+            run_start = sector as u64 + 1;
+        } else if sector as u64 + 1 - run_start == count {
+            break;
+        }
+    }
+    run_start
+}
+
+/// Loads a chunk straight out of the region files in `dir`, in network mode.
+///
+/// Returns `None` if the region or the chunk doesn't exist, and an error if the chunk is there but
+/// can't be read.
+pub async fn load_region_chunk(dir: &Path, chunk_x: i32, chunk_z: i32) -> Result<Option<Chunk>> {
+    let path = region_file_path(dir, chunk_x, chunk_z);
+    let local_x = chunk_x.rem_euclid(REGION_WIDTH) as usize;
+    let local_z = chunk_z.rem_euclid(REGION_WIDTH) as usize;
+
+    tokio::task::spawn_blocking(move || -> Result<Option<Chunk>> {
+        let mut file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        read_chunk_nbt(&mut file, local_x, local_z)?
+            .map(Chunk::from_nbt)
+            .transpose()
+    })
+    .await
+    .map_err(|e| Error::Generic(format!("Failed to load chunk: {}", e)))?
+}
+
+/// Only one region file is written at a time, so two chunks in the same region can't claim the
+/// same free sectors.
+static REGION_WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Saves a chunk into the region files in `dir`, creating the directory and region file if
+/// needed.
+pub async fn save_region_chunk(dir: &Path, chunk: &Chunk) -> Result<()> {
+    let mut nbt = Vec::new();
+    chunk.to_disk_mode()?.nbt_serialize(&mut nbt)?;
+
+    let dir = dir.to_path_buf();
+    let path = region_file_path(&dir, chunk.x_pos, chunk.z_pos);
+    let local_x = chunk.x_pos.rem_euclid(REGION_WIDTH) as usize;
+    let local_z = chunk.z_pos.rem_euclid(REGION_WIDTH) as usize;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs() as u32)
+        .unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let _guard = REGION_WRITE_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::fs::create_dir_all(&dir)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        write_chunk_nbt(&mut file, local_x, local_z, &nbt, timestamp)
+    })
+    .await
+    .map_err(|e| Error::Generic(format!("Failed to save chunk: {}", e)))?
+}
+
+impl ServerState {
+    /// Saves a modified chunk to wherever the configured generator loads it from, so the change
+    /// survives a restart. Region files go in the directory of the chunk's dimension, for
+    /// superflat worlds as well as anvil ones.
+    ///
+    /// The debug generator rebuilds every chunk from scratch, so there is nothing to save.
+    pub async fn save_chunk(self: &GlobalState, chunk: &Chunk) -> Result<()> {
+        match get_global_config().generator {
+            WorldGenerator::Anvil | WorldGenerator::Flat => {
+                let dimension = chunk
+                    .dimension
+                    .as_deref()
+                    .map(Dimension::from_name)
+                    .unwrap_or_default();
+                let region_dir = dimension.region_dir(Path::new(&get_global_config().region_dir));
+                save_region_chunk(&region_dir, chunk).await
+            }
+            WorldGenerator::Imported => self.database.update_chunk(chunk.clone()).await,
+            WorldGenerator::Debug => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::{Cursor, Write};
+
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+
+    use super::*;
+
+    /// A region with a single chunk in it, at `local_x`, `local_z`, stored in sector 2.
+    pub(crate) fn region_with_chunk(
+        local_x: usize,
+        local_z: usize,
+        compression: u8,
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut region = vec![0u8; 2 * SECTOR_SIZE as usize];
+        let index = 4 * (local_x + local_z * 32);
+        region[index..index + 4].copy_from_slice(&[0, 0, 2, 1]);
+
+        region.extend_from_slice(&(data.len() as u32 + 1).to_be_bytes());
+        region.push(compression);
+        region.extend_from_slice(data);
+        region.resize(3 * SECTOR_SIZE as usize, 0);
+        region
+    }
+
+    const NBT: &[u8] = &[
+        0x0A, 0x00, 0x00, 0x03, 0x00, 0x01, b'x', 0x00, 0x00, 0x00, 0x2A, 0x00,
+    ];
+
+    #[test]
+    fn test_read_zlib_chunk() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(NBT).unwrap();
+        let region = region_with_chunk(5, 7, 2, &encoder.finish().unwrap());
+
+        let nbt = read_chunk_nbt(&mut Cursor::new(region), 5, 7).unwrap();
+        assert_eq!(nbt.as_deref(), Some(NBT));
+    }
+
+    #[test]
+    fn test_read_gzip_chunk() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(NBT).unwrap();
+        let region = region_with_chunk(0, 31, 1, &encoder.finish().unwrap());
+
+        let nbt = read_chunk_nbt(&mut Cursor::new(region), 0, 31).unwrap();
+        assert_eq!(nbt.as_deref(), Some(NBT));
+    }
+
+    #[test]
+    fn test_missing_chunk() {
+        let region = region_with_chunk(5, 7, 3, NBT);
+        assert_eq!(
+            read_chunk_nbt(&mut Cursor::new(region), 6, 7).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_write_then_read_chunk() {
+        let mut region = Cursor::new(Vec::new());
+        write_chunk_nbt(&mut region, 3, 4, NBT, 1234).unwrap();
+        write_chunk_nbt(&mut region, 4, 4, NBT, 5678).unwrap();
+
+        let bytes = region.get_ref();
+        assert_eq!(bytes.len() as u64, 4 * SECTOR_SIZE);
+        let index = 4 * (3 + 4 * 32);
+        assert_eq!(&bytes[index..index + 4], &[0, 0, 2, 1]);
+        let timestamp = SECTOR_SIZE as usize + index;
+        assert_eq!(&bytes[timestamp..timestamp + 4], &1234u32.to_be_bytes());
+
+        assert_eq!(
+            read_chunk_nbt(&mut region, 3, 4).unwrap().as_deref(),
+            Some(NBT)
+        );
+        assert_eq!(
+            read_chunk_nbt(&mut region, 4, 4).unwrap().as_deref(),
+            Some(NBT)
+        );
+        assert_eq!(read_chunk_nbt(&mut region, 5, 4).unwrap(), None);
+    }
+
+    #[test]
+    fn test_grown_chunk_moves_to_free_sectors() {
+        let mut region = Cursor::new(Vec::new());
+        write_chunk_nbt(&mut region, 0, 0, NBT, 0).unwrap();
+        write_chunk_nbt(&mut region, 1, 0, NBT, 0).unwrap();
+
+        // Random bytes don't compress, so this needs two sectors and can't stay in sector 2
+        let mut state = 0x2545F491u32;
+        let big = (0..6000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect::<Vec<_>>();
+        write_chunk_nbt(&mut region, 0, 0, &big, 0).unwrap();
+        assert_eq!(&region.get_ref()[0..4], &[0, 0, 4, 2]);
+        assert_eq!(read_chunk_nbt(&mut region, 0, 0).unwrap(), Some(big));
+        assert_eq!(
+            read_chunk_nbt(&mut region, 1, 0).unwrap().as_deref(),
+            Some(NBT)
+        );
+
+        // Sector 2 is free again, so a small chunk is put back there
+        write_chunk_nbt(&mut region, 2, 0, NBT, 0).unwrap();
+        assert_eq!(&region.get_ref()[8..12], &[0, 0, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_saved_chunk_loads_back() {
+        let dir = std::env::temp_dir().join(format!("ferrumc-region-{}", std::process::id()));
+        let chunk = Chunk::empty(-3, 40);
+        save_region_chunk(&dir, &chunk).await.unwrap();
+
+        let loaded = load_region_chunk(&dir, -3, 40).await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded, chunk);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_chunk_is_an_error() {
+        let dir =
+            std::env::temp_dir().join(format!("ferrumc-region-corrupt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let region = region_with_chunk(1, 2, 3, &[0x0A, 0x00, 0x00, 0x63, 0xFF, 0x12, 0x00]);
+        std::fs::write(region_file_path(&dir, 1, 2), region).unwrap();
+
+        let loaded = load_region_chunk(&dir, 1, 2).await;
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn test_region_file_path() {
+        let dir = Path::new("region");
+        assert_eq!(region_file_path(dir, 0, 31), dir.join("r.0.0.mca"));
+        assert_eq!(region_file_path(dir, -1, 32), dir.join("r.-1.1.mca"));
+        assert_eq!(region_file_path(dir, -33, -32), dir.join("r.-2.-1.mca"));
+    }
+}
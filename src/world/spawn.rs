@@ -0,0 +1,95 @@
+use std::sync::RwLock;
+
+use crate::net::packets::outgoing::default_spawn_position::DefaultSpawnPosition;
+use crate::net::utils::broadcast::broadcast;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::constants::init;
+use crate::utils::encoding::position::Position;
+use crate::utils::prelude::*;
+
+/// Where players spawn, and which way the compass points.
+#[derive(Debug)]
+pub struct WorldSpawn {
+    spawn: RwLock<(Position, f32)>,
+}
+
+impl Default for WorldSpawn {
+    fn default() -> Self {
+        let position = Position {
+            x: init::DEFAULT_SPAWN_X_POS,
+            y: init::DEFAULT_SPAWN_Y_POS,
+            z: init::DEFAULT_SPAWN_Z_POS,
+        };
+        Self {
+            spawn: RwLock::new((position, 0.0)),
+        }
+    }
+}
+
+impl WorldSpawn {
+    pub fn position(&self) -> Position {
+        self.spawn.read().unwrap().0.clone()
+    }
+
+    pub fn angle(&self) -> f32 {
+        self.spawn.read().unwrap().1
+    }
+
+    /// The [DefaultSpawnPosition] packet for the current spawn.
+    pub fn spawn_position_packet(&self) -> DefaultSpawnPosition {
+        let (position, angle) = self.spawn.read().unwrap().clone();
+        DefaultSpawnPosition::new_auto(position, angle)
+    }
+}
+
+impl ServerState {
+    /// Moves the world spawn and tells every player about it, so their compasses and respawn
+    /// points follow.
+    pub async fn set_world_spawn(self: &GlobalState, position: Position, angle: f32) -> Result<()> {
+        *self.world_spawn.spawn.write().unwrap() = (position, angle);
+
+        let packet = self.world_spawn.spawn_position_packet();
+        broadcast(&packet, self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrumc_codec::enc::{EncodeOption, NetEncode};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    use crate::tests::connections::add_play_connection;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_world_spawn_is_broadcast() {
+        let state = crate::create_state(TcpListener::bind("0.0.0.0:0").await.unwrap())
+            .await
+            .unwrap();
+        let (_, first) = add_play_connection(&state).await;
+        let (_, second) = add_play_connection(&state).await;
+
+        let position = Position {
+            x: 100,
+            y: 72,
+            z: -50,
+        };
+        state.set_world_spawn(position.clone(), 90.0).await.unwrap();
+        assert_eq!(state.world_spawn.position(), position);
+        assert_eq!(state.world_spawn.angle(), 90.0);
+
+        let mut expected = Vec::new();
+        DefaultSpawnPosition::new_auto(position, 90.0)
+            .net_encode(&mut expected, &EncodeOption::Default)
+            .await
+            .unwrap();
+
+        for mut client in [first, second] {
+            let mut received = vec![0u8; expected.len()];
+            client.read_exact(&mut received).await.unwrap();
+            assert_eq!(received, expected);
+        }
+    }
+}
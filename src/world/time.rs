@@ -0,0 +1,122 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use crate::net::packets::outgoing::update_time::UpdateTime;
+use crate::net::utils::broadcast::broadcast;
+use crate::state::{GlobalState, ServerState};
+use crate::utils::config::get_global_config;
+use crate::utils::prelude::*;
+
+/// The length of a full day/night cycle, in ticks.
+pub const TICKS_PER_DAY: i64 = 24000;
+
+/// Keeps track of the world age and the current time of day.
+///
+/// Both values are in ticks. The world age always advances, while the time of day
+/// only advances when the daylight cycle is enabled.
+#[derive(Debug, Default)]
+pub struct WorldTime {
+    world_age: AtomicI64,
+    time_of_day: AtomicI64,
+}
+
+impl WorldTime {
+    pub fn new(world_age: i64, time_of_day: i64) -> Self {
+        Self {
+            world_age: AtomicI64::new(world_age),
+            time_of_day: AtomicI64::new(time_of_day),
+        }
+    }
+
+    pub fn world_age(&self) -> i64 {
+        self.world_age.load(Ordering::Relaxed)
+    }
+
+    pub fn time_of_day(&self) -> i64 {
+        self.time_of_day.load(Ordering::Relaxed)
+    }
+
+    pub fn set_time_of_day(&self, time_of_day: i64) {
+        self.time_of_day.store(time_of_day, Ordering::Relaxed);
+    }
+
+    /// The number of days that have gone by, counting from 0.
+    pub fn day(&self) -> i64 {
+        self.time_of_day().div_euclid(TICKS_PER_DAY)
+    }
+
+    /// Advance the clock by a single tick.
+    pub fn tick(&self, do_daylight_cycle: bool) {
+        self.world_age.fetch_add(1, Ordering::Relaxed);
+        if do_daylight_cycle {
+            self.time_of_day.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Build the [UpdateTime] packet for the current clock.
+    pub fn update_time_packet(&self, do_daylight_cycle: bool) -> UpdateTime {
+        UpdateTime::new(
+            self.world_age(),
+            network_time_of_day(self.time_of_day(), do_daylight_cycle),
+        )
+    }
+}
+
+impl ServerState {
+    /// Changes the time of day, and tells every player straight away instead of at the next sync.
+    pub async fn set_time_of_day(self: &GlobalState, time_of_day: i64) -> Result<()> {
+        self.world_time.set_time_of_day(time_of_day);
+        let do_daylight_cycle = get_global_config().gamerules.do_daylight_cycle;
+        let packet = self.world_time.update_time_packet(do_daylight_cycle);
+        broadcast(&packet, self).await
+    }
+}
+
+/// The time of day as it should be sent to the client.
+///
+/// When the daylight cycle is disabled the time is negated, which freezes the sun client-side.
+/// A time of 0 can't be negated, so vanilla sends -1 instead.
+pub fn network_time_of_day(time_of_day: i64, do_daylight_cycle: bool) -> i64 {
+    if do_daylight_cycle {
+        return time_of_day;
+    }
+    match -time_of_day {
+        0 => -1,
+        frozen => frozen,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frozen_time_is_negated() {
+        let time = WorldTime::new(0, 6000);
+        time.tick(false);
+        time.tick(false);
+
+        let packet = time.update_time_packet(false);
+        assert_eq!(packet.world_age, 2);
+        assert_eq!(packet.time_of_day, -6000);
+
+        let time = WorldTime::new(0, 0);
+        assert_eq!(time.update_time_packet(false).time_of_day, -1);
+    }
+
+    #[test]
+    fn test_time_advances_with_daylight_cycle() {
+        let time = WorldTime::new(0, 6000);
+        time.tick(true);
+        time.tick(true);
+
+        let packet = time.update_time_packet(true);
+        assert_eq!(packet.world_age, 2);
+        assert_eq!(packet.time_of_day, 6002);
+    }
+
+    #[test]
+    fn test_days_are_counted() {
+        assert_eq!(WorldTime::new(0, 6000).day(), 0);
+        assert_eq!(WorldTime::new(0, TICKS_PER_DAY * 3 + 1).day(), 3);
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::sync::Mutex;
+
+use rand::Rng;
+
+use crate::net::packets::outgoing::game_event::GameEvent;
+use crate::world::dimension::Dimension;
+
+/// How long it stays clear before it starts raining, in ticks.
+pub const RAIN_DELAY: RangeInclusive<i32> = 12000..=180000;
+/// How long it rains for, in ticks.
+pub const RAIN_DURATION: RangeInclusive<i32> = 12000..=24000;
+/// How long it goes without thunder before a storm, in ticks.
+pub const THUNDER_DELAY: RangeInclusive<i32> = 12000..=180000;
+/// How long a storm lasts, in ticks.
+pub const THUNDER_DURATION: RangeInclusive<i32> = 3600..=15600;
+
+/// How far the rain and thunder levels move each tick, so the weather fades in and out over 5
+/// seconds.
+const LEVEL_STEP: f32 = 0.01;
+
+/// The kinds of weather the `/weather` command can set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+impl WeatherKind {
+    /// How long the weather lasts when no duration is given, picked the same way vanilla does.
+    pub fn random_duration(self, rng: &mut impl Rng) -> i32 {
+        match self {
+            WeatherKind::Clear => rng.gen_range(RAIN_DELAY),
+            WeatherKind::Rain => rng.gen_range(RAIN_DURATION),
+            WeatherKind::Thunder => rng.gen_range(THUNDER_DURATION),
+        }
+    }
+}
+
+/// The weather in a single dimension.
+///
+/// Rain and thunder each have a timer, and flip on or off when it runs out. The levels are what
+/// the client actually sees, and follow the flags a step at a time.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WeatherState {
+    pub raining: bool,
+    pub thundering: bool,
+    /// Ticks until `raining` flips. At 0, a new time is picked on the next tick.
+    pub rain_time: i32,
+    /// Ticks until `thundering` flips. At 0, a new time is picked on the next tick.
+    pub thunder_time: i32,
+    /// Ticks of clear weather left, set by `/weather clear`. Both timers are held while it counts
+    /// down.
+    pub clear_time: i32,
+    pub rain_level: f32,
+    pub thunder_level: f32,
+}
+
+impl WeatherState {
+    /// Whether it's raining hard enough for the client to show it.
+    pub fn is_raining(&self) -> bool {
+        self.rain_level > 0.2
+    }
+
+    /// Advances the weather by a tick, and returns what the players in the dimension need to be
+    /// told. The timers only run when the weather cycle is enabled, but the levels always catch up
+    /// with the flags.
+    pub fn tick(&mut self, do_weather_cycle: bool, rng: &mut impl Rng) -> Vec<GameEvent> {
+        let was_raining = self.is_raining();
+        let old_rain_level = self.rain_level;
+        let old_thunder_level = self.thunder_level;
+
+        if do_weather_cycle {
+            self.advance_timers(rng);
+        }
+        self.thunder_level = step_level(self.thunder_level, self.thundering);
+        self.rain_level = step_level(self.rain_level, self.raining);
+
+        let mut events = Vec::new();
+        if was_raining != self.is_raining() {
+            // The client resets its rain level when the rain starts or stops, so both levels are
+            // sent again
+            events.push(match was_raining {
+                true => GameEvent::end_raining(),
+                false => GameEvent::begin_raining(),
+            });
+            events.push(GameEvent::rain_level(self.rain_level));
+            events.push(GameEvent::thunder_level(self.thunder_level));
+            return events;
+        }
+        if old_rain_level != self.rain_level {
+            events.push(GameEvent::rain_level(self.rain_level));
+        }
+        if old_thunder_level != self.thunder_level {
+            events.push(GameEvent::thunder_level(self.thunder_level));
+        }
+        events
+    }
+
+    fn advance_timers(&mut self, rng: &mut impl Rng) {
+        if self.clear_time > 0 {
+            self.clear_time -= 1;
+            self.thunder_time = if self.thundering { 0 } else { 1 };
+            self.rain_time = if self.raining { 0 } else { 1 };
+            self.thundering = false;
+            self.raining = false;
+            return;
+        }
+
+        advance_timer(
+            &mut self.thunder_time,
+            &mut self.thundering,
+            THUNDER_DURATION,
+            THUNDER_DELAY,
+            rng,
+        );
+        advance_timer(
+            &mut self.rain_time,
+            &mut self.raining,
+            RAIN_DURATION,
+            RAIN_DELAY,
+            rng,
+        );
+    }
+
+    /// Sets the weather for the next `duration` ticks. It fades in over the next few ticks.
+    pub fn set(&mut self, kind: WeatherKind, duration: i32) {
+        let (clear_time, rain_time, raining, thundering) = match kind {
+            WeatherKind::Clear => (duration, 0, false, false),
+            WeatherKind::Rain => (0, duration, true, false),
+            WeatherKind::Thunder => (0, duration, true, true),
+        };
+        self.clear_time = clear_time;
+        self.rain_time = rain_time;
+        self.thunder_time = rain_time;
+        self.raining = raining;
+        self.thundering = thundering;
+    }
+
+    /// What a player needs to be sent when they arrive in the dimension. Their client starts out
+    /// with clear weather.
+    pub fn join_packets(&self) -> Vec<GameEvent> {
+        if !self.is_raining() {
+            return Vec::new();
+        }
+        vec![
+            GameEvent::begin_raining(),
+            GameEvent::rain_level(self.rain_level),
+            GameEvent::thunder_level(self.thunder_level),
+        ]
+    }
+}
+
+fn advance_timer(
+    time: &mut i32,
+    active: &mut bool,
+    duration: RangeInclusive<i32>,
+    delay: RangeInclusive<i32>,
+    rng: &mut impl Rng,
+) {
+    if *time > 0 {
+        *time -= 1;
+        if *time == 0 {
+            *active = !*active;
+        }
+        return;
+    }
+    *time = rng.gen_range(if *active { duration } else { delay });
+}
+
+fn step_level(level: f32, active: bool) -> f32 {
+    let level = if active {
+        level + LEVEL_STEP
+    } else {
+        level - LEVEL_STEP
+    };
+    level.clamp(0.0, 1.0)
+}
+
+/// The weather of every dimension. Only the ones with [Dimension::has_weather] ever change.
+#[derive(Debug, Default)]
+pub struct Weather {
+    dimensions: Mutex<HashMap<Dimension, WeatherState>>,
+}
+
+impl Weather {
+    pub fn state(&self, dimension: Dimension) -> WeatherState {
+        self.dimensions
+            .lock()
+            .unwrap()
+            .get(&dimension)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Advances the weather in every dimension that has any, and returns what has to be sent to
+    /// the players in each.
+    pub fn tick(&self, do_weather_cycle: bool) -> Vec<(Dimension, Vec<GameEvent>)> {
+        let mut rng = rand::thread_rng();
+        let mut dimensions = self.dimensions.lock().unwrap();
+        Dimension::ALL
+            .into_iter()
+            .filter(|dimension| dimension.has_weather())
+            .map(|dimension| {
+                let events = dimensions
+                    .entry(dimension)
+                    .or_default()
+                    .tick(do_weather_cycle, &mut rng);
+                (dimension, events)
+            })
+            .filter(|(_, events)| !events.is_empty())
+            .collect()
+    }
+
+    /// Sets a dimension's weather, for a random amount of time if there's no `duration`. Returns
+    /// the duration that was used.
+    pub fn set(&self, dimension: Dimension, kind: WeatherKind, duration: Option<i32>) -> i32 {
+        let duration = duration.unwrap_or_else(|| kind.random_duration(&mut rand::thread_rng()));
+        self.dimensions
+            .lock()
+            .unwrap()
+            .entry(dimension)
+            .or_default()
+            .set(kind, duration);
+        duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick_n(state: &mut WeatherState, ticks: usize) -> Vec<GameEvent> {
+        let mut rng = rand::thread_rng();
+        (0..ticks)
+            .flat_map(|_| state.tick(true, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn test_timers_are_picked_from_vanilla_ranges() {
+        let mut state = WeatherState::default();
+        tick_n(&mut state, 1);
+
+        assert!(RAIN_DELAY.contains(&state.rain_time));
+        assert!(THUNDER_DELAY.contains(&state.thunder_time));
+        assert!(!state.raining);
+    }
+
+    #[test]
+    fn test_rain_fades_in_and_begins() {
+        let mut state = WeatherState {
+            rain_time: 1,
+            thunder_time: 1000,
+            ..Default::default()
+        };
+        let events = tick_n(&mut state, 1);
+        assert!(state.raining);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, GameEvent::RAIN_LEVEL_CHANGE);
+
+        let events = tick_n(&mut state, 100);
+        assert_eq!(state.rain_level, 1.0);
+        let begins = events
+            .iter()
+            .filter(|event| event.event == GameEvent::BEGIN_RAINING)
+            .count();
+        assert_eq!(begins, 1);
+        // Nothing changes once the rain is at full strength
+        assert!(tick_n(&mut state, 1).is_empty());
+    }
+
+    #[test]
+    fn test_clear_holds_the_timers() {
+        let mut state = WeatherState {
+            raining: true,
+            rain_level: 1.0,
+            ..Default::default()
+        };
+        state.set(WeatherKind::Clear, 200);
+        let events = tick_n(&mut state, 150);
+
+        assert!(!state.raining);
+        assert_eq!(state.rain_level, 0.0);
+        assert_eq!(state.clear_time, 50);
+        assert_eq!(state.rain_time, 1);
+        assert!(events
+            .iter()
+            .any(|event| event.event == GameEvent::END_RAINING));
+    }
+
+    #[test]
+    fn test_thunder_sets_both() {
+        let mut state = WeatherState::default();
+        state.set(WeatherKind::Thunder, 6000);
+        tick_n(&mut state, 110);
+
+        assert!(state.raining && state.thundering);
+        assert_eq!(state.rain_time, 5890);
+        assert_eq!(state.thunder_level, 1.0);
+        assert_eq!(state.join_packets().len(), 3);
+    }
+
+    #[test]
+    fn test_timers_stop_without_weather_cycle() {
+        let mut state = WeatherState {
+            raining: true,
+            rain_time: 10,
+            ..Default::default()
+        };
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            state.tick(false, &mut rng);
+        }
+
+        assert_eq!(state.rain_time, 10);
+        assert!((state.rain_level - 0.2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_only_the_overworld_has_weather() {
+        let weather = Weather::default();
+        weather.set(Dimension::Overworld, WeatherKind::Rain, Some(1000));
+        weather.set(Dimension::Nether, WeatherKind::Rain, Some(1000));
+
+        let changes = weather.tick(true);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].0, Dimension::Overworld);
+        assert_eq!(weather.state(Dimension::Nether).rain_level, 0.0);
+    }
+}